@@ -12,11 +12,15 @@
 
 use font_kit::handle::Handle;
 use gl;
-use pathfinder_canvas::{CanvasFontContext, CanvasRenderingContext2D, FillStyle, LineJoin, Path2D};
-use pathfinder_canvas::{TextAlign, TextMetrics};
-use pathfinder_color::{ColorF, ColorU};
+use pathfinder_canvas::{CanvasFontContext, CanvasRenderingContext2D, CompositeOperation};
+use pathfinder_canvas::{FillStyle, LineJoin, Path2D, TextAlign, TextBaseline, TextDirection};
+use pathfinder_canvas::TextMetrics;
+use pathfinder_color::{ColorF, ColorSpace, ColorU};
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientWrap};
 use pathfinder_content::outline::ArcDirection;
 use pathfinder_content::stroke::LineCap;
+use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::transform3d::{Perspective, Transform4F};
@@ -25,10 +29,11 @@ use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_gpu::resources::{FilesystemResourceLoader, ResourceLoader};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::options::{DestEncoding, DestFramebuffer, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::{BuildOptions, RenderTransform};
-use pathfinder_renderer::scene::Scene;
+use pathfinder_renderer::paint::PaintId;
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, PathObject, Scene};
 use pathfinder_simd::default::F32x4;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_void};
@@ -55,20 +60,80 @@ pub const PF_LINE_JOIN_ROUND:   u8 = 2;
 pub const PF_TEXT_ALIGN_LEFT:   u8 = 0;
 pub const PF_TEXT_ALIGN_CENTER: u8 = 1;
 pub const PF_TEXT_ALIGN_RIGHT:  u8 = 2;
+pub const PF_TEXT_ALIGN_START:  u8 = 3;
+pub const PF_TEXT_ALIGN_END:    u8 = 4;
+
+pub const PF_TEXT_BASELINE_ALPHABETIC:  u8 = 0;
+pub const PF_TEXT_BASELINE_TOP:         u8 = 1;
+pub const PF_TEXT_BASELINE_HANGING:     u8 = 2;
+pub const PF_TEXT_BASELINE_MIDDLE:      u8 = 3;
+pub const PF_TEXT_BASELINE_IDEOGRAPHIC: u8 = 4;
+pub const PF_TEXT_BASELINE_BOTTOM:      u8 = 5;
+
+pub const PF_TEXT_DIRECTION_LTR: u8 = 0;
+pub const PF_TEXT_DIRECTION_RTL: u8 = 1;
+
+pub const PF_COMPOSITE_OPERATION_SOURCE_OVER:       u8 = 0;
+pub const PF_COMPOSITE_OPERATION_SOURCE_IN:         u8 = 1;
+pub const PF_COMPOSITE_OPERATION_SOURCE_OUT:        u8 = 2;
+pub const PF_COMPOSITE_OPERATION_SOURCE_ATOP:       u8 = 3;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_OVER:  u8 = 4;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_IN:    u8 = 5;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_OUT:   u8 = 6;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_ATOP:  u8 = 7;
+pub const PF_COMPOSITE_OPERATION_LIGHTER:           u8 = 8;
+pub const PF_COMPOSITE_OPERATION_COPY:              u8 = 9;
+pub const PF_COMPOSITE_OPERATION_XOR:               u8 = 10;
+pub const PF_COMPOSITE_OPERATION_MULTIPLY:          u8 = 11;
+pub const PF_COMPOSITE_OPERATION_SCREEN:            u8 = 12;
+pub const PF_COMPOSITE_OPERATION_OVERLAY:           u8 = 13;
+pub const PF_COMPOSITE_OPERATION_DARKEN:            u8 = 14;
+pub const PF_COMPOSITE_OPERATION_LIGHTEN:           u8 = 15;
+pub const PF_COMPOSITE_OPERATION_COLOR_DODGE:       u8 = 16;
+pub const PF_COMPOSITE_OPERATION_COLOR_BURN:        u8 = 17;
+pub const PF_COMPOSITE_OPERATION_HARD_LIGHT:        u8 = 18;
+pub const PF_COMPOSITE_OPERATION_SOFT_LIGHT:        u8 = 19;
+pub const PF_COMPOSITE_OPERATION_DIFFERENCE:        u8 = 20;
+pub const PF_COMPOSITE_OPERATION_EXCLUSION:         u8 = 21;
+pub const PF_COMPOSITE_OPERATION_HUE:               u8 = 22;
+pub const PF_COMPOSITE_OPERATION_SATURATION:        u8 = 23;
+pub const PF_COMPOSITE_OPERATION_COLOR:             u8 = 24;
+pub const PF_COMPOSITE_OPERATION_LUMINOSITY:        u8 = 25;
 
 // `content`
 
 pub const PF_ARC_DIRECTION_CW:  u8 = 0;
 pub const PF_ARC_DIRECTION_CCW: u8 = 1;
 
+pub const PF_FILL_RULE_WINDING:  u8 = 0;
+pub const PF_FILL_RULE_EVEN_ODD: u8 = 1;
+
+pub const PF_GRADIENT_WRAP_CLAMP:   u8 = 0;
+pub const PF_GRADIENT_WRAP_REPEAT:  u8 = 1;
+pub const PF_GRADIENT_WRAP_REFLECT: u8 = 2;
+
+// `color`
+
+pub const PF_COLOR_SPACE_SRGB:       u8 = 0;
+pub const PF_COLOR_SPACE_DISPLAY_P3: u8 = 1;
+
 // `gl`
 
 pub const PF_GL_VERSION_GL3:    u8 = 0;
 pub const PF_GL_VERSION_GLES3:  u8 = 1;
+pub const PF_GL_VERSION_GL4:    u8 = 2;
 
 // `renderer`
 
+/// Without this flag, the destination isn't cleared, and scenes are drawn over its contents.
 pub const PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR: u8 = 0x1;
+pub const PF_RENDERER_OPTIONS_FLAGS_LINEAR_COMPOSITING:   u8 = 0x2;
+/// The destination stores extended-range linear light, as for HDR `RGBA16F` framebuffers.
+pub const PF_RENDERER_OPTIONS_FLAGS_EXTENDED_LINEAR_DEST: u8 = 0x4;
+pub const PF_RENDERER_OPTIONS_FLAGS_PREMULTIPLIED_ALPHA:  u8 = 0x8;
+
+/// Passed to `PFScenePushPath()` to draw a path unclipped.
+pub const PF_CLIP_PATH_NONE: u32 = !0;
 
 // Types
 
@@ -84,12 +149,18 @@ pub type PFLineCap = u8;
 pub type PFLineJoin = u8;
 pub type PFArcDirection = u8;
 pub type PFTextAlign = u8;
+pub type PFTextBaseline = u8;
+pub type PFTextDirection = u8;
+pub type PFCompositeOperation = u8;
 #[repr(C)]
 pub struct PFTextMetrics {
     pub width: f32,
 }
 
 // `content`
+pub type PFGradientRef = *mut Gradient;
+pub type PFFillRule = u8;
+pub type PFGradientWrap = u8;
 #[repr(C)]
 pub struct PFColorF {
     pub r: f32,
@@ -117,6 +188,11 @@ pub struct PFVector2I {
     pub y: i32,
 }
 #[repr(C)]
+pub struct PFLineSegment2F {
+    pub from: PFVector2F,
+    pub to: PFVector2F,
+}
+#[repr(C)]
 pub struct PFRectF {
     pub origin: PFVector2F,
     pub lower_right: PFVector2F,
@@ -182,6 +258,9 @@ pub struct PFRendererOptions {
     pub flags: PFRendererOptionsFlags,
 }
 pub type PFRendererOptionsFlags = u8;
+pub type PFColorSpace = u8;
+pub type PFPaintId = u16;
+pub type PFClipPathId = u32;
 pub type PFBuildOptionsRef = *mut BuildOptions;
 pub type PFRenderTransformRef = *mut RenderTransform;
 
@@ -351,10 +430,33 @@ pub unsafe extern "C" fn PFCanvasSetTextAlign(canvas: PFCanvasRef, new_text_alig
     (*canvas).set_text_align(match new_text_align {
         PF_TEXT_ALIGN_CENTER => TextAlign::Center,
         PF_TEXT_ALIGN_RIGHT  => TextAlign::Right,
+        PF_TEXT_ALIGN_START  => TextAlign::Start,
+        PF_TEXT_ALIGN_END    => TextAlign::End,
         _                    => TextAlign::Left,
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetTextBaseline(canvas: PFCanvasRef,
+                                                 new_text_baseline: PFTextBaseline) {
+    (*canvas).set_text_baseline(match new_text_baseline {
+        PF_TEXT_BASELINE_TOP         => TextBaseline::Top,
+        PF_TEXT_BASELINE_HANGING     => TextBaseline::Hanging,
+        PF_TEXT_BASELINE_MIDDLE      => TextBaseline::Middle,
+        PF_TEXT_BASELINE_IDEOGRAPHIC => TextBaseline::Ideographic,
+        PF_TEXT_BASELINE_BOTTOM      => TextBaseline::Bottom,
+        _                            => TextBaseline::Alphabetic,
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetDirection(canvas: PFCanvasRef, new_direction: PFTextDirection) {
+    (*canvas).set_direction(match new_direction {
+        PF_TEXT_DIRECTION_RTL => TextDirection::RightToLeft,
+        _                     => TextDirection::LeftToRight,
+    });
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetFillStyle(canvas: PFCanvasRef, fill_style: PFFillStyleRef) {
     // FIXME(pcwalton): Avoid the copy?
@@ -375,6 +477,15 @@ pub unsafe extern "C" fn PFCanvasFillPath(canvas: PFCanvasRef, path: PFPathRef)
     (*canvas).fill_path(*Box::from_raw(path))
 }
 
+/// This function automatically destroys the path. If you wish to use the path again, clone it
+/// first.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasFillPathWithFillRule(canvas: PFCanvasRef,
+                                                      path: PFPathRef,
+                                                      fill_rule: PFFillRule) {
+    (*canvas).fill_path_with_fill_rule(*Box::from_raw(path), fill_rule_to_rust(fill_rule))
+}
+
 /// This function automatically destroys the path. If you wish to use the path again, clone it
 /// first.
 #[no_mangle]
@@ -382,6 +493,75 @@ pub unsafe extern "C" fn PFCanvasStrokePath(canvas: PFCanvasRef, path: PFPathRef
     (*canvas).stroke_path(*Box::from_raw(path))
 }
 
+/// This function automatically destroys the path. If you wish to use the path again, clone it
+/// first.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasClipPath(canvas: PFCanvasRef,
+                                          path: PFPathRef,
+                                          fill_rule: PFFillRule) {
+    (*canvas).clip_path(*Box::from_raw(path), fill_rule_to_rust(fill_rule))
+}
+
+// Compositing
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetGlobalAlpha(canvas: PFCanvasRef, new_global_alpha: f32) {
+    (*canvas).set_global_alpha(new_global_alpha)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetGlobalCompositeOperation(
+        canvas: PFCanvasRef,
+        new_composite_operation: PFCompositeOperation) {
+    (*canvas).set_global_composite_operation(match new_composite_operation {
+        PF_COMPOSITE_OPERATION_SOURCE_IN        => CompositeOperation::SourceIn,
+        PF_COMPOSITE_OPERATION_SOURCE_OUT       => CompositeOperation::SourceOut,
+        PF_COMPOSITE_OPERATION_SOURCE_ATOP      => CompositeOperation::SourceAtop,
+        PF_COMPOSITE_OPERATION_DESTINATION_OVER => CompositeOperation::DestinationOver,
+        PF_COMPOSITE_OPERATION_DESTINATION_IN   => CompositeOperation::DestinationIn,
+        PF_COMPOSITE_OPERATION_DESTINATION_OUT  => CompositeOperation::DestinationOut,
+        PF_COMPOSITE_OPERATION_DESTINATION_ATOP => CompositeOperation::DestinationAtop,
+        PF_COMPOSITE_OPERATION_LIGHTER          => CompositeOperation::Lighter,
+        PF_COMPOSITE_OPERATION_COPY             => CompositeOperation::Copy,
+        PF_COMPOSITE_OPERATION_XOR              => CompositeOperation::Xor,
+        PF_COMPOSITE_OPERATION_MULTIPLY         => CompositeOperation::Multiply,
+        PF_COMPOSITE_OPERATION_SCREEN           => CompositeOperation::Screen,
+        PF_COMPOSITE_OPERATION_OVERLAY          => CompositeOperation::Overlay,
+        PF_COMPOSITE_OPERATION_DARKEN           => CompositeOperation::Darken,
+        PF_COMPOSITE_OPERATION_LIGHTEN          => CompositeOperation::Lighten,
+        PF_COMPOSITE_OPERATION_COLOR_DODGE      => CompositeOperation::ColorDodge,
+        PF_COMPOSITE_OPERATION_COLOR_BURN       => CompositeOperation::ColorBurn,
+        PF_COMPOSITE_OPERATION_HARD_LIGHT       => CompositeOperation::HardLight,
+        PF_COMPOSITE_OPERATION_SOFT_LIGHT       => CompositeOperation::SoftLight,
+        PF_COMPOSITE_OPERATION_DIFFERENCE       => CompositeOperation::Difference,
+        PF_COMPOSITE_OPERATION_EXCLUSION        => CompositeOperation::Exclusion,
+        PF_COMPOSITE_OPERATION_HUE              => CompositeOperation::Hue,
+        PF_COMPOSITE_OPERATION_SATURATION       => CompositeOperation::Saturation,
+        PF_COMPOSITE_OPERATION_COLOR            => CompositeOperation::Color,
+        PF_COMPOSITE_OPERATION_LUMINOSITY       => CompositeOperation::Luminosity,
+        _                                       => CompositeOperation::SourceOver,
+    })
+}
+
+// Shadows
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetShadowColor(canvas: PFCanvasRef,
+                                                new_shadow_color: *const PFColorU) {
+    (*canvas).set_shadow_color((*new_shadow_color).to_rust())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetShadowBlur(canvas: PFCanvasRef, new_shadow_blur: f32) {
+    (*canvas).set_shadow_blur(new_shadow_blur)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetShadowOffset(canvas: PFCanvasRef,
+                                                 new_shadow_offset: *const PFVector2F) {
+    (*canvas).set_shadow_offset((*new_shadow_offset).to_rust())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFPathCreate() -> PFPathRef {
     Box::into_raw(Box::new(Path2D::new()))
@@ -452,8 +632,15 @@ pub unsafe extern "C" fn PFPathEllipse(path: PFPathRef,
                                        axes: *const PFVector2F,
                                        rotation: f32,
                                        start_angle: f32,
-                                       end_angle: f32) {
-    (*path).ellipse((*center).to_rust(), (*axes).to_rust(), rotation, start_angle, end_angle)
+                                       end_angle: f32,
+                                       direction: PFArcDirection) {
+    let direction = if direction == 0 { ArcDirection::CW } else { ArcDirection::CCW };
+    (*path).ellipse((*center).to_rust(),
+                    (*axes).to_rust(),
+                    rotation,
+                    start_angle,
+                    end_angle,
+                    direction)
 }
 
 #[no_mangle]
@@ -466,11 +653,60 @@ pub unsafe extern "C" fn PFFillStyleCreateColor(color: *const PFColorU) -> PFFil
     Box::into_raw(Box::new(FillStyle::Color((*color).to_rust())))
 }
 
+/// This function takes ownership of the supplied gradient and will automatically destroy it when
+/// the fill style is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn PFFillStyleCreateGradient(gradient: PFGradientRef) -> PFFillStyleRef {
+    Box::into_raw(Box::new(FillStyle::Gradient(*Box::from_raw(gradient))))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFFillStyleDestroy(fill_style: PFFillStyleRef) {
     drop(Box::from_raw(fill_style))
 }
 
+// `content`
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientCreateLinear(line: *const PFLineSegment2F) -> PFGradientRef {
+    Box::into_raw(Box::new(Gradient::new((*line).to_rust())))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientCreateRadial(line: *const PFLineSegment2F,
+                                                start_radius: f32,
+                                                end_radius: f32)
+                                                -> PFGradientRef {
+    Box::into_raw(Box::new(Gradient::radial((*line).to_rust(), start_radius, end_radius)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientDestroy(gradient: PFGradientRef) {
+    drop(Box::from_raw(gradient))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientAddColorStop(gradient: PFGradientRef,
+                                                color: *const PFColorU,
+                                                offset: f32) {
+    (*gradient).add_color_stop(ColorStop { color: (*color).to_rust(), offset })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientSetWrap(gradient: PFGradientRef, new_wrap: PFGradientWrap) {
+    (*gradient).set_wrap(match new_wrap {
+        PF_GRADIENT_WRAP_REPEAT  => GradientWrap::Repeat,
+        PF_GRADIENT_WRAP_REFLECT => GradientWrap::Reflect,
+        _                        => GradientWrap::Clamp,
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientSetTransform(gradient: PFGradientRef,
+                                                transform: *const PFTransform2F) {
+    (*gradient).apply_transform(&(*transform).to_rust())
+}
+
 // `gl`
 
 #[no_mangle]
@@ -490,7 +726,11 @@ pub unsafe extern "C" fn PFGLLoadWith(loader: PFGLFunctionLoader, userdata: *mut
 #[no_mangle]
 pub unsafe extern "C" fn PFGLDeviceCreate(version: PFGLVersion, default_framebuffer: u32)
                                           -> PFGLDeviceRef {
-    let version = match version { PF_GL_VERSION_GLES3 => GLVersion::GLES3, _ => GLVersion::GL3 };
+    let version = match version {
+        PF_GL_VERSION_GLES3 => GLVersion::GLES3,
+        PF_GL_VERSION_GL4 => GLVersion::GL4,
+        _ => GLVersion::GL3,
+    };
     Box::into_raw(Box::new(GLDevice::new(version, default_framebuffer)))
 }
 
@@ -512,6 +752,17 @@ pub unsafe extern "C" fn PFGLDestFramebufferCreateFullWindow(window_size: *const
     Box::into_raw(Box::new(DestFramebuffer::full_window((*window_size).to_rust())))
 }
 
+/// Renders into `viewport` only, leaving the rest of the window untouched.
+#[no_mangle]
+pub unsafe extern "C" fn PFGLDestFramebufferCreateViewport(window_size: *const PFVector2I,
+                                                           viewport: *const PFRectI)
+                                                           -> PFGLDestFramebufferRef {
+    Box::into_raw(Box::new(DestFramebuffer::Default {
+        viewport: (*viewport).to_rust(),
+        window_size: (*window_size).to_rust(),
+    }))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFGLDestFramebufferDestroy(dest_framebuffer: PFGLDestFramebufferRef) {
     drop(Box::from_raw(dest_framebuffer))
@@ -549,6 +800,18 @@ pub unsafe extern "C" fn PFMetalDestFramebufferCreateFullWindow(window_size: *co
     Box::into_raw(Box::new(DestFramebuffer::full_window((*window_size).to_rust())))
 }
 
+/// Renders into `viewport` only, leaving the rest of the window untouched.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalDestFramebufferCreateViewport(window_size: *const PFVector2I,
+                                                              viewport: *const PFRectI)
+                                                              -> PFMetalDestFramebufferRef {
+    Box::into_raw(Box::new(DestFramebuffer::Default {
+        viewport: (*viewport).to_rust(),
+        window_size: (*window_size).to_rust(),
+    }))
+}
+
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFMetalDestFramebufferDestroy(dest_framebuffer:
@@ -590,21 +853,27 @@ pub unsafe extern "C" fn PFMetalRendererGetDevice(renderer: PFMetalRendererRef)
 
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
+///
+/// Returns false if the scene couldn't be built, in which case only the background is drawn.
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyBuildAndRenderGL(scene_proxy: PFSceneProxyRef,
                                                       renderer: PFGLRendererRef,
-                                                      build_options: PFBuildOptionsRef) {
-    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone())
+                                                      build_options: PFBuildOptionsRef)
+                                                      -> bool {
+    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone()).is_ok()
 }
 
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
+///
+/// Returns false if the scene couldn't be built, in which case only the background is drawn.
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyBuildAndRenderMetal(scene_proxy: PFSceneProxyRef,
                                                          renderer: PFMetalRendererRef,
-                                                         build_options: PFBuildOptionsRef) {
-    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone())
+                                                         build_options: PFBuildOptionsRef)
+                                                         -> bool {
+    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone()).is_ok()
 }
 
 // `metal`
@@ -676,11 +945,88 @@ pub unsafe extern "C" fn PFBuildOptionsSetSubpixelAAEnabled(options: PFBuildOpti
     (*options).subpixel_aa_enabled = subpixel_aa_enabled
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetColorSpace(options: PFBuildOptionsRef,
+                                                     color_space: PFColorSpace) {
+    (*options).color_space = color_space_to_rust(color_space)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetPremultipliedPaints(options: PFBuildOptionsRef,
+                                                              premultiplied_paints: bool) {
+    (*options).premultiplied_paints = premultiplied_paints
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetGPUTilingEnabled(options: PFBuildOptionsRef,
+                                                           gpu_tiling_enabled: bool) {
+    (*options).gpu_tiling = gpu_tiling_enabled
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneCreate() -> PFSceneRef {
+    Box::into_raw(Box::new(Scene::new()))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneDestroy(scene: PFSceneRef) {
     drop(Box::from_raw(scene))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneSetViewBox(scene: PFSceneRef, new_view_box: *const PFRectF) {
+    (*scene).set_view_box((*new_view_box).to_rust())
+}
+
+/// This function does not take ownership of `fill_style`. Pushing the same paint twice returns
+/// the same ID.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushPaint(scene: PFSceneRef, fill_style: PFFillStyleRef)
+                                          -> PFPaintId {
+    (*scene).push_paint(&(*fill_style).to_paint()).0
+}
+
+/// Like `PFScenePushPaint()`, but the colors of the fill style are in `color_space` rather than in
+/// sRGB.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushPaintInColorSpace(scene: PFSceneRef,
+                                                      fill_style: PFFillStyleRef,
+                                                      color_space: PFColorSpace)
+                                                      -> PFPaintId {
+    (*scene).push_paint_in_color_space(&(*fill_style).to_paint(),
+                                       color_space_to_rust(color_space)).0
+}
+
+/// This function automatically destroys the path. If you wish to use the path again, clone it
+/// first.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushClipPath(scene: PFSceneRef,
+                                             path: PFPathRef,
+                                             fill_rule: PFFillRule)
+                                             -> PFClipPathId {
+    let outline = (*Box::from_raw(path)).into_outline();
+    (*scene).push_clip_path(ClipPath::new(outline, fill_rule_to_rust(fill_rule))).0
+}
+
+/// Draws `path`, filled with nonzero winding, on top of the paths already in the scene. `paint`
+/// must have been returned by `PFScenePushPaint()` and `clip_path`, unless it's
+/// `PF_CLIP_PATH_NONE`, by `PFScenePushClipPath()` for the same scene.
+///
+/// This function automatically destroys the path. If you wish to use the path again, clone it
+/// first.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushPath(scene: PFSceneRef,
+                                         path: PFPathRef,
+                                         paint: PFPaintId,
+                                         clip_path: PFClipPathId) {
+    let outline = (*Box::from_raw(path)).into_outline();
+    let mut path_object = PathObject::new(outline, PaintId(paint), String::new());
+    if clip_path != PF_CLIP_PATH_NONE {
+        path_object.set_clip_path(Some(ClipPathId(clip_path)));
+    }
+    (*scene).push_path(path_object)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyCreateFromSceneAndRayonExecutor(scene: PFSceneRef)
                                                                      -> PFSceneProxyRef {
@@ -692,6 +1038,20 @@ pub unsafe extern "C" fn PFSceneProxyDestroy(scene_proxy: PFSceneProxyRef) {
     drop(Box::from_raw(scene_proxy))
 }
 
+/// This function takes ownership of the supplied scene and will automatically destroy it when it
+/// is replaced or the scene proxy is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneProxyReplaceScene(scene_proxy: PFSceneProxyRef,
+                                                  scene: PFSceneRef) {
+    (*scene_proxy).replace_scene(*Box::from_raw(scene))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneProxySetViewBox(scene_proxy: PFSceneProxyRef,
+                                                new_view_box: *const PFRectF) {
+    (*scene_proxy).set_view_box((*new_view_box).to_rust())
+}
+
 // Helpers for `canvas`
 
 unsafe fn to_rust_string(ptr: &*const c_char, mut len: usize) -> &str {
@@ -713,6 +1073,10 @@ impl TextMetricsExt for TextMetrics {
 
 // Helpers for `content`
 
+fn fill_rule_to_rust(fill_rule: PFFillRule) -> FillRule {
+    if fill_rule == PF_FILL_RULE_EVEN_ODD { FillRule::EvenOdd } else { FillRule::Winding }
+}
+
 impl PFColorF {
     #[inline]
     pub fn to_rust(&self) -> ColorF {
@@ -736,6 +1100,13 @@ impl PFRectF {
     }
 }
 
+impl PFLineSegment2F {
+    #[inline]
+    pub fn to_rust(&self) -> LineSegment2F {
+        LineSegment2F::new(self.from.to_rust(), self.to.to_rust())
+    }
+}
+
 impl PFRectI {
     #[inline]
     pub fn to_rust(&self) -> RectI {
@@ -802,6 +1173,202 @@ impl PFRendererOptions {
             } else {
                 None
             },
+            linear_compositing: self.flags & PF_RENDERER_OPTIONS_FLAGS_LINEAR_COMPOSITING != 0,
+            dest_encoding: if self.flags & PF_RENDERER_OPTIONS_FLAGS_EXTENDED_LINEAR_DEST != 0 {
+                DestEncoding::ExtendedLinear
+            } else {
+                DestEncoding::Srgb
+            },
+            premultiplied_alpha: self.flags & PF_RENDERER_OPTIONS_FLAGS_PREMULTIPLIED_ALPHA != 0,
+            ..RendererOptions::default()
+        }
+    }
+}
+
+fn color_space_to_rust(color_space: PFColorSpace) -> ColorSpace {
+    match color_space {
+        PF_COLOR_SPACE_DISPLAY_P3 => ColorSpace::DisplayP3,
+        _                         => ColorSpace::Srgb,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{PFCanvasClipPath, PFCanvasCreate, PFCanvasCreateScene, PFCanvasFillRect};
+    use crate::{PFCanvasFontContextCreateWithSystemSource, PFCanvasFontContextRelease};
+    use crate::{PFCanvasSetFillStyle, PFCanvasSetGlobalAlpha, PFCanvasSetGlobalCompositeOperation};
+    use crate::{PFCanvasSetShadowBlur, PFCanvasSetShadowColor, PFCanvasSetShadowOffset};
+    use crate::{PFColorU, PFFillStyleCreateColor, PFFillStyleCreateGradient, PFFillStyleDestroy};
+    use crate::{PFGradientAddColorStop, PFGradientCreateLinear, PFGradientCreateRadial};
+    use crate::{PFGradientSetTransform, PFGradientSetWrap, PFLineSegment2F, PFMatrix2x2F};
+    use crate::{PFPathCreate, PFPathRect, PFPathRef, PFRectF, PFSceneCreate, PFSceneDestroy};
+    use crate::{PFScenePushClipPath, PFScenePushPaint, PFScenePushPath, PFSceneSetViewBox};
+    use crate::{PFTransform2F, PFVector2F, PF_CLIP_PATH_NONE, PF_COMPOSITE_OPERATION_MULTIPLY};
+    use crate::{PF_FILL_RULE_EVEN_ODD, PF_FILL_RULE_WINDING, PF_GRADIENT_WRAP_REPEAT};
+    use pathfinder_canvas::FillStyle;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::gradient::{ColorStop, GradientGeometry, GradientWrap};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use pathfinder_renderer::paint::Paint;
+    use pathfinder_renderer::scene::ClipPathId;
+
+    const RED: PFColorU = PFColorU { r: 255, g: 0, b: 0, a: 255 };
+    const BLUE: PFColorU = PFColorU { r: 0, g: 0, b: 255, a: 255 };
+
+    fn vector(x: f32, y: f32) -> PFVector2F {
+        PFVector2F { x, y }
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> PFRectF {
+        PFRectF { origin: vector(x, y), lower_right: vector(x + width, y + height) }
+    }
+
+    unsafe fn rect_path(x: f32, y: f32, width: f32, height: f32) -> PFPathRef {
+        let path = PFPathCreate();
+        PFPathRect(path, &rect(x, y, width, height));
+        path
+    }
+
+    #[test]
+    fn test_scene_building() {
+        unsafe {
+            let scene = PFSceneCreate();
+            PFSceneSetViewBox(scene, &rect(0.0, 0.0, 100.0, 100.0));
+
+            // Equal paints share an ID.
+            let (red, blue) = (PFFillStyleCreateColor(&RED), PFFillStyleCreateColor(&BLUE));
+            let red_paint = PFScenePushPaint(scene, red);
+            assert_eq!(PFScenePushPaint(scene, red), red_paint);
+            let blue_paint = PFScenePushPaint(scene, blue);
+            assert_ne!(blue_paint, red_paint);
+            PFFillStyleDestroy(red);
+            PFFillStyleDestroy(blue);
+
+            let clip_path = PFScenePushClipPath(scene,
+                                                rect_path(10.0, 10.0, 50.0, 50.0),
+                                                PF_FILL_RULE_EVEN_ODD);
+            PFScenePushPath(scene, rect_path(0.0, 0.0, 20.0, 20.0), red_paint, PF_CLIP_PATH_NONE);
+            PFScenePushPath(scene, rect_path(30.0, 30.0, 20.0, 20.0), blue_paint, clip_path);
+
+            {
+                let scene = &*scene;
+                assert_eq!(scene.view_box(),
+                           RectF::new(Vector2F::default(), Vector2F::splat(100.0)));
+                assert_eq!(scene.path_count(), 2);
+                assert_eq!(scene.path(0).clip_path(), None);
+                assert_eq!(scene.path(1).clip_path(), Some(ClipPathId(clip_path)));
+
+                let clip_path = scene.clip_path(ClipPathId(clip_path));
+                assert_eq!(clip_path.fill_rule(), FillRule::EvenOdd);
+                assert_eq!(clip_path.outline().bounds(),
+                           RectF::new(Vector2F::splat(10.0), Vector2F::splat(50.0)));
+
+                let paths: Vec<_> = scene.paths().map(|(paint, outline, _, fill_rule)| {
+                    (paint.clone(), outline.bounds(), fill_rule)
+                }).collect();
+                assert_eq!(paths, vec![
+                    (Paint::Color(RED.to_rust()),
+                     RectF::new(Vector2F::default(), Vector2F::splat(20.0)),
+                     FillRule::Winding),
+                    (Paint::Color(BLUE.to_rust()),
+                     RectF::new(Vector2F::splat(30.0), Vector2F::splat(20.0)),
+                     FillRule::Winding),
+                ]);
+            }
+
+            PFSceneDestroy(scene);
+        }
+    }
+
+    #[test]
+    fn test_gradients() {
+        unsafe {
+            let line = PFLineSegment2F { from: vector(0.0, 0.0), to: vector(100.0, 0.0) };
+            let gradient = PFGradientCreateLinear(&line);
+
+            // Stops are kept in order of offset.
+            PFGradientAddColorStop(gradient, &BLUE, 1.0);
+            PFGradientAddColorStop(gradient, &RED, 0.0);
+            PFGradientSetWrap(gradient, PF_GRADIENT_WRAP_REPEAT);
+            let translation = PFTransform2F {
+                matrix: PFMatrix2x2F { m00: 1.0, m01: 0.0, m10: 0.0, m11: 1.0 },
+                vector: vector(10.0, 0.0),
+            };
+            PFGradientSetTransform(gradient, &translation);
+
+            let fill_style = PFFillStyleCreateGradient(gradient);
+            match *fill_style {
+                FillStyle::Gradient(ref gradient) => {
+                    assert_eq!(gradient.stops(), &[
+                        ColorStop { color: RED.to_rust(), offset: 0.0 },
+                        ColorStop { color: BLUE.to_rust(), offset: 1.0 },
+                    ]);
+                    assert_eq!(gradient.wrap(), GradientWrap::Repeat);
+                    assert_eq!(gradient.transform(),
+                               Transform2F::from_translation(Vector2F::new(10.0, 0.0)));
+                    assert_eq!(gradient.sample_at(Vector2F::new(10.0, 0.0)), RED.to_rust());
+                }
+                _ => panic!("expected a gradient fill style"),
+            }
+            PFFillStyleDestroy(fill_style);
+
+            let gradient = PFGradientCreateRadial(&line, 5.0, 50.0);
+            let fill_style = PFFillStyleCreateGradient(gradient);
+            match *fill_style {
+                FillStyle::Gradient(ref gradient) => {
+                    let line = LineSegment2F::new(Vector2F::default(), Vector2F::new(100.0, 0.0));
+                    let geometry =
+                        GradientGeometry::Radial { line, start_radius: 5.0, end_radius: 50.0 };
+                    assert_eq!(*gradient.geometry(), geometry);
+                }
+                _ => panic!("expected a gradient fill style"),
+            }
+            PFFillStyleDestroy(fill_style);
+        }
+    }
+
+    #[test]
+    fn test_canvas_state() {
+        unsafe {
+            let font_context = PFCanvasFontContextCreateWithSystemSource();
+            let canvas = PFCanvasCreate(font_context, &vector(100.0, 100.0));
+            PFCanvasFontContextRelease(font_context);
+
+            let fill_style = PFFillStyleCreateColor(&RED);
+            PFCanvasSetFillStyle(canvas, fill_style);
+            PFFillStyleDestroy(fill_style);
+            PFCanvasSetGlobalAlpha(canvas, 0.5);
+            PFCanvasSetGlobalCompositeOperation(canvas, PF_COMPOSITE_OPERATION_MULTIPLY);
+            PFCanvasSetShadowColor(canvas, &BLUE);
+            PFCanvasSetShadowOffset(canvas, &vector(5.0, 5.0));
+            PFCanvasClipPath(canvas, rect_path(0.0, 0.0, 50.0, 50.0), PF_FILL_RULE_WINDING);
+            PFCanvasFillRect(canvas, &rect(10.0, 10.0, 20.0, 20.0));
+
+            // Unknown operations are source-over.
+            PFCanvasSetGlobalCompositeOperation(canvas, 200);
+            PFCanvasSetShadowBlur(canvas, 4.0);
+            assert_eq!((*canvas).shadow_blur(), 4.0);
+            assert_eq!((*canvas).global_alpha(), 0.5);
+
+            let scene = PFCanvasCreateScene(canvas);
+            {
+                let scene = &*scene;
+                assert_eq!(scene.path_count(), 2);
+                for index in 0..2 {
+                    assert_eq!(scene.path(index).blend_mode(), BlendMode::Multiply);
+                    assert!(scene.path(index).clip_path().is_some());
+                }
+
+                let paints: Vec<_> = scene.paths().map(|(paint, _, _, _)| paint.clone()).collect();
+                assert_eq!(paints, vec![Paint::Color(ColorU { a: 128, ..BLUE.to_rust() }),
+                                        Paint::Color(ColorU { a: 128, ..RED.to_rust() })]);
+            }
+            PFSceneDestroy(scene);
         }
     }
 }
@@ -0,0 +1,187 @@
+// pathfinder/canvas/src/gradient_stroke.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Strokes whose colors follow the path.
+//!
+//! The paint texture can only vary colors affinely across the canvas, so each contour is cut into
+//! short pieces, and each piece is stroked with the color of the gradient at its distance along
+//! the contour. Pieces meet with butt ends, and the caps of the whole stroke are drawn separately
+//! so that they don't overlap the pieces.
+
+use pathfinder_color::ColorU;
+use pathfinder_content::gradient::Gradient;
+use pathfinder_content::measure::ContourMeasure;
+use pathfinder_content::outline::{ArcDirection, Contour, Outline};
+use pathfinder_content::stroke::{LineCap, OutlineStrokeToFill, StrokeStyle};
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+use pathfinder_geometry::vector::Vector2F;
+use std::f32::consts::PI;
+
+// The length of each piece of solid color, in device pixels.
+const PIECE_LENGTH: f32 = 2.0;
+// No contour is cut into more pieces than this.
+const MAX_PIECES_PER_CONTOUR: u32 = 1024;
+// Each piece runs on into the next by this many device pixels, so that it draws the join at a
+// corner that falls right on the boundary between them.
+const PIECE_OVERLAP: f32 = 0.125;
+// The tolerance to flatten curves to, in device pixels.
+const FLATTENING_TOLERANCE: f32 = 0.25;
+
+/// Strokes `outline` with `stroke_style`, coloring each contour with `gradient` from offset 0 at
+/// its start to 1 at its end.
+///
+/// `scale` is the scale from the space of the outline to device pixels. If `line_dash` is
+/// nonempty, the contours are dashed first, as `OutlineDash` does with the pattern restarting for
+/// each contour; the dashes keep the colors of the part of the contour they cover.
+///
+/// Returns the filled outlines to draw, in the space of `outline`, and their colors.
+pub(crate) fn stroke_with_gradient_along(outline: &Outline,
+                                         gradient: &Gradient,
+                                         stroke_style: StrokeStyle,
+                                         line_dash: &[f32],
+                                         line_dash_offset: f32,
+                                         scale: f32)
+                                         -> Vec<(Outline, ColorU)> {
+    let mut pieces = vec![];
+    for contour in outline.contours() {
+        let measure = ContourMeasure::new(contour, FLATTENING_TOLERANCE / scale);
+        let length = measure.length();
+        if length == 0.0 {
+            continue;
+        }
+
+        let runs = match dash_intervals(line_dash, line_dash_offset, length) {
+            Some(runs) => runs,
+            None => vec![(0.0, length)],
+        };
+        let closed = measure.is_closed() && runs.len() == 1 && runs[0] == (0.0, length);
+        for (start, end) in runs {
+            stroke_run(&measure,
+                       start,
+                       end,
+                       closed,
+                       gradient,
+                       stroke_style,
+                       scale,
+                       &mut pieces);
+        }
+    }
+    pieces
+}
+
+// Strokes the part of `measure` between `start` and `end`. If `closed` is true, the run is the
+// whole of a closed contour, so it has no caps, and its last piece joins its first.
+#[allow(clippy::too_many_arguments)]
+fn stroke_run(measure: &ContourMeasure,
+              start: f32,
+              end: f32,
+              closed: bool,
+              gradient: &Gradient,
+              stroke_style: StrokeStyle,
+              scale: f32,
+              pieces: &mut Vec<(Outline, ColorU)>) {
+    let contour_length = measure.length();
+    let run_length = end - start;
+    let piece_count = ((run_length * scale / PIECE_LENGTH).ceil() as u32)
+        .max(1)
+        .min(MAX_PIECES_PER_CONTOUR);
+    let piece_length = run_length / piece_count as f32;
+    let overlap = PIECE_OVERLAP / scale;
+    let piece_style = StrokeStyle { line_cap: LineCap::Butt, ..stroke_style };
+
+    for piece_index in 0..piece_count {
+        let piece_start = start + piece_index as f32 * piece_length;
+        let piece_end = piece_start + piece_length;
+        let last = piece_index + 1 == piece_count;
+
+        let mut contour = measure.sub_contour(piece_start,
+                                              if last { piece_end } else { piece_end + overlap });
+        if last && closed {
+            let wrapped = measure.sub_contour(0.0, overlap);
+            for point_index in 0..wrapped.len() {
+                let point = wrapped.position_of(point_index);
+                if contour.last_position() != Some(point) {
+                    contour.push_endpoint(point);
+                }
+            }
+        }
+
+        let mut piece_outline = Outline::new();
+        piece_outline.push_contour(contour);
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&piece_outline, piece_style);
+        stroke_to_fill.offset();
+
+        let offset = (piece_start + piece_end) * 0.5 / contour_length;
+        pieces.push((stroke_to_fill.into_outline(), gradient.sample(offset)));
+    }
+
+    if closed || stroke_style.line_cap == LineCap::Butt {
+        return;
+    }
+    let caps = [
+        (measure.point_at_length(start), -measure.tangent_at_length(start), start),
+        (measure.point_at_length(end), measure.tangent_at_length(end), end),
+    ];
+    for &(point, outward, distance) in &caps {
+        let mut cap_outline = Outline::new();
+        cap_outline.push_contour(cap(point, outward, stroke_style));
+        pieces.push((cap_outline, gradient.sample(distance / contour_length)));
+    }
+}
+
+// The cap that `stroke_style` puts on the end of a line at `point`, sticking out toward `outward`.
+fn cap(point: Vector2F, outward: Vector2F, stroke_style: StrokeStyle) -> Contour {
+    // Maps the unit square to the end of the line: +x along the line's normal, +y outward.
+    let half_width = stroke_style.line_width * 0.5;
+    let normal = Vector2F::new(-outward.y(), outward.x());
+    let transform = Transform2F {
+        matrix: Matrix2x2F::row_major(normal.x(), outward.x(), normal.y(), outward.y()),
+        vector: point,
+    } * Transform2F::from_uniform_scale(half_width);
+
+    let mut contour = Contour::new();
+    match stroke_style.line_cap {
+        LineCap::Butt => {}
+        LineCap::Square => {
+            for &corner in &[(1.0, 0.0), (1.0, 1.0), (-1.0, 1.0), (-1.0, 0.0)] {
+                contour.push_endpoint(transform * Vector2F::new(corner.0, corner.1));
+            }
+        }
+        LineCap::Round => contour.push_arc(&transform, 0.0, PI, ArcDirection::CW),
+    }
+    contour.close();
+    contour
+}
+
+// Returns the parts of a contour of `length` that the dash pattern `dashes`, starting at
+// `offset`, draws, or `None` if the pattern has no positive length.
+fn dash_intervals(dashes: &[f32], offset: f32, length: f32) -> Option<Vec<(f32, f32)>> {
+    let pattern_length: f32 = dashes.iter().sum();
+    if dashes.len() < 2 || !(pattern_length > 0.0) {
+        return None;
+    }
+
+    // Find where the pattern begins, at or before the start of the contour.
+    let mut position = -(offset - (offset / pattern_length).floor() * pattern_length);
+    let mut intervals = vec![];
+    'outer: loop {
+        for (dash_index, &dash_length) in dashes.iter().enumerate() {
+            if position >= length {
+                break 'outer;
+            }
+            let dash_end = position + dash_length;
+            if dash_index % 2 == 0 && dash_end > 0.0 && dash_length > 0.0 {
+                intervals.push((position.max(0.0), dash_end.min(length)));
+            }
+            position = dash_end;
+        }
+    }
+    Some(intervals)
+}
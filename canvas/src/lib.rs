@@ -10,31 +10,50 @@
 
 //! A simple API for Pathfinder that mirrors a subset of HTML canvas.
 
-use pathfinder_color::ColorU;
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::boolean::BooleanOp;
 use pathfinder_content::dash::OutlineDash;
+use pathfinder_content::effects::BlendMode;
 use pathfinder_content::gradient::Gradient;
 use pathfinder_content::outline::{ArcDirection, Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern};
 use pathfinder_content::stroke::{LineCap, LineJoin as StrokeLineJoin};
 use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
 use pathfinder_geometry::line_segment::LineSegment2F;
-use pathfinder_geometry::vector::Vector2F;
-use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_gpu::{Device, TextureData};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::BuildOptions;
 use pathfinder_renderer::paint::{Paint, PaintId};
-use pathfinder_renderer::scene::{PathObject, Scene};
+use pathfinder_renderer::picture::Picture;
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, PathObject, Scene, SceneBuildError};
 use std::borrow::Cow;
 use std::default::Default;
-use std::f32::consts::PI;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::mem;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use text::FontCollection;
+
+pub use pathfinder_content::fill::FillRule;
+#[cfg(feature = "pf-text")]
+pub use text::{FontFallbackChain, TextHinting, TextMetrics, TextRenderOptions, TextShaper};
 #[cfg(feature = "pf-text")]
-pub use text::TextMetrics;
+pub use text::{BidiShaper, VerticalShaper, VerticalSubstitution};
 pub use text::CanvasFontContext;
 
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 const DEFAULT_FONT_SIZE: f32 = 10.0;
 
+// The length of the renderer's paint texture, the largest image it can draw.
+const MAX_REPEATED_PATTERN_LENGTH: i32 = 1024;
+
+mod gradient_stroke;
+mod shadow;
+
 #[cfg_attr(not(feature = "pf-text"), path = "text_no_text.rs")]
 mod text;
 
@@ -42,6 +61,7 @@ pub struct CanvasRenderingContext2D {
     scene: Scene,
     current_state: State,
     saved_states: Vec<State>,
+    pixel_ratio: f32,
     #[allow(dead_code)]
     font_context: CanvasFontContext,
 }
@@ -54,6 +74,23 @@ impl CanvasRenderingContext2D {
         CanvasRenderingContext2D::from_scene(font_context, scene)
     }
 
+    /// Creates a canvas that is `logical_size` in size, backed by a scene `pixel_ratio` times as
+    /// large in each dimension.
+    ///
+    /// Coordinates, line widths, and font sizes are all given in logical units, as in an HTML
+    /// canvas on a HiDPI display, so callers don't need to scale anything themselves. Shadow
+    /// offsets and blur radii are scaled likewise. Pixel manipulation methods work on device
+    /// pixels.
+    pub fn new_with_pixel_ratio(font_context: CanvasFontContext,
+                                logical_size: Vector2F,
+                                pixel_ratio: f32)
+                                -> CanvasRenderingContext2D {
+        let mut canvas = CanvasRenderingContext2D::new(font_context, logical_size.scale(pixel_ratio));
+        canvas.pixel_ratio = pixel_ratio;
+        canvas.reset_transform();
+        canvas
+    }
+
     pub fn from_scene(font_context: CanvasFontContext, scene: Scene) -> CanvasRenderingContext2D {
         #[cfg(feature = "pf-text")]
         let default_font_collection = font_context.default_font_collection.clone();
@@ -63,6 +100,7 @@ impl CanvasRenderingContext2D {
             scene,
             current_state: State::default(default_font_collection),
             saved_states: vec![],
+            pixel_ratio: 1.0,
             font_context,
         }
     }
@@ -72,6 +110,12 @@ impl CanvasRenderingContext2D {
         self.scene
     }
 
+    /// The number of device pixels per logical unit in each dimension.
+    #[inline]
+    pub fn pixel_ratio(&self) -> f32 {
+        self.pixel_ratio
+    }
+
     // Drawing rectangles
 
     #[inline]
@@ -110,8 +154,23 @@ impl CanvasRenderingContext2D {
         self.current_state.miter_limit = new_miter_limit
     }
 
+    /// Returns the dash pattern, after any duplication that `set_line_dash()` did. An empty
+    /// pattern means that lines are solid.
+    #[inline]
+    pub fn line_dash(&self) -> &[f32] {
+        &self.current_state.line_dash
+    }
+
+    /// Sets the alternating lengths of the dashes and gaps of stroked lines, in user space.
+    ///
+    /// As in HTML canvas, an odd number of lengths is repeated to make it even, and patterns with
+    /// negative or non-finite lengths are ignored.
     #[inline]
     pub fn set_line_dash(&mut self, mut new_line_dash: Vec<f32>) {
+        if new_line_dash.iter().any(|&length| !(length >= 0.0 && length.is_finite())) {
+            return;
+        }
+
         // Duplicate and concatenate if an odd number of dashes are present.
         if new_line_dash.len() % 2 == 1 {
             let mut real_line_dash = new_line_dash.clone();
@@ -122,67 +181,163 @@ impl CanvasRenderingContext2D {
         self.current_state.line_dash = new_line_dash
     }
 
+    #[inline]
+    pub fn line_dash_offset(&self) -> f32 {
+        self.current_state.line_dash_offset
+    }
+
+    /// Sets the distance into the dash pattern at which each subpath starts. As in HTML canvas,
+    /// non-finite values are ignored.
     #[inline]
     pub fn set_line_dash_offset(&mut self, new_line_dash_offset: f32) {
-        self.current_state.line_dash_offset = new_line_dash_offset
+        if new_line_dash_offset.is_finite() {
+            self.current_state.line_dash_offset = new_line_dash_offset
+        }
     }
 
     // Fill and stroke styles
 
     #[inline]
     pub fn set_fill_style(&mut self, new_fill_style: FillStyle) {
-        self.current_state.fill_paint = new_fill_style.into_paint();
+        self.current_state.fill_style = new_fill_style;
     }
 
     #[inline]
     pub fn set_stroke_style(&mut self, new_stroke_style: FillStyle) {
-        self.current_state.stroke_paint = new_stroke_style.into_paint();
+        self.current_state.stroke_style = new_stroke_style;
+    }
+
+    /// Creates a fill style that paints `image`, repeated as specified.
+    ///
+    /// The pixels of the image map to user space until `CanvasPattern::set_transform()` is called.
+    #[inline]
+    pub fn create_pattern(&self, image: Image, repetition: PatternRepetition) -> CanvasPattern {
+        CanvasPattern { image, transform: Transform2F::default(), repetition }
     }
 
     // Shadows
 
+    #[inline]
+    pub fn shadow_color(&self) -> ColorU {
+        self.current_state.shadow_color
+    }
+
     #[inline]
     pub fn set_shadow_color(&mut self, new_shadow_color: ColorU) {
-        self.current_state.shadow_paint = Paint::Color(new_shadow_color);
+        self.current_state.shadow_color = new_shadow_color;
+    }
+
+    #[inline]
+    pub fn shadow_blur(&self) -> f32 {
+        self.current_state.shadow_blur
+    }
+
+    /// Sets the amount of blur, in logical pixels, which the pixel ratio scales to device pixels.
+    /// The current transform doesn't affect it. As in HTML canvas, negative and non-finite values
+    /// are ignored.
+    #[inline]
+    pub fn set_shadow_blur(&mut self, new_shadow_blur: f32) {
+        if new_shadow_blur >= 0.0 && new_shadow_blur.is_finite() {
+            self.current_state.shadow_blur = new_shadow_blur;
+        }
+    }
+
+    #[inline]
+    pub fn shadow_offset(&self) -> Vector2F {
+        self.current_state.shadow_offset
     }
 
+    /// Sets the offset of the shadow, in logical pixels, which the pixel ratio scales to device
+    /// pixels. The current transform doesn't affect it. As in HTML canvas, non-finite values are
+    /// ignored.
     #[inline]
     pub fn set_shadow_offset(&mut self, new_shadow_offset: Vector2F) {
-        self.current_state.shadow_offset = new_shadow_offset;
+        if new_shadow_offset.x().is_finite() && new_shadow_offset.y().is_finite() {
+            self.current_state.shadow_offset = new_shadow_offset;
+        }
     }
 
     // Drawing paths
 
     #[inline]
     pub fn fill_path(&mut self, path: Path2D) {
+        self.fill_path_with_fill_rule(path, FillRule::Winding)
+    }
+
+    /// Fills `path` with `fill_rule`, as HTML canvas `fill(path, fillRule)` does.
+    pub fn fill_path_with_fill_rule(&mut self, path: Path2D, fill_rule: FillRule) {
         let mut outline = path.into_outline();
         outline.transform(&self.current_state.transform);
 
-        let paint = self.current_state.resolve_paint(&self.current_state.fill_paint);
-        let paint_id = self.scene.push_paint(&paint);
-
-        self.push_path(outline, paint_id);
+        let fill_style = self.current_state.fill_style.clone();
+        self.push_styled_path(outline, fill_style, fill_rule);
     }
 
     #[inline]
     pub fn stroke_path(&mut self, path: Path2D) {
-        let paint = self.current_state.resolve_paint(&self.current_state.stroke_paint);
-        let paint_id = self.scene.push_paint(&paint);
+        let outline = self.stroke_outline(path);
+        let stroke_style = self.current_state.stroke_style.clone();
+        self.push_styled_path(outline, stroke_style, FillRule::Winding);
+    }
 
-        let mut stroke_style = self.current_state.resolve_stroke_style();
-        
-        // the smaller scale is relevant here, as we multiply by it and want to ensure it is always bigger than HAIRLINE_STROKE_WIDTH
-        let transform_scale = f32::min(self.current_state.transform.m11(), self.current_state.transform.m22());
-        // avoid the division in the normal case of sufficient thickness
-        if stroke_style.line_width * transform_scale < HAIRLINE_STROKE_WIDTH {
-            stroke_style.line_width = HAIRLINE_STROKE_WIDTH / transform_scale;
+    /// Strokes `path` with the colors of `gradient` laid out along each of its subpaths, from
+    /// offset 0 at the start of the subpath to 1 at its end, instead of across the canvas.
+    ///
+    /// Only the stops and wrap mode of the gradient are used; its geometry is ignored. The line
+    /// styles, dashes, shadow, and global alpha apply as for `stroke_path()`. Subpaths are drawn
+    /// as runs of short pieces of solid color, which overlap by a fraction of a pixel where they
+    /// meet, so translucent strokes can show faint seams.
+    pub fn stroke_path_with_gradient_along(&mut self, path: Path2D, gradient: &Gradient) {
+        if self.current_state.has_shadow() {
+            let outline = self.stroke_outline(path.clone());
+            self.push_shadow(&outline, FillRule::Winding);
         }
 
+        let transform = self.current_state.transform;
+        let scale = transform.matrix.det().abs().sqrt();
+        let pieces = gradient_stroke::stroke_with_gradient_along(&path.into_outline(),
+                                                                 gradient,
+                                                                 self.stroke_style(),
+                                                                 &self.current_state.line_dash,
+                                                                 self.current_state
+                                                                     .line_dash_offset,
+                                                                 scale);
+        for (mut outline, color) in pieces {
+            outline.transform(&transform);
+            let paint = self.current_state.resolve_paint(&Paint::Color(color)).into_owned();
+            let paint_id = self.scene.push_paint(&paint);
+            self.push_path_object(outline, paint_id, FillRule::Winding);
+        }
+    }
+
+    // Hit testing
+
+    /// Returns true if `point`, in canvas coordinates, would be painted by filling `path` with
+    /// `fill_rule` under the current transform.
+    #[inline]
+    pub fn is_point_in_path(&self, path: &Path2D, point: Vector2F, fill_rule: FillRule) -> bool {
+        let mut outline = path.clone().into_outline();
+        outline.transform(&self.current_state.transform);
+        outline.contains_point(point.scale(self.pixel_ratio), fill_rule)
+    }
+
+    /// Returns true if `point`, in canvas coordinates, would be painted by stroking `path` with
+    /// the current line styles and transform.
+    #[inline]
+    pub fn is_point_in_stroke(&self, path: &Path2D, point: Vector2F) -> bool {
+        let point = point.scale(self.pixel_ratio);
+        self.stroke_outline(path.clone()).contains_point(point, FillRule::Winding)
+    }
+
+    // Returns the outline that stroking `path` fills, in device pixels.
+    fn stroke_outline(&self, path: Path2D) -> Outline {
+        let stroke_style = self.stroke_style();
         let mut outline = path.into_outline();
         if !self.current_state.line_dash.is_empty() {
             let mut dash = OutlineDash::new(&outline,
                                             &self.current_state.line_dash,
                                             self.current_state.line_dash_offset);
+            dash.set_restart_per_contour(true);
             dash.dash();
             outline = dash.into_outline();
         }
@@ -192,37 +347,443 @@ impl CanvasRenderingContext2D {
         outline = stroke_to_fill.into_outline();
 
         outline.transform(&self.current_state.transform);
-        self.push_path(outline, paint_id);
+        outline
     }
 
-    fn push_path(&mut self, outline: Outline, paint_id: PaintId) {
-        if !self.current_state.shadow_paint.is_fully_transparent() {
-            let paint = self.current_state.resolve_paint(&self.current_state.shadow_paint);
-            let paint_id = self.scene.push_paint(&paint);
+    // Returns the current line styles, widened if need be so that lines are no thinner than a
+    // hairline on screen.
+    fn stroke_style(&self) -> StrokeStyle {
+        let mut stroke_style = self.current_state.resolve_stroke_style();
+        
+        // the smaller scale is relevant here, as we multiply by it and want to ensure it is always bigger than HAIRLINE_STROKE_WIDTH
+        let transform_scale = f32::min(self.current_state.transform.m11(), self.current_state.transform.m22());
+        // avoid the division in the normal case of sufficient thickness
+        if stroke_style.line_width * transform_scale < HAIRLINE_STROKE_WIDTH {
+            stroke_style.line_width = HAIRLINE_STROKE_WIDTH / transform_scale;
+        }
+        stroke_style
+    }
 
-            let mut outline = outline.clone();
-            outline.transform(&Transform2F::from_translation(self.current_state.shadow_offset));
-            self.scene.push_path(PathObject::new(outline, paint_id, String::new()))
+    // Drawing images
+
+    /// Draws all of `image` with its upper left corner at `dest_location`, if that's a point, or
+    /// stretched to fill `dest_location`, if that's a rectangle.
+    #[inline]
+    pub fn draw_image<'b, I, L>(&mut self, image: I, dest_location: L)
+                                where I: Into<CanvasImageSource<'b>>,
+                                      L: CanvasImageDestLocation {
+        let image = image.into();
+        let src_location = RectF::new(Vector2F::default(), image.size());
+        self.draw_subimage(image, src_location, dest_location)
+    }
+
+    /// Draws the part of `image` within `src_location`, stretched to fill `dest_location`.
+    ///
+    /// As in HTML canvas, the parts of `src_location` that lie outside the image are not drawn,
+    /// and nothing is drawn if either rectangle has no area.
+    pub fn draw_subimage<'b, I, L>(&mut self, image: I, src_location: RectF, dest_location: L)
+                                   where I: Into<CanvasImageSource<'b>>,
+                                         L: CanvasImageDestLocation {
+        let image = image.into();
+        let dest_location = dest_location.to_rect(src_location.size());
+        let has_area = |rect: RectF| rect.width() > 0.0 && rect.height() > 0.0;
+        if !has_area(src_location) || !has_area(dest_location) {
+            return;
         }
 
-        self.scene.push_path(PathObject::new(outline, paint_id, String::new()))
+        let src_to_dest_transform =
+            Transform2F::from_translation(dest_location.origin()) *
+            Transform2F::from_scale(dest_location.size() / src_location.size()) *
+            Transform2F::from_translation(-src_location.origin());
+
+        let image_rect = RectF::new(Vector2F::default(), image.size());
+        let src_location = match src_location.intersection(image_rect) {
+            None => return,
+            Some(src_location) => src_location,
+        };
+        let dest_location = RectF::from_points(src_to_dest_transform * src_location.origin(),
+                                               src_to_dest_transform *
+                                               src_location.lower_right());
+
+        match image {
+            CanvasImageSource::Image(image) => {
+                let mut path = Path2D::new();
+                path.rect(dest_location);
+                let mut outline = path.into_outline();
+                outline.transform(&self.current_state.transform);
+
+                let paint = Paint::Pattern(Pattern::new(image, src_to_dest_transform));
+                let paint = self.current_state.resolve_paint(&paint);
+                let paint_id = self.scene.push_paint(&paint);
+                self.push_path(outline, paint_id, FillRule::Winding);
+            }
+            CanvasImageSource::Canvas(canvas) => {
+                let view_box_origin = canvas.scene.view_box().origin();
+                let src_to_dest_transform = src_to_dest_transform *
+                    Transform2F::from_translation(-view_box_origin);
+                let transform = self.current_state.transform * src_to_dest_transform;
+
+                for (paint, outline, _, fill_rule) in canvas.scene.paths() {
+                    let mut outline = (*outline).clone();
+                    outline.transform(&src_to_dest_transform);
+                    outline.clip_against_rect(dest_location);
+                    if outline.contours().is_empty() {
+                        continue;
+                    }
+                    outline.transform(&self.current_state.transform);
+
+                    let mut paint = (*paint).clone();
+                    paint.apply_transform(&transform);
+                    paint.set_opacity(self.current_state.global_alpha);
+                    let paint_id = self.scene.push_paint(&paint);
+                    self.push_path(outline, paint_id, fill_rule);
+                }
+            }
+        }
+    }
+
+    /// Draws `image` as a nine-slice image stretched to fill `dest_location`, as UI themes do for
+    /// panels and buttons.
+    ///
+    /// `center_location`, in image pixels, divides the image into a 3×3 grid. The corners are
+    /// drawn at their natural size, the edges are stretched along their length, and the center
+    /// is stretched both ways. As with CSS `border-image`, if the corners don't fit within
+    /// `dest_location`, they're all scaled down by the same amount until they do.
+    pub fn draw_image_nine_slice<'b, I>(&mut self,
+                                        image: I,
+                                        center_location: RectF,
+                                        dest_location: RectF)
+                                        where I: Into<CanvasImageSource<'b>> {
+        let image = image.into();
+        let image_rect = RectF::new(Vector2F::default(), image.size());
+        let center_location = match center_location.intersection(image_rect) {
+            None => return,
+            Some(center_location) => center_location,
+        };
+
+        let (corner_origin, corner_lower_right) =
+            (center_location.origin(), image_rect.lower_right() - center_location.lower_right());
+        let corner_size = corner_origin + corner_lower_right;
+        let dest_size = dest_location.size();
+        let mut scale = 1.0;
+        if corner_size.x() > dest_size.x() {
+            scale = f32::min(scale, dest_size.x() / corner_size.x());
+        }
+        if corner_size.y() > dest_size.y() {
+            scale = f32::min(scale, dest_size.y() / corner_size.y());
+        }
+
+        let src_edges = [image_rect.origin(),
+                         center_location.origin(),
+                         center_location.lower_right(),
+                         image_rect.lower_right()];
+        let dest_edges = [dest_location.origin(),
+                          dest_location.origin() + corner_origin.scale(scale),
+                          dest_location.lower_right() - corner_lower_right.scale(scale),
+                          dest_location.lower_right()];
+        for row in 0..3 {
+            for column in 0..3 {
+                let src_rect =
+                    RectF::from_points(Vector2F::new(src_edges[column].x(), src_edges[row].y()),
+                                       Vector2F::new(src_edges[column + 1].x(),
+                                                     src_edges[row + 1].y()));
+                let dest_rect =
+                    RectF::from_points(Vector2F::new(dest_edges[column].x(),
+                                                     dest_edges[row].y()),
+                                       Vector2F::new(dest_edges[column + 1].x(),
+                                                     dest_edges[row + 1].y()));
+                self.draw_subimage(image.clone(), src_rect, dest_rect);
+            }
+        }
+    }
+
+    /// Replays `picture` under the current transform, within the current clipping region and
+    /// with the global alpha applied. The paths keep the blend modes they were recorded with.
+    pub fn draw_picture(&mut self, picture: &Picture) {
+        let transform = self.current_state.transform;
+        let clip_path = self.current_state.clip_path;
+        if self.current_state.global_alpha == 1.0 {
+            picture.replay(&mut self.scene, &transform, clip_path);
+            return;
+        }
+
+        let mut picture = picture.clone();
+        picture.set_opacity(self.current_state.global_alpha);
+        picture.replay(&mut self.scene, &transform, clip_path);
+    }
+
+    // Pixel manipulation
+
+    /// Renders the canvas with `renderer` and reads back the pixels within `rect`.
+    ///
+    /// This overwrites the contents of the renderer's destination framebuffer and waits for the
+    /// GPU to finish, so it's slow. As in HTML canvas, pixels outside the framebuffer are
+    /// transparent black. Nothing is drawn if the scene can't be built. Floating-point and
+    /// 10-bit destinations are converted to 8 bits per channel, clamping out-of-range values.
+    pub fn get_image_data<D>(&self, renderer: &mut Renderer<D>, rect: RectI)
+                             -> Result<Image, ImageDataError>
+                             where D: Device {
+        let commands = Mutex::new(vec![]);
+        self.scene.build(BuildOptions::default(),
+                         |command| commands.lock().unwrap().push(command),
+                         &SequentialExecutor)?;
+
+        renderer.begin_scene();
+        for command in commands.into_inner().unwrap() {
+            renderer.render_command(&command);
+        }
+        renderer.end_scene();
+
+        let framebuffer_size = renderer.dest_framebuffer().window_size(&renderer.device);
+        let framebuffer_rect = RectI::new(Vector2I::default(), framebuffer_size);
+        let receiver = renderer.device.read_pixels(&renderer.dest_render_target(),
+                                                   framebuffer_rect);
+        let pixel_count = framebuffer_size.x() as usize * framebuffer_size.y() as usize;
+        let framebuffer_pixels: Vec<ColorU> = match renderer.device.recv_texture_data(&receiver) {
+            TextureData::U8(ref texels) if texels.len() == pixel_count * 4 => {
+                texels.chunks(4).map(|texel| {
+                    ColorU { r: texel[0], g: texel[1], b: texel[2], a: texel[3] }
+                }).collect()
+            }
+            TextureData::F16(ref texels) if texels.len() == pixel_count * 4 => {
+                texels.chunks(4).map(|texel| {
+                    color_from_floats(texel[0].to_f32(),
+                                      texel[1].to_f32(),
+                                      texel[2].to_f32(),
+                                      texel[3].to_f32())
+                }).collect()
+            }
+            TextureData::F32(ref texels) if texels.len() == pixel_count * 4 => {
+                texels.chunks(4).map(|texel| {
+                    color_from_floats(texel[0], texel[1], texel[2], texel[3])
+                }).collect()
+            }
+            TextureData::U32(ref texels) if texels.len() == pixel_count => {
+                // Red is in the low 10 bits, then green, blue, and a 2-bit alpha.
+                texels.iter().map(|&texel| {
+                    color_from_floats((texel & 0x3ff) as f32 / 1023.0,
+                                      ((texel >> 10) & 0x3ff) as f32 / 1023.0,
+                                      ((texel >> 20) & 0x3ff) as f32 / 1023.0,
+                                      (texel >> 30) as f32 / 3.0)
+                }).collect()
+            }
+            _ => return Err(ImageDataError::UnsupportedPixelFormat),
+        };
+
+        let size = rect.size();
+        let mut pixels = vec![ColorU::transparent_black(); size.x() as usize * size.y() as usize];
+        if let Some(copy_rect) = rect.intersection(framebuffer_rect) {
+            for y in copy_rect.min_y()..copy_rect.max_y() {
+                for x in copy_rect.min_x()..copy_rect.max_x() {
+                    let src_index = (y * framebuffer_size.x() + x) as usize;
+                    let dest_index = ((y - rect.min_y()) * size.x() + x - rect.min_x()) as usize;
+                    pixels[dest_index] = framebuffer_pixels[src_index];
+                }
+            }
+        }
+        Ok(Image::new(size, Arc::new(pixels)))
+    }
+
+    /// Draws `image` with its upper left corner at `dest_origin`.
+    ///
+    /// As in HTML canvas, the current transform, global alpha, and shadow don't apply. Unlike in
+    /// HTML canvas, the image is composited over what's already been drawn instead of replacing
+    /// it.
+    pub fn put_image_data(&mut self, image: &Image, dest_origin: Vector2I) {
+        let dest_origin = dest_origin.to_f32();
+        let mut path = Path2D::new();
+        path.rect(RectF::new(dest_origin, image.size().to_f32()));
+
+        let pattern = Pattern::new(image.clone(), Transform2F::from_translation(dest_origin));
+        let paint_id = self.scene.push_paint(&Paint::Pattern(pattern));
+        self.scene.push_path(PathObject::new(path.into_outline(), paint_id, String::new()))
+    }
+
+    // Fills `outline`, which is in canvas coordinates, with `fill_style` in the current state.
+    fn push_styled_path(&mut self, outline: Outline, fill_style: FillStyle, fill_rule: FillRule) {
+        let paint = self.current_state.resolve_paint(&fill_style.to_paint()).into_owned();
+        match (paint, fill_style) {
+            (Paint::Pattern(pattern), FillStyle::Pattern(canvas_pattern)) => {
+                self.push_pattern_path(outline, pattern, canvas_pattern.repetition, fill_rule)
+            }
+            (paint, _) => {
+                let paint_id = self.scene.push_paint(&paint);
+                self.push_path(outline, paint_id, fill_rule)
+            }
+        }
+    }
+
+    // The renderer draws only one copy of the image of a pattern, so the outline is split into a
+    // path for each copy of the image that it covers.
+    //
+    // Destructive composite operations clear everything outside the path they're drawn with,
+    // which would erase the other copies, so with those the outline is drawn once, with an image
+    // of all the copies.
+    //
+    // TODO: Wrap texture coordinates on the GPU instead, since small images repeated
+    // over large areas make many paths.
+    fn push_pattern_path(&mut self,
+                         mut outline: Outline,
+                         pattern: Pattern,
+                         repetition: PatternRepetition,
+                         fill_rule: FillRule) {
+        let image_size = pattern.image.size().to_f32();
+        let region = match outline.bounds().intersection(self.scene.view_box()) {
+            Some(region) if image_size.x() > 0.0 && image_size.y() > 0.0 => region,
+            _ => return,
+        };
+
+        // Find the copies of the image that overlap the visible part of the outline.
+        let inverse_transform = pattern.transform.inverse();
+        let mut image_region = RectF::from_points(inverse_transform * region.origin(),
+                                                  inverse_transform * region.origin());
+        for &corner in &[region.upper_right(), region.lower_right(), region.lower_left()] {
+            image_region = image_region.union_point(inverse_transform * corner);
+        }
+        let mut first_copy = (image_region.origin() / image_size).floor().to_i32();
+        let mut last_copy = (image_region.lower_right() / image_size).ceil().to_i32();
+        if !repetition.repeats_x() {
+            first_copy.set_x(0);
+            last_copy.set_x(1);
+        }
+        if !repetition.repeats_y() {
+            first_copy.set_y(0);
+            last_copy.set_y(1);
+        }
+
+        let copies_rect = RectF::from_points(first_copy.to_f32() * image_size,
+                                             last_copy.to_f32() * image_size);
+        outline.clip_against_polygon(&transformed_rect_polygon(&pattern.transform, copies_rect));
+        if outline.contours().is_empty() {
+            return;
+        }
+        if self.current_state.has_shadow() {
+            self.push_shadow(&outline, fill_rule);
+        }
+
+        let copy_count = last_copy - first_copy;
+        let blend_mode = self.current_state.global_composite_operation.to_blend_mode();
+        if blend_mode.is_destructive() && (copy_count.x() > 1 || copy_count.y() > 1) {
+            let pattern = repeat_pattern(&pattern, first_copy, copy_count);
+            let paint_id = self.scene.push_paint(&Paint::Pattern(pattern));
+            self.push_path_object(outline, paint_id, fill_rule);
+            return;
+        }
+
+        for y in first_copy.y()..last_copy.y() {
+            for x in first_copy.x()..last_copy.x() {
+                let copy_origin = Vector2I::new(x, y).to_f32() * image_size;
+                let copy_rect = RectF::new(copy_origin, image_size);
+                let mut copy_outline = outline.clone();
+                copy_outline.clip_against_polygon(&transformed_rect_polygon(&pattern.transform,
+                                                                            copy_rect));
+                if copy_outline.contours().is_empty() {
+                    continue;
+                }
+
+                let copy_transform = pattern.transform *
+                    Transform2F::from_translation(copy_origin);
+                let copy_pattern = Pattern::new(pattern.image.clone(), copy_transform);
+                let paint_id = self.scene.push_paint(&Paint::Pattern(copy_pattern));
+                self.push_path_object(copy_outline, paint_id, fill_rule);
+            }
+        }
+    }
+
+    fn push_path(&mut self, outline: Outline, paint_id: PaintId, fill_rule: FillRule) {
+        if self.current_state.has_shadow() {
+            self.push_shadow(&outline, fill_rule);
+        }
+
+        self.push_path_object(outline, paint_id, fill_rule)
+    }
+
+    fn push_path_object(&mut self, outline: Outline, paint_id: PaintId, fill_rule: FillRule) {
+        let mut path_object = PathObject::new(outline, paint_id, String::new());
+        path_object.set_fill_rule(fill_rule);
+        path_object.set_blend_mode(self.current_state.global_composite_operation.to_blend_mode());
+        path_object.set_clip_path(self.current_state.clip_path);
+        self.scene.push_path(path_object)
+    }
+
+    // TODO: The shadows of gradients and patterns should follow their alpha.
+    fn push_shadow(&mut self, outline: &Outline, fill_rule: FillRule) {
+        let mut color = self.current_state.shadow_color;
+        color.a = (color.a as f32 * self.current_state.global_alpha).round() as u8;
+
+        let mut outline = outline.clone();
+        let offset = self.current_state.shadow_offset.scale(self.pixel_ratio);
+        outline.transform(&Transform2F::from_translation(offset));
+
+        if self.current_state.shadow_blur == 0.0 {
+            let paint_id = self.scene.push_paint(&Paint::Color(color));
+            self.push_path_object(outline, paint_id, fill_rule);
+            return;
+        }
+
+        let blur = self.current_state.shadow_blur * self.pixel_ratio;
+        let shadow = shadow::render_blurred_shadow(&outline, fill_rule, color, blur);
+        let (image, origin) = match shadow {
+            None => return,
+            Some((image, origin)) => (image, origin.to_f32()),
+        };
+
+        let mut path = Path2D::new();
+        path.rect(RectF::new(origin, image.size().to_f32()));
+        let pattern = Pattern::new(image, Transform2F::from_translation(origin));
+        let paint_id = self.scene.push_paint(&Paint::Pattern(pattern));
+        self.push_path_object(path.into_outline(), paint_id, FillRule::Winding);
+    }
+
+    // Clipping
+
+    /// Restricts drawing to the area inside `path`, filled with `fill_rule` under the current
+    /// transform, as HTML canvas `clip()` does.
+    ///
+    /// The new clipping region is the intersection of `path` with the current one. Restoring a
+    /// saved state restores the clipping region that was current when it was saved.
+    ///
+    /// TODO: Clip text too.
+    pub fn clip_path(&mut self, path: Path2D, fill_rule: FillRule) {
+        let mut outline = path.into_outline();
+        outline.transform(&self.current_state.transform);
+
+        let clip_path = match self.current_state.clip_path {
+            None => ClipPath::new(outline, fill_rule),
+            Some(clip_path_id) => {
+                let current_clip_path = self.scene.clip_path(clip_path_id);
+                let current_outline = current_clip_path.outline();
+                let outline = current_outline.boolean_op_with_fill_rules(
+                    current_clip_path.fill_rule(),
+                    &outline,
+                    fill_rule,
+                    BooleanOp::Intersection);
+                ClipPath::new(outline, FillRule::Winding)
+            }
+        };
+        self.current_state.clip_path = Some(self.scene.push_clip_path(clip_path));
     }
 
     // Transformations
 
+    // The transform in the state maps user space to device pixels, so it includes the pixel
+    // ratio. These methods hide that, as HTML canvas does.
+
     #[inline]
     pub fn current_transform(&self) -> Transform2F {
-        self.current_state.transform
+        Transform2F::from_scale(Vector2F::splat(1.0 / self.pixel_ratio)) *
+            self.current_state.transform
     }
 
     #[inline]
     pub fn set_current_transform(&mut self, new_transform: &Transform2F) {
-        self.current_state.transform = *new_transform;
+        self.current_state.transform =
+            Transform2F::from_scale(Vector2F::splat(self.pixel_ratio)) * *new_transform;
     }
 
     #[inline]
     pub fn reset_transform(&mut self) {
-        self.current_state.transform = Transform2F::default();
+        self.current_state.transform = Transform2F::from_scale(Vector2F::splat(self.pixel_ratio));
     }
 
     // Compositing
@@ -237,6 +798,16 @@ impl CanvasRenderingContext2D {
         self.current_state.global_alpha = new_global_alpha;
     }
 
+    #[inline]
+    pub fn global_composite_operation(&self) -> CompositeOperation {
+        self.current_state.global_composite_operation
+    }
+
+    #[inline]
+    pub fn set_global_composite_operation(&mut self, new_composite_operation: CompositeOperation) {
+        self.current_state.global_composite_operation = new_composite_operation;
+    }
+
     // The canvas state
 
     #[inline]
@@ -263,12 +834,17 @@ struct State {
     miter_limit: f32,
     line_dash: Vec<f32>,
     line_dash_offset: f32,
-    fill_paint: Paint,
-    stroke_paint: Paint,
-    shadow_paint: Paint,
+    fill_style: FillStyle,
+    stroke_style: FillStyle,
+    shadow_color: ColorU,
+    shadow_blur: f32,
     shadow_offset: Vector2F,
     text_align: TextAlign,
+    text_baseline: TextBaseline,
+    direction: TextDirection,
     global_alpha: f32,
+    global_composite_operation: CompositeOperation,
+    clip_path: Option<ClipPathId>,
 }
 
 impl State {
@@ -283,25 +859,45 @@ impl State {
             miter_limit: 10.0,
             line_dash: vec![],
             line_dash_offset: 0.0,
-            fill_paint: Paint::black(),
-            stroke_paint: Paint::black(),
-            shadow_paint: Paint::transparent_black(),
+            fill_style: FillStyle::Color(ColorU::black()),
+            stroke_style: FillStyle::Color(ColorU::black()),
+            shadow_color: ColorU::transparent_black(),
+            shadow_blur: 0.0,
             shadow_offset: Vector2F::default(),
-            text_align: TextAlign::Left,
+            text_align: TextAlign::Start,
+            text_baseline: TextBaseline::Alphabetic,
+            direction: TextDirection::LeftToRight,
             global_alpha: 1.0,
+            global_composite_operation: CompositeOperation::SourceOver,
+            clip_path: None,
         }
     }
 
+    // Paints are specified in the user coordinate space, so they're transformed along with the
+    // paths they fill.
     fn resolve_paint<'a>(&self, paint: &'a Paint) -> Cow<'a, Paint> {
-        if self.global_alpha == 1.0 {
+        let must_transform = match *paint {
+            Paint::Color(_) => false,
+            Paint::Gradient(_) | Paint::Pattern(_) => !self.transform.is_identity(),
+        };
+        if self.global_alpha == 1.0 && !must_transform {
             return Cow::Borrowed(paint);
         }
 
         let mut paint = (*paint).clone();
         paint.set_opacity(self.global_alpha);
+        if must_transform {
+            paint.apply_transform(&self.transform);
+        }
         Cow::Owned(paint)
     }
 
+    // As in HTML canvas, shadows are drawn only if they're visible and either blurred or offset.
+    fn has_shadow(&self) -> bool {
+        !self.shadow_color.is_fully_transparent() &&
+            (self.shadow_blur > 0.0 || self.shadow_offset != Vector2F::default())
+    }
+
     fn resolve_stroke_style(&self) -> StrokeStyle {
         StrokeStyle {
             line_width: self.line_width,
@@ -321,7 +917,6 @@ pub struct Path2D {
     current_contour: Contour,
 }
 
-// TODO(pcwalton): `ellipse`
 impl Path2D {
     #[inline]
     pub fn new() -> Path2D {
@@ -355,6 +950,10 @@ impl Path2D {
         self.current_contour.push_cubic(ctrl0, ctrl1, to);
     }
 
+    /// Adds an arc of the circle with the given center and radius, as in HTML canvas. If the path
+    /// has a current point, a line connects it to the start of the arc.
+    ///
+    /// Negative radii, which HTML canvas rejects with an exception, are ignored.
     #[inline]
     pub fn arc(&mut self,
                center: Vector2F,
@@ -362,27 +961,49 @@ impl Path2D {
                start_angle: f32,
                end_angle: f32,
                direction: ArcDirection) {
-        let transform = Transform2F::from_scale(Vector2F::splat(radius)).translate(center);
-        self.current_contour.push_arc(&transform, start_angle, end_angle, direction);
+        self.ellipse(center, Vector2F::splat(radius), 0.0, start_angle, end_angle, direction)
     }
 
-    #[inline]
+    /// Adds an arc of the given radius that's tangent to the line from the current point to
+    /// `ctrl` and to the line from `ctrl` to `to`, connected to the current point by a line.
+    ///
+    /// As in HTML canvas, if the current point, `ctrl`, and `to` are collinear, or if the radius
+    /// is zero, this adds a line to `ctrl` instead. A path with no current point starts at
+    /// `ctrl`. Negative radii are ignored.
     pub fn arc_to(&mut self, ctrl: Vector2F, to: Vector2F, radius: f32) {
-        // FIXME(pcwalton): What should we do if there's no initial point?
-        let from = self.current_contour.last_position().unwrap_or_default();
+        if !(radius >= 0.0) {
+            return;
+        }
+        let from = match self.current_contour.last_position() {
+            None => {
+                self.current_contour.push_endpoint(ctrl);
+                return;
+            }
+            Some(from) => from,
+        };
+
         let (v0, v1) = (from - ctrl, to - ctrl);
+        if radius == 0.0 || v0.det(v1) == 0.0 {
+            self.current_contour.push_endpoint(ctrl);
+            return;
+        }
+
+        // The tangent points lie at the same distance from `ctrl`, along each line.
         let (vu0, vu1) = (v0.normalize(), v1.normalize());
-        let hypot = radius / f32::sqrt(0.5 * (1.0 - vu0.dot(vu1)));
+        let half_cos = f32::sqrt(0.5 * (1.0 + vu0.dot(vu1)));
+        let half_sin = f32::sqrt(0.5 * (1.0 - vu0.dot(vu1)));
+        let (start, end) = (ctrl + vu0.scale(radius * half_cos / half_sin),
+                            ctrl + vu1.scale(radius * half_cos / half_sin));
         let bisector = vu0 + vu1;
-        let center = ctrl + bisector.scale(hypot / bisector.length());
+        let center = ctrl + bisector.scale(radius / half_sin / bisector.length());
 
+        // The arc turns the same way that the path turns at `ctrl`.
+        let direction = if (-v0).det(v1) > 0.0 { ArcDirection::CW } else { ArcDirection::CCW };
         let transform = Transform2F::from_scale(Vector2F::splat(radius)).translate(center);
-
-        let chord = LineSegment2F::new(vu0.yx().scale_xy(Vector2F::new(-1.0, 1.0)),
-                                      vu1.yx().scale_xy(Vector2F::new(1.0, -1.0)));
-
-        // FIXME(pcwalton): Is clockwise direction correct?
-        self.current_contour.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
+        let inverse_radius = 1.0 / radius;
+        let chord = LineSegment2F::new((start - center).scale(inverse_radius),
+                                       (end - center).scale(inverse_radius));
+        self.current_contour.push_arc_from_unit_chord(&transform, chord, direction);
     }
 
     pub fn rect(&mut self, rect: RectF) {
@@ -394,20 +1015,40 @@ impl Path2D {
         self.current_contour.close();
     }
 
+    /// Adds a closed subpath for `rect` with rounded corners, like HTML canvas `roundRect()`.
+    ///
+    /// `radii` are the horizontal and vertical radii of the upper left, upper right, lower right,
+    /// and lower left corners, in that order. Radii that overlap are scaled down, as
+    /// `RoundedRect::new()` describes.
+    pub fn round_rect(&mut self, rect: RectF, radii: [Vector2F; 4]) {
+        self.flush_current_contour();
+        let outline = Outline::from_rounded_rect(rect, radii);
+        self.current_contour = outline.contours()[0].clone();
+    }
+
+    /// Adds an arc of the ellipse with the given center, radii, and rotation, as in HTML canvas.
+    /// If the path has a current point, a line connects it to the start of the arc.
+    ///
+    /// The arc runs from `start_angle` to `end_angle` in `direction`, covering the entire ellipse
+    /// if the angles are a full turn or more apart in that direction. Negative radii, which HTML
+    /// canvas rejects with an exception, are ignored.
     pub fn ellipse(&mut self,
                    center: Vector2F,
                    axes: Vector2F,
                    rotation: f32,
                    start_angle: f32,
-                   end_angle: f32) {
-        self.flush_current_contour();
-
-        let transform = Transform2F::from_scale(axes).rotate(rotation).translate(center);
-        self.current_contour.push_arc(&transform, start_angle, end_angle, ArcDirection::CW);
-
-        if end_angle - start_angle >= 2.0 * PI {
-            self.current_contour.close();
+                   end_angle: f32,
+                   direction: ArcDirection) {
+        if !(axes.x() >= 0.0 && axes.y() >= 0.0) {
+            return;
         }
+
+        self.current_contour.push_ellipse_arc(center,
+                                              axes,
+                                              rotation,
+                                              start_angle,
+                                              end_angle,
+                                              direction);
     }
 
     /// Add other path to this path.
@@ -433,7 +1074,29 @@ impl Path2D {
         self
     }
     
-    fn into_outline(mut self) -> Outline {
+    /// Returns true if `point` lies inside this path when filled with `fill_rule`. Open subpaths
+    /// are treated as closed.
+    ///
+    /// Unlike `CanvasRenderingContext2D::is_point_in_path()`, no transform is applied, so `point`
+    /// is in the path's own coordinates.
+    #[inline]
+    pub fn is_point_in_path(&self, point: Vector2F, fill_rule: FillRule) -> bool {
+        let winding_number = self.outline.winding_number(point) +
+            self.current_contour.winding_number(point);
+        fill_rule.is_inside(winding_number)
+    }
+
+    /// Returns true if `point`, in the path's own coordinates, would be painted by stroking this
+    /// path with `style`.
+    pub fn is_point_in_stroke(&self, point: Vector2F, style: &StrokeStyle) -> bool {
+        let outline = self.clone().into_outline();
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, *style);
+        stroke_to_fill.offset();
+        stroke_to_fill.into_outline().contains_point(point, FillRule::Winding)
+    }
+
+    /// Returns the outline of the path, with the current subpath ended.
+    pub fn into_outline(mut self) -> Outline {
         self.flush_current_contour();
         self.outline
     }
@@ -449,22 +1112,268 @@ impl Path2D {
 pub enum FillStyle {
     Color(ColorU),
     Gradient(Gradient),
+    Pattern(CanvasPattern),
 }
 
 impl FillStyle {
-    fn into_paint(self) -> Paint {
-        match self {
+    /// Returns the paint that this fill style draws with, in user space.
+    pub fn to_paint(&self) -> Paint {
+        match *self {
             FillStyle::Color(color) => Paint::Color(color),
-            FillStyle::Gradient(gradient) => Paint::Gradient(gradient),
+            FillStyle::Gradient(ref gradient) => Paint::Gradient(gradient.clone()),
+            FillStyle::Pattern(ref pattern) => {
+                Paint::Pattern(Pattern::new(pattern.image.clone(), pattern.transform))
+            }
+        }
+    }
+}
+
+/// An image fill style, created with `create_pattern()`.
+#[derive(Clone)]
+pub struct CanvasPattern {
+    image: Image,
+    transform: Transform2F,
+    repetition: PatternRepetition,
+}
+
+impl CanvasPattern {
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    #[inline]
+    pub fn repetition(&self) -> PatternRepetition {
+        self.repetition
+    }
+
+    /// Sets the transform from the pixels of the image to user space. The current transform of
+    /// the context is applied on top of it when the pattern is drawn.
+    #[inline]
+    pub fn set_transform(&mut self, transform: &Transform2F) {
+        self.transform = *transform;
+    }
+}
+
+/// How a pattern repeats its image, like the `repetition` argument of `createPattern()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PatternRepetition {
+    Repeat,
+    RepeatX,
+    RepeatY,
+    NoRepeat,
+}
+
+impl PatternRepetition {
+    #[inline]
+    fn repeats_x(self) -> bool {
+        match self {
+            PatternRepetition::Repeat | PatternRepetition::RepeatX => true,
+            PatternRepetition::RepeatY | PatternRepetition::NoRepeat => false,
+        }
+    }
+
+    #[inline]
+    fn repeats_y(self) -> bool {
+        match self {
+            PatternRepetition::Repeat | PatternRepetition::RepeatY => true,
+            PatternRepetition::RepeatX | PatternRepetition::NoRepeat => false,
+        }
+    }
+}
+
+impl Default for PatternRepetition {
+    #[inline]
+    fn default() -> PatternRepetition {
+        PatternRepetition::Repeat
+    }
+}
+
+/// Something that can be drawn with `draw_image()`.
+#[derive(Clone)]
+pub enum CanvasImageSource<'a> {
+    Image(Image),
+    /// Another canvas, whose paths are copied as vectors.
+    Canvas(&'a CanvasRenderingContext2D),
+}
+
+impl<'a> CanvasImageSource<'a> {
+    /// The size of the image, or of the view box of the canvas.
+    pub fn size(&self) -> Vector2F {
+        match *self {
+            CanvasImageSource::Image(ref image) => image.size().to_f32(),
+            CanvasImageSource::Canvas(canvas) => canvas.scene.view_box().size(),
         }
     }
 }
 
+impl<'a> From<Image> for CanvasImageSource<'a> {
+    #[inline]
+    fn from(image: Image) -> CanvasImageSource<'a> {
+        CanvasImageSource::Image(image)
+    }
+}
+
+impl<'a> From<&'a CanvasRenderingContext2D> for CanvasImageSource<'a> {
+    #[inline]
+    fn from(canvas: &'a CanvasRenderingContext2D) -> CanvasImageSource<'a> {
+        CanvasImageSource::Canvas(canvas)
+    }
+}
+
+/// Where to draw an image: either a point, at which the image is drawn at its natural size, or a
+/// rectangle that the image is stretched to fill.
+pub trait CanvasImageDestLocation {
+    fn to_rect(&self, source_size: Vector2F) -> RectF;
+}
+
+impl CanvasImageDestLocation for RectF {
+    #[inline]
+    fn to_rect(&self, _: Vector2F) -> RectF {
+        *self
+    }
+}
+
+impl CanvasImageDestLocation for Vector2F {
+    #[inline]
+    fn to_rect(&self, source_size: Vector2F) -> RectF {
+        RectF::new(*self, source_size)
+    }
+}
+
+/// Where text is drawn relative to the point given to `fill_text()` and `stroke_text()`.
+///
+/// `Start` and `End` are the left and right ends of the text in left-to-right text, and the
+/// other way around in right-to-left text.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TextAlign {
     Left,
     Right,
     Center,
+    Start,
+    End,
+}
+
+/// The baseline of text that sits at the point given to `fill_text()` and `stroke_text()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextBaseline {
+    /// The top of the em square.
+    Top,
+    /// The hanging baseline of the font.
+    Hanging,
+    /// The middle of the em square.
+    Middle,
+    Alphabetic,
+    /// The bottom of the ideographic em box of the font.
+    Ideographic,
+    /// The bottom of the em square.
+    Bottom,
+}
+
+/// The direction of text, which decides the order of its directional runs and which end of it
+/// `TextAlign::Start` and `TextAlign::End` refer to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompositeOperation {
+    SourceOver,
+    SourceIn,
+    SourceOut,
+    SourceAtop,
+    DestinationOver,
+    DestinationIn,
+    DestinationOut,
+    DestinationAtop,
+    Lighter,
+    Copy,
+    Xor,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl CompositeOperation {
+    fn to_blend_mode(self) -> BlendMode {
+        match self {
+            CompositeOperation::SourceOver => BlendMode::SrcOver,
+            CompositeOperation::SourceIn => BlendMode::SrcIn,
+            CompositeOperation::SourceOut => BlendMode::SrcOut,
+            CompositeOperation::SourceAtop => BlendMode::SrcAtop,
+            CompositeOperation::DestinationOver => BlendMode::DestOver,
+            CompositeOperation::DestinationIn => BlendMode::DestIn,
+            CompositeOperation::DestinationOut => BlendMode::DestOut,
+            CompositeOperation::DestinationAtop => BlendMode::DestAtop,
+            CompositeOperation::Lighter => BlendMode::Lighter,
+            CompositeOperation::Copy => BlendMode::Copy,
+            CompositeOperation::Xor => BlendMode::Xor,
+            CompositeOperation::Multiply => BlendMode::Multiply,
+            CompositeOperation::Screen => BlendMode::Screen,
+            CompositeOperation::Overlay => BlendMode::Overlay,
+            CompositeOperation::Darken => BlendMode::Darken,
+            CompositeOperation::Lighten => BlendMode::Lighten,
+            CompositeOperation::ColorDodge => BlendMode::ColorDodge,
+            CompositeOperation::ColorBurn => BlendMode::ColorBurn,
+            CompositeOperation::HardLight => BlendMode::HardLight,
+            CompositeOperation::SoftLight => BlendMode::SoftLight,
+            CompositeOperation::Difference => BlendMode::Difference,
+            CompositeOperation::Exclusion => BlendMode::Exclusion,
+            CompositeOperation::Hue => BlendMode::Hue,
+            CompositeOperation::Saturation => BlendMode::Saturation,
+            CompositeOperation::Color => BlendMode::Color,
+            CompositeOperation::Luminosity => BlendMode::Luminosity,
+        }
+    }
+}
+
+/// Why `CanvasRenderingContext2D::get_image_data()` couldn't read back the canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageDataError {
+    /// The scene couldn't be built.
+    SceneBuild(SceneBuildError),
+    /// The destination framebuffer has a pixel format that can't be converted to RGBA8.
+    UnsupportedPixelFormat,
+}
+
+impl Display for ImageDataError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            ImageDataError::SceneBuild(ref error) => error.fmt(formatter),
+            ImageDataError::UnsupportedPixelFormat => {
+                write!(formatter, "the destination framebuffer's pixel format isn't supported")
+            }
+        }
+    }
+}
+
+impl Error for ImageDataError {}
+
+impl From<SceneBuildError> for ImageDataError {
+    #[inline]
+    fn from(error: SceneBuildError) -> ImageDataError {
+        ImageDataError::SceneBuild(error)
+    }
+}
+
+// Converts a color with floating-point channels to 8 bits per channel.
+fn color_from_floats(r: f32, g: f32, b: f32, a: f32) -> ColorU {
+    let clamp = |value: f32| f32::min(f32::max(value, 0.0), 1.0);
+    ColorF::new(clamp(r), clamp(g), clamp(b), clamp(a)).to_u8()
 }
 
 // We duplicate `pathfinder_content::stroke::LineJoin` here because the HTML canvas API treats the
@@ -479,3 +1388,540 @@ pub enum LineJoin {
     Round,
 }
 
+// Returns a pattern that draws `copy_count` copies of the image of `pattern`, starting from
+// `first_copy`, with a single image.
+//
+// The renderer can't draw images larger than its paint texture, so images of many copies are
+// downsampled to fit.
+fn repeat_pattern(pattern: &Pattern, first_copy: Vector2I, copy_count: Vector2I) -> Pattern {
+    let image_size = pattern.image.size();
+    let (image_width, image_height) = (image_size.x() as i64, image_size.y() as i64);
+    let full_width = image_width * copy_count.x() as i64;
+    let full_height = image_height * copy_count.y() as i64;
+    let max_length = MAX_REPEATED_PATTERN_LENGTH as i64;
+    let step = i64::max(1, (i64::max(full_width, full_height) + max_length - 1) / max_length);
+    let size = Vector2I::new(((full_width + step - 1) / step) as i32,
+                             ((full_height + step - 1) / step) as i32);
+
+    let image_pixels = pattern.image.pixels();
+    let mut pixels = Vec::with_capacity(size.x() as usize * size.y() as usize);
+    for y in 0..(size.y() as i64) {
+        let row_start = (y * step % image_height * image_width) as usize;
+        for x in 0..(size.x() as i64) {
+            pixels.push(image_pixels[row_start + (x * step % image_width) as usize]);
+        }
+    }
+
+    let transform = pattern.transform *
+        Transform2F::from_translation(first_copy.to_f32() * image_size.to_f32()) *
+        Transform2F::from_uniform_scale(step as f32);
+    Pattern::new(Image::new(size, Arc::new(pixels)), transform)
+}
+
+// The corners of `rect` under `transform`, wound so that `Outline::clip_against_polygon()` keeps
+// what's inside.
+fn transformed_rect_polygon(transform: &Transform2F, rect: RectF) -> [Vector2F; 4] {
+    let mut polygon = [
+        *transform * rect.origin(),
+        *transform * rect.upper_right(),
+        *transform * rect.lower_right(),
+        *transform * rect.lower_left(),
+    ];
+    if transform.matrix.det() < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CanvasFontContext, CanvasRenderingContext2D, CompositeOperation, FillRule};
+    use crate::{FillStyle, MAX_REPEATED_PATTERN_LENGTH, Path2D, PatternRepetition};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::pattern::{Image, Pattern};
+    use pathfinder_content::stroke::{LineCap, LineJoin, StrokeStyle};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use pathfinder_renderer::paint::Paint;
+    use std::sync::Arc;
+
+    fn canvas() -> CanvasRenderingContext2D {
+        CanvasRenderingContext2D::new(CanvasFontContext::from_system_source(),
+                                      Vector2F::splat(100.0))
+    }
+
+    // A square with a square hole wound the same way.
+    fn nested_squares() -> Path2D {
+        let mut path = Path2D::new();
+        path.rect(RectF::new(Vector2F::splat(10.0), Vector2F::splat(40.0)));
+        path.rect(RectF::new(Vector2F::splat(20.0), Vector2F::splat(20.0)));
+        path
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> RectF {
+        RectF::new(Vector2F::new(x, y), Vector2F::new(width, height))
+    }
+
+    // The paints and bounds of the paths that `canvas` drew, in drawing order.
+    fn drawn_paths(canvas: CanvasRenderingContext2D) -> Vec<(Paint, RectF)> {
+        canvas.into_scene().paths().map(|(paint, outline, _, _)| {
+            (paint.clone(), outline.bounds())
+        }).collect()
+    }
+
+    fn pattern_of(paint: &Paint) -> &Pattern {
+        match *paint {
+            Paint::Pattern(ref pattern) => pattern,
+            _ => panic!("expected a pattern paint"),
+        }
+    }
+
+    // A 10x10 image whose pixels are all different.
+    fn numbered_image() -> Image {
+        let pixels = (0..100).map(|index| ColorU { r: index as u8, g: 0, b: 0, a: 255 }).collect();
+        Image::new(Vector2I::splat(10), Arc::new(pixels))
+    }
+
+    // Fills the whole canvas with a repeating pattern of `numbered_image()` and returns the
+    // patterns of the resulting paths.
+    fn fill_with_pattern(pattern_transform: Transform2F, operation: CompositeOperation)
+                         -> Vec<Pattern> {
+        let mut canvas = canvas();
+        let mut pattern = canvas.create_pattern(numbered_image(), PatternRepetition::Repeat);
+        pattern.set_transform(&pattern_transform);
+        canvas.set_fill_style(FillStyle::Pattern(pattern));
+        canvas.set_global_composite_operation(operation);
+        canvas.fill_rect(RectF::new(Vector2F::default(), Vector2F::splat(100.0)));
+
+        drawn_paths(canvas).iter().map(|(paint, _)| pattern_of(paint).clone()).collect()
+    }
+
+    #[test]
+    fn test_path_is_point_in_path() {
+        let path = nested_squares();
+        let (inner, outer, outside) = (Vector2F::splat(30.0),
+                                       Vector2F::splat(15.0),
+                                       Vector2F::splat(60.0));
+        assert!(path.is_point_in_path(inner, FillRule::Winding));
+        assert!(!path.is_point_in_path(inner, FillRule::EvenOdd));
+        assert!(path.is_point_in_path(outer, FillRule::EvenOdd));
+        assert!(!path.is_point_in_path(outside, FillRule::Winding));
+
+        // The context method agrees under the identity transform.
+        let canvas = canvas();
+        for &point in &[inner, outer, outside] {
+            for &fill_rule in &[FillRule::Winding, FillRule::EvenOdd] {
+                assert_eq!(canvas.is_point_in_path(&path, point, fill_rule),
+                           path.is_point_in_path(point, fill_rule));
+            }
+        }
+    }
+
+    #[test]
+    fn test_path_is_point_in_stroke() {
+        let mut path = Path2D::new();
+        path.move_to(Vector2F::new(10.0, 50.0));
+        path.line_to(Vector2F::new(90.0, 50.0));
+        let style = StrokeStyle {
+            line_width: 10.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter(10.0),
+        };
+        assert!(path.is_point_in_stroke(Vector2F::new(50.0, 54.0), &style));
+        assert!(!path.is_point_in_stroke(Vector2F::new(50.0, 56.0), &style));
+        assert!(!path.is_point_in_stroke(Vector2F::new(5.0, 50.0), &style));
+
+        let style = StrokeStyle { line_cap: LineCap::Square, ..style };
+        assert!(path.is_point_in_stroke(Vector2F::new(6.0, 50.0), &style));
+
+        let mut canvas = canvas();
+        canvas.set_line_width(10.0);
+        for &y in &[54.0, 56.0] {
+            let point = Vector2F::new(50.0, y);
+            assert_eq!(canvas.is_point_in_stroke(&path, point),
+                       path.is_point_in_stroke(point, &style));
+        }
+    }
+
+    #[test]
+    fn test_repeating_pattern_copies() {
+        // Source-over draws one path per copy of the image.
+        let patterns = fill_with_pattern(Transform2F::default(), CompositeOperation::SourceOver);
+        assert!(patterns.len() >= 100);
+        assert!(patterns.iter().all(|pattern| pattern.image.size() == Vector2I::splat(10)));
+
+        // A destructive operation would clear the other copies, so it draws a single path with
+        // all of them in one image.
+        let patterns = fill_with_pattern(Transform2F::default(), CompositeOperation::Copy);
+        assert_eq!(patterns.len(), 1);
+        let image = &patterns[0].image;
+        let source = numbered_image();
+        let size = image.size();
+        assert!(size.x() >= 100 && size.y() >= 100);
+        for y in 0..size.y() {
+            for x in 0..size.x() {
+                assert_eq!(image.pixels()[(y * size.x() + x) as usize],
+                           source.pixels()[(y % 10 * 10 + x % 10) as usize]);
+            }
+        }
+
+        // The image covers the whole canvas in user space.
+        let bounds = patterns[0].transform * RectF::new(Vector2F::default(), size.to_f32());
+        assert!(bounds.contains_rect(RectF::new(Vector2F::default(), Vector2F::splat(100.0))));
+    }
+
+    #[test]
+    fn test_repeating_pattern_is_downsampled_to_fit() {
+        // 0.5-pixel copies take a 2000x2000 image to cover the canvas.
+        let transform = Transform2F::from_scale(Vector2F::splat(0.05));
+        let patterns = fill_with_pattern(transform, CompositeOperation::SourceIn);
+        assert_eq!(patterns.len(), 1);
+
+        let image = &patterns[0].image;
+        let size = image.size();
+        assert!(size.x() <= MAX_REPEATED_PATTERN_LENGTH && size.y() <= MAX_REPEATED_PATTERN_LENGTH);
+        assert_eq!(patterns[0].transform.matrix.m11(), 0.1);
+
+        let source = numbered_image();
+        for y in 0..size.y() {
+            for x in 0..size.x() {
+                assert_eq!(image.pixels()[(y * size.x() + x) as usize],
+                           source.pixels()[(y * 2 % 10 * 10 + x * 2 % 10) as usize]);
+            }
+        }
+
+        let bounds = patterns[0].transform * RectF::new(Vector2F::default(), size.to_f32());
+        assert!(bounds.contains_rect(RectF::new(Vector2F::default(), Vector2F::splat(100.0))));
+    }
+
+    #[test]
+    fn test_draw_image() {
+        // At a point, the image is drawn at its natural size...
+        let mut at_point = canvas();
+        at_point.draw_image(numbered_image(), Vector2F::new(5.0, 15.0));
+        let paths = drawn_paths(at_point);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].1, rect(5.0, 15.0, 10.0, 10.0));
+        let pattern = pattern_of(&paths[0].0);
+        assert_eq!(pattern.transform, Transform2F::from_translation(Vector2F::new(5.0, 15.0)));
+        assert_eq!(pattern.image.pixels(), numbered_image().pixels());
+
+        // ...and in a rectangle, stretched to fill it.
+        let mut stretched = canvas();
+        stretched.draw_image(numbered_image(), rect(0.0, 0.0, 40.0, 20.0));
+        let paths = drawn_paths(stretched);
+        assert_eq!(paths[0].1, rect(0.0, 0.0, 40.0, 20.0));
+        assert_eq!(pattern_of(&paths[0].0).transform,
+                   Transform2F::from_scale(Vector2F::new(4.0, 2.0)));
+    }
+
+    #[test]
+    fn test_draw_subimage() {
+        // The part of the source rectangle outside the image isn't drawn.
+        let mut clipped = canvas();
+        clipped.draw_subimage(numbered_image(),
+                              rect(5.0, 0.0, 10.0, 10.0),
+                              rect(20.0, 20.0, 40.0, 40.0));
+        let paths = drawn_paths(clipped);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].1, rect(20.0, 20.0, 20.0, 40.0));
+        let transform = pattern_of(&paths[0].0).transform;
+        assert_eq!(transform * Vector2F::new(5.0, 0.0), Vector2F::new(20.0, 20.0));
+        assert_eq!(transform * Vector2F::new(10.0, 10.0), Vector2F::new(40.0, 60.0));
+
+        // Nothing is drawn from outside the image, or into an empty rectangle.
+        let mut empty = canvas();
+        let image_rect = rect(0.0, 0.0, 10.0, 10.0);
+        empty.draw_subimage(numbered_image(), rect(20.0, 0.0, 10.0, 10.0), image_rect);
+        empty.draw_subimage(numbered_image(), image_rect, rect(0.0, 0.0, 0.0, 10.0));
+        assert!(drawn_paths(empty).is_empty());
+    }
+
+    #[test]
+    fn test_draw_canvas() {
+        let mut source = canvas();
+        source.set_fill_style(FillStyle::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        source.fill_rect(rect(10.0, 10.0, 20.0, 20.0));
+        source.fill_rect(rect(60.0, 60.0, 10.0, 10.0));
+
+        // The paths of the other canvas are copied, scaled, and clipped to the source rectangle.
+        let mut canvas = canvas();
+        canvas.draw_subimage(&source, rect(0.0, 0.0, 20.0, 20.0), rect(50.0, 50.0, 40.0, 40.0));
+        let paths = drawn_paths(canvas);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].1, rect(70.0, 70.0, 20.0, 20.0));
+        assert_eq!(paths[0].0, Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+    }
+
+    #[test]
+    fn test_put_image_data() {
+        // The transform, global alpha, and shadow are all ignored.
+        let mut canvas = canvas();
+        canvas.set_current_transform(&Transform2F::from_scale(Vector2F::splat(2.0)));
+        canvas.set_global_alpha(0.5);
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 0, a: 255 });
+        canvas.set_shadow_offset(Vector2F::splat(5.0));
+        canvas.put_image_data(&numbered_image(), Vector2I::new(30, 40));
+
+        let paths = drawn_paths(canvas);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].1, rect(30.0, 40.0, 10.0, 10.0));
+        let pattern = pattern_of(&paths[0].0);
+        assert_eq!(pattern.transform, Transform2F::from_translation(Vector2F::new(30.0, 40.0)));
+        assert_eq!(pattern.image.pixels(), numbered_image().pixels());
+    }
+
+    #[test]
+    fn test_shadow_offset() {
+        let red = ColorU { r: 255, g: 0, b: 0, a: 255 };
+        let shadow_color = ColorU { r: 0, g: 0, b: 0, a: 128 };
+        let draw = |canvas: &mut CanvasRenderingContext2D| {
+            canvas.set_fill_style(FillStyle::Color(red));
+            canvas.set_shadow_color(shadow_color);
+            canvas.set_shadow_offset(Vector2F::new(5.0, 10.0));
+            canvas.fill_rect(rect(20.0, 20.0, 30.0, 30.0));
+        };
+
+        // The shadow is drawn under the path, offset from it...
+        let mut offset = canvas();
+        draw(&mut offset);
+        let paths = drawn_paths(offset);
+        assert_eq!(paths, vec![(Paint::Color(shadow_color), rect(25.0, 30.0, 30.0, 30.0)),
+                               (Paint::Color(red), rect(20.0, 20.0, 30.0, 30.0))]);
+
+        // ...by an offset that the current transform doesn't affect but the pixel ratio does...
+        let mut scaled = canvas();
+        scaled.set_current_transform(&Transform2F::from_scale(Vector2F::splat(2.0)));
+        draw(&mut scaled);
+        assert_eq!(drawn_paths(scaled)[0].1, rect(45.0, 50.0, 60.0, 60.0));
+        let mut hidpi = CanvasRenderingContext2D::new_with_pixel_ratio(
+            CanvasFontContext::from_system_source(),
+            Vector2F::splat(50.0),
+            2.0);
+        draw(&mut hidpi);
+        assert_eq!(drawn_paths(hidpi)[0].1, rect(50.0, 60.0, 60.0, 60.0));
+
+        // ...with the global alpha applied.
+        let mut translucent = canvas();
+        translucent.set_global_alpha(0.5);
+        draw(&mut translucent);
+        assert_eq!(drawn_paths(translucent)[0].0,
+                   Paint::Color(ColorU { r: 0, g: 0, b: 0, a: 64 }));
+
+        // Transparent shadows, and shadows neither offset nor blurred, aren't drawn.
+        let mut transparent = canvas();
+        draw(&mut transparent);
+        transparent.set_shadow_color(ColorU::transparent_black());
+        transparent.fill_rect(rect(20.0, 20.0, 30.0, 30.0));
+        transparent.set_shadow_color(shadow_color);
+        transparent.set_shadow_offset(Vector2F::default());
+        transparent.fill_rect(rect(20.0, 20.0, 30.0, 30.0));
+        assert_eq!(drawn_paths(transparent).len(), 4);
+    }
+
+    #[test]
+    fn test_shadow_blur() {
+        let mut canvas = canvas();
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 255, a: 255 });
+        canvas.set_shadow_blur(8.0);
+        canvas.fill_rect(rect(20.0, 20.0, 40.0, 40.0));
+
+        // Invalid blurs and offsets are ignored.
+        canvas.set_shadow_blur(-1.0);
+        canvas.set_shadow_blur(f32::NAN);
+        canvas.set_shadow_offset(Vector2F::new(f32::INFINITY, 0.0));
+        assert_eq!(canvas.shadow_blur(), 8.0);
+        assert_eq!(canvas.shadow_offset(), Vector2F::default());
+
+        // The blurred shadow is an image that extends past the path.
+        let paths = drawn_paths(canvas);
+        assert_eq!(paths.len(), 2);
+        let bounds = paths[0].1;
+        assert!(bounds.contains_rect(rect(12.0, 12.0, 56.0, 56.0)), "{:?}", bounds);
+        let pattern = pattern_of(&paths[0].0);
+        assert_eq!(bounds, pattern.transform * RectF::new(Vector2F::default(),
+                                                          pattern.image.size().to_f32()));
+
+        // It's opaque in the middle, half transparent at the edges of the path, and fades out.
+        let alpha_at = |point: Vector2F| {
+            let position = (pattern.transform.inverse() * point).floor().to_i32();
+            let size = pattern.image.size();
+            let pixel = pattern.image.pixels()[(position.y() * size.x() + position.x()) as usize];
+            assert_eq!((pixel.r, pixel.g, pixel.b), (0, 0, 255));
+            pixel.a
+        };
+        assert_eq!(alpha_at(Vector2F::splat(40.0)), 255);
+        let edge_alpha = alpha_at(Vector2F::new(20.0, 40.0));
+        assert!(edge_alpha > 96 && edge_alpha < 160, "{}", edge_alpha);
+        assert!(alpha_at(Vector2F::new(14.0, 40.0)) < edge_alpha / 2);
+        assert_eq!(alpha_at(bounds.origin() + Vector2F::splat(0.5)), 0);
+    }
+
+    #[test]
+    fn test_global_composite_operation() {
+        let cases = [
+            (CompositeOperation::SourceOver, BlendMode::SrcOver),
+            (CompositeOperation::SourceIn, BlendMode::SrcIn),
+            (CompositeOperation::DestinationOut, BlendMode::DestOut),
+            (CompositeOperation::Copy, BlendMode::Copy),
+            (CompositeOperation::Xor, BlendMode::Xor),
+            (CompositeOperation::Multiply, BlendMode::Multiply),
+            (CompositeOperation::Luminosity, BlendMode::Luminosity),
+        ];
+        let mut canvas = canvas();
+        for &(operation, _) in &cases {
+            canvas.set_global_composite_operation(operation);
+            canvas.stroke_rect(rect(10.0, 10.0, 20.0, 20.0));
+        }
+
+        // Saving and restoring the state restores the operation.
+        canvas.save();
+        canvas.set_global_composite_operation(CompositeOperation::Screen);
+        canvas.fill_rect(rect(10.0, 10.0, 20.0, 20.0));
+        canvas.restore();
+        assert_eq!(canvas.global_composite_operation(), CompositeOperation::Luminosity);
+
+        let scene = canvas.into_scene();
+        assert_eq!(scene.path_count(), cases.len() + 1);
+        for (index, &(_, blend_mode)) in cases.iter().enumerate() {
+            assert_eq!(scene.path(index).blend_mode(), blend_mode);
+        }
+        assert_eq!(scene.path(cases.len()).blend_mode(), BlendMode::Screen);
+    }
+
+    #[test]
+    fn test_global_alpha() {
+        // The global alpha scales the alpha of each paint.
+        let mut canvas = canvas();
+        canvas.set_fill_style(FillStyle::Color(ColorU { r: 255, g: 0, b: 0, a: 200 }));
+        canvas.set_global_alpha(0.5);
+        canvas.fill_rect(rect(10.0, 10.0, 20.0, 20.0));
+        canvas.draw_image(numbered_image(), Vector2F::default());
+        canvas.set_global_alpha(1.0);
+        canvas.fill_rect(rect(10.0, 10.0, 20.0, 20.0));
+
+        let paths = drawn_paths(canvas);
+        assert_eq!(paths[0].0, Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 100 }));
+        let pixels = pattern_of(&paths[1].0).image.pixels();
+        assert!(pixels.iter().all(|pixel| pixel.a == 128), "{:?}", pixels[0]);
+        assert_eq!(paths[2].0, Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 200 }));
+    }
+
+    #[test]
+    fn test_line_dash() {
+        let mut canvas = canvas();
+        assert!(canvas.line_dash().is_empty());
+
+        // Odd numbers of lengths are repeated, and invalid patterns and offsets are ignored.
+        canvas.set_line_dash(vec![5.0, 10.0, 15.0]);
+        assert_eq!(canvas.line_dash(), &[5.0, 10.0, 15.0, 5.0, 10.0, 15.0]);
+        canvas.set_line_dash(vec![10.0, -1.0]);
+        canvas.set_line_dash(vec![f32::NAN]);
+        assert_eq!(canvas.line_dash().len(), 6);
+        canvas.set_line_dash_offset(5.0);
+        canvas.set_line_dash_offset(f32::INFINITY);
+        assert_eq!(canvas.line_dash_offset(), 5.0);
+    }
+
+    #[test]
+    fn test_dashed_strokes() {
+        // Returns the number of contours and the bounds of a horizontal line from x = 0 to
+        // x = 100, stroked with `dashes` starting at `offset`.
+        let stroke = |dashes: Vec<f32>, offset: f32| {
+            let mut canvas = canvas();
+            canvas.set_line_width(2.0);
+            canvas.set_line_dash(dashes);
+            canvas.set_line_dash_offset(offset);
+            let mut path = Path2D::new();
+            path.move_to(Vector2F::new(0.0, 50.0));
+            path.line_to(Vector2F::new(100.0, 50.0));
+            canvas.stroke_path(path);
+
+            let scene = canvas.into_scene();
+            assert_eq!(scene.path_count(), 1);
+            let outline = scene.path(0).outline();
+            (outline.contours().len(), outline.bounds())
+        };
+
+        assert_eq!(stroke(vec![], 0.0), (1, rect(0.0, 49.0, 100.0, 2.0)));
+        assert_eq!(stroke(vec![10.0, 10.0], 0.0), (5, rect(0.0, 49.0, 90.0, 2.0)));
+        assert_eq!(stroke(vec![10.0, 10.0], 5.0), (6, rect(0.0, 49.0, 100.0, 2.0)));
+        assert_eq!(stroke(vec![10.0], 15.0), (5, rect(5.0, 49.0, 90.0, 2.0)));
+    }
+
+    #[test]
+    fn test_clip_path() {
+        let rect_path = |rect: RectF| {
+            let mut path = Path2D::new();
+            path.rect(rect);
+            path
+        };
+
+        let mut canvas = canvas();
+        canvas.fill_rect(rect(0.0, 0.0, 10.0, 10.0));
+        canvas.save();
+        canvas.clip_path(rect_path(rect(10.0, 10.0, 50.0, 50.0)), FillRule::EvenOdd);
+        canvas.fill_rect(rect(0.0, 0.0, 100.0, 100.0));
+
+        // Clipping again intersects the regions, under the current transform. Shadows are
+        // clipped too.
+        canvas.set_current_transform(&Transform2F::from_scale(Vector2F::splat(2.0)));
+        canvas.clip_path(rect_path(rect(15.0, 15.0, 50.0, 50.0)), FillRule::Winding);
+        canvas.set_shadow_color(ColorU { r: 0, g: 0, b: 0, a: 255 });
+        canvas.set_shadow_offset(Vector2F::splat(5.0));
+        canvas.fill_rect(rect(0.0, 0.0, 50.0, 50.0));
+
+        // Restoring the state removes the clip.
+        canvas.restore();
+        canvas.fill_rect(rect(0.0, 0.0, 10.0, 10.0));
+
+        let scene = canvas.into_scene();
+        assert_eq!(scene.path_count(), 5);
+        assert_eq!(scene.path(0).clip_path(), None);
+        assert_eq!(scene.path(4).clip_path(), None);
+
+        let outer_clip = scene.clip_path(scene.path(1).clip_path().unwrap());
+        assert_eq!(outer_clip.outline().bounds(), rect(10.0, 10.0, 50.0, 50.0));
+        assert_eq!(outer_clip.fill_rule(), FillRule::EvenOdd);
+
+        let inner_clip_id = scene.path(2).clip_path().unwrap();
+        assert_eq!(scene.path(3).clip_path(), Some(inner_clip_id));
+        let inner_clip_bounds = scene.clip_path(inner_clip_id).outline().bounds();
+        assert!((inner_clip_bounds.origin() - Vector2F::splat(30.0)).length() < 0.01 &&
+                (inner_clip_bounds.lower_right() - Vector2F::splat(60.0)).length() < 0.01,
+                "{:?}",
+                inner_clip_bounds);
+    }
+
+    #[test]
+    fn test_draw_image_nine_slice() {
+        let image = Image::new(Vector2I::splat(30),
+                               Arc::new(vec![ColorU { r: 0, g: 0, b: 0, a: 255 }; 900]));
+        let nine_slice = |center_location: RectF, dest_location: RectF| -> Vec<RectF> {
+            let mut canvas = canvas();
+            canvas.draw_image_nine_slice(image.clone(), center_location, dest_location);
+            drawn_paths(canvas).into_iter().map(|(_, bounds)| bounds).collect()
+        };
+
+        // The corners keep their size, and the edges and center stretch.
+        let center = rect(10.0, 10.0, 10.0, 10.0);
+        assert_eq!(nine_slice(center, rect(0.0, 0.0, 100.0, 60.0)), vec![
+            rect(0.0, 0.0, 10.0, 10.0), rect(10.0, 0.0, 80.0, 10.0), rect(90.0, 0.0, 10.0, 10.0),
+            rect(0.0, 10.0, 10.0, 40.0), rect(10.0, 10.0, 80.0, 40.0), rect(90.0, 10.0, 10.0, 40.0),
+            rect(0.0, 50.0, 10.0, 10.0), rect(10.0, 50.0, 80.0, 10.0), rect(90.0, 50.0, 10.0, 10.0),
+        ]);
+
+        // Corners that don't fit are all scaled down, leaving the middle column empty.
+        assert_eq!(nine_slice(center, rect(0.0, 0.0, 10.0, 40.0)), vec![
+            rect(0.0, 0.0, 5.0, 5.0), rect(5.0, 0.0, 5.0, 5.0),
+            rect(0.0, 5.0, 5.0, 30.0), rect(5.0, 5.0, 5.0, 30.0),
+            rect(0.0, 35.0, 5.0, 5.0), rect(5.0, 35.0, 5.0, 5.0),
+        ]);
+
+        // A center covering the whole image stretches all of it.
+        assert_eq!(nine_slice(rect(0.0, 0.0, 30.0, 30.0), rect(0.0, 0.0, 50.0, 50.0)),
+                   vec![rect(0.0, 0.0, 50.0, 50.0)]);
+    }
+}
@@ -0,0 +1,59 @@
+// pathfinder/canvas/src/shadow.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Blurred shadows.
+//!
+//! The shadow is rasterized and blurred on the CPU, then drawn as an image.
+//!
+//! TODO: Blur on the GPU instead.
+
+use pathfinder_color::ColorU;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::Image;
+use pathfinder_content::raster;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::sync::Arc;
+
+/// Fills `outline`, which must be in device space, with `color` using `fill_rule` and blurs it as
+/// HTML canvas does for a shadow blur of `blur`.
+///
+/// Returns the image and the position of its upper left corner, or `None` if the shadow is
+/// invisible.
+pub(crate) fn render_blurred_shadow(outline: &Outline,
+                                    fill_rule: FillRule,
+                                    color: ColorU,
+                                    blur: f32)
+                                    -> Option<(Image, Vector2I)> {
+    let bounds = outline.bounds();
+    if bounds.is_empty() || color.is_fully_transparent() {
+        return None;
+    }
+
+    // HTML canvas specifies a Gaussian blur with a standard deviation of half the blur value.
+    let std_dev = Vector2F::splat(blur * 0.5);
+    let padding = raster::gaussian_blur_extent(std_dev) + Vector2I::splat(1);
+    let bounds = bounds.round_out().to_i32();
+    let rect = RectI::from_points(bounds.origin() - padding, bounds.lower_right() + padding);
+
+    let mut coverage = raster::rasterize_coverage(outline, rect, fill_rule);
+    let size = rect.size();
+    raster::gaussian_blur(&mut coverage, size, std_dev);
+
+    let pixels: Vec<ColorU> = coverage.into_iter().map(|coverage| {
+        ColorU { a: (color.a as f32 * coverage.min(1.0)).round() as u8, ..color }
+    }).collect();
+    let image = Image::new(size, Arc::new(pixels));
+    if image.is_fully_transparent() {
+        return None;
+    }
+    Some((image, rect.origin()))
+}
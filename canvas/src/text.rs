@@ -15,39 +15,98 @@ use std::sync::Arc;
 use std::iter;
 use font_kit::family_name::FamilyName;
 use font_kit::handle::Handle;
-use font_kit::hinting::HintingOptions;
 use font_kit::properties::Properties;
 use font_kit::source::{Source, SystemSource};
 use font_kit::sources::mem::MemSource;
 pub use skribo::{FontCollection, FontFamily, Layout, TextStyle};
-use pathfinder_text::{SceneExt, TextRenderMode};
+use pathfinder_text::baseline::FontBaselines;
+use pathfinder_text::bidi::TextDirection as BidiDirection;
+use pathfinder_text::{SceneExt, SkriboShaper, TextRenderMode};
+pub use pathfinder_text::{TextHinting, TextRenderOptions, TextShaper};
+pub use pathfinder_text::bidi::BidiShaper;
+pub use pathfinder_text::fallback::FontFallbackChain;
+pub use pathfinder_text::vertical::{VerticalShaper, VerticalSubstitution};
 pub use font_kit::loaders::default::Font;
-pub use crate::{CanvasRenderingContext2D, TextAlign};
+pub use crate::{CanvasRenderingContext2D, TextAlign, TextBaseline, TextDirection};
+
+// Where browsers put the hanging baseline when the font doesn't say, as a fraction of the ascent of
+// the em square.
+const HANGING_BASELINE_FRACTION: f32 = 0.8;
 
 impl CanvasRenderingContext2D {
     pub fn fill_text(&mut self, string: &str, position: Vector2F) {
-        let paint_id = self.scene.push_paint(&self.current_state.fill_paint);
+        let paint_id = self.scene.push_paint(&self.current_state.fill_style.to_paint());
         self.fill_or_stroke_text(string, position, paint_id, TextRenderMode::Fill);
     }
 
     pub fn stroke_text(&mut self, string: &str, position: Vector2F) {
-        let paint_id = self.scene.push_paint(&self.current_state.stroke_paint);
+        let paint_id = self.scene.push_paint(&self.current_state.stroke_style.to_paint());
         let render_mode = TextRenderMode::Stroke(self.current_state.resolve_stroke_style());
         self.fill_or_stroke_text(string, position, paint_id, render_mode);
     }
 
+    /// Measures `string` as `fill_text()` would draw it, relative to the alignment point and the
+    /// baseline given by the text baseline.
     pub fn measure_text(&self, string: &str) -> TextMetrics {
-        TextMetrics { width: self.layout_text(string).width() }
+        let layout = self.layout_text(string);
+        let width = layout.width();
+        let align_offset = self.align_offset(width);
+        let extents = LayoutExtents::new(&layout);
+        let baseline_height = extents.baseline_height(self.current_state.text_baseline);
+
+        let mut metrics = TextMetrics { width, ..TextMetrics::default() };
+        let mut ink_bounds: Option<(f32, f32, f32, f32)> = None;
+        for glyph in &layout.glyphs {
+            let font = &glyph.font.font;
+            let scale = layout.size / font.metrics().units_per_em as f32;
+
+            // Glyph bounds are in font units, with Y up.
+            let bounds = match font.typographic_bounds(glyph.glyph_id) {
+                Ok(bounds) if bounds.size.width > 0.0 && bounds.size.height > 0.0 => bounds,
+                _ => continue,
+            };
+            let (left, right) = (glyph.offset.x + bounds.min_x() * scale,
+                                 glyph.offset.x + bounds.max_x() * scale);
+            let (ascent, descent) = (bounds.max_y() * scale - glyph.offset.y,
+                                     glyph.offset.y - bounds.min_y() * scale);
+            ink_bounds = Some(match ink_bounds {
+                None => (left, right, ascent, descent),
+                Some((old_left, old_right, old_ascent, old_descent)) => {
+                    (f32::min(old_left, left),
+                     f32::max(old_right, right),
+                     f32::max(old_ascent, ascent),
+                     f32::max(old_descent, descent))
+                }
+            });
+        }
+
+        if let Some((left, right, ascent, descent)) = ink_bounds {
+            metrics.actual_bounding_box_left = align_offset - left;
+            metrics.actual_bounding_box_right = right - align_offset;
+            metrics.actual_bounding_box_ascent = ascent - baseline_height;
+            metrics.actual_bounding_box_descent = descent + baseline_height;
+        }
+
+        if !layout.glyphs.is_empty() {
+            metrics.font_bounding_box_ascent = extents.font_ascent - baseline_height;
+            metrics.font_bounding_box_descent = extents.font_descent + baseline_height;
+            metrics.em_height_ascent = extents.em_ascent - baseline_height;
+            metrics.em_height_descent = extents.em_descent + baseline_height;
+            metrics.hanging_baseline = extents.hanging - baseline_height;
+            metrics.alphabetic_baseline = -baseline_height;
+            metrics.ideographic_baseline = extents.ideographic - baseline_height;
+        }
+        metrics
     }
 
     pub fn fill_layout(&mut self, layout: &Layout, transform: Transform2F) {
-        let paint_id = self.scene.push_paint(&self.current_state.fill_paint);
-        drop(self.scene.push_layout(&layout,
-                                    &TextStyle { size: self.current_state.font_size },
-                                    &(transform * self.current_state.transform),
-                                    TextRenderMode::Fill,
-                                    HintingOptions::None,
-                                    paint_id));
+        let paint_id = self.scene.push_paint(&self.current_state.fill_style.to_paint());
+        drop(self.scene.push_layout_with_options(&layout,
+                                                 &TextStyle { size: self.current_state.font_size },
+                                                 &(self.current_state.transform * transform),
+                                                 TextRenderMode::Fill,
+                                                 &self.font_context.render_options,
+                                                 paint_id));
     }
 
     fn fill_or_stroke_text(&mut self,
@@ -57,27 +116,45 @@ impl CanvasRenderingContext2D {
                            render_mode: TextRenderMode) {
         let layout = self.layout_text(string);
 
-        match self.current_state.text_align {
-            TextAlign::Left => {},
-            TextAlign::Right => position.set_x(position.x() - layout.width()),
-            TextAlign::Center => position.set_x(position.x() - layout.width() * 0.5),
-        }
+        // Move the alignment point to the left end of the text on the alphabetic baseline.
+        let baseline_height =
+            LayoutExtents::new(&layout).baseline_height(self.current_state.text_baseline);
+        position = position - Vector2F::new(self.align_offset(layout.width()), -baseline_height);
 
         let transform = self.current_state.transform * Transform2F::from_translation(position);
 
         // TODO(pcwalton): Report errors.
-        drop(self.scene.push_layout(&layout,
-                                    &TextStyle { size: self.current_state.font_size },
-                                    &transform,
-                                    render_mode,
-                                    HintingOptions::None,
-                                    paint_id));
+        drop(self.scene.push_layout_with_options(&layout,
+                                                 &TextStyle { size: self.current_state.font_size },
+                                                 &transform,
+                                                 render_mode,
+                                                 &self.font_context.render_options,
+                                                 paint_id));
     }
 
     fn layout_text(&self, string: &str) -> Layout {
-        skribo::layout(&TextStyle { size: self.current_state.font_size },
-                       &self.current_state.font_collection,
-                       string)
+        let base_direction = match self.current_state.direction {
+            TextDirection::LeftToRight => BidiDirection::LeftToRight,
+            TextDirection::RightToLeft => BidiDirection::RightToLeft,
+        };
+        let shaper = BidiShaper::with_base_direction(&*self.font_context.shaper, base_direction);
+        shaper.shape(string,
+                     &TextStyle { size: self.current_state.font_size },
+                     &self.current_state.font_collection)
+    }
+
+    // The distance from the left end of text of the given width to its alignment point.
+    fn align_offset(&self, width: f32) -> f32 {
+        let right_to_left = self.current_state.direction == TextDirection::RightToLeft;
+        match self.current_state.text_align {
+            TextAlign::Left => 0.0,
+            TextAlign::Right => width,
+            TextAlign::Center => width * 0.5,
+            TextAlign::Start if right_to_left => width,
+            TextAlign::Start => 0.0,
+            TextAlign::End if right_to_left => 0.0,
+            TextAlign::End => width,
+        }
     }
 
     // Text styles
@@ -125,12 +202,112 @@ impl CanvasRenderingContext2D {
     pub fn set_text_align(&mut self, new_text_align: TextAlign) {
         self.current_state.text_align = new_text_align;
     }
+
+    #[inline]
+    pub fn set_text_baseline(&mut self, new_text_baseline: TextBaseline) {
+        self.current_state.text_baseline = new_text_baseline;
+    }
+
+    #[inline]
+    pub fn set_direction(&mut self, new_direction: TextDirection) {
+        self.current_state.direction = new_direction;
+    }
 }
 
-// TODO(pcwalton): Support other fields.
-#[derive(Clone, Copy, Debug)]
+/// The dimensions of a piece of text, as `measure_text()` returns them.
+///
+/// As in HTML canvas, horizontal distances are measured from the alignment point given by the
+/// text alignment, and vertical distances from the baseline given by the text baseline.
+/// Distances are positive going left for `actual_bounding_box_left`, positive going up for the
+/// ascents and baselines, and positive going down for the descents.
+///
+/// The font, em box, and baseline values are zero if no glyphs were laid out.
+#[derive(Clone, Copy, Default, Debug)]
 pub struct TextMetrics {
+    /// The advance width of the text.
     pub width: f32,
+    /// The distance from the alignment point to the left side of the ink of the text.
+    pub actual_bounding_box_left: f32,
+    /// The distance from the alignment point to the right side of the ink of the text.
+    pub actual_bounding_box_right: f32,
+    /// The largest ascent of the fonts used to draw the text.
+    pub font_bounding_box_ascent: f32,
+    /// The largest descent of the fonts used to draw the text.
+    pub font_bounding_box_descent: f32,
+    /// The distance from the baseline to the top of the ink of the text.
+    pub actual_bounding_box_ascent: f32,
+    /// The distance from the baseline to the bottom of the ink of the text.
+    pub actual_bounding_box_descent: f32,
+    /// The distance from the baseline to the top of the em square.
+    pub em_height_ascent: f32,
+    /// The distance from the baseline to the bottom of the em square.
+    pub em_height_descent: f32,
+    /// The distance from the baseline to the hanging baseline.
+    pub hanging_baseline: f32,
+    /// The distance from the baseline to the alphabetic baseline.
+    pub alphabetic_baseline: f32,
+    /// The distance from the baseline to the ideographic baseline.
+    pub ideographic_baseline: f32,
+}
+
+// The extents of the fonts of a layout, as heights above the alphabetic baseline in pixels.
+struct LayoutExtents {
+    font_ascent: f32,
+    font_descent: f32,
+    em_ascent: f32,
+    em_descent: f32,
+    hanging: f32,
+    ideographic: f32,
+}
+
+impl LayoutExtents {
+    // Ascents and descents are the largest of all the fonts. Other baselines come from the first
+    // font, as HTML canvas takes them from the first available font.
+    fn new(layout: &Layout) -> LayoutExtents {
+        let (mut font_ascent, mut font_descent) = (0.0, 0.0);
+        for glyph in &layout.glyphs {
+            let font_metrics = glyph.font.font.metrics();
+            let scale = layout.size / font_metrics.units_per_em as f32;
+            font_ascent = f32::max(font_ascent, font_metrics.ascent * scale);
+            font_descent = f32::max(font_descent, -font_metrics.descent * scale);
+        }
+
+        // Split the em square between the ascent and descent in proportion to them.
+        let (mut em_ascent, mut em_descent) = (0.0, 0.0);
+        let font_height = font_ascent + font_descent;
+        if font_height > 0.0 {
+            em_ascent = layout.size * font_ascent / font_height;
+            em_descent = layout.size - em_ascent;
+        }
+
+        // Without a `BASE` table, do as browsers do.
+        let (mut hanging, mut ideographic) = (em_ascent * HANGING_BASELINE_FRACTION, -em_descent);
+        if let Some(first_glyph) = layout.glyphs.first() {
+            let font = &first_glyph.font.font;
+            let scale = layout.size / font.metrics().units_per_em as f32;
+            let baselines = FontBaselines::from_font(&**font);
+            if let Some(font_hanging) = baselines.hanging {
+                hanging = font_hanging * scale;
+            }
+            if let Some(font_ideographic) = baselines.ideographic {
+                ideographic = font_ideographic * scale;
+            }
+        }
+
+        LayoutExtents { font_ascent, font_descent, em_ascent, em_descent, hanging, ideographic }
+    }
+
+    // The height of the given baseline above the alphabetic baseline.
+    fn baseline_height(&self, text_baseline: TextBaseline) -> f32 {
+        match text_baseline {
+            TextBaseline::Top => self.em_ascent,
+            TextBaseline::Hanging => self.hanging,
+            TextBaseline::Middle => (self.em_ascent - self.em_descent) * 0.5,
+            TextBaseline::Alphabetic => 0.0,
+            TextBaseline::Ideographic => self.ideographic,
+            TextBaseline::Bottom => -self.em_descent,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -139,6 +316,8 @@ pub struct CanvasFontContext {
     pub(super) font_source: Arc<dyn Source>,
     #[allow(dead_code)]
     pub(super) default_font_collection: Arc<FontCollection>,
+    pub(super) shaper: Arc<dyn TextShaper>,
+    pub(super) render_options: TextRenderOptions,
 }
 
 impl CanvasFontContext {
@@ -154,9 +333,27 @@ impl CanvasFontContext {
         CanvasFontContext {
             font_source,
             default_font_collection: Arc::new(default_font_collection),
+            shaper: Arc::new(SkriboShaper),
+            render_options: TextRenderOptions::default(),
         }
     }
 
+    /// Replaces the shaper used to lay out text. By default, `skribo` is used.
+    ///
+    /// Text is split into runs of a single direction in the order they're displayed before it's
+    /// passed to the shaper, so the shaper only ever sees one direction at a time.
+    #[inline]
+    pub fn set_shaper(&mut self, shaper: Arc<dyn TextShaper>) {
+        self.shaper = shaper;
+    }
+
+    /// Sets how glyphs are hinted and positioned on the pixel grid. By default, glyphs are
+    /// unhinted and placed at quarter-pixel positions.
+    #[inline]
+    pub fn set_text_render_options(&mut self, render_options: TextRenderOptions) {
+        self.render_options = render_options;
+    }
+
     /// A convenience method to create a font context with the system source.
     /// This allows usage of fonts installed on the system.
     pub fn from_system_source() -> CanvasFontContext {
@@ -182,10 +379,9 @@ impl LayoutExt for Layout {
             Some(last_glyph) => last_glyph,
         };
 
-        let glyph_id = last_glyph.glyph_id;
-        let font_metrics = last_glyph.font.font.metrics();
-        let glyph_rect = last_glyph.font.font.typographic_bounds(glyph_id).unwrap();
-        let scale_factor = self.size / font_metrics.units_per_em as f32;
-        last_glyph.offset.x + glyph_rect.max_x() * scale_factor
+        let font = &last_glyph.font.font;
+        let advance = font.advance(last_glyph.glyph_id).map(|advance| advance.x).unwrap_or(0.0);
+        let scale_factor = self.size / font.metrics().units_per_em as f32;
+        last_glyph.offset.x + advance * scale_factor
     }
 }
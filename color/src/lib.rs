@@ -8,11 +8,20 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::fmt::{self, Debug, Formatter};
 use pathfinder_simd::default::F32x4;
-use std::fmt::{self, Debug, Formatter};
+
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::math::FloatExt;
 
 // TODO(pcwalton): Maybe this should be a u32?
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
 pub struct ColorU {
     pub r: u8,
     pub g: u8,
@@ -61,6 +70,16 @@ impl ColorU {
     pub fn is_fully_transparent(&self) -> bool {
         self.a == 0
     }
+
+    /// Converts this color, given in `from`, to `to`. Colors outside the gamut of `to` are
+    /// clamped to it.
+    #[inline]
+    pub fn convert_color_space(&self, from: ColorSpace, to: ColorSpace) -> ColorU {
+        if from == to {
+            return *self;
+        }
+        self.to_f32().convert_color_space(from, to).to_u8()
+    }
 }
 
 impl Debug for ColorU {
@@ -110,6 +129,53 @@ impl ColorF {
         ColorF(self.0 + (other.0 - self.0) * F32x4::splat(t))
     }
 
+    /// Multiplies the color channels of this color by its alpha.
+    #[inline]
+    pub fn premultiply(&self) -> ColorF {
+        let alpha = self.a();
+        ColorF(self.0 * F32x4::new(alpha, alpha, alpha, 1.0))
+    }
+
+    /// Converts this color, given in `from`, to `to`. Colors outside the gamut of `to` are
+    /// clamped to it. Alpha is unchanged.
+    pub fn convert_color_space(&self, from: ColorSpace, to: ColorSpace) -> ColorF {
+        if from == to {
+            return *self;
+        }
+
+        // Both spaces use the sRGB transfer function, so only the primaries differ.
+        let matrix = match (from, to) {
+            (ColorSpace::Srgb, ColorSpace::DisplayP3) => &LINEAR_SRGB_TO_LINEAR_DISPLAY_P3,
+            (ColorSpace::DisplayP3, ColorSpace::Srgb) => &LINEAR_DISPLAY_P3_TO_LINEAR_SRGB,
+            _ => unreachable!(),
+        };
+        let linear = self.to_linear();
+        let (r, g, b) = (linear.r(), linear.g(), linear.b());
+        let convert = |row: &[f32; 3]| {
+            (row[0] * r + row[1] * g + row[2] * b).clamp(0.0, 1.0)
+        };
+        ColorF::new(convert(&matrix[0]), convert(&matrix[1]), convert(&matrix[2]), self.a())
+            .to_srgb()
+    }
+
+    /// Decodes the color channels of this color from sRGB to linear sRGB. Alpha is unchanged.
+    #[inline]
+    pub fn to_linear(&self) -> ColorF {
+        ColorF::new(srgb_to_linear(self.r()),
+                    srgb_to_linear(self.g()),
+                    srgb_to_linear(self.b()),
+                    self.a())
+    }
+
+    /// Encodes the color channels of this color from linear sRGB to sRGB. Alpha is unchanged.
+    #[inline]
+    pub fn to_srgb(&self) -> ColorF {
+        ColorF::new(linear_to_srgb(self.r()),
+                    linear_to_srgb(self.g()),
+                    linear_to_srgb(self.b()),
+                    self.a())
+    }
+
     #[inline]
     pub fn r(&self) -> f32 {
         self.0[0]
@@ -143,3 +209,54 @@ impl Debug for ColorF {
         )
     }
 }
+
+/// The RGB color spaces that paints can be specified in and that scenes can be built for.
+///
+/// All of these use the sRGB transfer function; they differ only in their primaries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// The standard color space of the Web and of most displays.
+    Srgb,
+    /// The wider gamut used by recent Apple displays and many HDR displays, with the DCI-P3
+    /// primaries and the D65 white point.
+    DisplayP3,
+}
+
+impl Default for ColorSpace {
+    #[inline]
+    fn default() -> ColorSpace {
+        ColorSpace::Srgb
+    }
+}
+
+// Row-major matrices between the linear forms of the color spaces, via CIE XYZ.
+static LINEAR_SRGB_TO_LINEAR_DISPLAY_P3: [[f32; 3]; 3] = [
+    [0.822_462_1, 0.177_538, 0.0],
+    [0.033_194_2, 0.966_805_8, 0.0],
+    [0.017_082_7, 0.072_397_4, 0.910_519_9],
+];
+static LINEAR_DISPLAY_P3_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [1.224_940_2, -0.224_940_4, 0.0],
+    [-0.042_056_9, 1.042_057_1, 0.0],
+    [-0.019_637_6, -0.078_636_1, 1.098_273_5],
+];
+
+/// Decodes a color channel, from 0 to 1, with the sRGB transfer function.
+#[inline]
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        f32::powf((value + 0.055) / 1.055, 2.4)
+    }
+}
+
+/// Encodes a linear color channel, from 0 to 1, with the sRGB transfer function.
+#[inline]
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * f32::powf(value, 1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,813 @@
+// pathfinder/content/src/boolean.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Boolean operations on outlines.
+//!
+//! Both operands are flattened and filled with the nonzero winding rule, unless another fill rule
+//! is given, so self-intersecting contours and contours of mixed orientation are handled. Every
+//! edge is split wherever it crosses or overlaps another edge, and each resulting piece is kept if
+//! the operation's result is filled on exactly one side of it. The surviving pieces are then
+//! linked back up into closed contours, all oriented the same way, and runs of pieces that came
+//! from the same curve are replaced with the part of that curve they approximate.
+//!
+//! Vertices that nearly coincide, as those in the output of the stroker often do, are merged
+//! first, so that edges which nearly coincide are treated as coinciding exactly. The flattening
+//! and merging tolerances are proportional to the size of the operands, so results don't depend
+//! on the scale of the coordinates.
+
+use crate::fill::FillRule;
+use crate::outline::{Contour, Outline};
+use crate::segment::Segment;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::util::lerp;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// The flattening and vertex merging tolerances, as fractions of the size of the operands.
+const FLATTENING_TOLERANCE: f32 = 0.0002;
+const VERTEX_MERGE_TOLERANCE: f32 = 0.000004;
+
+// No distance is smaller than this fraction of the largest coordinate, which is several times
+// the rounding error of the coordinates.
+const MIN_RELATIVE_TOLERANCE: f32 = 0.000001;
+
+// Curve parameters, and sines of angles between edges, smaller than this are treated as zero.
+const EPSILON: f32 = 0.00001;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl BooleanOp {
+    #[inline]
+    fn result_contains(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BooleanOp::Union => in_a || in_b,
+            BooleanOp::Intersection => in_a && in_b,
+            BooleanOp::Difference => in_a && !in_b,
+            BooleanOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+impl Outline {
+    /// Returns the area covered by either this outline or `other`.
+    #[inline]
+    pub fn union(&self, other: &Outline) -> Outline {
+        self.boolean_op(other, BooleanOp::Union)
+    }
+
+    /// Returns the area covered by both this outline and `other`.
+    #[inline]
+    pub fn intersection(&self, other: &Outline) -> Outline {
+        self.boolean_op(other, BooleanOp::Intersection)
+    }
+
+    /// Returns the area covered by this outline but not by `other`.
+    #[inline]
+    pub fn difference(&self, other: &Outline) -> Outline {
+        self.boolean_op(other, BooleanOp::Difference)
+    }
+
+    /// Returns the area covered by exactly one of this outline and `other`.
+    #[inline]
+    pub fn xor(&self, other: &Outline) -> Outline {
+        self.boolean_op(other, BooleanOp::Xor)
+    }
+
+    #[inline]
+    pub fn boolean_op(&self, other: &Outline, op: BooleanOp) -> Outline {
+        self.boolean_op_with_fill_rules(FillRule::Winding, other, FillRule::Winding, op)
+    }
+
+    /// Like `boolean_op()`, but fills this outline with `self_fill_rule` and `other` with
+    /// `other_fill_rule`. The result uses the nonzero winding rule.
+    pub fn boolean_op_with_fill_rules(&self,
+                                      self_fill_rule: FillRule,
+                                      other: &Outline,
+                                      other_fill_rule: FillRule,
+                                      op: BooleanOp)
+                                      -> Outline {
+        self.boolean_op_with_winding_tests(|winding| self_fill_rule.is_inside(winding),
+                                           other,
+                                           |winding| other_fill_rule.is_inside(winding),
+                                           op)
+    }
+
+    /// Like `boolean_op_with_fill_rules()`, but decides whether a point is inside each operand
+    /// by passing its winding number to the given function.
+    pub(crate) fn boolean_op_with_winding_tests<A, B>(&self,
+                                                      self_is_inside: A,
+                                                      other: &Outline,
+                                                      other_is_inside: B,
+                                                      op: BooleanOp)
+                                                      -> Outline
+                                                      where A: Fn(i32) -> bool,
+                                                            B: Fn(i32) -> bool {
+        let bounds = match operand_bounds(self, other) {
+            None => return Outline::new(),
+            Some(bounds) => bounds,
+        };
+
+        // Scale the tolerances to the size of the operands, but keep them well above the
+        // rounding error of coordinates far from the origin.
+        let size = f32::max(bounds.width(), bounds.height());
+        let extent = f32::max(f32::max(bounds.min_x().abs(), bounds.max_x().abs()),
+                              f32::max(bounds.min_y().abs(), bounds.max_y().abs()));
+        let merge_tolerance = f32::max(size * VERTEX_MERGE_TOLERANCE,
+                                       extent * MIN_RELATIVE_TOLERANCE * 4.0);
+        if merge_tolerance == 0.0 {
+            return Outline::new();
+        }
+        let flattening_tolerance = f32::max(size * FLATTENING_TOLERANCE, merge_tolerance);
+        let nudge = f32::max(merge_tolerance * 0.1, extent * MIN_RELATIVE_TOLERANCE);
+
+        let mut vertices = VertexMerger::new(merge_tolerance);
+        let mut segments = vec![];
+        let edges_a = flatten_outline(self, flattening_tolerance, &mut vertices, &mut segments);
+        let edges_b = flatten_outline(other, flattening_tolerance, &mut vertices, &mut segments);
+        let mut edges = Vec::with_capacity(edges_a.len() + edges_b.len());
+        edges.extend_from_slice(&edges_a);
+        edges.extend_from_slice(&edges_b);
+        let (bands_a, bands_b) = (EdgeBands::new(&edges_a), EdgeBands::new(&edges_b));
+
+        let mut result_edges = vec![];
+        for edge in split_edges(&edges, &mut vertices) {
+            let vector = edge.line.vector();
+            let length = vector.length();
+            if length < nudge {
+                continue;
+            }
+
+            let normal = vector.yx().scale_xy(Vector2F::new(-1.0, 1.0)).scale(1.0 / length);
+            let offset = normal.scale(f32::min(nudge, length * 0.25));
+            let midpoint = edge.line.midpoint();
+            let (positive_side, negative_side) = (midpoint + offset, midpoint - offset);
+
+            let is_filled = |point| {
+                op.result_contains(self_is_inside(bands_a.winding_number(point)),
+                                   other_is_inside(bands_b.winding_number(point)))
+            };
+            let (positive_filled, negative_filled) = (is_filled(positive_side),
+                                                      is_filled(negative_side));
+
+            // Orient every boundary edge so that the filled side is on the positive side.
+            match (positive_filled, negative_filled) {
+                (true, false) => result_edges.push(edge),
+                (false, true) => result_edges.push(edge.reversed()),
+                _ => {}
+            }
+        }
+
+        link_edges(cancel_coincident_edges(result_edges), &segments)
+    }
+}
+
+// A line approximating part of a segment of one of the operands. The segment is
+// `segments[source]`, and the line runs from its parameter `t0` to `t1`, which is less than `t0`
+// if the line runs backwards along it.
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    line: LineSegment2F,
+    source: u32,
+    t0: f32,
+    t1: f32,
+}
+
+impl Edge {
+    #[inline]
+    fn reversed(&self) -> Edge {
+        Edge { line: self.line.reversed(), source: self.source, t0: self.t1, t1: self.t0 }
+    }
+
+    // Whether `next` picks up along the same segment where this edge leaves off.
+    #[inline]
+    fn continues_into(&self, next: &Edge) -> bool {
+        self.source == next.source && self.t1 == next.t0
+    }
+}
+
+// The bounds of the nonempty contours of both operands, if there are any.
+fn operand_bounds(a: &Outline, b: &Outline) -> Option<RectF> {
+    let mut contours = a.contours().iter().chain(b.contours()).filter(|contour| {
+        !contour.is_empty()
+    });
+    let first_bounds = contours.next()?.bounds();
+    Some(contours.fold(first_bounds, |bounds, contour| bounds.union_rect(contour.bounds())))
+}
+
+fn flatten_outline(outline: &Outline,
+                   tolerance: f32,
+                   vertices: &mut VertexMerger,
+                   segments: &mut Vec<Segment>)
+                   -> Vec<Edge> {
+    let mut edges = vec![];
+    for contour in &outline.contours {
+        if contour.is_empty() {
+            continue;
+        }
+
+        // Every contour is implicitly closed for the purposes of filling.
+        let first_position = vertices.merge(contour.position_of(0));
+        let mut last_position = first_position;
+        for segment in contour.iter() {
+            let source = segments.len() as u32;
+            segments.push(segment);
+            let mut last_t = 0.0;
+            segment.flatten(tolerance, |position, t| {
+                let position = vertices.merge(position);
+                if position != last_position {
+                    let line = LineSegment2F::new(last_position, position);
+                    edges.push(Edge { line, source, t0: last_t, t1: t });
+                    last_t = t;
+                }
+                last_position = position;
+            });
+        }
+        if last_position != first_position {
+            let line = LineSegment2F::new(last_position, first_position);
+            edges.push(Edge { line, source: segments.len() as u32, t0: 0.0, t1: 1.0 });
+            segments.push(Segment::line(line));
+        }
+    }
+    edges
+}
+
+// Splits every edge at each point at which it crosses, touches, or overlaps another edge, so
+// that no two resulting edges intersect except at their endpoints. The same split point is used
+// for both edges so that their endpoints match exactly, and it's merged with any vertex near it.
+//
+// Only edges whose bounding boxes overlap can intersect, so the edges are swept from left to
+// right, and each is tested only against the edges whose horizontal extents it overlaps.
+fn split_edges(edges: &[Edge], vertices: &mut VertexMerger) -> Vec<Edge> {
+    let mut sorted_indices: Vec<usize> = (0..edges.len()).collect();
+    sorted_indices.sort_by(|&a, &b| {
+        edges[a].line.min_x().partial_cmp(&edges[b].line.min_x()).unwrap()
+    });
+
+    let mut splits: Vec<Vec<(f32, Vector2F)>> = vec![vec![]; edges.len()];
+    let mut active_indices: Vec<usize> = vec![];
+    for &index in &sorted_indices {
+        let edge = edges[index].line;
+        active_indices.retain(|&active_index| edges[active_index].line.max_x() >= edge.min_x());
+        for &active_index in &active_indices {
+            let active_edge = edges[active_index].line;
+            if edge.max_y() < active_edge.min_y() || active_edge.max_y() < edge.min_y() {
+                continue;
+            }
+            // Test pairs in a consistent order, so that which endpoint intersections snap to
+            // doesn't depend on the order of the sweep.
+            let (index_a, index_b) = (usize::min(index, active_index),
+                                      usize::max(index, active_index));
+            split_edge_pair(edges[index_a].line,
+                            edges[index_b].line,
+                            index_a,
+                            index_b,
+                            &mut splits,
+                            vertices);
+        }
+        active_indices.push(index);
+    }
+
+    let mut split_edges = Vec::with_capacity(edges.len());
+    for (edge, mut edge_splits) in edges.iter().zip(splits.into_iter()) {
+        edge_splits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let (mut from, mut from_t) = (edge.line.from(), edge.t0);
+        for (t, point) in edge_splits {
+            if point != from {
+                let to_t = lerp(edge.t0, edge.t1, t);
+                split_edges.push(Edge {
+                    line: LineSegment2F::new(from, point),
+                    source: edge.source,
+                    t0: from_t,
+                    t1: to_t,
+                });
+                from = point;
+                from_t = to_t;
+            }
+        }
+        if from != edge.line.to() {
+            split_edges.push(Edge {
+                line: LineSegment2F::new(from, edge.line.to()),
+                source: edge.source,
+                t0: from_t,
+                t1: edge.t1,
+            });
+        }
+    }
+    split_edges
+}
+
+fn split_edge_pair(edge_a: LineSegment2F,
+                   edge_b: LineSegment2F,
+                   index_a: usize,
+                   index_b: usize,
+                   splits: &mut [Vec<(f32, Vector2F)>],
+                   vertices: &mut VertexMerger) {
+    let (vector_a, vector_b) = (edge_a.vector(), edge_b.vector());
+    let denominator = vector_a.det(vector_b);
+    let from_vector = edge_b.from() - edge_a.from();
+
+    if denominator.abs() > EPSILON * vector_a.length() * vector_b.length() {
+        let t_a = from_vector.det(vector_b) / denominator;
+        let t_b = from_vector.det(vector_a) / denominator;
+        if t_a < -EPSILON || t_a > 1.0 + EPSILON || t_b < -EPSILON || t_b > 1.0 + EPSILON {
+            return;
+        }
+
+        // Snap intersections near an endpoint to that endpoint exactly.
+        let point = if t_a <= EPSILON {
+            edge_a.from()
+        } else if t_a >= 1.0 - EPSILON {
+            edge_a.to()
+        } else if t_b <= EPSILON {
+            edge_b.from()
+        } else if t_b >= 1.0 - EPSILON {
+            edge_b.to()
+        } else {
+            vertices.merge(edge_a.sample(t_a))
+        };
+        add_split(&mut splits[index_a], edge_a, t_a, point);
+        add_split(&mut splits[index_b], edge_b, t_b, point);
+        return;
+    }
+
+    // The edges are parallel. If they're collinear, split each at the other's endpoints.
+    if from_vector.det(vector_a).abs() > EPSILON * vector_a.length() * from_vector.length() {
+        return;
+    }
+    for &point in &[edge_b.from(), edge_b.to()] {
+        let t = (point - edge_a.from()).dot(vector_a) / vector_a.square_length();
+        add_split(&mut splits[index_a], edge_a, t, point);
+    }
+    for &point in &[edge_a.from(), edge_a.to()] {
+        let t = (point - edge_b.from()).dot(vector_b) / vector_b.square_length();
+        add_split(&mut splits[index_b], edge_b, t, point);
+    }
+}
+
+#[inline]
+fn add_split(splits: &mut Vec<(f32, Vector2F)>, edge: LineSegment2F, t: f32, point: Vector2F) {
+    if t > EPSILON && t < 1.0 - EPSILON && point != edge.from() && point != edge.to() {
+        splits.push((t, point));
+    }
+}
+
+// The edges of one operand, bucketed into horizontal bands by the range of heights that each
+// spans. Only edges that span a point's height affect the winding number around it, so only the
+// edges in the point's band need to be looked at.
+struct EdgeBands {
+    bands: Vec<Vec<LineSegment2F>>,
+    min_y: f32,
+    band_height: f32,
+}
+
+impl EdgeBands {
+    fn new(edges: &[Edge]) -> EdgeBands {
+        let (mut min_y, mut max_y) = (f32::INFINITY, f32::NEG_INFINITY);
+        for edge in edges {
+            min_y = f32::min(min_y, edge.line.min_y());
+            max_y = f32::max(max_y, edge.line.max_y());
+        }
+        if edges.is_empty() || max_y <= min_y {
+            return EdgeBands { bands: vec![], min_y: 0.0, band_height: 1.0 };
+        }
+
+        let band_count = (edges.len() as f32).sqrt().ceil() as usize;
+        let mut edge_bands = EdgeBands {
+            bands: vec![vec![]; band_count],
+            min_y,
+            band_height: (max_y - min_y) / band_count as f32,
+        };
+        for edge in edges {
+            let (first_band, last_band) = (edge_bands.band_index(edge.line.min_y()),
+                                           edge_bands.band_index(edge.line.max_y()));
+            for band in &mut edge_bands.bands[first_band..=last_band] {
+                band.push(edge.line);
+            }
+        }
+        edge_bands
+    }
+
+    #[inline]
+    fn band_index(&self, y: f32) -> usize {
+        let index = f32::max((y - self.min_y) / self.band_height, 0.0) as usize;
+        usize::min(index, self.bands.len() - 1)
+    }
+
+    fn winding_number(&self, point: Vector2F) -> i32 {
+        if self.bands.is_empty() {
+            return 0;
+        }
+        winding_number(&self.bands[self.band_index(point.y())], point)
+    }
+}
+
+// The winding number of the polygon made up of `edges` around `point`.
+fn winding_number(edges: &[LineSegment2F], point: Vector2F) -> i32 {
+    let mut winding = 0;
+    for edge in edges {
+        let (from, to) = (edge.from(), edge.to());
+        let side = (to - from).det(point - from);
+        if from.y() <= point.y() {
+            if to.y() > point.y() && side > 0.0 {
+                winding += 1;
+            }
+        } else if to.y() <= point.y() && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+// Removes duplicate edges, and pairs of identical edges running in opposite directions, which
+// arise where the boundaries of the two operands coincide.
+fn cancel_coincident_edges(edges: Vec<Edge>) -> Vec<Edge> {
+    let mut seen = BTreeSet::new();
+    let mut unique_edges = vec![];
+    for edge in edges {
+        if seen.insert(EdgeKey::new(edge.line)) {
+            unique_edges.push(edge);
+        }
+    }
+
+    unique_edges.into_iter()
+                .filter(|edge| !seen.contains(&EdgeKey::new(edge.line.reversed())))
+                .collect()
+}
+
+fn link_edges(edges: Vec<Edge>, segments: &[Segment]) -> Outline {
+    let mut outgoing: BTreeMap<PointKey, Vec<usize>> = BTreeMap::new();
+    for (edge_index, edge) in edges.iter().enumerate() {
+        outgoing.entry(PointKey::new(edge.line.from())).or_insert_with(Vec::new).push(edge_index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut outline = Outline::new();
+    for start_index in 0..edges.len() {
+        if used[start_index] {
+            continue;
+        }
+
+        let mut chain = vec![];
+        let start_point = edges[start_index].line.from();
+        let mut edge_index = start_index;
+        let closed = loop {
+            used[edge_index] = true;
+            let edge = edges[edge_index];
+            chain.push(edge);
+
+            let end_point = edge.line.to();
+            if end_point == start_point {
+                break true;
+            }
+
+            let next_edges = match outgoing.get_mut(&PointKey::new(end_point)) {
+                None => break false,
+                Some(next_edges) => next_edges,
+            };
+            match next_edges.iter().position(|&next_index| !used[next_index]) {
+                None => break false,
+                Some(position) => edge_index = next_edges.swap_remove(position),
+            }
+        };
+
+        // A chain that doesn't return to its start encloses nothing, so drop it rather than
+        // closing it with an edge that isn't part of the boundary.
+        if !closed || chain.len() < 3 {
+            continue;
+        }
+
+        outline.push_contour(chain_to_contour(&chain, segments));
+    }
+    outline
+}
+
+// Builds a closed contour from a closed chain of edges, replacing each run of edges that
+// continue along the same segment with the part of that segment they span.
+fn chain_to_contour(chain: &[Edge], segments: &[Segment]) -> Contour {
+    // Start at the beginning of a run, so that no run wraps around the end of the chain.
+    let edge_count = chain.len();
+    let first_index = (0..edge_count).find(|&edge_index| {
+        !chain[(edge_index + edge_count - 1) % edge_count].continues_into(&chain[edge_index])
+    }).unwrap_or(0);
+
+    let mut contour = Contour::new();
+    contour.push_endpoint(chain[first_index].line.from());
+    let mut edge_offset = 0;
+    while edge_offset < edge_count {
+        let first_edge = chain[(first_index + edge_offset) % edge_count];
+        let mut last_edge = first_edge;
+        edge_offset += 1;
+        while edge_offset < edge_count {
+            let next_edge = chain[(first_index + edge_offset) % edge_count];
+            if !last_edge.continues_into(&next_edge) {
+                break;
+            }
+            last_edge = next_edge;
+            edge_offset += 1;
+        }
+
+        let segment = &segments[first_edge.source as usize];
+        let to = last_edge.line.to();
+        if segment.is_line() {
+            // The contour is closed, so the last point needn't be repeated.
+            if edge_offset < edge_count {
+                contour.push_endpoint(to);
+            }
+            continue;
+        }
+
+        // The ends of the curve are moved onto the vertices, which the edges' intersections were
+        // found on, so that the contour still meets up with itself and the other contours.
+        let mut curve = sub_segment(segment, first_edge.t0, last_edge.t1);
+        curve.baseline = LineSegment2F::new(first_edge.line.from(), to);
+        if curve.is_quadratic() {
+            contour.push_quadratic(curve.ctrl.from(), to);
+        } else {
+            contour.push_cubic(curve.ctrl.from(), curve.ctrl.to(), to);
+        }
+    }
+    contour.close();
+    contour
+}
+
+// The part of `segment` from parameter `t0` to `t1`, reversed if `t1` is less than `t0`.
+fn sub_segment(segment: &Segment, t0: f32, t1: f32) -> Segment {
+    let (start_t, end_t) = (f32::min(t0, t1), f32::max(t0, t1));
+    let mut segment = *segment;
+    if end_t < 1.0 {
+        segment = segment.split(end_t).0;
+    }
+    if start_t > 0.0 {
+        segment = segment.split(start_t / end_t).1;
+    }
+    if t1 < t0 {
+        segment = segment.reversed();
+    }
+    segment
+}
+
+// Replaces points with the first point seen within the tolerance of them. Points are bucketed
+// into a grid whose cells are as wide as the tolerance, so only the neighboring cells need to be
+// searched.
+struct VertexMerger {
+    cells: BTreeMap<(i32, i32), Vec<Vector2F>>,
+    tolerance: f32,
+}
+
+impl VertexMerger {
+    #[inline]
+    fn new(tolerance: f32) -> VertexMerger {
+        VertexMerger { cells: BTreeMap::new(), tolerance }
+    }
+
+    fn merge(&mut self, point: Vector2F) -> Vector2F {
+        let cell = point.scale(1.0 / self.tolerance).floor().to_i32();
+        let square_tolerance = self.tolerance * self.tolerance;
+        for y in (cell.y() - 1)..(cell.y() + 2) {
+            for x in (cell.x() - 1)..(cell.x() + 2) {
+                if let Some(vertices) = self.cells.get(&(x, y)) {
+                    for &vertex in vertices {
+                        if (vertex - point).square_length() <= square_tolerance {
+                            return vertex;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.cells.entry((cell.x(), cell.y())).or_insert_with(Vec::new).push(point);
+        point
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct PointKey(u32, u32);
+
+impl PointKey {
+    #[inline]
+    fn new(point: Vector2F) -> PointKey {
+        // Normalize negative zero so that it compares equal to positive zero.
+        PointKey((point.x() + 0.0).to_bits(), (point.y() + 0.0).to_bits())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct EdgeKey(PointKey, PointKey);
+
+impl EdgeKey {
+    #[inline]
+    fn new(edge: LineSegment2F) -> EdgeKey {
+        EdgeKey(PointKey::new(edge.from()), PointKey::new(edge.to()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use core::f32::consts::PI;
+
+    const AREA_EPSILON: f32 = 0.05;
+
+    fn polygon(points: &[(f32, f32)]) -> Contour {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        contour
+    }
+
+    fn square(x: f32, y: f32, size: f32) -> Contour {
+        polygon(&[(x, y), (x + size, y), (x + size, y + size), (x, y + size)])
+    }
+
+    fn outline(contours: Vec<Contour>) -> Outline {
+        let mut outline = Outline::new();
+        contours.into_iter().for_each(|contour| outline.push_contour(contour));
+        outline
+    }
+
+    // The filled area of an outline produced by a boolean operation, all of whose contours are
+    // oriented so that holes cancel out.
+    fn area(outline: &Outline) -> f32 {
+        let mut outline = outline.clone();
+        outline.flatten(0.001);
+        f32::abs(outline.contours().iter().map(|contour| {
+            (0..contour.len()).map(|point_index| {
+                let p0 = contour.position_of(point_index);
+                let p1 = contour.position_of((point_index + 1) % contour.len());
+                p0.x() * p1.y() - p1.x() * p0.y()
+            }).sum::<f32>() * 0.5
+        }).sum())
+    }
+
+    fn assert_area(outline: &Outline, expected: f32) {
+        let actual = area(outline);
+        assert!(f32::abs(actual - expected) < AREA_EPSILON,
+                "expected area {}, found {}",
+                expected,
+                actual);
+        for contour in outline.contours() {
+            assert!(contour.is_closed() && contour.len() >= 3);
+        }
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = outline(vec![square(0.0, 0.0, 10.0)]);
+        let b = outline(vec![square(5.0, 5.0, 10.0)]);
+
+        let union = a.union(&b);
+        assert_eq!(union.len(), 1);
+        assert_area(&union, 175.0);
+        assert_area(&a.intersection(&b), 25.0);
+        assert_area(&a.difference(&b), 75.0);
+        assert_area(&a.xor(&b), 150.0);
+    }
+
+    #[test]
+    fn test_figure_eight() {
+        // The two lobes wind in opposite directions, so both are filled.
+        let bowtie = outline(vec![polygon(&[(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)])]);
+        assert_area(&bowtie.union(&Outline::new()), 50.0);
+
+        let square = outline(vec![square(0.0, 0.0, 10.0)]);
+        assert_area(&square.difference(&bowtie), 50.0);
+    }
+
+    #[test]
+    fn test_opposite_windings() {
+        let mut reversed = square(5.0, 5.0, 10.0);
+        reversed.reverse();
+        let a = outline(vec![square(0.0, 0.0, 10.0)]);
+        assert_area(&a.union(&outline(vec![reversed.clone()])), 175.0);
+        assert_area(&a.intersection(&outline(vec![reversed])), 25.0);
+
+        // An oppositely-wound contour nested inside another is a hole.
+        let mut hole = square(3.0, 3.0, 4.0);
+        hole.reverse();
+        let ring = outline(vec![square(0.0, 0.0, 10.0), hole]);
+        let union = ring.union(&Outline::new());
+        assert_eq!(union.len(), 2);
+        assert_area(&union, 84.0);
+        assert_area(&ring.intersection(&outline(vec![square(0.0, 0.0, 5.0)])), 21.0);
+    }
+
+    #[test]
+    fn test_coincident_edges() {
+        let a = outline(vec![square(0.0, 0.0, 10.0)]);
+
+        // The shared edge isn't part of the result's boundary.
+        let union = a.union(&outline(vec![square(10.0, 0.0, 10.0)]));
+        assert_eq!(union.len(), 1);
+        assert_area(&union, 200.0);
+
+        // Edges that only partially overlap.
+        let union = a.union(&outline(vec![square(10.0, 5.0, 10.0)]));
+        assert_eq!(union.len(), 1);
+        assert_area(&union, 200.0);
+
+        let union = a.union(&a);
+        assert_eq!(union.len(), 1);
+        assert_area(&union, 100.0);
+        assert_eq!(a.difference(&a).len(), 0);
+        assert_eq!(a.xor(&a).len(), 0);
+
+        // Vertices that differ only by rounding error are merged.
+        let nudged = outline(vec![square(0.0, 0.0, 10.0 + 0.00001)]);
+        let union = a.union(&nudged);
+        assert_eq!(union.len(), 1);
+        assert_area(&union, 100.0);
+        assert_eq!(a.difference(&nudged).len(), 0);
+    }
+
+    #[test]
+    fn test_stroke_union() {
+        // The round joins of the stroke's two sides nearly coincide except for rounding error.
+        let square = outline(vec![square(0.0, 0.0, 10.0)]);
+        for &(join, expected_area) in &[(LineJoin::Round, 180.0 + 4.0 * PI),
+                                        (LineJoin::Miter(10.0), 196.0),
+                                        (LineJoin::Bevel, 188.0)] {
+            let style = StrokeStyle { line_width: 4.0, line_cap: LineCap::Butt, line_join: join };
+            let mut stroke_to_fill = OutlineStrokeToFill::new(&square, style);
+            stroke_to_fill.offset();
+            let border = stroke_to_fill.into_outline();
+
+            let union = square.union(&border);
+            assert_eq!(union.len(), 1);
+            assert_area(&union, expected_area);
+            assert_area(&square.difference(&border), 36.0);
+        }
+    }
+
+    #[test]
+    fn test_scale_invariance() {
+        let triangle = polygon(&[(2.0, 2.0), (8.0, 5.0), (2.0, 8.0)]);
+        let a = outline(vec![square(0.0, 0.0, 10.0), triangle]);
+        let b = outline(vec![square(5.0, 5.0, 10.0)]);
+        let expected = a.xor(&b);
+        for &(scale, translation) in &[(0.0001, 0.0), (10000.0, 0.0), (1.0, 1000.0)] {
+            let transform = Transform2F::from_translation(Vector2F::splat(translation)) *
+                Transform2F::from_scale(Vector2F::splat(scale));
+            let (mut a, mut b) = (a.clone(), b.clone());
+            a.transform(&transform);
+            b.transform(&transform);
+
+            let actual = a.xor(&b);
+            assert_eq!(actual.len(), expected.len());
+            let point_counts = |outline: &Outline| {
+                outline.contours().iter().map(|contour| contour.len()).collect::<Vec<_>>()
+            };
+            assert_eq!(point_counts(&actual), point_counts(&expected));
+            let mut actual = actual.clone();
+            actual.transform(&transform.inverse());
+            assert_area(&actual, area(&expected));
+        }
+    }
+
+    #[test]
+    fn test_curves_are_kept() {
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(Vector2F::splat(10.0)));
+        let circle = outline(vec![circle]);
+
+        // A quarter of the circle, which is one curve and two lines.
+        let quarter = circle.intersection(&outline(vec![square(0.0, 0.0, 20.0)]));
+        assert_eq!(quarter.len(), 1);
+        let contour = &quarter.contours()[0];
+        let endpoint_count = (0..contour.len()).filter(|&point_index| {
+            contour.point_is_endpoint(point_index)
+        }).count();
+        assert!(endpoint_count <= 4 && contour.len() > endpoint_count as u32);
+        assert_area(&quarter, 25.0 * PI);
+
+        // Curves of the two operands that cross each other are split where they cross.
+        let mut other_circle = Contour::new();
+        let transform = Transform2F::from_translation(Vector2F::new(10.0, 0.0)) *
+            Transform2F::from_scale(Vector2F::splat(10.0));
+        other_circle.push_ellipse(&transform);
+        let lens = circle.intersection(&outline(vec![other_circle]));
+        assert_eq!(lens.len(), 1);
+        assert!(lens.contours()[0].len() < 24);
+        assert_area(&lens, 100.0 * (2.0 * PI / 3.0 - f32::sqrt(3.0) / 2.0));
+    }
+}
@@ -11,7 +11,7 @@
 //! Line dashing support.
 
 use crate::outline::{Contour, Outline, PushSegmentFlags};
-use std::mem;
+use core::mem;
 
 const EPSILON: f32 = 0.0001;
 
@@ -19,24 +19,52 @@ pub struct OutlineDash<'a> {
     input: &'a Outline,
     output: Outline,
     state: DashState<'a>,
+    offset: f32,
+    restart_per_contour: bool,
 }
 
 impl<'a> OutlineDash<'a> {
+    /// Creates a dasher for the given outline.
+    ///
+    /// `dashes` alternates between the lengths of the "on" and "off" parts of the pattern, and
+    /// `offset` is the distance into the pattern at which dashing starts, like the canvas
+    /// `lineDashOffset` and SVG `stroke-dashoffset` properties. Negative offsets are allowed. If
+    /// the pattern has no positive length, the outline is left undashed.
     #[inline]
     pub fn new(input: &'a Outline, dashes: &'a [f32], offset: f32) -> OutlineDash<'a> {
-        OutlineDash { input, output: Outline::new(), state: DashState::new(dashes, offset) }
+        OutlineDash {
+            input,
+            output: Outline::new(),
+            state: DashState::new(dashes, offset),
+            offset,
+            restart_per_contour: false,
+        }
+    }
+
+    /// If true, the dash pattern starts over at `offset` at the beginning of every contour, as
+    /// canvas and SVG require. Otherwise, the pattern continues where the previous contour left
+    /// off. The default is false.
+    #[inline]
+    pub fn set_restart_per_contour(&mut self, restart_per_contour: bool) {
+        self.restart_per_contour = restart_per_contour;
     }
 
     pub fn dash(&mut self) {
+        if !self.state.is_valid() {
+            self.output = self.input.clone();
+            return;
+        }
+
         for contour in &self.input.contours {
+            if self.restart_per_contour {
+                self.state = DashState::new(self.state.dashes, self.offset);
+            }
             ContourDash::new(contour, &mut self.output, &mut self.state).dash()
         }
     }
 
-    pub fn into_outline(mut self) -> Outline {
-        if self.state.is_on() {
-            self.output.push_contour(self.state.output);
-        }
+    #[inline]
+    pub fn into_outline(self) -> Outline {
         self.output
     }
 }
@@ -93,6 +121,11 @@ impl<'a, 'b, 'c> ContourDash<'a, 'b, 'c> {
                 self.state.distance_left = self.state.dashes[self.state.current_dash_index];
             }
         }
+
+        // Don't let a dash run across the gap between two contours.
+        if self.state.is_on() {
+            self.output.push_contour(mem::replace(&mut self.state.output, Contour::new()));
+        }
     }
 }
 
@@ -106,7 +139,19 @@ struct DashState<'a> {
 impl<'a> DashState<'a> {
     fn new(dashes: &'a [f32], mut offset: f32) -> DashState<'a> {
         let total: f32 = dashes.iter().cloned().sum();
+        if !(total > 0.0) || dashes.iter().any(|&dash| dash < 0.0) || !offset.is_finite() {
+            return DashState {
+                output: Contour::new(),
+                dashes: &[],
+                current_dash_index: 0,
+                distance_left: 0.0,
+            };
+        }
+
         offset %= total;
+        if offset < 0.0 {
+            offset += total;
+        }
 
         let mut current_dash_index = 0;
         while current_dash_index < dashes.len() {
@@ -118,14 +163,25 @@ impl<'a> DashState<'a> {
             current_dash_index += 1;
         }
 
+        // Guard against floating point error leaving us just past the end of the pattern.
+        if current_dash_index == dashes.len() {
+            current_dash_index = 0;
+            offset = 0.0;
+        }
+
         DashState {
             output: Contour::new(),
             dashes,
             current_dash_index,
-            distance_left: offset,
+            distance_left: dashes[current_dash_index] - offset,
         }
     }
 
+    #[inline]
+    fn is_valid(&self) -> bool {
+        !self.dashes.is_empty()
+    }
+
     #[inline]
     fn is_on(&self) -> bool {
         self.current_dash_index % 2 == 0
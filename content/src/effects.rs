@@ -0,0 +1,103 @@
+// pathfinder/content/src/effects.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Blending, compositing, and filters.
+
+/// How a path is combined with what's already been drawn beneath it.
+///
+/// These follow the W3C "Compositing and Blending" specification: the first group are the
+/// Porter-Duff operators, and the rest are blend modes, which composite with `SrcOver`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BlendMode {
+    // Porter-Duff operators
+    Clear,
+    Copy,
+    SrcIn,
+    SrcOut,
+    SrcOver,
+    SrcAtop,
+    DestIn,
+    DestOut,
+    DestOver,
+    DestAtop,
+    Xor,
+    Lighter,
+
+    // Blend modes, which are separable up to `Exclusion`
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl Default for BlendMode {
+    #[inline]
+    fn default() -> BlendMode {
+        BlendMode::SrcOver
+    }
+}
+
+impl BlendMode {
+    /// Whether this blend mode affects the destination where the path doesn't cover it.
+    ///
+    /// For example, `SrcIn` clears everything outside the path.
+    #[inline]
+    pub fn is_destructive(self) -> bool {
+        match self {
+            BlendMode::Clear |
+            BlendMode::Copy |
+            BlendMode::SrcIn |
+            BlendMode::SrcOut |
+            BlendMode::DestIn |
+            BlendMode::DestAtop => true,
+            _ => false,
+        }
+    }
+
+    /// Whether drawing an opaque path with this blend mode hides everything beneath it.
+    #[inline]
+    pub fn occludes_backdrop(self) -> bool {
+        match self {
+            BlendMode::SrcOver | BlendMode::Copy => true,
+            _ => false,
+        }
+    }
+}
+
+/// A filter applied to what's already been drawn beneath a path, inside the path, before the path
+/// is drawn over it.
+///
+/// A translucent paint over a blurred backdrop makes a frosted-glass panel.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BackdropFilter {
+    /// The standard deviation of a Gaussian blur, in device pixels. Zero doesn't blur.
+    pub blur_std_dev: f32,
+    /// Scales the saturation as the CSS `saturate()` filter does: 0 makes the backdrop gray, and 1
+    /// leaves it unchanged.
+    pub saturation: f32,
+}
+
+impl Default for BackdropFilter {
+    #[inline]
+    fn default() -> BackdropFilter {
+        BackdropFilter { blur_std_dev: 0.0, saturation: 1.0 }
+    }
+}
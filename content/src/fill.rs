@@ -0,0 +1,166 @@
+// pathfinder/content/src/fill.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fill rules, and hit testing of points against filled outlines.
+
+use crate::measure::ContourMeasure;
+use crate::outline::{Contour, Outline};
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+
+// Curves are flattened to within this distance of the true curve before hit testing.
+const FLATTENING_TOLERANCE: f32 = 0.1;
+// The points that decide which side of a contour is filled are this fraction of the size of the
+// contour away from it.
+const SIDE_SAMPLE_DISTANCE: f32 = 0.001;
+// The distances along a contour, as fractions of its length, to test points beside it at.
+const SIDE_SAMPLE_FRACTIONS: [f32; 4] = [0.5, 0.382, 0.618, 0.25];
+
+/// Determines which points are inside an outline whose contours overlap themselves or each other.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FillRule {
+    /// A point is inside if the contours wind around it a nonzero number of times.
+    Winding,
+    /// A point is inside if a ray from it crosses the contours an odd number of times.
+    EvenOdd,
+}
+
+impl Default for FillRule {
+    #[inline]
+    fn default() -> FillRule {
+        FillRule::Winding
+    }
+}
+
+impl FillRule {
+    /// Returns true if a point with the given winding number is inside the fill.
+    #[inline]
+    pub fn is_inside(self, winding_number: i32) -> bool {
+        match self {
+            FillRule::Winding => winding_number != 0,
+            FillRule::EvenOdd => winding_number % 2 != 0,
+        }
+    }
+}
+
+impl Outline {
+    /// Returns true if `point` lies inside this outline when filled with `fill_rule`.
+    ///
+    /// All contours are treated as closed, as they are when filled.
+    #[inline]
+    pub fn contains_point(&self, point: Vector2F, fill_rule: FillRule) -> bool {
+        fill_rule.is_inside(self.winding_number(point))
+    }
+
+    /// Returns the number of times the contours of this outline wind around `point`, counting
+    /// clockwise turns as positive.
+    pub fn winding_number(&self, point: Vector2F) -> i32 {
+        self.contours.iter().map(|contour| contour.winding_number(point)).sum()
+    }
+
+    /// Reorients the contours of this outline so that filling it with the nonzero winding rule
+    /// covers what filling it with `fill_rule` did before.
+    ///
+    /// Contours with filled area on their inside become clockwise, and contours around holes
+    /// become counterclockwise. Contours that don't separate filled area from unfilled area are
+    /// removed. Pass `FillRule::EvenOdd` for geometry, such as many CAD exports, whose holes are
+    /// marked only by nesting and not by orientation.
+    ///
+    /// This assumes that contours don't cross themselves or each other. Each contour is judged by
+    /// testing points on either side of it, near the middle of its length.
+    pub fn normalize_orientation(&mut self, fill_rule: FillRule) {
+        // Decide on every contour before changing any of them.
+        let orientations: Vec<_> = self.contours.iter().map(|contour| {
+            let (inside, contour_winding_number) = match contour.point_just_inside() {
+                None => return None,
+                Some(inside) => inside,
+            };
+            let winding_number = self.winding_number(inside);
+            let inside_is_filled = fill_rule.is_inside(winding_number);
+            let outside_is_filled = fill_rule.is_inside(winding_number - contour_winding_number);
+            match (inside_is_filled, outside_is_filled) {
+                (true, false) => Some(contour_winding_number > 0),
+                (false, true) => Some(contour_winding_number < 0),
+                _ => None,
+            }
+        }).collect();
+
+        let contours = mem::replace(&mut self.contours, vec![]);
+        self.clear();
+        for (mut contour, keep_orientation) in contours.into_iter().zip(orientations) {
+            match keep_orientation {
+                None => continue,
+                Some(false) => contour.reverse(),
+                Some(true) => {}
+            }
+            self.push_contour(contour);
+        }
+    }
+}
+
+impl Contour {
+    /// Returns the number of times this contour, treated as closed, winds around `point`.
+    pub fn winding_number(&self, point: Vector2F) -> i32 {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let first_position = self.position_of(0);
+        let mut from = first_position;
+        let mut winding_number = 0;
+        for segment in self.iter() {
+            segment.flatten(FLATTENING_TOLERANCE, |to, _| {
+                winding_number += edge_winding_number(from, to, point);
+                from = to;
+            });
+        }
+        winding_number + edge_winding_number(from, first_position, point)
+    }
+
+    // Returns a point just inside this contour, near the middle of its length, along with the
+    // number of times the contour winds around it. Returns `None` if the contour encloses no
+    // area there.
+    fn point_just_inside(&self) -> Option<(Vector2F, i32)> {
+        let measure = ContourMeasure::new(self, FLATTENING_TOLERANCE);
+        let bounds = self.bounds();
+        let offset_length = f32::max(bounds.width(), bounds.height()) * SIDE_SAMPLE_DISTANCE;
+
+        // A sample point at a corner may land on one of the edges that meet there, so try a few.
+        for &fraction in &SIDE_SAMPLE_FRACTIONS {
+            let distance = measure.length() * fraction;
+            let (point, tangent) = (measure.point_at_length(distance),
+                                    measure.tangent_at_length(distance));
+            let offset = Vector2F::new(-tangent.y(), tangent.x()).scale(offset_length);
+            for &side in &[point + offset, point - offset] {
+                let winding_number = self.winding_number(side);
+                if winding_number != 0 {
+                    return Some((side, winding_number));
+                }
+            }
+        }
+        None
+    }
+}
+
+// Counts the crossing of the edge from `from` to `to` with a horizontal ray from `point` out to
+// positive infinity. Edges going down in the Y-down coordinate system count as positive.
+fn edge_winding_number(from: Vector2F, to: Vector2F, point: Vector2F) -> i32 {
+    let side = (to - from).det(point - from);
+    if from.y() <= point.y() {
+        if to.y() > point.y() && side > 0.0 {
+            return 1;
+        }
+    } else if to.y() <= point.y() && side < 0.0 {
+        return -1;
+    }
+    0
+}
@@ -9,35 +9,96 @@
 // except according to those terms.
 
 use crate::sorted_vector::SortedVector;
-use pathfinder_color::ColorU;
+use alloc::vec::Vec;
+use pathfinder_color::{ColorF, ColorU};
 use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
 use pathfinder_simd::default::F32x4;
-use std::cmp::{self, Ordering, PartialOrd};
-use std::convert;
-use std::hash::{Hash, Hasher};
-use std::mem;
+use core::cmp::{Ordering, PartialOrd};
+use core::convert;
+use core::hash::{Hash, Hasher};
+
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
 pub struct Gradient {
-    line: LineSegment2F,
+    geometry: GradientGeometry,
+    #[cfg_attr(feature = "pf-serde", serde(deserialize_with = "deserialize_stops"))]
     stops: SortedVector<ColorStop>,
+    wrap: GradientWrap,
+    /// Maps gradient space, in which the geometry is specified, to scene coordinates.
+    transform: Transform2F,
 }
 
+// `offset` comes first, so that the derived ordering sorts stops by it.
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
 pub struct ColorStop {
-    pub color: ColorU,
     pub offset: f32,
+    pub color: ColorU,
+}
+
+/// The shape of a gradient, in gradient space.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
+pub enum GradientGeometry {
+    /// The color varies along the line, which runs from offset 0 to offset 1.
+    Linear(LineSegment2F),
+    /// A two-point conical gradient, as in HTML canvas `createRadialGradient()`: offset 0 is the
+    /// circle of `start_radius` around the start of the line, and offset 1 is the circle of
+    /// `end_radius` around its end.
+    Radial { line: LineSegment2F, start_radius: f32, end_radius: f32 },
+}
+
+/// What a gradient does beyond offsets 0 and 1, like the SVG `spreadMethod` attribute.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
+pub enum GradientWrap {
+    /// The colors of the end stops extend outward.
+    Clamp,
+    /// The gradient repeats.
+    Repeat,
+    /// The gradient repeats, reversing direction every other time.
+    Reflect,
+}
+
+impl Default for GradientWrap {
+    #[inline]
+    fn default() -> GradientWrap {
+        GradientWrap::Clamp
+    }
 }
 
 impl Eq for Gradient {}
 
 impl Hash for Gradient {
     fn hash<H>(&self, state: &mut H) where H: Hasher {
-        unsafe {
-            let data: [u32; 4] = mem::transmute::<F32x4, [u32; 4]>(self.line.0);
-            data.hash(state);
-            self.stops.hash(state);
+        let (line, radii) = match self.geometry {
+            GradientGeometry::Linear(line) => (line, None),
+            GradientGeometry::Radial { line, start_radius, end_radius } => {
+                (line, Some((start_radius.to_bits(), end_radius.to_bits())))
+            }
+        };
+        for value in &[
+            line.from_x(),
+            line.from_y(),
+            line.to_x(),
+            line.to_y(),
+            self.transform.m11(),
+            self.transform.m12(),
+            self.transform.m21(),
+            self.transform.m22(),
+            self.transform.vector.x(),
+            self.transform.vector.y(),
+        ] {
+            value.to_bits().hash(state);
         }
+        radii.hash(state);
+        self.stops.hash(state);
+        self.wrap.hash(state);
     }
 }
 
@@ -45,28 +106,57 @@ impl Eq for ColorStop {}
 
 impl Hash for ColorStop {
     fn hash<H>(&self, state: &mut H) where H: Hasher {
-        unsafe {
-            self.color.hash(state);
-            let offset = mem::transmute::<f32, u32>(self.offset);
-            offset.hash(state);
-        }
+        self.color.hash(state);
+        self.offset.to_bits().hash(state);
     }
 }
 
 impl Gradient {
+    /// Creates a linear gradient along `line`.
     #[inline]
     pub fn new(line: LineSegment2F) -> Gradient {
-        Gradient { line, stops: SortedVector::new() }
+        Gradient::from_geometry(GradientGeometry::Linear(line))
     }
 
+    /// Creates a two-point conical gradient. See `GradientGeometry::Radial`.
     #[inline]
+    pub fn radial(line: LineSegment2F, start_radius: f32, end_radius: f32) -> Gradient {
+        Gradient::from_geometry(GradientGeometry::Radial { line, start_radius, end_radius })
+    }
+
+    #[inline]
+    pub fn from_geometry(geometry: GradientGeometry) -> Gradient {
+        Gradient {
+            geometry,
+            stops: SortedVector::new(),
+            wrap: GradientWrap::default(),
+            transform: Transform2F::default(),
+        }
+    }
+
+    /// Adds a stop. Stops at the same offset keep the order they were added in, so that they
+    /// make a hard transition from the first color to the last.
     pub fn add_color_stop(&mut self, stop: ColorStop) {
-        self.stops.push(stop);
+        let index = self.stops
+                        .array
+                        .iter()
+                        .position(|other| other.offset > stop.offset)
+                        .unwrap_or(self.stops.len());
+        self.stops.array.insert(index, stop);
     }
 
+    #[inline]
+    pub fn geometry(&self) -> &GradientGeometry {
+        &self.geometry
+    }
+
+    /// The line from offset 0 to offset 1, in gradient space. For radial gradients, this runs
+    /// between the centers of the circles.
     #[inline]
     pub fn line(&self) -> LineSegment2F {
-        self.line
+        match self.geometry {
+            GradientGeometry::Linear(line) | GradientGeometry::Radial { line, .. } => line,
+        }
     }
 
     #[inline]
@@ -74,17 +164,53 @@ impl Gradient {
         &self.stops.array
     }
 
+    #[inline]
+    pub fn wrap(&self) -> GradientWrap {
+        self.wrap
+    }
+
+    #[inline]
+    pub fn set_wrap(&mut self, wrap: GradientWrap) {
+        self.wrap = wrap
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Transform2F {
+        self.transform
+    }
+
+    /// Returns the color at offset `t`, after wrapping it.
     pub fn sample(&self, t: f32) -> ColorU {
         if self.stops.is_empty() {
             return ColorU::transparent_black();
         }
 
-        let lower_index = self.stops.binary_search_by(|stop| {
-            stop.offset.partial_cmp(&t).unwrap_or(Ordering::Less)
-        }).unwrap_or_else(convert::identity);
-        let upper_index = cmp::min(lower_index + 1, self.stops.len() - 1);
+        let t = match self.wrap {
+            GradientWrap::Clamp => t,
+            GradientWrap::Repeat => t - t.floor(),
+            GradientWrap::Reflect => {
+                let t = (t * 0.5 - (t * 0.5).floor()) * 2.0;
+                if t > 1.0 { 2.0 - t } else { t }
+            }
+        };
+
+        let first_stop = &self.stops.array[0];
+        let last_stop = &self.stops.array[self.stops.len() - 1];
+        if t.is_nan() || t <= first_stop.offset {
+            return first_stop.color;
+        }
+        if t >= last_stop.offset {
+            return last_stop.color;
+        }
 
-        let lower_stop = &self.stops.array[lower_index];
+        // Find the first stop after `t`.
+        let upper_index = self.stops.binary_search_by(|stop| {
+            match stop.offset.partial_cmp(&t).unwrap_or(Ordering::Less) {
+                Ordering::Equal => Ordering::Less,
+                ordering => ordering,
+            }
+        }).unwrap_or_else(convert::identity);
+        let lower_stop = &self.stops.array[upper_index - 1];
         let upper_stop = &self.stops.array[upper_index];
 
         let denom = upper_stop.offset - lower_stop.offset;
@@ -98,9 +224,303 @@ impl Gradient {
                   .to_u8()
     }
 
+    /// Returns true if this gradient has a color at every point, so that `offset_at()` never
+    /// returns `None`.
+    ///
+    /// Linear gradients cover the plane unless they're degenerate. Two-point conical gradients
+    /// only do if one circle lies strictly inside the other; otherwise they cover a cone, and
+    /// the rest is transparent.
+    pub fn covers_plane(&self) -> bool {
+        if self.transform.matrix.det() == 0.0 {
+            return false;
+        }
+        match self.geometry {
+            GradientGeometry::Linear(line) => {
+                let length_squared = line.vector().square_length();
+                length_squared != 0.0 && length_squared.is_finite()
+            }
+            GradientGeometry::Radial { line, start_radius, end_radius } => {
+                let radius_delta = end_radius - start_radius;
+                line.vector().square_length() < radius_delta * radius_delta - EPSILON
+            }
+        }
+    }
+
+    /// Returns the unwrapped offset of the gradient at `point`, in scene coordinates, or `None`
+    /// if the gradient doesn't cover the point.
+    pub fn offset_at(&self, point: Vector2F) -> Option<f32> {
+        let point = self.transform.inverse() * point;
+        match self.geometry {
+            GradientGeometry::Linear(line) => {
+                let vector = line.vector();
+                let length_squared = vector.square_length();
+                if length_squared == 0.0 || !length_squared.is_finite() {
+                    return None;
+                }
+                Some((point - line.from()).dot(vector) / length_squared)
+            }
+            GradientGeometry::Radial { line, start_radius, end_radius } => {
+                // Find the largest `t` for which `point` lies on the circle interpolated between
+                // the start and end circles, and whose radius isn't negative.
+                let (center_delta, radius_delta) = (line.vector(), end_radius - start_radius);
+                let point_delta = point - line.from();
+                let a = center_delta.square_length() - radius_delta * radius_delta;
+                let b = point_delta.dot(center_delta) + start_radius * radius_delta;
+                let c = point_delta.square_length() - start_radius * start_radius;
+
+                let is_valid = |t: f32| t.is_finite() && start_radius + t * radius_delta >= 0.0;
+                if a.abs() < EPSILON {
+                    if b.abs() < EPSILON {
+                        return None;
+                    }
+                    let t = c / (2.0 * b);
+                    return if is_valid(t) { Some(t) } else { None };
+                }
+
+                let discriminant = b * b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                let (t0, t1) = ((b + root) / a, (b - root) / a);
+                let (t_max, t_min) = if t0 > t1 { (t0, t1) } else { (t1, t0) };
+                if is_valid(t_max) {
+                    Some(t_max)
+                } else if is_valid(t_min) {
+                    Some(t_min)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns the color at `point`, in scene coordinates.
+    #[inline]
+    pub fn sample_at(&self, point: Vector2F) -> ColorU {
+        match self.offset_at(point) {
+            None => ColorU::transparent_black(),
+            Some(t) => self.sample(t),
+        }
+    }
+
+    #[inline]
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        self.transform = *transform * self.transform;
+    }
+
     pub fn set_opacity(&mut self, alpha: f32) {
         for stop in &mut self.stops.array {
             stop.color.a = (stop.color.a as f32 * alpha).round() as u8;
         }
     }
 }
+
+/// Samples a gradient at four offsets at a time, for filling lookup tables quickly.
+///
+/// The colors are the same as those of `Gradient::sample()`, except that they aren't rounded to
+/// 8 bits per channel.
+pub struct GradientSampler {
+    segments: Vec<GradientSegment>,
+    wrap: GradientWrap,
+}
+
+// The colors from one stop to the next. The last segment holds the color of the last stop.
+#[derive(Clone, Copy)]
+struct GradientSegment {
+    start_color: F32x4,
+    color_delta: F32x4,
+    start_offset: f32,
+    end_offset: f32,
+    // The reciprocal of the distance between the stops, or zero if they coincide.
+    scale: f32,
+}
+
+impl GradientSampler {
+    pub fn new(gradient: &Gradient) -> GradientSampler {
+        let stops = gradient.stops();
+        let segments = stops.iter().enumerate().map(|(stop_index, stop)| {
+            let start_color = stop.color.to_f32().0;
+            match stops.get(stop_index + 1) {
+                None => {
+                    GradientSegment {
+                        start_color,
+                        color_delta: F32x4::default(),
+                        start_offset: stop.offset,
+                        end_offset: stop.offset,
+                        scale: 0.0,
+                    }
+                }
+                Some(next_stop) => {
+                    let denom = next_stop.offset - stop.offset;
+                    GradientSegment {
+                        start_color,
+                        color_delta: next_stop.color.to_f32().0 - start_color,
+                        start_offset: stop.offset,
+                        end_offset: next_stop.offset,
+                        scale: if denom == 0.0 { 0.0 } else { 1.0 / denom },
+                    }
+                }
+            }
+        }).collect();
+        GradientSampler { segments, wrap: gradient.wrap() }
+    }
+
+    /// Returns the colors at the four offsets in `t`, after wrapping them.
+    pub fn sample_x4(&self, t: F32x4) -> [ColorF; 4] {
+        if self.segments.is_empty() {
+            return [ColorF::transparent_black(); 4];
+        }
+
+        let t = match self.wrap {
+            GradientWrap::Clamp => t,
+            GradientWrap::Repeat => t - t.floor(),
+            GradientWrap::Reflect => {
+                let half_t = t * F32x4::splat(0.5);
+                let t = (half_t - half_t.floor()) * F32x4::splat(2.0);
+                F32x4::splat(1.0) - (t - F32x4::splat(1.0)).abs()
+            }
+        };
+
+        // Finding the segments is scalar, but neighboring offsets usually share one, so try the
+        // segment of the previous lane first.
+        let (mut lane_t, mut start_offsets, mut scales) = ([0.0; 4], [0.0; 4], [0.0; 4]);
+        let mut segment_indices = [0; 4];
+        let mut segment_index = 0;
+        for lane in 0..4 {
+            let (t, segment) = self.find_segment(t[lane], &mut segment_index);
+            lane_t[lane] = t;
+            start_offsets[lane] = segment.start_offset;
+            scales[lane] = segment.scale;
+            segment_indices[lane] = segment_index;
+        }
+
+        let fractions = (F32x4::from_slice(&lane_t) - F32x4::from_slice(&start_offsets)) *
+            F32x4::from_slice(&scales);
+        let mut colors = [ColorF::transparent_black(); 4];
+        for lane in 0..4 {
+            let segment = &self.segments[segment_indices[lane]];
+            colors[lane] = ColorF(segment.start_color +
+                                  segment.color_delta * F32x4::splat(fractions[lane]));
+        }
+        colors
+    }
+
+    // Returns the segment that the wrapped offset `t` falls in, along with `t` clamped to the
+    // stops. `segment_index` is tried first, and updated.
+    fn find_segment(&self, t: f32, segment_index: &mut usize) -> (f32, &GradientSegment) {
+        let first_segment = &self.segments[0];
+        let last_segment = &self.segments[self.segments.len() - 1];
+        if t.is_nan() || t <= first_segment.start_offset {
+            *segment_index = 0;
+            return (first_segment.start_offset, first_segment);
+        }
+        if t >= last_segment.start_offset {
+            *segment_index = self.segments.len() - 1;
+            return (last_segment.start_offset, last_segment);
+        }
+
+        let segment = &self.segments[*segment_index];
+        if segment.start_offset <= t && t < segment.end_offset {
+            return (t, segment);
+        }
+
+        // Find the last segment that starts at or before `t`.
+        *segment_index = self.segments.binary_search_by(|segment| {
+            match segment.start_offset.partial_cmp(&t).unwrap_or(Ordering::Less) {
+                Ordering::Equal => Ordering::Less,
+                ordering => ordering,
+            }
+        }).unwrap_or_else(convert::identity) - 1;
+        (t, &self.segments[*segment_index])
+    }
+}
+
+const EPSILON: f32 = 1.0e-6;
+
+// Stops are sorted by offset as they're loaded, keeping the order of stops at the same offset,
+// just as `add_color_stop()` does. The derived ordering would also compare the colors of stops
+// at the same offset, turning hard transitions around.
+#[cfg(feature = "pf-serde")]
+fn deserialize_stops<'de, D>(deserializer: D) -> Result<SortedVector<ColorStop>, D::Error>
+                             where D: Deserializer<'de> {
+    let mut stops = Vec::<ColorStop>::deserialize(deserializer)?;
+    stops.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap_or(Ordering::Equal));
+    Ok(SortedVector { array: stops })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gradient::{ColorStop, Gradient};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    #[cfg(feature = "pf-serde")]
+    use crate::gradient::GradientWrap;
+    #[cfg(feature = "pf-serde")]
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    const RED: ColorU = ColorU { r: 255, g: 0, b: 0, a: 255 };
+    const BLUE: ColorU = ColorU { r: 0, g: 0, b: 255, a: 255 };
+
+    fn gradient() -> Gradient {
+        Gradient::new(LineSegment2F::new(Vector2F::default(), Vector2F::new(1.0, 0.0)))
+    }
+
+    // Red would sort before blue by color, so this only passes if stops are sorted by offset.
+    #[test]
+    fn test_stops_are_sorted_by_offset() {
+        let mut gradient = gradient();
+        gradient.add_color_stop(ColorStop { offset: 1.0, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.0, color: BLUE });
+        assert_eq!(gradient.sample(0.0), BLUE);
+        assert_eq!(gradient.sample(1.0), RED);
+        assert_eq!(gradient.sample(0.5), ColorU { r: 128, g: 0, b: 128, a: 255 });
+    }
+
+    #[test]
+    fn test_stops_at_the_same_offset_keep_their_order() {
+        let mut gradient = gradient();
+        gradient.add_color_stop(ColorStop { offset: 0.0, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.5, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.5, color: BLUE });
+        gradient.add_color_stop(ColorStop { offset: 1.0, color: BLUE });
+        assert_eq!(gradient.sample(0.49), RED);
+        assert_eq!(gradient.sample(0.51), BLUE);
+    }
+
+    #[cfg(feature = "pf-serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut gradient = Gradient::radial(LineSegment2F::new(Vector2F::new(1.0, 2.0),
+                                                               Vector2F::new(3.0, 4.0)),
+                                            0.5,
+                                            8.0);
+        gradient.add_color_stop(ColorStop { offset: 0.0, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.5, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.5, color: BLUE });
+        gradient.add_color_stop(ColorStop { offset: 1.0, color: BLUE });
+        gradient.set_wrap(GradientWrap::Reflect);
+        let transform = Transform2F::from_scale(Vector2F::new(2.0, 0.5));
+        gradient.apply_transform(&transform.translate(Vector2F::new(3.0, -1.0)));
+
+        let json = serde_json::to_string(&gradient).unwrap();
+        assert_eq!(serde_json::from_str::<Gradient>(&json).unwrap(), gradient);
+    }
+
+    #[cfg(feature = "pf-serde")]
+    #[test]
+    fn test_serde_sorts_stops_by_offset() {
+        let mut gradient = gradient();
+        gradient.add_color_stop(ColorStop { offset: 0.0, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.5, color: RED });
+        gradient.add_color_stop(ColorStop { offset: 0.5, color: BLUE });
+        gradient.add_color_stop(ColorStop { offset: 1.0, color: BLUE });
+
+        // Blue sorts before red by color, so this also checks that the stops at 0.5 stay in order.
+        let mut json = serde_json::to_value(&gradient).unwrap();
+        json["stops"].as_array_mut().unwrap().swap(0, 3);
+        let loaded: Gradient = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.stops(), gradient.stops());
+    }
+}
@@ -0,0 +1,265 @@
+// pathfinder/content/src/hatch.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hatching, which fills an outline with evenly spaced parallel lines, as technical drawings do.
+//!
+//! Each hatch line is an open contour of one line segment, clipped exactly to the inside of the
+//! outline. Stroke the hatched outline, for example with `OutlineStrokeToFill`, to draw it.
+
+use crate::fill::FillRule;
+use crate::outline::{Contour, Outline};
+use crate::segment::Segment;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::f32::consts::FRAC_PI_2;
+
+// How far flattened curves may stray from the outline, in its units.
+const TOLERANCE: f32 = 0.1;
+
+/// Fills an outline with hatch lines.
+pub struct OutlineHatch<'a> {
+    input: &'a Outline,
+    output: Outline,
+    fill_rule: FillRule,
+    style: HatchStyle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HatchStyle {
+    /// The angle of the lines from the X axis, in radians.
+    pub angle: f32,
+    /// The distance between neighboring lines.
+    pub spacing: f32,
+    /// The distance of the lines from the origin, modulo `spacing`. Shapes hatched with the same
+    /// angle, spacing, and offset have lines that line up with each other.
+    pub offset: f32,
+    /// Whether to draw a second set of lines perpendicular to the first.
+    pub cross_hatch: bool,
+}
+
+// An edge of the flattened outline, in a space where hatch lines are horizontal, with its upper
+// end first.
+#[derive(Clone, Copy)]
+struct HatchEdge {
+    line: LineSegment2F,
+    winding: i32,
+}
+
+impl<'a> OutlineHatch<'a> {
+    /// Creates a hatcher for the inside of `input`, as `fill_rule` defines it. Open contours are
+    /// treated as closed, as they are when filled.
+    #[inline]
+    pub fn new(input: &'a Outline, fill_rule: FillRule, style: HatchStyle) -> OutlineHatch<'a> {
+        OutlineHatch { input, output: Outline::new(), fill_rule, style }
+    }
+
+    pub fn hatch(&mut self) {
+        if self.style.spacing.is_nan() || self.style.spacing <= 0.0 {
+            return;
+        }
+        self.hatch_at_angle(self.style.angle);
+        if self.style.cross_hatch {
+            self.hatch_at_angle(self.style.angle + FRAC_PI_2);
+        }
+    }
+
+    #[inline]
+    pub fn into_outline(self) -> Outline {
+        self.output
+    }
+
+    fn hatch_at_angle(&mut self, angle: f32) {
+        let from_hatch_space = Transform2F::from_rotation(angle);
+        let mut edges = self.edges_in_hatch_space(&from_hatch_space.inverse());
+        if edges.is_empty() {
+            return;
+        }
+        edges.sort_by(|a, b| {
+            a.line.from_y().partial_cmp(&b.line.from_y()).unwrap_or(Ordering::Equal)
+        });
+        let max_y = edges.iter().fold(edges[0].line.to_y(), |max_y, edge| {
+            f32::max(max_y, edge.line.to_y())
+        });
+
+        // Sweep down the lines, keeping the edges that cross the current one.
+        let (spacing, offset) = (self.style.spacing, self.style.offset);
+        let mut line_index = f32::ceil((edges[0].line.from_y() - offset) / spacing);
+        let (mut next_edge_index, mut active_edges) = (0, vec![]);
+        let mut crossings: Vec<(f32, i32)> = vec![];
+        loop {
+            let y = offset + line_index * spacing;
+            if y > max_y {
+                break;
+            }
+            line_index += 1.0;
+
+            while next_edge_index < edges.len() && edges[next_edge_index].line.from_y() <= y {
+                active_edges.push(edges[next_edge_index]);
+                next_edge_index += 1;
+            }
+            // Edges include their upper end but not their lower one, so that a line through a
+            // vertex crosses only one of the edges that meet there.
+            active_edges.retain(|edge: &HatchEdge| edge.line.to_y() > y);
+
+            crossings.clear();
+            crossings.extend(active_edges.iter().map(|edge| {
+                let t = (y - edge.line.from_y()) / (edge.line.to_y() - edge.line.from_y());
+                (edge.line.sample(t).x(), edge.winding)
+            }));
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+            self.push_spans(&crossings, y, &from_hatch_space);
+        }
+    }
+
+    fn edges_in_hatch_space(&self, to_hatch_space: &Transform2F) -> Vec<HatchEdge> {
+        let mut edges = vec![];
+        let mut push_edge = |from: Vector2F, to: Vector2F| {
+            if from.y() < to.y() {
+                edges.push(HatchEdge { line: LineSegment2F::new(from, to), winding: 1 });
+            } else if from.y() > to.y() {
+                edges.push(HatchEdge { line: LineSegment2F::new(to, from), winding: -1 });
+            }
+        };
+
+        for contour in &self.input.contours {
+            let mut segments: Vec<Segment> = contour.iter().collect();
+            if !contour.is_closed() && contour.len() > 1 {
+                let last = contour.position_of(contour.len() - 1);
+                segments.push(Segment::line(LineSegment2F::new(last, contour.position_of(0))));
+            }
+            for segment in segments {
+                let segment = segment.transform(to_hatch_space);
+                let mut from = segment.baseline.from();
+                segment.flatten(TOLERANCE, |to, _| {
+                    push_edge(from, to);
+                    from = to;
+                });
+            }
+        }
+        edges
+    }
+
+    // Pushes the parts of the line at `y` that are inside the outline, given where it crosses the
+    // edges, from left to right.
+    fn push_spans(&mut self, crossings: &[(f32, i32)], y: f32, from_hatch_space: &Transform2F) {
+        let (mut winding, mut span_start) = (0, None);
+        for &(x, edge_winding) in crossings {
+            winding += edge_winding;
+            let inside = match self.fill_rule {
+                FillRule::Winding => winding != 0,
+                FillRule::EvenOdd => winding % 2 != 0,
+            };
+            match span_start {
+                None if inside => span_start = Some(x),
+                Some(start_x) if !inside => {
+                    span_start = None;
+                    if x > start_x {
+                        let mut contour = Contour::with_capacity(2);
+                        contour.push_endpoint(*from_hatch_space * Vector2F::new(start_x, y));
+                        contour.push_endpoint(*from_hatch_space * Vector2F::new(x, y));
+                        self.output.push_contour(contour);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::hatch::{HatchStyle, OutlineHatch};
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::vector::Vector2F;
+    use core::f32::consts::FRAC_PI_4;
+
+    const EPSILON: f32 = 0.001;
+
+    fn square(x: f32, y: f32, size: f32) -> Contour {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(x, y));
+        contour.push_endpoint(Vector2F::new(x + size, y));
+        contour.push_endpoint(Vector2F::new(x + size, y + size));
+        contour.push_endpoint(Vector2F::new(x, y + size));
+        contour.close();
+        contour
+    }
+
+    fn hatch(outline: &Outline, fill_rule: FillRule, style: HatchStyle) -> Outline {
+        let mut hatch = OutlineHatch::new(outline, fill_rule, style);
+        hatch.hatch();
+        hatch.into_outline()
+    }
+
+    fn lines(outline: &Outline) -> Vec<(Vector2F, Vector2F)> {
+        outline.contours().iter().map(|contour| {
+            assert!(!contour.is_closed() && contour.len() == 2);
+            (contour.position_of(0), contour.position_of(1))
+        }).collect()
+    }
+
+    fn total_length(outline: &Outline) -> f32 {
+        lines(outline).iter().map(|&(from, to)| (to - from).length()).sum()
+    }
+
+    fn style(angle: f32, spacing: f32, offset: f32) -> HatchStyle {
+        HatchStyle { angle, spacing, offset, cross_hatch: false }
+    }
+
+    #[test]
+    fn test_hatch_square() {
+        let mut outline = Outline::new();
+        outline.push_contour(square(0.0, 0.0, 10.0));
+
+        // Lines through the bottom edge aren't inside.
+        let hatched = hatch(&outline, FillRule::Winding, style(0.0, 2.0, 1.0));
+        let expected: Vec<_> = [1.0, 3.0, 5.0, 7.0, 9.0].iter().map(|&y| {
+            (Vector2F::new(0.0, y), Vector2F::new(10.0, y))
+        }).collect();
+        assert_eq!(lines(&hatched), expected);
+
+        // Cross-hatching adds as many lines again, perpendicular to the first.
+        let cross_hatched = hatch(&outline,
+                                  FillRule::Winding,
+                                  HatchStyle { cross_hatch: true, ..style(0.0, 2.0, 1.0) });
+        assert_eq!(cross_hatched.len(), 10);
+        for &(from, to) in &lines(&cross_hatched)[5..] {
+            assert!((from.x() - to.x()).abs() < EPSILON);
+        }
+
+        // Diagonal lines cover the area once per spacing.
+        let diagonal = hatch(&outline, FillRule::Winding, style(FRAC_PI_4, 0.1, 0.0));
+        assert!((total_length(&diagonal) - 1000.0).abs() < 2.0);
+
+        // Spacings that aren't positive draw nothing.
+        assert_eq!(hatch(&outline, FillRule::Winding, style(0.0, 0.0, 0.0)).len(), 0);
+    }
+
+    #[test]
+    fn test_hatch_fill_rules() {
+        // A square inside another, wound the same way, is a hole only with the even-odd rule.
+        let mut outline = Outline::new();
+        outline.push_contour(square(0.0, 0.0, 10.0));
+        outline.push_contour(square(4.0, 4.0, 2.0));
+
+        let winding = hatch(&outline, FillRule::Winding, style(0.0, 1.0, 0.5));
+        assert_eq!(winding.len(), 10);
+        assert!((total_length(&winding) - 100.0).abs() < EPSILON);
+
+        let even_odd = hatch(&outline, FillRule::EvenOdd, style(0.0, 1.0, 0.5));
+        assert_eq!(even_odd.len(), 12);
+        assert!((total_length(&even_odd) - 96.0).abs() < EPSILON);
+    }
+}
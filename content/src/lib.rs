@@ -11,20 +11,38 @@
 //! Pathfinder's representation of a vector scene.
 //! 
 //! This module also contains various path utilities.
+//!
+//! Without the default `std` feature, this crate only needs `core` and `alloc`. Decoding images
+//! with the `pf-image` feature needs `std`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 #[macro_use]
 extern crate bitflags;
 #[macro_use]
 extern crate log;
 
+pub mod boolean;
 pub mod clip;
 pub mod dash;
+pub mod effects;
+pub mod fill;
 pub mod gradient;
+pub mod hatch;
+pub mod measure;
+pub mod morph;
 pub mod orientation;
 pub mod outline;
+pub mod pattern;
+pub mod raster;
+pub mod rounded_rect;
 pub mod segment;
+pub mod simplify;
 pub mod sorted_vector;
+pub mod spline;
 pub mod stroke;
 pub mod transform;
+pub mod warp;
 
 mod dilation;
@@ -0,0 +1,338 @@
+// pathfinder/content/src/measure.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Arc length measurement of paths.
+//!
+//! Contours are flattened adaptively to within a tolerance, and distances are then looked up in
+//! a table of cumulative lengths. This is useful for placing text or markers along a path.
+
+use crate::outline::{Contour, Outline};
+use pathfinder_geometry::util;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Measures distances along a single contour.
+#[derive(Clone, Debug)]
+pub struct ContourMeasure {
+    points: Vec<Vector2F>,
+    // The distance from the start of the contour to each point.
+    lengths: Vec<f32>,
+    closed: bool,
+}
+
+/// Measures distances along all the contours of an outline, one after another.
+#[derive(Clone, Debug)]
+pub struct OutlineMeasure {
+    contours: Vec<ContourMeasure>,
+}
+
+impl ContourMeasure {
+    /// Measures `contour`, flattening curves to within `tolerance`.
+    pub fn new(contour: &Contour, tolerance: f32) -> ContourMeasure {
+        let (mut points, mut lengths) = (vec![], vec![]);
+        if !contour.is_empty() {
+            points.push(contour.position_of(0));
+            lengths.push(0.0);
+        }
+
+        let mut length = 0.0;
+        for segment in contour.iter() {
+            segment.flatten(tolerance, |point, _| {
+                let last_point = points[points.len() - 1];
+                if point != last_point {
+                    length += (point - last_point).length();
+                    points.push(point);
+                    lengths.push(length);
+                }
+            });
+        }
+
+        ContourMeasure { points, lengths, closed: contour.is_closed() }
+    }
+
+    /// The total length of the contour, including the closing segment if it's closed.
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.lengths.last().cloned().unwrap_or(0.0)
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the point at `distance` along the contour. The distance is clamped to the length
+    /// of the contour.
+    pub fn point_at_length(&self, distance: f32) -> Vector2F {
+        match self.locate(distance) {
+            None => self.points.get(0).cloned().unwrap_or_default(),
+            Some((line_index, t)) => {
+                self.points[line_index].lerp(self.points[line_index + 1], t)
+            }
+        }
+    }
+
+    /// Returns the unit tangent vector at `distance` along the contour, pointing in the direction
+    /// of travel. At a corner, this is the direction of the segment after the corner. The
+    /// distance is clamped to the length of the contour.
+    pub fn tangent_at_length(&self, distance: f32) -> Vector2F {
+        match self.locate(distance) {
+            None => Vector2F::default(),
+            Some((line_index, _)) => {
+                (self.points[line_index + 1] - self.points[line_index]).normalize()
+            }
+        }
+    }
+
+    /// Returns the part of the contour between the distances `start` and `end`, as an open
+    /// polyline. The distances are clamped to the length of the contour, and the result is empty
+    /// if `end` comes before `start`.
+    pub fn sub_contour(&self, start: f32, end: f32) -> Contour {
+        let mut contour = Contour::new();
+        let (start_location, end_location) = match (self.locate(start), self.locate(end)) {
+            (Some(start_location), Some(end_location)) => (start_location, end_location),
+            _ => return contour,
+        };
+        if start_location > end_location {
+            return contour;
+        }
+
+        let (start_line_index, start_t) = start_location;
+        let (end_line_index, end_t) = end_location;
+        let mut push_point = |point| {
+            if contour.last_position() != Some(point) {
+                contour.push_endpoint(point);
+            }
+        };
+        push_point(self.points[start_line_index].lerp(self.points[start_line_index + 1], start_t));
+        for point_index in (start_line_index + 1)..=end_line_index {
+            push_point(self.points[point_index]);
+        }
+        push_point(self.points[end_line_index].lerp(self.points[end_line_index + 1], end_t));
+        contour
+    }
+
+    // Returns the index of the line containing `distance`, and the parameter along that line.
+    fn locate(&self, distance: f32) -> Option<(usize, f32)> {
+        if self.points.len() < 2 {
+            return None;
+        }
+
+        let distance = util::clamp(distance, 0.0, self.length());
+        let line_index = match self.lengths.binary_search_by(|length| {
+            length.partial_cmp(&distance).unwrap_or(Ordering::Less)
+        }) {
+            Ok(point_index) | Err(point_index) if point_index == 0 => 0,
+            Ok(point_index) => point_index,
+            Err(point_index) => point_index - 1,
+        };
+        let line_index = usize::min(line_index, self.points.len() - 2);
+
+        let (start, end) = (self.lengths[line_index], self.lengths[line_index + 1]);
+        Some((line_index, util::clamp((distance - start) / (end - start), 0.0, 1.0)))
+    }
+}
+
+impl OutlineMeasure {
+    /// Measures every contour of `outline`, flattening curves to within `tolerance`.
+    pub fn new(outline: &Outline, tolerance: f32) -> OutlineMeasure {
+        OutlineMeasure {
+            contours: outline.contours()
+                             .iter()
+                             .map(|contour| ContourMeasure::new(contour, tolerance))
+                             .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn contours(&self) -> &[ContourMeasure] {
+        &self.contours
+    }
+
+    /// The sum of the lengths of all contours.
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.contours.iter().map(|contour| contour.length()).sum()
+    }
+
+    /// Returns the point at `distance` along the outline, where each contour starts where the
+    /// previous one ends. The distance is clamped to the length of the outline.
+    pub fn point_at_length(&self, distance: f32) -> Vector2F {
+        match self.locate(distance) {
+            None => Vector2F::default(),
+            Some((contour, distance)) => contour.point_at_length(distance),
+        }
+    }
+
+    /// Returns the unit tangent vector at `distance` along the outline.
+    pub fn tangent_at_length(&self, distance: f32) -> Vector2F {
+        match self.locate(distance) {
+            None => Vector2F::default(),
+            Some((contour, distance)) => contour.tangent_at_length(distance),
+        }
+    }
+
+    fn locate(&self, mut distance: f32) -> Option<(&ContourMeasure, f32)> {
+        let mut last_contour = None;
+        for contour in &self.contours {
+            if contour.length() == 0.0 {
+                continue;
+            }
+            if distance <= contour.length() {
+                return Some((contour, distance));
+            }
+            distance -= contour.length();
+            last_contour = Some(contour);
+        }
+        last_contour.map(|contour| (contour, contour.length()))
+    }
+}
+
+impl Contour {
+    /// Returns the length of this contour, flattening curves to within `tolerance`.
+    #[inline]
+    pub fn length(&self, tolerance: f32) -> f32 {
+        ContourMeasure::new(self, tolerance).length()
+    }
+}
+
+impl Outline {
+    /// Returns the total length of the contours of this outline, flattening curves to within
+    /// `tolerance`.
+    #[inline]
+    pub fn length(&self, tolerance: f32) -> f32 {
+        OutlineMeasure::new(self, tolerance).length()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::measure::{ContourMeasure, OutlineMeasure};
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use core::f32::consts::PI;
+
+    const EPSILON: f32 = 0.001;
+
+    fn polyline(points: &[(f32, f32)], closed: bool) -> Contour {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        if closed {
+            contour.close();
+        }
+        contour
+    }
+
+    fn square() -> Contour {
+        polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], true)
+    }
+
+    fn assert_close(a: Vector2F, b: Vector2F) {
+        assert!((a - b).length() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_length() {
+        assert!((square().length(0.1) - 40.0).abs() < EPSILON);
+
+        let open = polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], false);
+        assert!((open.length(0.1) - 30.0).abs() < EPSILON);
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(Vector2F::splat(10.0)));
+        circle.close();
+        let length = ContourMeasure::new(&circle, 0.001).length();
+        assert!((length - 20.0 * PI).abs() < 0.05, "{}", length);
+        // Flattening more coarsely cuts corners, so it can only make the circle shorter.
+        assert!(ContourMeasure::new(&circle, 1.0).length() <= length);
+
+        assert_eq!(Contour::new().length(0.1), 0.0);
+        assert_eq!(polyline(&[(5.0, 5.0)], false).length(0.1), 0.0);
+    }
+
+    #[test]
+    fn test_point_at_length() {
+        let measure = ContourMeasure::new(&square(), 0.1);
+        assert_close(measure.point_at_length(0.0), Vector2F::new(0.0, 0.0));
+        assert_close(measure.point_at_length(5.0), Vector2F::new(5.0, 0.0));
+        assert_close(measure.point_at_length(10.0), Vector2F::new(10.0, 0.0));
+        assert_close(measure.point_at_length(25.0), Vector2F::new(5.0, 10.0));
+        assert_close(measure.point_at_length(35.0), Vector2F::new(0.0, 5.0));
+
+        assert_close(measure.tangent_at_length(5.0), Vector2F::new(1.0, 0.0));
+        assert_close(measure.tangent_at_length(15.0), Vector2F::new(0.0, 1.0));
+        assert_close(measure.tangent_at_length(35.0), Vector2F::new(0.0, -1.0));
+    }
+
+    #[test]
+    fn test_distances_are_clamped() {
+        let measure = ContourMeasure::new(&square(), 0.1);
+        assert_close(measure.point_at_length(-5.0), Vector2F::new(0.0, 0.0));
+        assert_close(measure.point_at_length(100.0), Vector2F::new(0.0, 0.0));
+        assert_close(measure.tangent_at_length(-5.0), Vector2F::new(1.0, 0.0));
+        assert_close(measure.tangent_at_length(100.0), Vector2F::new(0.0, -1.0));
+
+        let open = ContourMeasure::new(&polyline(&[(0.0, 0.0), (10.0, 0.0)], false), 0.1);
+        assert_close(open.point_at_length(100.0), Vector2F::new(10.0, 0.0));
+
+        let point = ContourMeasure::new(&polyline(&[(5.0, 5.0)], false), 0.1);
+        assert_close(point.point_at_length(1.0), Vector2F::new(5.0, 5.0));
+        assert_close(point.tangent_at_length(1.0), Vector2F::default());
+    }
+
+    #[test]
+    fn test_sub_contour() {
+        let measure = ContourMeasure::new(&square(), 0.1);
+        let sub_contour = measure.sub_contour(5.0, 25.0);
+        assert!(!sub_contour.is_closed());
+        let points: Vec<Vector2F> =
+            (0..sub_contour.len()).map(|index| sub_contour.position_of(index)).collect();
+        assert_eq!(points.len(), 4);
+        assert_close(points[0], Vector2F::new(5.0, 0.0));
+        assert_close(points[1], Vector2F::new(10.0, 0.0));
+        assert_close(points[2], Vector2F::new(10.0, 10.0));
+        assert_close(points[3], Vector2F::new(5.0, 10.0));
+        assert!((sub_contour.length(0.1) - 20.0).abs() < EPSILON);
+
+        let clamped = measure.sub_contour(-10.0, 5.0);
+        assert_close(clamped.position_of(0), Vector2F::new(0.0, 0.0));
+        assert_close(clamped.last_position().unwrap(), Vector2F::new(5.0, 0.0));
+
+        assert!(measure.sub_contour(25.0, 5.0).is_empty());
+    }
+
+    #[test]
+    fn test_outline_measure() {
+        let mut outline = Outline::new();
+        outline.push_contour(polyline(&[(0.0, 0.0), (10.0, 0.0)], false));
+        outline.push_contour(polyline(&[(50.0, 50.0)], false));
+        outline.push_contour(polyline(&[(0.0, 20.0), (0.0, 40.0)], false));
+
+        let measure = OutlineMeasure::new(&outline, 0.1);
+        assert_eq!(measure.contours().len(), 3);
+        assert!((measure.length() - 30.0).abs() < EPSILON);
+        assert!((outline.length(0.1) - 30.0).abs() < EPSILON);
+
+        // Each contour picks up where the previous one ends, skipping empty ones.
+        assert_close(measure.point_at_length(5.0), Vector2F::new(5.0, 0.0));
+        assert_close(measure.point_at_length(15.0), Vector2F::new(0.0, 25.0));
+        assert_close(measure.tangent_at_length(15.0), Vector2F::new(0.0, 1.0));
+        assert_close(measure.point_at_length(-1.0), Vector2F::new(0.0, 0.0));
+        assert_close(measure.point_at_length(100.0), Vector2F::new(0.0, 40.0));
+
+        assert_close(OutlineMeasure::new(&Outline::new(), 0.1).point_at_length(1.0),
+                     Vector2F::default());
+    }
+}
@@ -0,0 +1,260 @@
+// pathfinder/content/src/morph.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Interpolation between outlines, for shape tweening such as Lottie path morphs and icon
+//! transitions.
+//!
+//! Two outlines can be interpolated point by point when they're compatible: they have the same
+//! number of contours, and matching contours have the same kinds of points in the same order and
+//! are both open or both closed. `make_compatible()` rewrites any two outlines into compatible
+//! ones that draw the same shapes.
+//!
+//! Contours are matched in order, starting from their first points. Tweens look best when
+//! matching contours start at similar places and run in the same direction.
+
+use crate::outline::{Contour, Outline};
+use crate::segment::Segment;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec::Vec;
+use core::cmp;
+
+impl Outline {
+    /// Returns the outline `t` of the way from `a` to `b`, or `None` if they aren't compatible.
+    ///
+    /// A `t` of 0 returns `a` and a `t` of 1 returns `b`. Values outside that range extrapolate.
+    pub fn lerp(a: &Outline, b: &Outline, t: f32) -> Option<Outline> {
+        if !a.is_compatible_with(b) {
+            return None;
+        }
+
+        let mut outline = Outline::new();
+        for (contour_a, contour_b) in a.contours.iter().zip(b.contours.iter()) {
+            let mut contour = Contour::with_capacity(contour_a.points.len());
+            let points = contour_a.points.iter().zip(contour_b.points.iter());
+            for (&flags, (&point_a, &point_b)) in contour_a.flags.iter().zip(points) {
+                contour.push_point(point_a.lerp(point_b, t), flags, true);
+            }
+            contour.closed = contour_a.closed;
+            outline.push_contour(contour);
+        }
+        Some(outline)
+    }
+
+    /// Whether this outline can be interpolated with `other` by `Outline::lerp()`.
+    pub fn is_compatible_with(&self, other: &Outline) -> bool {
+        self.contours.len() == other.contours.len() &&
+            self.contours.iter().zip(other.contours.iter()).all(|(contour, other_contour)| {
+                contour.closed == other_contour.closed && contour.flags == other_contour.flags
+            })
+    }
+}
+
+/// Returns copies of `a` and `b` that draw the same shapes and are compatible with each other, so
+/// that they can be passed to `Outline::lerp()`.
+///
+/// Every segment becomes a cubic curve, and of each pair of matching contours, the one with fewer
+/// segments has its longest ones split in half until they have the same number. Where one outline
+/// has more contours than the other, the other gets contours collapsed to the centers of the extra
+/// ones, which grow out of a point as the tween goes on. A closed contour matched with an open one
+/// is left open, with its closing segment drawn explicitly.
+pub fn make_compatible(a: &Outline, b: &Outline) -> (Outline, Outline) {
+    let (mut compatible_a, mut compatible_b) = (Outline::new(), Outline::new());
+    for contour_index in 0..cmp::max(a.contours.len(), b.contours.len()) {
+        let (contour_a, contour_b) = (a.contours.get(contour_index), b.contours.get(contour_index));
+        let (mut segments_a, mut segments_b, closed) = match (contour_a, contour_b) {
+            (Some(contour_a), Some(contour_b)) => {
+                let closed = contour_a.closed && contour_b.closed;
+                (cubic_segments(contour_a), cubic_segments(contour_b), closed)
+            }
+            (Some(contour), None) => {
+                let segments = cubic_segments(contour);
+                let collapsed_segments = collapse(&segments, center_of(contour));
+                (segments, collapsed_segments, contour.closed)
+            }
+            (None, Some(contour)) => {
+                let segments = cubic_segments(contour);
+                let collapsed_segments = collapse(&segments, center_of(contour));
+                (collapsed_segments, segments, contour.closed)
+            }
+            (None, None) => unreachable!(),
+        };
+
+        split_to_count(&mut segments_a, segments_b.len());
+        split_to_count(&mut segments_b, segments_a.len());
+        compatible_a.push_contour(contour_from_cubics(&segments_a, closed));
+        compatible_b.push_contour(contour_from_cubics(&segments_b, closed));
+    }
+    (compatible_a, compatible_b)
+}
+
+// Returns the segments of `contour` as cubic curves, including the closing segment if it's
+// closed. A contour with only one point becomes a single curve collapsed to that point.
+fn cubic_segments(contour: &Contour) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = contour.iter().map(|segment| to_cubic(&segment)).collect();
+    if segments.is_empty() {
+        let point = contour.position_of(0);
+        segments.push(point_cubic(point));
+    }
+    segments
+}
+
+fn to_cubic(segment: &Segment) -> Segment {
+    if !segment.is_line() {
+        return segment.to_cubic();
+    }
+    let (from, to) = (segment.baseline.from(), segment.baseline.to());
+    let ctrl = LineSegment2F::new(from.lerp(to, 1.0 / 3.0), from.lerp(to, 2.0 / 3.0));
+    Segment::cubic(segment.baseline, ctrl)
+}
+
+fn center_of(contour: &Contour) -> Vector2F {
+    let bounds = contour.bounds();
+    bounds.origin().lerp(bounds.lower_right(), 0.5)
+}
+
+fn point_cubic(point: Vector2F) -> Segment {
+    let line = LineSegment2F::new(point, point);
+    Segment::cubic(line, line)
+}
+
+fn collapse(segments: &[Segment], point: Vector2F) -> Vec<Segment> {
+    segments.iter().map(|_| point_cubic(point)).collect()
+}
+
+// Splits the longest of `segments` in half until there are `count` of them.
+fn split_to_count(segments: &mut Vec<Segment>, count: usize) {
+    let mut lengths: Vec<f32> = segments.iter().map(|segment| segment.arc_length()).collect();
+    while segments.len() < count {
+        let mut longest_index = 0;
+        for (index, &length) in lengths.iter().enumerate() {
+            if length > lengths[longest_index] {
+                longest_index = index;
+            }
+        }
+
+        let (before, after) = segments[longest_index].split(0.5);
+        segments[longest_index] = before;
+        segments.insert(longest_index + 1, after);
+        let half_length = lengths[longest_index] * 0.5;
+        lengths[longest_index] = half_length;
+        lengths.insert(longest_index + 1, half_length);
+    }
+}
+
+fn contour_from_cubics(segments: &[Segment], closed: bool) -> Contour {
+    let mut contour = Contour::with_capacity(segments.len() * 3 + 1);
+    contour.push_endpoint(segments[0].baseline.from());
+    for segment in segments {
+        contour.push_cubic(segment.ctrl.from(), segment.ctrl.to(), segment.baseline.to());
+    }
+    if closed {
+        contour.close();
+    }
+    contour
+}
+
+#[cfg(test)]
+mod test {
+    use crate::morph::make_compatible;
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::vector::Vector2F;
+    use alloc::vec::Vec;
+
+    const EPSILON: f32 = 0.001;
+
+    fn polygon(points: &[(f32, f32)], closed: bool) -> Contour {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        if closed {
+            contour.close();
+        }
+        contour
+    }
+
+    fn outline(contours: Vec<Contour>) -> Outline {
+        let mut outline = Outline::new();
+        contours.into_iter().for_each(|contour| outline.push_contour(contour));
+        outline
+    }
+
+    fn area(contour: &Contour) -> f32 {
+        let mut outline = outline(vec![contour.clone()]);
+        outline.flatten(0.001);
+        let contour = &outline.contours()[0];
+        (0..contour.len()).map(|point_index| {
+            let p0 = contour.position_of(point_index);
+            let p1 = contour.position_of((point_index + 1) % contour.len());
+            p0.x() * p1.y() - p1.x() * p0.y()
+        }).sum::<f32>() * 0.5
+    }
+
+    #[test]
+    fn test_lerp() {
+        let a = outline(vec![polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], true)]);
+        let b = outline(vec![polygon(&[(0.0, 0.0), (20.0, 0.0), (20.0, 30.0)], true)]);
+        let middle = Outline::lerp(&a, &b, 0.5).unwrap();
+        let contour = &middle.contours()[0];
+        assert!(contour.is_closed());
+        assert_eq!(contour.position_of(1), Vector2F::new(15.0, 0.0));
+        assert_eq!(contour.position_of(2), Vector2F::new(15.0, 20.0));
+        assert_eq!(middle.bounds().size(), Vector2F::new(15.0, 20.0));
+
+        // Outlines with different kinds of points, or open and closed contours, don't lerp.
+        let open = outline(vec![polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false)]);
+        assert!(!a.is_compatible_with(&open));
+        assert!(Outline::lerp(&a, &open, 0.5).is_none());
+        let square = outline(vec![polygon(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+                                          true)]);
+        assert!(Outline::lerp(&a, &square, 0.5).is_none());
+    }
+
+    #[test]
+    fn test_make_compatible() {
+        let square = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)], true);
+        let triangle = polygon(&[(20.0, 0.0), (30.0, 0.0), (20.0, 10.0)], true);
+        let dot = polygon(&[(50.0, 50.0), (54.0, 50.0), (54.0, 54.0), (50.0, 54.0)], true);
+        let a = outline(vec![square.clone()]);
+        let b = outline(vec![triangle.clone(), dot.clone()]);
+
+        let (compatible_a, compatible_b) = make_compatible(&a, &b);
+        assert!(compatible_a.is_compatible_with(&compatible_b));
+        assert!(Outline::lerp(&compatible_a, &compatible_b, 0.5).is_some());
+
+        // The shapes are the same as before.
+        assert_eq!(compatible_a.len(), 2);
+        assert!((area(&compatible_a.contours()[0]) - area(&square)).abs() < EPSILON);
+        assert!((area(&compatible_b.contours()[0]) - area(&triangle)).abs() < EPSILON);
+        assert!((area(&compatible_b.contours()[1]) - area(&dot)).abs() < EPSILON);
+        assert_eq!(compatible_b.contours()[0].bounds(), triangle.bounds());
+
+        // The contour that `a` lacks grows out of the center of the one in `b`.
+        let collapsed = &compatible_a.contours()[1];
+        for point_index in 0..collapsed.len() {
+            assert_eq!(collapsed.position_of(point_index), Vector2F::new(52.0, 52.0));
+        }
+    }
+
+    #[test]
+    fn test_make_compatible_open_and_closed() {
+        let closed = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], true);
+        let open = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], false);
+        let (compatible_a, compatible_b) = make_compatible(&outline(vec![closed]),
+                                                           &outline(vec![open]));
+        assert!(compatible_a.is_compatible_with(&compatible_b));
+
+        // The closed contour is left open, with its closing segment drawn explicitly.
+        let contour = &compatible_a.contours()[0];
+        assert!(!contour.is_closed());
+        assert_eq!(contour.position_of(contour.len() - 1), Vector2F::new(0.0, 0.0));
+    }
+}
@@ -20,9 +20,20 @@ use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::unit_vector::UnitVector;
 use pathfinder_geometry::vector::Vector2F;
-use std::f32::consts::PI;
-use std::fmt::{self, Debug, Formatter};
-use std::mem;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use core::fmt::{self, Debug, Formatter};
+use core::mem;
+
+#[cfg(feature = "pf-serde")]
+use serde::de::Error;
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Curves are flattened to within this distance before perspective projection, in the
+// coordinates of the outline.
+const PERSPECTIVE_FLATTENING_TOLERANCE: f32 = 0.25;
 
 #[derive(Clone)]
 pub struct Outline {
@@ -167,6 +178,16 @@ impl Outline {
         self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
     }
 
+    /// Replaces the curves in this outline with lines, to within `tolerance`.
+    pub fn flatten(&mut self, tolerance: f32) {
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.flatten(tolerance);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
+    }
+
     pub fn dilate(&mut self, amount: Vector2F) {
         let orientation = Orientation::from_outline(self);
         self.contours
@@ -381,24 +402,66 @@ impl Contour {
         self.push_point(segment.baseline.to(), PointFlags::empty(), update_bounds);
     }
 
+    /// Pushes an arc of the ellipse that `transform` maps the unit circle to, from `start_angle`
+    /// to `end_angle` in the given direction, as HTML canvas `arc()` does.
+    ///
+    /// The arc covers at most a full turn: a sweep of a full turn or more in `direction` pushes
+    /// the entire ellipse, starting and ending at `start_angle`, and otherwise the arc runs from
+    /// the point at `start_angle` to the point at `end_angle`. Equal angles push a single point.
     pub fn push_arc(&mut self,
                     transform: &Transform2F,
                     start_angle: f32,
                     end_angle: f32,
                     direction: ArcDirection) {
-        if end_angle - start_angle >= PI * 2.0 {
-            self.push_ellipse(transform);
+        let (sign, sweep_angle) = match direction {
+            ArcDirection::CW => (1.0, end_angle - start_angle),
+            ArcDirection::CCW => (-1.0, start_angle - end_angle),
+        };
+        // As browsers do, wrap sweeps that run the wrong way around, treating multiples of a full
+        // turn as a full turn.
+        let sweep_angle = if sweep_angle >= PI * 2.0 {
+            PI * 2.0
+        } else if sweep_angle < 0.0 {
+            PI * 2.0 - (-sweep_angle % (PI * 2.0))
         } else {
-            let start = Vector2F::new(f32::cos(start_angle), f32::sin(start_angle));
-            let end = Vector2F::new(f32::cos(end_angle), f32::sin(end_angle));
+            sweep_angle
+        };
+
+        let unit_vector_at = |angle: f32| Vector2F::new(f32::cos(angle), f32::sin(angle));
+        let start = unit_vector_at(start_angle);
+        if sweep_angle == 0.0 {
+            self.push_endpoint(*transform * start);
+            return;
+        }
+
+        // A chord can't distinguish a sweep of nearly a full turn from a sweep of nearly nothing,
+        // so sweeps of more than a half turn are pushed in two halves.
+        let end = unit_vector_at(start_angle + sign * sweep_angle);
+        if sweep_angle <= PI {
             self.push_arc_from_unit_chord(transform, LineSegment2F::new(start, end), direction);
+            return;
         }
+        let middle = unit_vector_at(start_angle + sign * sweep_angle * 0.5);
+        self.push_arc_from_unit_chord(transform, LineSegment2F::new(start, middle), direction);
+        self.push_arc_segments_from_unit_chord(transform,
+                                               LineSegment2F::new(middle, end),
+                                               direction,
+                                               false);
     }
 
+    #[inline]
     pub fn push_arc_from_unit_chord(&mut self,
                                     transform: &Transform2F,
-                                    mut chord: LineSegment2F,
+                                    chord: LineSegment2F,
                                     direction: ArcDirection) {
+        self.push_arc_segments_from_unit_chord(transform, chord, direction, true)
+    }
+
+    fn push_arc_segments_from_unit_chord(&mut self,
+                                         transform: &Transform2F,
+                                         mut chord: LineSegment2F,
+                                         direction: ArcDirection,
+                                         include_from_point: bool) {
         let mut direction_transform = Transform2F::default();
         if direction == ArcDirection::CCW {
             chord = chord.scale_xy(Vector2F::new(-1.0, 1.0));
@@ -412,6 +475,14 @@ impl Contour {
             let mut sweep_vector = end_vector.rev_rotate_by(vector);
             let last = sweep_vector.0.x() >= -EPSILON && sweep_vector.0.y() >= -EPSILON;
 
+            // The curve of a sweep this small would be degenerate, so just end the arc.
+            if last && sweep_vector.0.x() >= 1.0 {
+                if first_segment && include_from_point {
+                    self.push_endpoint(*transform * direction_transform * vector.0);
+                }
+                break;
+            }
+
             let mut segment;
             if !last {
                 sweep_vector = UnitVector(Vector2F::new(0.0, 1.0));
@@ -422,13 +493,13 @@ impl Contour {
 
             let half_sweep_vector = sweep_vector.halve_angle();
             let rotation = Transform2F::from_rotation_vector(half_sweep_vector.rotate_by(vector));
-            segment = segment.transform(&(*transform * rotation * direction_transform));
+            segment = segment.transform(&(*transform * direction_transform * rotation));
 
             let mut push_segment_flags = PushSegmentFlags::UPDATE_BOUNDS;
-            if first_segment {
+            if first_segment && include_from_point {
                 push_segment_flags.insert(PushSegmentFlags::INCLUDE_FROM_POINT);
-                first_segment = false;
             }
+            first_segment = false;
             self.push_segment(&segment, push_segment_flags);
 
             if last {
@@ -441,6 +512,101 @@ impl Contour {
         const EPSILON: f32 = 0.001;
     }
 
+    /// Pushes an arc of the ellipse with the given center, radii, and rotation, from
+    /// `start_angle` to `end_angle` in the given direction. Angles are in radians and measured
+    /// before the rotation is applied. A sweep of a full turn or more in `direction` pushes the
+    /// entire ellipse, as in `push_arc()`.
+    pub fn push_ellipse_arc(&mut self,
+                            center: Vector2F,
+                            radii: Vector2F,
+                            rotation: f32,
+                            start_angle: f32,
+                            end_angle: f32,
+                            direction: ArcDirection) {
+        let transform = Transform2F::from_scale(radii).rotate(rotation).translate(center);
+        self.push_arc(&transform, start_angle, end_angle, direction);
+    }
+
+    /// Pushes an elliptical arc from the last point of this contour to `to`, with the semantics
+    /// of the SVG `A` path command.
+    ///
+    /// `radii` and `x_axis_rotation` describe the ellipse. Of the four arcs that connect the two
+    /// points, `large_arc` selects one that sweeps more than 180°, and `sweep` selects one that
+    /// runs in the direction of increasing angle (clockwise in a y-down coordinate system). Radii
+    /// too small to reach `to` are scaled up, and a zero radius produces a straight line.
+    pub fn push_svg_arc(&mut self,
+                        radii: Vector2F,
+                        x_axis_rotation: f32,
+                        large_arc: bool,
+                        sweep: bool,
+                        to: Vector2F) {
+        let from = match self.last_position() {
+            None => {
+                self.push_endpoint(to);
+                return;
+            }
+            Some(from) => from,
+        };
+        if from == to {
+            return;
+        }
+
+        let mut radii = Vector2F::new(radii.x().abs(), radii.y().abs());
+        if radii.x() == 0.0 || radii.y() == 0.0 {
+            self.push_endpoint(to);
+            return;
+        }
+
+        // Convert from endpoint to center parameterization, following the SVG 1.1 specification,
+        // appendix F.6.5.
+        let rotation = Transform2F::from_rotation(x_axis_rotation);
+        let half_chord = rotation.inverse() * (from - to).scale(0.5);
+
+        let lambda = half_chord.x() * half_chord.x() / (radii.x() * radii.x()) +
+            half_chord.y() * half_chord.y() / (radii.y() * radii.y());
+        if lambda > 1.0 {
+            radii = radii.scale(f32::sqrt(lambda));
+        }
+
+        let (rx2, ry2) = (radii.x() * radii.x(), radii.y() * radii.y());
+        let (x2, y2) = (half_chord.x() * half_chord.x(), half_chord.y() * half_chord.y());
+        let mut coefficient = f32::sqrt(f32::max(0.0, (rx2 * ry2 - rx2 * y2 - ry2 * x2) /
+                                                      (rx2 * y2 + ry2 * x2)));
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+        let center_prime = Vector2F::new(radii.x() * half_chord.y() / radii.y(),
+                                         -radii.y() * half_chord.x() / radii.x())
+            .scale(coefficient);
+        let center = rotation * center_prime + (from + to).scale(0.5);
+
+        let inverse_radii = Vector2F::new(1.0 / radii.x(), 1.0 / radii.y());
+        let start_vector = (half_chord - center_prime).scale_xy(inverse_radii);
+        let end_vector = (-half_chord - center_prime).scale_xy(inverse_radii);
+        let start_angle = f32::atan2(start_vector.y(), start_vector.x());
+        let mut sweep_angle = f32::atan2(start_vector.det(end_vector),
+                                         start_vector.dot(end_vector));
+        if sweep && sweep_angle < 0.0 {
+            sweep_angle += PI * 2.0;
+        } else if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= PI * 2.0;
+        }
+
+        let direction = if sweep { ArcDirection::CW } else { ArcDirection::CCW };
+        self.push_ellipse_arc(center,
+                              radii,
+                              x_axis_rotation,
+                              start_angle,
+                              start_angle + sweep_angle,
+                              direction);
+
+        // Make sure we end exactly where we were asked to, despite rounding error.
+        if let Some(last_point) = self.points.last_mut() {
+            *last_point = to;
+        }
+        union_rect(&mut self.bounds, to, false);
+    }
+
     pub fn push_ellipse(&mut self, transform: &Transform2F) {
         let segment = Segment::quarter_circle_arc();
         let mut rotation;
@@ -567,13 +733,58 @@ impl Contour {
         }
     }
 
+    /// Reverses the direction of this contour, so that it winds the other way around the points
+    /// it encloses. The contour traces the same path, starting from its last point.
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+        self.flags.reverse();
+
+        // The control points of each cubic are now in the opposite order.
+        let mut point_index = 0;
+        while point_index + 1 < self.flags.len() {
+            if self.flags[point_index].contains(PointFlags::CONTROL_POINT_1) {
+                self.flags[point_index] = PointFlags::CONTROL_POINT_0;
+                self.flags[point_index + 1] = PointFlags::CONTROL_POINT_1;
+                point_index += 2;
+            } else {
+                point_index += 1;
+            }
+        }
+    }
+
+    /// Projects this contour. Curves are flattened first, since projection does not map Bézier
+    /// curves to Bézier curves, although it does map lines to lines.
     pub fn apply_perspective(&mut self, perspective: &Perspective) {
+        if self.flags.iter().any(|flags| !flags.is_empty()) {
+            self.flatten(PERSPECTIVE_FLATTENING_TOLERANCE);
+        }
+
         for (point_index, point) in self.points.iter_mut().enumerate() {
             *point = *perspective * *point;
             union_rect(&mut self.bounds, *point, point_index == 0);
         }
     }
 
+    // Replaces the curves in this contour with lines, to within `tolerance`.
+    fn flatten(&mut self, tolerance: f32) {
+        let mut flattened = Contour::with_capacity(self.points.len());
+        // The last segment of a closed contour returns to the first point, which `close()` does.
+        let mut segment_count = self.iter().count();
+        if self.closed {
+            segment_count = segment_count.saturating_sub(1);
+        }
+        for segment in self.iter().take(segment_count) {
+            if flattened.is_empty() {
+                flattened.push_endpoint(segment.baseline.from());
+            }
+            segment.flatten(tolerance, |point, _| flattened.push_endpoint(point));
+        }
+        if self.closed {
+            flattened.close();
+        }
+        *self = flattened;
+    }
+
     pub fn dilate(&mut self, amount: Vector2F, orientation: Orientation) {
         ContourDilator::new(self, amount, orientation).dilate();
         self.bounds = self.bounds.dilate(amount);
@@ -872,3 +1083,244 @@ pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
         *bounds = bounds.union_point(new_point)
     }
 }
+
+// Outlines and contours are stored without their bounds, which are recomputed as they're loaded.
+
+#[cfg(feature = "pf-serde")]
+impl Serialize for Outline {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.contours.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for Outline {
+    fn deserialize<D>(deserializer: D) -> Result<Outline, D::Error> where D: Deserializer<'de> {
+        let mut outline = Outline::new();
+        for contour in Vec::<Contour>::deserialize(deserializer)? {
+            outline.push_contour(contour);
+        }
+        Ok(outline)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+#[derive(Serialize)]
+#[serde(rename = "Contour")]
+struct SerializedContour<'a> {
+    points: &'a [Vector2F],
+    flags: &'a [PointFlags],
+    closed: bool,
+}
+
+#[cfg(feature = "pf-serde")]
+#[derive(Deserialize)]
+#[serde(rename = "Contour")]
+struct DeserializedContour {
+    points: Vec<Vector2F>,
+    flags: Vec<PointFlags>,
+    closed: bool,
+}
+
+#[cfg(feature = "pf-serde")]
+impl Serialize for Contour {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedContour { points: &self.points, flags: &self.flags, closed: self.closed }
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for Contour {
+    fn deserialize<D>(deserializer: D) -> Result<Contour, D::Error> where D: Deserializer<'de> {
+        let data = DeserializedContour::deserialize(deserializer)?;
+        if data.points.len() != data.flags.len() {
+            return Err(D::Error::custom("a contour must have one set of flags for each point"));
+        }
+
+        let mut contour = Contour::with_capacity(data.points.len());
+        for (point, flags) in data.points.into_iter().zip(data.flags) {
+            contour.push_point(point, flags, true);
+        }
+        contour.closed = data.closed;
+        Ok(contour)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl Serialize for PointFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for PointFlags {
+    fn deserialize<D>(deserializer: D) -> Result<PointFlags, D::Error>
+                      where D: Deserializer<'de> {
+        let bits = u8::deserialize(deserializer)?;
+        PointFlags::from_bits(bits).ok_or_else(|| {
+            D::Error::custom(format_args!("invalid point flags: {:#x}", bits))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{ArcDirection, Contour};
+    #[cfg(feature = "pf-serde")]
+    use crate::outline::Outline;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use core::f32::consts::PI;
+
+    const EPSILON: f32 = 0.001;
+
+    fn contour_from(point: Vector2F) -> Contour {
+        let mut contour = Contour::new();
+        contour.push_endpoint(point);
+        contour
+    }
+
+    // Points sampled along every segment of the contour.
+    fn sample_points(contour: &Contour) -> Vec<Vector2F> {
+        contour.iter().flat_map(|segment| {
+            (0..=8).map(move |step| segment.sample(step as f32 / 8.0))
+        }).collect()
+    }
+
+    fn assert_close(actual: Vector2F, expected: Vector2F) {
+        assert!((actual - expected).length() < EPSILON,
+                "expected {:?}, found {:?}",
+                expected,
+                actual);
+    }
+
+    #[test]
+    fn test_svg_arc_scales_up_radii() {
+        // Radii of 1 can't reach a point 10 away, so they're scaled up into a half circle of
+        // radius 5.
+        let mut contour = contour_from(Vector2F::default());
+        contour.push_svg_arc(Vector2F::splat(1.0), 0.0, false, true, Vector2F::new(10.0, 0.0));
+        assert_close(contour.last_position().unwrap(), Vector2F::new(10.0, 0.0));
+        for point in sample_points(&contour) {
+            assert!(((point - Vector2F::new(5.0, 0.0)).length() - 5.0).abs() < 0.01);
+            assert!(point.y() <= EPSILON);
+        }
+        assert!((contour.bounds().min_y() + 5.0).abs() < 0.01);
+
+        // The other sweep direction bulges the other way.
+        let mut contour = contour_from(Vector2F::default());
+        contour.push_svg_arc(Vector2F::splat(1.0), 0.0, false, false, Vector2F::new(10.0, 0.0));
+        assert!(sample_points(&contour).iter().all(|point| point.y() >= -EPSILON));
+    }
+
+    #[test]
+    fn test_svg_arc_large_arc() {
+        // A circle of radius 5 through both points, centered below or above their chord.
+        let to = Vector2F::new(6.0, 0.0);
+        for &(large_arc, sweep, center_y) in &[(false, true, 4.0),
+                                               (true, true, -4.0),
+                                               (false, false, -4.0),
+                                               (true, false, 4.0)] {
+            let mut contour = contour_from(Vector2F::default());
+            contour.push_svg_arc(Vector2F::splat(5.0), 0.0, large_arc, sweep, to);
+            assert_close(contour.last_position().unwrap(), to);
+            for point in sample_points(&contour) {
+                let distance = (point - Vector2F::new(3.0, center_y)).length();
+                assert!((distance - 5.0).abs() < 0.01, "{:?} {:?}", (large_arc, sweep), point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_svg_arc_with_zero_radius_is_a_line() {
+        for &radii in &[Vector2F::new(0.0, 5.0), Vector2F::new(5.0, 0.0)] {
+            let mut contour = contour_from(Vector2F::default());
+            contour.push_svg_arc(radii, 0.0, false, true, Vector2F::new(10.0, 4.0));
+            assert_eq!(contour.len(), 2);
+            assert!(contour.point_is_endpoint(1));
+            assert_eq!(contour.position_of(1), Vector2F::new(10.0, 4.0));
+        }
+    }
+
+    #[test]
+    fn test_svg_arc_to_the_same_point() {
+        let mut contour = contour_from(Vector2F::new(3.0, 4.0));
+        contour.push_svg_arc(Vector2F::splat(5.0), 0.0, true, true, Vector2F::new(3.0, 4.0));
+        assert_eq!(contour.len(), 1);
+
+        // With no current point, the arc just starts there.
+        let mut contour = Contour::new();
+        contour.push_svg_arc(Vector2F::splat(5.0), 0.0, true, true, Vector2F::new(3.0, 4.0));
+        assert_eq!(contour.len(), 1);
+        assert_eq!(contour.position_of(0), Vector2F::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn test_arc_sweeps() {
+        let transform = Transform2F::from_scale(Vector2F::splat(2.0));
+
+        // A quarter turn clockwise, and the same angles counterclockwise, which wrap around to
+        // three quarters of a turn.
+        for &(direction, middle) in &[(ArcDirection::CW, Vector2F::new(1.0, 1.0)),
+                                      (ArcDirection::CCW, Vector2F::new(-1.0, -1.0))] {
+            let mut contour = Contour::new();
+            contour.push_arc(&transform, 0.0, PI * 0.5, direction);
+            assert_close(contour.position_of(0), Vector2F::new(2.0, 0.0));
+            assert_close(contour.last_position().unwrap(), Vector2F::new(0.0, 2.0));
+            assert!(sample_points(&contour).iter().any(|point| {
+                (*point - middle.scale(f32::sqrt(2.0))).length() < 0.01
+            }));
+        }
+
+        // Sweeps of a full turn or more push the whole circle.
+        let mut contour = Contour::new();
+        contour.push_arc(&transform, 0.0, PI * 5.0, ArcDirection::CW);
+        assert_close(contour.last_position().unwrap(), Vector2F::new(2.0, 0.0));
+        assert!((contour.bounds().size() - Vector2F::splat(4.0)).length() < 0.01);
+
+        // Equal angles push a single point.
+        let mut contour = Contour::new();
+        contour.push_arc(&transform, 1.0, 1.0, ArcDirection::CCW);
+        assert_eq!(contour.len(), 1);
+    }
+
+    #[cfg(feature = "pf-serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut outline = Outline::new();
+        let mut contour = contour_from(Vector2F::new(1.0, 2.0));
+        contour.push_quadratic(Vector2F::new(4.0, -1.0), Vector2F::new(6.0, 2.0));
+        contour.push_cubic(Vector2F::new(7.0, 4.0),
+                           Vector2F::new(5.0, 6.0),
+                           Vector2F::new(3.0, 5.0));
+        contour.close();
+        outline.push_contour(contour);
+        let mut contour = contour_from(Vector2F::new(-3.0, 0.0));
+        contour.push_endpoint(Vector2F::new(-2.0, 8.0));
+        outline.push_contour(contour);
+
+        let loaded: Outline = serde_json::from_str(&serde_json::to_string(&outline).unwrap())
+            .unwrap();
+        assert_eq!(loaded.contours().len(), 2);
+        for (loaded, contour) in loaded.contours().iter().zip(outline.contours()) {
+            assert_eq!(loaded.is_closed(), contour.is_closed());
+            assert_eq!(loaded.points, contour.points);
+            assert_eq!(loaded.flags, contour.flags);
+            assert_eq!(loaded.bounds(), contour.bounds());
+        }
+        assert_eq!(loaded.bounds(), outline.bounds());
+    }
+
+    #[cfg(feature = "pf-serde")]
+    #[test]
+    fn test_serde_rejects_malformed_contours() {
+        let contour = r#"{"points":[[0,0],[1,1]],"flags":[0],"closed":false}"#;
+        assert!(serde_json::from_str::<Contour>(contour).is_err());
+        let contour = r#"{"points":[[0,0]],"flags":[128],"closed":false}"#;
+        assert!(serde_json::from_str::<Contour>(contour).is_err());
+        let contour = r#"{"points":[[0,0],[1,1]],"flags":[0,0],"closed":true}"#;
+        assert!(serde_json::from_str::<Contour>(contour).unwrap().is_closed());
+    }
+}
@@ -0,0 +1,355 @@
+// pathfinder/content/src/pattern.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Raster image patterns.
+//!
+//! With the `pf-image` feature, images can be decoded from PNG and JPEG data.
+
+use pathfinder_color::ColorU;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2I;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
+
+#[cfg(feature = "pf-serde")]
+use serde::de::Error;
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "pf-image")]
+use image::{DynamicImage, ImageFormat, ImageResult};
+#[cfg(feature = "pf-image")]
+use std::path::Path;
+
+/// A raster image pattern.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
+pub struct Pattern {
+    pub image: Image,
+    /// Maps the pixels of the image to scene coordinates.
+    pub transform: Transform2F,
+}
+
+/// An image of straight-alpha RGBA pixels.
+///
+/// The pixels are reference counted, so images are cheap to clone.
+#[derive(Clone, Debug)]
+pub struct Image {
+    size: Vector2I,
+    pixels: Arc<Vec<ColorU>>,
+    pixels_hash: u64,
+    is_opaque: bool,
+}
+
+impl Pattern {
+    #[inline]
+    pub fn new(image: Image, transform: Transform2F) -> Pattern {
+        Pattern { image, transform }
+    }
+
+    #[inline]
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        self.transform = *transform * self.transform;
+    }
+
+    #[inline]
+    pub fn set_opacity(&mut self, alpha: f32) {
+        self.image.set_opacity(alpha);
+    }
+}
+
+impl Image {
+    /// Creates an image from pixels in row-major order, starting at the top left.
+    ///
+    /// Panics if the number of pixels doesn't match the size.
+    pub fn new(size: Vector2I, pixels: Arc<Vec<ColorU>>) -> Image {
+        assert_eq!(size.x().max(0) as usize * size.y().max(0) as usize, pixels.len());
+        let mut hasher = DefaultHasher::new();
+        pixels.hash(&mut hasher);
+        let pixels_hash = hasher.finish();
+        let is_opaque = pixels.iter().all(|pixel| pixel.is_opaque());
+        Image { size, pixels, pixels_hash, is_opaque }
+    }
+
+    /// Creates an image from tightly-packed 8-bit RGBA data.
+    ///
+    /// Panics if the length of the data doesn't match the size.
+    pub fn from_rgba8(size: Vector2I, data: &[u8]) -> Image {
+        let pixels = data.chunks(4).map(|pixel| {
+            ColorU { r: pixel[0], g: pixel[1], b: pixel[2], a: pixel[3] }
+        }).collect();
+        Image::new(size, Arc::new(pixels))
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+
+    #[inline]
+    pub fn pixels(&self) -> &Arc<Vec<ColorU>> {
+        &self.pixels
+    }
+
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        self.is_opaque
+    }
+
+    #[inline]
+    pub fn is_fully_transparent(&self) -> bool {
+        self.pixels.iter().all(|pixel| pixel.is_fully_transparent())
+    }
+
+    /// Multiplies the alpha of every pixel by `alpha`. This copies the pixels.
+    pub fn set_opacity(&mut self, alpha: f32) {
+        if alpha == 1.0 {
+            return;
+        }
+
+        let pixels = self.pixels.iter().map(|pixel| {
+            ColorU { a: (pixel.a as f32 * alpha).round() as u8, ..*pixel }
+        }).collect();
+        *self = Image::new(self.size, Arc::new(pixels));
+    }
+
+    /// Returns the chain of mipmap levels below this image, each half the size of the one before
+    /// it, rounding down, and ending with a 1×1 image. The result is empty if this image is
+    /// already a single pixel or empty.
+    ///
+    /// Drawing a level whose size is close to the size on screen avoids the aliasing that comes
+    /// from minifying a large image, since the paint texture samples only the nearest four pixels.
+    pub fn mipmaps(&self) -> Vec<Image> {
+        let mut levels: Vec<Image> = vec![];
+        loop {
+            let prev_level = levels.last().unwrap_or(self);
+            let prev_size = prev_level.size;
+            if prev_size.x() <= 1 && prev_size.y() <= 1 {
+                break;
+            }
+            let size = Vector2I::new(prev_size.x() / 2, prev_size.y() / 2).max(Vector2I::splat(1));
+            let level = prev_level.downsample(size);
+            levels.push(level);
+        }
+        levels
+    }
+
+    // Box-filters this image down to `new_size`, averaging premultiplied colors so that
+    // transparent pixels don't darken their neighbors.
+    fn downsample(&self, new_size: Vector2I) -> Image {
+        let (old_width, old_height) = (self.size.x() as usize, self.size.y() as usize);
+        let (new_width, new_height) = (new_size.x() as usize, new_size.y() as usize);
+        let mut pixels = Vec::with_capacity(new_width * new_height);
+        for y in 0..new_height {
+            let (y0, y1) = (y * old_height / new_height,
+                            ((y + 1) * old_height + new_height - 1) / new_height);
+            for x in 0..new_width {
+                let (x0, x1) = (x * old_width / new_width,
+                                ((x + 1) * old_width + new_width - 1) / new_width);
+                let (mut rgb, mut alpha) = ([0; 3], 0);
+                for pixel in (y0..y1).flat_map(|y| &self.pixels[(y * old_width + x0)..
+                                                                (y * old_width + x1)]) {
+                    let pixel_alpha = pixel.a as u32;
+                    rgb[0] += pixel.r as u32 * pixel_alpha;
+                    rgb[1] += pixel.g as u32 * pixel_alpha;
+                    rgb[2] += pixel.b as u32 * pixel_alpha;
+                    alpha += pixel_alpha;
+                }
+                let count = ((x1 - x0) * (y1 - y0)) as u32;
+                pixels.push(if alpha == 0 {
+                    ColorU::transparent_black()
+                } else {
+                    let channel = |sum: u32| ((sum + alpha / 2) / alpha) as u8;
+                    ColorU {
+                        r: channel(rgb[0]),
+                        g: channel(rgb[1]),
+                        b: channel(rgb[2]),
+                        a: ((alpha + count / 2) / count) as u8,
+                    }
+                });
+            }
+        }
+        Image::new(new_size, Arc::new(pixels))
+    }
+}
+
+#[cfg(feature = "pf-image")]
+impl Image {
+    /// Decodes a PNG or JPEG image, guessing the format from the data.
+    ///
+    /// Images with other pixel formats, such as grayscale, RGB without alpha, or 16 bits per
+    /// channel, are converted to 8-bit RGBA.
+    pub fn decode(data: &[u8]) -> ImageResult<Image> {
+        Ok(Image::from_dynamic_image(image::load_from_memory(data)?))
+    }
+
+    /// Decodes an image in the given format, converting it to 8-bit RGBA as `decode()` does.
+    pub fn decode_with_format(data: &[u8], format: ImageFormat) -> ImageResult<Image> {
+        Ok(Image::from_dynamic_image(image::load_from_memory_with_format(data, format)?))
+    }
+
+    /// Reads and decodes the image file at `path`, guessing the format from its extension.
+    pub fn open<P>(path: P) -> ImageResult<Image> where P: AsRef<Path> {
+        Ok(Image::from_dynamic_image(image::open(path)?))
+    }
+
+    /// Converts an image from the `image` crate to 8-bit RGBA.
+    pub fn from_dynamic_image(image: DynamicImage) -> Image {
+        let image = image.to_rgba();
+        let size = Vector2I::new(image.width() as i32, image.height() as i32);
+        Image::from_rgba8(size, &image.into_raw())
+    }
+}
+
+impl PartialEq for Image {
+    #[inline]
+    fn eq(&self, other: &Image) -> bool {
+        self.size == other.size && self.pixels_hash == other.pixels_hash &&
+            (Arc::ptr_eq(&self.pixels, &other.pixels) || self.pixels == other.pixels)
+    }
+}
+
+impl Eq for Image {}
+
+impl Hash for Image {
+    #[inline]
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.size.x().hash(state);
+        self.size.y().hash(state);
+        self.pixels_hash.hash(state);
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Hash for Pattern {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.image.hash(state);
+        for value in &[
+            self.transform.m11(),
+            self.transform.m12(),
+            self.transform.m21(),
+            self.transform.m22(),
+            self.transform.vector.x(),
+            self.transform.vector.y(),
+        ] {
+            value.to_bits().hash(state);
+        }
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+#[derive(Serialize)]
+#[serde(rename = "Image")]
+struct SerializedImage<'a> {
+    size: Vector2I,
+    pixels: &'a [ColorU],
+}
+
+#[cfg(feature = "pf-serde")]
+#[derive(Deserialize)]
+#[serde(rename = "Image")]
+struct DeserializedImage {
+    size: Vector2I,
+    pixels: Vec<ColorU>,
+}
+
+#[cfg(feature = "pf-serde")]
+impl Serialize for Image {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedImage { size: self.size, pixels: &self.pixels }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for Image {
+    fn deserialize<D>(deserializer: D) -> Result<Image, D::Error> where D: Deserializer<'de> {
+        let data = DeserializedImage::deserialize(deserializer)?;
+        if data.size.x().max(0) as usize * data.size.y().max(0) as usize != data.pixels.len() {
+            return Err(D::Error::custom("the number of pixels doesn't match the image size"));
+        }
+        Ok(Image::new(data.size, Arc::new(data.pixels)))
+    }
+}
+
+// `core` has no general-purpose hasher, so without `std`, pixels are hashed with FNV-1a.
+#[cfg(not(feature = "std"))]
+struct DefaultHasher(u64);
+
+#[cfg(not(feature = "std"))]
+impl DefaultHasher {
+    #[inline]
+    fn new() -> DefaultHasher {
+        DefaultHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Hasher for DefaultHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "pf-serde"))]
+mod test {
+    use crate::pattern::{Image, Pattern};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use std::sync::Arc;
+
+    fn image() -> Image {
+        let pixels = vec![
+            ColorU { r: 255, g: 0, b: 0, a: 255 },
+            ColorU { r: 0, g: 255, b: 0, a: 128 },
+            ColorU { r: 0, g: 0, b: 255, a: 0 },
+            ColorU { r: 255, g: 255, b: 255, a: 255 },
+            ColorU { r: 0, g: 0, b: 0, a: 255 },
+            ColorU { r: 10, g: 20, b: 30, a: 40 },
+        ];
+        Image::new(Vector2I::new(3, 2), Arc::new(pixels))
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let pattern = Pattern::new(image(),
+                                   Transform2F::from_scale(Vector2F::splat(4.0))
+                                               .translate(Vector2F::new(10.0, -2.5)));
+        let loaded: Pattern =
+            serde_json::from_str(&serde_json::to_string(&pattern).unwrap()).unwrap();
+        assert_eq!(loaded, pattern);
+        assert_eq!(loaded.image.size(), Vector2I::new(3, 2));
+        assert_eq!(loaded.image.pixels(), pattern.image.pixels());
+        assert!(!loaded.image.is_opaque());
+    }
+
+    #[test]
+    fn test_serde_rejects_wrong_pixel_counts() {
+        let mut json = serde_json::to_value(&image()).unwrap();
+        json["size"] = serde_json::json!([2, 2]);
+        assert!(serde_json::from_value::<Image>(json.clone()).is_err());
+        json["size"] = serde_json::json!([-3, 2]);
+        assert!(serde_json::from_value::<Image>(json).is_err());
+    }
+}
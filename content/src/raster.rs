@@ -0,0 +1,310 @@
+// pathfinder/content/src/raster.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Simple CPU rasterization and blurring, for effects like shadows and filters that can't be
+//! applied to outlines.
+
+use crate::fill::FillRule;
+use crate::outline::Outline;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use alloc::vec;
+use alloc::vec::Vec;
+
+const FLATTENING_TOLERANCE: f32 = 0.25;
+const SAMPLES_PER_PIXEL: u32 = 4;
+const BOX_BLUR_PASSES: usize = 3;
+
+/// Returns the coverage of each pixel of `rect` by `outline`, in row-major order.
+///
+/// Coverage is computed exactly along each row and with `SAMPLES_PER_PIXEL` samples down each
+/// column.
+pub fn rasterize_coverage(outline: &Outline, rect: RectI, fill_rule: FillRule) -> Vec<f32> {
+    let mut edges = vec![];
+    for contour in outline.contours() {
+        if contour.is_empty() {
+            continue;
+        }
+
+        let first_point = contour.position_of(0);
+        let mut from = first_point;
+        for segment in contour.iter() {
+            segment.flatten(FLATTENING_TOLERANCE, |to, _| {
+                edges.push(LineSegment2F::new(from, to));
+                from = to;
+            });
+        }
+        edges.push(LineSegment2F::new(from, first_point));
+    }
+    edges.retain(|edge| edge.from_y() != edge.to_y());
+
+    let size = rect.size();
+    let sample_weight = 1.0 / SAMPLES_PER_PIXEL as f32;
+    let mut coverage = vec![0.0; size.x().max(0) as usize * size.y().max(0) as usize];
+    let mut crossings = vec![];
+    for y in 0..size.y() {
+        let row = &mut coverage[(y * size.x()) as usize..((y + 1) * size.x()) as usize];
+        for sample in 0..SAMPLES_PER_PIXEL {
+            let sample_y = (rect.min_y() + y) as f32 + (sample as f32 + 0.5) * sample_weight;
+
+            crossings.clear();
+            for edge in &edges {
+                let (min_y, max_y) = (edge.min_y(), edge.max_y());
+                if sample_y < min_y || sample_y >= max_y {
+                    continue;
+                }
+                let t = (sample_y - edge.from_y()) / (edge.to_y() - edge.from_y());
+                let x = edge.from_x() + t * (edge.to_x() - edge.from_x()) - rect.min_x() as f32;
+                crossings.push((x, if edge.to_y() > edge.from_y() { 1 } else { -1 }));
+            }
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if fill_rule.is_inside(winding) {
+                    add_span(row, pair[0].0, pair[1].0, sample_weight);
+                }
+            }
+        }
+    }
+    coverage
+}
+
+/// Blurs `values`, an image of `size` in row-major order, as a Gaussian blur with the given
+/// standard deviation in each direction would. Values outside the image are zero.
+///
+/// This uses three box blurs. See Peter Kovesi, "Fast Almost-Gaussian Filtering", 2010.
+pub fn gaussian_blur(values: &mut [f32], size: Vector2I, std_dev: Vector2F) {
+    debug_assert_eq!(values.len(), size.x().max(0) as usize * size.y().max(0) as usize);
+    let transposed_size = Vector2I::new(size.y(), size.x());
+    let (radii_x, radii_y) = (gaussian_box_radii(std_dev.x()), gaussian_box_radii(std_dev.y()));
+    for (&radius_x, &radius_y) in radii_x.iter().zip(radii_y.iter()) {
+        box_blur(values, size, Vector2I::new(1, size.x()), radius_x);
+        box_blur(values, transposed_size, Vector2I::new(size.x(), 1), radius_y);
+    }
+}
+
+/// The distance that `gaussian_blur()` spreads values from their pixel, in each direction.
+pub fn gaussian_blur_extent(std_dev: Vector2F) -> Vector2I {
+    Vector2I::new(gaussian_box_radii(std_dev.x()).iter().sum(),
+                  gaussian_box_radii(std_dev.y()).iter().sum())
+}
+
+fn add_span(row: &mut [f32], from_x: f32, to_x: f32, weight: f32) {
+    let width = row.len() as f32;
+    let (from_x, to_x) = (f32::max(from_x, 0.0), f32::min(to_x, width));
+    if from_x >= to_x {
+        return;
+    }
+
+    let (from_pixel, to_pixel) = (from_x.floor() as usize, to_x.floor() as usize);
+    if from_pixel == to_pixel {
+        row[from_pixel] += (to_x - from_x) * weight;
+        return;
+    }
+
+    row[from_pixel] += (from_pixel as f32 + 1.0 - from_x) * weight;
+    for value in &mut row[(from_pixel + 1)..to_pixel] {
+        *value += weight;
+    }
+    if to_pixel < row.len() {
+        row[to_pixel] += (to_x - to_pixel as f32) * weight;
+    }
+}
+
+// The radii of box blurs that, applied in turn, approximate a Gaussian blur with standard
+// deviation `sigma`.
+fn gaussian_box_radii(sigma: f32) -> [i32; BOX_BLUR_PASSES] {
+    let mut radii = [0; BOX_BLUR_PASSES];
+    if sigma.is_nan() || sigma <= 0.0 {
+        return radii;
+    }
+
+    let passes = BOX_BLUR_PASSES as f32;
+    let ideal_width = (12.0 * sigma * sigma / passes + 1.0).sqrt();
+    let mut lower_width = ideal_width.floor() as i32;
+    if lower_width % 2 == 0 {
+        lower_width -= 1;
+    }
+
+    let lower_width_f = lower_width as f32;
+    let lower_pass_count = ((12.0 * sigma * sigma - passes * lower_width_f * lower_width_f -
+                             4.0 * passes * lower_width_f - 3.0 * passes) /
+                            (-4.0 * lower_width_f - 4.0)).round() as usize;
+
+    for (pass, radius) in radii.iter_mut().enumerate() {
+        let width = if pass < lower_pass_count { lower_width } else { lower_width + 2 };
+        *radius = width / 2;
+    }
+    radii
+}
+
+// Blurs each line of `values` with a box of the given radius. `size` is the length of each line
+// and the number of lines; `stride` is the distance between adjacent values within a line and
+// between adjacent lines.
+fn box_blur(values: &mut [f32], size: Vector2I, stride: Vector2I, radius: i32) {
+    if radius <= 0 {
+        return;
+    }
+
+    let scale = 1.0 / (radius * 2 + 1) as f32;
+    let mut line = vec![0.0; size.x() as usize];
+    for line_index in 0..size.y() {
+        let index_of = |position: i32| (position * stride.x() + line_index * stride.y()) as usize;
+        for (position, value) in line.iter_mut().enumerate() {
+            *value = values[index_of(position as i32)];
+        }
+
+        // Values outside the image are zero.
+        let mut sum: f32 = line.iter().take(radius as usize).sum();
+        for position in 0..size.x() {
+            if position + radius < size.x() {
+                sum += line[(position + radius) as usize];
+            }
+            values[index_of(position)] = sum * scale;
+            if position - radius >= 0 {
+                sum -= line[(position - radius) as usize];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, Outline};
+    use crate::raster::{gaussian_blur, gaussian_blur_extent, rasterize_coverage};
+    use pathfinder_geometry::rect::{RectF, RectI};
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const EPSILON: f32 = 0.001;
+
+    fn rect_outline(rects: &[RectF]) -> Outline {
+        let mut outline = Outline::new();
+        for &rect in rects {
+            let mut contour = Contour::new();
+            contour.push_endpoint(rect.origin());
+            contour.push_endpoint(rect.upper_right());
+            contour.push_endpoint(rect.lower_right());
+            contour.push_endpoint(rect.lower_left());
+            contour.close();
+            outline.push_contour(contour);
+        }
+        outline
+    }
+
+    // An image of `size` that is zero except for a one in the middle.
+    fn impulse(size: Vector2I) -> Vec<f32> {
+        let mut values = vec![0.0; (size.x() * size.y()) as usize];
+        values[(size.y() / 2 * size.x() + size.x() / 2) as usize] = 1.0;
+        values
+    }
+
+    #[test]
+    fn test_rasterize_coverage() {
+        // Half-covered columns on the left and right, whole rows, in a raster offset by (1, 1).
+        let outline = rect_outline(&[RectF::new(Vector2F::new(2.5, 2.0), Vector2F::new(4.0, 3.0))]);
+        let rect = RectI::new(Vector2I::splat(1), Vector2I::splat(7));
+        let coverage = rasterize_coverage(&outline, rect, FillRule::Winding);
+        assert_eq!(coverage.len(), 49);
+        for y in 0..7 {
+            for x in 0..7 {
+                let expected = match (x + 1, y + 1) {
+                    (_, 0..=1) | (_, 5..=8) | (0..=1, _) | (7..=8, _) => 0.0,
+                    (2, _) | (6, _) => 0.5,
+                    _ => 1.0,
+                };
+                let value = coverage[y * 7 + x];
+                assert!((value - expected).abs() < EPSILON, "({}, {}): {}", x, y, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rasterize_coverage_fill_rules() {
+        // Two squares wound the same way: the inner one is a hole only with the even-odd rule.
+        let outline = rect_outline(&[RectF::new(Vector2F::default(), Vector2F::splat(6.0)),
+                                     RectF::new(Vector2F::splat(2.0), Vector2F::splat(2.0))]);
+        let rect = RectI::new(Vector2I::default(), Vector2I::splat(6));
+        let winding = rasterize_coverage(&outline, rect, FillRule::Winding);
+        let even_odd = rasterize_coverage(&outline, rect, FillRule::EvenOdd);
+        for (index, (&winding, &even_odd)) in winding.iter().zip(even_odd.iter()).enumerate() {
+            let (x, y) = (index % 6, index / 6);
+            let in_hole = (2..4).contains(&x) && (2..4).contains(&y);
+            assert!((winding - 1.0).abs() < EPSILON);
+            assert!((even_odd - if in_hole { 0.0 } else { 1.0 }).abs() < EPSILON);
+        }
+
+        // Nothing outside the outline is covered.
+        let rect = RectI::new(Vector2I::splat(10), Vector2I::splat(4));
+        assert!(rasterize_coverage(&outline, rect, FillRule::Winding).iter().all(|&value| {
+            value == 0.0
+        }));
+    }
+
+    #[test]
+    fn test_gaussian_blur() {
+        let size = Vector2I::splat(41);
+        let sigma = 3.0;
+        let mut values = impulse(size);
+        gaussian_blur(&mut values, size, Vector2F::splat(sigma));
+
+        // The blur spreads the value without losing any of it...
+        let sum: f32 = values.iter().sum();
+        assert!((sum - 1.0).abs() < EPSILON);
+        let center = values[(20 * 41 + 20) as usize];
+        assert!(center < 0.1 && values.iter().all(|&value| value <= center));
+
+        // ...symmetrically...
+        let value_at = |x: i32, y: i32| values[(y * 41 + x) as usize];
+        for y in 0..41 {
+            for x in 0..41 {
+                assert!((value_at(x, y) - value_at(40 - x, y)).abs() < EPSILON);
+                assert!((value_at(x, y) - value_at(y, x)).abs() < EPSILON);
+            }
+        }
+
+        // ...with about the variance of a Gaussian.
+        let variance: f32 = (0..41).map(|x| {
+            let column_sum: f32 = (0..41).map(|y| value_at(x, y)).sum();
+            column_sum * ((x - 20) * (x - 20)) as f32
+        }).sum();
+        assert!((variance - sigma * sigma).abs() < sigma * sigma * 0.15, "{}", variance);
+    }
+
+    #[test]
+    fn test_gaussian_blur_extent() {
+        // A blur reaches exactly as far as its extent, in each direction separately. Rounding
+        // leaves traces of the sliding sums beyond that.
+        let size = Vector2I::splat(41);
+        let std_dev = Vector2F::new(4.0, 1.0);
+        let extent = gaussian_blur_extent(std_dev);
+        assert!(extent.x() > extent.y() && extent.y() > 0);
+
+        let mut values = impulse(size);
+        gaussian_blur(&mut values, size, std_dev);
+        for y in 0..41i32 {
+            for x in 0..41i32 {
+                let reached = (x - 20).abs() <= extent.x() && (y - 20).abs() <= extent.y();
+                assert_eq!(values[(y * 41 + x) as usize] > 1e-6, reached, "({}, {})", x, y);
+            }
+        }
+
+        // No blur leaves the values alone.
+        assert_eq!(gaussian_blur_extent(Vector2F::default()), Vector2I::default());
+        let mut values = impulse(size);
+        gaussian_blur(&mut values, size, Vector2F::default());
+        assert_eq!(values, impulse(size));
+    }
+}
@@ -0,0 +1,306 @@
+// pathfinder/content/src/rounded_rect.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rectangles with rounded corners.
+//!
+//! User interfaces are mostly made of these, so they can be flattened directly into the line
+//! segments that the tiler consumes, without the general curve machinery.
+
+use crate::outline::{ArcDirection, Contour, Outline};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use core::f32::consts::PI;
+
+// No corner is flattened into more line segments than this.
+const MAX_SEGMENTS_PER_CORNER: u32 = 64;
+
+/// A rectangle whose corners are each a quarter of an axis-aligned ellipse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RoundedRect {
+    rect: RectF,
+    // Upper left, upper right, lower right, and lower left, in that order.
+    radii: [Vector2F; 4],
+}
+
+impl RoundedRect {
+    /// Creates a rounded rectangle with the given horizontal and vertical radii for the upper
+    /// left, upper right, lower right, and lower left corners, in that order.
+    ///
+    /// Negative radii are treated as zero, and a corner with a zero radius in either direction
+    /// is square. As in CSS, if the radii of two corners on the same side add up to more than the
+    /// length of that side, all radii are scaled down by the same factor until they fit.
+    pub fn new(rect: RectF, radii: [Vector2F; 4]) -> RoundedRect {
+        let normalize = |radius: Vector2F| {
+            if radius.x() > 0.0 && radius.y() > 0.0 { radius } else { Vector2F::default() }
+        };
+        let mut radii = [normalize(radii[0]),
+                         normalize(radii[1]),
+                         normalize(radii[2]),
+                         normalize(radii[3])];
+
+        let size = rect.size();
+        let mut scale: f32 = 1.0;
+        let side_radii = [
+            (size.x(), radii[0].x() + radii[1].x()),
+            (size.y(), radii[1].y() + radii[2].y()),
+            (size.x(), radii[2].x() + radii[3].x()),
+            (size.y(), radii[3].y() + radii[0].y()),
+        ];
+        for &(side_length, radius_sum) in &side_radii {
+            if radius_sum > side_length {
+                scale = scale.min(side_length.max(0.0) / radius_sum);
+            }
+        }
+        if scale < 1.0 {
+            for radius in &mut radii {
+                *radius = radius.scale(scale);
+            }
+        }
+
+        RoundedRect { rect, radii }
+    }
+
+    /// Creates a rounded rectangle with the same circular radius at each corner.
+    #[inline]
+    pub fn uniform(rect: RectF, radius: f32) -> RoundedRect {
+        RoundedRect::new(rect, [Vector2F::splat(radius); 4])
+    }
+
+    /// Creates a pill shape: a rectangle whose shorter sides are semicircles.
+    #[inline]
+    pub fn pill(rect: RectF) -> RoundedRect {
+        RoundedRect::uniform(rect, f32::min(rect.width(), rect.height()) * 0.5)
+    }
+
+    #[inline]
+    pub fn rect(&self) -> RectF {
+        self.rect
+    }
+
+    /// The radii of the upper left, upper right, lower right, and lower left corners, in that
+    /// order.
+    #[inline]
+    pub fn radii(&self) -> [Vector2F; 4] {
+        self.radii
+    }
+
+    /// Returns this shape under `transform`, if the transform keeps it a rounded rectangle: that
+    /// is, if it only scales, possibly reflecting, and translates.
+    pub fn transform(&self, transform: &Transform2F) -> Option<RoundedRect> {
+        if transform.m12() != 0.0 || transform.m21() != 0.0 {
+            return None;
+        }
+
+        let (origin, lower_right) = (*transform * self.rect.origin(),
+                                     *transform * self.rect.lower_right());
+        let rect = RectF::from_points(origin.min(lower_right), origin.max(lower_right));
+        let scale = Vector2F::new(transform.m11().abs(), transform.m22().abs());
+        let mut radii = [
+            self.radii[0] * scale,
+            self.radii[1] * scale,
+            self.radii[2] * scale,
+            self.radii[3] * scale,
+        ];
+        // Reflections move the corners around.
+        if transform.m11() < 0.0 {
+            radii = [radii[1], radii[0], radii[3], radii[2]];
+        }
+        if transform.m22() < 0.0 {
+            radii = [radii[3], radii[2], radii[1], radii[0]];
+        }
+        Some(RoundedRect { rect, radii })
+    }
+
+    /// Returns the outline of this shape, with the corners as curves.
+    pub fn to_outline(&self) -> Outline {
+        let mut contour = Contour::new();
+        self.for_each_corner(|center, radii, start_angle| {
+            if radii == Vector2F::default() {
+                contour.push_endpoint(center);
+                return;
+            }
+            let transform = Transform2F::from_scale(radii).translate(center);
+            let unit_vector_at = |angle: f32| Vector2F::new(f32::cos(angle), f32::sin(angle));
+            let chord = LineSegment2F::new(unit_vector_at(start_angle),
+                                           unit_vector_at(start_angle + PI * 0.5));
+            contour.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
+        });
+        contour.close();
+
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    /// Returns the outline of this shape with the corners flattened into line segments that
+    /// stray no more than `tolerance` from the true curves.
+    ///
+    /// Each corner is a monotonic quarter ellipse, and the number of segments it needs follows
+    /// directly from its radius, so no subdivision is necessary.
+    pub fn to_flattened_outline(&self, tolerance: f32) -> Outline {
+        let mut contour = Contour::new();
+        self.for_each_corner(|center, radii, start_angle| {
+            let max_radius = f32::max(radii.x(), radii.y());
+            if radii == Vector2F::default() {
+                contour.push_endpoint(center);
+                return;
+            }
+
+            // A chord subtending `angle` strays `max_radius * (1 - cos(angle / 2))` from a
+            // circle of radius `max_radius`.
+            let max_angle = if tolerance >= max_radius {
+                PI
+            } else {
+                2.0 * f32::acos(1.0 - tolerance / max_radius)
+            };
+            let segment_count = ((PI * 0.5 / max_angle).ceil() as u32)
+                .max(1)
+                .min(MAX_SEGMENTS_PER_CORNER);
+            for point_index in 0..=segment_count {
+                let angle = start_angle + PI * 0.5 * point_index as f32 / segment_count as f32;
+                let point = center + Vector2F::new(f32::cos(angle), f32::sin(angle)) * radii;
+                if contour.last_position() != Some(point) {
+                    contour.push_endpoint(point);
+                }
+            }
+        });
+        contour.close();
+
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    // Calls `f` with the center, radii, and starting angle of each corner's quarter ellipse,
+    // clockwise from the upper right. A corner with a zero radius is centered on the corner of
+    // the rectangle. The sides connect each corner to the next.
+    fn for_each_corner<F>(&self, mut f: F) where F: FnMut(Vector2F, Vector2F, f32) {
+        let rect = self.rect;
+        let radii = self.radii;
+        f(rect.upper_right() + Vector2F::new(-radii[1].x(), radii[1].y()), radii[1], -PI * 0.5);
+        f(rect.lower_right() - radii[2], radii[2], 0.0);
+        f(rect.lower_left() + Vector2F::new(radii[3].x(), -radii[3].y()), radii[3], PI * 0.5);
+        f(rect.origin() + radii[0], radii[0], PI);
+    }
+}
+
+impl Outline {
+    /// Creates the outline of a rectangle with rounded corners, as `RoundedRect::new()`
+    /// describes.
+    #[inline]
+    pub fn from_rounded_rect(rect: RectF, radii: [Vector2F; 4]) -> Outline {
+        RoundedRect::new(rect, radii).to_outline()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::Outline;
+    use crate::rounded_rect::RoundedRect;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use core::f32::consts::PI;
+
+    fn rect() -> RectF {
+        RectF::new(Vector2F::new(10.0, 20.0), Vector2F::new(100.0, 40.0))
+    }
+
+    fn area(outline: &Outline) -> f32 {
+        let mut outline = outline.clone();
+        outline.flatten(0.001);
+        let contour = &outline.contours()[0];
+        (0..contour.len()).map(|point_index| {
+            let p0 = contour.position_of(point_index);
+            let p1 = contour.position_of((point_index + 1) % contour.len());
+            p0.x() * p1.y() - p1.x() * p0.y()
+        }).sum::<f32>() * 0.5
+    }
+
+    #[test]
+    fn test_radii_are_normalized() {
+        let radii = [Vector2F::new(10.0, 5.0),
+                     Vector2F::new(-3.0, 4.0),
+                     Vector2F::new(6.0, 0.0),
+                     Vector2F::splat(2.0)];
+        assert_eq!(RoundedRect::new(rect(), radii).radii(),
+                   [Vector2F::new(10.0, 5.0),
+                    Vector2F::default(),
+                    Vector2F::default(),
+                    Vector2F::splat(2.0)]);
+
+        // Radii too big for the sides they're on are all scaled down by the same factor.
+        let rounded_rect = RoundedRect::uniform(rect(), 30.0);
+        assert_eq!(rounded_rect.radii(), [Vector2F::splat(20.0); 4]);
+        let rounded_rect = RoundedRect::new(rect(), [Vector2F::new(80.0, 10.0),
+                                                      Vector2F::new(40.0, 10.0),
+                                                      Vector2F::default(),
+                                                      Vector2F::default()]);
+        assert_eq!(rounded_rect.radii()[0], Vector2F::new(80.0 / 1.2, 10.0 / 1.2));
+
+        assert_eq!(RoundedRect::pill(rect()).radii(), [Vector2F::splat(20.0); 4]);
+    }
+
+    #[test]
+    fn test_transform() {
+        let radii = [Vector2F::splat(1.0),
+                     Vector2F::splat(2.0),
+                     Vector2F::splat(3.0),
+                     Vector2F::splat(4.0)];
+        let rounded_rect = RoundedRect::new(rect(), radii);
+
+        let transform = Transform2F::from_scale(Vector2F::new(-2.0, 1.0));
+        let flipped = rounded_rect.transform(&transform).unwrap();
+        assert_eq!(flipped.rect(),
+                   RectF::new(Vector2F::new(-220.0, 20.0), Vector2F::new(200.0, 40.0)));
+        assert_eq!(flipped.radii(), [Vector2F::new(4.0, 2.0),
+                                     Vector2F::new(2.0, 1.0),
+                                     Vector2F::new(8.0, 4.0),
+                                     Vector2F::new(6.0, 3.0)]);
+
+        let flipped = rounded_rect.transform(&Transform2F::from_scale(Vector2F::new(1.0, -1.0)));
+        assert_eq!(flipped.unwrap().radii(), [radii[3], radii[2], radii[1], radii[0]]);
+
+        // Rotations don't keep it axis-aligned.
+        assert!(rounded_rect.transform(&Transform2F::from_rotation(0.1)).is_none());
+    }
+
+    #[test]
+    fn test_outlines() {
+        let rounded_rect = RoundedRect::uniform(rect(), 10.0);
+        let expected_area = 100.0 * 40.0 - (4.0 - PI) * 100.0;
+
+        let outline = rounded_rect.to_outline();
+        let bounds = outline.bounds();
+        assert!((bounds.origin() - rect().origin()).length() < 0.001);
+        assert!((bounds.lower_right() - rect().lower_right()).length() < 0.001);
+        assert!((area(&outline) - expected_area).abs() < 0.1);
+
+        // Every flattened point lies on the rectangle's sides or on a corner's circle.
+        for &tolerance in &[1.0, 0.1, 0.01] {
+            let flattened = rounded_rect.to_flattened_outline(tolerance);
+            let contour = &flattened.contours()[0];
+            for point_index in 0..contour.len() {
+                assert!(contour.point_is_endpoint(point_index));
+                let point = contour.position_of(point_index);
+                let inner = RectF::new(Vector2F::new(20.0, 30.0), Vector2F::new(80.0, 20.0));
+                let nearest = point.clamp(inner.origin(), inner.lower_right());
+                assert!(((point - nearest).length() - 10.0).abs() < 0.001);
+            }
+            assert!((area(&flattened) - expected_area).abs() <= 4.0 * PI * 10.0 * tolerance);
+        }
+
+        // Square corners are single points.
+        let square = RoundedRect::uniform(rect(), 0.0).to_flattened_outline(0.1);
+        assert_eq!(square.contours()[0].len(), 4);
+    }
+}
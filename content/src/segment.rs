@@ -15,9 +15,11 @@ use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util::{self, EPSILON};
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_simd::default::F32x4;
-use std::f32::consts::SQRT_2;
+use core::f32::consts::SQRT_2;
 
 const MAX_NEWTON_ITERATIONS: u32 = 32;
+const MAX_FLATTENING_DEPTH: u32 = 16;
+const ARC_LENGTH_TOLERANCE: f32 = 0.01;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Segment {
@@ -224,14 +226,64 @@ impl Segment {
         }
     }
 
+    /// Approximates this segment with lines to within `tolerance`, calling `callback` with the
+    /// end point and curve parameter of each line in order. The start point is not reported.
+    pub fn flatten<F>(&self, tolerance: f32, mut callback: F) where F: FnMut(Vector2F, f32) {
+        if !self.is_none() {
+            self.flatten_range(tolerance, 0.0, 1.0, 0, &mut callback);
+        }
+    }
+
+    fn flatten_range<F>(&self, tolerance: f32, t0: f32, t1: f32, depth: u32, callback: &mut F)
+                        where F: FnMut(Vector2F, f32) {
+        if self.is_line() || depth >= MAX_FLATTENING_DEPTH ||
+                self.to_cubic().as_cubic_segment().is_flat(tolerance) {
+            callback(self.baseline.to(), t1);
+            return;
+        }
+
+        let t_mid = (t0 + t1) * 0.5;
+        let (before, after) = self.split(0.5);
+        before.flatten_range(tolerance, t0, t_mid, depth + 1, callback);
+        after.flatten_range(tolerance, t_mid, t1, depth + 1, callback);
+    }
+
     pub fn arc_length(&self) -> f32 {
-        // FIXME(pcwalton)
-        self.baseline.vector().length()
+        if self.is_line() {
+            return self.baseline.vector().length();
+        }
+
+        let (mut length, mut from) = (0.0, self.baseline.from());
+        self.flatten(ARC_LENGTH_TOLERANCE, |to, _| {
+            length += (to - from).length();
+            from = to;
+        });
+        length
     }
 
+    /// Returns the curve parameter at which the arc length from the start of this segment
+    /// reaches `distance`, or 1.0 if the segment is shorter than that.
     pub fn time_for_distance(&self, distance: f32) -> f32 {
-        // FIXME(pcwalton)
-        distance / self.arc_length()
+        if self.is_line() {
+            let length = self.baseline.vector().length();
+            return if distance < length { distance / length } else { 1.0 };
+        }
+
+        let (mut length, mut from, mut from_t) = (0.0, self.baseline.from(), 0.0);
+        let mut time = None;
+        self.flatten(ARC_LENGTH_TOLERANCE, |to, to_t| {
+            if time.is_some() {
+                return;
+            }
+            let line_length = (to - from).length();
+            if length + line_length > distance {
+                time = Some(util::lerp(from_t, to_t, (distance - length) / line_length));
+            }
+            length += line_length;
+            from = to;
+            from_t = to_t;
+        });
+        time.unwrap_or(1.0)
     }
 }
 
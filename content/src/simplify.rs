@@ -0,0 +1,426 @@
+// pathfinder/content/src/simplify.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reduces the number of points in an outline while staying within a tolerance of the original.
+//!
+//! Each contour is flattened, and the Ramer–Douglas–Peucker algorithm picks out the points that
+//! matter. Runs of points between sharp corners are then refitted with cubic Béziers, using the
+//! least-squares method from Philip J. Schneider, "An Algorithm for Automatically Fitting
+//! Digitized Curves", Graphics Gems, 1990. Runs that need more curves than lines are left as
+//! lines.
+
+use crate::outline::{Contour, Outline};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Where the simplified polyline turns by more than this much, the corner is kept sharp. This is
+// the cosine of the angle between the incoming and outgoing directions.
+const CORNER_COS_THRESHOLD: f32 = 0.5;
+
+const EPSILON: f32 = 0.000001;
+
+impl Outline {
+    /// Returns a copy of this outline with redundant points removed. No point of the result is
+    /// farther than about `tolerance` from the original outline.
+    pub fn simplify(&self, tolerance: f32) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.simplify(tolerance));
+        }
+        outline
+    }
+}
+
+impl Contour {
+    /// Returns a copy of this contour with redundant points removed. No point of the result is
+    /// farther than about `tolerance` from the original contour.
+    pub fn simplify(&self, tolerance: f32) -> Contour {
+        let points = self.flatten_for_simplification(tolerance * 0.25);
+        let mut output = Contour::new();
+        if points.len() < 2 {
+            points.iter().for_each(|&point| output.push_endpoint(point));
+            if self.is_closed() {
+                output.close();
+            }
+            return output;
+        }
+
+        let kept = simplify_polyline(&points, tolerance);
+        let corners = find_corners(&points, &kept);
+
+        output.push_endpoint(points[0]);
+        for corner_pair in corners.windows(2) {
+            let (start, end) = (corner_pair[0], corner_pair[1]);
+            let kept_in_run: Vec<usize> =
+                kept.iter().cloned().filter(|&index| index >= start && index <= end).collect();
+            let run = &points[start..(end + 1)];
+
+            let mut cubics = vec![];
+            let fitted = kept_in_run.len() > 2 &&
+                fit_cubics(run, &kept_in_run, start, tolerance, &mut cubics) &&
+                cubics.len() < kept_in_run.len() - 1;
+            if fitted {
+                for (ctrl, to) in cubics {
+                    output.push_cubic(ctrl.from(), ctrl.to(), to);
+                }
+            } else {
+                kept_in_run[1..].iter().for_each(|&index| output.push_endpoint(points[index]));
+            }
+        }
+
+        if self.is_closed() {
+            // If the contour ends with a line back to its start, let the closing segment stand in
+            // for it.
+            let point_count = output.points.len();
+            if point_count > 2 && output.last_position() == Some(points[0]) &&
+                    output.flags[point_count - 2].is_empty() {
+                output.points.pop();
+                output.flags.pop();
+            }
+            output.close();
+        }
+
+        // Contours that are already made of a few well-fitting curves can only get worse.
+        if output.len() >= self.len() {
+            return self.clone();
+        }
+        output
+    }
+
+    fn flatten_for_simplification(&self, tolerance: f32) -> Vec<Vector2F> {
+        let mut points = vec![];
+        if self.is_empty() {
+            return points;
+        }
+
+        points.push(self.position_of(0));
+        for segment in self.iter() {
+            segment.flatten(tolerance, |point, _| {
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+            });
+        }
+        points
+    }
+}
+
+// Ramer–Douglas–Peucker. Returns the indices of the points to keep, in order.
+fn simplify_polyline(points: &[Vector2F], tolerance: f32) -> Vec<usize> {
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let chord = LineSegment2F::new(points[start], points[end]);
+        let mut farthest = None;
+        let mut farthest_square_distance = tolerance * tolerance;
+        for index in (start + 1)..end {
+            let square_distance = square_distance_to_line_segment(points[index], chord);
+            if square_distance > farthest_square_distance {
+                farthest = Some(index);
+                farthest_square_distance = square_distance;
+            }
+        }
+
+        if let Some(farthest) = farthest {
+            keep[farthest] = true;
+            stack.push((start, farthest));
+            stack.push((farthest, end));
+        }
+    }
+
+    (0..points.len()).filter(|&index| keep[index]).collect()
+}
+
+// Returns the indices of the kept points at which the path should stay sharp, including both
+// ends of the polyline.
+fn find_corners(points: &[Vector2F], kept: &[usize]) -> Vec<usize> {
+    let mut corners = vec![kept[0]];
+    for window in kept.windows(3) {
+        let incoming = points[window[1]] - points[window[0]];
+        let outgoing = points[window[2]] - points[window[1]];
+        let lengths = incoming.length() * outgoing.length();
+        if lengths < EPSILON || incoming.dot(outgoing) < CORNER_COS_THRESHOLD * lengths {
+            corners.push(window[1]);
+        }
+    }
+
+    corners.push(kept[kept.len() - 1]);
+    corners.dedup();
+    corners
+}
+
+// Fits cubics to `run`, pushing the control points and end point of each. `kept` holds the
+// indices of the points in the run kept by RDP, offset by `offset`. Returns false if fitting
+// failed.
+fn fit_cubics(run: &[Vector2F],
+              kept: &[usize],
+              offset: usize,
+              tolerance: f32,
+              cubics: &mut Vec<(LineSegment2F, Vector2F)>)
+              -> bool {
+    // Estimate the end tangents from the simplified polyline, which is less noisy than the
+    // flattened points.
+    let start_tangent = (run[kept[1] - offset] - run[0]).normalize();
+    let end_tangent = (run[kept[kept.len() - 2] - offset] - run[run.len() - 1]).normalize();
+    fit_cubic_range(run, start_tangent, end_tangent, tolerance, cubics)
+}
+
+fn fit_cubic_range(points: &[Vector2F],
+                   start_tangent: Vector2F,
+                   end_tangent: Vector2F,
+                   tolerance: f32,
+                   cubics: &mut Vec<(LineSegment2F, Vector2F)>)
+                   -> bool {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if points.len() == 2 {
+        let third = (last - first).length() / 3.0;
+        let ctrl = LineSegment2F::new(first + start_tangent.scale(third),
+                                      last + end_tangent.scale(third));
+        cubics.push((ctrl, last));
+        return true;
+    }
+
+    let times = chord_length_parameterize(points);
+    let ctrl = fit_cubic(points, &times, start_tangent, end_tangent);
+    let (max_square_error, split_index) = max_square_error(points, &times, ctrl);
+    if max_square_error <= tolerance * tolerance {
+        cubics.push((ctrl, last));
+        return true;
+    }
+
+    if split_index == 0 || split_index >= points.len() - 1 {
+        return false;
+    }
+
+    let center_tangent = (points[split_index - 1] - points[split_index + 1]).normalize();
+    if center_tangent.is_zero() {
+        return false;
+    }
+    fit_cubic_range(&points[0..(split_index + 1)],
+                    start_tangent,
+                    center_tangent,
+                    tolerance,
+                    cubics) &&
+        fit_cubic_range(&points[split_index..],
+                        -center_tangent,
+                        end_tangent,
+                        tolerance,
+                        cubics)
+}
+
+fn chord_length_parameterize(points: &[Vector2F]) -> Vec<f32> {
+    let mut times = Vec::with_capacity(points.len());
+    let mut length = 0.0;
+    times.push(0.0);
+    for index in 1..points.len() {
+        length += (points[index] - points[index - 1]).length();
+        times.push(length);
+    }
+    if length > 0.0 {
+        times.iter_mut().for_each(|time| *time /= length);
+    }
+    times
+}
+
+// Finds the control points of the cubic with the given end tangents that best fits `points` in
+// the least-squares sense.
+fn fit_cubic(points: &[Vector2F], times: &[f32], start_tangent: Vector2F, end_tangent: Vector2F)
+             -> LineSegment2F {
+    let (first, last) = (points[0], points[points.len() - 1]);
+
+    let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (&point, &t) in points.iter().zip(times.iter()) {
+        let mt = 1.0 - t;
+        let (b0, b1, b2, b3) = (mt * mt * mt, 3.0 * t * mt * mt, 3.0 * t * t * mt, t * t * t);
+        let a0 = start_tangent.scale(b1);
+        let a1 = end_tangent.scale(b2);
+        c00 += a0.dot(a0);
+        c01 += a0.dot(a1);
+        c11 += a1.dot(a1);
+        let residual = point - (first.scale(b0 + b1) + last.scale(b2 + b3));
+        x0 += a0.dot(residual);
+        x1 += a1.dot(residual);
+    }
+
+    let det_c = c00 * c11 - c01 * c01;
+    let (mut alpha_0, mut alpha_1) = if det_c.abs() > EPSILON {
+        ((x0 * c11 - x1 * c01) / det_c, (c00 * x1 - c01 * x0) / det_c)
+    } else {
+        (0.0, 0.0)
+    };
+
+    // Fall back to Wu and Barsky's heuristic if the solution is degenerate.
+    let chord_length = (last - first).length();
+    let min_alpha = chord_length * EPSILON;
+    if alpha_0 < min_alpha || alpha_1 < min_alpha {
+        alpha_0 = chord_length / 3.0;
+        alpha_1 = alpha_0;
+    }
+
+    LineSegment2F::new(first + start_tangent.scale(alpha_0), last + end_tangent.scale(alpha_1))
+}
+
+fn max_square_error(points: &[Vector2F], times: &[f32], ctrl: LineSegment2F) -> (f32, usize) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (mut max_square_error, mut max_index) = (0.0, points.len() / 2);
+    for (index, (&point, &t)) in points.iter().zip(times.iter()).enumerate() {
+        let mt = 1.0 - t;
+        let sample = first.scale(mt * mt * mt) + ctrl.from().scale(3.0 * t * mt * mt) +
+            ctrl.to().scale(3.0 * t * t * mt) + last.scale(t * t * t);
+        let square_error = (sample - point).square_length();
+        if square_error > max_square_error {
+            max_square_error = square_error;
+            max_index = index;
+        }
+    }
+    (max_square_error, max_index)
+}
+
+fn square_distance_to_line_segment(point: Vector2F, line_segment: LineSegment2F) -> f32 {
+    let vector = line_segment.vector();
+    let square_length = vector.square_length();
+    if square_length < EPSILON {
+        return (point - line_segment.from()).square_length();
+    }
+    let t = (point - line_segment.from()).dot(vector) / square_length;
+    let t = f32::max(0.0, f32::min(1.0, t));
+    (point - line_segment.sample(t)).square_length()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::simplify::square_distance_to_line_segment;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn polyline(points: &[Vector2F], closed: bool) -> Contour {
+        let mut contour = Contour::new();
+        points.iter().for_each(|&point| contour.push_endpoint(point));
+        if closed {
+            contour.close();
+        }
+        contour
+    }
+
+    fn flattened_lines(contour: &Contour) -> Vec<LineSegment2F> {
+        let mut lines = vec![];
+        let mut last_point = contour.position_of(0);
+        for segment in contour.iter() {
+            segment.flatten(0.001, |point, _| {
+                lines.push(LineSegment2F::new(last_point, point));
+                last_point = point;
+            });
+        }
+        lines
+    }
+
+    // The farthest any point of `a` gets from `b`, measured both ways.
+    fn distance(a: &Contour, b: &Contour) -> f32 {
+        fn one_way(a: &Contour, b: &Contour) -> f32 {
+            let b_lines = flattened_lines(b);
+            flattened_lines(a).iter().flat_map(|line| {
+                (0..=4).map(move |step| line.sample(step as f32 / 4.0))
+            }).map(|point| {
+                b_lines.iter()
+                       .map(|&line| square_distance_to_line_segment(point, line))
+                       .fold(f32::INFINITY, f32::min)
+            }).fold(0.0, f32::max).sqrt()
+        }
+        f32::max(one_way(a, b), one_way(b, a))
+    }
+
+    fn wave(point_count: usize, amplitude: f32) -> Contour {
+        let points: Vec<Vector2F> = (0..point_count).map(|index| {
+            let x = index as f32 * 0.25;
+            Vector2F::new(x, (x * 0.2).sin() * amplitude)
+        }).collect();
+        polyline(&points, false)
+    }
+
+    #[test]
+    fn test_keeps_endpoints() {
+        let contour = wave(200, 10.0);
+        let simplified = contour.simplify(0.1);
+        assert!(!simplified.is_closed());
+        assert!(simplified.len() < contour.len() / 2);
+        assert_eq!(simplified.position_of(0), contour.position_of(0));
+        assert_eq!(simplified.last_position(), contour.last_position());
+
+        let line: Vec<Vector2F> =
+            (0..=10).map(|index| Vector2F::new(index as f32, index as f32 * 2.0)).collect();
+        let simplified = polyline(&line, false).simplify(0.01);
+        assert_eq!(simplified.len(), 2);
+        assert_eq!(simplified.position_of(0), line[0]);
+        assert_eq!(simplified.position_of(1), line[10]);
+    }
+
+    #[test]
+    fn test_honours_tolerance() {
+        let contour = wave(200, 10.0);
+        let mut last_len = contour.len() + 1;
+        for &tolerance in &[0.01, 0.1, 1.0] {
+            let simplified = contour.simplify(tolerance);
+            let distance = distance(&simplified, &contour);
+            assert!(distance <= tolerance * 1.1, "{} > {}", distance, tolerance);
+            assert!(simplified.len() <= last_len);
+            last_len = simplified.len();
+        }
+
+        // A zigzag that sticks out by more than the tolerance keeps its teeth.
+        let zigzag: Vec<Vector2F> = (0..=8).map(|index| {
+            Vector2F::new(index as f32 * 4.0, if index % 2 == 0 { 0.0 } else { 1.0 })
+        }).collect();
+        let zigzag = polyline(&zigzag, false);
+        assert_eq!(zigzag.simplify(0.5).len(), zigzag.len());
+        assert_eq!(zigzag.simplify(2.0).len(), 2);
+    }
+
+    #[test]
+    fn test_closed_contours_keep_corners() {
+        let mut points = vec![];
+        let corners = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        for index in 0..4 {
+            let from = Vector2F::new(corners[index].0, corners[index].1);
+            let next = corners[(index + 1) % 4];
+            let to = Vector2F::new(next.0, next.1);
+            (0..10).for_each(|step| points.push(from.lerp(to, step as f32 / 10.0)));
+        }
+        let contour = polyline(&points, true);
+
+        let simplified = contour.simplify(0.1);
+        assert!(simplified.is_closed());
+        assert_eq!(simplified.len(), 4);
+        for (index, &(x, y)) in corners.iter().enumerate() {
+            let corner = simplified.position_of(index as u32);
+            assert!((corner - Vector2F::new(x, y)).length() < EPSILON);
+        }
+        assert!(distance(&simplified, &contour) < EPSILON);
+
+        // Nothing left to remove.
+        let again = simplified.simplify(0.1);
+        assert_eq!(again.len(), 4);
+        assert!(again.is_closed());
+
+        let mut outline = Outline::new();
+        outline.push_contour(contour.clone());
+        outline.push_contour(wave(200, 10.0));
+        let simplified = outline.simplify(0.1);
+        assert_eq!(simplified.contours().len(), 2);
+        assert_eq!(simplified.contours()[0].len(), 4);
+    }
+}
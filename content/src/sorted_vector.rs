@@ -10,8 +10,13 @@
 
 //! A vector that maintains sorted order with insertion sort.
 
-use std::cmp::Ordering;
-use std::convert;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert;
+
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct SortedVector<T>
@@ -98,3 +103,23 @@ mod test {
         }
     }
 }
+
+#[cfg(feature = "pf-serde")]
+impl<T> Serialize for SortedVector<T> where T: PartialOrd + Serialize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.array.serialize(serializer)
+    }
+}
+
+// The values are sorted as they're loaded, in case they were edited out of order.
+#[cfg(feature = "pf-serde")]
+impl<'de, T> Deserialize<'de> for SortedVector<T> where T: PartialOrd + Deserialize<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<SortedVector<T>, D::Error>
+                      where D: Deserializer<'de> {
+        let mut vector = SortedVector::new();
+        for value in Vec::<T>::deserialize(deserializer)? {
+            vector.push(value);
+        }
+        Ok(vector)
+    }
+}
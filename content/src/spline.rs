@@ -0,0 +1,249 @@
+// pathfinder/content/src/spline.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Contours that pass smoothly through a list of points, such as smoothed line series in charts.
+//!
+//! Splines are converted to cubic Bézier curves, one between each pair of consecutive points.
+//! Both kinds take a tension, which pulls the control points toward the points they're next to:
+//! a tension of 0 gives the usual spline, and a tension of 1 gives straight lines.
+
+use crate::outline::Contour;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec;
+use alloc::vec::Vec;
+
+impl Contour {
+    /// Creates a contour through `points` with a cardinal spline, whose tangent at each point is
+    /// parallel to the line between its neighbors. A tension of 0 gives a Catmull-Rom spline.
+    ///
+    /// Closed contours wrap around, so the spline is smooth at the first point too. The ends of
+    /// open ones are treated as their own neighbors.
+    pub fn from_cardinal_spline(points: &[Vector2F], tension: f32, closed: bool) -> Contour {
+        let mut contour = Contour::with_capacity(points.len() * 3);
+        if points.is_empty() {
+            return contour;
+        }
+
+        let point_count = points.len();
+        let point_at = |index: isize| -> Vector2F {
+            if closed {
+                points[index.rem_euclid(point_count as isize) as usize]
+            } else {
+                points[index.max(0).min(point_count as isize - 1) as usize]
+            }
+        };
+
+        contour.push_endpoint(points[0]);
+        let segment_count = if closed { point_count } else { point_count - 1 };
+        let scale = (1.0 - tension) / 6.0;
+        for index in 0..segment_count as isize {
+            let (prev, from) = (point_at(index - 1), point_at(index));
+            let (to, next) = (point_at(index + 1), point_at(index + 2));
+            let ctrl0 = from + (to - prev).scale(scale);
+            let ctrl1 = to - (next - from).scale(scale);
+            contour.push_cubic(ctrl0, ctrl1, to);
+        }
+
+        if closed {
+            contour.close();
+        }
+        contour
+    }
+
+    /// Creates a contour through `points` with a Catmull-Rom spline.
+    #[inline]
+    pub fn from_catmull_rom_spline(points: &[Vector2F], closed: bool) -> Contour {
+        Contour::from_cardinal_spline(points, 0.0, closed)
+    }
+
+    /// Creates an open contour through `points` with a natural cubic spline, whose curvature
+    /// changes continuously along it and is zero at the ends.
+    ///
+    /// Unlike a cardinal spline, moving one point bends the whole curve a little rather than just
+    /// the segments next to it.
+    pub fn from_natural_spline(points: &[Vector2F], tension: f32) -> Contour {
+        let mut contour = Contour::with_capacity(points.len() * 3);
+        if points.is_empty() {
+            return contour;
+        }
+
+        contour.push_endpoint(points[0]);
+        let segment_count = points.len() - 1;
+        if segment_count == 0 {
+            return contour;
+        }
+
+        let first_ctrls = natural_spline_first_ctrls(points);
+        for index in 0..segment_count {
+            let (from, to) = (points[index], points[index + 1]);
+            let ctrl0 = first_ctrls[index];
+            let ctrl1 = if index + 1 < segment_count {
+                to.scale(2.0) - first_ctrls[index + 1]
+            } else {
+                (to + ctrl0).scale(0.5)
+            };
+            contour.push_cubic(from.lerp(ctrl0, 1.0 - tension), to.lerp(ctrl1, 1.0 - tension), to);
+        }
+        contour
+    }
+}
+
+// Solves for the first control point of each segment of a natural cubic spline through `points`,
+// of which there must be at least two. Matching first and second derivatives at each inner point,
+// with zero second derivatives at the ends, gives a tridiagonal system, solved here with the
+// Thomas algorithm.
+fn natural_spline_first_ctrls(points: &[Vector2F]) -> Vec<Vector2F> {
+    let segment_count = points.len() - 1;
+    if segment_count == 1 {
+        return vec![points[0].lerp(points[1], 1.0 / 3.0)];
+    }
+
+    // Row `i` is `lower[i] * x[i - 1] + diagonal[i] * x[i] + upper[i] * x[i + 1] = rhs[i]`.
+    let mut lower = vec![1.0; segment_count];
+    let mut diagonal = vec![4.0; segment_count];
+    let upper = vec![1.0; segment_count];
+    let mut rhs: Vec<Vector2F> = (0..segment_count).map(|index| {
+        points[index].scale(4.0) + points[index + 1].scale(2.0)
+    }).collect();
+    diagonal[0] = 2.0;
+    rhs[0] = points[0] + points[1].scale(2.0);
+    let last = segment_count - 1;
+    lower[last] = 2.0;
+    diagonal[last] = 7.0;
+    rhs[last] = points[last].scale(8.0) + points[last + 1];
+
+    for index in 1..segment_count {
+        let factor = lower[index] / diagonal[index - 1];
+        diagonal[index] -= factor * upper[index - 1];
+        rhs[index] = rhs[index] - rhs[index - 1].scale(factor);
+    }
+
+    let mut ctrls = vec![Vector2F::default(); segment_count];
+    ctrls[last] = rhs[last].scale(1.0 / diagonal[last]);
+    for index in (0..last).rev() {
+        let rhs = rhs[index] - ctrls[index + 1].scale(upper[index]);
+        ctrls[index] = rhs.scale(1.0 / diagonal[index]);
+    }
+    ctrls
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::Contour;
+    use pathfinder_geometry::vector::Vector2F;
+
+    const EPSILON: f32 = 0.0001;
+
+    fn points() -> Vec<Vector2F> {
+        [(0.0, 0.0), (10.0, 5.0), (20.0, -5.0), (30.0, 0.0), (35.0, 10.0)].iter().map(|&(x, y)| {
+            Vector2F::new(x, y)
+        }).collect()
+    }
+
+    // The control points of each cubic segment of a contour made only of cubics.
+    fn cubics(contour: &Contour) -> Vec<[Vector2F; 4]> {
+        (0..(contour.len() - 1) / 3).map(|segment_index| {
+            let point_index = segment_index * 3;
+            [contour.position_of(point_index), contour.position_of(point_index + 1),
+             contour.position_of(point_index + 2), contour.position_of(point_index + 3)]
+        }).collect()
+    }
+
+    fn assert_close(a: Vector2F, b: Vector2F) {
+        assert!((a - b).length() < EPSILON, "expected {:?}, found {:?}", b, a);
+    }
+
+    #[test]
+    fn test_catmull_rom_spline() {
+        let points = points();
+        let contour = Contour::from_catmull_rom_spline(&points, false);
+        let cubics = cubics(&contour);
+        assert_eq!(cubics.len(), points.len() - 1);
+        assert!(!contour.is_closed());
+        for (index, cubic) in cubics.iter().enumerate() {
+            assert_eq!(cubic[0], points[index]);
+            assert_eq!(cubic[3], points[index + 1]);
+        }
+
+        // The tangent at each inner point is parallel to the line between its neighbors, and the
+        // same on both sides of it.
+        for index in 1..(points.len() - 1) {
+            let incoming = cubics[index - 1][3] - cubics[index - 1][2];
+            let outgoing = cubics[index][1] - cubics[index][0];
+            assert_close(incoming, outgoing);
+            assert_close(outgoing, (points[index + 1] - points[index - 1]).scale(1.0 / 6.0));
+        }
+    }
+
+    #[test]
+    fn test_closed_cardinal_spline() {
+        let points = points();
+        let contour = Contour::from_cardinal_spline(&points, 0.5, true);
+        let cubics = cubics(&contour);
+        assert!(contour.is_closed());
+        assert_eq!(cubics.len(), points.len());
+        assert_eq!(cubics[points.len() - 1][3], points[0]);
+
+        // The spline wraps around, so it's smooth at the first point too.
+        let incoming = cubics[points.len() - 1][3] - cubics[points.len() - 1][2];
+        let outgoing = cubics[0][1] - cubics[0][0];
+        assert_close(incoming, outgoing);
+        assert_close(outgoing, (points[1] - points[points.len() - 1]).scale(0.5 / 6.0));
+    }
+
+    #[test]
+    fn test_full_tension_gives_straight_lines() {
+        let points = points();
+        for contour in &[Contour::from_cardinal_spline(&points, 1.0, false),
+                         Contour::from_natural_spline(&points, 1.0)] {
+            for cubic in cubics(contour) {
+                assert_close(cubic[1], cubic[0]);
+                assert_close(cubic[2], cubic[3]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_natural_spline() {
+        let points = points();
+        let contour = Contour::from_natural_spline(&points, 0.0);
+        let cubics = cubics(&contour);
+        assert_eq!(cubics.len(), points.len() - 1);
+
+        // Second derivatives at the start and end of a cubic, up to a common factor of 6.
+        let start_curvature = |cubic: &[Vector2F; 4]| cubic[0] - cubic[1].scale(2.0) + cubic[2];
+        let end_curvature = |cubic: &[Vector2F; 4]| cubic[1] - cubic[2].scale(2.0) + cubic[3];
+
+        // Both the slope and the curvature are continuous at the inner points, and the curvature
+        // is zero at the ends.
+        for index in 1..cubics.len() {
+            let (before, after) = (&cubics[index - 1], &cubics[index]);
+            assert_eq!(before[3], points[index]);
+            assert_close(before[3] - before[2], after[1] - after[0]);
+            assert_close(end_curvature(before), start_curvature(after));
+        }
+        assert_close(start_curvature(&cubics[0]), Vector2F::default());
+        assert_close(end_curvature(&cubics[cubics.len() - 1]), Vector2F::default());
+
+        // Two points give a straight line.
+        let line = Contour::from_natural_spline(&points[0..2], 0.0);
+        let line = [line.position_of(0), line.position_of(1), line.position_of(2)];
+        assert_close(line[1], points[0].lerp(points[1], 1.0 / 3.0));
+        assert_close(line[2], points[0].lerp(points[1], 2.0 / 3.0));
+    }
+
+    #[test]
+    fn test_splines_through_few_points() {
+        assert!(Contour::from_catmull_rom_spline(&[], true).is_empty());
+        assert!(Contour::from_natural_spline(&[], 0.0).is_empty());
+        assert_eq!(Contour::from_catmull_rom_spline(&points()[0..1], false).len(), 1);
+        assert_eq!(Contour::from_natural_spline(&points()[0..1], 0.0).len(), 1);
+    }
+}
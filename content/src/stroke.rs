@@ -10,16 +10,26 @@
 
 //! Utilities for converting path strokes to fills.
 
+use crate::boolean::BooleanOp;
+use crate::fill::FillRule;
 use crate::outline::{ArcDirection, Contour, Outline, PushSegmentFlags};
 use crate::segment::Segment;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::util;
 use pathfinder_geometry::vector::Vector2F;
-use std::f32;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32;
 
 const TOLERANCE: f32 = 0.01;
 
+// The largest factor by which a variable-width stroke's offset is lengthened at a corner before
+// the join is clipped.
+const VARIABLE_STROKE_MITER_LIMIT: f32 = 4.0;
+
 pub struct OutlineStrokeToFill<'a> {
     input: &'a Outline,
     output: Outline,
@@ -47,6 +57,28 @@ pub enum LineJoin {
     Round,
 }
 
+/// Converts an outline into the fill of a stroke whose width varies along each contour.
+///
+/// Curves are flattened before offsetting, and corners are joined with a miter whose length is
+/// clipped so that it never exceeds a few times the local stroke width.
+pub struct OutlineVariableStrokeToFill<'a> {
+    input: &'a Outline,
+    output: Outline,
+    profile: &'a StrokeWidthProfile,
+    line_cap: LineCap,
+}
+
+/// Describes how the width of a variable-width stroke changes along each contour.
+pub enum StrokeWidthProfile {
+    /// The stroke width at each on-curve point of each contour, one list per contour. Widths are
+    /// linearly interpolated along each segment. If a list is shorter than its contour, its last
+    /// width is repeated; a missing or empty list produces no stroke for that contour.
+    PerPoint(Vec<Vec<f32>>),
+    /// A function that returns the stroke width given a contour index and the normalized
+    /// distance along that contour, from 0.0 at its start to 1.0 at its end.
+    Function(Box<dyn Fn(u32, f32) -> f32>),
+}
+
 impl<'a> OutlineStrokeToFill<'a> {
     #[inline]
     pub fn new(input: &Outline, style: StrokeStyle) -> OutlineStrokeToFill {
@@ -147,6 +179,190 @@ impl<'a> OutlineStrokeToFill<'a> {
     }
 }
 
+impl<'a> OutlineVariableStrokeToFill<'a> {
+    #[inline]
+    pub fn new(input: &'a Outline, profile: &'a StrokeWidthProfile, line_cap: LineCap)
+               -> OutlineVariableStrokeToFill<'a> {
+        OutlineVariableStrokeToFill { input, output: Outline::new(), profile, line_cap }
+    }
+
+    pub fn offset(&mut self) {
+        let mut new_contours = vec![];
+        for (contour_index, input) in self.input.contours.iter().enumerate() {
+            let (positions, widths) = self.flatten_contour(contour_index as u32, input);
+            self.stroke_polyline(&mut new_contours, &positions, &widths, input.closed);
+        }
+
+        let mut new_bounds = None;
+        new_contours.iter().for_each(|contour| contour.update_bounds(&mut new_bounds));
+
+        self.output.contours = new_contours;
+        self.output.bounds = new_bounds.unwrap_or_else(|| RectF::default());
+    }
+
+    #[inline]
+    pub fn into_outline(self) -> Outline {
+        self.output
+    }
+
+    // Returns the flattened positions of the contour along with the stroke width at each one.
+    fn flatten_contour(&self, contour_index: u32, contour: &Contour) -> (Vec<Vector2F>, Vec<f32>) {
+        let (mut positions, mut segment_times) = (vec![], vec![]);
+        if contour.is_empty() {
+            return (positions, vec![]);
+        }
+
+        positions.push(contour.position_of(0));
+        segment_times.push((0, 0.0));
+        let mut segment_count = 0;
+        for (segment_index, segment) in contour.iter().enumerate() {
+            segment.flatten(TOLERANCE, |position, t| {
+                positions.push(position);
+                segment_times.push((segment_index as u32, t));
+            });
+            segment_count += 1;
+        }
+
+        let widths = match *self.profile {
+            StrokeWidthProfile::PerPoint(ref widths) => {
+                let widths = match widths.get(contour_index as usize) {
+                    Some(widths) if !widths.is_empty() => widths,
+                    _ => return (vec![], vec![]),
+                };
+
+                // The endpoint after the closing segment of a closed contour is its first point.
+                let endpoint_count = if contour.closed { segment_count } else { segment_count + 1 };
+                let width_of = |endpoint_index: u32| {
+                    let endpoint_index = endpoint_index % u32::max(endpoint_count, 1);
+                    widths[usize::min(endpoint_index as usize, widths.len() - 1)]
+                };
+                segment_times.iter().map(|&(segment_index, t)| {
+                    util::lerp(width_of(segment_index), width_of(segment_index + 1), t)
+                }).collect()
+            }
+            StrokeWidthProfile::Function(ref function) => {
+                let mut distances = Vec::with_capacity(positions.len());
+                let mut distance = 0.0;
+                for (position_index, &position) in positions.iter().enumerate() {
+                    if position_index > 0 {
+                        distance += (position - positions[position_index - 1]).length();
+                    }
+                    distances.push(distance);
+                }
+
+                let total_distance = distance;
+                distances.iter().map(|&distance| {
+                    let t = if total_distance > 0.0 { distance / total_distance } else { 0.0 };
+                    function(contour_index, t)
+                }).collect()
+            }
+        };
+
+        (positions, widths)
+    }
+
+    fn stroke_polyline(&self,
+                       new_contours: &mut Vec<Contour>,
+                       positions: &[Vector2F],
+                       widths: &[f32],
+                       closed: bool) {
+        // Drop coincident points, since they have no direction.
+        let (mut points, mut radii): (Vec<Vector2F>, Vec<f32>) = (vec![], vec![]);
+        for (&position, &width) in positions.iter().zip(widths.iter()) {
+            if let Some(&last_position) = points.last() {
+                if (position - last_position).square_length() < TOLERANCE * TOLERANCE {
+                    continue;
+                }
+            }
+            points.push(position);
+            radii.push(f32::max(width, 0.0) * 0.5);
+        }
+        if closed && points.len() > 1 &&
+                (points[0] - points[points.len() - 1]).square_length() < TOLERANCE * TOLERANCE {
+            points.pop();
+            radii.pop();
+        }
+        if points.len() < 2 {
+            return;
+        }
+
+        // Compute the unit normal of each edge of the polyline.
+        let edge_count = if closed { points.len() } else { points.len() - 1 };
+        let normals: Vec<Vector2F> = (0..edge_count).map(|edge_index| {
+            let next_point = points[(edge_index + 1) % points.len()];
+            let gradient = (next_point - points[edge_index]).normalize();
+            gradient.yx().scale_xy(Vector2F::new(-1.0, 1.0))
+        }).collect();
+
+        // Offset each point along the bisector of its adjacent edges.
+        let (mut side_0, mut side_1) = (vec![], vec![]);
+        for point_index in 0..points.len() {
+            let next_normal = normals[usize::min(point_index, edge_count - 1)];
+            let prev_normal = if point_index > 0 {
+                normals[point_index - 1]
+            } else if closed {
+                normals[edge_count - 1]
+            } else {
+                next_normal
+            };
+
+            let mut bisector = prev_normal + next_normal;
+            if bisector.square_length() < util::EPSILON {
+                bisector = next_normal;
+            }
+            let bisector = bisector.normalize();
+            let miter_scale = f32::min(1.0 / f32::max(bisector.dot(next_normal), util::EPSILON),
+                                       VARIABLE_STROKE_MITER_LIMIT);
+            let offset = bisector.scale(radii[point_index] * miter_scale);
+            side_0.push(points[point_index] - offset);
+            side_1.push(points[point_index] + offset);
+        }
+
+        if closed {
+            let mut outer = Contour::with_capacity(side_0.len());
+            side_0.iter().for_each(|&point| outer.push_endpoint(point));
+            outer.close();
+            new_contours.push(outer);
+
+            let mut inner = Contour::with_capacity(side_1.len());
+            side_1.iter().rev().for_each(|&point| inner.push_endpoint(point));
+            inner.close();
+            new_contours.push(inner);
+            return;
+        }
+
+        let last_index = points.len() - 1;
+        let mut contour = Contour::with_capacity(side_0.len() + side_1.len());
+        side_0.iter().for_each(|&point| contour.push_endpoint(point));
+        self.add_cap(&mut contour, points[last_index], normals[edge_count - 1], radii[last_index]);
+        side_1.iter().rev().for_each(|&point| contour.push_endpoint(point));
+        self.add_cap(&mut contour, points[0], -normals[0], radii[0]);
+        contour.close();
+        new_contours.push(contour);
+    }
+
+    // Adds a cap at `center`, going from the point at `-normal` to the one at `normal`.
+    fn add_cap(&self, contour: &mut Contour, center: Vector2F, normal: Vector2F, radius: f32) {
+        let gradient = normal.yx().scale_xy(Vector2F::new(1.0, -1.0));
+        match self.line_cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                let offset = gradient.scale(radius);
+                contour.push_endpoint(center - normal.scale(radius) + offset);
+                contour.push_endpoint(center + normal.scale(radius) + offset);
+            }
+            LineCap::Round => {
+                if radius <= 0.0 {
+                    return;
+                }
+                let transform = Transform2F::from_scale(Vector2F::splat(radius)).translate(center);
+                let chord = LineSegment2F::new(-normal, normal);
+                contour.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
+            }
+        }
+    }
+}
+
 struct ContourStrokeToFill<'a> {
     input: &'a Contour,
     output: Contour,
@@ -340,6 +556,121 @@ impl Offset for Segment {
     }
 }
 
+impl Outline {
+    /// Returns a copy of this outline with its filled area grown by `distance`, or shrunk if
+    /// `distance` is negative. Open contours are treated as closed, and the outline is filled
+    /// with the nonzero winding rule. Parts that are thinner than twice the inset vanish, so an
+    /// inset deeper than a shape's inradius leaves nothing of it.
+    ///
+    /// Corners that the offset pulls apart are filled in with `join`. A miter join is beveled if
+    /// the miter would reach further than its limit times `distance` from the corner.
+    pub fn offset(&self, distance: f32, join: LineJoin) -> Outline {
+        let mut input = self.clone();
+        input.contours.iter_mut().for_each(|contour| contour.close());
+        if distance == 0.0 {
+            return input;
+        }
+        input.flatten(TOLERANCE);
+
+        // Each contour is offset away from the side of it that's filled, which depends on its own
+        // orientation and on the contours around it. Reorienting the contours so that all of
+        // them are filled on their right lets every edge move the same way.
+        input.normalize_orientation(FillRule::Winding);
+
+        // The raw offset winds around each point of the result positively and around every
+        // other point zero or fewer times, however the offset edges cross one another.
+        let mut raw = Outline::new();
+        for contour in &input.contours {
+            if let Some(contour) = offset_contour(contour, distance, join) {
+                raw.push_contour(contour);
+            }
+        }
+        raw.boolean_op_with_winding_tests(|winding| winding > 0,
+                                          &Outline::new(),
+                                          |_| false,
+                                          BooleanOp::Union)
+    }
+}
+
+// Moves each edge of a flattened contour to its right by `distance`, joining the ends of
+// adjacent edges with `join` where that pulls them apart, and through the original vertex where
+// it makes them cross.
+fn offset_contour(contour: &Contour, distance: f32, join: LineJoin) -> Option<Contour> {
+    let mut points: Vec<Vector2F> = vec![];
+    for point_index in 0..contour.len() {
+        let point = contour.position_of(point_index);
+        match points.last() {
+            Some(&last_point) if (point - last_point).square_length() < TOLERANCE * TOLERANCE => {}
+            _ => points.push(point),
+        }
+    }
+    while points.len() > 1 &&
+            (points[0] - points[points.len() - 1]).square_length() < TOLERANCE * TOLERANCE {
+        points.pop();
+    }
+    if points.len() < 3 {
+        return None;
+    }
+
+    let normals: Vec<Vector2F> = (0..points.len()).map(|edge_index| {
+        let next_point = points[(edge_index + 1) % points.len()];
+        let gradient = (next_point - points[edge_index]).normalize();
+        gradient.yx().scale_xy(Vector2F::new(1.0, -1.0))
+    }).collect();
+
+    let mut output = Contour::with_capacity(points.len() * 3);
+    for (point_index, &point) in points.iter().enumerate() {
+        let prev_normal = normals[(point_index + normals.len() - 1) % normals.len()];
+        let next_normal = normals[point_index];
+        let (from, to) = (point + prev_normal.scale(distance), point + next_normal.scale(distance));
+
+        // Positive if the offset side of the corner is on the outside of the turn.
+        let turn = prev_normal.det(next_normal) * distance;
+        let straight = prev_normal.dot(next_normal) > 0.0;
+        if straight && turn.abs() < util::EPSILON {
+            output.push_endpoint(from);
+            continue;
+        }
+        if !straight && turn.abs() < util::EPSILON || turn < 0.0 {
+            output.push_endpoint(from);
+            output.push_endpoint(point);
+            output.push_endpoint(to);
+            continue;
+        }
+
+        match join {
+            LineJoin::Bevel => {
+                output.push_endpoint(from);
+                output.push_endpoint(to);
+            }
+            LineJoin::Miter(miter_limit) => {
+                let bisector = (prev_normal + next_normal).normalize();
+                let miter_scale = 1.0 / bisector.dot(next_normal);
+                output.push_endpoint(from);
+                if miter_scale <= miter_limit {
+                    output.push_endpoint(point + bisector.scale(distance * miter_scale));
+                }
+                output.push_endpoint(to);
+            }
+            LineJoin::Round => {
+                let radius = distance.abs();
+                let transform = Transform2F::from_scale(Vector2F::splat(radius)).translate(point);
+                let chord = LineSegment2F::new((from - point).scale(1.0 / radius),
+                                               (to - point).scale(1.0 / radius));
+                let direction = if chord.from().det(chord.to()) > 0.0 {
+                    ArcDirection::CW
+                } else {
+                    ArcDirection::CCW
+                };
+                output.push_arc_from_unit_chord(&transform, chord, direction);
+            }
+        }
+    }
+
+    output.close();
+    Some(output)
+}
+
 impl Contour {
     fn might_need_join(&self, join: LineJoin) -> bool {
         if self.len() < 2 {
@@ -403,3 +734,235 @@ impl Default for LineJoin {
     #[inline]
     fn default() -> LineJoin { LineJoin::Miter(10.0) }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::stroke::{LineCap, LineJoin, OutlineVariableStrokeToFill, StrokeWidthProfile};
+    use pathfinder_geometry::vector::Vector2F;
+    use alloc::boxed::Box;
+    use alloc::vec;
+    use core::f32::consts::PI;
+
+    const AREA_EPSILON: f32 = 0.05;
+
+    fn polyline(points: &[(f32, f32)], closed: bool) -> Contour {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        if closed {
+            contour.close();
+        }
+        contour
+    }
+
+    fn outline(contours: Vec<Contour>) -> Outline {
+        let mut outline = Outline::new();
+        contours.into_iter().for_each(|contour| outline.push_contour(contour));
+        outline
+    }
+
+    fn square(size: f32) -> Outline {
+        outline(vec![polyline(&[(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)], true)])
+    }
+
+    fn line() -> Outline {
+        outline(vec![polyline(&[(0.0, 0.0), (10.0, 0.0)], false)])
+    }
+
+    // The nonzero area of an outline whose contours don't overlap except by nesting.
+    fn area(outline: &Outline) -> f32 {
+        let mut outline = outline.clone();
+        outline.flatten(0.001);
+        f32::abs(outline.contours().iter().map(|contour| {
+            (0..contour.len()).map(|point_index| {
+                let p0 = contour.position_of(point_index);
+                let p1 = contour.position_of((point_index + 1) % contour.len());
+                p0.x() * p1.y() - p1.x() * p0.y()
+            }).sum::<f32>() * 0.5
+        }).sum())
+    }
+
+    fn stroke(input: &Outline, profile: StrokeWidthProfile, line_cap: LineCap) -> Outline {
+        let mut stroke_to_fill = OutlineVariableStrokeToFill::new(input, &profile, line_cap);
+        stroke_to_fill.offset();
+        stroke_to_fill.into_outline()
+    }
+
+    fn assert_area(outline: &Outline, expected: f32) {
+        let actual = area(outline);
+        assert!(f32::abs(actual - expected) < AREA_EPSILON,
+                "expected area {}, found {}",
+                expected,
+                actual);
+    }
+
+    #[test]
+    fn test_variable_stroke_per_point_open() {
+        let stroked = stroke(&line(), StrokeWidthProfile::PerPoint(vec![vec![2.0]]), LineCap::Butt);
+        assert_eq!(stroked.len(), 1);
+        assert_area(&stroked, 20.0);
+
+        let profile = StrokeWidthProfile::PerPoint(vec![vec![2.0, 4.0]]);
+        let stroked = stroke(&line(), profile, LineCap::Butt);
+        assert_area(&stroked, 30.0);
+        assert!((stroked.bounds().size().y() - 4.0).abs() < AREA_EPSILON);
+    }
+
+    #[test]
+    fn test_variable_stroke_per_point_missing_widths() {
+        let input = outline(vec![polyline(&[(0.0, 0.0), (10.0, 0.0)], false),
+                                 polyline(&[(0.0, 5.0), (10.0, 5.0)], false)]);
+        let profile = StrokeWidthProfile::PerPoint(vec![vec![2.0], vec![]]);
+        let stroked = stroke(&input, profile, LineCap::Butt);
+        assert_eq!(stroked.len(), 1);
+        assert_area(&stroked, 20.0);
+    }
+
+    #[test]
+    fn test_variable_stroke_per_point_closed() {
+        let stroked = stroke(&square(10.0),
+                             StrokeWidthProfile::PerPoint(vec![vec![2.0]]),
+                             LineCap::Round);
+        assert_eq!(stroked.len(), 2);
+        assert_area(&stroked, 12.0 * 12.0 - 8.0 * 8.0);
+    }
+
+    #[test]
+    fn test_variable_stroke_function_open() {
+        let profile = StrokeWidthProfile::Function(Box::new(|_, t| 2.0 + 2.0 * t));
+        assert_area(&stroke(&line(), profile, LineCap::Butt), 30.0);
+
+        // The function receives the index of the contour being stroked.
+        let input = outline(vec![polyline(&[(0.0, 0.0), (10.0, 0.0)], false),
+                                 polyline(&[(0.0, 5.0), (10.0, 5.0)], false)]);
+        let profile = StrokeWidthProfile::Function(Box::new(|contour_index, _| {
+            if contour_index == 0 { 1.0 } else { 2.0 }
+        }));
+        assert_area(&stroke(&input, profile, LineCap::Butt), 30.0);
+    }
+
+    #[test]
+    fn test_variable_stroke_function_closed() {
+        let profile = StrokeWidthProfile::Function(Box::new(|_, _| 2.0));
+        assert_area(&stroke(&square(10.0), profile, LineCap::Butt), 12.0 * 12.0 - 8.0 * 8.0);
+    }
+
+    #[test]
+    fn test_variable_stroke_caps() {
+        let widths = || StrokeWidthProfile::PerPoint(vec![vec![2.0]]);
+        assert_area(&stroke(&line(), widths(), LineCap::Square), 24.0);
+        assert_area(&stroke(&line(), widths(), LineCap::Round), 20.0 + PI);
+
+        // Round caps follow the width at each end.
+        let profile = StrokeWidthProfile::PerPoint(vec![vec![2.0, 4.0]]);
+        assert_area(&stroke(&line(), profile, LineCap::Round), 30.0 + PI * 0.5 + PI * 2.0);
+    }
+
+    fn l_shape() -> Outline {
+        outline(vec![polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (5.0, 5.0), (5.0, 10.0),
+                                (0.0, 10.0)],
+                              true)])
+    }
+
+    #[test]
+    fn test_offset_outset() {
+        let square = square(10.0);
+        let offset = square.offset(2.0, LineJoin::Miter(10.0));
+        assert_eq!(offset.len(), 1);
+        assert_area(&offset, 196.0);
+        assert_area(&square.offset(2.0, LineJoin::Bevel), 188.0);
+        assert_area(&square.offset(2.0, LineJoin::Round), 180.0 + 4.0 * PI);
+
+        // Miters longer than the limit are beveled.
+        assert_area(&square.offset(2.0, LineJoin::Miter(1.0)), 188.0);
+
+        // The concave corner needs no join.
+        let l_shape = l_shape();
+        assert_area(&l_shape.offset(1.0, LineJoin::Miter(10.0)), 119.0);
+        assert_area(&l_shape.offset(1.0, LineJoin::Bevel), 116.5);
+        assert_area(&l_shape.offset(1.0, LineJoin::Round), 119.0 - 5.0 * (1.0 - PI / 4.0));
+    }
+
+    #[test]
+    fn test_offset_inset() {
+        // Only the corners that the inset pulls apart are joined.
+        let square = square(10.0);
+        for &join in &[LineJoin::Miter(10.0), LineJoin::Bevel, LineJoin::Round] {
+            let offset = square.offset(-2.0, join);
+            assert_eq!(offset.len(), 1);
+            assert_area(&offset, 36.0);
+        }
+
+        let l_shape = l_shape();
+        assert_area(&l_shape.offset(-1.0, LineJoin::Miter(10.0)), 39.0);
+        assert_area(&l_shape.offset(-1.0, LineJoin::Bevel), 39.5);
+        assert_area(&l_shape.offset(-1.0, LineJoin::Round), 40.0 - PI / 4.0);
+
+        // The arms of the L are 5 wide, so insetting by more than half of that empties it.
+        assert_eq!(l_shape.offset(-3.0, LineJoin::Miter(10.0)).len(), 0);
+    }
+
+    #[test]
+    fn test_offset_inset_past_inradius() {
+        let square = square(10.0);
+        assert_area(&square.offset(-4.9, LineJoin::Miter(10.0)), 0.04);
+        for &distance in &[-5.5, -6.0, -8.0, -20.0] {
+            for &join in &[LineJoin::Miter(10.0), LineJoin::Bevel, LineJoin::Round] {
+                assert_eq!(square.offset(distance, join).len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_offset_orientation() {
+        // Reversed contours, and holes wound against their outer contours, offset the same way.
+        let mut reversed = square(10.0);
+        reversed.contours[0].reverse();
+        assert_area(&reversed.offset(2.0, LineJoin::Miter(10.0)), 196.0);
+        assert_area(&reversed.offset(-2.0, LineJoin::Miter(10.0)), 36.0);
+
+        let mut hole = polyline(&[(3.0, 3.0), (7.0, 3.0), (7.0, 7.0), (3.0, 7.0)], true);
+        hole.reverse();
+        let mut ring = square(10.0);
+        ring.push_contour(hole);
+        assert_area(&ring.offset(1.0, LineJoin::Miter(10.0)), 144.0 - 4.0);
+        assert_area(&ring.offset(-1.0, LineJoin::Miter(10.0)), 64.0 - 36.0);
+        assert_eq!(ring.offset(-1.0, LineJoin::Miter(10.0)).len(), 2);
+    }
+
+    #[test]
+    fn test_offset_oppositely_wound_contours() {
+        // Each of two disjoint squares is offset away from its own inside, whichever way it
+        // winds.
+        let mut reversed = polyline(&[(20.0, 0.0), (30.0, 0.0), (30.0, 10.0), (20.0, 10.0)], true);
+        reversed.reverse();
+        let mut squares = square(10.0);
+        squares.push_contour(reversed);
+        let outset = squares.offset(1.0, LineJoin::Miter(10.0));
+        assert_eq!(outset.len(), 2);
+        assert_area(&outset, 2.0 * 144.0);
+        let inset = squares.offset(-1.0, LineJoin::Miter(10.0));
+        assert_eq!(inset.len(), 2);
+        assert_area(&inset, 2.0 * 64.0);
+
+        // Reversing both contours of a ring leaves the hole a hole.
+        let mut outer = square(10.0);
+        outer.contours[0].reverse();
+        outer.push_contour(polyline(&[(3.0, 3.0), (7.0, 3.0), (7.0, 7.0), (3.0, 7.0)], true));
+        assert_area(&outer.offset(1.0, LineJoin::Miter(10.0)), 144.0 - 4.0);
+    }
+
+    #[test]
+    fn test_offset_nested_contours_wound_the_same_way() {
+        // The inner square winds around its inside a second time, so it isn't the edge of a
+        // hole, and only the outer square is offset.
+        let mut nested = square(10.0);
+        nested.push_contour(polyline(&[(3.0, 3.0), (7.0, 3.0), (7.0, 7.0), (3.0, 7.0)], true));
+        let outset = nested.offset(1.0, LineJoin::Miter(10.0));
+        assert_eq!(outset.len(), 1);
+        assert_area(&outset, 144.0);
+        assert_area(&nested.offset(-1.0, LineJoin::Miter(10.0)), 64.0);
+    }
+}
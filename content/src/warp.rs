@@ -0,0 +1,355 @@
+// pathfinder/content/src/warp.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Nonlinear distortions of outlines, for effects such as text bent along an arc or waving like
+//! a flag.
+//!
+//! Warps don't map Bézier curves to Bézier curves, or even lines to lines, so every segment is
+//! subdivided until the warped pieces are within a tolerance of straight, and the result consists
+//! solely of line segments.
+
+use crate::outline::{Contour, Outline};
+use crate::segment::Segment;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::Vector2F;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+// Segments are never subdivided more than this many times, which bounds the number of points
+// each one can produce at 2^16.
+const MAX_SUBDIVISION_DEPTH: u32 = 16;
+// The parameters along each piece at which the warp is checked for flatness. These are
+// deliberately not simple fractions, so that periodic warps are unlikely to line up with them
+// and look flat when they aren't.
+const FLATNESS_SAMPLE_TIMES: [f32; 3] = [0.382, 0.5, 0.618];
+
+/// A function that distorts outlines point by point.
+///
+/// This is implemented for closures, so `outline.warp(&|point| ..., tolerance)` works.
+pub trait Warp {
+    fn warp_point(&self, point: Vector2F) -> Vector2F;
+}
+
+impl<F> Warp for F where F: Fn(Vector2F) -> Vector2F {
+    #[inline]
+    fn warp_point(&self, point: Vector2F) -> Vector2F {
+        (*self)(point)
+    }
+}
+
+impl Outline {
+    /// Returns this outline mapped through `warp`, as line segments that stray no more than
+    /// about `tolerance` from the true warped curves.
+    ///
+    /// Each segment is split in half until a few warped points along it are within `tolerance`
+    /// of the line between its warped endpoints. Distortions much finer than a segment may be
+    /// missed, so split long lines first if the warp is very wavy.
+    pub fn warp<W>(&self, warp: &W, tolerance: f32) -> Outline where W: Warp + ?Sized {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.warp(warp, tolerance));
+        }
+        outline
+    }
+}
+
+impl Contour {
+    /// Returns this contour mapped through `warp`, as `Outline::warp()` describes.
+    pub fn warp<W>(&self, warp: &W, tolerance: f32) -> Contour where W: Warp + ?Sized {
+        let mut points = vec![];
+        if !self.is_empty() {
+            points.push(warp.warp_point(self.position_of(0)));
+        }
+        for segment in self.iter() {
+            let from = points[points.len() - 1];
+            let to = warp.warp_point(segment.baseline.to());
+            warp_segment(warp, &segment, (0.0, 1.0), (from, to), tolerance, 0, &mut points);
+        }
+
+        // The closing segment comes back around to the first point.
+        if self.is_closed() && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let mut contour = Contour::with_capacity(points.len());
+        points.into_iter().for_each(|point| contour.push_endpoint(point));
+        if self.is_closed() {
+            contour.close();
+        }
+        contour
+    }
+}
+
+// Pushes the warped points of the part of `segment` between the parameters `t`, excluding its
+// start, whose warped endpoints are `endpoints`.
+fn warp_segment<W>(warp: &W,
+                   segment: &Segment,
+                   t: (f32, f32),
+                   endpoints: (Vector2F, Vector2F),
+                   tolerance: f32,
+                   depth: u32,
+                   points: &mut Vec<Vector2F>)
+                   where W: Warp + ?Sized {
+    let (t0, t1) = t;
+    let (from, to) = endpoints;
+    let is_flat = depth >= MAX_SUBDIVISION_DEPTH ||
+        FLATNESS_SAMPLE_TIMES.iter().all(|&time| {
+            let point = warp.warp_point(segment.sample(t0 + (t1 - t0) * time));
+            distance_from_line(point, from, to) <= tolerance
+        });
+    if is_flat {
+        if points.last() != Some(&to) {
+            points.push(to);
+        }
+        return;
+    }
+
+    let t_mid = (t0 + t1) * 0.5;
+    let mid = warp.warp_point(segment.sample(t_mid));
+    warp_segment(warp, segment, (t0, t_mid), (from, mid), tolerance, depth + 1, points);
+    warp_segment(warp, segment, (t_mid, t1), (mid, to), tolerance, depth + 1, points);
+}
+
+fn distance_from_line(point: Vector2F, from: Vector2F, to: Vector2F) -> f32 {
+    let vector = to - from;
+    let length = vector.length();
+    if length == 0.0 {
+        (point - from).length()
+    } else {
+        f32::abs(vector.det(point - from)) / length
+    }
+}
+
+/// Bends the rectangle `bounds` into a sector of a ring, for text curved along an arc.
+///
+/// The middle of the top edge stays put, and the top edge becomes an arc of the same length
+/// turning through `sweep_angle` radians, curving down on either side if the angle is positive
+/// and up if it's negative. Vertical lines become rays from the center of the arc.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArcWarp {
+    pub bounds: RectF,
+    pub sweep_angle: f32,
+}
+
+impl Warp for ArcWarp {
+    fn warp_point(&self, point: Vector2F) -> Vector2F {
+        let (bounds, sweep_angle) = (self.bounds, self.sweep_angle);
+        if sweep_angle == 0.0 || bounds.width() == 0.0 {
+            return point;
+        }
+
+        let radius = bounds.width() / sweep_angle;
+        let top_center = Vector2F::new(bounds.origin().x() + bounds.width() * 0.5,
+                                       bounds.origin().y());
+        let center = top_center + Vector2F::new(0.0, radius);
+        let angle = (point.x() - top_center.x()) / bounds.width() * sweep_angle - PI * 0.5;
+        let distance = radius - (point.y() - top_center.y());
+        center + Vector2F::new(f32::cos(angle), f32::sin(angle)).scale(distance)
+    }
+}
+
+/// Waves the rectangle `bounds` up and down like a flag, moving each point vertically by a sine
+/// of its horizontal position.
+///
+/// The left edge stays put, and the displacement grows to `amplitude` at the right edge, with
+/// `wave_count` full waves across the width.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlagWarp {
+    pub bounds: RectF,
+    pub amplitude: f32,
+    pub wave_count: f32,
+}
+
+impl Warp for FlagWarp {
+    fn warp_point(&self, point: Vector2F) -> Vector2F {
+        if self.bounds.width() == 0.0 {
+            return point;
+        }
+        let x = (point.x() - self.bounds.origin().x()) / self.bounds.width();
+        let offset = self.amplitude * x * f32::sin(x * self.wave_count * PI * 2.0);
+        point + Vector2F::new(0.0, offset)
+    }
+}
+
+/// Maps the rectangle `bounds` onto a grid of quadrilaterals, as the envelope distortion of
+/// drawing programs does.
+///
+/// The rectangle is divided into `columns` by `rows` equal cells, and each cell is mapped
+/// bilinearly onto the quadrilateral between the mesh points at its corners. Points outside
+/// `bounds` follow the nearest cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshWarp {
+    bounds: RectF,
+    columns: u32,
+    rows: u32,
+    points: Vec<Vector2F>,
+}
+
+impl MeshWarp {
+    /// Creates a mesh that leaves `bounds` as it was, with `columns` by `rows` cells. Move its
+    /// points with `set_point()` to distort it.
+    pub fn new(bounds: RectF, columns: u32, rows: u32) -> MeshWarp {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let mut points = Vec::with_capacity(((columns + 1) * (rows + 1)) as usize);
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let position = Vector2F::new(column as f32 / columns as f32,
+                                             row as f32 / rows as f32);
+                points.push(bounds.origin() + bounds.size() * position);
+            }
+        }
+        MeshWarp { bounds, columns, rows, points }
+    }
+
+    #[inline]
+    pub fn point(&self, column: u32, row: u32) -> Vector2F {
+        self.points[(row * (self.columns + 1) + column) as usize]
+    }
+
+    #[inline]
+    pub fn set_point(&mut self, column: u32, row: u32, point: Vector2F) {
+        self.points[(row * (self.columns + 1) + column) as usize] = point;
+    }
+}
+
+impl Warp for MeshWarp {
+    fn warp_point(&self, point: Vector2F) -> Vector2F {
+        let size = self.bounds.size();
+        if size.x() == 0.0 || size.y() == 0.0 {
+            return point;
+        }
+
+        let cells = Vector2F::new(self.columns as f32, self.rows as f32);
+        let position = (point - self.bounds.origin()) / size * cells;
+        let column = (position.x().floor().max(0.0) as u32).min(self.columns - 1);
+        let row = (position.y().floor().max(0.0) as u32).min(self.rows - 1);
+        let fraction = position - Vector2F::new(column as f32, row as f32);
+
+        let top = self.point(column, row).lerp(self.point(column + 1, row), fraction.x());
+        let bottom = self.point(column, row + 1).lerp(self.point(column + 1, row + 1),
+                                                      fraction.x());
+        top.lerp(bottom, fraction.y())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::warp::{ArcWarp, FlagWarp, MeshWarp, Warp};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::Vector2F;
+    use core::f32::consts::PI;
+
+    const EPSILON: f32 = 0.001;
+
+    fn assert_close(actual: Vector2F, expected: Vector2F) {
+        assert!((actual - expected).length() < EPSILON,
+                "expected {:?}, found {:?}",
+                expected,
+                actual);
+    }
+
+    fn bounds() -> RectF {
+        RectF::new(Vector2F::new(10.0, 20.0), Vector2F::new(100.0, 30.0))
+    }
+
+    #[test]
+    fn test_warp_subdivides_to_tolerance() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(0.0, 0.0));
+        contour.push_endpoint(Vector2F::new(100.0, 0.0));
+        contour.push_endpoint(Vector2F::new(100.0, 10.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        // The identity leaves lines alone.
+        let identity = outline.warp(&|point| point, 0.1);
+        assert_eq!(identity.contours()[0].len(), 3);
+        assert!(identity.contours()[0].is_closed());
+
+        // A parabola is followed to within about the tolerance, halfway between points too.
+        let warp = |point: Vector2F| point + Vector2F::new(0.0, point.x() * point.x() * 0.01);
+        for &tolerance in &[1.0, 0.1, 0.01] {
+            let warped = outline.warp(&warp, tolerance);
+            let contour = &warped.contours()[0];
+            assert!(contour.is_closed());
+            let mut point_count = 0;
+            for point_index in 0..contour.len() {
+                let point = contour.position_of(point_index);
+                if point.y() > point.x() * point.x() * 0.01 + EPSILON {
+                    continue;
+                }
+                point_count += 1;
+                if point_index > 0 {
+                    let prev = contour.position_of(point_index - 1);
+                    let middle = warp(Vector2F::new((prev.x() + point.x()) * 0.5, 0.0));
+                    let chord = point - prev;
+                    let distance = chord.det(middle - prev).abs() / chord.length();
+                    assert!(distance <= tolerance * 1.5);
+                }
+            }
+            assert!(point_count > 2);
+        }
+    }
+
+    #[test]
+    fn test_arc_warp() {
+        let warp = ArcWarp { bounds: bounds(), sweep_angle: PI * 0.5 };
+        let radius = 100.0 / (PI * 0.5);
+        let center = Vector2F::new(60.0, 20.0 + radius);
+
+        // The middle of the top edge stays put, and the top edge becomes an arc.
+        assert_close(warp.warp_point(Vector2F::new(60.0, 20.0)), Vector2F::new(60.0, 20.0));
+        for &x in &[10.0, 35.0, 110.0] {
+            let point = warp.warp_point(Vector2F::new(x, 20.0));
+            assert!(((point - center).length() - radius).abs() < EPSILON);
+        }
+        let right = warp.warp_point(Vector2F::new(110.0, 20.0));
+        assert_close(right, center + Vector2F::new(f32::sqrt(0.5), -f32::sqrt(0.5)).scale(radius));
+
+        // Vertical lines become rays from the center.
+        let bottom_right = warp.warp_point(Vector2F::new(110.0, 50.0));
+        assert_close(bottom_right, center + (right - center).scale((radius - 30.0) / radius));
+
+        // No sweep is no warp.
+        let flat = ArcWarp { bounds: bounds(), sweep_angle: 0.0 };
+        assert_eq!(flat.warp_point(Vector2F::new(35.0, 40.0)), Vector2F::new(35.0, 40.0));
+    }
+
+    #[test]
+    fn test_flag_warp() {
+        let warp = FlagWarp { bounds: bounds(), amplitude: 8.0, wave_count: 1.25 };
+        assert_close(warp.warp_point(Vector2F::new(10.0, 25.0)), Vector2F::new(10.0, 25.0));
+        assert_close(warp.warp_point(Vector2F::new(110.0, 25.0)), Vector2F::new(110.0, 33.0));
+
+        // A fifth of the way across is a quarter of a wave in, at a fifth of the amplitude.
+        assert_close(warp.warp_point(Vector2F::new(30.0, 25.0)), Vector2F::new(30.0, 26.6));
+    }
+
+    #[test]
+    fn test_mesh_warp() {
+        let mut warp = MeshWarp::new(bounds(), 2, 1);
+        for &point in &[Vector2F::new(10.0, 20.0), Vector2F::new(33.0, 41.0)] {
+            assert_close(warp.warp_point(point), point);
+        }
+
+        // Moving the bottom of the middle column skews both cells, bilinearly.
+        warp.set_point(1, 1, Vector2F::new(80.0, 60.0));
+        assert_eq!(warp.point(1, 1), Vector2F::new(80.0, 60.0));
+        assert_close(warp.warp_point(Vector2F::new(60.0, 50.0)), Vector2F::new(80.0, 60.0));
+        assert_close(warp.warp_point(Vector2F::new(35.0, 35.0)), Vector2F::new(40.0, 37.5));
+        assert_close(warp.warp_point(Vector2F::new(60.0, 20.0)), Vector2F::new(60.0, 20.0));
+
+        // Points outside the mesh follow the nearest cell.
+        assert_close(warp.warp_point(Vector2F::new(0.0, 20.0)), Vector2F::new(0.0, 20.0));
+    }
+}
@@ -43,7 +43,7 @@ use std::io::{BufWriter};
 use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
-use usvg::{Options as UsvgOptions, Tree};
+use usvg::Options as UsvgOptions;
 
 #[cfg(any(not(target_os = "macos"), feature = "pf-gl"))]
 use pathfinder_gl::GLDevice as DeviceImpl;
@@ -162,6 +162,7 @@ impl<W> DemoApp<W> where W: Window {
         // FIXME(pcwalton)
         let render_options = RendererOptions {
             background_color: None,
+            ..RendererOptions::default()
         };
 
         let renderer = Renderer::new(device, resources, dest_framebuffer, render_options);
@@ -274,6 +275,7 @@ impl<W> DemoApp<W> where W: Window {
                 Vector2F::default()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
+            ..BuildOptions::default()
         };
 
         self.render_command_stream = Some(self.scene_proxy.build_with_stream(build_options));
@@ -763,7 +765,7 @@ fn load_scene(resource_loader: &dyn ResourceLoader, input_path: &SVGPath) -> Bui
         SVGPath::Path(ref path) => fs::read(path).unwrap().into()
     };
 
-    BuiltSVG::from_tree(Tree::from_data(&data, &UsvgOptions::default()).unwrap())
+    BuiltSVG::from_data(&data, &UsvgOptions::default()).unwrap()
 }
 
 fn center_of_window(window_size: &WindowSize) -> Vector2F {
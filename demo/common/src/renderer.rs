@@ -92,7 +92,10 @@ impl<W> DemoApp<W> where W: Window {
             Mode::ThreeD => None,
             Mode::VR => Some(ColorF::transparent_black()),
         };
-        self.renderer.set_options(RendererOptions { background_color: clear_color });
+        self.renderer.set_options(RendererOptions {
+            background_color: clear_color,
+            ..RendererOptions::default()
+        });
 
         scene_count
     }
@@ -277,13 +280,17 @@ impl<W> DemoApp<W> where W: Window {
         self.renderer.begin_scene();
 
         // Issue render commands!
-        for command in self.render_command_stream.as_mut().unwrap() {
+        let mut render_command_stream = self.render_command_stream.take().unwrap();
+        for command in &mut render_command_stream {
             self.renderer.render_command(&command);
 
             if let RenderCommand::Finish { build_time } = command {
                 self.build_time = Some(build_time);
             }
         }
+        if let Err(error) = render_command_stream.finish() {
+            error!("Failed to build the scene: {}", error);
+        }
 
         self.current_frame
             .as_mut()
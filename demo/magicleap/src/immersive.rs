@@ -58,7 +58,7 @@ impl<D: Display> ImmersiveDemo<D> {
         let options = Options::get();
         let svg_data = resources.slurp(DEFAULT_SVG_VIRTUAL_PATH)?;
         let tree = usvg::Tree::from_data(&svg_data[..], &usvg::Options::default())?;
-        let svg = BuiltSVG::from_tree(tree);
+        let svg = BuiltSVG::from_tree(&tree);
 	let svg_size = svg.scene.view_box.size();
         let scene_thread_proxy = SceneThreadProxy::new(svg.scene, options);
         let _ = scene_thread_proxy.sender.send(MainToSceneMsg::SetDrawableSize(display.size()));
@@ -184,7 +184,7 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(pf: *mut c_void, options: *
             let svg_filename = CStr::from_ptr(options.svg_filename).to_string_lossy();
             let data = resources.slurp(&*svg_filename).unwrap();
             let tree = Tree::from_data(&data, &UsvgOptions::default()).unwrap();
-            BuiltSVG::from_tree(tree)
+            BuiltSVG::from_tree(&tree)
         });
 
         let mut width = 0;
@@ -227,7 +227,7 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(pf: *mut c_void, options: *
         };
 
         let scene_proxy = SceneProxy::from_scene(svg.scene.clone(), SequentialExecutor);
-        scene_proxy.build_and_render(renderer, render_options);
+        scene_proxy.build_and_render(renderer, render_options).unwrap();
     }
 }
 
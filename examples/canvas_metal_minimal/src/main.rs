@@ -49,7 +49,10 @@ fn main() {
     let mut renderer = Renderer::new(MetalDevice::new(metal_layer),
                                      &FilesystemResourceLoader::locate(),
                                      DestFramebuffer::full_window(window_size),
-                                     RendererOptions { background_color: Some(ColorF::white()) });
+                                     RendererOptions {
+                                         background_color: Some(ColorF::white()),
+                                         ..RendererOptions::default()
+                                     });
 
     // Make a canvas. We're going to draw a house.
     let mut canvas = CanvasRenderingContext2D::new(CanvasFontContext::from_system_source(),
@@ -74,7 +77,7 @@ fn main() {
 
     // Render the canvas to screen.
     let scene = SceneProxy::from_scene(canvas.into_scene(), RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     renderer.device.present_drawable();
 
     // Wait for a keypress.
@@ -10,6 +10,7 @@
 
 use pathfinder_canvas::{CanvasFontContext, CanvasRenderingContext2D, FillStyle, Path2D};
 use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::outline::ArcDirection;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_gpu::resources::FilesystemResourceLoader;
@@ -68,7 +69,10 @@ fn main() {
     let renderer = Renderer::new(GLDevice::new(GLVersion::GL3, 0),
                                  &FilesystemResourceLoader::locate(),
                                  DestFramebuffer::full_window(drawable_size),
-                                 RendererOptions { background_color: Some(ColorF::white()) });
+                                 RendererOptions {
+                                     background_color: Some(ColorF::white()),
+                                     ..RendererOptions::default()
+                                 });
     let mut moire_renderer = MoireRenderer::new(renderer, window_size, drawable_size);
 
     // Enter main render loop.
@@ -125,7 +129,10 @@ impl MoireRenderer {
             Vector2F::new(1.0, sin_time).scale(cos_time * INNER_RADIUS);
 
         // Clear to background color.
-        self.renderer.set_options(RendererOptions { background_color: Some(background_color) });
+        self.renderer.set_options(RendererOptions {
+            background_color: Some(background_color),
+            ..RendererOptions::default()
+        });
 
         // Make a canvas.
         let mut canvas = CanvasRenderingContext2D::new(self.font_context.clone(),
@@ -140,7 +147,7 @@ impl MoireRenderer {
 
         // Build and render scene.
         self.scene.replace_scene(canvas.into_scene());
-        self.scene.build_and_render(&mut self.renderer, BuildOptions::default());
+        self.scene.build_and_render(&mut self.renderer, BuildOptions::default()).unwrap();
 
         self.frame += 1;
     }
@@ -150,7 +157,7 @@ impl MoireRenderer {
         for index in 0..CIRCLE_COUNT {
             let radius = (index + 1) as f32 * CIRCLE_SPACING * self.device_pixel_ratio;
             let mut path = Path2D::new();
-            path.ellipse(center, Vector2F::splat(radius), 0.0, 0.0, PI * 2.0);
+            path.ellipse(center, Vector2F::splat(radius), 0.0, 0.0, PI * 2.0, ArcDirection::CW);
             canvas.stroke_path(path);
         }
     }
@@ -52,7 +52,10 @@ fn main() {
     let mut renderer = Renderer::new(GLDevice::new(GLVersion::GL3, 0),
                                      &resource_loader,
                                      DestFramebuffer::full_window(window_size),
-                                     RendererOptions { background_color: Some(ColorF::white()) });
+                                     RendererOptions {
+                                         background_color: Some(ColorF::white()),
+                                         ..RendererOptions::default()
+                                     });
 
     // Load a font.
     let font_data = resource_loader.slurp("fonts/overpass-regular.otf").unwrap().to_vec();
@@ -71,7 +74,7 @@ fn main() {
 
     // Render the canvas to screen.
     let scene = SceneProxy::from_scene(canvas.into_scene(), RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     window.gl_swap_window();
 
     // Wait for a keypress.
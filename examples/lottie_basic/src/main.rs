@@ -10,14 +10,92 @@
 
 //! Experimental example for reading Lottie animations. This is very incomplete.
 
+use pathfinder_color::ColorF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::resources::FilesystemResourceLoader;
 use pathfinder_lottie::Lottie;
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::video::GLProfile;
 use std::env;
 use std::fs::File;
 use std::io::BufReader;
+use std::time::Instant;
 
 fn main() {
     let path = env::args().skip(1).next().unwrap();
     let file = BufReader::new(File::open(path).unwrap());
     let lottie = Lottie::from_reader(file).unwrap();
-    println!("{:#?}", lottie);
+
+    // Set up SDL2.
+    let sdl_context = sdl2::init().unwrap();
+    let video = sdl_context.video().unwrap();
+
+    // Make sure we have at least a GL 3.0 context. Pathfinder requires this.
+    let gl_attributes = video.gl_attr();
+    gl_attributes.set_context_profile(GLProfile::Core);
+    gl_attributes.set_context_version(3, 3);
+
+    // Open a window the size of the animation.
+    let window_size = Vector2I::new(lottie.width as i32, lottie.height as i32);
+    let window = video.window("Lottie example", window_size.x() as u32, window_size.y() as u32)
+                      .opengl()
+                      .allow_highdpi()
+                      .build()
+                      .unwrap();
+    let mut event_pump = sdl_context.event_pump().unwrap();
+
+    // Get the real window size (for HiDPI).
+    let (drawable_width, drawable_height) = window.drawable_size();
+    let drawable_size = Vector2I::new(drawable_width as i32, drawable_height as i32);
+    let device_pixel_ratio = drawable_size.x() as f32 / window_size.x() as f32;
+
+    // Create the GL context, and make it current.
+    let gl_context = window.gl_create_context().unwrap();
+    gl::load_with(|name| video.gl_get_proc_address(name) as *const _);
+    window.gl_make_current(&gl_context).unwrap();
+
+    // Create a Pathfinder renderer.
+    let mut renderer = Renderer::new(GLDevice::new(GLVersion::GL3, 0),
+                                     &FilesystemResourceLoader::locate(),
+                                     DestFramebuffer::full_window(drawable_size),
+                                     RendererOptions {
+                                         background_color: Some(ColorF::white()),
+                                         ..RendererOptions::default()
+                                     });
+    let scene = SceneProxy::new(RayonExecutor);
+
+    // Play the animation in a loop, at its own frame rate.
+    let start_time = Instant::now();
+    let duration = lottie.out_point - lottie.in_point;
+    loop {
+        let elapsed = start_time.elapsed().as_secs_f32() * lottie.frame_rate;
+        let frame = lottie.in_point + elapsed % duration;
+
+        let mut frame_scene = lottie.build_scene(frame);
+        frame_scene.set_view_box(frame_scene.view_box().scale(device_pixel_ratio));
+        scene.replace_scene(frame_scene);
+
+        let transform = Transform2F::from_scale(Vector2F::splat(device_pixel_ratio));
+        let build_options = BuildOptions {
+            transform: RenderTransform::Transform2D(transform),
+            ..BuildOptions::default()
+        };
+        scene.build_and_render(&mut renderer, build_options).unwrap();
+        window.gl_swap_window();
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return,
+                _ => {}
+            }
+        }
+    }
 }
@@ -103,7 +103,10 @@ fn main() {
         GLDevice::new(GLVersion::GL3, 0),
         &resource_loader,
         DestFramebuffer::full_window(pixel_size),
-        RendererOptions { background_color: Some(stage.background_color()) }
+        RendererOptions {
+            background_color: Some(stage.background_color()),
+            ..RendererOptions::default()
+        }
     );
     // Clear to swf stage background color.
     let mut scene = Scene::new();
@@ -122,7 +125,7 @@ fn main() {
         Vector2F::new(device_pixel_ratio, device_pixel_ratio)
     );
     build_options.transform = RenderTransform::Transform2D(scale_transform);
-    scene.build_and_render(&mut renderer, build_options);
+    scene.build_and_render(&mut renderer, build_options).unwrap();
 
     window.gl_swap_window();
     // Wait for a keypress.
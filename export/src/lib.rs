@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::segment::SegmentKind;
 use pathfinder_renderer::paint::Paint;
 use pathfinder_renderer::scene::Scene;
@@ -53,11 +54,14 @@ fn export_svg<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
         view_box.size().x(),
         view_box.size().y()
     )?;
-    for (paint, outline, name) in scene.paths() {
+    for (paint, outline, name, fill_rule) in scene.paths() {
         write!(writer, "    <path")?;
         if !name.is_empty() {
             write!(writer, " id=\"{}\"", name)?;
         }
+        if fill_rule == FillRule::EvenOdd {
+            write!(writer, " fill-rule=\"evenodd\"")?;
+        }
         writeln!(writer, " fill=\"{:?}\" d=\"{:?}\" />", paint, outline)?;
     }
     writeln!(writer, "</svg>")?;
@@ -75,11 +79,11 @@ fn export_pdf<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
         Vector2F::new(r.x(), height - r.y())
     };
     
-    for (paint, outline, _) in scene.paths() {
+    for (paint, outline, _, fill_rule) in scene.paths() {
         match paint {
             Paint::Color(color) => pdf.set_fill_color(*color),
-            Paint::Gradient(_) => {
-                // TODO(pcwalton): Gradients.
+            Paint::Gradient(_) | Paint::Pattern(_) => {
+                // TODO: Gradients and patterns.
             }
         }
         
@@ -110,7 +114,10 @@ fn export_pdf<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
         }
         
         // closes implicitly
-        pdf.fill();
+        match fill_rule {
+            FillRule::Winding => pdf.fill(),
+            FillRule::EvenOdd => pdf.eo_fill(),
+        }
     }
     pdf.write_to(writer)
 }
@@ -136,7 +143,7 @@ fn export_ps<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
     writeln!(writer, "0 {} translate", view_box.size().y())?;
     writeln!(writer, "1 -1 scale")?;
     
-    for (paint, outline, name) in scene.paths() {
+    for (paint, outline, name, fill_rule) in scene.paths() {
         if !name.is_empty() {
             writeln!(writer, "newpath % {}", name)?;
         } else {
@@ -181,12 +188,15 @@ fn export_ps<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
             Paint::Color(color) => {
                 writeln!(writer, "{} {} {} setrgbcolor", color.r, color.g, color.b)?;
             }
-            Paint::Gradient(_) => {
-                // TODO(pcwalton): Gradients.
+            Paint::Gradient(_) | Paint::Pattern(_) => {
+                // TODO: Gradients and patterns.
             }
         }
 
-        writeln!(writer, "fill")?;
+        match fill_rule {
+            FillRule::Winding => writeln!(writer, "fill")?,
+            FillRule::EvenOdd => writeln!(writer, "eofill")?,
+        }
     }
     writeln!(writer, "showpage")?;
     Ok(())
@@ -148,6 +148,10 @@ impl Pdf {
     pub fn fill(&mut self) {
         writeln!(self.page_buffer, "f").unwrap();
     }
+
+    pub fn eo_fill(&mut self) {
+        writeln!(self.page_buffer, "f*").unwrap();
+    }
     
     pub fn close(&mut self) {
         writeln!(self.page_buffer, "h").unwrap();
@@ -9,6 +9,10 @@
 // except according to those terms.
 
 //! Basic geometry and linear algebra primitives, optimized with SIMD.
+//!
+//! Without the default `std` feature, this crate only needs `core`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
 pub mod line_segment;
 pub mod rect;
@@ -14,7 +14,10 @@ use crate::transform2d::Matrix2x2F;
 use crate::vector::Vector2F;
 use crate::util;
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Sub};
+use core::ops::{Add, Sub};
+
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct LineSegment2F(pub F32x4);
@@ -263,6 +266,22 @@ impl LineSegment2F {
     }
 }
 
+#[cfg(feature = "pf-serde")]
+impl Serialize for LineSegment2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        [self.from_x(), self.from_y(), self.to_x(), self.to_y()].serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for LineSegment2F {
+    fn deserialize<D>(deserializer: D) -> Result<LineSegment2F, D::Error>
+                      where D: Deserializer<'de> {
+        let [from_x, from_y, to_x, to_y] = <[f32; 4]>::deserialize(deserializer)?;
+        Ok(LineSegment2F::new(Vector2F::new(from_x, from_y), Vector2F::new(to_x, to_y)))
+    }
+}
+
 impl Add<Vector2F> for LineSegment2F {
     type Output = LineSegment2F;
     #[inline]
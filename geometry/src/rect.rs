@@ -10,9 +10,12 @@
 
 //! 2D axis-aligned rectangles, optimized with SIMD.
 
-use crate::vector::{Vector2F, Vector2I};
+use crate::vector::{Vector2F, Vector2F64, Vector2I};
 use pathfinder_simd::default::{F32x4, I32x4};
 
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct RectF(pub F32x4);
 
@@ -185,6 +188,143 @@ impl RectF {
     pub fn to_i32(&self) -> RectI {
         RectI(self.0.to_i32x4())
     }
+
+    #[inline]
+    pub fn to_f64(self) -> RectF64 {
+        RectF64::from_points(self.origin().to_f64(), self.lower_right().to_f64())
+    }
+}
+
+// Rectangles are stored as their minimum and maximum corners, which round-trip exactly.
+#[cfg(feature = "pf-serde")]
+impl Serialize for RectF {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        [self.min_x(), self.min_y(), self.max_x(), self.max_y()].serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for RectF {
+    fn deserialize<D>(deserializer: D) -> Result<RectF, D::Error> where D: Deserializer<'de> {
+        let [min_x, min_y, max_x, max_y] = <[f32; 4]>::deserialize(deserializer)?;
+        Ok(RectF::from_points(Vector2F::new(min_x, min_y), Vector2F::new(max_x, max_y)))
+    }
+}
+
+/// A rectangle with 64-bit floating point coordinates, for scenes whose coordinates are too large
+/// for `RectF`. This is not SIMD-optimized.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct RectF64 {
+    origin: Vector2F64,
+    lower_right: Vector2F64,
+}
+
+impl RectF64 {
+    #[inline]
+    pub fn new(origin: Vector2F64, size: Vector2F64) -> RectF64 {
+        RectF64 { origin, lower_right: origin + size }
+    }
+
+    #[inline]
+    pub fn from_points(origin: Vector2F64, lower_right: Vector2F64) -> RectF64 {
+        RectF64 { origin, lower_right }
+    }
+
+    // Accessors
+
+    #[inline]
+    pub fn origin(self) -> Vector2F64 {
+        self.origin
+    }
+
+    #[inline]
+    pub fn size(self) -> Vector2F64 {
+        self.lower_right - self.origin
+    }
+
+    #[inline]
+    pub fn width(self) -> f64 {
+        self.size().x()
+    }
+
+    #[inline]
+    pub fn height(self) -> f64 {
+        self.size().y()
+    }
+
+    #[inline]
+    pub fn upper_right(self) -> Vector2F64 {
+        Vector2F64::new(self.lower_right.x(), self.origin.y())
+    }
+
+    #[inline]
+    pub fn lower_left(self) -> Vector2F64 {
+        Vector2F64::new(self.origin.x(), self.lower_right.y())
+    }
+
+    #[inline]
+    pub fn lower_right(self) -> Vector2F64 {
+        self.lower_right
+    }
+
+    #[inline]
+    pub fn contains_point(self, point: Vector2F64) -> bool {
+        // self.origin <= point && point <= self.lower_right
+        self.origin.x() <= point.x() && self.origin.y() <= point.y() &&
+            point.x() <= self.lower_right.x() && point.y() <= self.lower_right.y()
+    }
+
+    #[inline]
+    pub fn union_point(self, point: Vector2F64) -> RectF64 {
+        RectF64::from_points(self.origin.min(point), self.lower_right.max(point))
+    }
+
+    #[inline]
+    pub fn union_rect(self, other: RectF64) -> RectF64 {
+        RectF64::from_points(self.origin.min(other.origin), self.lower_right.max(other.lower_right))
+    }
+
+    #[inline]
+    pub fn intersects(self, other: RectF64) -> bool {
+        self.origin.x() < other.lower_right.x() && self.origin.y() < other.lower_right.y() &&
+            other.origin.x() < self.lower_right.x() && other.origin.y() < self.lower_right.y()
+    }
+
+    #[inline]
+    pub fn intersection(self, other: RectF64) -> Option<RectF64> {
+        if !self.intersects(other) {
+            None
+        } else {
+            Some(RectF64::from_points(self.origin.max(other.origin),
+                                      self.lower_right.min(other.lower_right)))
+        }
+    }
+
+    #[inline]
+    pub fn min_x(self) -> f64 {
+        self.origin.x()
+    }
+
+    #[inline]
+    pub fn min_y(self) -> f64 {
+        self.origin.y()
+    }
+
+    #[inline]
+    pub fn max_x(self) -> f64 {
+        self.lower_right.x()
+    }
+
+    #[inline]
+    pub fn max_y(self) -> f64 {
+        self.lower_right.y()
+    }
+
+    /// Rounds all points to the nearest `f32`.
+    #[inline]
+    pub fn to_f32(self) -> RectF {
+        RectF::from_points(self.origin.to_f32(), self.lower_right.to_f32())
+    }
 }
 
 /// NB: The origin is inclusive, while the lower right point is exclusive.
@@ -11,12 +11,18 @@
 //! 2D affine transforms.
 
 use crate::line_segment::LineSegment2F;
-use crate::rect::RectF;
+use crate::rect::{RectF, RectF64};
 use crate::transform3d::Transform4F;
 use crate::unit_vector::UnitVector;
-use crate::vector::{Vector2F, Vector2I};
+use crate::vector::{Vector2F, Vector2F64, Vector2I};
 use pathfinder_simd::default::{F32x4, I32x4};
-use std::ops::{Mul, MulAssign, Sub};
+use core::ops::{Mul, MulAssign, Sub};
+
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::math::FloatExt;
 
 /// A 2x2 matrix, optimized with SIMD, in column-major order.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -88,6 +94,21 @@ impl Matrix2x2F {
     }
 }
 
+#[cfg(feature = "pf-serde")]
+impl Serialize for Matrix2x2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        [self.m11(), self.m12(), self.m21(), self.m22()].serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for Matrix2x2F {
+    fn deserialize<D>(deserializer: D) -> Result<Matrix2x2F, D::Error> where D: Deserializer<'de> {
+        let [m11, m12, m21, m22] = <[f32; 4]>::deserialize(deserializer)?;
+        Ok(Matrix2x2F::row_major(m11, m12, m21, m22))
+    }
+}
+
 impl Sub<Matrix2x2F> for Matrix2x2F {
     type Output = Matrix2x2F;
     #[inline]
@@ -138,6 +159,7 @@ impl Matrix2x2I {
 
 /// An affine transform, optimized with SIMD.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
 pub struct Transform2F {
     pub matrix: Matrix2x2F,
     pub vector: Vector2F,
@@ -299,6 +321,16 @@ impl Transform2F {
         let vector_inv = -(matrix_inv * self.vector);
         Transform2F { matrix: matrix_inv, vector: vector_inv }
     }
+
+    #[inline]
+    pub fn to_f64(&self) -> Transform2F64 {
+        Transform2F64::row_major(self.m11() as f64,
+                                 self.m12() as f64,
+                                 self.m21() as f64,
+                                 self.m22() as f64,
+                                 self.vector.x() as f64,
+                                 self.vector.y() as f64)
+    }
 }
 
 impl Mul<Transform2F> for Transform2F {
@@ -347,6 +379,229 @@ impl MulAssign for Transform2F {
     }
 }
 
+/// A 2x2 matrix of 64-bit floats, in column-major order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix2x2F64(pub [f64; 4]);
+
+impl Default for Matrix2x2F64 {
+    #[inline]
+    fn default() -> Matrix2x2F64 {
+        Self::from_scale(Vector2F64::splat(1.0))
+    }
+}
+
+impl Matrix2x2F64 {
+    #[inline]
+    pub fn from_scale(scale: Vector2F64) -> Matrix2x2F64 {
+        Matrix2x2F64([scale.x(), 0.0, 0.0, scale.y()])
+    }
+
+    #[inline]
+    pub fn from_rotation(theta: f64) -> Matrix2x2F64 {
+        let (sin, cos) = theta.sin_cos();
+        Matrix2x2F64([cos, sin, -sin, cos])
+    }
+
+    #[inline]
+    pub fn row_major(m00: f64, m01: f64, m10: f64, m11: f64) -> Matrix2x2F64 {
+        Matrix2x2F64([m00, m10, m01, m11])
+    }
+
+    #[inline]
+    pub fn det(&self) -> f64 {
+        self.0[0] * self.0[3] - self.0[2] * self.0[1]
+    }
+
+    #[inline]
+    pub fn inverse(&self) -> Matrix2x2F64 {
+        let inv_det = 1.0 / self.det();
+        Matrix2x2F64([self.0[3] * inv_det,
+                      -self.0[1] * inv_det,
+                      -self.0[2] * inv_det,
+                      self.0[0] * inv_det])
+    }
+
+    #[inline]
+    pub fn m11(&self) -> f64 {
+        self.0[0]
+    }
+    #[inline]
+    pub fn m21(&self) -> f64 {
+        self.0[1]
+    }
+    #[inline]
+    pub fn m12(&self) -> f64 {
+        self.0[2]
+    }
+    #[inline]
+    pub fn m22(&self) -> f64 {
+        self.0[3]
+    }
+}
+
+impl Mul<Matrix2x2F64> for Matrix2x2F64 {
+    type Output = Matrix2x2F64;
+    #[inline]
+    fn mul(self, other: Matrix2x2F64) -> Matrix2x2F64 {
+        let (a, b) = (self.0, other.0);
+        Matrix2x2F64([a[0] * b[0] + a[2] * b[1],
+                      a[1] * b[0] + a[3] * b[1],
+                      a[0] * b[2] + a[2] * b[3],
+                      a[1] * b[2] + a[3] * b[3]])
+    }
+}
+
+impl Mul<Vector2F64> for Matrix2x2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn mul(self, vector: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.0[0] * vector.x() + self.0[2] * vector.y(),
+                        self.0[1] * vector.x() + self.0[3] * vector.y())
+    }
+}
+
+/// An affine transform with 64-bit floats, for placing geometry in scenes whose coordinates are
+/// too large for `Transform2F`.
+///
+/// Compose the transforms that take geometry from its own space to the view with this type, and
+/// convert the result with `to_f32()`. The large translations then cancel out in double precision
+/// before anything is rounded to single precision.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2F64 {
+    pub matrix: Matrix2x2F64,
+    pub vector: Vector2F64,
+}
+
+impl Default for Transform2F64 {
+    #[inline]
+    fn default() -> Transform2F64 {
+        Self::from_scale(Vector2F64::splat(1.0))
+    }
+}
+
+impl Transform2F64 {
+    #[inline]
+    pub fn from_scale(scale: Vector2F64) -> Transform2F64 {
+        Transform2F64 {
+            matrix: Matrix2x2F64::from_scale(scale),
+            vector: Vector2F64::default(),
+        }
+    }
+
+    #[inline]
+    pub fn from_uniform_scale(scale: f64) -> Transform2F64 {
+        Transform2F64::from_scale(Vector2F64::splat(scale))
+    }
+
+    #[inline]
+    pub fn from_rotation(theta: f64) -> Transform2F64 {
+        Transform2F64 {
+            matrix: Matrix2x2F64::from_rotation(theta),
+            vector: Vector2F64::default(),
+        }
+    }
+
+    #[inline]
+    pub fn from_translation(vector: Vector2F64) -> Transform2F64 {
+        Transform2F64 { matrix: Matrix2x2F64::default(), vector }
+    }
+
+    #[inline]
+    pub fn row_major(m11: f64, m12: f64, m21: f64, m22: f64, m31: f64, m32: f64)
+                     -> Transform2F64 {
+        Transform2F64 {
+            matrix: Matrix2x2F64::row_major(m11, m12, m21, m22),
+            vector: Vector2F64::new(m31, m32),
+        }
+    }
+
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Transform2F64::default()
+    }
+
+    #[inline]
+    pub fn translate(&self, vector: Vector2F64) -> Transform2F64 {
+        Transform2F64::from_translation(vector) * *self
+    }
+
+    #[inline]
+    pub fn rotate(&self, theta: f64) -> Transform2F64 {
+        Transform2F64::from_rotation(theta) * *self
+    }
+
+    #[inline]
+    pub fn scale(&self, scale: Vector2F64) -> Transform2F64 {
+        Transform2F64::from_scale(scale) * *self
+    }
+
+    #[inline]
+    pub fn uniform_scale(&self, scale: f64) -> Transform2F64 {
+        self.scale(Vector2F64::splat(scale))
+    }
+
+    #[inline]
+    pub fn translation(&self) -> Vector2F64 {
+        self.vector
+    }
+
+    #[inline]
+    pub fn inverse(&self) -> Transform2F64 {
+        let matrix_inv = self.matrix.inverse();
+        let vector_inv = -(matrix_inv * self.vector);
+        Transform2F64 { matrix: matrix_inv, vector: vector_inv }
+    }
+
+    /// Rounds this transform to single precision.
+    #[inline]
+    pub fn to_f32(&self) -> Transform2F {
+        Transform2F::row_major(self.matrix.m11() as f32,
+                               self.matrix.m12() as f32,
+                               self.matrix.m21() as f32,
+                               self.matrix.m22() as f32,
+                               self.vector.x() as f32,
+                               self.vector.y() as f32)
+    }
+}
+
+impl Mul<Transform2F64> for Transform2F64 {
+    type Output = Transform2F64;
+    #[inline]
+    fn mul(self, other: Transform2F64) -> Transform2F64 {
+        Transform2F64 {
+            matrix: self.matrix * other.matrix,
+            vector: self * other.vector,
+        }
+    }
+}
+
+impl Mul<Vector2F64> for Transform2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn mul(self, vector: Vector2F64) -> Vector2F64 {
+        self.matrix * vector + self.vector
+    }
+}
+
+impl Mul<RectF64> for Transform2F64 {
+    type Output = RectF64;
+    #[inline]
+    fn mul(self, rect: RectF64) -> RectF64 {
+        let (upper_left, upper_right) = (self * rect.origin(),     self * rect.upper_right());
+        let (lower_left, lower_right) = (self * rect.lower_left(), self * rect.lower_right());
+        let min_point = upper_left.min(upper_right).min(lower_left).min(lower_right);
+        let max_point = upper_left.max(upper_right).max(lower_left).max(lower_right);
+        RectF64::from_points(min_point, max_point)
+    }
+}
+
+impl MulAssign for Transform2F64 {
+    #[inline]
+    fn mul_assign(&mut self, other: Transform2F64) {
+        *self = *self * other
+    }
+}
+
 /// An affine integer transform, optimized with SIMD.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Transform2I {
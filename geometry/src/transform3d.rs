@@ -14,7 +14,10 @@ use crate::vector::{Vector2F, Vector2I, Vector3F, Vector4F};
 use crate::rect::RectF;
 use crate::transform2d::Matrix2x2F;
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Mul, MulAssign, Neg};
+use core::ops::{Add, Mul, MulAssign, Neg};
+
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::math::FloatExt;
 
 /// An transform, optimized with SIMD.
 ///
@@ -13,6 +13,9 @@
 use crate::vector::Vector2F;
 use pathfinder_simd::default::F32x2;
 
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::math::FloatExt;
+
 #[derive(Clone, Copy, Debug)]
 pub struct UnitVector(pub Vector2F);
 
@@ -11,7 +11,13 @@
 //! A SIMD-optimized point type.
 
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
-use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::math::FloatExt;
 
 /// 2D points with 32-bit floating point coordinates.
 #[derive(Clone, Copy, Debug, Default)]
@@ -149,6 +155,11 @@ impl Vector2F {
     pub fn to_i32(self) -> Vector2I {
         Vector2I(self.0.to_i32x2())
     }
+
+    #[inline]
+    pub fn to_f64(self) -> Vector2F64 {
+        Vector2F64::new(self.x() as f64, self.y() as f64)
+    }
 }
 
 impl PartialEq for Vector2F {
@@ -198,6 +209,143 @@ impl Neg for Vector2F {
     }
 }
 
+#[cfg(feature = "pf-serde")]
+impl Serialize for Vector2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        [self.x(), self.y()].serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for Vector2F {
+    fn deserialize<D>(deserializer: D) -> Result<Vector2F, D::Error> where D: Deserializer<'de> {
+        let [x, y] = <[f32; 2]>::deserialize(deserializer)?;
+        Ok(Vector2F::new(x, y))
+    }
+}
+
+/// 2D points with 64-bit floating point coordinates, for scenes whose coordinates are too large
+/// for `Vector2F` to represent precisely.
+///
+/// These are not SIMD-optimized. Convert to `Vector2F` once the coordinates are small again, for
+/// example after the view transform.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector2F64(pub [f64; 2]);
+
+impl Vector2F64 {
+    #[inline]
+    pub fn new(x: f64, y: f64) -> Vector2F64 {
+        Vector2F64([x, y])
+    }
+
+    #[inline]
+    pub fn splat(value: f64) -> Vector2F64 {
+        Vector2F64([value, value])
+    }
+
+    #[inline]
+    pub fn x(self) -> f64 {
+        self.0[0]
+    }
+
+    #[inline]
+    pub fn y(self) -> f64 {
+        self.0[1]
+    }
+
+    #[inline]
+    pub fn set_x(&mut self, x: f64) {
+        self.0[0] = x;
+    }
+
+    #[inline]
+    pub fn set_y(&mut self, y: f64) {
+        self.0[1] = y;
+    }
+
+    #[inline]
+    pub fn min(self, other: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.x().min(other.x()), self.y().min(other.y()))
+    }
+
+    #[inline]
+    pub fn max(self, other: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.x().max(other.x()), self.y().max(other.y()))
+    }
+
+    #[inline]
+    pub fn dot(self, other: Vector2F64) -> f64 {
+        self.x() * other.x() + self.y() * other.y()
+    }
+
+    #[inline]
+    pub fn scale(self, x: f64) -> Vector2F64 {
+        Vector2F64::new(self.x() * x, self.y() * x)
+    }
+
+    #[inline]
+    pub fn scale_xy(self, factors: Vector2F64) -> Vector2F64 {
+        self * factors
+    }
+
+    /// Treats this point as a vector and calculates its length.
+    #[inline]
+    pub fn length(self) -> f64 {
+        f64::sqrt(self.dot(self))
+    }
+
+    #[inline]
+    pub fn lerp(self, other: Vector2F64, t: f64) -> Vector2F64 {
+        self + (other - self).scale(t)
+    }
+
+    /// Rounds both coordinates to the nearest `f32`.
+    #[inline]
+    pub fn to_f32(self) -> Vector2F {
+        Vector2F::new(self.x() as f32, self.y() as f32)
+    }
+}
+
+impl Add<Vector2F64> for Vector2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn add(self, other: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.x() + other.x(), self.y() + other.y())
+    }
+}
+
+impl Sub<Vector2F64> for Vector2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn sub(self, other: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.x() - other.x(), self.y() - other.y())
+    }
+}
+
+impl Mul<Vector2F64> for Vector2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn mul(self, other: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.x() * other.x(), self.y() * other.y())
+    }
+}
+
+impl Div<Vector2F64> for Vector2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn div(self, other: Vector2F64) -> Vector2F64 {
+        Vector2F64::new(self.x() / other.x(), self.y() / other.y())
+    }
+}
+
+impl Neg for Vector2F64 {
+    type Output = Vector2F64;
+    #[inline]
+    fn neg(self) -> Vector2F64 {
+        Vector2F64::new(-self.x(), -self.y())
+    }
+}
+
 /// 2D points with 32-bit signed integer coordinates.
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Vector2I(pub I32x2);
@@ -297,6 +445,21 @@ impl PartialEq for Vector2I {
     }
 }
 
+#[cfg(feature = "pf-serde")]
+impl Serialize for Vector2I {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        [self.x(), self.y()].serialize(serializer)
+    }
+}
+
+#[cfg(feature = "pf-serde")]
+impl<'de> Deserialize<'de> for Vector2I {
+    fn deserialize<D>(deserializer: D) -> Result<Vector2I, D::Error> where D: Deserializer<'de> {
+        let [x, y] = <[i32; 2]>::deserialize(deserializer)?;
+        Ok(Vector2I::new(x, y))
+    }
+}
+
 /// 3D points.
 ///
 /// The w value in the SIMD vector is always 0.0.
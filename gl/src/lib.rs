@@ -13,14 +13,15 @@
 #[macro_use]
 extern crate log;
 
-use gl::types::{GLboolean, GLchar, GLenum, GLfloat, GLint, GLsizei, GLsizeiptr, GLsync};
-use gl::types::{GLuint, GLvoid};
+use gl::types::{GLboolean, GLchar, GLenum, GLfloat, GLint, GLintptr, GLsizei, GLsizeiptr};
+use gl::types::{GLsync, GLuint, GLvoid};
 use half::f16;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{DepthFunc, Device, Primitive, RenderOptions, RenderState, RenderTarget};
+use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, Primitive};
+use pathfinder_gpu::{RenderOptions, RenderState, RenderTarget};
 use pathfinder_gpu::{ShaderKind, StencilFunc, TextureData, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_simd::default::F32x4;
@@ -70,8 +71,10 @@ impl GLDevice {
             gl::Viewport(origin.x(), origin.y(), size.x(), size.y());
         }
 
+        // The scissor must be in place before clearing, since it restricts clears too.
+        self.set_scissor(render_state.options.scissor);
         if render_state.options.clear_ops.has_ops() {
-            self.clear(&render_state.options.clear_ops);
+            self.apply_clear_ops(&render_state.options.clear_ops);
         }
 
         self.use_program(render_state.program);
@@ -92,23 +95,10 @@ impl GLDevice {
                     gl::Disable(gl::BLEND); ck();
                 }
                 Some(ref blend) => {
-                    match blend.func {
-                        BlendFunc::RGBOneAlphaOne => {
-                            gl::BlendFunc(gl::ONE, gl::ONE); ck();
-                        }
-                        BlendFunc::RGBOneAlphaOneMinusSrcAlpha => {
-                            gl::BlendFuncSeparate(gl::ONE,
-                                                  gl::ONE_MINUS_SRC_ALPHA,
-                                                  gl::ONE,
-                                                  gl::ONE); ck();
-                        }
-                        BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha => {
-                            gl::BlendFuncSeparate(gl::SRC_ALPHA,
-                                                  gl::ONE_MINUS_SRC_ALPHA,
-                                                  gl::ONE,
-                                                  gl::ONE); ck();
-                        }
-                    }
+                    gl::BlendFuncSeparate(blend.src_rgb_factor.to_gl_blend_factor(),
+                                          blend.dest_rgb_factor.to_gl_blend_factor(),
+                                          blend.src_alpha_factor.to_gl_blend_factor(),
+                                          blend.dest_alpha_factor.to_gl_blend_factor()); ck();
                     match blend.op {
                         BlendOp::Add => {
                             gl::BlendEquation(gl::FUNC_ADD); ck();
@@ -222,6 +212,25 @@ impl GLDevice {
 
             gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE); ck();
         }
+
+        if render_options.scissor.is_some() {
+            self.set_scissor(None);
+        }
+    }
+
+    fn set_scissor(&self, scissor: Option<RectI>) {
+        unsafe {
+            match scissor {
+                None => {
+                    gl::Disable(gl::SCISSOR_TEST); ck();
+                }
+                Some(scissor) => {
+                    let (origin, size) = (scissor.origin(), scissor.size());
+                    gl::Scissor(origin.x(), origin.y(), size.x(), size.y()); ck();
+                    gl::Enable(gl::SCISSOR_TEST); ck();
+                }
+            }
+        }
     }
 }
 
@@ -280,42 +289,11 @@ impl Device for GLDevice {
     }
 
     fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind) -> GLShader {
-        // FIXME(pcwalton): Do this once and cache it.
-        let glsl_version_spec = self.version.to_glsl_version_spec();
-
-        let mut output = vec![];
-        self.preprocess(&mut output, source, glsl_version_spec);
-        let source = output;
-
         let gl_shader_kind = match kind {
             ShaderKind::Vertex => gl::VERTEX_SHADER,
             ShaderKind::Fragment => gl::FRAGMENT_SHADER,
         };
-
-        unsafe {
-            let gl_shader = gl::CreateShader(gl_shader_kind); ck();
-            gl::ShaderSource(gl_shader,
-                             1,
-                             [source.as_ptr() as *const GLchar].as_ptr(),
-                             [source.len() as GLint].as_ptr()); ck();
-            gl::CompileShader(gl_shader); ck();
-
-            let mut compile_status = 0;
-            gl::GetShaderiv(gl_shader, gl::COMPILE_STATUS, &mut compile_status); ck();
-            if compile_status != gl::TRUE as GLint {
-                let mut info_log_length = 0;
-                gl::GetShaderiv(gl_shader, gl::INFO_LOG_LENGTH, &mut info_log_length); ck();
-                let mut info_log = vec![0; info_log_length as usize];
-                gl::GetShaderInfoLog(gl_shader,
-                                     info_log.len() as GLint,
-                                     ptr::null_mut(),
-                                     info_log.as_mut_ptr() as *mut GLchar); ck();
-                error!("Shader info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("{:?} shader '{}' compilation failed", kind, name);
-            }
-
-            GLShader { gl_shader }
-        }
+        self.compile_shader(name, source, gl_shader_kind, &format!("{:?}", kind))
     }
 
     fn create_program_from_shaders(&self,
@@ -324,29 +302,57 @@ impl Device for GLDevice {
                                    vertex_shader: GLShader,
                                    fragment_shader: GLShader)
                                    -> GLProgram {
-        let gl_program;
-        unsafe {
-            gl_program = gl::CreateProgram(); ck();
-            gl::AttachShader(gl_program, vertex_shader.gl_shader); ck();
-            gl::AttachShader(gl_program, fragment_shader.gl_shader); ck();
-            gl::LinkProgram(gl_program); ck();
+        self.link_program(name, vec![vertex_shader, fragment_shader])
+    }
 
-            let mut link_status = 0;
-            gl::GetProgramiv(gl_program, gl::LINK_STATUS, &mut link_status); ck();
-            if link_status != gl::TRUE as GLint {
-                let mut info_log_length = 0;
-                gl::GetProgramiv(gl_program, gl::INFO_LOG_LENGTH, &mut info_log_length); ck();
-                let mut info_log = vec![0; info_log_length as usize];
-                gl::GetProgramInfoLog(gl_program,
-                                      info_log.len() as GLint,
-                                      ptr::null_mut(),
-                                      info_log.as_mut_ptr() as *mut GLchar); ck();
-                eprintln!("Program info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("Program '{}' linking failed", name);
+    fn supports_compute(&self) -> bool {
+        match self.version {
+            GLVersion::GL4 => true,
+            GLVersion::GL3 | GLVersion::GLES3 => false,
+        }
+    }
+
+    fn create_compute_program(&self, resources: &dyn ResourceLoader, name: &str) -> GLProgram {
+        assert!(self.supports_compute());
+        let path = format!("shaders/gl4/{}.cs.glsl", name);
+        let source = resources.slurp(&path).unwrap();
+        let shader = self.compile_shader(name, &source, gl::COMPUTE_SHADER, "Compute");
+        self.link_program(name, vec![shader])
+    }
+
+    fn dispatch_compute(&self, dimensions: ComputeDimensions, state: &ComputeState<GLDevice>) {
+        self.use_program(state.program);
+        for &(uniform, ref data) in state.uniforms {
+            self.set_uniform(uniform, data);
+        }
+        unsafe {
+            for (binding, buffer) in state.storage_buffers.iter().enumerate() {
+                gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER,
+                                   binding as GLuint,
+                                   buffer.gl_buffer); ck();
+            }
+            gl::DispatchCompute(dimensions.x, dimensions.y, dimensions.z); ck();
+            gl::MemoryBarrier(gl::ALL_BARRIER_BITS); ck();
+            for binding in 0..(state.storage_buffers.len() as GLuint) {
+                gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, 0); ck();
             }
         }
+        self.unuse_program();
+    }
 
-        GLProgram { gl_program, vertex_shader, fragment_shader }
+    fn read_buffer<T>(&self, buffer: &GLBuffer, start: usize, length: usize) -> Vec<T>
+                      where T: Copy {
+        let mut values = Vec::with_capacity(length);
+        unsafe {
+            gl::BindBuffer(gl::COPY_READ_BUFFER, buffer.gl_buffer); ck();
+            gl::GetBufferSubData(gl::COPY_READ_BUFFER,
+                                 (start * mem::size_of::<T>()) as GLintptr,
+                                 (length * mem::size_of::<T>()) as GLsizeiptr,
+                                 values.as_mut_ptr() as *mut GLvoid); ck();
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0); ck();
+            values.set_len(length);
+        }
+        values
     }
 
     #[inline]
@@ -432,7 +438,48 @@ impl Device for GLDevice {
             assert_eq!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE);
         }
 
-        GLFramebuffer { gl_framebuffer, texture }
+        GLFramebuffer { gl_framebuffer, gl_renderbuffer: 0, texture }
+    }
+
+    fn create_multisampled_framebuffer(&self,
+                                       format: TextureFormat,
+                                       size: Vector2I,
+                                       sample_count: u32)
+                                       -> GLFramebuffer {
+        let (mut gl_framebuffer, mut gl_renderbuffer) = (0, 0);
+        unsafe {
+            gl::GenRenderbuffers(1, &mut gl_renderbuffer); ck();
+            gl::BindRenderbuffer(gl::RENDERBUFFER, gl_renderbuffer); ck();
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER,
+                                               sample_count as GLsizei,
+                                               format.gl_internal_format() as GLuint,
+                                               size.x() as GLsizei,
+                                               size.y() as GLsizei); ck();
+
+            gl::GenFramebuffers(1, &mut gl_framebuffer); ck();
+            gl::BindFramebuffer(gl::FRAMEBUFFER, gl_framebuffer); ck();
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER,
+                                        gl::COLOR_ATTACHMENT0,
+                                        gl::RENDERBUFFER,
+                                        gl_renderbuffer); ck();
+            assert_eq!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE);
+        }
+
+        // The samples live in the renderbuffer, so the texture only records the size and format.
+        let texture = GLTexture { gl_texture: 0, size, format };
+        GLFramebuffer { gl_framebuffer, gl_renderbuffer, texture }
+    }
+
+    fn resolve_framebuffer(&self, source: &GLFramebuffer, dest: &GLFramebuffer) {
+        let (width, height) = (source.texture.size.x(), source.texture.size.y());
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, source.gl_framebuffer); ck();
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, dest.gl_framebuffer); ck();
+            gl::BlitFramebuffer(0, 0, width, height,
+                                0, 0, width, height,
+                                gl::COLOR_BUFFER_BIT,
+                                gl::NEAREST); ck();
+        }
     }
 
     fn create_buffer(&self) -> GLBuffer {
@@ -470,6 +517,10 @@ impl Device for GLDevice {
     }
 
     #[inline]
+    fn texture_format(&self, texture: &Self::Texture) -> TextureFormat {
+        texture.format
+    }
+
     fn texture_size(&self, texture: &Self::Texture) -> Vector2I {
         texture.size
     }
@@ -540,6 +591,24 @@ impl Device for GLDevice {
         }
     }
 
+    fn copy_render_target(&self, render_target: &RenderTarget<GLDevice>, rect: RectI)
+                          -> GLTexture {
+        let texture = self.create_texture(self.render_target_format(render_target), rect.size());
+        self.bind_render_target(render_target);
+        self.bind_texture(&texture, 0);
+        unsafe {
+            gl::CopyTexSubImage2D(gl::TEXTURE_2D,
+                                  0,
+                                  0,
+                                  0,
+                                  rect.origin().x(),
+                                  rect.origin().y(),
+                                  rect.size().x() as GLsizei,
+                                  rect.size().y() as GLsizei); ck();
+        }
+        texture
+    }
+
     fn begin_commands(&self) {
         // TODO(pcwalton): Add some checks in debug mode to make sure render commands are bracketed
         // by these?
@@ -559,6 +628,17 @@ impl Device for GLDevice {
         self.reset_render_state(render_state);
     }
 
+    fn clear(&self, target: &RenderTarget<Self>, scissor: Option<RectI>, ops: &ClearOps) {
+        self.bind_render_target(target);
+        if ops.has_ops() {
+            self.set_scissor(scissor);
+            self.apply_clear_ops(ops);
+            if scissor.is_some() {
+                self.set_scissor(None);
+            }
+        }
+    }
+
     fn draw_elements(&self, index_count: u32, render_state: &RenderState<Self>) {
         self.set_render_state(render_state);
         unsafe {
@@ -734,6 +814,68 @@ impl GLDevice {
         }
     }
 
+    fn compile_shader(&self, name: &str, source: &[u8], gl_shader_kind: GLenum, kind_name: &str)
+                      -> GLShader {
+        // FIXME(pcwalton): Do this once and cache it.
+        let glsl_version_spec = self.version.to_glsl_version_spec();
+
+        let mut output = vec![];
+        self.preprocess(&mut output, source, glsl_version_spec);
+        let source = output;
+
+        unsafe {
+            let gl_shader = gl::CreateShader(gl_shader_kind); ck();
+            gl::ShaderSource(gl_shader,
+                             1,
+                             [source.as_ptr() as *const GLchar].as_ptr(),
+                             [source.len() as GLint].as_ptr()); ck();
+            gl::CompileShader(gl_shader); ck();
+
+            let mut compile_status = 0;
+            gl::GetShaderiv(gl_shader, gl::COMPILE_STATUS, &mut compile_status); ck();
+            if compile_status != gl::TRUE as GLint {
+                let mut info_log_length = 0;
+                gl::GetShaderiv(gl_shader, gl::INFO_LOG_LENGTH, &mut info_log_length); ck();
+                let mut info_log = vec![0; info_log_length as usize];
+                gl::GetShaderInfoLog(gl_shader,
+                                     info_log.len() as GLint,
+                                     ptr::null_mut(),
+                                     info_log.as_mut_ptr() as *mut GLchar); ck();
+                error!("Shader info log:\n{}", String::from_utf8_lossy(&info_log));
+                panic!("{} shader '{}' compilation failed", kind_name, name);
+            }
+
+            GLShader { gl_shader }
+        }
+    }
+
+    fn link_program(&self, name: &str, shaders: Vec<GLShader>) -> GLProgram {
+        let gl_program;
+        unsafe {
+            gl_program = gl::CreateProgram(); ck();
+            for shader in &shaders {
+                gl::AttachShader(gl_program, shader.gl_shader); ck();
+            }
+            gl::LinkProgram(gl_program); ck();
+
+            let mut link_status = 0;
+            gl::GetProgramiv(gl_program, gl::LINK_STATUS, &mut link_status); ck();
+            if link_status != gl::TRUE as GLint {
+                let mut info_log_length = 0;
+                gl::GetProgramiv(gl_program, gl::INFO_LOG_LENGTH, &mut info_log_length); ck();
+                let mut info_log = vec![0; info_log_length as usize];
+                gl::GetProgramInfoLog(gl_program,
+                                      info_log.len() as GLint,
+                                      ptr::null_mut(),
+                                      info_log.as_mut_ptr() as *mut GLchar); ck();
+                eprintln!("Program info log:\n{}", String::from_utf8_lossy(&info_log));
+                panic!("Program '{}' linking failed", name);
+            }
+        }
+
+        GLProgram { gl_program, shaders }
+    }
+
     fn preprocess(&self, output: &mut Vec<u8>, source: &[u8], version: &str) {
         let mut index = 0;
         while index < source.len() {
@@ -756,7 +898,7 @@ impl GLDevice {
         }
     }
 
-    fn clear(&self, ops: &ClearOps) {
+    fn apply_clear_ops(&self, ops: &ClearOps) {
         unsafe {
             let mut flags = 0;
             if let Some(color) = ops.color {
@@ -815,6 +957,12 @@ impl GLDevice {
                     texture_data_len = pixels.len() * mem::size_of::<f32>();
                     texture_data = TextureData::F32(pixels);
                 }
+                TextureFormat::RGB10A2 => {
+                    let mut pixels: Vec<u32> = vec![0; size.x() as usize * size.y() as usize];
+                    texture_data_ptr = pixels.as_mut_ptr() as *mut u8;
+                    texture_data_len = pixels.len() * mem::size_of::<u32>();
+                    texture_data = TextureData::U32(pixels);
+                }
             }
 
             gl::BindBuffer(gl::PIXEL_PACK_BUFFER, receiver.gl_pixel_buffer); ck();
@@ -829,6 +977,7 @@ impl GLDevice {
                 TextureData::U16(ref mut pixels) => flip_y(pixels, size, channels),
                 TextureData::F16(ref mut pixels) => flip_y(pixels, size, channels),
                 TextureData::F32(ref mut pixels) => flip_y(pixels, size, channels),
+                TextureData::U32(ref mut pixels) => flip_y(pixels, size, 1),
             }
 
             texture_data
@@ -893,6 +1042,8 @@ impl GLVertexAttr {
 
 pub struct GLFramebuffer {
     pub gl_framebuffer: GLuint,
+    /// The multisampled color buffer, or 0 if the framebuffer draws into `texture`.
+    pub gl_renderbuffer: GLuint,
     pub texture: GLTexture,
 }
 
@@ -900,6 +1051,9 @@ impl Drop for GLFramebuffer {
     fn drop(&mut self) {
         unsafe {
             gl::DeleteFramebuffers(1, &mut self.gl_framebuffer); ck();
+            if self.gl_renderbuffer != 0 {
+                gl::DeleteRenderbuffers(1, &mut self.gl_renderbuffer); ck();
+            }
         }
     }
 }
@@ -924,9 +1078,7 @@ pub struct GLUniform {
 pub struct GLProgram {
     pub gl_program: GLuint,
     #[allow(dead_code)]
-    vertex_shader: GLShader,
-    #[allow(dead_code)]
-    fragment_shader: GLShader,
+    shaders: Vec<GLShader>,
 }
 
 impl Drop for GLProgram {
@@ -955,6 +1107,14 @@ pub struct GLTexture {
     pub format: TextureFormat,
 }
 
+impl Drop for GLTexture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &mut self.gl_texture); ck();
+        }
+    }
+}
+
 pub struct GLTimerQuery {
     gl_query: GLuint,
 }
@@ -968,6 +1128,26 @@ impl Drop for GLTimerQuery {
     }
 }
 
+trait BlendFactorExt {
+    fn to_gl_blend_factor(self) -> GLenum;
+}
+
+impl BlendFactorExt for BlendFactor {
+    #[inline]
+    fn to_gl_blend_factor(self) -> GLenum {
+        match self {
+            BlendFactor::Zero => gl::ZERO,
+            BlendFactor::One => gl::ONE,
+            BlendFactor::SrcAlpha => gl::SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => gl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::DestAlpha => gl::DST_ALPHA,
+            BlendFactor::OneMinusDestAlpha => gl::ONE_MINUS_DST_ALPHA,
+            BlendFactor::DestColor => gl::DST_COLOR,
+            BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+        }
+    }
+}
+
 trait BufferTargetExt {
     fn to_gl_target(self) -> GLuint;
 }
@@ -1047,13 +1227,17 @@ impl TextureFormatExt for TextureFormat {
             TextureFormat::RGBA8 => gl::RGBA as GLint,
             TextureFormat::RGBA16F => gl::RGBA16F as GLint,
             TextureFormat::RGBA32F => gl::RGBA32F as GLint,
+            TextureFormat::RGB10A2 => gl::RGB10_A2 as GLint,
         }
     }
 
     fn gl_format(self) -> GLuint {
         match self {
             TextureFormat::R8 | TextureFormat::R16F => gl::RED,
-            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => gl::RGBA,
+            TextureFormat::RGBA8 |
+            TextureFormat::RGBA16F |
+            TextureFormat::RGBA32F |
+            TextureFormat::RGB10A2 => gl::RGBA,
         }
     }
 
@@ -1062,6 +1246,7 @@ impl TextureFormatExt for TextureFormat {
             TextureFormat::R8 | TextureFormat::RGBA8 => gl::UNSIGNED_BYTE,
             TextureFormat::R16F | TextureFormat::RGBA16F => gl::HALF_FLOAT,
             TextureFormat::RGBA32F => gl::FLOAT,
+            TextureFormat::RGB10A2 => gl::UNSIGNED_INT_2_10_10_10_REV,
         }
     }
 }
@@ -1106,6 +1291,8 @@ pub enum GLVersion {
     GL3 = 0,
     /// OpenGL ES 3.0+.
     GLES3 = 1,
+    /// OpenGL 4.3+, core profile. This adds compute shaders.
+    GL4 = 2,
 }
 
 impl GLVersion {
@@ -1113,6 +1300,7 @@ impl GLVersion {
         match *self {
             GLVersion::GL3 => "330",
             GLVersion::GLES3 => "300 es",
+            GLVersion::GL4 => "430",
         }
     }
 }
@@ -0,0 +1,215 @@
+// pathfinder/glutin/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Opens a window with `glutin` and draws Pathfinder scenes into it.
+//!
+//! The window owns the OpenGL context and the renderer, and takes care of resizing, changes of
+//! DPI, and presenting each frame, so that an application only has to supply scenes:
+//!
+//! ```norun
+//! PathfinderWindow::new(WindowOptions::default()).run(|frame| {
+//!     let mut canvas = CanvasRenderingContext2D::new(font_context.clone(), frame.size);
+//!     canvas.fill_rect(RectF::new(Vector2F::splat(10.0), Vector2F::splat(100.0)));
+//!     (canvas.into_scene(), FrameAction::Wait)
+//! })
+//! ```
+//!
+//! Scenes are drawn in logical pixels, which the window scales to physical pixels.
+
+use glutin::dpi::{LogicalSize, PhysicalSize};
+use glutin::event::{Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use glutin::event_loop::{ControlFlow, EventLoop};
+use glutin::window::WindowBuilder;
+use glutin::{ContextBuilder, GlProfile, GlRequest, PossiblyCurrent, WindowedContext};
+use pathfinder_color::ColorF;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::resources::{FilesystemResourceLoader, ResourceLoader};
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::{Scene, SceneBuildError};
+
+/// How to open a window.
+#[derive(Clone, Debug)]
+pub struct WindowOptions {
+    pub title: String,
+    /// The initial size of the window, in logical pixels.
+    pub size: Vector2F,
+    pub resizable: bool,
+    /// Waits for the vertical blank before presenting each frame, to avoid tearing.
+    pub vsync: bool,
+    /// The color that the window is cleared to before each scene is drawn.
+    pub background_color: Option<ColorF>,
+}
+
+impl Default for WindowOptions {
+    fn default() -> WindowOptions {
+        WindowOptions {
+            title: "Pathfinder".to_owned(),
+            size: Vector2F::new(640.0, 480.0),
+            resizable: true,
+            vsync: true,
+            background_color: Some(ColorF::white()),
+        }
+    }
+}
+
+/// The state of the window when a frame is drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct Frame {
+    /// The size of the window, in the logical pixels that scenes are drawn in.
+    pub size: Vector2F,
+    /// The number of physical pixels per logical pixel.
+    pub scale_factor: f32,
+    /// Why the scene of the previous frame couldn't be built, if it couldn't. Only the
+    /// background was drawn for that frame.
+    pub build_error: Option<SceneBuildError>,
+}
+
+/// What the window should do after drawing a frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FrameAction {
+    /// Draws another frame as soon as possible, for animations.
+    Redraw,
+    /// Draws the next frame only when the window needs it, such as after a resize.
+    Wait,
+    /// Closes the window.
+    Exit,
+}
+
+/// A window that draws Pathfinder scenes.
+pub struct PathfinderWindow {
+    event_loop: EventLoop<()>,
+    context: WindowedContext<PossiblyCurrent>,
+    renderer: Renderer<GLDevice>,
+    scene_proxy: SceneProxy,
+}
+
+impl PathfinderWindow {
+    /// Opens a window with an OpenGL 3 context, loading shaders and other resources from the
+    /// `resources` directory that `FilesystemResourceLoader::locate()` finds.
+    #[inline]
+    pub fn new(options: WindowOptions) -> PathfinderWindow {
+        PathfinderWindow::with_resources(options, &FilesystemResourceLoader::locate())
+    }
+
+    /// Opens a window with an OpenGL 3 context, loading resources from `resources`.
+    pub fn with_resources(options: WindowOptions, resources: &dyn ResourceLoader)
+                          -> PathfinderWindow {
+        let event_loop = EventLoop::new();
+        let logical_size = LogicalSize::new(options.size.x() as f64, options.size.y() as f64);
+        let window_builder = WindowBuilder::new().with_title(options.title)
+                                                 .with_inner_size(logical_size)
+                                                 .with_resizable(options.resizable);
+        let context = ContextBuilder::new().with_gl(GlRequest::Latest)
+                                           .with_gl_profile(GlProfile::Core)
+                                           .with_vsync(options.vsync)
+                                           .build_windowed(window_builder, &event_loop)
+                                           .unwrap();
+        let context = unsafe { context.make_current().unwrap() };
+        gl::load_with(|name| context.get_proc_address(name) as *const _);
+
+        let physical_size = physical_size_of(&context);
+        let renderer = Renderer::new(GLDevice::new(GLVersion::GL3, 0),
+                                     resources,
+                                     DestFramebuffer::full_window(physical_size),
+                                     RendererOptions {
+                                         background_color: options.background_color,
+                                         ..RendererOptions::default()
+                                     });
+        let scene_proxy = SceneProxy::from_scene(Scene::new(), RayonExecutor);
+        PathfinderWindow { event_loop, context, renderer, scene_proxy }
+    }
+
+    /// Calls `draw` for each frame and presents the scene it returns, until `draw` asks to exit
+    /// or the window is closed or Escape is pressed. A scene that fails to build is reported to
+    /// `draw` in the next frame's `Frame::build_error`.
+    pub fn run<F>(self, mut draw: F) -> !
+                  where F: FnMut(&Frame) -> (Scene, FrameAction) + 'static {
+        let PathfinderWindow { event_loop, context, mut renderer, scene_proxy } = self;
+        let mut build_error = None;
+        context.window().request_redraw();
+        event_loop.run(move |event, _, control_flow| {
+            match event {
+                Event::WindowEvent { event: WindowEvent::CloseRequested, .. } |
+                Event::WindowEvent {
+                    event: WindowEvent::KeyboardInput {
+                        input: KeyboardInput {
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
+                            ..
+                        },
+                        ..
+                    },
+                    ..
+                } => *control_flow = ControlFlow::Exit,
+                Event::WindowEvent { event: WindowEvent::Resized(physical_size), .. } => {
+                    resize(&context, &mut renderer, physical_size);
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::ScaleFactorChanged { new_inner_size, .. },
+                    ..
+                } => {
+                    resize(&context, &mut renderer, *new_inner_size);
+                }
+                Event::RedrawRequested(_) => {
+                    let physical_size = physical_size_of(&context);
+                    let scale_factor = context.window().scale_factor() as f32;
+                    let frame = Frame {
+                        size: physical_size.to_f32().scale(1.0 / scale_factor),
+                        scale_factor,
+                        build_error,
+                    };
+
+                    let (mut scene, action) = draw(&frame);
+                    scene.set_view_box(RectF::new(Vector2F::default(), physical_size.to_f32()));
+                    scene_proxy.replace_scene(scene);
+                    let transform = Transform2F::from_scale(Vector2F::splat(scale_factor));
+                    let build_options = BuildOptions {
+                        transform: RenderTransform::Transform2D(transform),
+                        ..BuildOptions::default()
+                    };
+                    build_error = scene_proxy.build_and_render(&mut renderer, build_options).err();
+                    context.swap_buffers().unwrap();
+
+                    *control_flow = match action {
+                        FrameAction::Redraw => {
+                            context.window().request_redraw();
+                            ControlFlow::Poll
+                        }
+                        FrameAction::Wait => ControlFlow::Wait,
+                        FrameAction::Exit => ControlFlow::Exit,
+                    };
+                }
+                _ => {}
+            }
+        })
+    }
+}
+
+fn physical_size_of(context: &WindowedContext<PossiblyCurrent>) -> Vector2I {
+    let physical_size = context.window().inner_size();
+    Vector2I::new(physical_size.width as i32, physical_size.height as i32)
+}
+
+// Resizes the drawable to match the window, and redraws it.
+fn resize(context: &WindowedContext<PossiblyCurrent>,
+          renderer: &mut Renderer<GLDevice>,
+          physical_size: PhysicalSize<u32>) {
+    context.resize(physical_size);
+    let size = Vector2I::new(physical_size.width as i32, physical_size.height as i32);
+    renderer.replace_dest_framebuffer(DestFramebuffer::full_window(size));
+    renderer.set_main_framebuffer_size(size);
+    context.window().request_redraw();
+}
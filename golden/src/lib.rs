@@ -0,0 +1,302 @@
+// pathfinder/golden/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! End-to-end rendering tests against reference images.
+//!
+//! Scenes are rendered offscreen with OpenGL and compared against the PNG images in the `images`
+//! directory of this crate. Unless `PATHFINDER_GOLDEN_HARDWARE` is set, Mesa's software
+//! rasterizer is requested, so that results don't depend on the GPU and driver of the machine.
+//!
+//! Images are compared with a perceptual color difference, so that small changes in antialiasing
+//! pass. When an image doesn't match, the rendered image and an image highlighting the
+//! differences in red are written to `target/golden`. To accept new results as the reference
+//! images, run the tests with `PATHFINDER_GOLDEN_BLESS` set. This is also how the reference image
+//! of a new scene is recorded; without it, a missing reference image is a failure.
+
+use glutin::dpi::PhysicalSize;
+use glutin::event_loop::EventLoop;
+use glutin::{Context, ContextBuilder, GlProfile, GlRequest, PossiblyCurrent};
+use image::{Rgba, RgbaImage};
+use pathfinder_color::ColorF;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::resources::FilesystemResourceLoader;
+use pathfinder_gpu::{Device, RenderTarget, TextureData, TextureFormat};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_renderer::scene::Scene;
+use std::env;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const BLESS_VARIABLE: &str = "PATHFINDER_GOLDEN_BLESS";
+const HARDWARE_VARIABLE: &str = "PATHFINDER_GOLDEN_HARDWARE";
+
+// The largest possible value of `color_delta()` before normalization, between black and white.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+/// How different a rendered image may be from its reference image.
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+    /// The perceptual difference, from 0 to 1, above which two pixels are considered different.
+    pub color_threshold: f32,
+    /// The number of different pixels that are allowed, to make room for antialiasing changes.
+    pub max_different_pixels: u32,
+}
+
+impl Default for Tolerance {
+    #[inline]
+    fn default() -> Tolerance {
+        Tolerance { color_threshold: 0.1, max_different_pixels: 0 }
+    }
+}
+
+/// Renders scenes offscreen for comparison against reference images.
+///
+/// OpenGL contexts are tied to the thread that creates them, so tests that use this want to run
+/// on the main thread, without the standard test harness.
+pub struct GoldenRenderer {
+    renderer: Renderer<GLDevice>,
+    // Declared after the renderer, so that the context is destroyed after the renderer's
+    // resources are.
+    #[allow(dead_code)]
+    context: Context<PossiblyCurrent>,
+    #[allow(dead_code)]
+    event_loop: EventLoop<()>,
+}
+
+impl GoldenRenderer {
+    pub fn new() -> GoldenRenderer {
+        if env::var_os(HARDWARE_VARIABLE).is_none() {
+            env::set_var("LIBGL_ALWAYS_SOFTWARE", "1");
+        }
+
+        // The context itself needs no surface, since scenes are drawn into framebuffers of their
+        // own size.
+        let event_loop = EventLoop::new();
+        let context = ContextBuilder::new().with_gl(GlRequest::Latest)
+                                           .with_gl_profile(GlProfile::Core)
+                                           .build_headless(&event_loop, PhysicalSize::new(1, 1))
+                                           .unwrap();
+        let context = unsafe { context.make_current().unwrap() };
+        gl::load_with(|name| context.get_proc_address(name) as *const _);
+
+        let device = GLDevice::new(GLVersion::GL3, 0);
+        let framebuffer = device.create_framebuffer(device.create_texture(TextureFormat::RGBA8,
+                                                                          Vector2I::splat(1)));
+        let renderer = Renderer::new(device,
+                                     &FilesystemResourceLoader::locate(),
+                                     DestFramebuffer::Other(framebuffer),
+                                     RendererOptions {
+                                         background_color: Some(ColorF::white()),
+                                         ..RendererOptions::default()
+                                     });
+        GoldenRenderer { renderer, context, event_loop }
+    }
+
+    /// Renders `scene` at the size of its view box, on a white background.
+    ///
+    /// Panics if the scene can't be built.
+    pub fn render(&mut self, scene: &Scene) -> RgbaImage {
+        let size = scene.view_box().size().ceil().to_i32().max(Vector2I::splat(1));
+        let texture = self.renderer.device.create_texture(TextureFormat::RGBA8, size);
+        let framebuffer = self.renderer.device.create_framebuffer(texture);
+        self.renderer.replace_dest_framebuffer(DestFramebuffer::Other(framebuffer));
+
+        // Build on one thread, so that the order of fills is the same every time.
+        let commands = Mutex::new(vec![]);
+        scene.build(BuildOptions::default(),
+                    |command| commands.lock().unwrap().push(command),
+                    &SequentialExecutor)
+             .unwrap();
+        self.renderer.begin_scene();
+        for command in commands.into_inner().unwrap() {
+            self.renderer.render_command(&command);
+        }
+        self.renderer.end_scene();
+
+        let framebuffer = match *self.renderer.dest_framebuffer() {
+            DestFramebuffer::Other(ref framebuffer) => framebuffer,
+            _ => unreachable!(),
+        };
+        let viewport = RectI::new(Vector2I::default(), size);
+        let device = &self.renderer.device;
+        let receiver = device.read_pixels(&RenderTarget::Framebuffer(framebuffer), viewport);
+        let pixels = match device.recv_texture_data(&receiver) {
+            TextureData::U8(pixels) => pixels,
+            _ => panic!("Unexpected pixel format for an RGBA8 framebuffer!"),
+        };
+        RgbaImage::from_raw(size.x() as u32, size.y() as u32, pixels).unwrap()
+    }
+}
+
+impl Default for GoldenRenderer {
+    #[inline]
+    fn default() -> GoldenRenderer {
+        GoldenRenderer::new()
+    }
+}
+
+/// Why a rendered image didn't match its reference image.
+#[derive(Debug)]
+pub enum GoldenError {
+    /// The images have different sizes.
+    SizeMismatch { name: String, actual: (u32, u32), expected: (u32, u32) },
+    /// Too many pixels differ. The diff image shows where.
+    PixelMismatch { name: String, different_pixels: u32, diff_path: PathBuf },
+    /// There's no reference image to compare against.
+    MissingReference { name: String, path: PathBuf },
+    /// A reference image couldn't be loaded or saved.
+    Io { path: PathBuf, message: String },
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            GoldenError::SizeMismatch { ref name, actual, expected } => {
+                write!(formatter,
+                       "`{}` is {}×{}, but the reference image is {}×{}",
+                       name,
+                       actual.0,
+                       actual.1,
+                       expected.0,
+                       expected.1)
+            }
+            GoldenError::PixelMismatch { ref name, different_pixels, ref diff_path } => {
+                write!(formatter,
+                       "`{}` differs from the reference image in {} pixels (see {})",
+                       name,
+                       different_pixels,
+                       diff_path.display())
+            }
+            GoldenError::MissingReference { ref name, ref path } => {
+                write!(formatter,
+                       "`{}` has no reference image at {}; run with {} set to record it",
+                       name,
+                       path.display(),
+                       BLESS_VARIABLE)
+            }
+            GoldenError::Io { ref path, ref message } => {
+                write!(formatter, "couldn't access {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+/// Compares `actual` against the reference image called `name`.
+///
+/// If `PATHFINDER_GOLDEN_BLESS` is set, `actual` becomes the reference image instead.
+pub fn check(name: &str, actual: &RgbaImage, tolerance: Tolerance) -> Result<(), GoldenError> {
+    let reference_path = reference_dir().join(format!("{}.png", name));
+    if env::var_os(BLESS_VARIABLE).is_some() {
+        println!("recording reference image {}", reference_path.display());
+        return save(actual, reference_path);
+    }
+    if !reference_path.exists() {
+        return Err(GoldenError::MissingReference { name: name.to_owned(), path: reference_path });
+    }
+
+    let expected = match image::open(&reference_path) {
+        Ok(expected) => expected.to_rgba(),
+        Err(error) => {
+            return Err(GoldenError::Io { path: reference_path, message: error.to_string() })
+        }
+    };
+    if actual.dimensions() != expected.dimensions() {
+        return Err(GoldenError::SizeMismatch {
+            name: name.to_owned(),
+            actual: actual.dimensions(),
+            expected: expected.dimensions(),
+        });
+    }
+
+    let (different_pixels, diff) = compare(actual, &expected, tolerance.color_threshold);
+    if different_pixels <= tolerance.max_different_pixels {
+        return Ok(());
+    }
+
+    let output_dir = output_dir();
+    let actual_path = output_dir.join(format!("{}-actual.png", name));
+    let diff_path = output_dir.join(format!("{}-diff.png", name));
+    save(actual, actual_path)?;
+    save(&diff, diff_path.clone())?;
+    Err(GoldenError::PixelMismatch { name: name.to_owned(), different_pixels, diff_path })
+}
+
+/// Returns the number of pixels whose perceptual difference exceeds `threshold`, and an image
+/// showing them in red over a faded copy of `expected`.
+///
+/// The images must be the same size.
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, threshold: f32) -> (u32, RgbaImage) {
+    let mut different_pixels = 0;
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    for (x, y, &expected_pixel) in expected.enumerate_pixels() {
+        let delta = color_delta(*actual.get_pixel(x, y), expected_pixel);
+        let diff_pixel = if delta > threshold * threshold {
+            different_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let gray = 255.0 - (255.0 - luma(blend_with_white(expected_pixel))) * 0.1;
+            Rgba([gray as u8, gray as u8, gray as u8, 255])
+        };
+        diff.put_pixel(x, y, diff_pixel);
+    }
+    (different_pixels, diff)
+}
+
+// The difference between two colors in the YIQ color space, from 0 to 1, as in "Measuring
+// perceived color difference using YIQ NTSC transmission color space in mobile applications" by
+// Kotsalos et al.
+fn color_delta(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+    let (a, b) = (blend_with_white(a), blend_with_white(b));
+    let y = luma(a) - luma(b);
+    let i = (a[0] * 0.59597799 - a[1] * 0.2741761 - a[2] * 0.3218019) -
+        (b[0] * 0.59597799 - b[1] * 0.2741761 - b[2] * 0.3218019);
+    let q = (a[0] * 0.21147017 - a[1] * 0.5226171 + a[2] * 0.31114694) -
+        (b[0] * 0.21147017 - b[1] * 0.5226171 + b[2] * 0.31114694);
+    (0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q) / MAX_YIQ_DELTA
+}
+
+fn luma(rgb: [f32; 3]) -> f32 {
+    rgb[0] * 0.2988953 + rgb[1] * 0.5866225 + rgb[2] * 0.11448223
+}
+
+fn blend_with_white(pixel: Rgba<u8>) -> [f32; 3] {
+    let alpha = pixel[3] as f32 / 255.0;
+    let blend = |channel: u8| 255.0 + (channel as f32 - 255.0) * alpha;
+    [blend(pixel[0]), blend(pixel[1]), blend(pixel[2])]
+}
+
+fn save(image: &RgbaImage, path: PathBuf) -> Result<(), GoldenError> {
+    let result = match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).and_then(|_| image.save(&path)),
+        None => image.save(&path),
+    };
+    result.map_err(|error| GoldenError::Io { path, message: error.to_string() })
+}
+
+fn reference_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("images")
+}
+
+fn output_dir() -> PathBuf {
+    match env::var_os("CARGO_TARGET_DIR") {
+        Some(target_dir) => PathBuf::from(target_dir).join("golden"),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/golden"),
+    }
+}
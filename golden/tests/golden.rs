@@ -0,0 +1,155 @@
+// pathfinder/golden/tests/golden.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders each reference scene and compares it against its reference image.
+//!
+//! This runs on the main thread, without the standard test harness, because the OpenGL context
+//! can't be shared between the threads that tests would otherwise run on.
+
+use pathfinder_color::ColorU;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient};
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_golden::{GoldenRenderer, Tolerance};
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{ClipPath, PathObject, Scene};
+use pathfinder_svg::BuiltSVG;
+use std::f32::consts::PI;
+use std::fs;
+use std::process;
+use usvg::Options as UsvgOptions;
+
+// Antialiased edges may change slightly between drivers, even in the software rasterizer.
+const TOLERANCE: Tolerance = Tolerance { color_threshold: 0.1, max_different_pixels: 64 };
+
+const SCENES: &[(&str, fn() -> Scene)] = &[
+    ("pathfinder_logo", pathfinder_logo),
+    ("ghostscript_tiger", ghostscript_tiger),
+    ("translucent_circles", translucent_circles),
+    ("clipped_gradient", clipped_gradient),
+];
+
+fn main() {
+    let mut renderer = GoldenRenderer::new();
+    let mut failure_count = 0;
+    for &(name, build_scene) in SCENES {
+        let image = renderer.render(&build_scene());
+        match pathfinder_golden::check(name, &image, TOLERANCE) {
+            Ok(()) => println!("golden {} ... ok", name),
+            Err(error) => {
+                println!("golden {} ... FAILED: {}", name, error);
+                failure_count += 1;
+            }
+        }
+    }
+
+    println!("\n{} passed; {} failed", SCENES.len() - failure_count, failure_count);
+    if failure_count > 0 {
+        process::exit(1);
+    }
+}
+
+fn pathfinder_logo() -> Scene {
+    load_svg("pathfinder_logo.svg")
+}
+
+fn ghostscript_tiger() -> Scene {
+    load_svg("Ghostscript_Tiger.svg")
+}
+
+fn load_svg(name: &str) -> Scene {
+    let path = format!("{}/../resources/svg/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let data = fs::read(&path).unwrap();
+    BuiltSVG::from_data(&data, &UsvgOptions::default()).unwrap().scene
+}
+
+// Blending of overlapping translucent paths.
+fn translucent_circles() -> Scene {
+    let mut scene = Scene::new();
+    scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(256.0)));
+    let colors = [
+        ColorU { r: 255, g: 0, b: 0, a: 160 },
+        ColorU { r: 0, g: 255, b: 0, a: 160 },
+        ColorU { r: 0, g: 0, b: 255, a: 160 },
+    ];
+    for (circle_index, &color) in colors.iter().enumerate() {
+        let angle = circle_index as f32 / colors.len() as f32 * PI * 2.0;
+        let offset = Vector2F::new(f32::cos(angle), f32::sin(angle)).scale(40.0);
+        let center = Vector2F::splat(128.0) + offset;
+        let paint_id = scene.push_paint(&Paint::Color(color));
+        scene.push_path(PathObject::new(circle(center, 72.0), paint_id, String::new()));
+    }
+    scene
+}
+
+// A radial gradient clipped to a star with the even-odd rule, which leaves a hole in the middle.
+fn clipped_gradient() -> Scene {
+    let mut scene = Scene::new();
+    let view_box = RectF::new(Vector2F::default(), Vector2F::splat(256.0));
+    scene.set_view_box(view_box);
+
+    let mut star = Contour::new();
+    for point_index in 0..5 {
+        let angle = (point_index * 2) as f32 / 5.0 * PI * 2.0 - PI * 0.5;
+        star.push_endpoint(Vector2F::splat(128.0) +
+                           Vector2F::new(f32::cos(angle), f32::sin(angle)).scale(120.0));
+    }
+    star.close();
+    let mut clip_outline = Outline::new();
+    clip_outline.push_contour(star);
+    let clip_path = scene.push_clip_path(ClipPath::new(clip_outline, FillRule::EvenOdd));
+
+    let center = Vector2F::splat(128.0);
+    let mut gradient = Gradient::radial(LineSegment2F::new(center, center), 0.0, 128.0);
+    let (inner_color, outer_color) = (ColorU { r: 255, g: 200, b: 0, a: 255 },
+                                      ColorU { r: 128, g: 0, b: 255, a: 255 });
+    gradient.add_color_stop(ColorStop { color: inner_color, offset: 0.0 });
+    gradient.add_color_stop(ColorStop { color: outer_color, offset: 1.0 });
+    let paint_id = scene.push_paint(&Paint::Gradient(gradient));
+
+    let mut outline = Outline::new();
+    outline.push_contour(rect_contour(view_box));
+    let mut path = PathObject::new(outline, paint_id, String::new());
+    path.set_clip_path(Some(clip_path));
+    scene.push_path(path);
+    scene
+}
+
+fn circle(center: Vector2F, radius: f32) -> Outline {
+    // Four cubics approximate a circle closely enough.
+    const KAPPA: f32 = 0.5522848;
+    let mut contour = Contour::new();
+    contour.push_endpoint(center + Vector2F::new(radius, 0.0));
+    for quadrant in 0..4 {
+        let angle = quadrant as f32 * PI * 0.5;
+        let (from, to) = (Vector2F::new(f32::cos(angle), f32::sin(angle)),
+                          Vector2F::new(f32::cos(angle + PI * 0.5), f32::sin(angle + PI * 0.5)));
+        contour.push_cubic(center + (from + to.scale(KAPPA)).scale(radius),
+                           center + (to + from.scale(KAPPA)).scale(radius),
+                           center + to.scale(radius));
+    }
+    contour.close();
+    let mut outline = Outline::new();
+    outline.push_contour(contour);
+    outline
+}
+
+fn rect_contour(rect: RectF) -> Contour {
+    let mut contour = Contour::new();
+    contour.push_endpoint(rect.origin());
+    contour.push_endpoint(rect.upper_right());
+    contour.push_endpoint(rect.lower_right());
+    contour.push_endpoint(rect.lower_left());
+    contour.close();
+    contour
+}
@@ -61,6 +61,18 @@ pub trait Device: Sized {
                              attr: &Self::VertexAttr,
                              descriptor: &VertexAttrDescriptor);
     fn create_framebuffer(&self, texture: Self::Texture) -> Self::Framebuffer;
+    /// Creates a framebuffer that stores `sample_count` samples per pixel.
+    ///
+    /// Its contents can't be sampled by shaders; copy them into an ordinary framebuffer with
+    /// `resolve_framebuffer()` first. `framebuffer_texture()` still reports its size and format.
+    fn create_multisampled_framebuffer(&self,
+                                       format: TextureFormat,
+                                       size: Vector2I,
+                                       sample_count: u32)
+                                       -> Self::Framebuffer;
+    /// Averages the samples of each pixel of the multisampled framebuffer `source` and writes the
+    /// result into `dest`, which must have the same size and format.
+    fn resolve_framebuffer(&self, source: &Self::Framebuffer, dest: &Self::Framebuffer);
     fn create_buffer(&self) -> Self::Buffer;
     fn allocate_buffer<T>(
         &self,
@@ -70,12 +82,23 @@ pub trait Device: Sized {
         mode: BufferUploadMode,
     );
     fn framebuffer_texture<'f>(&self, framebuffer: &'f Self::Framebuffer) -> &'f Self::Texture;
+    fn texture_format(&self, texture: &Self::Texture) -> TextureFormat;
     fn texture_size(&self, texture: &Self::Texture) -> Vector2I;
     fn upload_to_texture(&self, texture: &Self::Texture, rect: RectI, data: TextureDataRef);
     fn read_pixels(&self, target: &RenderTarget<Self>, viewport: RectI)
                    -> Self::TextureDataReceiver;
+    /// Copies the pixels of `target` inside `rect` into a new texture of the same format, so
+    /// that shaders can read what has been drawn so far.
+    ///
+    /// `rect` is in the same coordinates as `RenderState::viewport`. On Metal, the default target
+    /// must not be framebuffer-only.
+    fn copy_render_target(&self, target: &RenderTarget<Self>, rect: RectI) -> Self::Texture;
     fn begin_commands(&self);
     fn end_commands(&self);
+    /// Clears `target` as `ops` asks, without drawing anything.
+    ///
+    /// If `scissor` is present, only the pixels inside it are cleared.
+    fn clear(&self, target: &RenderTarget<Self>, scissor: Option<RectI>, ops: &ClearOps);
     fn draw_arrays(&self, index_count: u32, render_state: &RenderState<Self>);
     fn draw_elements(&self, index_count: u32, render_state: &RenderState<Self>);
     fn draw_elements_instanced(&self,
@@ -115,6 +138,33 @@ pub trait Device: Sized {
     fn create_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
         self.create_program_from_shader_names(resources, name, name, name)
     }
+
+    /// Whether this device can run compute shaders. If it can't, the other compute methods below
+    /// must not be called.
+    fn supports_compute(&self) -> bool {
+        false
+    }
+
+    /// Creates a program from the compute shader `name`.
+    fn create_compute_program(&self, _: &dyn ResourceLoader, name: &str) -> Self::Program {
+        panic!("Compute program '{}' can't be created: compute is unsupported", name)
+    }
+
+    /// Runs `state.program` over `dimensions` work groups.
+    ///
+    /// The storage buffers are bound in order, starting at binding point 0. Everything the program
+    /// writes is visible to the commands issued afterward.
+    fn dispatch_compute(&self, _dimensions: ComputeDimensions, _state: &ComputeState<Self>) {
+        panic!("Compute is unsupported by this device")
+    }
+
+    /// Reads `length` values of type `T`, starting at value `start`, back out of `buffer`.
+    ///
+    /// This blocks until the commands that write to `buffer` have finished.
+    fn read_buffer<T>(&self, _buffer: &Self::Buffer, _start: usize, _length: usize) -> Vec<T>
+                      where T: Copy {
+        panic!("Reading buffers back is unsupported by this device")
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -124,6 +174,9 @@ pub enum TextureFormat {
     RGBA8,
     RGBA16F,
     RGBA32F,
+    /// Ten bits for each color channel and two for alpha, packed into 32 bits, for deep color
+    /// displays.
+    RGB10A2,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -188,6 +241,22 @@ pub struct RenderState<'a, D> where D: Device {
     pub options: RenderOptions,
 }
 
+/// What a compute program runs with; see `Device::dispatch_compute()`.
+#[derive(Clone)]
+pub struct ComputeState<'a, D> where D: Device {
+    pub program: &'a D::Program,
+    pub uniforms: &'a [(&'a D::Uniform, UniformData)],
+    pub storage_buffers: &'a [&'a D::Buffer],
+}
+
+/// The number of work groups to dispatch along each axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComputeDimensions {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct RenderOptions {
     pub blend: Option<BlendState>,
@@ -195,6 +264,8 @@ pub struct RenderOptions {
     pub stencil: Option<StencilState>,
     pub clear_ops: ClearOps,
     pub color_mask: bool,
+    /// If present, drawing and clearing are restricted to this rectangle of the target.
+    pub scissor: Option<RectI>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -210,17 +281,25 @@ pub enum RenderTarget<'a, D> where D: Device {
     Framebuffer(&'a D::Framebuffer),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct BlendState {
-    pub func: BlendFunc,
+    pub src_rgb_factor: BlendFactor,
+    pub dest_rgb_factor: BlendFactor,
+    pub src_alpha_factor: BlendFactor,
+    pub dest_alpha_factor: BlendFactor,
     pub op: BlendOp,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub enum BlendFunc {
-    RGBOneAlphaOne,
-    RGBOneAlphaOneMinusSrcAlpha,
-    RGBSrcAlphaAlphaOneMinusSrcAlpha,
+pub enum BlendFactor {
+    Zero,
+    One,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DestAlpha,
+    OneMinusDestAlpha,
+    DestColor,
+    OneMinusSrcColor,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -264,14 +343,21 @@ impl Default for RenderOptions {
             stencil: None,
             clear_ops: ClearOps::default(),
             color_mask: true,
+            scissor: None,
         }
     }
 }
 
-impl Default for BlendFunc {
+impl Default for BlendState {
     #[inline]
-    fn default() -> BlendFunc {
-        BlendFunc::RGBOneAlphaOneMinusSrcAlpha
+    fn default() -> BlendState {
+        BlendState {
+            src_rgb_factor: BlendFactor::One,
+            dest_rgb_factor: BlendFactor::OneMinusSrcAlpha,
+            src_alpha_factor: BlendFactor::One,
+            dest_alpha_factor: BlendFactor::One,
+            op: BlendOp::Add,
+        }
     }
 }
 
@@ -314,6 +400,8 @@ pub enum TextureData {
     U16(Vec<u16>),
     F16(Vec<f16>),
     F32(Vec<f32>),
+    /// One packed 32-bit value per pixel, as for `TextureFormat::RGB10A2`.
+    U32(Vec<u32>),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -353,7 +441,10 @@ impl TextureFormat {
     pub fn channels(self) -> usize {
         match self {
             TextureFormat::R8 | TextureFormat::R16F => 1,
-            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => 4,
+            TextureFormat::RGBA8 |
+            TextureFormat::RGBA16F |
+            TextureFormat::RGBA32F |
+            TextureFormat::RGB10A2 => 4,
         }
     }
 
@@ -362,7 +453,7 @@ impl TextureFormat {
         match self {
             TextureFormat::R8 => 1,
             TextureFormat::R16F => 2,
-            TextureFormat::RGBA8 => 4,
+            TextureFormat::RGBA8 | TextureFormat::RGB10A2 => 4,
             TextureFormat::RGBA16F => 8,
             TextureFormat::RGBA32F => 16,
         }
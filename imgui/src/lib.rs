@@ -0,0 +1,255 @@
+// pathfinder/imgui/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Draws Dear ImGui user interfaces as Pathfinder paths.
+//!
+//! ImGui produces meshes of triangles. Each run of triangles with the same paint becomes one path,
+//! and triangles that sample a texture are filled with it as a pattern. Pathfinder antialiases
+//! the paths itself, so ImGui's antialiasing fringes are turned off.
+
+use imgui::{Context, DrawCmd, DrawCmdParams, DrawData, DrawVert, TextureId, Textures};
+use pathfinder_color::ColorU;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, PathObject, Scene};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+const WHITE: ColorU = ColorU { r: 255, g: 255, b: 255, a: 255 };
+
+/// Converts ImGui draw data to paths, keeping the textures that it refers to.
+pub struct ImguiRenderer {
+    textures: Textures<Image>,
+    // Copies of textures multiplied by vertex colors, by texture ID and color.
+    tinted_textures: HashMap<(usize, ColorU), Image>,
+}
+
+impl ImguiRenderer {
+    /// Builds the font atlas of `context` and registers it as a texture. Also turns off ImGui's
+    /// antialiasing, which would otherwise blur the edges that Pathfinder antialiases.
+    pub fn new(context: &mut Context) -> ImguiRenderer {
+        let mut renderer = ImguiRenderer {
+            textures: Textures::new(),
+            tinted_textures: HashMap::new(),
+        };
+
+        {
+            let mut fonts = context.fonts();
+            let font_texture = fonts.build_rgba32_texture();
+            let size = Vector2I::new(font_texture.width as i32, font_texture.height as i32);
+            let font_image = Image::from_rgba8(size, font_texture.data);
+            fonts.tex_id = renderer.textures.insert(font_image);
+        }
+
+        let style = context.style_mut();
+        style.anti_aliased_lines = false;
+        style.anti_aliased_fill = false;
+        renderer
+    }
+
+    /// The textures that draw commands may refer to. Images inserted here can be drawn with
+    /// `imgui::Image` under the returned IDs.
+    #[inline]
+    pub fn textures_mut(&mut self) -> &mut Textures<Image> {
+        &mut self.textures
+    }
+
+    /// Appends the user interface that `draw_data` describes to `scene`, in logical pixels with
+    /// the upper left corner of the ImGui display at the origin.
+    pub fn push_draw_data(&mut self, draw_data: &DrawData, scene: &mut Scene) {
+        let display_origin = Vector2F::new(draw_data.display_pos[0], draw_data.display_pos[1]);
+        let display_size = Vector2F::new(draw_data.display_size[0], draw_data.display_size[1]);
+        let display_rect = RectF::new(Vector2F::default(), display_size);
+
+        for draw_list in draw_data.draw_lists() {
+            let vertices = draw_list.vtx_buffer();
+            let indices = draw_list.idx_buffer();
+            for command in draw_list.commands() {
+                let (count, params) = match command {
+                    DrawCmd::Elements { count, cmd_params } => (count, cmd_params),
+                    // There's no render state to reset, and callbacks need a GPU backend.
+                    DrawCmd::ResetRenderState | DrawCmd::RawCallback { .. } => continue,
+                };
+                let DrawCmdParams { clip_rect, texture_id, vtx_offset, idx_offset } = params;
+
+                // Draw commands that fit the display don't need clipping.
+                let clip_rect = RectF::from_points(Vector2F::new(clip_rect[0], clip_rect[1]),
+                                                   Vector2F::new(clip_rect[2], clip_rect[3]));
+                let clip_rect = RectF::new(clip_rect.origin() - display_origin, clip_rect.size());
+                let clip_path = if clip_rect.contains_rect(display_rect) {
+                    None
+                } else {
+                    let mut clip_outline = Outline::new();
+                    clip_outline.push_contour(polygon(&[clip_rect.origin(),
+                                                        clip_rect.upper_right(),
+                                                        clip_rect.lower_right(),
+                                                        clip_rect.lower_left()]));
+                    Some(scene.push_clip_path(ClipPath::new(clip_outline, FillRule::Winding)))
+                };
+
+                let mut run_paint = None;
+                let mut run_outline = Outline::new();
+                for triangle in indices[idx_offset..(idx_offset + count)].chunks(3) {
+                    if triangle.len() < 3 {
+                        break;
+                    }
+                    let triangle = [vertices[vtx_offset + triangle[0] as usize],
+                                    vertices[vtx_offset + triangle[1] as usize],
+                                    vertices[vtx_offset + triangle[2] as usize]];
+                    let points = [position_of(&triangle[0]) - display_origin,
+                                  position_of(&triangle[1]) - display_origin,
+                                  position_of(&triangle[2]) - display_origin];
+                    let paint = match self.paint_for_triangle(texture_id, &triangle, &points) {
+                        None => continue,
+                        Some(paint) => paint,
+                    };
+
+                    if run_paint.as_ref() != Some(&paint) {
+                        if let Some(run_paint) = run_paint.take() {
+                            let outline = mem::replace(&mut run_outline, Outline::new());
+                            push_path(scene, outline, &run_paint, clip_path);
+                        }
+                        run_paint = Some(paint);
+                    }
+
+                    // Triangles are all wound the same way, so that overlapping ones in the same
+                    // path don't cancel out.
+                    if (points[1] - points[0]).det(points[2] - points[0]) < 0.0 {
+                        run_outline.push_contour(polygon(&[points[0], points[2], points[1]]));
+                    } else {
+                        run_outline.push_contour(polygon(&points));
+                    }
+                }
+                if let Some(run_paint) = run_paint {
+                    push_path(scene, run_outline, &run_paint, clip_path);
+                }
+            }
+        }
+    }
+
+    // Returns the paint for a triangle, or `None` if its texture isn't registered.
+    fn paint_for_triangle(&mut self,
+                          texture_id: TextureId,
+                          triangle: &[DrawVert; 3],
+                          points: &[Vector2F; 3])
+                          -> Option<Paint> {
+        let texture = self.textures.get(texture_id)?;
+
+        // ImGui colors are the same across a triangle when antialiasing is off. Otherwise, they're
+        // averaged.
+        let color = average_color(&[color_of(&triangle[0]),
+                                    color_of(&triangle[1]),
+                                    color_of(&triangle[2])]);
+
+        // Untextured shapes sample a single white texel of the font atlas, as do triangles whose
+        // texture coordinates are degenerate.
+        let texture_size = texture.size().to_f32();
+        let texels = [uv_of(&triangle[0]) * texture_size,
+                      uv_of(&triangle[1]) * texture_size,
+                      uv_of(&triangle[2]) * texture_size];
+        let texel_matrix = Matrix2x2F::row_major(texels[1].x() - texels[0].x(),
+                                                 texels[2].x() - texels[0].x(),
+                                                 texels[1].y() - texels[0].y(),
+                                                 texels[2].y() - texels[0].y());
+        if texel_matrix.det().abs() < 0.0001 {
+            return Some(Paint::Color(tint(texel_at(texture, texels[0]), color)));
+        }
+
+        let point_matrix = Matrix2x2F::row_major(points[1].x() - points[0].x(),
+                                                 points[2].x() - points[0].x(),
+                                                 points[1].y() - points[0].y(),
+                                                 points[2].y() - points[0].y());
+        let matrix = point_matrix * texel_matrix.inverse();
+        let transform = Transform2F { matrix, vector: points[0] - matrix * texels[0] };
+
+        let image = if color == WHITE {
+            texture.clone()
+        } else {
+            let texture_key = (texture_id.id(), color);
+            self.tinted_textures.entry(texture_key).or_insert_with(|| {
+                let pixels = texture.pixels().iter().map(|&pixel| tint(pixel, color)).collect();
+                Image::new(texture.size(), Arc::new(pixels))
+            }).clone()
+        };
+        Some(Paint::Pattern(Pattern::new(image, transform)))
+    }
+}
+
+fn push_path(scene: &mut Scene,
+             outline: Outline,
+             paint: &Paint,
+             clip_path: Option<ClipPathId>) {
+    let paint_id = scene.push_paint(paint);
+    let mut path = PathObject::new(outline, paint_id, String::new());
+    path.set_clip_path(clip_path);
+    scene.push_path(path);
+}
+
+fn polygon(points: &[Vector2F]) -> Contour {
+    let mut contour = Contour::with_capacity(points.len());
+    for &point in points {
+        contour.push_endpoint(point);
+    }
+    contour.close();
+    contour
+}
+
+fn texel_at(image: &Image, texel: Vector2F) -> ColorU {
+    let size = image.size();
+    if size.x() <= 0 || size.y() <= 0 {
+        return WHITE;
+    }
+    let texel = texel.floor().to_i32().max(Vector2I::default()).min(size - Vector2I::splat(1));
+    image.pixels()[(texel.y() * size.x() + texel.x()) as usize]
+}
+
+// Multiplies straight-alpha colors channel by channel, as ImGui's shaders do.
+fn tint(color: ColorU, tint: ColorU) -> ColorU {
+    let mul = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+    ColorU {
+        r: mul(color.r, tint.r),
+        g: mul(color.g, tint.g),
+        b: mul(color.b, tint.b),
+        a: mul(color.a, tint.a),
+    }
+}
+
+fn average_color(colors: &[ColorU; 3]) -> ColorU {
+    let average = |channel: fn(&ColorU) -> u8| {
+        ((colors.iter().map(|color| channel(color) as u32).sum::<u32>() + 1) / 3) as u8
+    };
+    ColorU {
+        r: average(|color| color.r),
+        g: average(|color| color.g),
+        b: average(|color| color.b),
+        a: average(|color| color.a),
+    }
+}
+
+#[inline]
+fn position_of(vertex: &DrawVert) -> Vector2F {
+    Vector2F::new(vertex.pos[0], vertex.pos[1])
+}
+
+#[inline]
+fn uv_of(vertex: &DrawVert) -> Vector2F {
+    Vector2F::new(vertex.uv[0], vertex.uv[1])
+}
+
+#[inline]
+fn color_of(vertex: &DrawVert) -> ColorU {
+    ColorU { r: vertex.col[0], g: vertex.col[1], b: vertex.col[2], a: vertex.col[3] }
+}
@@ -0,0 +1,340 @@
+// pathfinder/lottie/src/animation.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Evaluation of animated properties at a frame.
+
+use crate::{MultidimensionalPropertyValue, OffsetInterpolation, OffsetKeyframe, PropertyValue};
+use crate::{ShapeKeyframeProperty, ShapeProperty, ShapeVertices};
+use pathfinder_geometry::util;
+use pathfinder_geometry::vector::Vector2F;
+
+// Easing curves are solved for time by bisection, to about 2^-20 of the length of the keyframe.
+const EASING_ITERATIONS: u32 = 20;
+
+impl PropertyValue {
+    /// Returns the value at `frame`.
+    pub fn value_at(&self, frame: f32) -> f32 {
+        match *self {
+            PropertyValue::Value { value, .. } => value,
+            PropertyValue::KeyframedValue { ref keyframes, .. } => {
+                components_at(keyframes, frame).first().cloned().unwrap_or(0.0)
+            }
+        }
+    }
+}
+
+impl MultidimensionalPropertyValue {
+    /// Returns the components of the value at `frame`.
+    pub fn value_at(&self, frame: f32) -> Vec<f32> {
+        match *self {
+            MultidimensionalPropertyValue::Value { ref value, .. } => value.clone(),
+            MultidimensionalPropertyValue::KeyframedValue { ref keyframes, .. } => {
+                components_at(keyframes, frame)
+            }
+            MultidimensionalPropertyValue::Split { ref x, ref y } => {
+                vec![x.value_at(frame), y.value_at(frame)]
+            }
+        }
+    }
+
+    /// Returns the first two components of the value at `frame`, or zero for missing ones.
+    pub fn vector_at(&self, frame: f32) -> Vector2F {
+        let value = self.value_at(frame);
+        Vector2F::new(value.first().cloned().unwrap_or(0.0), value.get(1).cloned().unwrap_or(0.0))
+    }
+}
+
+impl ShapeVertices {
+    /// Returns the path at `frame`. Paths with the same number of vertices are morphed into one
+    /// another.
+    pub fn shape_at(&self, frame: f32) -> Option<ShapeProperty> {
+        match *self {
+            ShapeVertices::Shape { ref value, .. } => Some(value.clone()),
+            ShapeVertices::ShapeKeyframed { ref value, .. } => {
+                let segment = KeyframeSegment::find(value, frame)?;
+                let (from, to) = (&segment.from[0], &segment.to[0]);
+                if from.vertices.len() != to.vertices.len() ||
+                        from.in_points.len() != to.in_points.len() ||
+                        from.out_points.len() != to.out_points.len() {
+                    return Some(from.clone());
+                }
+
+                let t = segment.eased_time(0);
+                let lerp_points = |from: &[[f32; 2]], to: &[[f32; 2]]| {
+                    from.iter().zip(to.iter()).map(|(from, to)| {
+                        [from[0] + (to[0] - from[0]) * t, from[1] + (to[1] - from[1]) * t]
+                    }).collect()
+                };
+                Some(ShapeProperty {
+                    closed: from.closed,
+                    in_points: lerp_points(&from.in_points, &to.in_points),
+                    out_points: lerp_points(&from.out_points, &to.out_points),
+                    vertices: lerp_points(&from.vertices, &to.vertices),
+                })
+            }
+        }
+    }
+}
+
+fn components_at(keyframes: &[OffsetKeyframe], frame: f32) -> Vec<f32> {
+    let segment = match KeyframeSegment::find(keyframes, frame) {
+        None => return vec![],
+        Some(segment) => segment,
+    };
+    segment.from.iter().zip(segment.to.iter()).enumerate().map(|(index, (&from, &to))| {
+        from + (to - from) * segment.eased_time(index)
+    }).collect()
+}
+
+trait Keyframe {
+    type Value: ?Sized;
+    fn time(&self) -> f32;
+    fn start(&self) -> Option<&Self::Value>;
+    fn end(&self) -> Option<&Self::Value>;
+    fn is_hold(&self) -> bool;
+    fn out_value(&self) -> Option<&OffsetInterpolation>;
+    fn in_value(&self) -> Option<&OffsetInterpolation>;
+}
+
+// The part of an animation between two keyframes.
+struct KeyframeSegment<'a, K> where K: Keyframe {
+    keyframe: &'a K,
+    from: &'a K::Value,
+    to: &'a K::Value,
+    // How far through the segment the frame is, before easing, from 0 to 1.
+    time: f32,
+}
+
+impl<'a, K> KeyframeSegment<'a, K> where K: Keyframe {
+    // Finds the segment that `frame` falls in. Before the first keyframe and after the last, the
+    // value is held.
+    fn find(keyframes: &'a [K], frame: f32) -> Option<KeyframeSegment<'a, K>> {
+        let index = keyframes.iter().rposition(|keyframe| keyframe.time() <= frame).unwrap_or(0);
+        let keyframe = keyframes.get(index)?;
+
+        // In older files, the last keyframe has no value, and the animation ends at the end
+        // value of the keyframe before it.
+        let from = match keyframe.start() {
+            Some(from) => from,
+            None => {
+                keyframes[..index].iter()
+                                  .rev()
+                                  .find_map(|keyframe| keyframe.end().or(keyframe.start()))?
+            }
+        };
+
+        let next_keyframe = keyframes.get(index + 1);
+        let to = keyframe.end().or_else(|| next_keyframe.and_then(|next| next.start()));
+        let (to, time) = match (next_keyframe, to) {
+            (Some(next_keyframe), Some(to)) if !keyframe.is_hold() &&
+                    next_keyframe.time() > keyframe.time() => {
+                let duration = next_keyframe.time() - keyframe.time();
+                (to, util::clamp((frame - keyframe.time()) / duration, 0.0, 1.0))
+            }
+            _ => (from, 0.0),
+        };
+
+        Some(KeyframeSegment { keyframe, from, to, time })
+    }
+
+    // Returns the time through the segment after easing, for the given component of the value.
+    fn eased_time(&self, component: usize) -> f32 {
+        if self.time <= 0.0 || self.time >= 1.0 {
+            return self.time;
+        }
+        match (self.keyframe.out_value(), self.keyframe.in_value()) {
+            (Some(out_value), Some(in_value)) => {
+                let ctrl0 = control_point(out_value, component);
+                let ctrl1 = control_point(in_value, component);
+                solve_easing(ctrl0, ctrl1, self.time)
+            }
+            _ => self.time,
+        }
+    }
+}
+
+fn control_point(interpolation: &OffsetInterpolation, component: usize) -> Vector2F {
+    let get = |values: &[f32], default| {
+        values.get(component).or(values.last()).cloned().unwrap_or(default)
+    };
+    Vector2F::new(util::clamp(get(&interpolation.x, 0.0), 0.0, 1.0),
+                  get(&interpolation.y, 0.0))
+}
+
+// Evaluates the easing curve, a cubic Bézier curve from (0, 0) to (1, 1) with the given
+// control points, at `time` along the X axis. The X coordinates of the control points lie
+// between 0 and 1, so X increases monotonically along the curve.
+fn solve_easing(ctrl0: Vector2F, ctrl1: Vector2F, time: f32) -> f32 {
+    let evaluate = |from: f32, ctrl0: f32, ctrl1: f32, to: f32, t: f32| {
+        let u = 1.0 - t;
+        u * u * u * from + 3.0 * u * u * t * ctrl0 + 3.0 * u * t * t * ctrl1 + t * t * t * to
+    };
+
+    let (mut min_t, mut max_t) = (0.0, 1.0);
+    for _ in 0..EASING_ITERATIONS {
+        let t = (min_t + max_t) * 0.5;
+        if evaluate(0.0, ctrl0.x(), ctrl1.x(), 1.0, t) < time {
+            min_t = t;
+        } else {
+            max_t = t;
+        }
+    }
+    evaluate(0.0, ctrl0.y(), ctrl1.y(), 1.0, (min_t + max_t) * 0.5)
+}
+
+impl Keyframe for OffsetKeyframe {
+    type Value = [f32];
+
+    #[inline]
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    #[inline]
+    fn start(&self) -> Option<&[f32]> {
+        self.start.as_ref().map(|start| &start[..])
+    }
+
+    #[inline]
+    fn end(&self) -> Option<&[f32]> {
+        self.end.as_ref().map(|end| &end[..])
+    }
+
+    #[inline]
+    fn is_hold(&self) -> bool {
+        self.hold != 0
+    }
+
+    #[inline]
+    fn out_value(&self) -> Option<&OffsetInterpolation> {
+        self.out_value.as_ref()
+    }
+
+    #[inline]
+    fn in_value(&self) -> Option<&OffsetInterpolation> {
+        self.in_value.as_ref()
+    }
+}
+
+impl Keyframe for ShapeKeyframeProperty {
+    type Value = [ShapeProperty];
+
+    #[inline]
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    #[inline]
+    fn start(&self) -> Option<&[ShapeProperty]> {
+        if self.start.is_empty() { None } else { Some(&self.start) }
+    }
+
+    #[inline]
+    fn end(&self) -> Option<&[ShapeProperty]> {
+        if self.end.is_empty() { None } else { Some(&self.end) }
+    }
+
+    #[inline]
+    fn is_hold(&self) -> bool {
+        self.hold != 0
+    }
+
+    #[inline]
+    fn out_value(&self) -> Option<&OffsetInterpolation> {
+        self.out_value.as_ref()
+    }
+
+    #[inline]
+    fn in_value(&self) -> Option<&OffsetInterpolation> {
+        self.in_value.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{MultidimensionalPropertyValue, PropertyValue, ShapeVertices};
+    use pathfinder_geometry::vector::Vector2F;
+
+    const EPSILON: f32 = 0.001;
+
+    fn property(json: &str) -> PropertyValue {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn assert_values(property: &PropertyValue, cases: &[(f32, f32)]) {
+        for &(frame, expected) in cases {
+            let value = property.value_at(frame);
+            assert!((value - expected).abs() < EPSILON, "{}: {} != {}", frame, value, expected);
+        }
+    }
+
+    #[test]
+    fn test_keyframed_values() {
+        // Values are interpolated linearly between keyframes, and held before and after them.
+        let linear = property(r#"{"k":[{"t":10,"s":[0]},{"t":20,"s":[100]},{"t":30,"s":[50]}]}"#);
+        assert_values(&linear, &[(0.0, 0.0), (10.0, 0.0), (15.0, 50.0), (20.0, 100.0),
+                                 (25.0, 75.0), (30.0, 50.0), (40.0, 50.0)]);
+
+        // Older files give the end value in the keyframe, and leave it out of the last one.
+        let old = property(r#"{"k":[{"t":0,"s":[0],"e":[10]},{"t":10}]}"#);
+        assert_values(&old, &[(5.0, 5.0), (10.0, 10.0), (20.0, 10.0)]);
+
+        let hold = property(r#"{"k":[{"t":0,"s":[0],"h":1},{"t":10,"s":[100]}]}"#);
+        assert_values(&hold, &[(5.0, 0.0), (9.9, 0.0), (10.0, 100.0)]);
+
+        assert_values(&property(r#"{"k":7}"#), &[(0.0, 7.0), (100.0, 7.0)]);
+    }
+
+    #[test]
+    fn test_easing() {
+        // Ease in and out: slow at the ends, halfway at the midpoint.
+        let eased = property(concat!(r#"{"k":[{"t":0,"s":[0],"o":{"x":0.5,"y":0},"#,
+                                     r#""i":{"x":0.5,"y":1}},{"t":10,"s":[100]}]}"#));
+        assert_values(&eased, &[(0.0, 0.0), (5.0, 50.0), (10.0, 100.0)]);
+        assert!(eased.value_at(2.0) < 20.0 && eased.value_at(8.0) > 80.0);
+
+        // Control points on the diagonal are linear.
+        let linear = property(concat!(r#"{"k":[{"t":0,"s":[0],"o":{"x":0.25,"y":0.25},"#,
+                                      r#""i":{"x":0.75,"y":0.75}},{"t":10,"s":[100]}]}"#));
+        assert_values(&linear, &[(2.0, 20.0), (7.0, 70.0)]);
+
+        // Each component can have an easing curve of its own.
+        let position: MultidimensionalPropertyValue = serde_json::from_str(concat!(
+            r#"{"k":[{"t":0,"s":[0,0],"o":{"x":[0.25,0.5],"y":[0.25,0]},"#,
+            r#""i":{"x":[0.75,0.5],"y":[0.75,1]}},{"t":10,"s":[100,100]}]}"#)).unwrap();
+        let value = position.vector_at(2.0);
+        assert!((value.x() - 20.0).abs() < EPSILON && value.y() < 20.0, "{:?}", value);
+        assert_eq!(position.vector_at(10.0), Vector2F::splat(100.0));
+    }
+
+    #[test]
+    fn test_shape_morph() {
+        let shape = concat!(r#"{"c":false,"i":[[0,0],[0,0]],"o":[[0,0],[0,0]],"#,
+                            r#""v":[[0,0],[10,0]]}"#);
+        let other = concat!(r#"{"c":false,"i":[[0,0],[0,0]],"o":[[0,0],[0,0]],"#,
+                            r#""v":[[0,10],[30,20]]}"#);
+        let triangle = concat!(r#"{"c":true,"i":[[0,0],[0,0],[0,0]],"o":[[0,0],[0,0],[0,0]],"#,
+                               r#""v":[[0,0],[10,0],[0,10]]}"#);
+        let vertices = |from: &str, to: &str| -> ShapeVertices {
+            let json = format!(r#"{{"k":[{{"t":0,"s":[{}]}},{{"t":10,"s":[{}]}}]}}"#, from, to);
+            serde_json::from_str(&json).unwrap()
+        };
+
+        // Paths with the same vertex count are morphed...
+        let morph = vertices(shape, other);
+        assert_eq!(morph.shape_at(5.0).unwrap().vertices, vec![[0.0, 5.0], [20.0, 10.0]]);
+        assert_eq!(morph.shape_at(20.0).unwrap().vertices, vec![[0.0, 10.0], [30.0, 20.0]]);
+
+        // ...and others jump to the next path at its keyframe.
+        let jump = vertices(shape, triangle);
+        assert_eq!(jump.shape_at(5.0).unwrap().vertices.len(), 2);
+        assert_eq!(jump.shape_at(10.0).unwrap().vertices.len(), 3);
+    }
+}
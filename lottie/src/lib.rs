@@ -9,27 +9,36 @@
 // except according to those terms.
 
 //! Experimental support for Lottie. This is very incomplete.
+//!
+//! `Lottie::build_scene()` draws a frame of an animation: shape layers, with their parenting and
+//! transforms, and their paths, rectangles, and ellipses, morphed between keyframes, filled,
+//! stroked, and trimmed. Precompositions, masks, mattes, effects, and text aren't drawn.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Error as JSONError;
 use std::io::Read;
 
+mod animation;
+mod scene;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Lottie {
     #[serde(rename = "v")]
     pub version: String,
     #[serde(rename = "fr")]
-    pub frame_rate: i64,
+    pub frame_rate: f32,
     #[serde(rename = "ip")]
-    pub in_point: i64,
+    pub in_point: f32,
     #[serde(rename = "op")]
-    pub out_point: i64,
+    pub out_point: f32,
     #[serde(rename = "w")]
     pub width: f64,
     #[serde(rename = "h")]
     pub height: f64,
     #[serde(rename = "ddd")]
+    #[serde(default)]
     pub three_d: i64,
+    #[serde(default)]
     pub assets: Vec<Asset>,
     pub layers: Vec<Layer>,
 }
@@ -37,37 +46,49 @@ pub struct Lottie {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Asset {}
 
-// FIXME(pcwalton): Using an untagged enum is a botch here. There actually is a tag: it's just an
-// integer, which `serde_json` doesn't support natively.
+/// A layer. Only shape layers are drawn, but layers of all types can be parents.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum Layer {
-    Shape {
-        #[serde(rename = "ddd")]
-        three_d: i64,
-        #[serde(rename = "ind")]
-        index: i64,
-        #[serde(rename = "nm")]
-        name: String,
-        #[serde(rename = "ks")]
-        transform: Transform,
-        #[serde(rename = "ao")]
-        auto_orient: i64,
-        #[serde(rename = "ip")]
-        in_point: i64,
-        #[serde(rename = "op")]
-        out_point: i64,
-        #[serde(rename = "st")]
-        start_time: i64,
-        #[serde(rename = "bm")]
-        blend_mode: i64,
-        #[serde(rename = "sr")]
-        stretch: i64,
-        #[serde(rename = "ln")]
-        #[serde(default)]
-        layer_id: Option<String>,
-        shapes: Vec<Shape>,
-    },
+pub struct Layer {
+    #[serde(rename = "ty")]
+    pub layer_type: i64,
+    #[serde(rename = "ddd")]
+    #[serde(default)]
+    pub three_d: i64,
+    #[serde(rename = "ind")]
+    #[serde(default)]
+    pub index: Option<i64>,
+    #[serde(rename = "parent")]
+    #[serde(default)]
+    pub parent: Option<i64>,
+    #[serde(rename = "nm")]
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "hd")]
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(rename = "ks")]
+    pub transform: Transform,
+    #[serde(rename = "ao")]
+    #[serde(default)]
+    pub auto_orient: i64,
+    #[serde(rename = "ip")]
+    pub in_point: f32,
+    #[serde(rename = "op")]
+    pub out_point: f32,
+    #[serde(rename = "st")]
+    #[serde(default)]
+    pub start_time: f32,
+    #[serde(rename = "bm")]
+    #[serde(default)]
+    pub blend_mode: i64,
+    #[serde(rename = "sr")]
+    #[serde(default = "default_stretch")]
+    pub stretch: f32,
+    #[serde(rename = "ln")]
+    #[serde(default)]
+    pub layer_id: Option<String>,
+    #[serde(default)]
+    pub shapes: Vec<Shape>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -79,7 +100,8 @@ pub struct Transform {
     #[serde(rename = "s")]
     pub scale: MultidimensionalPropertyValue,
     #[serde(rename = "r")]
-    pub rotation: PropertyValue,
+    #[serde(default)]
+    pub rotation: Option<PropertyValue>,
     #[serde(rename = "o")]
     #[serde(default)]
     pub opacity: Option<PropertyValue>,
@@ -115,7 +137,7 @@ pub enum PropertyValue {
     },
     KeyframedValue {
         #[serde(rename = "k")]
-        keyframes: Vec<KeyframeValue>,
+        keyframes: Vec<OffsetKeyframe>,
         #[serde(rename = "x")]
         #[serde(default)]
         expression: Option<String>,
@@ -125,43 +147,46 @@ pub enum PropertyValue {
     },
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct KeyframeValue {
-    #[serde(rename = "s")]
-    #[serde(default)]
-    pub start: Option<Vec<f32>>,
-    #[serde(rename = "t")]
-    pub time: i64,
-    #[serde(rename = "i")]
-    #[serde(default)]
-    pub interpolation: Option<OffsetInterpolation>,
-}
-
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Interpolation {
     pub x: f32,
     pub y: f32,
 }
 
+/// A control point of the easing curve between two keyframes, with a coordinate for each
+/// component of the value or one for all of them.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OffsetInterpolation {
+    #[serde(deserialize_with = "deserialize_components")]
     pub x: Vec<f32>,
+    #[serde(deserialize_with = "deserialize_components")]
     pub y: Vec<f32>,
 }
 
+/// A keyframe of a numeric property. The value runs from `start` to `end`, or to the `start` of
+/// the next keyframe if `end` is missing, eased by the curve from `out_value` to `in_value`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OffsetKeyframe {
     #[serde(rename = "s")]
     #[serde(default)]
+    #[serde(deserialize_with = "deserialize_optional_components")]
     pub start: Option<Vec<f32>>,
+    #[serde(rename = "e")]
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_optional_components")]
+    pub end: Option<Vec<f32>>,
     #[serde(rename = "t")]
-    pub time: i64,
+    pub time: f32,
     #[serde(rename = "i")]
     #[serde(default)]
     pub in_value: Option<OffsetInterpolation>,
     #[serde(rename = "o")]
     #[serde(default)]
     pub out_value: Option<OffsetInterpolation>,
+    /// If nonzero, the value jumps to that of the next keyframe instead of easing.
+    #[serde(rename = "h")]
+    #[serde(default)]
+    pub hold: i64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -193,6 +218,13 @@ pub enum MultidimensionalPropertyValue {
         #[serde(default)]
         out_tangent: Option<i64>,
     },
+    /// A position whose X and Y coordinates are animated separately.
+    Split {
+        #[serde(rename = "x")]
+        x: PropertyValue,
+        #[serde(rename = "y")]
+        y: PropertyValue,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -213,6 +245,25 @@ pub enum Shape {
         #[serde(default)]
         direction: Option<i64>,
     },
+    #[serde(rename = "rc")]
+    Rect {
+        /// The center.
+        #[serde(rename = "p")]
+        position: MultidimensionalPropertyValue,
+        #[serde(rename = "s")]
+        size: MultidimensionalPropertyValue,
+        /// The radius of the corners.
+        #[serde(rename = "r")]
+        roundness: PropertyValue,
+    },
+    #[serde(rename = "el")]
+    Ellipse {
+        /// The center.
+        #[serde(rename = "p")]
+        position: MultidimensionalPropertyValue,
+        #[serde(rename = "s")]
+        size: MultidimensionalPropertyValue,
+    },
     #[serde(rename = "fl")]
     Fill {
         #[serde(rename = "nm")]
@@ -223,21 +274,70 @@ pub enum Shape {
         opacity: Option<PropertyValue>,
         #[serde(rename = "c")]
         color: MultidimensionalPropertyValue,
+        /// 1 for the nonzero winding rule, 2 for even-odd.
+        #[serde(rename = "r")]
+        #[serde(default)]
+        fill_rule: Option<i64>,
+    },
+    #[serde(rename = "st")]
+    Stroke {
+        #[serde(rename = "nm")]
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(rename = "o")]
+        #[serde(default)]
+        opacity: Option<PropertyValue>,
+        #[serde(rename = "c")]
+        color: MultidimensionalPropertyValue,
+        #[serde(rename = "w")]
+        width: PropertyValue,
+        /// 1 for butt caps, 2 for round, and 3 for square.
+        #[serde(rename = "lc")]
+        #[serde(default)]
+        line_cap: Option<i64>,
+        /// 1 for miter joins, 2 for round, and 3 for bevel.
+        #[serde(rename = "lj")]
+        #[serde(default)]
+        line_join: Option<i64>,
+        #[serde(rename = "ml")]
+        #[serde(default)]
+        miter_limit: Option<f32>,
+    },
+    /// Trims the paths before it in its group, and in the groups before it, to the part between
+    /// `start` and `end` percent of the way along them, moved along by `offset` degrees.
+    #[serde(rename = "tm")]
+    TrimPath {
+        #[serde(rename = "s")]
+        start: PropertyValue,
+        #[serde(rename = "e")]
+        end: PropertyValue,
+        #[serde(rename = "o")]
+        offset: PropertyValue,
+        /// 1 to trim each path separately, 2 to trim the paths as if they were joined end to end.
+        #[serde(rename = "m")]
+        #[serde(default)]
+        mode: Option<i64>,
     },
     #[serde(rename = "tr")]
     Transform {
         #[serde(rename = "r")]
-        rotation: PropertyValue,
+        #[serde(default)]
+        rotation: Option<PropertyValue>,
         #[serde(rename = "sk")]
-        skew: PropertyValue,
+        #[serde(default)]
+        skew: Option<PropertyValue>,
         #[serde(rename = "sa")]
-        skew_axis: PropertyValue,
+        #[serde(default)]
+        skew_axis: Option<PropertyValue>,
         #[serde(rename = "p")]
         position: MultidimensionalPropertyValue,
         #[serde(rename = "a")]
         anchor_point: MultidimensionalPropertyValue,
         #[serde(rename = "s")]
         scale: MultidimensionalPropertyValue,
+        #[serde(rename = "o")]
+        #[serde(default)]
+        opacity: Option<PropertyValue>,
     },
     #[serde(other)]
     Unimplemented,
@@ -256,6 +356,7 @@ pub enum ShapeVertices {
         #[serde(default)]
         index: Option<i64>,
         #[serde(rename = "a")]
+        #[serde(default)]
         animated: i64,
     },
     ShapeKeyframed {
@@ -268,6 +369,7 @@ pub enum ShapeVertices {
         #[serde(default)]
         index: Option<i64>,
         #[serde(rename = "a")]
+        #[serde(default)]
         animated: i64,
         #[serde(rename = "ti")]
         #[serde(default)]
@@ -290,19 +392,26 @@ pub struct ShapeProperty {
     pub vertices: Vec<[f32; 2]>,
 }
 
+/// A keyframe of a path. `start` and `end` hold the path as their only element.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ShapeKeyframeProperty {
     #[serde(rename = "s")]
     #[serde(default)]
-    pub start: Vec<Option<ShapeProperty>>,
+    pub start: Vec<ShapeProperty>,
+    #[serde(rename = "e")]
+    #[serde(default)]
+    pub end: Vec<ShapeProperty>,
     #[serde(rename = "t")]
-    pub time: i64,
+    pub time: f32,
     #[serde(rename = "i")]
     #[serde(default)]
     pub in_value: Option<OffsetInterpolation>,
     #[serde(rename = "o")]
     #[serde(default)]
     pub out_value: Option<OffsetInterpolation>,
+    #[serde(rename = "h")]
+    #[serde(default)]
+    pub hold: i64,
 }
 
 impl Lottie {
@@ -311,3 +420,36 @@ impl Lottie {
         serde_json::from_reader(reader)
     }
 }
+
+#[inline]
+fn default_stretch() -> f32 {
+    1.0
+}
+
+// Values with one component can be written as a bare number instead of as an array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Components {
+    One(f32),
+    Many(Vec<f32>),
+}
+
+impl Components {
+    #[inline]
+    fn into_vec(self) -> Vec<f32> {
+        match self {
+            Components::One(value) => vec![value],
+            Components::Many(values) => values,
+        }
+    }
+}
+
+fn deserialize_components<'de, D>(deserializer: D) -> Result<Vec<f32>, D::Error>
+                                  where D: Deserializer<'de> {
+    Ok(Components::deserialize(deserializer)?.into_vec())
+}
+
+fn deserialize_optional_components<'de, D>(deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+                                           where D: Deserializer<'de> {
+    Ok(Option::<Components>::deserialize(deserializer)?.map(Components::into_vec))
+}
@@ -0,0 +1,651 @@
+// pathfinder/lottie/src/scene.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Building Pathfinder scenes from the frames of an animation.
+
+use crate::{Layer, Lottie, MultidimensionalPropertyValue, PropertyValue, Shape, ShapeProperty};
+use crate::Transform;
+use pathfinder_color::ColorF;
+use pathfinder_content::dash::OutlineDash;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::util;
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{PathObject, Scene};
+
+const SHAPE_LAYER: i64 = 4;
+
+// The length of a cubic Bézier control polygon that approximates a quarter circle, relative to
+// the radius.
+const KAPPA: f32 = 0.5522847;
+
+// Trimmed paths are measured by flattening them to within this distance.
+const TRIM_TOLERANCE: f32 = 0.1;
+// Trims shorter than this are empty, and trims that leave less than this out are whole.
+const TRIM_EPSILON: f32 = 0.001;
+const DEFAULT_MITER_LIMIT: f32 = 4.0;
+
+impl Lottie {
+    /// Returns the frame shown `time` seconds into the animation, when it loops.
+    pub fn frame_at_time(&self, time: f32) -> f32 {
+        let duration = self.out_point - self.in_point;
+        if duration.is_nan() || duration <= 0.0 {
+            return self.in_point;
+        }
+        let frame = (time * self.frame_rate) % duration;
+        self.in_point + if frame < 0.0 { frame + duration } else { frame }
+    }
+
+    /// Builds a scene that shows the animation at `frame`, with a view box of the size of the
+    /// animation.
+    pub fn build_scene(&self, frame: f32) -> Scene {
+        let mut scene = Scene::new();
+        let size = Vector2F::new(self.width as f32, self.height as f32);
+        scene.set_view_box(RectF::new(Vector2F::default(), size));
+
+        // Layers at the start of the list are drawn on top.
+        for layer in self.layers.iter().rev() {
+            if layer.layer_type != SHAPE_LAYER || layer.hidden || frame < layer.in_point ||
+                    frame >= layer.out_point {
+                continue;
+            }
+
+            let layer_frame = layer.local_frame(frame);
+            let opacity = opacity_at(&layer.transform.opacity, layer_frame);
+            let transform = self.layer_transform(layer, frame);
+            let mut shapes = ShapeGroup::build(&layer.shapes, layer_frame, &[]);
+            shapes.apply(&transform, opacity);
+
+            // Shapes at the start of the list are drawn on top too.
+            for draw in shapes.draws.into_iter().rev() {
+                let color = draw.color.to_u8();
+                if color.is_fully_transparent() || draw.outline.contours().is_empty() {
+                    continue;
+                }
+                let paint = scene.push_paint(&Paint::Color(color));
+                let name = format!("{}({})", layer.name, draw.name);
+                let mut path = PathObject::new(draw.outline, paint, name);
+                path.set_fill_rule(draw.fill_rule);
+                scene.push_path(path);
+            }
+        }
+
+        scene
+    }
+
+    // Returns the transform of `layer`, including those of its parents.
+    fn layer_transform(&self, layer: &Layer, frame: f32) -> Transform2F {
+        let mut transform = layer.transform.matrix_at(layer.local_frame(frame));
+        let mut parent_index = layer.parent;
+
+        // Guard against cycles.
+        for _ in 0..self.layers.len() {
+            let parent = match parent_index.and_then(|index| {
+                self.layers.iter().find(|layer| layer.index == Some(index))
+            }) {
+                None => break,
+                Some(parent) => parent,
+            };
+            transform = parent.transform.matrix_at(parent.local_frame(frame)) * transform;
+            parent_index = parent.parent;
+        }
+
+        transform
+    }
+}
+
+impl Layer {
+    // Maps a frame of the composition to the time of this layer's keyframes.
+    fn local_frame(&self, frame: f32) -> f32 {
+        let stretch = if self.stretch != 0.0 { self.stretch } else { 1.0 };
+        (frame - self.start_time) / stretch
+    }
+}
+
+impl Transform {
+    fn matrix_at(&self, frame: f32) -> Transform2F {
+        // TODO: Skew.
+        transform_matrix(&self.anchor_point,
+                         &self.position,
+                         &self.scale,
+                         &self.rotation,
+                         frame)
+    }
+}
+
+fn transform_matrix(anchor_point: &MultidimensionalPropertyValue,
+                    position: &MultidimensionalPropertyValue,
+                    scale: &MultidimensionalPropertyValue,
+                    rotation: &Option<PropertyValue>,
+                    frame: f32)
+                    -> Transform2F {
+    let rotation = rotation.as_ref().map_or(0.0, |rotation| rotation.value_at(frame));
+    Transform2F::from_translation(position.vector_at(frame)) *
+        Transform2F::from_rotation(rotation.to_radians()) *
+        Transform2F::from_scale(scale.vector_at(frame).scale(0.01)) *
+        Transform2F::from_translation(-anchor_point.vector_at(frame))
+}
+
+// Returns the opacity as a fraction. Lottie files specify it as a percentage.
+fn opacity_at(opacity: &Option<PropertyValue>, frame: f32) -> f32 {
+    opacity.as_ref().map_or(1.0, |opacity| opacity.value_at(frame) * 0.01)
+}
+
+// The output of drawing the items of a group.
+struct ShapeGroup {
+    // The paths in the group, after trimming.
+    paths: Outline,
+    // The fills and strokes in the group, topmost first.
+    draws: Vec<Draw>,
+}
+
+struct Draw {
+    outline: Outline,
+    color: ColorF,
+    fill_rule: FillRule,
+    name: String,
+}
+
+// A path in a group, or the paths of a group within it.
+struct ShapePath {
+    outline: Outline,
+    // The index of the item that drew the path among the items of the group.
+    index: usize,
+    is_group: bool,
+}
+
+// Trims paths, as `Shape::TrimPath` describes.
+#[derive(Clone, Copy)]
+struct Trim {
+    // Fractions of the length of the paths.
+    start: f32,
+    end: f32,
+    offset: f32,
+    // Whether the paths are trimmed as if they were joined end to end.
+    joined: bool,
+}
+
+impl ShapeGroup {
+    // Draws `items` at `frame`. `outer_trims` trim the paths of the group after its own trims.
+    //
+    // A path is modified by the trims after it among the items, and drawn by the fills and
+    // strokes after it. These also apply to the paths of the groups before them.
+    fn build(items: &[Shape], frame: f32, outer_trims: &[Trim]) -> ShapeGroup {
+        let trims: Vec<(usize, Trim)> = items.iter().enumerate().filter_map(|(index, item)| {
+            match *item {
+                Shape::TrimPath { ref start, ref end, ref offset, mode } => {
+                    Some((index, Trim {
+                        start: start.value_at(frame) * 0.01,
+                        end: end.value_at(frame) * 0.01,
+                        offset: offset.value_at(frame) / 360.0,
+                        joined: mode == Some(2),
+                    }))
+                }
+                _ => None,
+            }
+        }).collect();
+
+        let mut paths = vec![];
+        let mut group_draws = vec![];
+        let (mut transform, mut opacity) = (Transform2F::default(), 1.0);
+        for (index, item) in items.iter().enumerate() {
+            let contour = match *item {
+                Shape::Group { ref items, .. } => {
+                    // Trims after the group apply to its paths within it.
+                    let mut group_trims: Vec<Trim> =
+                        trims.iter()
+                             .filter(|&&(trim_index, _)| trim_index > index)
+                             .map(|&(_, trim)| trim)
+                             .collect();
+                    group_trims.extend_from_slice(outer_trims);
+
+                    let group = ShapeGroup::build(items, frame, &group_trims);
+                    paths.push(ShapePath { outline: group.paths, index, is_group: true });
+                    group_draws.push(group.draws);
+                    continue;
+                }
+                Shape::Shape { ref vertices, .. } => {
+                    match vertices.shape_at(frame) {
+                        None => continue,
+                        Some(shape) => shape.to_contour(),
+                    }
+                }
+                Shape::Rect { ref position, ref size, ref roundness } => {
+                    rect_contour(position.vector_at(frame),
+                                 size.vector_at(frame),
+                                 roundness.value_at(frame))
+                }
+                Shape::Ellipse { ref position, ref size } => {
+                    ellipse_contour(position.vector_at(frame), size.vector_at(frame))
+                }
+                Shape::Transform {
+                    ref rotation,
+                    ref position,
+                    ref anchor_point,
+                    ref scale,
+                    opacity: ref group_opacity,
+                    ..
+                } => {
+                    // TODO: Skew.
+                    transform = transform_matrix(anchor_point, position, scale, rotation, frame);
+                    opacity = opacity_at(group_opacity, frame);
+                    continue;
+                }
+                Shape::Fill { .. } | Shape::Stroke { .. } | Shape::TrimPath { .. } |
+                Shape::Unimplemented => continue,
+            };
+
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+            paths.push(ShapePath { outline, index, is_group: false });
+        }
+
+        // TODO: Trims that join paths should join them with the paths of groups.
+        for &(trim_index, trim) in &trims {
+            trim_paths(&mut paths, trim, |path| !path.is_group && path.index < trim_index);
+        }
+        for &trim in outer_trims {
+            trim_paths(&mut paths, trim, |path| !path.is_group);
+        }
+
+        let mut draws = vec![];
+        let mut group_draws = group_draws.into_iter();
+        for (index, item) in items.iter().enumerate() {
+            match *item {
+                Shape::Group { .. } => draws.extend(group_draws.next().unwrap_or_default()),
+                Shape::Fill { ref name, ref opacity, ref color, fill_rule } => {
+                    draws.push(Draw {
+                        outline: paths_before(&paths, index),
+                        color: color_at(color, opacity, frame),
+                        fill_rule: if fill_rule == Some(2) {
+                            FillRule::EvenOdd
+                        } else {
+                            FillRule::Winding
+                        },
+                        name: name.clone().unwrap_or_else(|| "Fill".to_owned()),
+                    });
+                }
+                Shape::Stroke {
+                    ref name,
+                    ref opacity,
+                    ref color,
+                    ref width,
+                    line_cap,
+                    line_join,
+                    miter_limit,
+                } => {
+                    let style = StrokeStyle {
+                        line_width: width.value_at(frame),
+                        line_cap: match line_cap {
+                            Some(2) => LineCap::Round,
+                            Some(3) => LineCap::Square,
+                            _ => LineCap::Butt,
+                        },
+                        line_join: match line_join {
+                            Some(2) => LineJoin::Round,
+                            Some(3) => LineJoin::Bevel,
+                            _ => LineJoin::Miter(miter_limit.unwrap_or(DEFAULT_MITER_LIMIT)),
+                        },
+                    };
+                    let outline = paths_before(&paths, index);
+                    let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+                    stroke_to_fill.offset();
+                    draws.push(Draw {
+                        outline: stroke_to_fill.into_outline(),
+                        color: color_at(color, opacity, frame),
+                        fill_rule: FillRule::Winding,
+                        name: name.clone().unwrap_or_else(|| "Stroke".to_owned()),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut group = ShapeGroup { paths: paths_before(&paths, items.len()), draws };
+        group.apply(&transform, opacity);
+        group
+    }
+
+    fn apply(&mut self, transform: &Transform2F, opacity: f32) {
+        self.paths.transform(transform);
+        for draw in &mut self.draws {
+            draw.outline.transform(transform);
+            let color = draw.color;
+            draw.color = ColorF::new(color.r(), color.g(), color.b(), color.a() * opacity);
+        }
+    }
+}
+
+// Returns the paths drawn by the items before `index`.
+fn paths_before(paths: &[ShapePath], index: usize) -> Outline {
+    let mut outline = Outline::new();
+    for path in paths.iter().filter(|path| path.index < index) {
+        for contour in path.outline.contours() {
+            outline.push_contour(contour.clone());
+        }
+    }
+    outline
+}
+
+fn trim_paths<F>(paths: &mut [ShapePath], trim: Trim, is_trimmed: F)
+                 where F: Fn(&ShapePath) -> bool {
+    let intervals = trim.intervals();
+    let lengths: Vec<Option<f32>> = paths.iter().map(|path| {
+        if is_trimmed(path) { Some(path.outline.length(TRIM_TOLERANCE)) } else { None }
+    }).collect();
+    let total_length: f32 = lengths.iter().filter_map(|&length| length).sum();
+
+    let mut path_start = 0.0;
+    for (path, length) in paths.iter_mut().zip(lengths) {
+        let length = match length {
+            None => continue,
+            Some(length) => length,
+        };
+
+        // Find the parts of the path to keep, as distances along it.
+        let (scale, origin) = if trim.joined { (total_length, path_start) } else { (length, 0.0) };
+        let mut outline = Outline::new();
+        for &(from, to) in &intervals {
+            let from = f32::max(from * scale - origin, 0.0);
+            let to = f32::min(to * scale - origin, length);
+            for contour in outline_between(&path.outline, length, from, to).contours() {
+                outline.push_contour(contour.clone());
+            }
+        }
+
+        path.outline = outline;
+        path_start += length;
+    }
+}
+
+// Returns the part of `outline`, whose contours are treated as joined end to end, between the
+// distances `from` and `to` along it.
+fn outline_between(outline: &Outline, length: f32, from: f32, to: f32) -> Outline {
+    if to - from < TRIM_EPSILON {
+        return Outline::new();
+    }
+    if from < TRIM_EPSILON && to > length - TRIM_EPSILON {
+        return outline.clone();
+    }
+    // Leave the rest of the outline out, even where the dasher measures it as slightly longer.
+    let dashes = [to - from, length * 2.0];
+    let mut dash = OutlineDash::new(outline, &dashes, -from);
+    dash.dash();
+    dash.into_outline()
+}
+
+impl Trim {
+    // Returns the parts of the paths to keep, as fractions of their length.
+    fn intervals(&self) -> Vec<(f32, f32)> {
+        let start = util::clamp(f32::min(self.start, self.end), 0.0, 1.0);
+        let end = util::clamp(f32::max(self.start, self.end), 0.0, 1.0);
+        let length = end - start;
+        if length >= 1.0 {
+            return vec![(0.0, 1.0)];
+        }
+        if length.is_nan() || length <= 0.0 || !self.offset.is_finite() {
+            return vec![];
+        }
+
+        // The offset can move the part to keep across the ends of the paths.
+        let from = (start + self.offset).rem_euclid(1.0);
+        let to = from + length;
+        if to <= 1.0 {
+            vec![(from, to)]
+        } else {
+            vec![(from, 1.0), (0.0, to - 1.0)]
+        }
+    }
+}
+
+fn color_at(color: &MultidimensionalPropertyValue, opacity: &Option<PropertyValue>, frame: f32)
+            -> ColorF {
+    let color = color.value_at(frame);
+    let component = |index: usize, default| {
+        util::clamp(color.get(index).cloned().unwrap_or(default), 0.0, 1.0)
+    };
+    let alpha = util::clamp(opacity_at(opacity, frame), 0.0, 1.0);
+    ColorF::new(component(0, 0.0), component(1, 0.0), component(2, 0.0), component(3, 1.0) * alpha)
+}
+
+impl ShapeProperty {
+    // The tangents are relative to their vertices.
+    fn to_contour(&self) -> Contour {
+        let mut contour = Contour::new();
+        let point = |points: &[[f32; 2]], index: usize| {
+            points.get(index).map_or(Vector2F::default(), |point| Vector2F::new(point[0], point[1]))
+        };
+
+        let vertex_count = self.vertices.len();
+        if vertex_count == 0 {
+            return contour;
+        }
+        contour.push_endpoint(point(&self.vertices, 0));
+
+        let segment_count = if self.closed { vertex_count } else { vertex_count - 1 };
+        for from_index in 0..segment_count {
+            let to_index = (from_index + 1) % vertex_count;
+            let (from, to) = (point(&self.vertices, from_index), point(&self.vertices, to_index));
+            let ctrl0 = from + point(&self.out_points, from_index);
+            let ctrl1 = to + point(&self.in_points, to_index);
+            if ctrl0 == from && ctrl1 == to {
+                contour.push_endpoint(to);
+            } else {
+                contour.push_cubic(ctrl0, ctrl1, to);
+            }
+        }
+
+        if self.closed {
+            contour.close();
+        }
+        contour
+    }
+}
+
+// Lottie rectangles start at the top right corner and run clockwise.
+fn rect_contour(center: Vector2F, size: Vector2F, roundness: f32) -> Contour {
+    let rect = RectF::new(center - size.scale(0.5), size);
+    let radius = f32::max(0.0, f32::min(roundness, f32::min(size.x(), size.y()) * 0.5));
+    let (dx, dy) = (Vector2F::new(radius, 0.0), Vector2F::new(0.0, radius));
+
+    let mut contour = Contour::new();
+    contour.push_endpoint(rect.upper_right() + dy);
+    contour.push_endpoint(rect.lower_right() - dy);
+    push_rounded_corner(&mut contour, rect.lower_right() - dy, rect.lower_right(),
+                        rect.lower_right() - dx);
+    contour.push_endpoint(rect.lower_left() + dx);
+    push_rounded_corner(&mut contour, rect.lower_left() + dx, rect.lower_left(),
+                        rect.lower_left() - dy);
+    contour.push_endpoint(rect.origin() + dy);
+    push_rounded_corner(&mut contour, rect.origin() + dy, rect.origin(), rect.origin() + dx);
+    contour.push_endpoint(rect.upper_right() - dx);
+    push_rounded_corner(&mut contour, rect.upper_right() - dx, rect.upper_right(),
+                        rect.upper_right() + dy);
+    contour.close();
+    contour
+}
+
+// Lottie ellipses start at the top and run clockwise.
+fn ellipse_contour(center: Vector2F, size: Vector2F) -> Contour {
+    let rect = RectF::new(center - size.scale(0.5), size);
+    let (top, right) = (Vector2F::new(center.x(), rect.min_y()),
+                        Vector2F::new(rect.max_x(), center.y()));
+    let (bottom, left) = (Vector2F::new(center.x(), rect.max_y()),
+                          Vector2F::new(rect.min_x(), center.y()));
+
+    let mut contour = Contour::new();
+    contour.push_endpoint(top);
+    push_rounded_corner(&mut contour, top, rect.upper_right(), right);
+    push_rounded_corner(&mut contour, right, rect.lower_right(), bottom);
+    push_rounded_corner(&mut contour, bottom, rect.lower_left(), left);
+    push_rounded_corner(&mut contour, left, rect.origin(), top);
+    contour.close();
+    contour
+}
+
+// Pushes a quarter ellipse from `from`, the last point of `contour`, to `to`, inscribed in the
+// corner at `corner`. Corners with no radius become points.
+fn push_rounded_corner(contour: &mut Contour, from: Vector2F, corner: Vector2F, to: Vector2F) {
+    if from == corner && to == corner {
+        return;
+    }
+    let ctrl0 = from + (corner - from).scale(KAPPA);
+    let ctrl1 = to + (corner - to).scale(KAPPA);
+    contour.push_cubic(ctrl0, ctrl1, to);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Lottie;
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::Vector2F;
+    use pathfinder_renderer::paint::Paint;
+
+    const EPSILON: f32 = 0.01;
+
+    // A red 20×40 rectangle centered at (50, 50).
+    const RECT: &str = concat!(r#"{"ty":"rc","p":{"k":[50,50]},"s":{"k":[20,40]},"r":{"k":0}},"#,
+                               r#"{"ty":"fl","c":{"k":[1,0,0,1]},"o":{"k":100}}"#);
+
+    // A 100×100 animation of 60 frames at 30 frames per second.
+    fn animation(layers: &[String]) -> Lottie {
+        let json = format!(concat!(r#"{{"v":"5.5.2","fr":30,"ip":0,"op":60,"w":100,"h":100,"#,
+                                   r#""layers":[{}]}}"#),
+                           layers.join(","));
+        Lottie::from_reader(json.as_bytes()).unwrap()
+    }
+
+    // A shape layer with the given name, extra fields, and shapes, and no transform of its own.
+    fn shape_layer(name: &str, fields: &str, shapes: &str) -> String {
+        format!(concat!(r#"{{"ty":4,"nm":"{}","ip":0,"op":60,{}"ks":{{"p":{{"k":[0,0]}},"#,
+                        r#""a":{{"k":[0,0]}},"s":{{"k":[100,100]}}}},"shapes":[{}]}}"#),
+                name,
+                fields,
+                shapes)
+    }
+
+    // The names, colors, and bounds of the paths of the scene at `frame`.
+    fn paths(lottie: &Lottie, frame: f32) -> Vec<(String, ColorU, RectF)> {
+        lottie.build_scene(frame).paths().map(|(paint, outline, name, _)| {
+            let color = match *paint {
+                Paint::Color(color) => color,
+                _ => panic!("expected a color"),
+            };
+            (name.to_owned(), color, outline.bounds())
+        }).collect()
+    }
+
+    fn assert_rect_eq(actual: RectF, expected: RectF) {
+        assert!((actual.origin() - expected.origin()).length() < EPSILON &&
+                (actual.lower_right() - expected.lower_right()).length() < EPSILON,
+                "{:?} != {:?}",
+                actual,
+                expected);
+    }
+
+    fn rect(origin: (f32, f32), size: (f32, f32)) -> RectF {
+        RectF::new(Vector2F::new(origin.0, origin.1), Vector2F::new(size.0, size.1))
+    }
+
+    #[test]
+    fn test_fill() {
+        let paths = paths(&animation(&[shape_layer("Layer", "", RECT)]), 0.0);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].0, "Layer(Fill)");
+        assert_eq!(paths[0].1, ColorU { r: 255, g: 0, b: 0, a: 255 });
+        assert_rect_eq(paths[0].2, rect((40.0, 30.0), (20.0, 40.0)));
+    }
+
+    #[test]
+    fn test_layer_transforms() {
+        // A null layer moves its child, which doubles the rectangle's size.
+        let parent = concat!(r#"{"ty":3,"ind":1,"ip":0,"op":60,"ks":{"p":{"k":[10,0]},"#,
+                             r#""a":{"k":[0,0]},"s":{"k":[100,100]}}}"#);
+        let child = shape_layer("Layer", r#""parent":1,"#, RECT)
+            .replace(r#""s":{"k":[100,100]}}"#, r#""s":{"k":[200,200]}}"#);
+        let paths = paths(&animation(&[parent.to_owned(), child]), 0.0);
+        assert_eq!(paths.len(), 1);
+        assert_rect_eq(paths[0].2, rect((90.0, 60.0), (40.0, 80.0)));
+    }
+
+    #[test]
+    fn test_layer_order_and_timing() {
+        let blue = RECT.replace("[1,0,0,1]", "[0,0,1,1]");
+        let lottie = animation(&[shape_layer("Top", "", &blue).replace(r#""ip":0"#, r#""ip":30"#),
+                                 shape_layer("Bottom", "", RECT),
+                                 shape_layer("Hidden", r#""hd":true,"#, RECT)]);
+
+        // Layers are drawn from the end of the list, while the frame is within their in and out
+        // points.
+        let names = |frame| -> Vec<String> {
+            paths(&lottie, frame).into_iter().map(|(name, _, _)| name).collect()
+        };
+        assert_eq!(names(0.0), vec!["Bottom(Fill)"]);
+        assert_eq!(names(30.0), vec!["Bottom(Fill)", "Top(Fill)"]);
+        assert!(names(60.0).is_empty());
+    }
+
+    #[test]
+    fn test_animated_shapes() {
+        // The rectangle moves right and grows, over 10 frames.
+        let animated = RECT.replace(r#""p":{"k":[50,50]}"#,
+                                    r#""p":{"k":[{"t":0,"s":[50,50]},{"t":10,"s":[70,50]}]}"#)
+                           .replace(r#""s":{"k":[20,40]}"#,
+                                    r#""s":{"k":[{"t":0,"s":[20,40]},{"t":10,"s":[40,40]}]}"#);
+        let lottie = animation(&[shape_layer("Layer", "", &animated)]);
+        assert_rect_eq(paths(&lottie, 5.0)[0].2, rect((45.0, 30.0), (30.0, 40.0)));
+        assert_rect_eq(paths(&lottie, 10.0)[0].2, rect((50.0, 30.0), (40.0, 40.0)));
+
+        // Layers that start late see their keyframes later.
+        let lottie = animation(&[shape_layer("Layer", r#""st":10,"#, &animated)]);
+        assert_rect_eq(paths(&lottie, 5.0)[0].2, rect((40.0, 30.0), (20.0, 40.0)));
+        assert_rect_eq(paths(&lottie, 15.0)[0].2, rect((45.0, 30.0), (30.0, 40.0)));
+    }
+
+    #[test]
+    fn test_strokes_and_trims() {
+        // A horizontal line, stroked 2 units wide with butt caps.
+        let line = concat!(r#"{"ty":"sh","ks":{"k":{"c":false,"i":[[0,0],[0,0]],"#,
+                           r#""o":[[0,0],[0,0]],"v":[[0,50],[100,50]]}}}"#);
+        let stroke = r#"{"ty":"st","c":{"k":[0,0,0,1]},"o":{"k":100},"w":{"k":2},"lc":1}"#;
+        let trimmed = |start: f32, end: f32, offset: f32| {
+            let trim = format!(r#"{{"ty":"tm","s":{{"k":{}}},"e":{{"k":{}}},"o":{{"k":{}}}}}"#,
+                               start,
+                               end,
+                               offset);
+            let shapes = format!("{},{},{}", line, trim, stroke);
+            paths(&animation(&[shape_layer("Layer", "", &shapes)]), 0.0)
+        };
+
+        let paths = trimmed(0.0, 100.0, 0.0);
+        assert_eq!(paths[0].0, "Layer(Stroke)");
+        assert_rect_eq(paths[0].2, rect((0.0, 49.0), (100.0, 2.0)));
+        assert_rect_eq(trimmed(0.0, 50.0, 0.0)[0].2, rect((0.0, 49.0), (50.0, 2.0)));
+
+        // The offset is in degrees of a full turn around the path.
+        assert_rect_eq(trimmed(0.0, 50.0, 90.0)[0].2, rect((25.0, 49.0), (50.0, 2.0)));
+
+        // Trims that leave nothing draw nothing.
+        assert!(trimmed(30.0, 30.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_frame_at_time() {
+        let lottie = animation(&[]);
+        for &(time, frame) in &[(0.0, 0.0), (1.0, 30.0), (2.5, 15.0), (-0.5, 45.0)] {
+            assert!((lottie.frame_at_time(time) - frame).abs() < EPSILON, "{}", time);
+        }
+    }
+}
@@ -24,14 +24,15 @@ use core_foundation::base::TCFType;
 use core_foundation::string::{CFString, CFStringRef};
 use foreign_types::{ForeignType, ForeignTypeRef};
 use half::f16;
-use metal::{self, Argument, ArgumentEncoder, Buffer, CommandBuffer, CommandBufferRef};
+use metal::{self, Argument, ArgumentEncoder, Buffer, BufferRef, CommandBuffer, CommandBufferRef};
 use metal::{CommandQueue, CompileOptions, CoreAnimationDrawable, CoreAnimationDrawableRef};
 use metal::{CoreAnimationLayer, CoreAnimationLayerRef, DepthStencilDescriptor, Function, Library};
 use metal::{MTLArgument, MTLArgumentEncoder, MTLBlendFactor, MTLBlendOperation, MTLClearColor};
 use metal::{MTLColorWriteMask, MTLCompareFunction, MTLDataType, MTLDevice, MTLFunctionType};
 use metal::{MTLIndexType, MTLLoadAction, MTLOrigin, MTLPixelFormat, MTLPrimitiveType, MTLRegion};
 use metal::{MTLRenderPipelineReflection, MTLRenderPipelineState, MTLResourceOptions};
-use metal::{MTLResourceUsage, MTLSamplerAddressMode, MTLSamplerMinMagFilter, MTLSize};
+use metal::{MTLResourceUsage, MTLSamplerAddressMode, MTLSamplerMinMagFilter, MTLScissorRect};
+use metal::MTLSize;
 use metal::{MTLStencilOperation, MTLStorageMode, MTLStoreAction, MTLTextureType, MTLTextureUsage};
 use metal::{MTLVertexFormat, MTLVertexStepFunction, MTLViewport, RenderCommandEncoder};
 use metal::{RenderCommandEncoderRef, RenderPassDescriptor, RenderPassDescriptorRef};
@@ -44,7 +45,8 @@ use objc::runtime::{Class, Object};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendOp, BufferData, BufferTarget, BufferUploadMode, DepthFunc};
+use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
+use pathfinder_gpu::DepthFunc;
 use pathfinder_gpu::{Device, Primitive, RenderState, RenderTarget, ShaderKind, StencilFunc};
 use pathfinder_gpu::{TextureData, TextureDataRef, TextureFormat, UniformData, VertexAttrClass};
 use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
@@ -59,17 +61,61 @@ use std::time::{Duration, Instant};
 
 const FIRST_VERTEX_BUFFER_INDEX: u64 = 1;
 
+// The number of frames that the GPU can be working on while the CPU encodes the next one. Each
+// has its own upload buffers, which are reused only once the GPU has finished the frame that last
+// wrote to them.
+const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+// Dynamic buffers, uniforms, and argument buffers are suballocated from upload buffers of this
+// size. Larger allocations get buffers of their own.
+const UPLOAD_BUFFER_SIZE: u64 = 4 * 1024 * 1024;
+// Offsets of buffers in the constant address space must be multiples of 256 on macOS.
+const UPLOAD_BUFFER_ALIGNMENT: u64 = 256;
+
+// Draws a quad over the whole target at the given depth, in the given color. Clears restricted to
+// a scissor rect use this, since load actions always clear the whole attachment.
+const CLEAR_SHADER_SOURCE: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct ClearVertexOutput {
+    float4 position [[position]];
+};
+
+vertex ClearVertexOutput clearVertex(uint vertexID [[vertex_id]],
+                                     constant float &depth [[buffer(0)]]) {
+    float2 position = float2(float(vertexID & 1), float(vertexID >> 1)) * 2.0 - 1.0;
+    ClearVertexOutput output;
+    output.position = float4(position, depth, 1.0);
+    return output;
+}
+
+fragment float4 clearFragment(constant float4 &color [[buffer(0)]]) {
+    return color;
+}
+"#;
+
 pub struct MetalDevice {
     device: metal::Device,
     layer: CoreAnimationLayer,
     drawable: CoreAnimationDrawable,
     main_depth_stencil_texture: Texture,
+    clear_vertex_function: Function,
+    clear_fragment_function: Function,
     command_queue: CommandQueue,
     command_buffers: RefCell<Vec<CommandBuffer>>,
     sampler: SamplerState,
     shared_event: SharedEvent,
     shared_event_listener: SharedEventListener,
     next_timer_query_event_value: Cell<u64>,
+
+    // The upload buffers of each frame in flight.
+    upload_heaps: RefCell<Vec<UploadHeap>>,
+    // The number of frames ended so far, which is also the index of the one being encoded.
+    frame_index: Cell<u64>,
+    // Signaled with the number of frames the GPU has finished.
+    frame_event: SharedEvent,
+    frame_fence: Arc<FrameFence>,
 }
 
 pub struct MetalProgram {
@@ -79,7 +125,24 @@ pub struct MetalProgram {
 
 #[derive(Clone)]
 pub struct MetalBuffer {
-    buffer: Rc<RefCell<Option<Buffer>>>,
+    buffer: Rc<RefCell<Option<BufferSlice>>>,
+}
+
+// A range of a Metal buffer. Static buffers have a Metal buffer to themselves, while dynamic ones
+// are suballocated from the upload buffers of the frame they're written in.
+struct BufferSlice {
+    buffer: Buffer,
+    offset: u64,
+    size: u64,
+}
+
+// Upload buffers that data is allocated from linearly during a frame and freed all at once when
+// the frame is reused.
+struct UploadHeap {
+    buffers: Vec<Buffer>,
+    // The buffer that's being allocated from, and the first free byte in it.
+    current_buffer_index: usize,
+    next_offset: u64,
 }
 
 impl MetalDevice {
@@ -104,19 +167,32 @@ impl MetalDevice {
                                              main_color_texture.height() as i32);
         let main_depth_stencil_texture = device.create_depth_stencil_texture(framebuffer_size);
 
+        let clear_library = device.new_library_with_source(CLEAR_SHADER_SOURCE,
+                                                           &CompileOptions::new()).unwrap();
+        let clear_vertex_function = clear_library.get_function("clearVertex", None).unwrap();
+        let clear_fragment_function = clear_library.get_function("clearFragment", None).unwrap();
+
         let shared_event = device.new_shared_event();
+        let frame_event = device.new_shared_event();
 
         MetalDevice {
             device,
             layer,
             drawable,
             main_depth_stencil_texture,
+            clear_vertex_function,
+            clear_fragment_function,
             command_queue,
             command_buffers: RefCell::new(vec![]),
             sampler,
             shared_event,
             shared_event_listener: SharedEventListener::new(),
             next_timer_query_event_value: Cell::new(1),
+            upload_heaps: RefCell::new((0..MAX_FRAMES_IN_FLIGHT).map(|_| UploadHeap::new())
+                                                                .collect()),
+            frame_index: Cell::new(0),
+            frame_event,
+            frame_fence: Arc::new(FrameFence::new()),
         }
     }
 
@@ -124,8 +200,63 @@ impl MetalDevice {
         self.begin_commands();
         self.command_buffers.borrow_mut().last().unwrap().present_drawable(&self.drawable);
         self.end_commands();
+        self.end_frame();
         self.drawable = self.layer.next_drawable().unwrap().retain();
     }
+
+    /// Ends the current frame, so that the data uploaded during it can be overwritten once the GPU
+    /// has finished drawing it.
+    ///
+    /// `present_drawable()` calls this. Applications that draw offscreen without presenting should
+    /// call it once per frame instead, or dynamic buffers will use more and more memory. This
+    /// blocks while the GPU is still working on the frame three frames back.
+    pub fn end_frame(&self) {
+        let frame_count = self.frame_index.get() + 1;
+        let captured_frame_fence = self.frame_fence.clone();
+        let block = ConcreteBlock::new(move |_: *mut Object, frame_count: u64| {
+            captured_frame_fence.signal(frame_count);
+        });
+        self.frame_event.notify_listener_at_value(&self.shared_event_listener,
+                                                  frame_count,
+                                                  block.copy());
+
+        self.begin_commands();
+        self.command_buffers
+            .borrow_mut()
+            .last()
+            .unwrap()
+            .encode_signal_event(&self.frame_event, frame_count);
+        self.end_commands();
+
+        // The next frame writes to the upload buffers of the frame `MAX_FRAMES_IN_FLIGHT` before
+        // it, so wait for the GPU to finish that one.
+        self.frame_index.set(frame_count);
+        if frame_count >= MAX_FRAMES_IN_FLIGHT as u64 {
+            self.frame_fence.wait(frame_count + 1 - MAX_FRAMES_IN_FLIGHT as u64);
+        }
+        let heap_index = frame_count as usize % MAX_FRAMES_IN_FLIGHT;
+        self.upload_heaps.borrow_mut()[heap_index].reset();
+    }
+
+    // Allocates `size` bytes from the upload buffers of the current frame, without initializing
+    // them.
+    fn allocate_upload(&self, size: u64) -> BufferSlice {
+        let heap_index = self.frame_index.get() as usize % MAX_FRAMES_IN_FLIGHT;
+        self.upload_heaps.borrow_mut()[heap_index].allocate(&self.device, size)
+    }
+
+    // Copies `data` into the upload buffers of the current frame.
+    fn upload(&self, data: &[u8]) -> BufferSlice {
+        let buffer_slice = self.allocate_upload(data.len() as u64);
+        unsafe {
+            let contents = buffer_slice.buffer.contents() as *mut u8;
+            ptr::copy_nonoverlapping(data.as_ptr(),
+                                     contents.offset(buffer_slice.offset as isize),
+                                     data.len());
+        }
+        buffer_slice.buffer.did_modify_range(NSRange::new(buffer_slice.offset, buffer_slice.size));
+        buffer_slice
+    }
 }
 
 pub struct MetalFramebuffer(MetalTexture);
@@ -218,13 +349,7 @@ impl Device for MetalDevice {
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> MetalTexture {
         let descriptor = TextureDescriptor::new();
         descriptor.set_texture_type(MTLTextureType::D2);
-        match format {
-            TextureFormat::R8 => descriptor.set_pixel_format(MTLPixelFormat::R8Unorm),
-            TextureFormat::R16F => descriptor.set_pixel_format(MTLPixelFormat::R16Float),
-            TextureFormat::RGBA8 => descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm),
-            TextureFormat::RGBA16F => descriptor.set_pixel_format(MTLPixelFormat::RGBA16Float),
-            TextureFormat::RGBA32F => descriptor.set_pixel_format(MTLPixelFormat::RGBA32Float),
-        }
+        descriptor.set_pixel_format(format.metal_pixel_format());
         descriptor.set_width(size.x() as u64);
         descriptor.set_height(size.y() as u64);
         descriptor.set_storage_mode(MTLStorageMode::Managed);
@@ -402,6 +527,37 @@ impl Device for MetalDevice {
         MetalFramebuffer(texture)
     }
 
+    fn create_multisampled_framebuffer(&self,
+                                       format: TextureFormat,
+                                       size: Vector2I,
+                                       sample_count: u32)
+                                       -> MetalFramebuffer {
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_texture_type(MTLTextureType::D2Multisample);
+        descriptor.set_pixel_format(format.metal_pixel_format());
+        descriptor.set_width(size.x() as u64);
+        descriptor.set_height(size.y() as u64);
+        descriptor.set_sample_count(sample_count as u64);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget);
+        let texture = self.device.new_texture(&descriptor);
+        MetalFramebuffer(MetalTexture { texture, dirty: Cell::new(false) })
+    }
+
+    fn resolve_framebuffer(&self, source: &MetalFramebuffer, dest: &MetalFramebuffer) {
+        let render_pass_descriptor = RenderPassDescriptor::new();
+        let color_attachment = render_pass_descriptor.color_attachments().object_at(0).unwrap();
+        color_attachment.set_texture(Some(&source.0.texture));
+        color_attachment.set_resolve_texture(Some(&dest.0.texture));
+        color_attachment.set_load_action(MTLLoadAction::Load);
+        color_attachment.set_store_action(MTLStoreAction::MultisampleResolve);
+
+        // An empty pass resolves the samples when it ends.
+        let command_buffers = self.command_buffers.borrow();
+        let command_buffer = command_buffers.last().unwrap();
+        command_buffer.new_render_command_encoder(&render_pass_descriptor).end_encoding();
+    }
+
     fn create_buffer(&self) -> MetalBuffer {
         MetalBuffer { buffer: Rc::new(RefCell::new(None)) }
     }
@@ -411,32 +567,46 @@ impl Device for MetalDevice {
                           data: BufferData<T>,
                           _: BufferTarget,
                           mode: BufferUploadMode) {
-        let mut options = match mode {
-            BufferUploadMode::Static => MTLResourceOptions::CPUCacheModeWriteCombined,
-            BufferUploadMode::Dynamic => MTLResourceOptions::CPUCacheModeDefaultCache,
-        };
-        options |= MTLResourceOptions::StorageModeManaged;
+        // Dynamic buffers are rewritten every frame, so rather than creating new Metal buffers
+        // for them, which is slow, they're written into the upload buffers of the frame.
+        if let BufferUploadMode::Dynamic = mode {
+            let buffer_slice = match data {
+                BufferData::Uninitialized(size) => {
+                    self.allocate_upload((size * mem::size_of::<T>()) as u64)
+                }
+                BufferData::Memory(slice) => {
+                    let size = slice.len() * mem::size_of::<T>();
+                    self.upload(unsafe { slice::from_raw_parts(slice.as_ptr() as *const u8, size) })
+                }
+            };
+            *buffer.buffer.borrow_mut() = Some(buffer_slice);
+            return;
+        }
 
-        match data {
+        let options = MTLResourceOptions::CPUCacheModeWriteCombined |
+            MTLResourceOptions::StorageModeManaged;
+        let new_buffer = match data {
             BufferData::Uninitialized(size) => {
                 let size = (size * mem::size_of::<T>()) as u64;
-                let new_buffer = self.device.new_buffer(size, options);
-                *buffer.buffer.borrow_mut() = Some(new_buffer);
+                self.device.new_buffer(size, options)
             }
             BufferData::Memory(slice) => {
                 let size = (slice.len() * mem::size_of::<T>()) as u64;
-                let new_buffer = self.device.new_buffer_with_data(slice.as_ptr() as *const _,
-                                                                  size,
-                                                                  options);
-                *buffer.buffer.borrow_mut() = Some(new_buffer);
+                self.device.new_buffer_with_data(slice.as_ptr() as *const _, size, options)
             }
-        }
+        };
+        let size = new_buffer.length();
+        *buffer.buffer.borrow_mut() = Some(BufferSlice { buffer: new_buffer, offset: 0, size });
     }
 
     fn framebuffer_texture<'f>(&self, framebuffer: &'f MetalFramebuffer) -> &'f MetalTexture {
         &framebuffer.0
     }
 
+    fn texture_format(&self, texture: &MetalTexture) -> TextureFormat {
+        TextureFormat::from_metal_pixel_format(texture.texture.pixel_format()).unwrap()
+    }
+
     fn texture_size(&self, texture: &MetalTexture) -> Vector2I {
         Vector2I::new(texture.texture.width() as i32, texture.texture.height() as i32)
     }
@@ -484,6 +654,47 @@ impl Device for MetalDevice {
         texture_data_receiver
     }
 
+    fn copy_render_target(&self, target: &RenderTarget<MetalDevice>, rect: RectI)
+                          -> MetalTexture {
+        // Blits need matching pixel formats, which `TextureFormat` can't always express (the
+        // drawable is BGRA), so the copy is made with the source's own format.
+        let source_texture = self.render_target_color_texture(target);
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_texture_type(MTLTextureType::D2);
+        descriptor.set_pixel_format(source_texture.pixel_format());
+        descriptor.set_width(rect.size().x() as u64);
+        descriptor.set_height(rect.size().y() as u64);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+        descriptor.set_usage(MTLTextureUsage::ShaderRead);
+        let texture = self.device.new_texture(&descriptor);
+
+        let command_buffers = self.command_buffers.borrow();
+        let command_buffer = command_buffers.last().unwrap();
+        let encoder = command_buffer.new_blit_command_encoder();
+        let source_origin = MTLOrigin {
+            x: rect.origin().x() as u64,
+            y: rect.origin().y() as u64,
+            z: 0,
+        };
+        let size = MTLSize {
+            width: rect.size().x() as u64,
+            height: rect.size().y() as u64,
+            depth: 1,
+        };
+        encoder.copy_from_texture(&source_texture,
+                                  0,
+                                  0,
+                                  source_origin,
+                                  size,
+                                  &texture,
+                                  0,
+                                  0,
+                                  MTLOrigin { x: 0, y: 0, z: 0 });
+        encoder.end_encoding();
+
+        MetalTexture { texture, dirty: Cell::new(false) }
+    }
+
     fn begin_commands(&self) {
         self.command_buffers.borrow_mut().push(self.command_queue.new_command_buffer().retain());
     }
@@ -493,6 +704,81 @@ impl Device for MetalDevice {
         command_buffer.commit();
     }
 
+    fn clear(&self, target: &RenderTarget<MetalDevice>, scissor: Option<RectI>, ops: &ClearOps) {
+        let command_buffers = self.command_buffers.borrow();
+        let command_buffer = command_buffers.last().unwrap();
+
+        // A render pass that draws nothing still performs the load actions.
+        let scissor = match scissor {
+            None => {
+                let render_pass_descriptor = self.create_render_pass_descriptor(target, ops);
+                let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
+                encoder.end_encoding();
+                return;
+            }
+            Some(scissor) => scissor,
+        };
+
+        // Load actions ignore the scissor, so draw a quad instead.
+        let render_pass_descriptor = self.create_render_pass_descriptor(target,
+                                                                        &ClearOps::default());
+        let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor);
+        self.set_scissor_rect(&encoder, &scissor);
+
+        let render_pipeline_descriptor = RenderPipelineDescriptor::new();
+        render_pipeline_descriptor.set_vertex_function(Some(&self.clear_vertex_function));
+        render_pipeline_descriptor.set_fragment_function(Some(&self.clear_fragment_function));
+        let color_texture = self.render_target_color_texture(target);
+        render_pipeline_descriptor.set_sample_count(color_texture.sample_count());
+        let pipeline_color_attachment = render_pipeline_descriptor.color_attachments()
+                                                                  .object_at(0)
+                                                                  .unwrap();
+        pipeline_color_attachment.set_pixel_format(color_texture.pixel_format());
+        pipeline_color_attachment.set_blending_enabled(false);
+        if ops.color.is_some() {
+            pipeline_color_attachment.set_write_mask(MTLColorWriteMask::all());
+        } else {
+            pipeline_color_attachment.set_write_mask(MTLColorWriteMask::empty());
+        }
+
+        let depth_stencil_descriptor = DepthStencilDescriptor::new();
+        depth_stencil_descriptor.set_depth_compare_function(MTLCompareFunction::Always);
+        if self.render_target_has_depth(target) {
+            let depth_stencil_format = MTLPixelFormat::Depth32Float_Stencil8;
+            render_pipeline_descriptor.set_depth_attachment_pixel_format(depth_stencil_format);
+            render_pipeline_descriptor.set_stencil_attachment_pixel_format(depth_stencil_format);
+
+            depth_stencil_descriptor.set_depth_write_enabled(ops.depth.is_some());
+            if let Some(value) = ops.stencil {
+                let stencil_descriptor = StencilDescriptor::new();
+                stencil_descriptor.set_stencil_compare_function(MTLCompareFunction::Always);
+                stencil_descriptor.set_depth_stencil_pass_operation(MTLStencilOperation::Replace);
+                stencil_descriptor.set_write_mask(!0);
+                depth_stencil_descriptor.set_front_face_stencil(Some(&stencil_descriptor));
+                depth_stencil_descriptor.set_back_face_stencil(Some(&stencil_descriptor));
+                encoder.set_stencil_reference_value(value as u32);
+            }
+        }
+
+        let render_pipeline_state =
+            self.device.new_render_pipeline_state(&render_pipeline_descriptor).unwrap();
+        let depth_stencil_state = self.device.new_depth_stencil_state(&depth_stencil_descriptor);
+        encoder.set_render_pipeline_state(&render_pipeline_state);
+        encoder.set_depth_stencil_state(&depth_stencil_state);
+
+        let color = ops.color.unwrap_or_default();
+        let color = [color.r(), color.g(), color.b(), color.a()];
+        let depth: f32 = ops.depth.unwrap_or(0.0);
+        encoder.set_vertex_bytes(0,
+                                 mem::size_of::<f32>() as u64,
+                                 &depth as *const f32 as *const _);
+        encoder.set_fragment_bytes(0,
+                                   mem::size_of::<[f32; 4]>() as u64,
+                                   color.as_ptr() as *const _);
+        encoder.draw_primitives(MTLPrimitiveType::TriangleStrip, 0, 4);
+        encoder.end_encoding();
+    }
+
     fn draw_arrays(&self, index_count: u32, render_state: &RenderState<MetalDevice>) {
         let encoder = self.prepare_to_draw(render_state);
         let primitive = render_state.primitive.to_metal_primitive();
@@ -511,7 +797,11 @@ impl Device for MetalDevice {
         let index_buffer = index_buffer.as_ref().expect("No index buffer bound to VAO!");
         let index_buffer = index_buffer.buffer.borrow();
         let index_buffer = index_buffer.as_ref().expect("Index buffer not allocated!");
-        encoder.draw_indexed_primitives(primitive, index_count, index_type, index_buffer, 0);
+        encoder.draw_indexed_primitives(primitive,
+                                        index_count,
+                                        index_type,
+                                        &index_buffer.buffer,
+                                        index_buffer.offset);
         encoder.end_encoding();
     }
 
@@ -531,8 +821,8 @@ impl Device for MetalDevice {
         encoder.draw_indexed_primitives_instanced(primitive,
                                                   index_count as u64,
                                                   index_type,
-                                                  index_buffer,
-                                                  0,
+                                                  &index_buffer.buffer,
+                                                  index_buffer.offset,
                                                   instance_count as u64);
         encoder.end_encoding();
     }
@@ -709,10 +999,15 @@ impl MetalDevice {
             blit_command_encoder.end_encoding();
         }
 
-        let render_pass_descriptor = self.create_render_pass_descriptor(render_state);
+        let render_pass_descriptor =
+            self.create_render_pass_descriptor(render_state.target,
+                                               &render_state.options.clear_ops);
 
         let encoder = command_buffer.new_render_command_encoder(&render_pass_descriptor).retain();
         self.set_viewport(&encoder, &render_state.viewport);
+        if let Some(ref scissor) = render_state.options.scissor {
+            self.set_scissor_rect(&encoder, scissor);
+        }
 
         let render_pipeline_descriptor = RenderPipelineDescriptor::new();
         render_pipeline_descriptor.set_vertex_function(Some(&render_state.program
@@ -723,6 +1018,8 @@ impl MetalDevice {
                                                                            .function));
         render_pipeline_descriptor.set_vertex_descriptor(Some(&render_state.vertex_array
                                                                            .descriptor));
+        let sample_count = self.render_target_color_texture(render_state.target).sample_count();
+        render_pipeline_descriptor.set_sample_count(sample_count);
 
         // Create render pipeline state.
         let pipeline_color_attachment = render_pipeline_descriptor.color_attachments()
@@ -753,9 +1050,9 @@ impl MetalDevice {
                                                                 .enumerate() {
             let real_index = vertex_buffer_index as u64 + FIRST_VERTEX_BUFFER_INDEX;
             let buffer = vertex_buffer.buffer.borrow();
-            let buffer = buffer.as_ref().map(|buffer| buffer.as_ref()).unwrap();
-            encoder.set_vertex_buffer(real_index, Some(buffer), 0);
-            encoder.use_resource(buffer, MTLResourceUsage::Read);
+            let buffer = buffer.as_ref().unwrap();
+            encoder.set_vertex_buffer(real_index, Some(buffer.buffer.as_ref()), buffer.offset);
+            encoder.use_resource(&buffer.buffer, MTLResourceUsage::Read);
         }
 
         self.set_uniforms(&encoder, render_state);
@@ -803,7 +1100,7 @@ impl MetalDevice {
         *uniforms = ShaderUniforms::Uniforms { encoder, struct_type };
     }
 
-    fn create_argument_buffer(&self, shader: &MetalShader) -> Option<Buffer> {
+    fn create_argument_buffer(&self, shader: &MetalShader) -> Option<BufferSlice> {
         let uniforms = shader.uniforms.borrow();
         let encoder = match *uniforms {
             ShaderUniforms::Unknown => unreachable!(),
@@ -811,11 +1108,9 @@ impl MetalDevice {
             ShaderUniforms::Uniforms { ref encoder, .. } => encoder,
         };
 
-        let buffer_options = MTLResourceOptions::CPUCacheModeDefaultCache |
-            MTLResourceOptions::StorageModeManaged;
-        let buffer = self.device.new_buffer(encoder.encoded_length(), buffer_options);
-        encoder.set_argument_buffer(&buffer, 0);
-        Some(buffer)
+        let argument_buffer = self.allocate_upload(encoder.encoded_length());
+        encoder.set_argument_buffer(&argument_buffer.buffer, argument_buffer.offset);
+        Some(argument_buffer)
     }
 
     fn set_uniforms(&self,
@@ -831,14 +1126,21 @@ impl MetalDevice {
         if let ShaderUniforms::Uniforms { .. } = *vertex_uniforms {
             have_vertex_uniforms = true;
             let vertex_argument_buffer = vertex_argument_buffer.as_ref().unwrap();
-            render_command_encoder.use_resource(vertex_argument_buffer, MTLResourceUsage::Read);
-            render_command_encoder.set_vertex_buffer(0, Some(vertex_argument_buffer), 0);
+            render_command_encoder.use_resource(&vertex_argument_buffer.buffer,
+                                                MTLResourceUsage::Read);
+            render_command_encoder.set_vertex_buffer(0,
+                                                     Some(vertex_argument_buffer.buffer.as_ref()),
+                                                     vertex_argument_buffer.offset);
         }
         if let ShaderUniforms::Uniforms { .. } = *fragment_uniforms {
             have_fragment_uniforms = true;
             let fragment_argument_buffer = fragment_argument_buffer.as_ref().unwrap();
-            render_command_encoder.use_resource(fragment_argument_buffer, MTLResourceUsage::Read);
-            render_command_encoder.set_fragment_buffer(0, Some(fragment_argument_buffer), 0);
+            render_command_encoder.use_resource(&fragment_argument_buffer.buffer,
+                                                MTLResourceUsage::Read);
+            render_command_encoder.set_fragment_buffer(0,
+                                                       Some(fragment_argument_buffer.buffer
+                                                                                    .as_ref()),
+                                                       fragment_argument_buffer.offset);
         }
 
         if !have_vertex_uniforms && !have_fragment_uniforms {
@@ -885,12 +1187,8 @@ impl MetalDevice {
             uniform_buffer_ranges.push(start_index..end_index);
         }
 
-        let buffer_options = MTLResourceOptions::CPUCacheModeWriteCombined |
-            MTLResourceOptions::StorageModeManaged;
-        let data_buffer = self.device
-                              .new_buffer_with_data(uniform_buffer_data.as_ptr() as *const _,
-                                                    uniform_buffer_data.len() as u64,
-                                                    buffer_options);
+        // The upload buffers of the frame keep the data live until the GPU has drawn it.
+        let data_buffer = self.upload(&uniform_buffer_data);
 
         for (&(uniform, ref uniform_data), buffer_range) in
                 render_state.uniforms.iter().zip(uniform_buffer_ranges.iter()) {
@@ -905,8 +1203,8 @@ impl MetalDevice {
                     self.set_uniform(vertex_index,
                                      argument_encoder,
                                      uniform_data,
-                                     &data_buffer,
-                                     buffer_range.start as u64,
+                                     &data_buffer.buffer,
+                                     data_buffer.offset + buffer_range.start as u64,
                                      render_command_encoder,
                                      render_state);
                 }
@@ -919,28 +1217,20 @@ impl MetalDevice {
                     self.set_uniform(fragment_index,
                                      argument_encoder,
                                      uniform_data,
-                                     &data_buffer,
-                                     buffer_range.start as u64,
+                                     &data_buffer.buffer,
+                                     data_buffer.offset + buffer_range.start as u64,
                                      render_command_encoder,
                                      render_state);
                 }
             }
         }
 
-        render_command_encoder.use_resource(&data_buffer, MTLResourceUsage::Read);
+        render_command_encoder.use_resource(&data_buffer.buffer, MTLResourceUsage::Read);
 
-        // Metal expects the data buffer to remain live. (Issue #199.)
-        // FIXME(pcwalton): When do we deallocate this? What are the expected
-        // lifetime semantics?
-        mem::forget(data_buffer);
-
-        if let Some(vertex_argument_buffer) = vertex_argument_buffer {
-            let range = NSRange::new(0, vertex_argument_buffer.length());
-            vertex_argument_buffer.did_modify_range(range);
-        }
-        if let Some(fragment_argument_buffer) = fragment_argument_buffer {
-            let range = NSRange::new(0, fragment_argument_buffer.length());
-            fragment_argument_buffer.did_modify_range(range);
+        let argument_buffers = vertex_argument_buffer.iter().chain(fragment_argument_buffer.iter());
+        for argument_buffer in argument_buffers {
+            let range = NSRange::new(argument_buffer.offset, argument_buffer.size);
+            argument_buffer.buffer.did_modify_range(range);
         }
     }
 
@@ -978,36 +1268,14 @@ impl MetalDevice {
             None => pipeline_color_attachment.set_blending_enabled(false),
             Some(ref blend) => {
                 pipeline_color_attachment.set_blending_enabled(true);
-                match blend.func {
-                    BlendFunc::RGBOneAlphaOne => {
-                        pipeline_color_attachment.set_source_rgb_blend_factor(MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_rgb_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_source_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                    }
-                    BlendFunc::RGBOneAlphaOneMinusSrcAlpha => {
-                        pipeline_color_attachment.set_source_rgb_blend_factor(MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_rgb_blend_factor(
-                            MTLBlendFactor::OneMinusSourceAlpha);
-                        pipeline_color_attachment.set_source_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                    }
-                    BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha => {
-                        pipeline_color_attachment.set_source_rgb_blend_factor(
-                            MTLBlendFactor::SourceAlpha);
-                        pipeline_color_attachment.set_destination_rgb_blend_factor(
-                            MTLBlendFactor::OneMinusSourceAlpha);
-                        pipeline_color_attachment.set_source_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                        pipeline_color_attachment.set_destination_alpha_blend_factor(
-                            MTLBlendFactor::One);
-                    }
-                }
+                pipeline_color_attachment.set_source_rgb_blend_factor(
+                    blend.src_rgb_factor.to_metal_blend_factor());
+                pipeline_color_attachment.set_destination_rgb_blend_factor(
+                    blend.dest_rgb_factor.to_metal_blend_factor());
+                pipeline_color_attachment.set_source_alpha_blend_factor(
+                    blend.src_alpha_factor.to_metal_blend_factor());
+                pipeline_color_attachment.set_destination_alpha_blend_factor(
+                    blend.dest_alpha_factor.to_metal_blend_factor());
                 match blend.op {
                     BlendOp::Add => {
                         pipeline_color_attachment.set_rgb_blend_operation(MTLBlendOperation::Add);
@@ -1031,13 +1299,15 @@ impl MetalDevice {
         }
     }
 
-    fn create_render_pass_descriptor(&self, render_state: &RenderState<MetalDevice>)
+    fn create_render_pass_descriptor(&self,
+                                     target: &RenderTarget<MetalDevice>,
+                                     clear_ops: &ClearOps)
                                      -> RenderPassDescriptor {
         let render_pass_descriptor = RenderPassDescriptor::new().retain();
         let color_attachment = render_pass_descriptor.color_attachments().object_at(0).unwrap();
-        color_attachment.set_texture(Some(&self.render_target_color_texture(render_state.target)));
+        color_attachment.set_texture(Some(&self.render_target_color_texture(target)));
 
-        match render_state.options.clear_ops.color {
+        match clear_ops.color {
             Some(color) => {
                 let color = MTLClearColor::new(color.r() as f64,
                                                color.g() as f64,
@@ -1050,14 +1320,14 @@ impl MetalDevice {
         }
         color_attachment.set_store_action(MTLStoreAction::Store);
 
-        let depth_stencil_texture = self.render_target_depth_texture(render_state.target);
+        let depth_stencil_texture = self.render_target_depth_texture(target);
         if let Some(depth_stencil_texture) = depth_stencil_texture {
             let depth_attachment = render_pass_descriptor.depth_attachment().unwrap();
             let stencil_attachment = render_pass_descriptor.stencil_attachment().unwrap();
             depth_attachment.set_texture(Some(&depth_stencil_texture));
             stencil_attachment.set_texture(Some(&depth_stencil_texture));
 
-            match render_state.options.clear_ops.depth {
+            match clear_ops.depth {
                 Some(depth) => {
                     depth_attachment.set_clear_depth(depth as f64);
                     depth_attachment.set_load_action(MTLLoadAction::Clear);
@@ -1066,7 +1336,7 @@ impl MetalDevice {
             }
             depth_attachment.set_store_action(MTLStoreAction::Store);
 
-            match render_state.options.clear_ops.stencil {
+            match clear_ops.stencil {
                 Some(value) => {
                     stencil_attachment.set_clear_stencil(value as u32);
                     stencil_attachment.set_load_action(MTLLoadAction::Clear);
@@ -1110,6 +1380,7 @@ impl MetalDevice {
                 stencil_descriptor.set_depth_failure_operation(MTLStencilOperation::Keep);
                 stencil_descriptor.set_depth_stencil_pass_operation(pass_operation);
                 stencil_descriptor.set_write_mask(write_mask);
+                stencil_descriptor.set_read_mask(stencil_state.mask);
                 depth_stencil_descriptor.set_front_face_stencil(Some(&stencil_descriptor));
                 depth_stencil_descriptor.set_back_face_stencil(Some(&stencil_descriptor));
                 encoder.set_stencil_reference_value(stencil_state.reference);
@@ -1139,6 +1410,15 @@ impl MetalDevice {
         })
     }
 
+    fn set_scissor_rect(&self, encoder: &RenderCommandEncoderRef, scissor: &RectI) {
+        encoder.set_scissor_rect(MTLScissorRect {
+            x: scissor.origin().x() as u64,
+            y: scissor.origin().y() as u64,
+            width: scissor.size().x() as u64,
+            height: scissor.size().y() as u64,
+        })
+    }
+
     fn synchronize_texture(&self, texture: &Texture, block: RcBlock<(*mut Object,), ()>) {
         unsafe {
             let command_buffers = self.command_buffers.borrow();
@@ -1173,6 +1453,26 @@ impl DeviceExtra for metal::Device {
 
 // Conversion helpers
 
+trait BlendFactorExt {
+    fn to_metal_blend_factor(self) -> MTLBlendFactor;
+}
+
+impl BlendFactorExt for BlendFactor {
+    #[inline]
+    fn to_metal_blend_factor(self) -> MTLBlendFactor {
+        match self {
+            BlendFactor::Zero => MTLBlendFactor::Zero,
+            BlendFactor::One => MTLBlendFactor::One,
+            BlendFactor::SrcAlpha => MTLBlendFactor::SourceAlpha,
+            BlendFactor::OneMinusSrcAlpha => MTLBlendFactor::OneMinusSourceAlpha,
+            BlendFactor::DestAlpha => MTLBlendFactor::DestinationAlpha,
+            BlendFactor::OneMinusDestAlpha => MTLBlendFactor::OneMinusDestinationAlpha,
+            BlendFactor::DestColor => MTLBlendFactor::DestinationColor,
+            BlendFactor::OneMinusSrcColor => MTLBlendFactor::OneMinusSourceColor,
+        }
+    }
+}
+
 trait DepthFuncExt {
     fn to_metal_compare_function(self) -> MTLCompareFunction;
 }
@@ -1246,6 +1546,7 @@ impl UniformDataExt for UniformData {
 
 trait TextureFormatExt: Sized {
     fn from_metal_pixel_format(metal_pixel_format: MTLPixelFormat) -> Option<Self>;
+    fn metal_pixel_format(self) -> MTLPixelFormat;
 }
 
 impl TextureFormatExt for TextureFormat {
@@ -1254,6 +1555,9 @@ impl TextureFormatExt for TextureFormat {
             MTLPixelFormat::R8Unorm => Some(TextureFormat::R8),
             MTLPixelFormat::R16Float => Some(TextureFormat::R16F),
             MTLPixelFormat::RGBA8Unorm => Some(TextureFormat::RGBA8),
+            MTLPixelFormat::RGBA16Float => Some(TextureFormat::RGBA16F),
+            MTLPixelFormat::RGBA32Float => Some(TextureFormat::RGBA32F),
+            MTLPixelFormat::RGB10A2Unorm => Some(TextureFormat::RGB10A2),
             MTLPixelFormat::BGRA8Unorm => {
                 // FIXME(pcwalton): This is wrong! But it prevents a crash for now.
                 Some(TextureFormat::RGBA8)
@@ -1261,10 +1565,85 @@ impl TextureFormatExt for TextureFormat {
             _ => None,
         }
     }
+
+    fn metal_pixel_format(self) -> MTLPixelFormat {
+        match self {
+            TextureFormat::R8 => MTLPixelFormat::R8Unorm,
+            TextureFormat::R16F => MTLPixelFormat::R16Float,
+            TextureFormat::RGBA8 => MTLPixelFormat::RGBA8Unorm,
+            TextureFormat::RGBA16F => MTLPixelFormat::RGBA16Float,
+            TextureFormat::RGBA32F => MTLPixelFormat::RGBA32Float,
+            TextureFormat::RGB10A2 => MTLPixelFormat::RGB10A2Unorm,
+        }
+    }
+}
+
+impl UploadHeap {
+    fn new() -> UploadHeap {
+        UploadHeap { buffers: vec![], current_buffer_index: 0, next_offset: 0 }
+    }
+
+    fn allocate(&mut self, device: &metal::Device, size: u64) -> BufferSlice {
+        let options = MTLResourceOptions::CPUCacheModeWriteCombined |
+            MTLResourceOptions::StorageModeManaged;
+        if size > UPLOAD_BUFFER_SIZE {
+            // The command buffers that use this buffer keep it alive until they're done with it.
+            return BufferSlice { buffer: device.new_buffer(size, options), offset: 0, size };
+        }
+
+        if self.current_buffer_index < self.buffers.len() &&
+                self.next_offset + size > UPLOAD_BUFFER_SIZE {
+            self.current_buffer_index += 1;
+            self.next_offset = 0;
+        }
+        if self.current_buffer_index == self.buffers.len() {
+            self.buffers.push(device.new_buffer(UPLOAD_BUFFER_SIZE, options));
+        }
+
+        let offset = self.next_offset;
+        self.next_offset = align(offset + size, UPLOAD_BUFFER_ALIGNMENT);
+        let buffer = self.buffers[self.current_buffer_index].retain();
+        BufferSlice { buffer, offset, size }
+    }
+
+    // Frees everything allocated so far. The GPU must have finished with it.
+    fn reset(&mut self) {
+        self.current_buffer_index = 0;
+        self.next_offset = 0;
+    }
+}
+
+fn align(offset: u64, alignment: u64) -> u64 {
+    (offset + alignment - 1) / alignment * alignment
 }
 
 // Synchronization helpers
 
+// The number of frames that the GPU has finished, for reusing the upload buffers of old frames.
+struct FrameFence {
+    completed_frame_count: Mutex<u64>,
+    cond: Condvar,
+}
+
+impl FrameFence {
+    fn new() -> FrameFence {
+        FrameFence { completed_frame_count: Mutex::new(0), cond: Condvar::new() }
+    }
+
+    fn signal(&self, completed_frame_count: u64) {
+        let mut guard = self.completed_frame_count.lock().unwrap();
+        *guard = u64::max(*guard, completed_frame_count);
+        self.cond.notify_all();
+    }
+
+    fn wait(&self, completed_frame_count: u64) {
+        let mut guard = self.completed_frame_count.lock().unwrap();
+        while *guard < completed_frame_count {
+            guard = self.cond.wait(guard).unwrap();
+        }
+    }
+}
+
 fn try_recv_timer_query_with_guard(guard: &mut MutexGuard<MetalTimerQueryData>)
                                    -> Option<Duration> {
     match (guard.start_time, guard.end_time) {
@@ -1314,6 +1693,15 @@ impl MetalTextureDataReceiver {
                                          stride as u64 * 4);
                 TextureData::F32(pixels)
             }
+            TextureFormat::RGB10A2 => {
+                let stride = size.x() as usize;
+                let mut pixels = vec![0; stride * size.y() as usize];
+                self.0.texture.get_bytes(pixels.as_mut_ptr() as *mut _,
+                                         metal_region,
+                                         0,
+                                         stride as u64 * 4);
+                TextureData::U32(pixels)
+            }
         };
 
         let mut guard = self.0.mutex.lock().unwrap();
@@ -1581,6 +1969,13 @@ trait Retain {
     fn retain(&self) -> Self::Owned;
 }
 
+impl Retain for BufferRef {
+    type Owned = Buffer;
+    fn retain(&self) -> Buffer {
+        unsafe { Buffer::from_ptr(msg_send![self.as_ptr(), retain]) }
+    }
+}
+
 impl Retain for CommandBufferRef {
     type Owned = CommandBuffer;
     fn retain(&self) -> CommandBuffer {
@@ -0,0 +1,420 @@
+// pathfinder/renderer/src/binning.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Bins the segments of paths into tiles for `BuildOptions::gpu_tiling`, on devices without
+//! compute shaders.
+//!
+//! This must make the same fills and tiles as the compute shaders `bin`, `propagate`, and
+//! `patch_fills`. Unlike the sweep in `tiles.rs`, every segment is binned on its own: it makes a
+//! fill in each tile it crosses, and where it crosses the top edge of a tile, a fill along that
+//! edge from the crossing to the right side of the tile. It also adds its winding to the backdrop
+//! of the next tile to the right, and summing those along each row gives the winding number at
+//! the left of every tile.
+
+use crate::gpu_data::{AlphaTileBatchPrimitive, FillBatchPrimitive, SegmentBatch, SegmentPath};
+use crate::tile_map::DenseTileMap;
+use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+use pathfinder_content::fill::FillRule;
+use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::cmp;
+use std::ops::Range;
+
+pub(crate) struct BinnedSegments {
+    pub(crate) fills: Vec<FillBatchPrimitive>,
+    // The alpha tiles of each path of the batch, in order.
+    pub(crate) alpha_tiles: Vec<Vec<AlphaTileBatchPrimitive>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct BinnedTile {
+    // The winding of the segments that cross the top edge of the tile to the left, until the
+    // backdrops are summed along the row.
+    backdrop: i32,
+    has_fills: bool,
+    alpha_tile_index: Option<u16>,
+}
+
+pub(crate) fn bin_segments(batch: &SegmentBatch) -> BinnedSegments {
+    let mut binned = BinnedSegments { fills: vec![], alpha_tiles: vec![] };
+    for path in &batch.paths {
+        let mut tiles = DenseTileMap::new(path.tile_rect);
+        let mut fills = vec![];
+        let segments = &batch.segments[path.segments.start as usize..path.segments.end as usize];
+        for &segment in segments {
+            bin_segment(segment, &mut tiles, &mut fills);
+        }
+
+        binned.alpha_tiles.push(propagate(path, &mut tiles));
+
+        // Fills in tiles that didn't get places in the mask framebuffer are dropped.
+        for (mut fill, tile_index) in fills {
+            if let Some(alpha_tile_index) = tiles.data[tile_index].alpha_tile_index {
+                fill.alpha_tile_index = alpha_tile_index;
+                binned.fills.push(fill);
+            }
+        }
+    }
+    binned
+}
+
+// The most fills, and so alpha tiles, that `segment` can make: one in each tile it crosses, plus
+// one for each top edge of a tile it crosses.
+pub(crate) fn max_fill_count(segment: LineSegment2F) -> u32 {
+    let rows = tile_span(segment.min_y(), segment.max_y(), TILE_HEIGHT);
+    let columns = tile_span(segment.min_x(), segment.max_x(), TILE_WIDTH);
+    let (row_count, column_count) = (rows.len() as u32, columns.len() as u32);
+    row_count.saturating_mul(2).saturating_add(column_count)
+}
+
+fn bin_segment(segment: LineSegment2F,
+               tiles: &mut DenseTileMap<BinnedTile>,
+               fills: &mut Vec<(FillBatchPrimitive, usize)>) {
+    for tile_y in tile_span(segment.min_y(), segment.max_y(), TILE_HEIGHT) {
+        let row_segment = clip_to_row(segment, tile_y);
+        if row_segment.from_x() == row_segment.to_x() {
+            continue;
+        }
+        for tile_x in tile_span(row_segment.min_x(), row_segment.max_x(), TILE_WIDTH) {
+            let fill_segment = clip_to_column(row_segment, tile_x);
+            add_fill(fill_segment, Vector2I::new(tile_x, tile_y), tiles, fills);
+        }
+    }
+
+    // A segment crosses the top edges at `y` with `min_y < y <= max_y`, so that a contour passing
+    // through a corner of a tile crosses it once.
+    if segment.from_y() == segment.to_y() {
+        return;
+    }
+    let winding = if segment.from_y() < segment.to_y() { 1 } else { -1 };
+    let tile_rect = tiles.rect;
+    let mut tile_y = f32::floor(segment.min_y() / TILE_HEIGHT as f32) as i32 + 1;
+    while ((tile_y * TILE_HEIGHT as i32) as f32) <= segment.max_y() {
+        if tile_y >= tile_rect.min_y() && tile_y < tile_rect.max_y() {
+            let y = (tile_y * TILE_HEIGHT as i32) as f32;
+            let x = solve_x_for_y(segment, y);
+            let tile_x = f32::floor(x / TILE_WIDTH as f32) as i32;
+            let tile_x = cmp::min(cmp::max(tile_x, tile_rect.min_x()), tile_rect.max_x() - 1);
+            let crossing = Vector2F::new(x, y);
+            let right = Vector2F::new(((tile_x + 1) * TILE_WIDTH as i32) as f32, y);
+            let fill_segment = if winding > 0 {
+                LineSegment2F::new(right, crossing)
+            } else {
+                LineSegment2F::new(crossing, right)
+            };
+            add_fill(fill_segment, Vector2I::new(tile_x, tile_y), tiles, fills);
+            if let Some(tile_index) = tiles.coords_to_index(Vector2I::new(tile_x + 1, tile_y)) {
+                tiles.data[tile_index].backdrop += winding;
+            }
+        }
+        tile_y += 1;
+    }
+}
+
+// Sums the backdrops along each row, gives the tiles with fills places in the mask framebuffer,
+// and returns the tiles to draw.
+fn propagate(path: &SegmentPath, tiles: &mut DenseTileMap<BinnedTile>)
+             -> Vec<AlphaTileBatchPrimitive> {
+    let mut alpha_tiles = vec![];
+    let mut alpha_tile_count = 0;
+    let tile_rect = tiles.rect;
+    for tile_y in tile_rect.min_y()..tile_rect.max_y() {
+        let mut backdrop = 0;
+        for tile_x in tile_rect.min_x()..tile_rect.max_x() {
+            let tile_coords = Vector2I::new(tile_x, tile_y);
+            let tile_index = tiles.coords_to_index_unchecked(tile_coords);
+            let tile = &mut tiles.data[tile_index];
+            backdrop += tile.backdrop;
+            tile.backdrop = backdrop;
+            if tile.has_fills && alpha_tile_count < path.alpha_tile_count {
+                tile.alpha_tile_index = Some(path.first_alpha_tile_index + alpha_tile_count);
+                alpha_tile_count += 1;
+            }
+
+            let inside = match path.fill_rule {
+                FillRule::Winding => backdrop != 0,
+                FillRule::EvenOdd => backdrop % 2 != 0,
+            };
+            if tile.alpha_tile_index.is_none() && !inside {
+                continue;
+            }
+            alpha_tiles.push(AlphaTileBatchPrimitive::new(tile_coords,
+                                                          backdrop as i8,
+                                                          path.object_index,
+                                                          tile.alpha_tile_index.unwrap_or(!0),
+                                                          &path.tex_transform));
+        }
+    }
+    alpha_tiles
+}
+
+// Converts a fill to 4.8 fixed point relative to its tile, as `BuiltObject::add_fill()` does, and
+// marks the tile as having fills. The alpha tile index is filled in once tiles have places.
+fn add_fill(segment: LineSegment2F,
+            tile_coords: Vector2I,
+            tiles: &mut DenseTileMap<BinnedTile>,
+            fills: &mut Vec<(FillBatchPrimitive, usize)>) {
+    let tile_index = match tiles.coords_to_index(tile_coords) {
+        None => return,
+        Some(tile_index) => tile_index,
+    };
+
+    let tile_origin = Vector2F::new((tile_coords.x() * TILE_WIDTH as i32) as f32,
+                                    (tile_coords.y() * TILE_HEIGHT as i32) as f32);
+    let to_fixed = |point: Vector2F| {
+        let point = (point - tile_origin).scale(256.0);
+        let max = (TILE_WIDTH * 256 - 1) as f32;
+        let x = f32::floor(point.x() + 0.5).max(0.0).min(max) as u32;
+        let y = f32::floor(point.y() + 0.5).max(0.0).min(max) as u32;
+        (x, y)
+    };
+    let ((from_x, from_y), (to_x, to_y)) = (to_fixed(segment.from()), to_fixed(segment.to()));
+    if from_x == to_x {
+        return;
+    }
+
+    let fill = FillBatchPrimitive {
+        px: LineSegmentU4 {
+            from: ((from_x >> 8) | ((from_y >> 8) << 4)) as u8,
+            to: ((to_x >> 8) | ((to_y >> 8) << 4)) as u8,
+        },
+        subpx: LineSegmentU8 {
+            from_x: from_x as u8,
+            from_y: from_y as u8,
+            to_x: to_x as u8,
+            to_y: to_y as u8,
+        },
+        alpha_tile_index: 0,
+    };
+    fills.push((fill, tile_index));
+    tiles.data[tile_index].has_fills = true;
+}
+
+// The tiles of size `tile_size` from the one containing `min` to the one containing `max`. A `max`
+// on a tile edge belongs to the tile before it, unless the span is empty.
+fn tile_span(min: f32, max: f32, tile_size: u32) -> Range<i32> {
+    let tile_size = tile_size as f32;
+    let first = f32::floor(min / tile_size) as i32;
+    let mut last = f32::floor(max / tile_size) as i32;
+    if max > min && last as f32 * tile_size == max {
+        last -= 1;
+    }
+    first..(last + 1)
+}
+
+// Returns the part of `segment` in row `tile_y`, keeping its direction.
+fn clip_to_row(segment: LineSegment2F, tile_y: i32) -> LineSegment2F {
+    if segment.from_y() == segment.to_y() {
+        return segment;
+    }
+    let top = (tile_y * TILE_HEIGHT as i32) as f32;
+    let bottom = top + TILE_HEIGHT as f32;
+    let from_y = segment.from_y().max(top).min(bottom);
+    let to_y = segment.to_y().max(top).min(bottom);
+    LineSegment2F::new(Vector2F::new(solve_x_for_y(segment, from_y), from_y),
+                       Vector2F::new(solve_x_for_y(segment, to_y), to_y))
+}
+
+// Returns the part of `segment`, which isn't vertical, in column `tile_x`, keeping its direction.
+fn clip_to_column(segment: LineSegment2F, tile_x: i32) -> LineSegment2F {
+    let left = (tile_x * TILE_WIDTH as i32) as f32;
+    let right = left + TILE_WIDTH as f32;
+    let from_x = segment.from_x().max(left).min(right);
+    let to_x = segment.to_x().max(left).min(right);
+    LineSegment2F::new(Vector2F::new(from_x, solve_y_for_x(segment, from_x)),
+                       Vector2F::new(to_x, solve_y_for_x(segment, to_x)))
+}
+
+// These are written out, rather than using `LineSegment2F::solve_x_for_y()`, so that they round
+// the same way as the compute shaders.
+fn solve_x_for_y(segment: LineSegment2F, y: f32) -> f32 {
+    let t = (y - segment.from_y()) / (segment.to_y() - segment.from_y());
+    segment.from_x() + t * (segment.to_x() - segment.from_x())
+}
+
+fn solve_y_for_x(segment: LineSegment2F, x: f32) -> f32 {
+    let t = (x - segment.from_x()) / (segment.to_x() - segment.from_x());
+    segment.from_y() + t * (segment.to_y() - segment.from_y())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bin_segments, max_fill_count, BinnedSegments};
+    use crate::gpu_data::{FillBatchPrimitive, SegmentBatch, SegmentPath};
+    use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectI;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+
+    fn polygon(points: &[(f32, f32)]) -> Vec<LineSegment2F> {
+        (0..points.len()).map(|point_index| {
+            let (from_x, from_y) = points[point_index];
+            let (to_x, to_y) = points[(point_index + 1) % points.len()];
+            LineSegment2F::new(Vector2F::new(from_x, from_y), Vector2F::new(to_x, to_y))
+        }).collect()
+    }
+
+    fn segment_batch(segments: Vec<LineSegment2F>,
+                     tile_rect: RectI,
+                     alpha_tile_count: u16,
+                     fill_rule: FillRule)
+                     -> SegmentBatch {
+        let max_fill_count = segments.iter().map(|&segment| max_fill_count(segment)).sum();
+        let path = SegmentPath {
+            segments: 0..(segments.len() as u32),
+            tile_rect,
+            first_alpha_tile_index: 10,
+            alpha_tile_count,
+            max_fill_count,
+            object_index: 3,
+            fill_rule,
+            tex_transform: Transform2F::default(),
+        };
+        SegmentBatch { paths: vec![path], segments }
+    }
+
+    // The winding number at `point`, relative to the origin of its tile, inside `fill`: fills
+    // cover the area below them, positively if they go from right to left.
+    fn fill_winding(fill: &FillBatchPrimitive, point: Vector2F) -> i32 {
+        let unpack = |px: u8, subpx_x: u8, subpx_y: u8| {
+            Vector2F::new(((px & 0xf) as u32 * 256 + subpx_x as u32) as f32,
+                          ((px >> 4) as u32 * 256 + subpx_y as u32) as f32).scale(1.0 / 256.0)
+        };
+        let from = unpack(fill.px.from, fill.subpx.from_x, fill.subpx.from_y);
+        let to = unpack(fill.px.to, fill.subpx.to_x, fill.subpx.to_y);
+        let (left, right) = if from.x() < to.x() { (from, to) } else { (to, from) };
+        if point.x() < left.x() || point.x() >= right.x() {
+            return 0;
+        }
+        let y = left.y() + (point.x() - left.x()) * (right.y() - left.y()) / (right.x() - left.x());
+        if point.y() < y {
+            0
+        } else if from.x() > to.x() {
+            1
+        } else {
+            -1
+        }
+    }
+
+    fn binned_winding(binned: &BinnedSegments, point: Vector2F) -> Option<i32> {
+        let tile_coords = Vector2I::new(f32::floor(point.x() / TILE_WIDTH as f32) as i32,
+                                        f32::floor(point.y() / TILE_HEIGHT as f32) as i32);
+        let alpha_tile = binned.alpha_tiles[0].iter().find(|alpha_tile| {
+            alpha_tile.tile_coords() == tile_coords
+        })?;
+        let tile_origin = Vector2F::new((tile_coords.x() * TILE_WIDTH as i32) as f32,
+                                        (tile_coords.y() * TILE_HEIGHT as i32) as f32);
+        let mut winding = alpha_tile.backdrop as i32;
+        for fill in &binned.fills {
+            if fill.alpha_tile_index == alpha_tile.tile_index {
+                winding += fill_winding(fill, point - tile_origin);
+            }
+        }
+        Some(winding)
+    }
+
+    // The winding number at `point`, counting the segments that cross the ray to its left.
+    fn true_winding(segments: &[LineSegment2F], point: Vector2F) -> i32 {
+        let mut winding = 0;
+        for segment in segments {
+            if (segment.from_y() <= point.y()) == (segment.to_y() <= point.y()) {
+                continue;
+            }
+            let t = (point.y() - segment.from_y()) / (segment.to_y() - segment.from_y());
+            if segment.from_x() + t * (segment.to_x() - segment.from_x()) < point.x() {
+                winding += if segment.from_y() < segment.to_y() { 1 } else { -1 };
+            }
+        }
+        winding
+    }
+
+    fn is_inside(winding: i32, fill_rule: FillRule) -> bool {
+        match fill_rule {
+            FillRule::Winding => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+
+    // Checks every pixel center of the tile rect against the segments, except for those so close
+    // to an edge that rounding to fixed point could move them across it.
+    fn check_coverage(batch: &SegmentBatch) {
+        let binned = bin_segments(batch);
+        let path = &batch.paths[0];
+        let pixel_rect = path.tile_rect.scale_xy(Vector2I::new(TILE_WIDTH as i32,
+                                                               TILE_HEIGHT as i32));
+        for y in pixel_rect.min_y()..pixel_rect.max_y() {
+            for x in pixel_rect.min_x()..pixel_rect.max_x() {
+                let point = Vector2F::new(x as f32 + 0.5, y as f32 + 0.5);
+                if batch.segments.iter().any(|segment| {
+                    distance_to_segment(*segment, point) < 0.1
+                }) {
+                    continue;
+                }
+                let expected = is_inside(true_winding(&batch.segments, point), path.fill_rule);
+                let binned = binned_winding(&binned, point).is_some_and(|winding| {
+                    is_inside(winding, path.fill_rule)
+                });
+                assert_eq!(binned, expected, "at {:?}", point);
+            }
+        }
+    }
+
+    fn distance_to_segment(segment: LineSegment2F, point: Vector2F) -> f32 {
+        let vector = segment.to() - segment.from();
+        let t = (point - segment.from()).dot(vector) / vector.dot(vector);
+        let nearest = segment.from() + vector.scale(t.clamp(0.0, 1.0));
+        (point - nearest).length()
+    }
+
+    #[test]
+    fn test_binned_coverage_matches_winding() {
+        let pentagram = polygon(&[(128.0, 20.0), (193.0, 216.0), (25.0, 95.0), (231.0, 95.0),
+                                  (63.0, 216.0)]);
+        let tile_rect = RectI::new(Vector2I::new(1, 1), Vector2I::splat(14));
+        for &fill_rule in &[FillRule::Winding, FillRule::EvenOdd] {
+            check_coverage(&segment_batch(pentagram.clone(), tile_rect, 196, fill_rule));
+        }
+
+        // Parts of this triangle lie on all sides of the tile rect, as if clipped by the view box.
+        let triangle = polygon(&[(-50.0, 20.0), (300.0, 60.5), (100.25, 250.0)]);
+        let tile_rect = RectI::new(Vector2I::new(2, 3), Vector2I::new(10, 9));
+        check_coverage(&segment_batch(triangle, tile_rect, 90, FillRule::Winding));
+
+        // Horizontal and vertical edges, along tile edges and not.
+        let rects = [polygon(&[(16.0, 16.0), (80.0, 16.0), (80.0, 80.0), (16.0, 80.0)]),
+                     polygon(&[(40.5, 20.25), (40.5, 70.75), (100.5, 70.75), (100.5, 20.25)])];
+        let rects = rects.concat();
+        let tile_rect = RectI::new(Vector2I::new(1, 1), Vector2I::new(6, 5));
+        check_coverage(&segment_batch(rects, tile_rect, 30, FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn test_binning_keeps_to_the_alpha_tile_budget() {
+        let pentagram = polygon(&[(128.0, 20.0), (193.0, 216.0), (25.0, 95.0), (231.0, 95.0),
+                                  (63.0, 216.0)]);
+        let tile_rect = RectI::new(Vector2I::new(1, 1), Vector2I::splat(14));
+        let batch = segment_batch(pentagram, tile_rect, 5, FillRule::Winding);
+        let binned = bin_segments(&batch);
+
+        let indexed_tiles: Vec<u16> = binned.alpha_tiles[0].iter().filter_map(|alpha_tile| {
+            Some(alpha_tile.tile_index).filter(|&tile_index| tile_index != !0)
+        }).collect();
+        assert_eq!(indexed_tiles, [10, 11, 12, 13, 14]);
+        assert!(binned.fills.iter().all(|fill| {
+            indexed_tiles.contains(&{ fill.alpha_tile_index })
+        }));
+        assert!(binned.alpha_tiles[0].iter().all(|alpha_tile| alpha_tile.object_index == 3));
+
+        // Tiles without places keep their backdrops, so those inside are still drawn.
+        assert!(binned.alpha_tiles[0].len() > 5);
+    }
+}
@@ -10,20 +10,36 @@
 
 //! Packs data onto the GPU.
 
+use crate::binning;
+use crate::clip::{ClipTileCoverage, ClipTiles};
 use crate::concurrent::executor::Executor;
-use crate::gpu_data::{AlphaTileBatchPrimitive, BuiltObject, FillBatchPrimitive, RenderCommand};
+use crate::coverage::TileCoverage;
+use crate::gpu_data::{AlphaTileBatchPrimitive, BuiltObject, FillBatchPrimitive, PaintPageId};
+use crate::gpu_data::{RenderCommand, SegmentBatch, SegmentPath, SolidTileBatchPrimitive};
 use crate::options::{PreparedBuildOptions, RenderCommandListener};
 use crate::paint::{PaintInfo, PaintMetadata};
-use crate::scene::Scene;
+use crate::scene::{ClipPathId, MAX_PATH_COUNT, PathObject, Scene, SceneBuildError};
 use crate::tile_map::DenseTileMap;
 use crate::tiles::{self, TILE_HEIGHT, TILE_WIDTH, Tiler};
 use crate::z_buffer::ZBuffer;
+use hashbrown::HashMap;
+use pathfinder_content::boolean::BooleanOp;
+use pathfinder_content::effects::{BackdropFilter, BlendMode};
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::Outline;
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::util;
 use pathfinder_simd::default::{F32x4, I32x4};
-use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use pathfinder_simd::x86::avx::{self, F32x8, I32x8};
+use std::borrow::Cow;
+use std::cmp;
+use std::mem;
+use std::ops::Range;
+use std::sync::Arc;
 use std::u16;
 
 #[cfg(target_arch = "wasm32")]
@@ -32,13 +48,49 @@ use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
+// The mask framebuffer holds 256×256 alpha tiles. The last one is never allocated, so that tiles
+// without fills, whose index is `!0`, read zero coverage from it.
+const MAX_ALPHA_TILES_PER_PAGE: u32 = 256 * 256 - 1;
+
 pub(crate) struct SceneBuilder<'a, L: RenderCommandListener> {
     scene: &'a Scene,
     built_options: &'a PreparedBuildOptions,
+    // The paths of the scene in the order they're drawn in. Object indices count paths in this
+    // order, so that the Z-buffer and batching see the order the z-indices ask for.
+    paths: Vec<&'a PathObject>,
+    // The index in the scene of each path, by object index.
+    scene_path_indices: Vec<u32>,
 
-    pub(crate) next_alpha_tile_index: AtomicUsize,
+    // The first free tile in the current page of the mask framebuffer.
+    next_alpha_tile_index: u32,
     pub(crate) z_buffer: ZBuffer,
     pub(crate) listener: L,
+
+    // Shared outlines flattened in their own space, keyed by the address of the outline and the
+    // scale of the similarity transform that instances draw it with.
+    flattened_instances: HashMap<(usize, u32), Outline>,
+    // The tiles of each clip path that some path uses, by clip path ID.
+    clip_tiles: Vec<Option<ClipTiles>>,
+
+    // With `stencil_clip_paths`, each clip path that a 2D path uses, tiled once for all of them,
+    // by clip path ID.
+    stencil_clips: Vec<Option<StencilClip>>,
+    // The alpha tiles of the stencil clips placed in the current page of the mask framebuffer, by
+    // clip path ID.
+    page_stencil_clips: Vec<Option<Vec<AlphaTileBatchPrimitive>>>,
+    // The clip path that each path is drawn inside with `ClippedAlphaTile`, by path index.
+    path_stencil_clips: Vec<Option<ClipPathId>>,
+    // The fill rule that each path's alpha tiles are drawn with, by path index.
+    path_fill_rules: Vec<FillRule>,
+    // The index of the first path with a backdrop filter, or the path count if there's none.
+    first_backdrop_filter_path: usize,
+    // The index of the first path that the renderer tiles, or the path count if there's none.
+    first_segment_path: usize,
+    // The clip path in the stencil buffer.
+    current_stencil_clip: Option<ClipPathId>,
+
+    // The tiles that each path touched so far, with `record_tile_coverage`.
+    tile_coverage: Option<TileCoverage>,
 }
 
 impl<'a, L: RenderCommandListener> SceneBuilder<'a, L> {
@@ -48,40 +100,97 @@ impl<'a, L: RenderCommandListener> SceneBuilder<'a, L> {
         listener: L,
     ) -> SceneBuilder<'a, L> {
         let effective_view_box = scene.effective_view_box(built_options);
+
+        // The sort is stable, so paths with equal z-indices keep the order they were pushed in.
+        let mut scene_path_indices: Vec<u32> = (0..scene.paths.len() as u32).collect();
+        scene_path_indices.sort_by_key(|&path_index| scene.paths[path_index as usize].z_index());
+        let paths = scene_path_indices.iter().map(|&path_index| {
+            &scene.paths[path_index as usize]
+        }).collect();
+
         SceneBuilder {
             scene,
             built_options,
+            paths,
+            scene_path_indices,
 
-            next_alpha_tile_index: AtomicUsize::new(0),
+            next_alpha_tile_index: 0,
             z_buffer: ZBuffer::new(effective_view_box),
             listener,
+            flattened_instances: HashMap::new(),
+            clip_tiles: vec![],
+            stencil_clips: vec![],
+            page_stencil_clips: vec![],
+            path_stencil_clips: vec![],
+            path_fill_rules: vec![],
+            first_backdrop_filter_path: 0,
+            first_segment_path: 0,
+            current_stencil_clip: None,
+            tile_coverage: None,
         }
     }
 
-    pub fn build<E>(&mut self, executor: &E) where E: Executor {
+    pub fn build<E>(&mut self, executor: &E) -> Result<(), SceneBuildError> where E: Executor {
         #[cfg(not(target_arch = "wasm32"))]
         let start_time = Instant::now();
 
-        let bounding_quad = self.built_options.bounding_quad();
         let path_count = self.scene.paths.len();
-        self.listener.send(RenderCommand::Start { bounding_quad, path_count });
+        if path_count > MAX_PATH_COUNT {
+            return Err(SceneBuildError::TooManyPaths { path_count });
+        }
 
+        // Failures are found before any commands are sent, so that listeners never see a scene
+        // that's only partly built.
+        let effective_view_box = self.scene.effective_view_box(self.built_options);
         let PaintInfo {
             data: paint_data,
             metadata: paint_metadata,
-        } = self.scene.build_paint_info();
+        } = self.scene.build_paint_info(&self.built_options.transform_2d(),
+                                        effective_view_box,
+                                        self.built_options.color_space,
+                                        self.built_options.premultiplied_paints)?;
+
+        let bounding_quad = self.built_options.bounding_quad();
+        self.listener.send(RenderCommand::Start { bounding_quad, path_count });
         self.listener.send(RenderCommand::AddPaintData(paint_data));
 
-        let effective_view_box = self.scene.effective_view_box(self.built_options);
-        let alpha_tiles = executor.flatten_into_vector(path_count, |path_index| {
-            self.build_path(path_index,
-                            effective_view_box,
-                            &self.built_options,
-                            &self.scene,
-                            &paint_metadata)
-        });
+        self.flatten_instances();
+        self.classify_clip_tiles();
+        self.tile_stencil_clips(effective_view_box);
+        self.path_stencil_clips = vec![None; path_count];
+        self.path_fill_rules = vec![FillRule::Winding; path_count];
+        self.first_backdrop_filter_path = self.paths.iter().position(|path_object| {
+            path_object.backdrop_filter().is_some()
+        }).unwrap_or(path_count);
+        self.first_segment_path = self.paths.iter().position(|path_object| {
+            self.is_tiled_by_renderer(path_object)
+        }).unwrap_or(path_count);
+        if self.built_options.record_tile_coverage {
+            let view_box_tile_rect = tiles::round_rect_out_to_tile_bounds(effective_view_box);
+            self.tile_coverage = Some(TileCoverage::new(path_count, view_box_tile_rect));
+        }
+
+        // Without streaming, the whole scene is one chunk.
+        let chunk_size = match self.built_options.streaming_chunk_size {
+            None => path_count,
+            Some(chunk_size) => cmp::max(chunk_size, 1),
+        };
 
-        self.finish_building(&paint_metadata, alpha_tiles);
+        let mut chunk_start = 0;
+        while chunk_start < path_count {
+            let chunk_end = cmp::min(chunk_start + chunk_size, path_count);
+            let built_paths = executor.flatten_into_vector(chunk_end - chunk_start, |index| {
+                self.build_path(chunk_start + index,
+                                effective_view_box,
+                                &self.built_options,
+                                &self.scene,
+                                &paint_metadata)
+            });
+
+            let path_range = chunk_start as u32..chunk_end as u32;
+            self.finish_building(&paint_metadata, path_range, built_paths);
+            chunk_start = chunk_end;
+        }
 
         #[cfg(not(target_arch = "wasm32"))]
         let build_time = Instant::now() - start_time;
@@ -89,7 +198,11 @@ impl<'a, L: RenderCommandListener> SceneBuilder<'a, L> {
         #[cfg(target_arch = "wasm32")]
         let build_time = Duration::from_millis(0);
 
+        if let Some(tile_coverage) = self.tile_coverage.take() {
+            self.listener.send(RenderCommand::TileCoverage(Arc::new(tile_coverage)));
+        }
         self.listener.send(RenderCommand::Finish { build_time });
+        Ok(())
     }
 
     fn build_path(
@@ -99,61 +212,619 @@ impl<'a, L: RenderCommandListener> SceneBuilder<'a, L> {
         built_options: &PreparedBuildOptions,
         scene: &Scene,
         paint_metadata: &[PaintMetadata],
-    ) -> Vec<AlphaTileBatchPrimitive> {
-        let path_object = &scene.paths[path_index];
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
+    ) -> Vec<BuiltPath> {
+        let path_object = self.paths[path_index];
+        if path_object.is_culled_by_lod(built_options) {
+            return vec![];
+        }
+
+        let mut stencil_clip = None;
+        let mut fill_rule = path_object.fill_rule();
+        let outline = match rounded_rect_outline(path_object, view_box, built_options) {
+            Some(outline) => outline,
+            None => {
+                let path_transform = path_object.transform_3d();
+                let placed_outline = self.placed_outline(path_object);
+                match path_object.clip_path() {
+                    None => {
+                        scene.apply_render_options(&placed_outline, path_transform, built_options)
+                    }
+                    Some(clip_path_id) => {
+                        let clip_tiles = self.clip_tiles[clip_path_id.0 as usize].as_ref();
+                        match clip_tiles.unwrap().coverage(placed_outline.bounds()) {
+                            ClipTileCoverage::Outside => return vec![],
+                            ClipTileCoverage::Inside => {
+                                scene.apply_render_options(&placed_outline,
+                                                           path_transform,
+                                                           built_options)
+                            }
+                            ClipTileCoverage::Partial if path_transform.is_none() &&
+                                    self.stencil_clips[clip_path_id.0 as usize].is_some() => {
+                                stencil_clip = Some(clip_path_id);
+                                scene.apply_render_options(&placed_outline, None, built_options)
+                            }
+                            ClipTileCoverage::Partial => {
+                                let clip_path = scene.clip_path(clip_path_id);
+                                let (clipped_outline, clipped_fill_rule) =
+                                    clip_path.clip_for_tiling(&placed_outline, fill_rule);
+                                fill_rule = clipped_fill_rule;
+                                scene.apply_render_options(&clipped_outline,
+                                                           path_transform,
+                                                           built_options)
+                            }
+                        }
+                    }
+                }
+            }
+        };
         let paint_id = path_object.paint();
 
+        if self.is_tiled_by_renderer(path_object) {
+            let built_segments = match BuiltSegments::new(outline, view_box) {
+                None => return vec![],
+                Some(built_segments) => built_segments,
+            };
+            let tile_rect = built_segments.tile_rect;
+            let tile_count = tile_rect.width() as u32 * tile_rect.height() as u32;
+            let alpha_tile_count = cmp::min(tile_count, built_segments.max_fill_count);
+            return vec![BuiltPath {
+                path_index: path_index as u32,
+                fills: vec![],
+                alpha_tiles: vec![],
+                alpha_tile_count: cmp::min(alpha_tile_count, MAX_ALPHA_TILES_PER_PAGE),
+                stencil_clip: None,
+                fill_rule,
+                touched_tiles: vec![],
+                segments: Some(built_segments),
+            }];
+        }
+
         let mut tiler = Tiler::new(self,
                                    &outline,
                                    view_box,
                                    path_index as u16,
-                                   &paint_metadata[paint_id.0 as usize]);
+                                   &paint_metadata[paint_id.0 as usize],
+                                   path_object.blend_mode(),
+                                   fill_rule);
+        // Solid tiles are drawn before alpha tiles, so a backdrop filter would see the solid tiles
+        // of the paths above it, and its own path must be drawn over the filtered backdrop. The
+        // tiles of paths that the renderer tiles can't be culled, so nothing above them may cover
+        // them with solid tiles either.
+        if stencil_clip.is_some() || path_index >= self.first_backdrop_filter_path ||
+                path_index >= self.first_segment_path {
+            tiler.can_occlude = false;
+        }
 
         tiler.generate_tiles();
 
-        self.listener.send(RenderCommand::AddFills(tiler.built_object.fills));
-        tiler.built_object.alpha_tiles
+        let touched_tiles = if built_options.record_tile_coverage {
+            tiler.touched_tiles()
+        } else {
+            vec![]
+        };
+        let built_object = tiler.built_object;
+        if built_object.alpha_tile_count == MAX_ALPHA_TILES_PER_PAGE {
+            warn!("Path {} may have more alpha tiles than fit in the mask framebuffer!",
+                  path_index);
+        }
+        vec![BuiltPath {
+            path_index: path_index as u32,
+            fills: built_object.fills,
+            alpha_tiles: built_object.alpha_tiles,
+            alpha_tile_count: built_object.alpha_tile_count,
+            stencil_clip,
+            fill_rule,
+            touched_tiles,
+            segments: None,
+        }]
     }
 
-    fn cull_alpha_tiles(&self, alpha_tiles: &mut Vec<AlphaTileBatchPrimitive>) {
-        for alpha_tile in alpha_tiles {
-            let alpha_tile_coords = alpha_tile.tile_coords();
-            if self
-                .z_buffer
-                .test(alpha_tile_coords, alpha_tile.object_index as u32)
-            {
+    // With `gpu_tiling`, paths that don't need the CPU's help are left for the renderer to tile.
+    fn is_tiled_by_renderer(&self, path_object: &PathObject) -> bool {
+        self.built_options.gpu_tiling && !self.built_options.record_tile_coverage &&
+            path_object.clip_path().is_none() && path_object.backdrop_filter().is_none() &&
+            !path_object.blend_mode().is_destructive()
+    }
+
+    // Flattens each outline that instances draw at the same scale once, rather than once per
+    // instance. Flattening commutes with similarity transforms, as long as the tolerance is
+    // scaled too.
+    fn flatten_instances(&mut self) {
+        for path_object in &self.scene.paths {
+            let (key, scale) = match self.instance_flattening_key(path_object) {
+                None => continue,
+                Some(key_and_scale) => key_and_scale,
+            };
+            if !self.flattened_instances.contains_key(&key) {
+                let mut outline = (**path_object.shared_outline()).clone();
+                outline.flatten(tiles::FLATTENING_TOLERANCE / scale);
+                self.flattened_instances.insert(key, outline);
+            }
+        }
+    }
+
+    // Sorts the tiles of each clip path in use into those inside it, those outside it, and those
+    // on its edge, so that paths entirely on one side of it skip clipping.
+    fn classify_clip_tiles(&mut self) {
+        let mut clip_tiles: Vec<Option<ClipTiles>> = vec![];
+        clip_tiles.resize_with(self.scene.clip_paths.len(), || None);
+        for path_object in &self.scene.paths {
+            if let Some(clip_path_id) = path_object.clip_path() {
+                let tiles = &mut clip_tiles[clip_path_id.0 as usize];
+                if tiles.is_none() {
+                    *tiles = Some(ClipTiles::new(self.scene.clip_path(clip_path_id)));
+                }
+            }
+        }
+        self.clip_tiles = clip_tiles;
+    }
+
+    // Tiles the clip paths that paths will be clipped to in the stencil buffer. Paths with 3D
+    // transforms are still clipped on the CPU, since their clips are in a different space.
+    fn tile_stencil_clips(&mut self, view_box: RectF) {
+        let clip_path_count = self.scene.clip_paths.len();
+        let mut stencil_clips: Vec<Option<StencilClip>> = vec![];
+        stencil_clips.resize_with(clip_path_count, || None);
+        self.page_stencil_clips = vec![None; clip_path_count];
+        if !self.built_options.stencil_clip_paths {
+            self.stencil_clips = stencil_clips;
+            return;
+        }
+
+        // Clip tiles only read the mask framebuffer, so their paint is never sampled.
+        let paint_metadata = PaintMetadata {
+            tex_transform: Transform2F::default(),
+            page: PaintPageId(0),
+            is_opaque: false,
+        };
+        for path_object in &self.scene.paths {
+            let clip_path_id = match path_object.clip_path() {
+                Some(clip_path_id) if path_object.transform_3d().is_none() => clip_path_id,
+                _ => continue,
+            };
+            if stencil_clips[clip_path_id.0 as usize].is_some() {
                 continue;
             }
 
-            // FIXME(pcwalton): Clean this up.
-            alpha_tile.tile_x_lo = 0xff;
-            alpha_tile.tile_y_lo = 0xff;
-            alpha_tile.tile_hi = 0xff;
+            // Fills are always accumulated with the nonzero rule.
+            let clip_path = self.scene.clip_path(clip_path_id);
+            let outline = match clip_path.fill_rule() {
+                FillRule::Winding => Cow::Borrowed(clip_path.outline()),
+                FillRule::EvenOdd => {
+                    Cow::Owned(clip_path.outline().boolean_op_with_fill_rules(FillRule::EvenOdd,
+                                                                              &Outline::new(),
+                                                                              FillRule::Winding,
+                                                                              BooleanOp::Union))
+                }
+            };
+            let outline = self.scene.apply_render_options(&outline, None, self.built_options);
+
+            let mut tiler = Tiler::new(self,
+                                       &outline,
+                                       view_box,
+                                       0,
+                                       &paint_metadata,
+                                       BlendMode::default(),
+                                       FillRule::Winding);
+            tiler.can_occlude = false;
+            tiler.generate_tiles();
+            let built_object = tiler.built_object;
+            stencil_clips[clip_path_id.0 as usize] = Some(StencilClip {
+                fills: built_object.fills,
+                alpha_tiles: built_object.alpha_tiles,
+                alpha_tile_count: built_object.alpha_tile_count,
+            });
+        }
+        self.stencil_clips = stencil_clips;
+    }
+
+    fn instance_flattening_key(&self, path_object: &PathObject) -> Option<((usize, u32), f32)> {
+        // Perspective and subpixel AA don't preserve shapes, so they're excluded.
+        if !path_object.is_instance() || path_object.transform_3d().is_some() ||
+                self.built_options.zoom().is_none() {
+            return None;
+        }
+        let transform = self.built_options.transform_2d() * path_object.outline_transform();
+        let scale = similarity_scale(&transform.matrix)?;
+        let address = &**path_object.shared_outline() as *const Outline as usize;
+        Some(((address, scale.to_bits()), scale))
+    }
+
+    // Returns the outline of `path_object` in scene coordinates.
+    fn placed_outline<'b>(&'b self, path_object: &'b PathObject) -> Cow<'b, Outline> {
+        match self.instance_flattening_key(path_object) {
+            None => path_object.transformed_outline(),
+            Some((key, _)) => {
+                let mut outline = self.flattened_instances[&key].clone();
+                outline.transform(&path_object.outline_transform());
+                Cow::Owned(outline)
+            }
         }
     }
 
+    // Occluded tiles are removed outright rather than being sent to the GPU as degenerate
+    // instances, so that the surviving tiles from all paths pack densely into one draw call.
+    fn cull_alpha_tiles(&self, alpha_tiles: &mut Vec<AlphaTileBatchPrimitive>) {
+        let z_buffer = &self.z_buffer;
+        alpha_tiles.retain(|alpha_tile| {
+            z_buffer.test(alpha_tile.tile_coords(), alpha_tile.object_index as u32)
+        });
+    }
+
+    // Each run of tiles that share a blend mode, a fill rule, a backdrop filter, a page of the
+    // paint texture, and a stencil clip becomes one batch. Runs stay in path order, since blending
+    // isn't commutative. A path with a backdrop filter is a batch of its own, so that the filter
+    // sees the paths beneath it.
+    //
+    // The paths that the renderer tiles, whose indices are in `segment_paths`, are batched the
+    // same way in between.
     fn pack_alpha_tiles(&mut self,
                         paint_metadata: &[PaintMetadata],
-                        alpha_tiles: Vec<AlphaTileBatchPrimitive>) {
-        let path_count = self.scene.paths.len() as u32;
-        let solid_tiles = self.z_buffer.build_solid_tiles(&self.scene.paths,
-                                                          paint_metadata,
-                                                          0..path_count);
-        if !solid_tiles.is_empty() {
-            self.listener.send(RenderCommand::SolidTile(solid_tiles));
+                        alpha_tiles: Vec<AlphaTileBatchPrimitive>,
+                        segment_paths: &[u32]) {
+        let mut batch: Vec<AlphaTileBatchPrimitive> = vec![];
+        let (mut batch_blend_mode, mut batch_page) = (BlendMode::default(), PaintPageId(0));
+        let (mut batch_fill_rule, mut batch_clip) = (FillRule::Winding, None);
+        let (mut batch_backdrop_filter, mut batch_path_index) = (None, 0);
+        let mut segment_batch = None;
+        let mut segment_paths = segment_paths.iter().cloned().enumerate().peekable();
+        for alpha_tile in alpha_tiles {
+            let path_index = alpha_tile.object_index as usize;
+            while let Some(&(segment_path, segment_path_index)) = segment_paths.peek() {
+                if segment_path_index as usize > path_index {
+                    break;
+                }
+                segment_paths.next();
+                if !batch.is_empty() {
+                    let tiles = mem::take(&mut batch);
+                    self.send_alpha_tiles(tiles,
+                                          batch_blend_mode,
+                                          batch_fill_rule,
+                                          batch_backdrop_filter,
+                                          batch_page,
+                                          batch_clip);
+                }
+                self.batch_segment_path(&mut segment_batch,
+                                        segment_path as u32,
+                                        segment_path_index,
+                                        paint_metadata);
+            }
+            self.send_segment_alpha_tiles(segment_batch.take());
+
+            let path_object = self.paths[path_index];
+            let blend_mode = path_object.blend_mode();
+            let fill_rule = self.path_fill_rules[path_index];
+            let backdrop_filter = path_object.backdrop_filter();
+            let page = paint_metadata[path_object.paint().0 as usize].page;
+            let clip = self.path_stencil_clips[path_index];
+            let filters_new_path = backdrop_filter.is_some() && path_index != batch_path_index;
+            if (blend_mode != batch_blend_mode || fill_rule != batch_fill_rule ||
+                    backdrop_filter != batch_backdrop_filter || page != batch_page ||
+                    clip != batch_clip || filters_new_path) && !batch.is_empty() {
+                let tiles = mem::replace(&mut batch, vec![]);
+                self.send_alpha_tiles(tiles,
+                                      batch_blend_mode,
+                                      batch_fill_rule,
+                                      batch_backdrop_filter,
+                                      batch_page,
+                                      batch_clip);
+            }
+            batch_blend_mode = blend_mode;
+            batch_fill_rule = fill_rule;
+            batch_backdrop_filter = backdrop_filter;
+            batch_page = page;
+            batch_clip = clip;
+            batch_path_index = path_index;
+            batch.push(alpha_tile);
         }
-        if !alpha_tiles.is_empty() {
-            self.listener.send(RenderCommand::AlphaTile(alpha_tiles));
+        if !batch.is_empty() {
+            self.send_alpha_tiles(batch,
+                                  batch_blend_mode,
+                                  batch_fill_rule,
+                                  batch_backdrop_filter,
+                                  batch_page,
+                                  batch_clip);
         }
+        for (segment_path, segment_path_index) in segment_paths {
+            self.batch_segment_path(&mut segment_batch,
+                                    segment_path as u32,
+                                    segment_path_index,
+                                    paint_metadata);
+        }
+        self.send_segment_alpha_tiles(segment_batch);
     }
 
-    fn finish_building(&mut self,
-                       paint_metadata: &[PaintMetadata],
-                       mut alpha_tiles: Vec<AlphaTileBatchPrimitive>) {
+    // Adds the path at `path_index`, which is `segment_path` among those that the renderer tiles
+    // on this page, to `batch`. If it can't join the batch, the batch is sent first.
+    fn batch_segment_path(&mut self,
+                          batch: &mut Option<SegmentAlphaTileBatch>,
+                          segment_path: u32,
+                          path_index: u32,
+                          paint_metadata: &[PaintMetadata]) {
+        let path_object = self.paths[path_index as usize];
+        let blend_mode = path_object.blend_mode();
+        let fill_rule = self.path_fill_rules[path_index as usize];
+        let page = paint_metadata[path_object.paint().0 as usize].page;
+        if let Some(ref mut batch) = *batch {
+            if batch.blend_mode == blend_mode && batch.fill_rule == fill_rule &&
+                    batch.page == page {
+                batch.segment_paths.end = segment_path + 1;
+                return;
+            }
+        }
+        self.send_segment_alpha_tiles(batch.take());
+        *batch = Some(SegmentAlphaTileBatch {
+            segment_paths: segment_path..(segment_path + 1),
+            blend_mode,
+            fill_rule,
+            page,
+        });
+    }
+
+    fn send_segment_alpha_tiles(&mut self, batch: Option<SegmentAlphaTileBatch>) {
+        if let Some(batch) = batch {
+            self.listener.send(RenderCommand::SegmentAlphaTile(batch.segment_paths,
+                                                               batch.blend_mode,
+                                                               batch.fill_rule,
+                                                               batch.page));
+        }
+    }
+
+    // Sends a batch of alpha tiles, first drawing their clip path into the stencil buffer if it
+    // isn't there already, and then filtering their backdrop if they have a backdrop filter.
+    fn send_alpha_tiles(&mut self,
+                        tiles: Vec<AlphaTileBatchPrimitive>,
+                        blend_mode: BlendMode,
+                        fill_rule: FillRule,
+                        backdrop_filter: Option<BackdropFilter>,
+                        page: PaintPageId,
+                        clip: Option<ClipPathId>) {
+        let clip_path_id = match clip {
+            None => {
+                if let Some(backdrop_filter) = backdrop_filter {
+                    self.listener.send(RenderCommand::BackdropFilter(tiles.clone(),
+                                                                     backdrop_filter,
+                                                                     fill_rule));
+                }
+                self.listener.send(RenderCommand::AlphaTile(tiles, blend_mode, fill_rule, page));
+                return;
+            }
+            Some(clip_path_id) => clip_path_id,
+        };
+        if self.current_stencil_clip != Some(clip_path_id) {
+            let clip_tiles = self.page_stencil_clips[clip_path_id.0 as usize].clone();
+            self.listener.send(RenderCommand::ClipStencil(clip_tiles.unwrap()));
+            self.current_stencil_clip = Some(clip_path_id);
+        }
+        if let Some(backdrop_filter) = backdrop_filter {
+            self.listener.send(RenderCommand::ClippedBackdropFilter(tiles.clone(),
+                                                                    backdrop_filter,
+                                                                    fill_rule));
+        }
+        self.listener.send(RenderCommand::ClippedAlphaTile(tiles, blend_mode, fill_rule, page));
+    }
+
+    // Gives a stencil clip consecutive places in the current page of the mask framebuffer and
+    // sends its fills.
+    fn place_stencil_clip(&mut self, clip_path_id: ClipPathId) {
+        let stencil_clip = self.stencil_clips[clip_path_id.0 as usize].as_ref().unwrap();
+        let mut fills = stencil_clip.fills.clone();
+        let mut alpha_tiles = stencil_clip.alpha_tiles.clone();
+        let first_alpha_tile_index = self.next_alpha_tile_index as u16;
+        self.next_alpha_tile_index += stencil_clip.alpha_tile_count;
+        offset_alpha_tile_indices(&mut fills, &mut alpha_tiles, first_alpha_tile_index);
+
+        if !fills.is_empty() {
+            self.listener.send(RenderCommand::AddFills(fills));
+        }
+        self.page_stencil_clips[clip_path_id.0 as usize] = Some(alpha_tiles);
+    }
+
+    // Rasterizes the fills sent so far, along with those of the paths in `segment_batch`, into
+    // the mask framebuffer and draws the alpha tiles that read them.
+    fn flush_alpha_tiles(&mut self,
+                         paint_metadata: &[PaintMetadata],
+                         mut alpha_tiles: Vec<AlphaTileBatchPrimitive>,
+                         segment_batch: SegmentBatch) {
+        let segment_paths: Vec<u32> = segment_batch.paths.iter().map(|segment_path| {
+            segment_path.object_index as u32
+        }).collect();
+        if !segment_paths.is_empty() {
+            self.listener.send(RenderCommand::AddSegments(segment_batch));
+        }
         self.listener.send(RenderCommand::FlushFills);
         self.cull_alpha_tiles(&mut alpha_tiles);
-        self.pack_alpha_tiles(paint_metadata, alpha_tiles);
+        self.pack_alpha_tiles(paint_metadata, alpha_tiles, &segment_paths);
+    }
+
+    // Sends the tiles for the paths in `path_range`, all of which must have been tiled. Only
+    // paths tiled so far can occlude them, and later chunks are drawn over them.
+    //
+    // Each path's alpha tiles are numbered from zero, and here they're given consecutive places in
+    // the mask framebuffer. When it fills up, the tiles so far are drawn, and the framebuffer is
+    // cleared and reused for the rest, so that large scenes cost extra passes instead of wrapping
+    // tile indices around onto tiles that are still in use.
+    fn finish_building(&mut self,
+                       paint_metadata: &[PaintMetadata],
+                       path_range: Range<u32>,
+                       built_paths: Vec<BuiltPath>) {
+        let solid_tiles = self.z_buffer.build_solid_tiles(&self.paths,
+                                                          paint_metadata,
+                                                          path_range);
+        // Solid tiles are opaque and never overlap, so they can be drawn in any order, in one
+        // batch per page of the paint texture.
+        let mut solid_tile_batches: Vec<Vec<SolidTileBatchPrimitive>> = vec![];
+        for solid_tile in solid_tiles {
+            let path_object = self.paths[solid_tile.object_index as usize];
+            let page_index = paint_metadata[path_object.paint().0 as usize].page.0 as usize;
+            if page_index >= solid_tile_batches.len() {
+                solid_tile_batches.resize_with(page_index + 1, Vec::new);
+            }
+            solid_tile_batches[page_index].push(solid_tile);
+        }
+        for (page_index, solid_tiles) in solid_tile_batches.into_iter().enumerate() {
+            if !solid_tiles.is_empty() {
+                let page = PaintPageId(page_index as u32);
+                self.listener.send(RenderCommand::SolidTile(solid_tiles, page));
+            }
+        }
+
+        let mut alpha_tiles = vec![];
+        let mut segment_batch = SegmentBatch::default();
+        for mut built_path in built_paths {
+            // A stencil clip goes on the page along with the first path there that it clips.
+            let stencil_clip = built_path.stencil_clip.filter(|_| {
+                !built_path.alpha_tiles.is_empty()
+            });
+            let mut new_stencil_clip = stencil_clip.filter(|clip_path_id| {
+                self.page_stencil_clips[clip_path_id.0 as usize].is_none()
+            });
+            if self.next_alpha_tile_index + built_path.alpha_tile_count +
+                    self.stencil_clip_tile_count(new_stencil_clip) > MAX_ALPHA_TILES_PER_PAGE {
+                let page_alpha_tiles = mem::replace(&mut alpha_tiles, vec![]);
+                let page_segment_batch = mem::take(&mut segment_batch);
+                self.flush_alpha_tiles(paint_metadata, page_alpha_tiles, page_segment_batch);
+                self.listener.send(RenderCommand::ClearMaskFramebuffer);
+                self.next_alpha_tile_index = 0;
+                for page_stencil_clip in &mut self.page_stencil_clips {
+                    *page_stencil_clip = None;
+                }
+                new_stencil_clip = stencil_clip;
+            }
+            if let Some(clip_path_id) = new_stencil_clip {
+                self.place_stencil_clip(clip_path_id);
+            }
+            self.path_stencil_clips[built_path.path_index as usize] = stencil_clip;
+            self.path_fill_rules[built_path.path_index as usize] = built_path.fill_rule;
+            if let Some(ref mut tile_coverage) = self.tile_coverage {
+                let scene_path_index = self.scene_path_indices[built_path.path_index as usize];
+                tile_coverage.set_path_tiles(scene_path_index as usize,
+                                             mem::replace(&mut built_path.touched_tiles, vec![]));
+            }
+
+            let first_alpha_tile_index = self.next_alpha_tile_index as u16;
+            self.next_alpha_tile_index += built_path.alpha_tile_count;
+            if let Some(built_segments) = built_path.segments {
+                let path_object = self.paths[built_path.path_index as usize];
+                let paint = &paint_metadata[path_object.paint().0 as usize];
+                let first_segment = segment_batch.segments.len() as u32;
+                segment_batch.segments.extend(built_segments.segments);
+                segment_batch.paths.push(SegmentPath {
+                    segments: first_segment..(segment_batch.segments.len() as u32),
+                    tile_rect: built_segments.tile_rect,
+                    first_alpha_tile_index,
+                    alpha_tile_count: built_path.alpha_tile_count as u16,
+                    max_fill_count: built_segments.max_fill_count,
+                    object_index: built_path.path_index as u16,
+                    fill_rule: built_path.fill_rule,
+                    tex_transform: paint.tex_transform,
+                });
+                continue;
+            }
+            offset_alpha_tile_indices(&mut built_path.fills,
+                                      &mut built_path.alpha_tiles,
+                                      first_alpha_tile_index);
+
+            if !built_path.fills.is_empty() {
+                self.listener.send(RenderCommand::AddFills(built_path.fills));
+            }
+            alpha_tiles.extend(built_path.alpha_tiles);
+        }
+        self.flush_alpha_tiles(paint_metadata, alpha_tiles, segment_batch);
+    }
+
+    fn stencil_clip_tile_count(&self, clip: Option<ClipPathId>) -> u32 {
+        match clip {
+            None => 0,
+            Some(clip_path_id) => {
+                self.stencil_clips[clip_path_id.0 as usize].as_ref().unwrap().alpha_tile_count
+            }
+        }
+    }
+}
+
+// The fills and alpha tiles of one path, with alpha tile indices counted from zero.
+struct BuiltPath {
+    path_index: u32,
+    fills: Vec<FillBatchPrimitive>,
+    alpha_tiles: Vec<AlphaTileBatchPrimitive>,
+    alpha_tile_count: u32,
+    // The clip path to draw this path inside with the stencil buffer, if it wasn't clipped on the
+    // CPU.
+    stencil_clip: Option<ClipPathId>,
+    // The fill rule of the outline that was tiled. Paths clipped on the CPU become nonzero.
+    fill_rule: FillRule,
+    // The tiles that this path draws to, with `record_tile_coverage`.
+    touched_tiles: Vec<Vector2I>,
+    // With `gpu_tiling`, the segments that the renderer tiles in place of the fills and alpha
+    // tiles, which are empty. The alpha tile count is the most tiles they can need.
+    segments: Option<BuiltSegments>,
+}
+
+// The outline of a path flattened into line segments, for the renderer to tile.
+struct BuiltSegments {
+    segments: Vec<LineSegment2F>,
+    tile_rect: RectI,
+    max_fill_count: u32,
+}
+
+impl BuiltSegments {
+    fn new(mut outline: Outline, view_box: RectF) -> Option<BuiltSegments> {
+        let bounds = outline.bounds().intersection(view_box)?;
+        let tile_rect = tiles::round_rect_out_to_tile_bounds(bounds);
+        if tile_rect.width() <= 0 || tile_rect.height() <= 0 {
+            return None;
+        }
+
+        // Contours are filled as if they were closed, whether or not they are.
+        outline.flatten(tiles::FLATTENING_TOLERANCE);
+        let (mut segments, mut max_fill_count) = (vec![], 0u32);
+        for contour in outline.contours() {
+            for point_index in 0..contour.len() {
+                let from = contour.position_of(point_index);
+                let to = contour.position_of((point_index + 1) % contour.len());
+                if from == to {
+                    continue;
+                }
+                let segment = LineSegment2F::new(from, to);
+                max_fill_count = max_fill_count.saturating_add(binning::max_fill_count(segment));
+                segments.push(segment);
+            }
+        }
+        Some(BuiltSegments { segments, tile_rect, max_fill_count })
+    }
+}
+
+// A run of paths tiled by the renderer, by their indices among those on the page, that are drawn
+// with one `SegmentAlphaTile`.
+struct SegmentAlphaTileBatch {
+    segment_paths: Range<u32>,
+    blend_mode: BlendMode,
+    fill_rule: FillRule,
+    page: PaintPageId,
+}
+
+// The fills and alpha tiles of a clip path tiled for the stencil buffer, with alpha tile indices
+// counted from zero.
+struct StencilClip {
+    fills: Vec<FillBatchPrimitive>,
+    alpha_tiles: Vec<AlphaTileBatchPrimitive>,
+    alpha_tile_count: u32,
+}
+
+// Moves the alpha tiles of a path or clip, numbered from zero, to the places in the mask
+// framebuffer starting at `first_alpha_tile_index`.
+fn offset_alpha_tile_indices(fills: &mut [FillBatchPrimitive],
+                             alpha_tiles: &mut [AlphaTileBatchPrimitive],
+                             first_alpha_tile_index: u16) {
+    for fill in fills {
+        fill.alpha_tile_index += first_alpha_tile_index;
+    }
+    for alpha_tile in alpha_tiles {
+        // Tiles without fills keep reading the last tile, which is never allocated.
+        if alpha_tile.tile_index != !0 {
+            alpha_tile.tile_index += first_alpha_tile_index;
+        }
     }
 }
 
@@ -163,6 +834,42 @@ pub struct TileStats {
     pub alpha_tile_count: u32,
 }
 
+// Returns the device-space outline of a rounded rectangle that needs no clipping, with its corners
+// flattened directly, or `None` if the path must go through `Scene::apply_render_options()`.
+fn rounded_rect_outline(path_object: &PathObject,
+                        view_box: RectF,
+                        built_options: &PreparedBuildOptions)
+                        -> Option<Outline> {
+    let rounded_rect = path_object.rounded_rect()?;
+    if path_object.clip_path().is_some() || path_object.transform_3d().is_some() ||
+            built_options.zoom().is_none() || built_options.subpixel_aa_enabled ||
+            !built_options.dilation.is_zero() {
+        return None;
+    }
+
+    let transform = built_options.transform_2d() * path_object.outline_transform();
+    let rounded_rect = rounded_rect.transform(&transform)?;
+    if !view_box.contains_rect(rounded_rect.rect()) {
+        return None;
+    }
+    Some(rounded_rect.to_flattened_outline(tiles::FLATTENING_TOLERANCE))
+}
+
+// Returns the factor by which `matrix` scales lengths, if it's a rotation, possibly with a
+// reflection, and a uniform scale.
+fn similarity_scale(matrix: &Matrix2x2F) -> Option<f32> {
+    const EPSILON: f32 = 0.0001;
+    let (m11, m12, m21, m22) = (matrix.m11(), matrix.m12(), matrix.m21(), matrix.m22());
+    let rotation = f32::abs(m11 - m22) < EPSILON && f32::abs(m12 + m21) < EPSILON;
+    let reflection = f32::abs(m11 + m22) < EPSILON && f32::abs(m12 - m21) < EPSILON;
+    let scale = f32::sqrt(f32::abs(matrix.det()));
+    if (rotation || reflection) && scale > 0.0 {
+        Some(scale)
+    } else {
+        None
+    }
+}
+
 // Utilities for built objects
 
 impl BuiltObject {
@@ -173,6 +880,7 @@ impl BuiltObject {
             bounds,
             fills: vec![],
             alpha_tiles: vec![],
+            alpha_tile_count: 0,
             tiles,
         }
     }
@@ -182,9 +890,8 @@ impl BuiltObject {
         self.tiles.rect
     }
 
-    fn add_fill<L: RenderCommandListener>(
+    fn add_fill(
         &mut self,
-        builder: &SceneBuilder<L>,
         segment: LineSegment2F,
         tile_coords: Vector2I,
     ) {
@@ -205,6 +912,46 @@ impl BuiltObject {
         let segment = (segment.0 - tile_upper_left) * F32x4::splat(256.0);
         let (min, max) = (F32x4::default(), F32x4::splat((TILE_WIDTH * 256 - 1) as f32));
         let segment = segment.clamp(min, max).to_i32x4();
+
+        // Pack whole pixels.
+        let px = (segment & I32x4::splat(0xf00)).to_u32x4();
+        let px = (px >> 8).to_i32x4() | (px >> 4).to_i32x4().yxwz();
+
+        self.push_fill(segment, px, tile_coords);
+    }
+
+    // Adds two fills at once. With AVX2, both are converted to fixed point together.
+    fn add_fill_pair(
+        &mut self,
+        (segment_a, tile_coords_a): (LineSegment2F, Vector2I),
+        (segment_b, tile_coords_b): (LineSegment2F, Vector2I),
+    ) {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if avx::is_supported() &&
+                    self.tile_coords_to_local_index(tile_coords_a).is_some() &&
+                    self.tile_coords_to_local_index(tile_coords_b).is_some() {
+                let (segment_a, px_a, segment_b, px_b) = unsafe {
+                    fixed_point_fill_pair_avx2(segment_a, tile_coords_a, segment_b, tile_coords_b)
+                };
+                self.push_fill(segment_a, px_a, tile_coords_a);
+                self.push_fill(segment_b, px_b, tile_coords_b);
+                return;
+            }
+        }
+
+        self.add_fill(segment_a, tile_coords_a);
+        self.add_fill(segment_b, tile_coords_b);
+    }
+
+    // Pushes a fill that has been converted to 4.8 fixed point, with whole pixels packed into
+    // `px`. The tile must be in bounds.
+    fn push_fill(
+        &mut self,
+        segment: I32x4,
+        px: I32x4,
+        tile_coords: Vector2I,
+    ) {
         let (from_x, from_y, to_x, to_y) = (segment[0], segment[1], segment[2], segment[3]);
 
         // Cull degenerate fills.
@@ -213,12 +960,12 @@ impl BuiltObject {
             return;
         }
 
-        // Allocate global tile if necessary.
-        let alpha_tile_index = self.get_or_allocate_alpha_tile_index(builder, tile_coords);
-
-        // Pack whole pixels.
-        let px = (segment & I32x4::splat(0xf00)).to_u32x4();
-        let px = (px >> 8).to_i32x4() | (px >> 4).to_i32x4().yxwz();
+        // Allocate a tile if necessary. If this path alone has more alpha tiles than the mask
+        // framebuffer can hold, the fills of the rest are dropped.
+        let alpha_tile_index = match self.get_or_allocate_alpha_tile_index(tile_coords) {
+            None => return,
+            Some(alpha_tile_index) => alpha_tile_index,
+        };
 
         // Pack instance data.
         debug!("... OK, pushing");
@@ -234,27 +981,30 @@ impl BuiltObject {
         });
     }
 
-    fn get_or_allocate_alpha_tile_index<L: RenderCommandListener>(
+    fn get_or_allocate_alpha_tile_index(
         &mut self,
-        builder: &SceneBuilder<L>,
         tile_coords: Vector2I,
-    ) -> u16 {
+    ) -> Option<u16> {
         let local_tile_index = self.tiles.coords_to_index_unchecked(tile_coords);
         let alpha_tile_index = self.tiles.data[local_tile_index].alpha_tile_index;
         if alpha_tile_index != !0 {
-            return alpha_tile_index;
+            return Some(alpha_tile_index);
         }
 
-        let alpha_tile_index = builder
-            .next_alpha_tile_index
-            .fetch_add(1, Ordering::Relaxed) as u16;
+        if self.alpha_tile_count == MAX_ALPHA_TILES_PER_PAGE {
+            return None;
+        }
+
+        // Indices are counted from zero for each path and offset into a page of the mask
+        // framebuffer in `SceneBuilder::finish_building()`.
+        let alpha_tile_index = self.alpha_tile_count as u16;
+        self.alpha_tile_count += 1;
         self.tiles.data[local_tile_index].alpha_tile_index = alpha_tile_index;
-        alpha_tile_index
+        Some(alpha_tile_index)
     }
 
-    pub(crate) fn add_active_fill<L: RenderCommandListener>(
+    pub(crate) fn add_active_fill(
         &mut self,
-        builder: &SceneBuilder<L>,
         left: f32,
         right: f32,
         mut winding: i32,
@@ -279,7 +1029,7 @@ impl BuiltObject {
         );
 
         while winding != 0 {
-            self.add_fill(builder, segment, tile_coords);
+            self.add_fill(segment, tile_coords);
             if winding < 0 {
                 winding += 1
             } else {
@@ -288,9 +1038,8 @@ impl BuiltObject {
         }
     }
 
-    pub(crate) fn generate_fill_primitives_for_line<L: RenderCommandListener>(
+    pub(crate) fn generate_fill_primitives_for_line(
         &mut self,
-        builder: &SceneBuilder<L>,
         mut segment: LineSegment2F,
         tile_y: i32,
     ) {
@@ -320,6 +1069,7 @@ impl BuiltObject {
             self.tile_rect()
         );
 
+        let mut pending_fill = None;
         for subsegment_tile_x in segment_tile_left..segment_tile_right {
             let (mut fill_from, mut fill_to) = (segment.from(), segment.to());
             let subsegment_tile_right =
@@ -336,9 +1086,17 @@ impl BuiltObject {
                 }
             }
 
-            let fill_segment = LineSegment2F::new(fill_from, fill_to);
-            let fill_tile_coords = Vector2I::new(subsegment_tile_x, tile_y);
-            self.add_fill(builder, fill_segment, fill_tile_coords);
+            // Fills are added in pairs, so that they can be converted together.
+            let fill = (LineSegment2F::new(fill_from, fill_to),
+                        Vector2I::new(subsegment_tile_x, tile_y));
+            match pending_fill.take() {
+                None => pending_fill = Some(fill),
+                Some(pending_fill) => self.add_fill_pair(pending_fill, fill),
+            }
+        }
+
+        if let Some((fill_segment, fill_tile_coords)) = pending_fill {
+            self.add_fill(fill_segment, fill_tile_coords);
         }
     }
 
@@ -352,3 +1110,274 @@ impl BuiltObject {
         self.tiles.index_to_coords(tile_index as usize)
     }
 }
+
+// Converts two fills to 4.8 fixed point and packs their whole pixels, as `add_fill()` does for
+// one. Both tiles must be in bounds.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn fixed_point_fill_pair_avx2(segment_a: LineSegment2F,
+                                     tile_coords_a: Vector2I,
+                                     segment_b: LineSegment2F,
+                                     tile_coords_b: Vector2I)
+                                     -> (I32x4, I32x4, I32x4, I32x4) {
+    debug_assert_eq!(TILE_WIDTH, TILE_HEIGHT);
+
+    let (tile_a, tile_b) = (tile_coords_a.to_f32(), tile_coords_b.to_f32());
+    let tile_upper_left = F32x8::new(tile_a.x(), tile_a.y(), tile_a.x(), tile_a.y(),
+                                     tile_b.x(), tile_b.y(), tile_b.x(), tile_b.y()) *
+        F32x8::splat(TILE_WIDTH as f32);
+
+    let (from_a, to_a, from_b, to_b) =
+        (segment_a.from(), segment_a.to(), segment_b.from(), segment_b.to());
+    let segments = F32x8::new(from_a.x(), from_a.y(), to_a.x(), to_a.y(),
+                              from_b.x(), from_b.y(), to_b.x(), to_b.y());
+
+    let segments = (segments - tile_upper_left) * F32x8::splat(256.0);
+    let (min, max) = (F32x8::splat(0.0), F32x8::splat((TILE_WIDTH * 256 - 1) as f32));
+    let segments = segments.clamp(min, max).to_i32x8();
+
+    let px = segments & I32x8::splat(0xf00);
+    let px = (px >> 8) | (px >> 4).yxwz();
+
+    (I32x4::new(segments[0], segments[1], segments[2], segments[3]),
+     I32x4::new(px[0], px[1], px[2], px[3]),
+     I32x4::new(segments[4], segments[5], segments[6], segments[7]),
+     I32x4::new(px[4], px[5], px[6], px[7]))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::executor::{Executor, SequentialExecutor, ThreadPool};
+    use crate::concurrent::executor::ThreadPoolExecutor;
+    use crate::concurrent::rayon::RayonExecutor;
+    use crate::gpu_data::RenderCommand;
+    use crate::options::BuildOptions;
+    use crate::paint::Paint;
+    use crate::scene::{ClipPath, PathObject, Scene};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::BackdropFilter;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use std::sync::Mutex;
+
+    // Starts jobs in reverse order, on whichever Rayon threads pick them up.
+    struct ReversedThreadPool;
+
+    impl ThreadPool for ReversedThreadPool {
+        fn run(&self, job_count: usize, job: &(dyn Fn(usize) + Sync)) {
+            rayon::scope(|scope| {
+                for index in (0..job_count).rev() {
+                    scope.spawn(move |_| job(index));
+                }
+            });
+        }
+    }
+
+    // Overlapping triangles, some opaque and some translucent, so that tiles are both culled and
+    // blended.
+    fn build_scene() -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(512.0)));
+        let mut seed: u32 = 1;
+        let mut random = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        for path_index in 0..300 {
+            let mut contour = Contour::new();
+            for _ in 0..3 {
+                contour.push_endpoint(Vector2F::new(random(), random()).scale(512.0));
+            }
+            contour.close();
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+
+            let alpha = if path_index % 3 == 0 { 128 } else { 255 };
+            let color = ColorU { r: path_index as u8, g: 64, b: 192, a: alpha };
+            let paint_id = scene.push_paint(&Paint::Color(color));
+            scene.push_path(PathObject::new(outline, paint_id, String::new()));
+        }
+        scene
+    }
+
+    fn build_commands<E>(scene: &Scene, options: BuildOptions, executor: &E) -> Vec<String>
+                         where E: Executor {
+        let commands = Mutex::new(vec![]);
+        scene.build(options, |command| {
+            let description = match command {
+                RenderCommand::AddFills(fills) => format!("AddFills({:?})", fills),
+                RenderCommand::AlphaTile(tiles, blend_mode, fill_rule, page) => {
+                    format!("AlphaTile({:?}, {:?}, {:?}, {:?})", tiles, blend_mode, fill_rule, page)
+                }
+                RenderCommand::SolidTile(tiles, page) => {
+                    format!("SolidTile({:?}, {:?})", tiles, page)
+                }
+                // Build times differ from run to run.
+                RenderCommand::Finish { .. } => return,
+                command => format!("{:?}", command),
+            };
+            commands.lock().unwrap().push(description);
+        }, executor).unwrap();
+        commands.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_parallel_building_is_deterministic() {
+        let scene = build_scene();
+        for &streaming_chunk_size in &[None, Some(7)] {
+            let options = BuildOptions { streaming_chunk_size, ..BuildOptions::default() };
+            let expected = build_commands(&scene, options.clone(), &SequentialExecutor);
+            for _ in 0..4 {
+                assert_eq!(build_commands(&scene, options.clone(), &RayonExecutor), expected);
+                let executor = ThreadPoolExecutor(ReversedThreadPool);
+                assert_eq!(build_commands(&scene, options.clone(), &executor), expected);
+            }
+        }
+    }
+
+    fn rect_outline(rect: RectF) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(rect.origin());
+        contour.push_endpoint(rect.upper_right());
+        contour.push_endpoint(rect.lower_right());
+        contour.push_endpoint(rect.lower_left());
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    #[test]
+    fn test_backdrop_filter_precedes_its_path() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(128.0)));
+        let opaque = scene.push_paint(&Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        let translucent = scene.push_paint(&Paint::Color(ColorU { r: 255, g: 255, b: 255, a: 64 }));
+        let backdrop_rect = RectF::new(Vector2F::default(), Vector2F::splat(128.0));
+        scene.push_path(PathObject::new(rect_outline(backdrop_rect), opaque, String::new()));
+        let panel_rect = RectF::new(Vector2F::splat(16.0), Vector2F::splat(96.0));
+        let mut panel = PathObject::new(rect_outline(panel_rect), translucent, String::new());
+        panel.set_backdrop_filter(Some(BackdropFilter { blur_std_dev: 4.0, saturation: 1.0 }));
+        scene.push_path(panel);
+        let top_rect = RectF::new(Vector2F::splat(32.0), Vector2F::splat(64.0));
+        scene.push_path(PathObject::new(rect_outline(top_rect), opaque, String::new()));
+
+        let commands = Mutex::new(vec![]);
+        scene.build(BuildOptions::default(), |command| {
+            commands.lock().unwrap().push(command)
+        }, &SequentialExecutor).unwrap();
+        let commands = commands.into_inner().unwrap();
+
+        let filter_index = commands.iter().position(|command| {
+            matches!(*command, RenderCommand::BackdropFilter(..))
+        }).unwrap();
+        match (&commands[filter_index], &commands[filter_index + 1]) {
+            (RenderCommand::BackdropFilter(filter_tiles, _, _),
+             RenderCommand::AlphaTile(path_tiles, _, _, _)) => {
+                assert_eq!(filter_tiles.len(), 36);
+                assert_eq!(path_tiles.len(), 36);
+            }
+            _ => panic!("the backdrop filter wasn't followed by its path"),
+        }
+
+        // The path above the filter can't occlude, or the filter would miss what it covers.
+        match commands[filter_index + 2] {
+            RenderCommand::AlphaTile(ref tiles, _, _, _) => assert_eq!(tiles.len(), 16),
+            _ => panic!("the path above the backdrop filter wasn't drawn with alpha tiles"),
+        }
+    }
+
+    fn polygon_outline(points: &[(f32, f32)]) -> Outline {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(Vector2F::new(x, y));
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    #[test]
+    fn test_convex_clips_keep_curves_and_fill_rule() {
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(Vector2F::splat(10.0)));
+        let mut outline = Outline::new();
+        outline.push_contour(circle);
+
+        // Both orientations of a convex polygon take the polygon clipper.
+        let mut triangle = vec![(0.0, -20.0), (16.0, 20.0), (-16.0, 20.0)];
+        for _ in 0..2 {
+            let clip_path = ClipPath::new(polygon_outline(&triangle), FillRule::Winding);
+            let (clipped, fill_rule) = clip_path.clip_for_tiling(&outline, FillRule::EvenOdd);
+            assert_eq!(fill_rule, FillRule::EvenOdd);
+            let contour = &clipped.contours()[0];
+            assert!((0..contour.len()).any(|point_index| !contour.point_is_endpoint(point_index)));
+            assert!(clipped.bounds().max_x() < 9.9);
+            triangle.reverse();
+        }
+
+        // A concave polygon and a pentagram, whose turns all go the same way, are intersected.
+        let concave = [(-20.0, -20.0), (20.0, -20.0), (20.0, 0.0), (0.0, 0.0), (0.0, 20.0),
+                       (-20.0, 20.0)];
+        let pentagram = [(0.0, -20.0), (12.0, 16.0), (-19.0, -6.0), (19.0, -6.0), (-12.0, 16.0)];
+        for points in &[&concave[..], &pentagram[..]] {
+            let clip_path = ClipPath::new(polygon_outline(points), FillRule::EvenOdd);
+            let (_, fill_rule) = clip_path.clip_for_tiling(&outline, FillRule::EvenOdd);
+            assert_eq!(fill_rule, FillRule::Winding);
+        }
+    }
+
+    #[test]
+    fn test_gpu_tiling_leaves_unclipped_paths_to_the_renderer() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(128.0)));
+        let paint = scene.push_paint(&Paint::Color(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        let clip_rect = RectF::new(Vector2F::splat(8.0), Vector2F::splat(64.0));
+        let clip_path = scene.push_clip_path(ClipPath::new(rect_outline(clip_rect),
+                                                           FillRule::Winding));
+        let rect = RectF::new(Vector2F::splat(4.0), Vector2F::splat(100.0));
+        scene.push_path(PathObject::new(rect_outline(rect), paint, String::new()));
+        let mut clipped = PathObject::new(rect_outline(rect), paint, String::new());
+        clipped.set_clip_path(Some(clip_path));
+        scene.push_path(clipped);
+        let mut even_odd = PathObject::new(rect_outline(rect), paint, String::new());
+        even_odd.set_fill_rule(FillRule::EvenOdd);
+        scene.push_path(even_odd);
+
+        let commands = Mutex::new(vec![]);
+        let options = BuildOptions { gpu_tiling: true, ..BuildOptions::default() };
+        scene.build(options, |command| commands.lock().unwrap().push(command), &SequentialExecutor)
+             .unwrap();
+        let commands = commands.into_inner().unwrap();
+
+        let segment_batch = commands.iter().find_map(|command| {
+            match *command {
+                RenderCommand::AddSegments(ref segment_batch) => Some(segment_batch),
+                _ => None,
+            }
+        }).unwrap();
+        let object_indices: Vec<u16> = segment_batch.paths.iter().map(|segment_path| {
+            segment_path.object_index
+        }).collect();
+        assert_eq!(object_indices, [0, 2]);
+        assert_eq!(segment_batch.segments.len(), 8);
+
+        // The clipped path is drawn between the others, which differ in fill rule.
+        let draws: Vec<String> = commands.iter().filter_map(|command| {
+            match *command {
+                RenderCommand::AlphaTile(..) => Some("AlphaTile".to_owned()),
+                RenderCommand::SegmentAlphaTile(ref paths, _, fill_rule, _) => {
+                    Some(format!("SegmentAlphaTile({:?}, {:?})", paths, fill_rule))
+                }
+                _ => None,
+            }
+        }).collect();
+        assert_eq!(draws, ["SegmentAlphaTile(0..1, Winding)",
+                           "AlphaTile",
+                           "SegmentAlphaTile(1..2, EvenOdd)"]);
+    }
+}
@@ -0,0 +1,204 @@
+// pathfinder/renderer/src/clip.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Classifies the tiles of a clip path as inside it, outside it, or on its edge.
+//!
+//! Clipping an outline is expensive, so paths that only cover tiles entirely on one side of their
+//! clip path are either drawn unclipped or culled instead.
+
+use crate::scene::ClipPath;
+use crate::tile_map::DenseTileMap;
+use pathfinder_content::fill::FillRule;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::cmp::Ordering;
+
+// Clip paths are in scene space, so their tiles are sized relative to the clip path's bounds
+// rather than to the tiles of the framebuffer. This is the number of tiles along the longer side
+// of the bounds.
+const CLIP_TILES_ACROSS: f32 = 128.0;
+
+// The flattening tolerance, as a fraction of the tile size.
+const FLATTENING_TOLERANCE: f32 = 0.25;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum ClipTileCoverage {
+    Outside,
+    Inside,
+    Partial,
+}
+
+impl Default for ClipTileCoverage {
+    #[inline]
+    fn default() -> ClipTileCoverage {
+        ClipTileCoverage::Outside
+    }
+}
+
+impl ClipTileCoverage {
+    #[inline]
+    fn combine(self, other: ClipTileCoverage) -> ClipTileCoverage {
+        if self == other { self } else { ClipTileCoverage::Partial }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ClipTiles {
+    origin: Vector2F,
+    tile_size: f32,
+    tiles: DenseTileMap<ClipTileCoverage>,
+}
+
+impl ClipTiles {
+    pub(crate) fn new(clip_path: &ClipPath) -> ClipTiles {
+        let bounds = clip_path.outline().bounds();
+        let tile_size = f32::max(bounds.width(), bounds.height()) / CLIP_TILES_ACROSS;
+        if tile_size <= 0.0 {
+            // The clip path has no area, so everything is outside it.
+            let tiles = DenseTileMap::new(RectI::default());
+            return ClipTiles { origin: bounds.origin(), tile_size: 1.0, tiles };
+        }
+
+        let tile_count = bounds.size().scale(1.0 / tile_size).ceil().to_i32();
+        let tile_count = tile_count.max(Vector2I::splat(1));
+        let tiles = DenseTileMap::new(RectI::new(Vector2I::default(), tile_count));
+        let mut clip_tiles = ClipTiles { origin: bounds.origin(), tile_size, tiles };
+
+        let mut outline = clip_path.outline().clone();
+        let tolerance = tile_size * FLATTENING_TOLERANCE;
+        outline.flatten(tolerance);
+        let mut lines = vec![];
+        for contour in outline.contours() {
+            lines.extend(contour.iter().map(|segment| segment.baseline));
+            if !contour.is_closed() && contour.len() > 1 {
+                let last_position = contour.position_of(contour.len() - 1);
+                lines.push(LineSegment2F::new(last_position, contour.position_of(0)));
+            }
+        }
+
+        // Flattened lines stray up to `tolerance` from the curves, and the tiles they touch are
+        // marked as partial. The winding number is constant across each remaining tile, so it's
+        // found at the center.
+        for &line in &lines {
+            clip_tiles.mark_partial_tiles(line, tolerance);
+        }
+        clip_tiles.classify_remaining_tiles(&lines, clip_path.fill_rule());
+        clip_tiles
+    }
+
+    /// Returns how the tiles that `rect` overlaps are covered.
+    pub(crate) fn coverage(&self, rect: RectF) -> ClipTileCoverage {
+        let (min, max) = tile_span(rect, self.origin, self.tile_size);
+        let tile_rect = self.clamp_tile_span(min, max);
+
+        // Tiles beyond the bounds of the clip path are outside it.
+        let mut coverage = None;
+        if tile_rect.origin() != min || tile_rect.lower_right() != max {
+            coverage = Some(ClipTileCoverage::Outside);
+        }
+
+        for y in tile_rect.min_y()..tile_rect.max_y() {
+            for x in tile_rect.min_x()..tile_rect.max_x() {
+                let index = self.tiles.coords_to_index_unchecked(Vector2I::new(x, y));
+                let tile_coverage = self.tiles.data[index];
+                let new_coverage = match coverage {
+                    None => tile_coverage,
+                    Some(coverage) => coverage.combine(tile_coverage),
+                };
+                if new_coverage == ClipTileCoverage::Partial {
+                    return new_coverage;
+                }
+                coverage = Some(new_coverage);
+            }
+        }
+        coverage.unwrap_or(ClipTileCoverage::Outside)
+    }
+
+    fn clamp_tile_span(&self, min: Vector2I, max: Vector2I) -> RectI {
+        let min = min.max(self.tiles.rect.origin()).min(self.tiles.rect.lower_right());
+        let max = max.min(self.tiles.rect.lower_right()).max(min);
+        RectI::from_points(min, max)
+    }
+
+    // Marks the tiles that `line`, dilated by `tolerance`, touches as partial. The line is split
+    // into pieces no longer than half a tile, so that the bounds of each piece only cover tiles
+    // close to the line.
+    fn mark_partial_tiles(&mut self, line: LineSegment2F, tolerance: f32) {
+        let piece_count = f32::ceil(line.vector().length() * 2.0 / self.tile_size).max(1.0);
+        for piece_index in 0..(piece_count as u32) {
+            let from = line.sample(piece_index as f32 / piece_count);
+            let to = line.sample((piece_index + 1) as f32 / piece_count);
+            let piece_bounds = RectF::from_points(from.min(to), from.max(to))
+                .dilate(Vector2F::splat(tolerance));
+            let (min, max) = tile_span(piece_bounds, self.origin, self.tile_size);
+            let tile_rect = self.clamp_tile_span(min, max);
+            for y in tile_rect.min_y()..tile_rect.max_y() {
+                for x in tile_rect.min_x()..tile_rect.max_x() {
+                    let index = self.tiles.coords_to_index_unchecked(Vector2I::new(x, y));
+                    self.tiles.data[index] = ClipTileCoverage::Partial;
+                }
+            }
+        }
+    }
+
+    // Classifies each tile that isn't partial by the winding number at its center, sweeping from
+    // left to right across each row.
+    fn classify_remaining_tiles(&mut self, lines: &[LineSegment2F], fill_rule: FillRule) {
+        let mut crossings: Vec<(f32, i32)> = vec![];
+        for y in self.tiles.rect.min_y()..self.tiles.rect.max_y() {
+            let center_y = self.origin.y() + (y as f32 + 0.5) * self.tile_size;
+            crossings.clear();
+            for line in lines {
+                let (from_y, to_y) = (line.from_y(), line.to_y());
+                if (from_y <= center_y) == (to_y <= center_y) {
+                    continue;
+                }
+                let winding = if from_y < to_y { 1 } else { -1 };
+                crossings.push((line.solve_x_for_y(center_y), winding));
+            }
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+            let mut remaining_crossings = crossings.iter().peekable();
+            let mut winding = 0;
+            for x in self.tiles.rect.min_x()..self.tiles.rect.max_x() {
+                let center_x = self.origin.x() + (x as f32 + 0.5) * self.tile_size;
+                while let Some(&&(crossing_x, crossing_winding)) = remaining_crossings.peek() {
+                    if crossing_x >= center_x {
+                        break;
+                    }
+                    winding += crossing_winding;
+                    remaining_crossings.next();
+                }
+
+                let index = self.tiles.coords_to_index_unchecked(Vector2I::new(x, y));
+                if self.tiles.data[index] == ClipTileCoverage::Partial {
+                    continue;
+                }
+                let inside = match fill_rule {
+                    FillRule::Winding => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                self.tiles.data[index] = if inside {
+                    ClipTileCoverage::Inside
+                } else {
+                    ClipTileCoverage::Outside
+                };
+            }
+        }
+    }
+}
+
+// Returns the range of tiles, possibly nonexistent ones, that `rect` overlaps.
+fn tile_span(rect: RectF, origin: Vector2F, tile_size: f32) -> (Vector2I, Vector2I) {
+    let min = (rect.origin() - origin).scale(1.0 / tile_size).floor().to_i32();
+    let max = (rect.lower_right() - origin).scale(1.0 / tile_size).ceil().to_i32();
+    (min, max.max(min + Vector2I::splat(1)))
+}
@@ -10,6 +10,8 @@
 
 //! An abstraction over threading and parallelism systems such as Rayon.
 
+use std::sync::{Arc, Mutex};
+
 /// An abstraction over threading and parallelism systems such as Rayon.
 pub trait Executor {
     /// Like the Rayon snippet:
@@ -17,6 +19,9 @@ pub trait Executor {
     /// ```norun
     /// (0..length).into_par_iter().flat_map(builder).collect()
     /// ```
+    ///
+    /// The results must be in index order, no matter the order in which `builder` runs, since
+    /// the order of fills and tiles in the render commands follows it.
     fn flatten_into_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
                                  where T: Send, F: Fn(usize) -> Vec<T> + Send + Sync;
 }
@@ -29,3 +34,30 @@ impl Executor for SequentialExecutor {
         (0..length).into_iter().flat_map(builder).collect()
     }
 }
+
+/// A job system supplied by the application, for use with `ThreadPoolExecutor`.
+pub trait ThreadPool: Send + Sync {
+    /// Calls `job` once for each index in `0..job_count`, possibly in parallel, and returns once
+    /// all of the calls have finished.
+    fn run(&self, job_count: usize, job: &(dyn Fn(usize) + Sync));
+}
+
+impl<P> ThreadPool for Arc<P> where P: ThreadPool + ?Sized {
+    #[inline]
+    fn run(&self, job_count: usize, job: &(dyn Fn(usize) + Sync)) {
+        (**self).run(job_count, job)
+    }
+}
+
+/// Builds scenes on the application's own thread pool, so that applications that already have a
+/// job system don't need to run a competing one.
+pub struct ThreadPoolExecutor<P>(pub P) where P: ThreadPool;
+
+impl<P> Executor for ThreadPoolExecutor<P> where P: ThreadPool {
+    fn flatten_into_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
+                                 where T: Send, F: Fn(usize) -> Vec<T> + Send + Sync {
+        let results: Vec<Mutex<Vec<T>>> = (0..length).map(|_| Mutex::new(vec![])).collect();
+        self.0.run(length, &|index| *results[index].lock().unwrap() = builder(index));
+        results.into_iter().flat_map(|result| result.into_inner().unwrap()).collect()
+    }
+}
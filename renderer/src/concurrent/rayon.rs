@@ -10,7 +10,7 @@
 
 //! An implementation of the executor using the Rayon library.
 
-use crate::concurrent::executor::Executor;
+use crate::concurrent::executor::{Executor, ThreadPool};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 pub struct RayonExecutor;
@@ -21,3 +21,11 @@ impl Executor for RayonExecutor {
         (0..length).into_par_iter().flat_map(builder).collect()
     }
 }
+
+/// Lets a particular Rayon thread pool, rather than the global one, be used via
+/// `ThreadPoolExecutor`.
+impl ThreadPool for rayon::ThreadPool {
+    fn run(&self, job_count: usize, job: &(dyn Fn(usize) + Sync)) {
+        self.install(|| (0..job_count).into_par_iter().for_each(job))
+    }
+}
@@ -23,7 +23,7 @@ use crate::concurrent::executor::Executor;
 use crate::gpu::renderer::Renderer;
 use crate::gpu_data::RenderCommand;
 use crate::options::{BuildOptions, RenderCommandListener};
-use crate::scene::Scene;
+use crate::scene::{Scene, SceneBuildError};
 use pathfinder_geometry::rect::RectF;
 use pathfinder_gpu::Device;
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -57,19 +57,26 @@ impl SceneProxy {
         self.sender.send(MainToWorkerMsg::SetViewBox(new_view_box)).unwrap();
     }
 
+    /// Builds the scene on the worker thread, sending the commands to `listener`.
+    ///
+    /// The returned receiver gets the outcome once building is done. If the scene can't be built,
+    /// `listener` gets no commands at all.
     #[inline]
     pub fn build_with_listener(&self,
                                options: BuildOptions,
-                               listener: Box<dyn RenderCommandListener>) {
-        self.sender.send(MainToWorkerMsg::Build(options, listener)).unwrap();
+                               listener: Box<dyn RenderCommandListener>)
+                               -> Receiver<Result<(), SceneBuildError>> {
+        let (result_sender, result_receiver) = mpsc::channel();
+        self.sender.send(MainToWorkerMsg::Build(options, listener, result_sender)).unwrap();
+        result_receiver
     }
 
     #[inline]
     pub fn build_with_stream(&self, options: BuildOptions) -> RenderCommandStream {
         let (sender, receiver) = mpsc::sync_channel(MAX_MESSAGES_IN_FLIGHT);
         let listener = Box::new(move |command| drop(sender.send(command)));
-        self.build_with_listener(options, listener);
-        RenderCommandStream::new(receiver)
+        let result_receiver = self.build_with_listener(options, listener);
+        RenderCommandStream::new(receiver, result_receiver)
     }
 
     /// A convenience method to build a scene and send the resulting commands
@@ -78,18 +85,25 @@ impl SceneProxy {
     /// Exactly equivalent to:
     ///
     /// ```norun
-    /// for command in scene_proxy.build_with_stream(options) {
+    /// let mut stream = scene_proxy.build_with_stream(options);
+    /// for command in &mut stream {
     ///     renderer.render_command(&command)
     /// }
+    /// stream.finish()
     /// ```
+    ///
+    /// If the scene can't be built, nothing but the background is drawn.
     #[inline]
     pub fn build_and_render<D>(&self, renderer: &mut Renderer<D>, build_options: BuildOptions)
+                               -> Result<(), SceneBuildError>
                                where D: Device {
         renderer.begin_scene();
-        for command in self.build_with_stream(build_options) {
+        let mut stream = self.build_with_stream(build_options);
+        for command in &mut stream {
             renderer.render_command(&command);
         }
         renderer.end_scene();
+        stream.finish()
     }
 
     #[inline]
@@ -109,7 +123,9 @@ fn scene_thread<E>(mut scene: Scene,
             MainToWorkerMsg::ReplaceScene(new_scene) => scene = new_scene,
             MainToWorkerMsg::CopyScene(sender) => sender.send(scene.clone()).unwrap(),
             MainToWorkerMsg::SetViewBox(new_view_box) => scene.set_view_box(new_view_box),
-            MainToWorkerMsg::Build(options, listener) => scene.build(options, listener, &executor)
+            MainToWorkerMsg::Build(options, listener, result_sender) => {
+                let _ = result_sender.send(scene.build(options, listener, &executor));
+            }
         }
     }
 }
@@ -118,17 +134,27 @@ enum MainToWorkerMsg {
     ReplaceScene(Scene),
     CopyScene(Sender<Scene>),
     SetViewBox(RectF),
-    Build(BuildOptions, Box<dyn RenderCommandListener>),
+    Build(BuildOptions, Box<dyn RenderCommandListener>, Sender<Result<(), SceneBuildError>>),
 }
 
 pub struct RenderCommandStream {
     receiver: Receiver<RenderCommand>,
+    result_receiver: Receiver<Result<(), SceneBuildError>>,
     done: bool,
 }
 
 impl RenderCommandStream {
-    fn new(receiver: Receiver<RenderCommand>) -> RenderCommandStream {
-        RenderCommandStream { receiver, done: false }
+    fn new(receiver: Receiver<RenderCommand>,
+           result_receiver: Receiver<Result<(), SceneBuildError>>)
+           -> RenderCommandStream {
+        RenderCommandStream { receiver, result_receiver, done: false }
+    }
+
+    /// Waits for building to finish and returns whether the scene could be built. Any commands
+    /// that haven't been received yet are discarded.
+    pub fn finish(mut self) -> Result<(), SceneBuildError> {
+        for _ in &mut self {}
+        self.result_receiver.recv().unwrap()
     }
 }
 
@@ -140,7 +166,14 @@ impl Iterator for RenderCommandStream {
         if self.done {
             None
         } else {
-            let command = self.receiver.recv().unwrap();
+            // Failed builds send no commands, so the stream just ends.
+            let command = match self.receiver.recv() {
+                Ok(command) => command,
+                Err(_) => {
+                    self.done = true;
+                    return None;
+                }
+            };
             if let RenderCommand::Finish { .. } = command {
                 self.done = true;
             }
@@ -0,0 +1,124 @@
+// pathfinder/renderer/src/coverage.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The tiles that each path touched when a scene was built, for applications that track dirty
+//! regions or route input themselves.
+//!
+//! A path touches a tile if any of its pixels there would be drawn, whether or not other paths
+//! hide them. Tiles are `TILE_WIDTH` × `TILE_HEIGHT` device pixels, numbered from the origin of
+//! device space, so tile `(x, y)` covers the pixels from `(x * TILE_WIDTH, y * TILE_HEIGHT)` up
+//! to the next tile's.
+
+pub use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector2I;
+use std::cmp;
+
+/// The number of regions across and down that `TileCoverage::region_mask()` divides the view box
+/// into, for one bit per region.
+pub const REGION_GRID_SIZE: i32 = 8;
+
+/// The tiles that each path of a scene touched in one build, recorded with
+/// `BuildOptions::record_tile_coverage`.
+#[derive(Clone, Debug)]
+pub struct TileCoverage {
+    view_box_tile_rect: RectI,
+    // The tiles of each path, by path index, sorted by row and then by column.
+    path_tiles: Vec<Vec<Vector2I>>,
+}
+
+impl TileCoverage {
+    pub(crate) fn new(path_count: usize, view_box_tile_rect: RectI) -> TileCoverage {
+        TileCoverage { view_box_tile_rect, path_tiles: vec![vec![]; path_count] }
+    }
+
+    pub(crate) fn set_path_tiles(&mut self, path_index: usize, mut tiles: Vec<Vector2I>) {
+        tiles.sort_by_key(|tile_coords| (tile_coords.y(), tile_coords.x()));
+        self.path_tiles[path_index] = tiles;
+    }
+
+    /// The number of paths in the scene, including those that touched no tiles.
+    #[inline]
+    pub fn path_count(&self) -> usize {
+        self.path_tiles.len()
+    }
+
+    /// The tiles of the view box the scene was built for.
+    #[inline]
+    pub fn view_box_tile_rect(&self) -> RectI {
+        self.view_box_tile_rect
+    }
+
+    /// The tiles that the path at `path_index` touched, sorted by row and then by column.
+    ///
+    /// Paths that were culled, clipped out, or entirely outside the view box touched none.
+    #[inline]
+    pub fn tiles(&self, path_index: usize) -> &[Vector2I] {
+        match self.path_tiles.get(path_index) {
+            None => &[],
+            Some(tiles) => tiles,
+        }
+    }
+
+    /// The smallest rectangle of tiles holding all the tiles that the path at `path_index`
+    /// touched, or `None` if it touched none.
+    pub fn tile_bounds(&self, path_index: usize) -> Option<RectI> {
+        let tiles = self.tiles(path_index);
+        let first_tile_coords = *tiles.first()?;
+        let (mut min, mut max) = (first_tile_coords, first_tile_coords);
+        for &tile_coords in tiles {
+            min = min.min(tile_coords);
+            max = max.max(tile_coords);
+        }
+        Some(RectI::from_points(min, max + Vector2I::splat(1)))
+    }
+
+    /// Whether the path at `path_index` touched the tile at `tile_coords`.
+    #[inline]
+    pub fn touches(&self, path_index: usize, tile_coords: Vector2I) -> bool {
+        self.tiles(path_index)
+            .binary_search_by_key(&(tile_coords.y(), tile_coords.x()), |tile_coords| {
+                (tile_coords.y(), tile_coords.x())
+            })
+            .is_ok()
+    }
+
+    /// The indices of the paths that touched the tile at `tile_coords`, in the order they were
+    /// pushed into the scene. For hit testing, the paths under a point are among those that
+    /// touched its tile.
+    pub fn paths_touching(&self, tile_coords: Vector2I) -> Vec<usize> {
+        (0..self.path_tiles.len()).filter(|&path_index| {
+            self.touches(path_index, tile_coords)
+        }).collect()
+    }
+
+    /// A coarse summary of where the path at `path_index` touched, for cheap dirty region checks.
+    ///
+    /// The view box is divided into a grid of `REGION_GRID_SIZE` × `REGION_GRID_SIZE` regions of
+    /// whole tiles, and bit `row * REGION_GRID_SIZE + column` is set if the path touched the
+    /// region at that row and column. Paths whose masks share no bits touched no tiles in common.
+    pub fn region_mask(&self, path_index: usize) -> u64 {
+        let view_box_tile_rect = self.view_box_tile_rect;
+        let grid_size = view_box_tile_rect.size() + Vector2I::splat(REGION_GRID_SIZE - 1);
+        let region_size = Vector2I::new(cmp::max(grid_size.x() / REGION_GRID_SIZE, 1),
+                                        cmp::max(grid_size.y() / REGION_GRID_SIZE, 1));
+
+        let mut mask = 0;
+        for &tile_coords in self.tiles(path_index) {
+            // Paths are tiled inside the view box, so no tile is above or left of it.
+            let region = tile_coords - view_box_tile_rect.origin();
+            let column = cmp::min(region.x() / region_size.x(), REGION_GRID_SIZE - 1);
+            let row = cmp::min(region.y() / region_size.y(), REGION_GRID_SIZE - 1);
+            mask |= 1 << (row * REGION_GRID_SIZE + column);
+        }
+        mask
+    }
+}
@@ -14,8 +14,12 @@
 //! debug Pathfinder if it's totally busted.
 //!
 //! The debug font atlas was generated using: https://evanw.github.io/font-texture-generator/
+//!
+//! Applications can add counters of their own with `DebugUIPresenter::add_counter()`, like frame
+//! times or network fetches, which are drawn in a window above the built-in stats.
 
 use crate::gpu::renderer::{RenderStats, RenderTime};
+use pathfinder_color::ColorU;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_gpu::resources::ResourceLoader;
@@ -33,6 +37,11 @@ const STATS_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
 const PERFORMANCE_WINDOW_WIDTH: i32 = 400;
 const PERFORMANCE_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
 
+const COUNTER_WINDOW_WIDTH: i32 = 400;
+const COUNTER_GRAPH_HEIGHT: i32 = LINE_HEIGHT;
+
+static COUNTER_GRAPH_COLOR: ColorU = ColorU { r: 255, g: 255, b: 255, a: 192 };
+
 pub struct DebugUIPresenter<D>
 where
     D: Device,
@@ -41,6 +50,29 @@ where
 
     cpu_samples: SampleBuffer<CPUSample>,
     gpu_samples: SampleBuffer<GPUSample>,
+    counters: Vec<CounterSamples>,
+}
+
+/// A value that the application reports to the debug UI, shown as the mean of its recent samples
+/// along with the built-in stats.
+#[derive(Clone, Debug)]
+pub struct DebugCounter {
+    pub label: String,
+    /// Shown after the value, like `"ms"`. Leave it empty for plain counts.
+    pub unit: String,
+    /// The number of digits shown after the decimal point.
+    pub precision: usize,
+    /// Whether to draw a bar graph of the recent samples under the value.
+    pub graph: bool,
+}
+
+/// The handle of a counter added with `DebugUIPresenter::add_counter()`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DebugCounterId(u32);
+
+struct CounterSamples {
+    counter: DebugCounter,
+    samples: SampleBuffer<CounterSample>,
 }
 
 impl<D> DebugUIPresenter<D>
@@ -57,9 +89,23 @@ where
             ui_presenter,
             cpu_samples: SampleBuffer::new(),
             gpu_samples: SampleBuffer::new(),
+            counters: vec![],
         }
     }
 
+    /// Adds a counter, drawn below those added before it.
+    pub fn add_counter(&mut self, counter: DebugCounter) -> DebugCounterId {
+        let counter_id = DebugCounterId(self.counters.len() as u32);
+        self.counters.push(CounterSamples { counter, samples: SampleBuffer::new() });
+        counter_id
+    }
+
+    /// Records the value of a counter, usually once a frame. Like the built-in stats, the mean of
+    /// the last 60 samples is shown.
+    pub fn add_counter_sample(&mut self, counter_id: DebugCounterId, value: f64) {
+        self.counters[counter_id.0 as usize].samples.push(CounterSample(value));
+    }
+
     pub fn add_sample(
         &mut self,
         stats: RenderStats,
@@ -79,6 +125,9 @@ where
         let mean_cpu_sample = self.cpu_samples.mean();
         self.draw_stats_window(device, &mean_cpu_sample);
         self.draw_performance_window(device, &mean_cpu_sample);
+        if !self.counters.is_empty() {
+            self.draw_counter_window(device);
+        }
     }
 
     fn draw_stats_window(&self, device: &D, mean_cpu_sample: &CPUSample) {
@@ -176,6 +225,75 @@ where
         );
     }
 
+    fn draw_counter_window(&self, device: &D) {
+        let window_height = self.counters.iter().fold(PADDING + 2, |height, counter_samples| {
+            let graph_height = if counter_samples.counter.graph { COUNTER_GRAPH_HEIGHT } else { 0 };
+            height + LINE_HEIGHT + graph_height
+        });
+
+        let framebuffer_size = self.ui_presenter.framebuffer_size();
+        let bottom = framebuffer_size.y() - PADDING;
+        let window_rect = RectI::new(
+            Vector2I::new(
+                framebuffer_size.x() - PADDING - COUNTER_WINDOW_WIDTH,
+                bottom - PERFORMANCE_WINDOW_HEIGHT - PADDING - STATS_WINDOW_HEIGHT - PADDING -
+                    window_height,
+            ),
+            Vector2I::new(COUNTER_WINDOW_WIDTH, window_height),
+        );
+
+        self.ui_presenter.draw_solid_rounded_rect(device, window_rect, WINDOW_COLOR);
+
+        let mut origin = window_rect.origin() + Vector2I::new(PADDING, PADDING);
+        for counter_samples in &self.counters {
+            let counter = &counter_samples.counter;
+            let mean = counter_samples.samples.mean().0;
+            let text = if counter.unit.is_empty() {
+                format!("{}: {:.*}", counter.label, counter.precision, mean)
+            } else {
+                format!("{}: {:.*} {}", counter.label, counter.precision, mean, counter.unit)
+            };
+            self.ui_presenter.draw_text(device,
+                                        &text,
+                                        origin + Vector2I::new(0, FONT_ASCENT),
+                                        false);
+            origin += Vector2I::new(0, LINE_HEIGHT);
+
+            if counter.graph {
+                let graph_size = Vector2I::new(COUNTER_WINDOW_WIDTH - PADDING * 2,
+                                               COUNTER_GRAPH_HEIGHT - PADDING);
+                let graph_rect = RectI::new(origin, graph_size);
+                self.draw_counter_graph(device, &counter_samples.samples, graph_rect);
+                origin += Vector2I::new(0, COUNTER_GRAPH_HEIGHT);
+            }
+        }
+    }
+
+    // Draws a bar for each sample, scaled so that the largest one fills the height of the graph.
+    // Negative samples are drawn as zero.
+    fn draw_counter_graph(&self,
+                          device: &D,
+                          samples: &SampleBuffer<CounterSample>,
+                          graph_rect: RectI) {
+        self.ui_presenter.draw_rect_outline(device, graph_rect, COUNTER_GRAPH_COLOR);
+
+        let max_value = samples.samples.iter().fold(0.0, |max_value, sample| {
+            f64::max(max_value, sample.0)
+        });
+        if max_value <= 0.0 {
+            return;
+        }
+
+        let bar_width = graph_rect.size().x() / SAMPLE_BUFFER_SIZE as i32;
+        for (sample_index, sample) in samples.samples.iter().enumerate() {
+            let bar_height = (f64::max(sample.0, 0.0) / max_value *
+                              graph_rect.size().y() as f64).round() as i32;
+            let bar_origin = Vector2I::new(graph_rect.min_x() + sample_index as i32 * bar_width,
+                                           graph_rect.max_y() - bar_height);
+            let bar_rect = RectI::new(bar_origin, Vector2I::new(bar_width, bar_height));
+            self.ui_presenter.draw_solid_rect(device, bar_rect, COUNTER_GRAPH_COLOR);
+        }
+    }
 }
 
 struct SampleBuffer<S>
@@ -268,6 +386,23 @@ impl Div<usize> for GPUSample {
     }
 }
 
+#[derive(Clone, Copy, Default)]
+struct CounterSample(f64);
+
+impl Add<CounterSample> for CounterSample {
+    type Output = CounterSample;
+    fn add(self, other: CounterSample) -> CounterSample {
+        CounterSample(self.0 + other.0)
+    }
+}
+
+impl Div<usize> for CounterSample {
+    type Output = CounterSample;
+    fn div(self, divisor: usize) -> CounterSample {
+        CounterSample(self.0 / divisor as f64)
+    }
+}
+
 fn duration_to_ms(time: Duration) -> f64 {
     time.as_secs() as f64 * 1000.0 + time.subsec_nanos() as f64 / 1000000.0
 }
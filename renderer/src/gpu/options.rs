@@ -16,9 +16,82 @@ use pathfinder_gpu::Device;
 /// Options that influence rendering.
 #[derive(Default)]
 pub struct RendererOptions {
+    /// The color to clear the destination to at the start of each scene.
+    ///
+    /// `ColorF::transparent_black()` leaves a transparent framebuffer, for compositing the result
+    /// elsewhere. `None` doesn't clear at all, so that the scene is drawn over the existing
+    /// contents of the destination, as for UI overlays on top of a 3D scene.
     pub background_color: Option<ColorF>,
+    /// Blends coverage and colors in linear sRGB rather than in sRGB, so that thin strokes and
+    /// text have the right weight. Paints are converted when they are uploaded, the scene is drawn
+    /// to a floating point framebuffer, and that is converted back to sRGB at the end of the
+    /// scene. This has no effect when postprocessing is enabled.
+    pub linear_compositing: bool,
+    /// How the destination framebuffer stores colors.
+    pub dest_encoding: DestEncoding,
+    /// Produces premultiplied-alpha output, for compositing into pipelines that expect it.
+    ///
+    /// Paths are always blended with premultiplied alpha, so this only changes how the background
+    /// color is written: without it, the background color is written as given, which is only
+    /// premultiplied if the background is opaque. To treat the colors of patterns and gradients
+    /// as premultiplied as well, use `BuildOptions::premultiplied_paints`.
+    pub premultiplied_alpha: bool,
+    /// A soft limit, in bytes, on the GPU memory that the renderer uses.
+    ///
+    /// If the renderer is over budget at the end of a scene, it frees its paint texture and
+    /// intermediate framebuffers, as `Renderer::evict_cached_resources()` does. They're recreated
+    /// as needed, so going over budget costs time but never fails.
+    pub gpu_memory_budget: Option<u64>,
+    /// How the edges of paths are rasterized into coverage.
+    pub fill_rasterization: FillRasterization,
 }
 
+/// How the renderer turns the edges of paths into coverage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FillRasterization {
+    /// Each edge adds the exact area it covers in each pixel, looked up in a precomputed table.
+    /// This gives the best antialiasing.
+    AreaCoverage,
+    /// Edges are drawn as triangles into a framebuffer with `sample_count` samples per pixel,
+    /// which is then resolved into coverage. Antialiasing is limited to `sample_count + 1` levels
+    /// of coverage, but this only needs hardware multisampling, for GPUs and drivers that
+    /// misbehave with `AreaCoverage`.
+    Multisample { sample_count: u32 },
+}
+
+impl Default for FillRasterization {
+    #[inline]
+    fn default() -> FillRasterization {
+        FillRasterization::AreaCoverage
+    }
+}
+
+/// How colors are stored in the destination framebuffer.
+///
+/// The color space of the destination is chosen when building the scene, with
+/// `BuildOptions::color_space`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DestEncoding {
+    /// Colors are encoded with the sRGB transfer function and clamped to the range 0 to 1. This
+    /// is right for ordinary 8-bit framebuffers and for 10-bit ones, like `RGB10A2` textures.
+    Srgb,
+    /// Colors are linear light and are not clamped, as for the extended-range `RGBA16F`
+    /// framebuffers that HDR displays use. This implies linear compositing.
+    ExtendedLinear,
+}
+
+impl Default for DestEncoding {
+    #[inline]
+    fn default() -> DestEncoding {
+        DestEncoding::Srgb
+    }
+}
+
+/// Where the renderer draws.
+///
+/// The viewport variants restrict rendering to `viewport`, which lets several independent scenes
+/// share one surface. Scenes are laid out relative to the viewport's origin, so a scene meant for
+/// a viewport should be built with a transform whose size is that of the viewport.
 #[derive(Clone)]
 pub enum DestFramebuffer<D>
 where
@@ -29,6 +102,10 @@ where
         window_size: Vector2I,
     },
     Other(D::Framebuffer),
+    OtherViewport {
+        framebuffer: D::Framebuffer,
+        viewport: RectI,
+    },
 }
 
 impl<D> Default for DestFramebuffer<D> where D: Device {
@@ -52,7 +129,8 @@ where
     pub fn window_size(&self, device: &D) -> Vector2I {
         match *self {
             DestFramebuffer::Default { window_size, .. } => window_size,
-            DestFramebuffer::Other(ref framebuffer) => {
+            DestFramebuffer::Other(ref framebuffer) |
+            DestFramebuffer::OtherViewport { ref framebuffer, .. } => {
                 device.texture_size(device.framebuffer_texture(framebuffer))
             }
         }
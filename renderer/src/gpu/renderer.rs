@@ -11,25 +11,34 @@
 #[cfg(feature="debug_ui")]
 use crate::gpu::debug::DebugUIPresenter;
 
-use crate::gpu::options::{DestFramebuffer, RendererOptions};
-use crate::gpu_data::{AlphaTileBatchPrimitive, FillBatchPrimitive, PaintData};
-use crate::gpu_data::{RenderCommand, SolidTileBatchPrimitive};
+use crate::binning;
+use crate::coverage::TileCoverage;
+use crate::gpu::options::{DestEncoding, DestFramebuffer, FillRasterization, RendererOptions};
+use crate::gpu_data::{AlphaTileBatchPrimitive, FillBatchPrimitive, PaintData, PaintPageData};
+use crate::gpu_data::{PaintPageFormat, PaintPageId, RenderCommand, SegmentBatch};
+use crate::gpu_data::SolidTileBatchPrimitive;
 use crate::post::DefringingKernel;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
-use pathfinder_color::ColorF;
+use half::f16;
+use pathfinder_color::{self as color, ColorF};
+use pathfinder_content::effects::{BackdropFilter, BlendMode};
+use pathfinder_content::fill::FillRule;
 use pathfinder_geometry::vector::{Vector2I, Vector4F};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendState, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{DepthFunc, DepthState, Device, Primitive, RenderOptions, RenderState};
+use pathfinder_gpu::{BlendFactor, BlendState, BufferData, BufferTarget, BufferUploadMode, ClearOps};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, DepthState, Device, Primitive};
+use pathfinder_gpu::{RenderOptions, RenderState};
 use pathfinder_gpu::{RenderTarget, StencilFunc, StencilState, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_simd::default::{F32x2, F32x4};
+use std::cell::RefCell;
 use std::cmp;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::mem;
 use std::ops::{Add, Div};
+use std::sync::Arc;
 use std::time::Duration;
 use std::u32;
 
@@ -42,11 +51,23 @@ const MASK_FRAMEBUFFER_HEIGHT: i32 = TILE_HEIGHT as i32 * 256;
 
 // TODO(pcwalton): Replace with `mem::size_of` calls?
 const FILL_INSTANCE_SIZE: usize = 8;
-const SOLID_TILE_INSTANCE_SIZE: usize = 20;
-const MASK_TILE_INSTANCE_SIZE: usize = 20;
+const SOLID_TILE_INSTANCE_SIZE: usize = 32;
+const MASK_TILE_INSTANCE_SIZE: usize = 32;
 
 const MAX_FILLS_PER_BATCH: usize = 0x4000;
 
+// Segment batches that could make more fills than this are binned on the CPU, to bound the size of
+// the buffers that the compute shaders write to.
+const MAX_GPU_TILED_FILLS: u32 = 1 << 22;
+
+// The size of a work group of the tiling compute shaders.
+const TILING_WORK_GROUP_SIZE: u32 = 64;
+const MAX_TILING_WORK_GROUPS: u32 = 0xffff;
+
+// The stencil bit that marks the inside of the current clip path, for `stencil_clip_paths`. Bit 1
+// holds the 3D bounding quad.
+const CLIP_STENCIL_BIT: u32 = 2;
+
 pub struct Renderer<D>
 where
     D: Device,
@@ -57,35 +78,44 @@ where
     // Core data
     dest_framebuffer: DestFramebuffer<D>,
     options: RendererOptions,
-    fill_program: FillProgram<D>,
-    solid_tile_program: SolidTileProgram<D>,
-    alpha_tile_program: AlphaTileProgram<D>,
+    shared: Arc<SharedResources<D>>,
     solid_tile_vertex_array: SolidTileVertexArray<D>,
     alpha_tile_vertex_array: AlphaTileVertexArray<D>,
-    area_lut_texture: D::Texture,
-    quad_vertex_positions_buffer: D::Buffer,
-    quad_vertex_indices_buffer: D::Buffer,
     fill_vertex_array: FillVertexArray<D>,
-    mask_framebuffer: D::Framebuffer,
-    paint_texture: Option<D::Texture>,
+    mask_framebuffer: Option<D::Framebuffer>,
+    // Fills are drawn here and resolved into `mask_framebuffer` with
+    // `FillRasterization::Multisample`.
+    msaa_mask_framebuffer: Option<D::Framebuffer>,
 
     // Postprocessing shader
     postprocess_source_framebuffer: Option<D::Framebuffer>,
-    postprocess_program: PostprocessProgram<D>,
     postprocess_vertex_array: PostprocessVertexArray<D>,
-    gamma_lut_texture: D::Texture,
+
+    // Linear compositing
+    linear_framebuffer: Option<D::Framebuffer>,
+    resolve_vertex_array: PostprocessVertexArray<D>,
+
+    // Backdrop filters, which blur into the first framebuffer and then into the second
+    backdrop_framebuffers: Option<(D::Framebuffer, D::Framebuffer)>,
+    backdrop_filter_vertex_array: PostprocessVertexArray<D>,
 
     // Stencil shader
-    stencil_program: StencilProgram<D>,
     stencil_vertex_array: StencilVertexArray<D>,
 
     // Reprojection shader
-    reprojection_program: ReprojectionProgram<D>,
     reprojection_vertex_array: ReprojectionVertexArray<D>,
 
     // Rendering state
     framebuffer_flags: FramebufferFlags,
     buffered_fills: Vec<FillBatchPrimitive>,
+    tile_coverage: Option<Arc<TileCoverage>>,
+
+    // Tiling for `BuildOptions::gpu_tiling`. The segments wait here until the fills are flushed,
+    // and then their alpha tiles wait for the `SegmentAlphaTile` commands that draw them, by path.
+    segment_batch: SegmentBatch,
+    segment_alpha_tiles: Vec<Vec<AlphaTileBatchPrimitive>>,
+    // Present if the device supports compute.
+    segment_buffers: Option<SegmentBuffers<D>>,
 
     // Debug
     pub stats: RenderStats,
@@ -101,27 +131,39 @@ where
     use_depth: bool,
 }
 
-impl<D> Renderer<D>
-where
-    D: Device,
-{
-    pub fn new(device: D,
-               resources: &dyn ResourceLoader,
-               dest_framebuffer: DestFramebuffer<D>,
-               options: RendererOptions)
-               -> Renderer<D> {
-        let fill_program = FillProgram::new(&device, resources);
-
-        let solid_tile_program = SolidTileProgram::new(&device, resources);
-        let alpha_tile_program = AlphaTileProgram::new(&device, resources);
-
-        let postprocess_program = PostprocessProgram::new(&device, resources);
-        let stencil_program = StencilProgram::new(&device, resources);
-        let reprojection_program = ReprojectionProgram::new(&device, resources);
-
-        let area_lut_texture = device.create_texture_from_png(resources, "lut/area");
-        let gamma_lut_texture = device.create_texture_from_png(resources, "lut/gamma");
+/// The GPU objects that don't depend on a renderer's destination or options.
+///
+/// Applications with several windows can create these once and pass them to
+/// `Renderer::with_shared_resources()` to save memory and startup time. Framebuffers depend on
+/// the destination, so each renderer still has its own.
+///
+/// The paint texture is shared too. Every scene uploads its paints when it starts, so renderers
+/// that share resources must draw their scenes one after another, not interleaved.
+pub struct SharedResources<D> where D: Device {
+    fill_program: FillProgram<D>,
+    msaa_fill_program: MSAAFillProgram<D>,
+    solid_tile_program: SolidTileProgram<D>,
+    alpha_tile_program: AlphaTileProgram<D>,
+    alpha_tile_blend_program: AlphaTileBlendProgram<D>,
+    backdrop_tile_program: BackdropTileProgram<D>,
+    clip_tile_program: ClipTileProgram<D>,
+    postprocess_program: PostprocessProgram<D>,
+    resolve_program: ResolveProgram<D>,
+    backdrop_filter_program: BackdropFilterProgram<D>,
+    stencil_program: StencilProgram<D>,
+    reprojection_program: ReprojectionProgram<D>,
+    // Present if the device supports compute.
+    tiling_programs: Option<TilingPrograms<D>>,
+    area_lut_texture: D::Texture,
+    gamma_lut_texture: D::Texture,
+    quad_vertex_positions_buffer: D::Buffer,
+    quad_vertex_indices_buffer: D::Buffer,
+    // The pages of the paint texture, indexed by `PaintPageId`.
+    paint_textures: RefCell<Vec<PaintTexture<D>>>,
+}
 
+impl<D> SharedResources<D> where D: Device {
+    pub fn new(device: &D, resources: &dyn ResourceLoader) -> SharedResources<D> {
         let quad_vertex_positions_buffer = device.create_buffer();
         device.allocate_buffer(
             &quad_vertex_positions_buffer,
@@ -137,43 +179,111 @@ where
             BufferUploadMode::Static,
         );
 
+        SharedResources {
+            fill_program: FillProgram::new(device, resources),
+            msaa_fill_program: MSAAFillProgram::new(device, resources),
+            solid_tile_program: SolidTileProgram::new(device, resources),
+            alpha_tile_program: AlphaTileProgram::new(device, resources),
+            alpha_tile_blend_program: AlphaTileBlendProgram::new(device, resources),
+            backdrop_tile_program: BackdropTileProgram::new(device, resources),
+            clip_tile_program: ClipTileProgram::new(device, resources),
+            postprocess_program: PostprocessProgram::new(device, resources),
+            resolve_program: ResolveProgram::new(device, resources),
+            backdrop_filter_program: BackdropFilterProgram::new(device, resources),
+            stencil_program: StencilProgram::new(device, resources),
+            reprojection_program: ReprojectionProgram::new(device, resources),
+            tiling_programs: if device.supports_compute() {
+                Some(TilingPrograms::new(device, resources))
+            } else {
+                None
+            },
+            area_lut_texture: device.create_texture_from_png(resources, "lut/area"),
+            gamma_lut_texture: device.create_texture_from_png(resources, "lut/gamma"),
+            quad_vertex_positions_buffer,
+            quad_vertex_indices_buffer,
+            paint_textures: RefCell::new(vec![]),
+        }
+    }
+}
+
+impl<D> Renderer<D>
+where
+    D: Device,
+{
+    pub fn new(device: D,
+               resources: &dyn ResourceLoader,
+               dest_framebuffer: DestFramebuffer<D>,
+               options: RendererOptions)
+               -> Renderer<D> {
+        let shared = Arc::new(SharedResources::new(&device, resources));
+        Renderer::with_shared_resources(device, shared, resources, dest_framebuffer, options)
+    }
+
+    /// Creates a renderer that uses the shader programs, lookup tables, and vertex buffers in
+    /// `shared`.
+    ///
+    /// `device` must be able to use objects created by the device that `shared` was created with:
+    /// for example, an OpenGL context in the same share group, or a Metal device wrapping the same
+    /// `MTLDevice`.
+    #[cfg_attr(not(feature="debug_ui"), allow(unused_variables))]
+    pub fn with_shared_resources(device: D,
+                                 shared: Arc<SharedResources<D>>,
+                                 resources: &dyn ResourceLoader,
+                                 dest_framebuffer: DestFramebuffer<D>,
+                                 options: RendererOptions)
+                                 -> Renderer<D> {
+        // Vertex arrays can't be shared between OpenGL contexts, so each renderer has its own.
         let fill_vertex_array = FillVertexArray::new(
             &device,
-            &fill_program,
-            &quad_vertex_positions_buffer,
-            &quad_vertex_indices_buffer,
+            &shared.fill_program,
+            &shared.msaa_fill_program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
         );
         let alpha_tile_vertex_array = AlphaTileVertexArray::new(
             &device,
-            &alpha_tile_program,
-            &quad_vertex_positions_buffer,
-            &quad_vertex_indices_buffer,
+            &shared.alpha_tile_program,
+            &shared.alpha_tile_blend_program,
+            &shared.backdrop_tile_program,
+            &shared.clip_tile_program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
         );
         let solid_tile_vertex_array = SolidTileVertexArray::new(
             &device,
-            &solid_tile_program,
-            &quad_vertex_positions_buffer,
-            &quad_vertex_indices_buffer,
+            &shared.solid_tile_program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
         );
         let postprocess_vertex_array = PostprocessVertexArray::new(
             &device,
-            &postprocess_program,
-            &quad_vertex_positions_buffer,
-            &quad_vertex_indices_buffer,
+            &shared.postprocess_program.program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
+        );
+        let resolve_vertex_array = PostprocessVertexArray::new(
+            &device,
+            &shared.resolve_program.program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
+        );
+        let backdrop_filter_vertex_array = PostprocessVertexArray::new(
+            &device,
+            &shared.backdrop_filter_program.program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
         );
-        let stencil_vertex_array = StencilVertexArray::new(&device, &stencil_program);
+        let stencil_vertex_array = StencilVertexArray::new(&device, &shared.stencil_program);
         let reprojection_vertex_array = ReprojectionVertexArray::new(
             &device,
-            &reprojection_program,
-            &quad_vertex_positions_buffer,
-            &quad_vertex_indices_buffer,
+            &shared.reprojection_program,
+            &shared.quad_vertex_positions_buffer,
+            &shared.quad_vertex_indices_buffer,
         );
 
-        let mask_framebuffer_size =
-            Vector2I::new(MASK_FRAMEBUFFER_WIDTH, MASK_FRAMEBUFFER_HEIGHT);
-        let mask_framebuffer_texture =
-            device.create_texture(TextureFormat::R16F, mask_framebuffer_size);
-        let mask_framebuffer = device.create_framebuffer(mask_framebuffer_texture);
+        let segment_buffers = shared.tiling_programs.as_ref().map(|_| {
+            SegmentBuffers::new(&device)
+        });
 
         let window_size = dest_framebuffer.window_size(&device);
 
@@ -185,27 +295,24 @@ where
 
             dest_framebuffer,
             options,
-            fill_program,
-            solid_tile_program,
-            alpha_tile_program,
+            shared,
             solid_tile_vertex_array,
             alpha_tile_vertex_array,
-            area_lut_texture,
-            quad_vertex_positions_buffer,
-            quad_vertex_indices_buffer,
             fill_vertex_array,
-            mask_framebuffer,
-            paint_texture: None,
+            mask_framebuffer: None,
+            msaa_mask_framebuffer: None,
 
             postprocess_source_framebuffer: None,
-            postprocess_program,
             postprocess_vertex_array,
-            gamma_lut_texture,
 
-            stencil_program,
+            linear_framebuffer: None,
+            resolve_vertex_array,
+
+            backdrop_framebuffers: None,
+            backdrop_filter_vertex_array,
+
             stencil_vertex_array,
 
-            reprojection_program,
             reprojection_vertex_array,
 
             stats: RenderStats::default(),
@@ -218,16 +325,29 @@ where
 
             framebuffer_flags: FramebufferFlags::empty(),
             buffered_fills: vec![],
+            tile_coverage: None,
+
+            segment_batch: SegmentBatch::default(),
+            segment_alpha_tiles: vec![],
+            segment_buffers,
 
             postprocess_options: None,
             use_depth: false,
         }
     }
 
+    /// The resources this renderer shares, for passing to `Renderer::with_shared_resources()`.
+    #[inline]
+    pub fn shared_resources(&self) -> &Arc<SharedResources<D>> {
+        &self.shared
+    }
+
     pub fn begin_scene(&mut self) {
         self.framebuffer_flags = FramebufferFlags::empty();
         self.device.begin_commands();
+        self.init_mask_framebuffer();
         self.init_postprocessing_framebuffer();
+        self.init_linear_framebuffer();
         self.stats = RenderStats::default();
     }
 
@@ -238,38 +358,145 @@ where
                     self.draw_stencil(&bounding_quad);
                 }
                 self.stats.path_count = path_count;
+                self.tile_coverage = None;
             }
             RenderCommand::AddPaintData(ref paint_data) => self.upload_paint_data(paint_data),
             RenderCommand::AddFills(ref fills) => self.add_fills(fills),
+            RenderCommand::AddSegments(ref segment_batch) => self.add_segments(segment_batch),
             RenderCommand::FlushFills => {
+                self.tile_segments();
                 self.draw_buffered_fills();
                 self.begin_composite_timer_query();
             }
-            RenderCommand::SolidTile(ref solid_tiles) => {
+            RenderCommand::ClearMaskFramebuffer => {
+                self.draw_buffered_fills();
+                self.framebuffer_flags
+                    .remove(FramebufferFlags::MUST_PRESERVE_MASK_FRAMEBUFFER_CONTENTS);
+            }
+            RenderCommand::SolidTile(ref solid_tiles, page) => {
                 let count = solid_tiles.len();
                 self.stats.solid_tile_count += count;
                 self.upload_solid_tiles(solid_tiles);
-                self.draw_solid_tiles(count as u32);
+                self.draw_solid_tiles(count as u32, page);
+            }
+            RenderCommand::ClipStencil(ref clip_tiles) => {
+                self.upload_alpha_tiles(clip_tiles);
+                self.draw_clip_stencil(clip_tiles.len() as u32);
+            }
+            RenderCommand::BackdropFilter(ref alpha_tiles, filter, fill_rule) => {
+                self.draw_backdrop_filter(alpha_tiles, filter, fill_rule, false);
             }
-            RenderCommand::AlphaTile(ref alpha_tiles) => {
-                let count = alpha_tiles.len();
-                self.stats.alpha_tile_count += count;
-                self.upload_alpha_tiles(alpha_tiles);
-                self.draw_alpha_tiles(count as u32);
+            RenderCommand::ClippedBackdropFilter(ref alpha_tiles, filter, fill_rule) => {
+                self.draw_backdrop_filter(alpha_tiles, filter, fill_rule, true);
+            }
+            RenderCommand::AlphaTile(ref alpha_tiles, blend_mode, fill_rule, page) => {
+                self.stats.alpha_tile_count += alpha_tiles.len();
+                self.draw_alpha_tile_batch(alpha_tiles, blend_mode, fill_rule, page, false);
+            }
+            RenderCommand::ClippedAlphaTile(ref alpha_tiles, blend_mode, fill_rule, page) => {
+                self.stats.alpha_tile_count += alpha_tiles.len();
+                self.draw_alpha_tile_batch(alpha_tiles, blend_mode, fill_rule, page, true);
+            }
+            RenderCommand::SegmentAlphaTile(ref segment_paths, blend_mode, fill_rule, page) => {
+                let segment_paths = segment_paths.start as usize..segment_paths.end as usize;
+                let alpha_tiles = self.segment_alpha_tiles[segment_paths].concat();
+                self.stats.alpha_tile_count += alpha_tiles.len();
+                self.draw_alpha_tile_batch(&alpha_tiles, blend_mode, fill_rule, page, false);
+            }
+            RenderCommand::TileCoverage(ref coverage) => {
+                self.tile_coverage = Some(coverage.clone());
             }
             RenderCommand::Finish { .. } => {}
         }
     }
 
+    /// The tiles that each path touched in the last scene rendered, if it was built with
+    /// `BuildOptions::record_tile_coverage`.
+    #[inline]
+    pub fn tile_coverage(&self) -> Option<&TileCoverage> {
+        self.tile_coverage.as_ref().map(|coverage| &**coverage)
+    }
+
     pub fn end_scene(&mut self) {
+        // Clearing is done as part of the first draw, so if nothing was drawn, clear now.
+        self.clear_draw_framebuffer_if_necessary();
+
         if self.postprocess_options.is_some() {
             self.postprocess();
+        } else if self.linear_compositing_enabled() {
+            self.resolve_linear_framebuffer();
         }
 
         self.end_composite_timer_query();
         self.pending_timers.push_back(mem::replace(&mut self.current_timers, RenderTimers::new()));
 
         self.device.end_commands();
+
+        if let Some(budget) = self.options.gpu_memory_budget {
+            if self.gpu_memory_usage().total() > budget {
+                self.evict_cached_resources();
+            }
+        }
+    }
+
+    /// Returns the amount of GPU memory that this renderer's objects occupy.
+    ///
+    /// Objects in the renderer's `SharedResources` are counted too, even if other renderers use
+    /// them.
+    pub fn gpu_memory_usage(&self) -> GPUMemoryUsage {
+        let mut textures = self.texture_bytes(&self.shared.area_lut_texture) +
+            self.texture_bytes(&self.shared.gamma_lut_texture);
+        for paint_texture in self.shared.paint_textures.borrow().iter() {
+            textures += self.texture_bytes(&paint_texture.texture);
+        }
+
+        let mut framebuffers = 0;
+        for framebuffer in self.mask_framebuffer.iter()
+                                                .chain(self.postprocess_source_framebuffer.iter())
+                                                .chain(self.linear_framebuffer.iter()) {
+            framebuffers += self.texture_bytes(self.device.framebuffer_texture(framebuffer));
+        }
+        if let Some((ref horizontal_framebuffer, ref vertical_framebuffer)) =
+                self.backdrop_framebuffers {
+            for framebuffer in &[horizontal_framebuffer, vertical_framebuffer] {
+                framebuffers += self.texture_bytes(self.device.framebuffer_texture(framebuffer));
+            }
+        }
+        if let (Some(framebuffer), FillRasterization::Multisample { sample_count }) =
+                (self.msaa_mask_framebuffer.as_ref(), self.options.fill_rasterization) {
+            let texture = self.device.framebuffer_texture(framebuffer);
+            framebuffers += self.texture_bytes(texture) * sample_count as u64;
+        }
+
+        let buffers = mem::size_of_val(&QUAD_VERTEX_POSITIONS) +
+            mem::size_of_val(&QUAD_VERTEX_INDICES) +
+            self.fill_vertex_array.vertex_buffer_size +
+            self.alpha_tile_vertex_array.vertex_buffer_size +
+            self.solid_tile_vertex_array.vertex_buffer_size +
+            self.segment_buffers.as_ref().map_or(0, |buffers| buffers.size);
+
+        GPUMemoryUsage { textures, framebuffers, buffers: buffers as u64 }
+    }
+
+    /// Frees the paint texture and the framebuffers that are only needed while drawing.
+    ///
+    /// The paint texture is freed for every renderer sharing it. They're all recreated when the
+    /// next scene needs them. This is done automatically at the end of
+    /// a scene if the renderer is over `RendererOptions::gpu_memory_budget`; applications can call
+    /// it between scenes to release memory sooner, for example when a window is hidden.
+    pub fn evict_cached_resources(&mut self) {
+        self.shared.paint_textures.borrow_mut().clear();
+        self.mask_framebuffer = None;
+        self.msaa_mask_framebuffer = None;
+        self.postprocess_source_framebuffer = None;
+        self.linear_framebuffer = None;
+        self.backdrop_framebuffers = None;
+    }
+
+    fn texture_bytes(&self, texture: &D::Texture) -> u64 {
+        let size = self.device.texture_size(texture);
+        let bytes_per_pixel = self.device.texture_format(texture).bytes_per_pixel();
+        size.x() as u64 * size.y() as u64 * bytes_per_pixel as u64
     }
 
     #[cfg(feature="debug_ui")]
@@ -289,19 +516,19 @@ where
             }
         }
 
-        // Get stage-1 time.
-        let stage_1_time = {
-            let stage_1_timer_query = timers.stage_1.as_ref().unwrap();
-            match self.device.try_recv_timer_query(stage_1_timer_query) {
+        // Accumulate stage-1 time. There's one query per batch of tiles.
+        let mut stage_1_time = Duration::new(0, 0);
+        for timer_query in &timers.stage_1 {
+            match self.device.try_recv_timer_query(timer_query) {
                 None => return None,
-                Some(query) => query,
+                Some(time) => stage_1_time += time,
             }
-        };
+        }
 
         // Recycle all timer queries.
         let timers = self.pending_timers.pop_front().unwrap();
         self.free_timer_queries.extend(timers.stage_0.into_iter());
-        self.free_timer_queries.push(timers.stage_1.unwrap());
+        self.free_timer_queries.extend(timers.stage_1.into_iter());
 
         Some(RenderTime { stage_0: total_stage_0_time, stage_1: stage_1_time })
     }
@@ -321,6 +548,9 @@ where
 
     #[inline]
     pub fn set_options(&mut self, new_options: RendererOptions) {
+        if new_options.fill_rasterization != self.options.fill_rasterization {
+            self.msaa_mask_framebuffer = None;
+        }
         self.options = new_options
     }
 
@@ -347,37 +577,91 @@ where
 
     #[inline]
     pub fn quad_vertex_positions_buffer(&self) -> &D::Buffer {
-        &self.quad_vertex_positions_buffer
+        &self.shared.quad_vertex_positions_buffer
     }
 
     #[inline]
     pub fn quad_vertex_indices_buffer(&self) -> &D::Buffer {
-        &self.quad_vertex_indices_buffer
+        &self.shared.quad_vertex_indices_buffer
     }
 
     fn upload_paint_data(&mut self, paint_data: &PaintData) {
+        self.shared.paint_textures.borrow_mut().truncate(paint_data.pages.len());
+        for (page_index, page) in paint_data.pages.iter().enumerate() {
+            self.upload_paint_page(page_index, page);
+        }
+    }
+
+    fn upload_paint_page(&mut self, page_index: usize, page: &PaintPageData) {
         // FIXME(pcwalton): This is a hack. We shouldn't be generating paint data at all on the
         // renderer side.
-        let (paint_size, paint_texels): (Vector2I, &[u8]);
+        let (page_format, paint_size, paint_texels): (PaintPageFormat, Vector2I, &[u8]);
         if self.postprocess_options.is_some() {
+            page_format = PaintPageFormat::RGBA8;
             paint_size = Vector2I::splat(1);
             paint_texels = &[255; 4];
         } else {
-            paint_size = paint_data.size;
-            paint_texels = &paint_data.texels;
+            page_format = page.format;
+            paint_size = page.size;
+            paint_texels = &page.texels;
+        };
+
+        // Gray levels are sRGB-encoded like colors are, but opacity is already linear.
+        let linear_compositing_enabled = self.linear_compositing_enabled();
+        let texture_format = match (page_format, linear_compositing_enabled) {
+            (PaintPageFormat::RGBA8, false) => TextureFormat::RGBA8,
+            (PaintPageFormat::RGBA8, true) => TextureFormat::RGBA16F,
+            (PaintPageFormat::Gray8, false) | (PaintPageFormat::Alpha8, _) => TextureFormat::R8,
+            (PaintPageFormat::Gray8, true) => TextureFormat::R16F,
         };
 
-        match self.paint_texture {
-            Some(ref paint_texture) if self.device.texture_size(paint_texture) == paint_size => {}
+        let mut paint_textures = self.shared.paint_textures.borrow_mut();
+        match paint_textures.get(page_index) {
+            Some(paint_texture) if self.device.texture_size(&paint_texture.texture) ==
+                paint_size && paint_texture.texture_format == texture_format => {}
             _ => {
-                let texture = self.device.create_texture(TextureFormat::RGBA8, paint_size);
-                self.paint_texture = Some(texture)
+                let paint_texture = PaintTexture {
+                    texture: self.device.create_texture(texture_format, paint_size),
+                    texture_format,
+                    page_format,
+                };
+                if page_index < paint_textures.len() {
+                    paint_textures[page_index] = paint_texture;
+                } else {
+                    paint_textures.push(paint_texture);
+                }
+            }
+        }
+        paint_textures[page_index].page_format = page_format;
+
+        let paint_texture = &paint_textures[page_index].texture;
+        let paint_rect = RectI::new(Vector2I::default(), paint_size);
+        match texture_format {
+            TextureFormat::RGBA16F | TextureFormat::R16F => {
+                // Decode the sRGB channels to linear light. Alpha is already linear.
+                let lut: Vec<f16> = (0..256).map(|value| {
+                    f16::from_f32(color::srgb_to_linear(value as f32 / 255.0))
+                }).collect();
+                let linear_texels: Vec<f16> = if texture_format == TextureFormat::R16F {
+                    paint_texels.iter().map(|&gray| lut[gray as usize]).collect()
+                } else {
+                    paint_texels.chunks(4).flat_map(|texel| {
+                        vec![lut[texel[0] as usize],
+                             lut[texel[1] as usize],
+                             lut[texel[2] as usize],
+                             f16::from_f32(texel[3] as f32 / 255.0)]
+                    }).collect()
+                };
+                self.device.upload_to_texture(paint_texture,
+                                              paint_rect,
+                                              TextureDataRef::F16(&linear_texels));
+            }
+            _ => {
+                self.device.upload_to_texture(paint_texture,
+                                              paint_rect,
+                                              TextureDataRef::U8(paint_texels));
             }
         }
-
-        self.device.upload_to_texture(self.paint_texture.as_ref().unwrap(),
-                                      RectI::new(Vector2I::default(), paint_size),
-                                      TextureDataRef::U8(paint_texels));
     }
 
     fn upload_solid_tiles(&mut self, solid_tiles: &[SolidTileBatchPrimitive]) {
@@ -387,6 +671,7 @@ where
             BufferTarget::Vertex,
             BufferUploadMode::Dynamic,
         );
+        self.solid_tile_vertex_array.vertex_buffer_size = mem::size_of_val(solid_tiles);
     }
 
     fn upload_alpha_tiles(&mut self, alpha_tiles: &[AlphaTileBatchPrimitive]) {
@@ -396,6 +681,7 @@ where
             BufferTarget::Vertex,
             BufferUploadMode::Dynamic,
         );
+        self.alpha_tile_vertex_array.vertex_buffer_size = mem::size_of_val(alpha_tiles);
     }
 
     fn add_fills(&mut self, mut fills: &[FillBatchPrimitive]) {
@@ -426,47 +712,260 @@ where
             BufferTarget::Vertex,
             BufferUploadMode::Dynamic,
         );
+        self.fill_vertex_array.vertex_buffer_size =
+            mem::size_of_val(self.buffered_fills.as_slice());
+
+        debug_assert!(self.buffered_fills.len() <= u32::MAX as usize);
+        let instance_count = self.buffered_fills.len() as u32;
+        self.draw_fills(instance_count);
+        self.buffered_fills.clear();
+    }
 
+    // Draws the first `instance_count` fills in the fill vertex buffer into the mask framebuffer.
+    fn draw_fills(&mut self, instance_count: u32) {
         let mut clear_color = None;
         if !self.framebuffer_flags.contains(
                 FramebufferFlags::MUST_PRESERVE_MASK_FRAMEBUFFER_CONTENTS) {
             clear_color = Some(ColorF::default());
         };
 
+        self.end_composite_timer_query();
         let timer_query = self.allocate_timer_query();
         self.device.begin_timer_query(&timer_query);
 
-        debug_assert!(self.buffered_fills.len() <= u32::MAX as usize);
-        self.device.draw_elements_instanced(6, self.buffered_fills.len() as u32, &RenderState {
-            target: &RenderTarget::Framebuffer(&self.mask_framebuffer),
-            program: &self.fill_program.program,
-            vertex_array: &self.fill_vertex_array.vertex_array,
-            primitive: Primitive::Triangles,
-            textures: &[&self.area_lut_texture],
-            uniforms: &[
-                (&self.fill_program.framebuffer_size_uniform,
-                 UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
-                                              MASK_FRAMEBUFFER_HEIGHT as f32))),
-                (&self.fill_program.tile_size_uniform,
-                 UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
-                (&self.fill_program.area_lut_uniform, UniformData::TextureUnit(0)),
-            ],
-            viewport: self.mask_viewport(),
-            options: RenderOptions {
-                blend: Some(BlendState {
-                    func: BlendFunc::RGBOneAlphaOne,
-                    ..BlendState::default()
-                }),
-                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
-                ..RenderOptions::default()
-            },
-        });
+        let framebuffer_size = F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
+                                          MASK_FRAMEBUFFER_HEIGHT as f32);
+        let tile_size = F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32);
+        let blend_state = BlendState {
+            src_rgb_factor: BlendFactor::One,
+            dest_rgb_factor: BlendFactor::One,
+            src_alpha_factor: BlendFactor::One,
+            dest_alpha_factor: BlendFactor::One,
+            ..BlendState::default()
+        };
+
+        let mask_framebuffer = self.mask_framebuffer.as_ref().unwrap();
+        match self.msaa_mask_framebuffer {
+            None => {
+                self.device.draw_elements_instanced(6, instance_count, &RenderState {
+                    target: &RenderTarget::Framebuffer(mask_framebuffer),
+                    program: &self.shared.fill_program.program,
+                    vertex_array: &self.fill_vertex_array.vertex_array,
+                    primitive: Primitive::Triangles,
+                    textures: &[&self.shared.area_lut_texture],
+                    uniforms: &[
+                        (&self.shared.fill_program.framebuffer_size_uniform,
+                         UniformData::Vec2(framebuffer_size)),
+                        (&self.shared.fill_program.tile_size_uniform,
+                         UniformData::Vec2(tile_size)),
+                        (&self.shared.fill_program.area_lut_uniform, UniformData::TextureUnit(0)),
+                    ],
+                    viewport: self.mask_viewport(),
+                    options: RenderOptions {
+                        blend: Some(blend_state),
+                        clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                        ..RenderOptions::default()
+                    },
+                });
+            }
+            Some(ref msaa_mask_framebuffer) => {
+                // The samples of earlier batches are kept, so resolving overwrites the whole mask.
+                self.device.draw_elements_instanced(6, instance_count, &RenderState {
+                    target: &RenderTarget::Framebuffer(msaa_mask_framebuffer),
+                    program: &self.shared.msaa_fill_program.program,
+                    vertex_array: &self.fill_vertex_array.msaa_vertex_array,
+                    primitive: Primitive::Triangles,
+                    textures: &[],
+                    uniforms: &[
+                        (&self.shared.msaa_fill_program.framebuffer_size_uniform,
+                         UniformData::Vec2(framebuffer_size)),
+                        (&self.shared.msaa_fill_program.tile_size_uniform,
+                         UniformData::Vec2(tile_size)),
+                    ],
+                    viewport: self.mask_viewport(),
+                    options: RenderOptions {
+                        blend: Some(blend_state),
+                        clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                        ..RenderOptions::default()
+                    },
+                });
+                self.device.resolve_framebuffer(msaa_mask_framebuffer, mask_framebuffer);
+            }
+        }
 
         self.device.end_timer_query(&timer_query);
         self.current_timers.stage_0.push(timer_query);
 
         self.framebuffer_flags.insert(FramebufferFlags::MUST_PRESERVE_MASK_FRAMEBUFFER_CONTENTS);
-        self.buffered_fills.clear();
+    }
+
+    fn add_segments(&mut self, segment_batch: &SegmentBatch) {
+        let first_segment = self.segment_batch.segments.len() as u32;
+        self.segment_batch.segments.extend_from_slice(&segment_batch.segments);
+        self.segment_batch.paths.extend(segment_batch.paths.iter().map(|segment_path| {
+            let mut segment_path = segment_path.clone();
+            segment_path.segments.start += first_segment;
+            segment_path.segments.end += first_segment;
+            segment_path
+        }));
+    }
+
+    // Bins the segments added since the fills were last flushed, on the GPU if it can, and buffers
+    // or draws their fills. Their alpha tiles are kept for the `SegmentAlphaTile` commands.
+    fn tile_segments(&mut self) {
+        let segment_batch = mem::take(&mut self.segment_batch);
+        self.segment_alpha_tiles.clear();
+        if segment_batch.paths.is_empty() {
+            return;
+        }
+
+        let fill_capacity = segment_batch.paths.iter().fold(0u32, |fill_capacity, path| {
+            fill_capacity.saturating_add(path.max_fill_count)
+        });
+        if self.segment_buffers.is_none() || fill_capacity > MAX_GPU_TILED_FILLS {
+            let binned = binning::bin_segments(&segment_batch);
+            self.add_fills(&binned.fills);
+            self.segment_alpha_tiles = binned.alpha_tiles;
+            return;
+        }
+
+        // The compute shaders write fills to the fill vertex buffer, so draw what's there first.
+        self.draw_buffered_fills();
+        self.segment_alpha_tiles = self.tile_segments_on_gpu(&segment_batch, fill_capacity);
+    }
+
+    fn tile_segments_on_gpu(&mut self, segment_batch: &SegmentBatch, fill_capacity: u32)
+                            -> Vec<Vec<AlphaTileBatchPrimitive>> {
+        let mut paths = Vec::with_capacity(segment_batch.paths.len());
+        let mut segment_paths = Vec::with_capacity(segment_batch.segments.len());
+        let mut path_slots = HashMap::new();
+        let (mut tile_count, mut row_count) = (0, 0);
+        for (path_index, path) in segment_batch.paths.iter().enumerate() {
+            let (tile_rect, tex_transform) = (path.tile_rect, path.tex_transform);
+            paths.push(SegmentPathPrimitive {
+                tile_rect: [tile_rect.min_x(), tile_rect.min_y(), tile_rect.max_x(),
+                            tile_rect.max_y()],
+                tiles: [tile_count,
+                        row_count,
+                        path.first_alpha_tile_index as u32,
+                        path.alpha_tile_count as u32],
+                info: [path.object_index as u32,
+                       match path.fill_rule { FillRule::Winding => 0, FillRule::EvenOdd => 1 },
+                       0,
+                       0],
+                tex_matrix: [tex_transform.matrix.m11(),
+                             tex_transform.matrix.m21(),
+                             tex_transform.matrix.m12(),
+                             tex_transform.matrix.m22()],
+                tex_vector: [tex_transform.vector.x(), tex_transform.vector.y(), 0.0, 0.0],
+            });
+            segment_paths.extend((path.segments.start..path.segments.end).map(|_| {
+                path_index as u32
+            }));
+            path_slots.insert(path.object_index, path_index);
+            tile_count += tile_rect.width() as u32 * tile_rect.height() as u32;
+            row_count += tile_rect.height() as u32;
+        }
+        let segments: Vec<[f32; 4]> = segment_batch.segments.iter().map(|segment| {
+            [segment.from_x(), segment.from_y(), segment.to_x(), segment.to_y()]
+        }).collect();
+
+        let device = &self.device;
+        let buffers = self.segment_buffers.as_mut().unwrap();
+        buffers.size =
+            upload_storage_buffer(device, &buffers.segments, BufferData::Memory(&segments)) +
+            upload_storage_buffer(device,
+                                  &buffers.segment_paths,
+                                  BufferData::Memory(&segment_paths)) +
+            upload_storage_buffer(device, &buffers.paths, BufferData::Memory(&paths)) +
+            upload_storage_buffer(device,
+                                  &buffers.tiles,
+                                  BufferData::Memory(&vec![0i32; tile_count as usize * 2])) +
+            upload_storage_buffer(device,
+                                  &buffers.counters,
+                                  BufferData::Memory(&vec![0u32; 2 + paths.len()])) +
+            upload_storage_buffer::<D, u32>(device,
+                                            &buffers.fill_tiles,
+                                            BufferData::Uninitialized(fill_capacity as usize)) +
+            upload_storage_buffer::<D, AlphaTileBatchPrimitive>(
+                device,
+                &buffers.alpha_tiles,
+                BufferData::Uninitialized(tile_count as usize));
+        self.device.allocate_buffer::<FillBatchPrimitive>(
+            &self.fill_vertex_array.vertex_buffer,
+            BufferData::Uninitialized(fill_capacity as usize),
+            BufferTarget::Vertex,
+            BufferUploadMode::Dynamic,
+        );
+        self.fill_vertex_array.vertex_buffer_size =
+            fill_capacity as usize * mem::size_of::<FillBatchPrimitive>();
+
+        let programs = self.shared.tiling_programs.as_ref().unwrap();
+        let buffers = self.segment_buffers.as_ref().unwrap();
+        let bin_program = &programs.bin_program;
+        self.device.dispatch_compute(tiling_dimensions(segments.len() as u32), &ComputeState {
+            program: &bin_program.program,
+            uniforms: &[
+                (&bin_program.segment_count_uniform, UniformData::Int(segments.len() as i32)),
+                (&bin_program.fill_capacity_uniform, UniformData::Int(fill_capacity as i32)),
+            ],
+            storage_buffers: &[
+                &buffers.segments,
+                &buffers.segment_paths,
+                &buffers.paths,
+                &buffers.tiles,
+                &self.fill_vertex_array.vertex_buffer,
+                &buffers.fill_tiles,
+                &buffers.counters,
+            ],
+        });
+        let propagate_program = &programs.propagate_program;
+        self.device.dispatch_compute(tiling_dimensions(row_count), &ComputeState {
+            program: &propagate_program.program,
+            uniforms: &[
+                (&propagate_program.row_count_uniform, UniformData::Int(row_count as i32)),
+                (&propagate_program.path_count_uniform, UniformData::Int(paths.len() as i32)),
+                (&propagate_program.alpha_tile_capacity_uniform,
+                 UniformData::Int(tile_count as i32)),
+            ],
+            storage_buffers: &[
+                &buffers.paths,
+                &buffers.tiles,
+                &buffers.counters,
+                &buffers.alpha_tiles,
+            ],
+        });
+        let patch_fills_program = &programs.patch_fills_program;
+        self.device.dispatch_compute(tiling_dimensions(fill_capacity), &ComputeState {
+            program: &patch_fills_program.program,
+            uniforms: &[
+                (&patch_fills_program.fill_capacity_uniform,
+                 UniformData::Int(fill_capacity as i32)),
+            ],
+            storage_buffers: &[
+                &buffers.tiles,
+                &self.fill_vertex_array.vertex_buffer,
+                &buffers.fill_tiles,
+                &buffers.counters,
+            ],
+        });
+
+        let counters: Vec<u32> = self.device.read_buffer(&buffers.counters, 0, 2);
+        let fill_count = cmp::min(counters[0], fill_capacity);
+        let alpha_tile_count = cmp::min(counters[1], tile_count) as usize;
+        let alpha_tiles: Vec<AlphaTileBatchPrimitive> =
+            self.device.read_buffer(&buffers.alpha_tiles, 0, alpha_tile_count);
+
+        if fill_count > 0 {
+            self.stats.fill_count += fill_count as usize;
+            self.draw_fills(fill_count);
+        }
+
+        let mut path_alpha_tiles = vec![vec![]; paths.len()];
+        for alpha_tile in alpha_tiles {
+            path_alpha_tiles[path_slots[&alpha_tile.object_index]].push(alpha_tile);
+        }
+        path_alpha_tiles
     }
 
     fn tile_transform(&self) -> Transform4F {
@@ -475,46 +974,238 @@ where
         Transform4F::from_scale(scale).translate(Vector4F::new(-1.0, 1.0, 0.0, 1.0))
     }
 
-    fn draw_alpha_tiles(&mut self, count: u32) {
-        let clear_color = self.clear_color_for_draw_operation();
+    fn draw_alpha_tile_batch(&mut self,
+                             alpha_tiles: &[AlphaTileBatchPrimitive],
+                             blend_mode: BlendMode,
+                             fill_rule: FillRule,
+                             page: PaintPageId,
+                             clipped: bool) {
+        if !self.reads_dest_for_blend_mode(blend_mode) {
+            self.upload_alpha_tiles(alpha_tiles);
+            self.draw_alpha_tiles(alpha_tiles.len() as u32, blend_mode, fill_rule, page, clipped);
+            return;
+        }
 
-        let mut textures = vec![self.device.framebuffer_texture(&self.mask_framebuffer)];
-        let mut uniforms = vec![
-            (&self.alpha_tile_program.transform_uniform,
-             UniformData::Mat4(self.tile_transform().to_columns())),
-            (&self.alpha_tile_program.tile_size_uniform,
-             UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
-            (&self.alpha_tile_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
-            (&self.alpha_tile_program.stencil_texture_size_uniform,
-             UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
-                                          MASK_FRAMEBUFFER_HEIGHT as f32))),
-        ];
+        // Each draw reads a copy of the destination made just before it, so a tile that covers
+        // another tile of the same draw wouldn't see it. Start a new draw wherever a tile repeats.
+        let mut tile_coords = HashSet::new();
+        let mut start = 0;
+        for (index, tile) in alpha_tiles.iter().enumerate() {
+            let coords = (tile.tile_x_lo, tile.tile_y_lo, tile.tile_hi);
+            if !tile_coords.insert(coords) {
+                self.upload_alpha_tiles(&alpha_tiles[start..index]);
+                self.draw_alpha_tiles((index - start) as u32,
+                                      blend_mode,
+                                      fill_rule,
+                                      page,
+                                      clipped);
+                tile_coords.clear();
+                tile_coords.insert(coords);
+                start = index;
+            }
+        }
+        self.upload_alpha_tiles(&alpha_tiles[start..]);
+        self.draw_alpha_tiles((alpha_tiles.len() - start) as u32,
+                              blend_mode,
+                              fill_rule,
+                              page,
+                              clipped);
+    }
 
-        let paint_texture = self.paint_texture.as_ref().unwrap();
-        textures.push(paint_texture);
-        uniforms.push((&self.alpha_tile_program.paint_texture_uniform,
-                        UniformData::TextureUnit(1)));
-        uniforms.push((&self.alpha_tile_program.paint_texture_size_uniform,
-                        UniformData::Vec2(self.device
-                                              .texture_size(paint_texture)
-                                              .0
-                                              .to_f32x2())));
+    // When postprocessing, the target holds coverage rather than color, and every blend mode that
+    // the shader handles composites coverage as source-over does.
+    fn reads_dest_for_blend_mode(&self, blend_mode: BlendMode) -> bool {
+        blend_mode.needs_dest_copy() && self.postprocess_options.is_none()
+    }
+
+    // Clipped tiles are only drawn inside the clip path in the stencil buffer.
+    fn draw_alpha_tiles(&mut self,
+                        count: u32,
+                        blend_mode: BlendMode,
+                        fill_rule: FillRule,
+                        page: PaintPageId,
+                        clipped: bool) {
+        // The copy of the destination must include the clear that the first draw would do.
+        let dest_texture = if self.reads_dest_for_blend_mode(blend_mode) {
+            self.clear_draw_framebuffer_if_necessary();
+            Some(self.device.copy_render_target(&self.draw_render_target(), self.draw_viewport()))
+        } else {
+            None
+        };
+
+        let clear_color = self.clear_color_for_draw_operation();
+        let stencil = if clipped { Some(self.clip_stencil_state()) } else { self.stencil_state() };
+
+        let mask_framebuffer = self.mask_framebuffer.as_ref().unwrap();
+        let paint_textures = self.shared.paint_textures.borrow();
+        let paint_texture = &paint_textures[page.0 as usize];
+        let mut textures = vec![self.device.framebuffer_texture(mask_framebuffer),
+                                &paint_texture.texture];
+        let tile_transform = UniformData::Mat4(self.tile_transform().to_columns());
+        let tile_size = UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32));
+        let stencil_texture_size = UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
+                                                                MASK_FRAMEBUFFER_HEIGHT as f32));
+        let paint_texture_size =
+            UniformData::Vec2(self.device.texture_size(&paint_texture.texture).0.to_f32x2());
+        let paint_format = paint_format_uniform_data(paint_texture.page_format);
+        let fill_rule = fill_rule_uniform_data(fill_rule);
+
+        let (program, vertex_array, uniforms);
+        match dest_texture {
+            None => {
+                let alpha_tile_program = &self.shared.alpha_tile_program;
+                program = &alpha_tile_program.program;
+                vertex_array = &self.alpha_tile_vertex_array.vertex_array;
+                uniforms = vec![
+                    (&alpha_tile_program.transform_uniform, tile_transform),
+                    (&alpha_tile_program.tile_size_uniform, tile_size),
+                    (&alpha_tile_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
+                    (&alpha_tile_program.stencil_texture_size_uniform, stencil_texture_size),
+                    (&alpha_tile_program.paint_texture_uniform, UniformData::TextureUnit(1)),
+                    (&alpha_tile_program.paint_texture_size_uniform, paint_texture_size),
+                    (&alpha_tile_program.paint_format_uniform, paint_format),
+                    (&alpha_tile_program.fill_rule_uniform, fill_rule),
+                ];
+            }
+            Some(ref dest_texture) => {
+                let blend_program = &self.shared.alpha_tile_blend_program;
+                program = &blend_program.program;
+                vertex_array = &self.alpha_tile_vertex_array.blend_vertex_array;
+                textures.push(dest_texture);
+                let draw_viewport = self.draw_viewport();
+                let dest_rect = F32x4::new(draw_viewport.origin().x() as f32,
+                                           draw_viewport.origin().y() as f32,
+                                           draw_viewport.size().x() as f32,
+                                           draw_viewport.size().y() as f32);
+                uniforms = vec![
+                    (&blend_program.transform_uniform, tile_transform),
+                    (&blend_program.tile_size_uniform, tile_size),
+                    (&blend_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
+                    (&blend_program.stencil_texture_size_uniform, stencil_texture_size),
+                    (&blend_program.paint_texture_uniform, UniformData::TextureUnit(1)),
+                    (&blend_program.paint_texture_size_uniform, paint_texture_size),
+                    (&blend_program.paint_format_uniform, paint_format),
+                    (&blend_program.fill_rule_uniform, fill_rule),
+                    (&blend_program.dest_texture_uniform, UniformData::TextureUnit(2)),
+                    (&blend_program.dest_rect_uniform, UniformData::Vec4(dest_rect)),
+                    (&blend_program.blend_mode_uniform,
+                     blend_mode_uniform_data(blend_mode).unwrap()),
+                ];
+            }
+        }
 
         self.device.draw_elements_instanced(6, count, &RenderState {
             target: &self.draw_render_target(),
-            program: &self.alpha_tile_program.program,
-            vertex_array: &self.alpha_tile_vertex_array.vertex_array,
+            program,
+            vertex_array,
             primitive: Primitive::Triangles,
             textures: &textures,
             uniforms: &uniforms,
             viewport: self.draw_viewport(),
             options: RenderOptions {
-                blend: Some(BlendState {
-                    func: BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha,
-                    ..BlendState::default()
-                }),
-                stencil: self.stencil_state(),
+                blend: Some(blend_mode.to_blend_state()),
+                stencil,
+                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                scissor: self.draw_scissor(),
+                ..RenderOptions::default()
+            },
+        });
+        drop(paint_textures);
+
+        self.preserve_draw_framebuffer();
+    }
+
+    // Blurs and saturates a copy of the destination, then draws it inside the tiles in place of
+    // the destination. The tiles' own paint is drawn next, over the filtered backdrop.
+    fn draw_backdrop_filter(&mut self,
+                            alpha_tiles: &[AlphaTileBatchPrimitive],
+                            filter: BackdropFilter,
+                            fill_rule: FillRule,
+                            clipped: bool) {
+        // When postprocessing, the target holds coverage, which has no colors to filter.
+        if self.postprocess_options.is_some() {
+            return;
+        }
+
+        // The copy of the destination must include the clear that the first draw would do.
+        self.clear_draw_framebuffer_if_necessary();
+        let draw_viewport = self.draw_viewport();
+        let dest_texture = self.device.copy_render_target(&self.draw_render_target(),
+                                                          draw_viewport);
+        self.init_backdrop_framebuffers();
+
+        // A two-dimensional Gaussian blur is a horizontal blur followed by a vertical one.
+        let (ref horizontal_framebuffer, ref vertical_framebuffer) =
+            *self.backdrop_framebuffers.as_ref().unwrap();
+        let horizontal_texture = self.device.framebuffer_texture(horizontal_framebuffer);
+        let passes = [
+            (&dest_texture, horizontal_framebuffer, F32x2::new(1.0, 0.0), 1.0),
+            (horizontal_texture, vertical_framebuffer, F32x2::new(0.0, 1.0), filter.saturation),
+        ];
+        let source_size = UniformData::Vec2(draw_viewport.size().to_f32().0);
+        let backdrop_filter_program = &self.shared.backdrop_filter_program;
+        for &(source_texture, target_framebuffer, direction, saturation) in &passes {
+            self.device.draw_elements(6, &RenderState {
+                target: &RenderTarget::Framebuffer(target_framebuffer),
+                program: &backdrop_filter_program.program,
+                vertex_array: &self.backdrop_filter_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[source_texture],
+                uniforms: &[
+                    (&backdrop_filter_program.source_uniform, UniformData::TextureUnit(0)),
+                    (&backdrop_filter_program.source_size_uniform, source_size),
+                    (&backdrop_filter_program.direction_uniform, UniformData::Vec2(direction)),
+                    (&backdrop_filter_program.blur_std_dev_uniform,
+                     UniformData::Float(filter.blur_std_dev)),
+                    (&backdrop_filter_program.saturation_uniform, UniformData::Float(saturation)),
+                ],
+                viewport: RectI::new(Vector2I::default(), draw_viewport.size()),
+                options: RenderOptions::default(),
+            });
+        }
+
+        self.upload_alpha_tiles(alpha_tiles);
+
+        let clear_color = self.clear_color_for_draw_operation();
+        let stencil = if clipped { Some(self.clip_stencil_state()) } else { self.stencil_state() };
+
+        let backdrop_tile_program = &self.shared.backdrop_tile_program;
+        let mask_framebuffer = self.mask_framebuffer.as_ref().unwrap();
+        let (_, ref vertical_framebuffer) = *self.backdrop_framebuffers.as_ref().unwrap();
+        let dest_rect = F32x4::new(draw_viewport.origin().x() as f32,
+                                   draw_viewport.origin().y() as f32,
+                                   draw_viewport.size().x() as f32,
+                                   draw_viewport.size().y() as f32);
+        self.device.draw_elements_instanced(6, alpha_tiles.len() as u32, &RenderState {
+            target: &self.draw_render_target(),
+            program: &backdrop_tile_program.program,
+            vertex_array: &self.alpha_tile_vertex_array.backdrop_vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[
+                self.device.framebuffer_texture(mask_framebuffer),
+                &dest_texture,
+                self.device.framebuffer_texture(vertical_framebuffer),
+            ],
+            uniforms: &[
+                (&backdrop_tile_program.transform_uniform,
+                 UniformData::Mat4(self.tile_transform().to_columns())),
+                (&backdrop_tile_program.tile_size_uniform,
+                 UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+                (&backdrop_tile_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
+                (&backdrop_tile_program.stencil_texture_size_uniform,
+                 UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
+                                              MASK_FRAMEBUFFER_HEIGHT as f32))),
+                (&backdrop_tile_program.fill_rule_uniform, fill_rule_uniform_data(fill_rule)),
+                (&backdrop_tile_program.dest_texture_uniform, UniformData::TextureUnit(1)),
+                (&backdrop_tile_program.filtered_dest_texture_uniform,
+                 UniformData::TextureUnit(2)),
+                (&backdrop_tile_program.dest_rect_uniform, UniformData::Vec4(dest_rect)),
+            ],
+            viewport: draw_viewport,
+            options: RenderOptions {
+                stencil,
                 clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                scissor: self.draw_scissor(),
                 ..RenderOptions::default()
             },
         });
@@ -522,27 +1213,33 @@ where
         self.preserve_draw_framebuffer();
     }
 
-    fn draw_solid_tiles(&mut self, count: u32) {
+    fn draw_solid_tiles(&mut self, count: u32, page: PaintPageId) {
         let clear_color = self.clear_color_for_draw_operation();
 
         let mut textures = vec![];
         let mut uniforms = vec![
-            (&self.solid_tile_program.transform_uniform,
+            (&self.shared.solid_tile_program.transform_uniform,
              UniformData::Mat4(self.tile_transform().to_columns())),
-            (&self.solid_tile_program.tile_size_uniform,
+            (&self.shared.solid_tile_program.tile_size_uniform,
              UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
         ];
 
-        let paint_texture = self.paint_texture.as_ref().unwrap();
-        textures.push(paint_texture);
-        uniforms.push((&self.solid_tile_program.paint_texture_uniform,
+        let paint_textures = self.shared.paint_textures.borrow();
+        let paint_texture = &paint_textures[page.0 as usize];
+        textures.push(&paint_texture.texture);
+        uniforms.push((&self.shared.solid_tile_program.paint_texture_uniform,
                         UniformData::TextureUnit(0)));
-        uniforms.push((&self.solid_tile_program.paint_texture_size_uniform,
-                        UniformData::Vec2(self.device.texture_size(paint_texture).0.to_f32x2())));
+        uniforms.push((&self.shared.solid_tile_program.paint_texture_size_uniform,
+                        UniformData::Vec2(self.device
+                                              .texture_size(&paint_texture.texture)
+                                              .0
+                                              .to_f32x2())));
+        uniforms.push((&self.shared.solid_tile_program.paint_format_uniform,
+                        paint_format_uniform_data(paint_texture.page_format)));
 
         self.device.draw_elements_instanced(6, count, &RenderState {
             target: &self.draw_render_target(),
-            program: &self.solid_tile_program.program,
+            program: &self.shared.solid_tile_program.program,
             vertex_array: &self.solid_tile_vertex_array.vertex_array,
             primitive: Primitive::Triangles,
             textures: &textures,
@@ -551,9 +1248,11 @@ where
             options: RenderOptions {
                 stencil: self.stencil_state(),
                 clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                scissor: self.draw_scissor(),
                 ..RenderOptions::default()
             },
         });
+        drop(paint_textures);
 
         self.preserve_draw_framebuffer();
     }
@@ -562,7 +1261,7 @@ where
         let mut clear_color = None;
         if !self.framebuffer_flags
                 .contains(FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS) {
-            clear_color = self.options.background_color;
+            clear_color = self.background_color();
         }
 
         let postprocess_options = match self.postprocess_options {
@@ -578,41 +1277,88 @@ where
         let main_viewport = self.main_viewport();
 
         let mut uniforms = vec![
-            (&self.postprocess_program.framebuffer_size_uniform,
+            (&self.shared.postprocess_program.framebuffer_size_uniform,
              UniformData::Vec2(main_viewport.size().to_f32().0)),
-            (&self.postprocess_program.source_uniform, UniformData::TextureUnit(0)),
-            (&self.postprocess_program.source_size_uniform,
+            (&self.shared.postprocess_program.source_uniform, UniformData::TextureUnit(0)),
+            (&self.shared.postprocess_program.source_size_uniform,
              UniformData::Vec2(source_texture_size.0.to_f32x2())),
-            (&self.postprocess_program.gamma_lut_uniform, UniformData::TextureUnit(1)),
-            (&self.postprocess_program.fg_color_uniform,
+            (&self.shared.postprocess_program.gamma_lut_uniform, UniformData::TextureUnit(1)),
+            (&self.shared.postprocess_program.fg_color_uniform,
              UniformData::Vec4(postprocess_options.fg_color.0)),
-            (&self.postprocess_program.bg_color_uniform,
+            (&self.shared.postprocess_program.bg_color_uniform,
              UniformData::Vec4(postprocess_options.bg_color.0)),
-            (&self.postprocess_program.gamma_correction_enabled_uniform,
+            (&self.shared.postprocess_program.gamma_correction_enabled_uniform,
              UniformData::Int(postprocess_options.gamma_correction as i32)),
         ];
 
         match postprocess_options.defringing_kernel {
             Some(ref kernel) => {
-                uniforms.push((&self.postprocess_program.kernel_uniform,
+                uniforms.push((&self.shared.postprocess_program.kernel_uniform,
                                UniformData::Vec4(F32x4::from_slice(&kernel.0))));
             }
             None => {
-                uniforms.push((&self.postprocess_program.kernel_uniform,
+                uniforms.push((&self.shared.postprocess_program.kernel_uniform,
                                UniformData::Vec4(F32x4::default())));
             }
         }
 
         self.device.draw_elements(6, &RenderState {
             target: &self.dest_render_target(),
-            program: &self.postprocess_program.program,
+            program: &self.shared.postprocess_program.program,
             vertex_array: &self.postprocess_vertex_array.vertex_array,
             primitive: Primitive::Triangles,
-            textures: &[&source_texture, &self.gamma_lut_texture],
+            textures: &[&source_texture, &self.shared.gamma_lut_texture],
             uniforms: &uniforms,
             viewport: main_viewport,
             options: RenderOptions {
                 clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                scissor: self.dest_scissor(),
+                ..RenderOptions::default()
+            },
+        });
+
+        self.framebuffer_flags.insert(FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS);
+    }
+
+    fn resolve_linear_framebuffer(&mut self) {
+        if !self.framebuffer_flags
+                .contains(FramebufferFlags::MUST_PRESERVE_LINEAR_FRAMEBUFFER_CONTENTS) {
+            return;
+        }
+
+        // The background color, if any, has already been composited in linear space, so the
+        // destination only needs to be cleared to transparent.
+        let mut clear_color = None;
+        if !self.framebuffer_flags
+                .contains(FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS) {
+            clear_color = self.options.background_color.map(|_| ColorF::transparent_black());
+        }
+
+        let linear_framebuffer = self.linear_framebuffer.as_ref().unwrap();
+        let source_texture = self.device.framebuffer_texture(linear_framebuffer);
+
+        self.device.draw_elements(6, &RenderState {
+            target: &self.dest_render_target(),
+            program: &self.shared.resolve_program.program,
+            vertex_array: &self.resolve_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[source_texture],
+            uniforms: &[
+                (&self.shared.resolve_program.source_uniform, UniformData::TextureUnit(0)),
+                (&self.shared.resolve_program.encode_srgb_uniform,
+                 UniformData::Int((self.options.dest_encoding == DestEncoding::Srgb) as i32)),
+            ],
+            viewport: self.main_viewport(),
+            options: RenderOptions {
+                blend: Some(BlendState {
+                    src_rgb_factor: BlendFactor::One,
+                    dest_rgb_factor: BlendFactor::OneMinusSrcAlpha,
+                    src_alpha_factor: BlendFactor::One,
+                    dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+                    ..BlendState::default()
+                }),
+                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                scissor: self.dest_scissor(),
                 ..RenderOptions::default()
             },
         });
@@ -621,6 +1367,80 @@ where
     }
 
     fn draw_stencil(&mut self, quad_positions: &[Vector4F]) {
+        // FIXME(pcwalton): Should we really write to the depth buffer?
+        let depth = Some(DepthState { func: DepthFunc::Less, write: true });
+        let stencil = StencilState {
+            func: StencilFunc::Always,
+            reference: 1,
+            mask: 1,
+            write: true,
+        };
+        let clear_ops = ClearOps { stencil: Some(0), ..ClearOps::default() };
+        self.draw_stencil_polygon(quad_positions, depth, stencil, clear_ops);
+    }
+
+    // Replaces the clip path in the stencil buffer with the pixels that the uploaded alpha tiles
+    // cover more than halfway.
+    fn draw_clip_stencil(&mut self, count: u32) {
+        // Clear the clip bit everywhere, leaving the bounding quad alone.
+        let viewport_quad = [
+            Vector4F::new(-1.0, -1.0, 0.0, 1.0),
+            Vector4F::new( 1.0, -1.0, 0.0, 1.0),
+            Vector4F::new( 1.0,  1.0, 0.0, 1.0),
+            Vector4F::new(-1.0,  1.0, 0.0, 1.0),
+        ];
+        let stencil = StencilState {
+            func: StencilFunc::Always,
+            reference: 0,
+            mask: CLIP_STENCIL_BIT,
+            write: true,
+        };
+        self.draw_stencil_polygon(&viewport_quad, None, stencil, ClearOps::default());
+        if count == 0 {
+            return;
+        }
+
+        let mask_framebuffer = self.mask_framebuffer.as_ref().unwrap();
+        let textures = [self.device.framebuffer_texture(mask_framebuffer)];
+        let uniforms = [
+            (&self.shared.clip_tile_program.transform_uniform,
+             UniformData::Mat4(self.tile_transform().to_columns())),
+            (&self.shared.clip_tile_program.tile_size_uniform,
+             UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+            (&self.shared.clip_tile_program.stencil_texture_uniform, UniformData::TextureUnit(0)),
+            (&self.shared.clip_tile_program.stencil_texture_size_uniform,
+             UniformData::Vec2(F32x2::new(MASK_FRAMEBUFFER_WIDTH as f32,
+                                          MASK_FRAMEBUFFER_HEIGHT as f32))),
+        ];
+
+        self.device.draw_elements_instanced(6, count, &RenderState {
+            target: &self.draw_render_target(),
+            program: &self.shared.clip_tile_program.program,
+            vertex_array: &self.alpha_tile_vertex_array.clip_vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &textures,
+            uniforms: &uniforms,
+            viewport: self.draw_viewport(),
+            options: RenderOptions {
+                stencil: Some(StencilState {
+                    func: StencilFunc::Always,
+                    reference: CLIP_STENCIL_BIT,
+                    mask: CLIP_STENCIL_BIT,
+                    write: true,
+                }),
+                color_mask: false,
+                scissor: self.draw_scissor(),
+                ..RenderOptions::default()
+            },
+        });
+    }
+
+    // Draws a convex polygon, given in normalized device coordinates, into the stencil buffer.
+    fn draw_stencil_polygon(&mut self,
+                            quad_positions: &[Vector4F],
+                            depth: Option<DepthState>,
+                            stencil: StencilState,
+                            clear_ops: ClearOps) {
         self.device.allocate_buffer(
             &self.stencil_vertex_array.vertex_buffer,
             BufferData::Memory(quad_positions),
@@ -643,23 +1463,18 @@ where
 
         self.device.draw_elements(indices.len() as u32, &RenderState {
             target: &self.draw_render_target(),
-            program: &self.stencil_program.program,
+            program: &self.shared.stencil_program.program,
             vertex_array: &self.stencil_vertex_array.vertex_array,
             primitive: Primitive::Triangles,
             textures: &[],
             uniforms: &[],
             viewport: self.draw_viewport(),
             options: RenderOptions {
-                // FIXME(pcwalton): Should we really write to the depth buffer?
-                depth: Some(DepthState { func: DepthFunc::Less, write: true }),
-                stencil: Some(StencilState {
-                    func: StencilFunc::Always,
-                    reference: 1,
-                    mask: 1,
-                    write: true,
-                }),
+                depth,
+                stencil: Some(stencil),
                 color_mask: false,
-                clear_ops: ClearOps { stencil: Some(0), ..ClearOps::default() },
+                clear_ops,
+                scissor: self.draw_scissor(),
                 ..RenderOptions::default()
             },
         });
@@ -675,25 +1490,26 @@ where
 
         self.device.draw_elements(6, &RenderState {
             target: &self.draw_render_target(),
-            program: &self.reprojection_program.program,
+            program: &self.shared.reprojection_program.program,
             vertex_array: &self.reprojection_vertex_array.vertex_array,
             primitive: Primitive::Triangles,
             textures: &[texture],
             uniforms: &[
-                (&self.reprojection_program.old_transform_uniform,
+                (&self.shared.reprojection_program.old_transform_uniform,
                  UniformData::from_transform_3d(old_transform)),
-                (&self.reprojection_program.new_transform_uniform,
+                (&self.shared.reprojection_program.new_transform_uniform,
                  UniformData::from_transform_3d(new_transform)),
-                (&self.reprojection_program.texture_uniform, UniformData::TextureUnit(0)),
+                (&self.shared.reprojection_program.texture_uniform, UniformData::TextureUnit(0)),
             ],
             viewport: self.draw_viewport(),
             options: RenderOptions {
                 blend: Some(BlendState {
-                    func: BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha,
+                    src_rgb_factor: BlendFactor::SrcAlpha,
                     ..BlendState::default()
                 }),
                 depth: Some(DepthState { func: DepthFunc::Less, write: false, }),
                 clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                scissor: self.draw_scissor(),
                 ..RenderOptions::default()
             },
         });
@@ -704,6 +1520,8 @@ where
     pub fn draw_render_target(&self) -> RenderTarget<D> {
         if self.postprocess_options.is_some() {
             RenderTarget::Framebuffer(self.postprocess_source_framebuffer.as_ref().unwrap())
+        } else if self.linear_compositing_enabled() {
+            RenderTarget::Framebuffer(self.linear_framebuffer.as_ref().unwrap())
         } else {
             self.dest_render_target()
         }
@@ -712,7 +1530,53 @@ where
     pub fn dest_render_target(&self) -> RenderTarget<D> {
         match self.dest_framebuffer {
             DestFramebuffer::Default { .. } => RenderTarget::Default,
-            DestFramebuffer::Other(ref framebuffer) => RenderTarget::Framebuffer(framebuffer),
+            DestFramebuffer::Other(ref framebuffer) |
+            DestFramebuffer::OtherViewport { ref framebuffer, .. } => {
+                RenderTarget::Framebuffer(framebuffer)
+            }
+        }
+    }
+
+    // Draws into the destination are clipped to the viewport, so that several scenes can share
+    // one surface without disturbing one another.
+    fn dest_scissor(&self) -> Option<RectI> {
+        let main_viewport = self.main_viewport();
+        let window_size = self.dest_framebuffer.window_size(&self.device);
+        if main_viewport == RectI::new(Vector2I::default(), window_size) {
+            None
+        } else {
+            Some(main_viewport)
+        }
+    }
+
+    fn draw_scissor(&self) -> Option<RectI> {
+        if self.postprocess_options.is_some() || self.linear_compositing_enabled() {
+            None
+        } else {
+            self.dest_scissor()
+        }
+    }
+
+    fn init_mask_framebuffer(&mut self) {
+        if self.mask_framebuffer.is_none() {
+            let size = Vector2I::new(MASK_FRAMEBUFFER_WIDTH, MASK_FRAMEBUFFER_HEIGHT);
+            let texture = self.device.create_texture(TextureFormat::R16F, size);
+            self.mask_framebuffer = Some(self.device.create_framebuffer(texture));
+        }
+
+        match self.options.fill_rasterization {
+            FillRasterization::AreaCoverage => self.msaa_mask_framebuffer = None,
+            FillRasterization::Multisample { sample_count } => {
+                if self.msaa_mask_framebuffer.is_none() {
+                    let size = Vector2I::new(MASK_FRAMEBUFFER_WIDTH, MASK_FRAMEBUFFER_HEIGHT);
+                    let framebuffer = self.device.create_multisampled_framebuffer(
+                        TextureFormat::R16F,
+                        size,
+                        sample_count,
+                    );
+                    self.msaa_mask_framebuffer = Some(framebuffer);
+                }
+            }
         }
     }
 
@@ -750,6 +1614,70 @@ where
         */
     }
 
+    fn init_linear_framebuffer(&mut self) {
+        if !self.linear_compositing_enabled() {
+            self.linear_framebuffer = None;
+            return;
+        }
+
+        let linear_framebuffer_size = self.main_viewport().size();
+        match self.linear_framebuffer {
+            Some(ref framebuffer)
+                if self
+                    .device
+                    .texture_size(self.device.framebuffer_texture(framebuffer))
+                    == linear_framebuffer_size => {}
+            _ => {
+                let texture = self
+                    .device
+                    .create_texture(TextureFormat::RGBA16F, linear_framebuffer_size);
+                self.linear_framebuffer = Some(self.device.create_framebuffer(texture));
+            }
+        };
+    }
+
+    // The framebuffers hold colors in the same range as the draw target.
+    fn init_backdrop_framebuffers(&mut self) {
+        let backdrop_framebuffer_size = self.draw_viewport().size();
+        match self.backdrop_framebuffers {
+            Some((ref framebuffer, _))
+                if self
+                    .device
+                    .texture_size(self.device.framebuffer_texture(framebuffer))
+                    == backdrop_framebuffer_size => {}
+            _ => {
+                let format = if self.linear_compositing_enabled() {
+                    TextureFormat::RGBA16F
+                } else {
+                    TextureFormat::RGBA8
+                };
+                let horizontal_texture =
+                    self.device.create_texture(format, backdrop_framebuffer_size);
+                let vertical_texture = self.device.create_texture(format, backdrop_framebuffer_size);
+                self.backdrop_framebuffers =
+                    Some((self.device.create_framebuffer(horizontal_texture),
+                          self.device.create_framebuffer(vertical_texture)));
+            }
+        };
+    }
+
+    #[inline]
+    fn linear_compositing_enabled(&self) -> bool {
+        // Postprocessing is done on coverage only, so it takes precedence.
+        let linear_dest = self.options.dest_encoding == DestEncoding::ExtendedLinear;
+        (self.options.linear_compositing || linear_dest) && self.postprocess_options.is_none()
+    }
+
+    fn draw_framebuffer_flag(&self) -> FramebufferFlags {
+        if self.postprocess_options.is_some() {
+            FramebufferFlags::MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS
+        } else if self.linear_compositing_enabled() {
+            FramebufferFlags::MUST_PRESERVE_LINEAR_FRAMEBUFFER_CONTENTS
+        } else {
+            FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS
+        }
+    }
+
     fn stencil_state(&self) -> Option<StencilState> {
         if !self.use_depth {
             return None;
@@ -763,29 +1691,48 @@ where
         })
     }
 
-    fn clear_color_for_draw_operation(&mut self) -> Option<ColorF> {
-        let postprocessing_needed = self.postprocess_options.is_some();
-        let flag = if postprocessing_needed {
-            FramebufferFlags::MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS
-        } else {
-            FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS
-        };
+    // Passes pixels inside the clip path, and inside the bounding quad if there is one.
+    fn clip_stencil_state(&self) -> StencilState {
+        let mask = if self.use_depth { CLIP_STENCIL_BIT | 1 } else { CLIP_STENCIL_BIT };
+        StencilState { func: StencilFunc::Equal, reference: mask, mask, write: false }
+    }
 
+    fn clear_color_for_draw_operation(&mut self) -> Option<ColorF> {
+        let flag = self.draw_framebuffer_flag();
         if self.framebuffer_flags.contains(flag) {
             None
-        } else if !postprocessing_needed {
-            self.options.background_color
-        } else {
+        } else if self.postprocess_options.is_some() {
             Some(ColorF::default())
+        } else if self.linear_compositing_enabled() {
+            // The linear framebuffer is always premultiplied, since the resolve pass expects it.
+            let background_color = self.options.background_color;
+            Some(background_color.map_or(ColorF::transparent_black(),
+                                         |color| color.to_linear().premultiply()))
+        } else {
+            self.background_color()
         }
     }
 
-    fn preserve_draw_framebuffer(&mut self) {
-        let flag = if self.postprocess_options.is_some() {
-            FramebufferFlags::MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS
+    // The color to clear the destination to, in the alpha mode the options ask for.
+    fn background_color(&self) -> Option<ColorF> {
+        let background_color = self.options.background_color?;
+        if self.options.premultiplied_alpha {
+            Some(background_color.premultiply())
         } else {
-            FramebufferFlags::MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS
-        };
+            Some(background_color)
+        }
+    }
+
+    fn clear_draw_framebuffer_if_necessary(&mut self) {
+        if let Some(clear_color) = self.clear_color_for_draw_operation() {
+            let clear_ops = ClearOps { color: Some(clear_color), ..ClearOps::default() };
+            self.device.clear(&self.draw_render_target(), self.draw_scissor(), &clear_ops);
+            self.preserve_draw_framebuffer();
+        }
+    }
+
+    fn preserve_draw_framebuffer(&mut self) {
+        let flag = self.draw_framebuffer_flag();
         self.framebuffer_flags.insert(flag);
     }
 
@@ -795,13 +1742,17 @@ where
             Some(PostprocessOptions { defringing_kernel: Some(_), .. }) => {
                 RectI::new(Vector2I::default(), main_viewport.size().scale_xy(Vector2I::new(3, 1)))
             }
+            _ if self.linear_compositing_enabled() => {
+                RectI::new(Vector2I::default(), main_viewport.size())
+            }
             _ => main_viewport,
         }
     }
 
     fn main_viewport(&self) -> RectI {
         match self.dest_framebuffer {
-            DestFramebuffer::Default { viewport, .. } => viewport,
+            DestFramebuffer::Default { viewport, .. } |
+            DestFramebuffer::OtherViewport { viewport, .. } => viewport,
             DestFramebuffer::Other(ref framebuffer) => {
                 let size = self
                     .device
@@ -812,7 +1763,7 @@ where
     }
 
     fn mask_viewport(&self) -> RectI {
-        let texture = self.device.framebuffer_texture(&self.mask_framebuffer);
+        let texture = self.device.framebuffer_texture(self.mask_framebuffer.as_ref().unwrap());
         RectI::new(Vector2I::default(), self.device.texture_size(texture))
     }
 
@@ -824,14 +1775,19 @@ where
     }
 
     fn begin_composite_timer_query(&mut self) {
+        self.end_composite_timer_query();
         let timer_query = self.allocate_timer_query();
         self.device.begin_timer_query(&timer_query);
-        self.current_timers.stage_1 = Some(timer_query);
+        self.current_timers.stage_1.push(timer_query);
+        self.current_timers.stage_1_active = true;
     }
 
+    // Timer queries can't nest, so this must be called before fills are drawn as well as at the
+    // end of the scene.
     fn end_composite_timer_query(&mut self) {
-        if let Some(ref query) = self.current_timers.stage_1 {
-            self.device.end_timer_query(query);
+        if self.current_timers.stage_1_active {
+            self.device.end_timer_query(self.current_timers.stage_1.last().unwrap());
+            self.current_timers.stage_1_active = false;
         }
     }
 }
@@ -841,7 +1797,11 @@ where
     D: Device,
 {
     vertex_array: D::VertexArray,
+    // The same vertices, laid out for `MSAAFillProgram`.
+    msaa_vertex_array: D::VertexArray,
     vertex_buffer: D::Buffer,
+    // In bytes.
+    vertex_buffer_size: usize,
 }
 
 impl<D> FillVertexArray<D>
@@ -851,11 +1811,10 @@ where
     fn new(
         device: &D,
         fill_program: &FillProgram<D>,
+        msaa_fill_program: &MSAAFillProgram<D>,
         quad_vertex_positions_buffer: &D::Buffer,
         quad_vertex_indices_buffer: &D::Buffer,
     ) -> FillVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
-
         let vertex_buffer = device.create_buffer();
         let vertex_buffer_data: BufferData<FillBatchPrimitive> =
             BufferData::Uninitialized(MAX_FILLS_PER_BATCH);
@@ -866,15 +1825,40 @@ where
             BufferUploadMode::Dynamic,
         );
 
-        let tess_coord_attr = device.get_vertex_attr(&fill_program.program, "TessCoord").unwrap();
-        let from_px_attr = device.get_vertex_attr(&fill_program.program, "FromPx").unwrap();
-        let to_px_attr = device.get_vertex_attr(&fill_program.program, "ToPx").unwrap();
-        let from_subpx_attr = device.get_vertex_attr(&fill_program.program, "FromSubpx").unwrap();
-        let to_subpx_attr = device.get_vertex_attr(&fill_program.program, "ToSubpx").unwrap();
-        let tile_index_attr = device.get_vertex_attr(&fill_program.program, "TileIndex").unwrap();
+        let vertex_array = device.create_vertex_array();
+        FillVertexArray::configure(device,
+                                   &fill_program.program,
+                                   &vertex_array,
+                                   &vertex_buffer,
+                                   quad_vertex_positions_buffer,
+                                   quad_vertex_indices_buffer);
+        let msaa_vertex_array = device.create_vertex_array();
+        FillVertexArray::configure(device,
+                                   &msaa_fill_program.program,
+                                   &msaa_vertex_array,
+                                   &vertex_buffer,
+                                   quad_vertex_positions_buffer,
+                                   quad_vertex_indices_buffer);
+
+        let vertex_buffer_size = MAX_FILLS_PER_BATCH * mem::size_of::<FillBatchPrimitive>();
+        FillVertexArray { vertex_array, msaa_vertex_array, vertex_buffer, vertex_buffer_size }
+    }
 
-        device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
-        device.configure_vertex_attr(&vertex_array, &tess_coord_attr, &VertexAttrDescriptor {
+    fn configure(device: &D,
+                 program: &D::Program,
+                 vertex_array: &D::VertexArray,
+                 vertex_buffer: &D::Buffer,
+                 quad_vertex_positions_buffer: &D::Buffer,
+                 quad_vertex_indices_buffer: &D::Buffer) {
+        let tess_coord_attr = device.get_vertex_attr(program, "TessCoord").unwrap();
+        let from_px_attr = device.get_vertex_attr(program, "FromPx").unwrap();
+        let to_px_attr = device.get_vertex_attr(program, "ToPx").unwrap();
+        let from_subpx_attr = device.get_vertex_attr(program, "FromSubpx").unwrap();
+        let to_subpx_attr = device.get_vertex_attr(program, "ToSubpx").unwrap();
+        let tile_index_attr = device.get_vertex_attr(program, "TileIndex").unwrap();
+
+        device.bind_buffer(vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(vertex_array, &tess_coord_attr, &VertexAttrDescriptor {
             size: 2,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::U16,
@@ -883,8 +1867,8 @@ where
             divisor: 0,
             buffer_index: 0,
         });
-        device.bind_buffer(&vertex_array, &vertex_buffer, BufferTarget::Vertex);
-        device.configure_vertex_attr(&vertex_array, &from_px_attr, &VertexAttrDescriptor {
+        device.bind_buffer(vertex_array, vertex_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(vertex_array, &from_px_attr, &VertexAttrDescriptor {
             size: 1,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::U8,
@@ -893,7 +1877,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array, &to_px_attr, &VertexAttrDescriptor {
+        device.configure_vertex_attr(vertex_array, &to_px_attr, &VertexAttrDescriptor {
             size: 1,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::U8,
@@ -902,7 +1886,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array, &from_subpx_attr, &VertexAttrDescriptor {
+        device.configure_vertex_attr(vertex_array, &from_subpx_attr, &VertexAttrDescriptor {
             size: 2,
             class: VertexAttrClass::FloatNorm,
             attr_type: VertexAttrType::U8,
@@ -911,7 +1895,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array, &to_subpx_attr, &VertexAttrDescriptor {
+        device.configure_vertex_attr(vertex_array, &to_subpx_attr, &VertexAttrDescriptor {
             size: 2,
             class: VertexAttrClass::FloatNorm,
             attr_type: VertexAttrType::U8,
@@ -920,7 +1904,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array, &tile_index_attr, &VertexAttrDescriptor {
+        device.configure_vertex_attr(vertex_array, &tile_index_attr, &VertexAttrDescriptor {
             size: 1,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::U16,
@@ -929,9 +1913,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
-
-        FillVertexArray { vertex_array, vertex_buffer }
+        device.bind_buffer(vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
     }
 }
 
@@ -940,7 +1922,15 @@ where
     D: Device,
 {
     vertex_array: D::VertexArray,
+    // The same vertices, laid out for `AlphaTileBlendProgram`.
+    blend_vertex_array: D::VertexArray,
+    // The same vertices, laid out for `BackdropTileProgram`.
+    backdrop_vertex_array: D::VertexArray,
+    // The same vertices, laid out for `ClipTileProgram`.
+    clip_vertex_array: D::VertexArray,
     vertex_buffer: D::Buffer,
+    // In bytes.
+    vertex_buffer_size: usize,
 }
 
 impl<D> AlphaTileVertexArray<D>
@@ -950,28 +1940,72 @@ where
     fn new(
         device: &D,
         alpha_tile_program: &AlphaTileProgram<D>,
+        alpha_tile_blend_program: &AlphaTileBlendProgram<D>,
+        backdrop_tile_program: &BackdropTileProgram<D>,
+        clip_tile_program: &ClipTileProgram<D>,
         quad_vertex_positions_buffer: &D::Buffer,
         quad_vertex_indices_buffer: &D::Buffer,
     ) -> AlphaTileVertexArray<D> {
-        let (vertex_array, vertex_buffer) = (device.create_vertex_array(), device.create_buffer());
+        let vertex_buffer = device.create_buffer();
+        let vertex_array = device.create_vertex_array();
+        AlphaTileVertexArray::configure(device,
+                                        &alpha_tile_program.program,
+                                        &vertex_array,
+                                        &vertex_buffer,
+                                        quad_vertex_positions_buffer,
+                                        quad_vertex_indices_buffer);
+
+        let blend_vertex_array = device.create_vertex_array();
+        AlphaTileVertexArray::configure(device,
+                                        &alpha_tile_blend_program.program,
+                                        &blend_vertex_array,
+                                        &vertex_buffer,
+                                        quad_vertex_positions_buffer,
+                                        quad_vertex_indices_buffer);
+
+        let backdrop_vertex_array = device.create_vertex_array();
+        AlphaTileVertexArray::configure(device,
+                                        &backdrop_tile_program.program,
+                                        &backdrop_vertex_array,
+                                        &vertex_buffer,
+                                        quad_vertex_positions_buffer,
+                                        quad_vertex_indices_buffer);
+
+        let clip_vertex_array = device.create_vertex_array();
+        AlphaTileVertexArray::configure(device,
+                                        &clip_tile_program.program,
+                                        &clip_vertex_array,
+                                        &vertex_buffer,
+                                        quad_vertex_positions_buffer,
+                                        quad_vertex_indices_buffer);
+
+        AlphaTileVertexArray {
+            vertex_array,
+            blend_vertex_array,
+            backdrop_vertex_array,
+            clip_vertex_array,
+            vertex_buffer,
+            vertex_buffer_size: 0,
+        }
+    }
 
-        let tess_coord_attr = device.get_vertex_attr(&alpha_tile_program.program, "TessCoord")
-                                    .unwrap();
-        let tile_origin_attr = device.get_vertex_attr(&alpha_tile_program.program, "TileOrigin")
-                                     .unwrap();
-        let backdrop_attr = device.get_vertex_attr(&alpha_tile_program.program, "Backdrop")
-                                  .unwrap();
-        let tile_index_attr = device.get_vertex_attr(&alpha_tile_program.program, "TileIndex")
-                                    .unwrap();
-        let color_tex_matrix_attr = device.get_vertex_attr(&alpha_tile_program.program,
-                                                           "ColorTexMatrix").unwrap();
-        let color_tex_offset_attr = device.get_vertex_attr(&alpha_tile_program.program,
-                                                           "ColorTexOffset").unwrap();
+    // Sets up the attributes of `program`. Clip and backdrop tiles have no paint, so the shader
+    // compiler may drop the paint attributes.
+    fn configure(device: &D,
+                 program: &D::Program,
+                 vertex_array: &D::VertexArray,
+                 vertex_buffer: &D::Buffer,
+                 quad_vertex_positions_buffer: &D::Buffer,
+                 quad_vertex_indices_buffer: &D::Buffer) {
+        let tess_coord_attr = device.get_vertex_attr(program, "TessCoord").unwrap();
+        let tile_origin_attr = device.get_vertex_attr(program, "TileOrigin").unwrap();
+        let backdrop_attr = device.get_vertex_attr(program, "Backdrop").unwrap();
+        let tile_index_attr = device.get_vertex_attr(program, "TileIndex").unwrap();
 
         // NB: The object must be of type `I16`, not `U16`, to work around a macOS Radeon
         // driver bug.
-        device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
-        device.configure_vertex_attr(&vertex_array, &tess_coord_attr, &VertexAttrDescriptor {
+        device.bind_buffer(vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(vertex_array, &tess_coord_attr, &VertexAttrDescriptor {
             size: 2,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::U16,
@@ -980,8 +2014,8 @@ where
             divisor: 0,
             buffer_index: 0,
         });
-        device.bind_buffer(&vertex_array, &vertex_buffer, BufferTarget::Vertex);
-        device.configure_vertex_attr(&vertex_array, &tile_origin_attr, &VertexAttrDescriptor {
+        device.bind_buffer(vertex_array, vertex_buffer, BufferTarget::Vertex);
+        device.configure_vertex_attr(vertex_array, &tile_origin_attr, &VertexAttrDescriptor {
             size: 3,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::U8,
@@ -990,7 +2024,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array, &backdrop_attr, &VertexAttrDescriptor {
+        device.configure_vertex_attr(vertex_array, &backdrop_attr, &VertexAttrDescriptor {
             size: 1,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::I8,
@@ -999,7 +2033,7 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array, &tile_index_attr, &VertexAttrDescriptor {
+        device.configure_vertex_attr(vertex_array, &tile_index_attr, &VertexAttrDescriptor {
             size: 1,
             class: VertexAttrClass::Int,
             attr_type: VertexAttrType::I16,
@@ -1008,31 +2042,33 @@ where
             divisor: 1,
             buffer_index: 1,
         });
-        device.configure_vertex_attr(&vertex_array,
-                                     &color_tex_matrix_attr,
-                                     &VertexAttrDescriptor {
-                                        size: 4,
-                                        class: VertexAttrClass::FloatNorm,
-                                        attr_type: VertexAttrType::U16,
-                                        stride: MASK_TILE_INSTANCE_SIZE,
-                                        offset: 8,
-                                        divisor: 1,
-                                        buffer_index: 1,
-                                     });
-        device.configure_vertex_attr(&vertex_array,
-                                     &color_tex_offset_attr,
-                                     &VertexAttrDescriptor {
-                                        size: 2,
-                                        class: VertexAttrClass::FloatNorm,
-                                        attr_type: VertexAttrType::U16,
-                                        stride: MASK_TILE_INSTANCE_SIZE,
-                                        offset: 16,
-                                        divisor: 1,
-                                        buffer_index: 1,
-                                     });
-        device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
-
-        AlphaTileVertexArray { vertex_array, vertex_buffer }
+        if let Some(color_tex_matrix_attr) = device.get_vertex_attr(program, "ColorTexMatrix") {
+            device.configure_vertex_attr(vertex_array,
+                                         &color_tex_matrix_attr,
+                                         &VertexAttrDescriptor {
+                                            size: 4,
+                                            class: VertexAttrClass::Float,
+                                            attr_type: VertexAttrType::F32,
+                                            stride: MASK_TILE_INSTANCE_SIZE,
+                                            offset: 8,
+                                            divisor: 1,
+                                            buffer_index: 1,
+                                         });
+        }
+        if let Some(color_tex_offset_attr) = device.get_vertex_attr(program, "ColorTexOffset") {
+            device.configure_vertex_attr(vertex_array,
+                                         &color_tex_offset_attr,
+                                         &VertexAttrDescriptor {
+                                            size: 2,
+                                            class: VertexAttrClass::Float,
+                                            attr_type: VertexAttrType::F32,
+                                            stride: MASK_TILE_INSTANCE_SIZE,
+                                            offset: 24,
+                                            divisor: 1,
+                                            buffer_index: 1,
+                                         });
+        }
+        device.bind_buffer(vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
     }
 }
 
@@ -1042,6 +2078,8 @@ where
 {
     vertex_array: D::VertexArray,
     vertex_buffer: D::Buffer,
+    // In bytes.
+    vertex_buffer_size: usize,
 }
 
 impl<D> SolidTileVertexArray<D>
@@ -1091,8 +2129,8 @@ where
                                      &color_tex_matrix_attr,
                                      &VertexAttrDescriptor {
                                         size: 4,
-                                        class: VertexAttrClass::FloatNorm,
-                                        attr_type: VertexAttrType::U16,
+                                        class: VertexAttrClass::Float,
+                                        attr_type: VertexAttrType::F32,
                                         stride: SOLID_TILE_INSTANCE_SIZE,
                                         offset: 4,
                                         divisor: 1,
@@ -1102,16 +2140,16 @@ where
                                      &color_tex_offset_attr,
                                      &VertexAttrDescriptor {
                                         size: 2,
-                                        class: VertexAttrClass::FloatNorm,
-                                        attr_type: VertexAttrType::U16,
+                                        class: VertexAttrClass::Float,
+                                        attr_type: VertexAttrType::F32,
                                         stride: SOLID_TILE_INSTANCE_SIZE,
-                                        offset: 12,
+                                        offset: 20,
                                         divisor: 1,
                                         buffer_index: 1,
                                      });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        SolidTileVertexArray { vertex_array, vertex_buffer }
+        SolidTileVertexArray { vertex_array, vertex_buffer, vertex_buffer_size: 0 }
     }
 }
 
@@ -1143,12 +2181,61 @@ where
     }
 }
 
+// Draws fills for `FillRasterization::Multisample`.
+struct MSAAFillProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    framebuffer_size_uniform: D::Uniform,
+    tile_size_uniform: D::Uniform,
+}
+
+impl<D> MSAAFillProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> MSAAFillProgram<D> {
+        let program = device.create_program(resources, "fill_msaa");
+        let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let tile_size_uniform = device.get_uniform(&program, "TileSize");
+        MSAAFillProgram { program, framebuffer_size_uniform, tile_size_uniform }
+    }
+}
+
+// A page of the paint texture.
+struct PaintTexture<D> where D: Device {
+    texture: D::Texture,
+    texture_format: TextureFormat,
+    // How the tile shaders read the texels. This is `RGBA8` for the placeholder page that's
+    // uploaded when postprocessing, whatever the page's format.
+    page_format: PaintPageFormat,
+}
+
+// The values of `uFillRule` in the alpha tile shaders.
+fn fill_rule_uniform_data(fill_rule: FillRule) -> UniformData {
+    UniformData::Int(match fill_rule {
+        FillRule::Winding => 0,
+        FillRule::EvenOdd => 1,
+    })
+}
+
+// The values of `uPaintFormat` in the tile shaders.
+fn paint_format_uniform_data(page_format: PaintPageFormat) -> UniformData {
+    UniformData::Int(match page_format {
+        PaintPageFormat::RGBA8 => 0,
+        PaintPageFormat::Gray8 => 1,
+        PaintPageFormat::Alpha8 => 2,
+    })
+}
+
 struct SolidTileProgram<D> where D: Device {
     program: D::Program,
     transform_uniform: D::Uniform,
     tile_size_uniform: D::Uniform,
     paint_texture_uniform: D::Uniform,
     paint_texture_size_uniform: D::Uniform,
+    paint_format_uniform: D::Uniform,
 }
 
 impl<D> SolidTileProgram<D> where D: Device {
@@ -1158,12 +2245,14 @@ impl<D> SolidTileProgram<D> where D: Device {
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let paint_texture_uniform = device.get_uniform(&program, "PaintTexture");
         let paint_texture_size_uniform = device.get_uniform(&program, "PaintTextureSize");
+        let paint_format_uniform = device.get_uniform(&program, "PaintFormat");
         SolidTileProgram {
             program,
             transform_uniform,
             tile_size_uniform,
             paint_texture_uniform,
             paint_texture_size_uniform,
+            paint_format_uniform,
         }
     }
 }
@@ -1176,6 +2265,8 @@ struct AlphaTileProgram<D> where D: Device {
     stencil_texture_size_uniform: D::Uniform,
     paint_texture_uniform: D::Uniform,
     paint_texture_size_uniform: D::Uniform,
+    paint_format_uniform: D::Uniform,
+    fill_rule_uniform: D::Uniform,
 }
 
 impl<D> AlphaTileProgram<D> where D: Device {
@@ -1187,6 +2278,8 @@ impl<D> AlphaTileProgram<D> where D: Device {
         let stencil_texture_size_uniform = device.get_uniform(&program, "StencilTextureSize");
         let paint_texture_uniform = device.get_uniform(&program, "PaintTexture");
         let paint_texture_size_uniform = device.get_uniform(&program, "PaintTextureSize");
+        let paint_format_uniform = device.get_uniform(&program, "PaintFormat");
+        let fill_rule_uniform = device.get_uniform(&program, "FillRule");
         AlphaTileProgram {
             program,
             transform_uniform,
@@ -1195,6 +2288,129 @@ impl<D> AlphaTileProgram<D> where D: Device {
             stencil_texture_size_uniform,
             paint_texture_uniform,
             paint_texture_size_uniform,
+            paint_format_uniform,
+            fill_rule_uniform,
+        }
+    }
+}
+
+// Draws alpha tiles with the blend modes that read the destination, with the alpha tile vertex
+// shader.
+struct AlphaTileBlendProgram<D> where D: Device {
+    program: D::Program,
+    transform_uniform: D::Uniform,
+    tile_size_uniform: D::Uniform,
+    stencil_texture_uniform: D::Uniform,
+    stencil_texture_size_uniform: D::Uniform,
+    paint_texture_uniform: D::Uniform,
+    paint_texture_size_uniform: D::Uniform,
+    paint_format_uniform: D::Uniform,
+    fill_rule_uniform: D::Uniform,
+    dest_texture_uniform: D::Uniform,
+    dest_rect_uniform: D::Uniform,
+    blend_mode_uniform: D::Uniform,
+}
+
+impl<D> AlphaTileBlendProgram<D> where D: Device {
+    fn new(device: &D, resources: &dyn ResourceLoader) -> AlphaTileBlendProgram<D> {
+        let program = device.create_program_from_shader_names(resources,
+                                                              "tile_alpha_blend",
+                                                              "tile_alpha",
+                                                              "tile_alpha_blend");
+        let transform_uniform = device.get_uniform(&program, "Transform");
+        let tile_size_uniform = device.get_uniform(&program, "TileSize");
+        let stencil_texture_uniform = device.get_uniform(&program, "StencilTexture");
+        let stencil_texture_size_uniform = device.get_uniform(&program, "StencilTextureSize");
+        let paint_texture_uniform = device.get_uniform(&program, "PaintTexture");
+        let paint_texture_size_uniform = device.get_uniform(&program, "PaintTextureSize");
+        let paint_format_uniform = device.get_uniform(&program, "PaintFormat");
+        let fill_rule_uniform = device.get_uniform(&program, "FillRule");
+        let dest_texture_uniform = device.get_uniform(&program, "DestTexture");
+        let dest_rect_uniform = device.get_uniform(&program, "DestRect");
+        let blend_mode_uniform = device.get_uniform(&program, "BlendMode");
+        AlphaTileBlendProgram {
+            program,
+            transform_uniform,
+            tile_size_uniform,
+            stencil_texture_uniform,
+            stencil_texture_size_uniform,
+            paint_texture_uniform,
+            paint_texture_size_uniform,
+            paint_format_uniform,
+            fill_rule_uniform,
+            dest_texture_uniform,
+            dest_rect_uniform,
+            blend_mode_uniform,
+        }
+    }
+}
+
+// Draws filtered backdrops inside paths, with the alpha tile vertex shader.
+struct BackdropTileProgram<D> where D: Device {
+    program: D::Program,
+    transform_uniform: D::Uniform,
+    tile_size_uniform: D::Uniform,
+    stencil_texture_uniform: D::Uniform,
+    stencil_texture_size_uniform: D::Uniform,
+    fill_rule_uniform: D::Uniform,
+    dest_texture_uniform: D::Uniform,
+    filtered_dest_texture_uniform: D::Uniform,
+    dest_rect_uniform: D::Uniform,
+}
+
+impl<D> BackdropTileProgram<D> where D: Device {
+    fn new(device: &D, resources: &dyn ResourceLoader) -> BackdropTileProgram<D> {
+        let program = device.create_program_from_shader_names(resources,
+                                                              "tile_backdrop",
+                                                              "tile_alpha",
+                                                              "tile_backdrop");
+        let transform_uniform = device.get_uniform(&program, "Transform");
+        let tile_size_uniform = device.get_uniform(&program, "TileSize");
+        let stencil_texture_uniform = device.get_uniform(&program, "StencilTexture");
+        let stencil_texture_size_uniform = device.get_uniform(&program, "StencilTextureSize");
+        let fill_rule_uniform = device.get_uniform(&program, "FillRule");
+        let dest_texture_uniform = device.get_uniform(&program, "DestTexture");
+        let filtered_dest_texture_uniform = device.get_uniform(&program, "FilteredDestTexture");
+        let dest_rect_uniform = device.get_uniform(&program, "DestRect");
+        BackdropTileProgram {
+            program,
+            transform_uniform,
+            tile_size_uniform,
+            stencil_texture_uniform,
+            stencil_texture_size_uniform,
+            fill_rule_uniform,
+            dest_texture_uniform,
+            filtered_dest_texture_uniform,
+            dest_rect_uniform,
+        }
+    }
+}
+
+// Draws clip paths into the stencil buffer, with the alpha tile vertex shader.
+struct ClipTileProgram<D> where D: Device {
+    program: D::Program,
+    transform_uniform: D::Uniform,
+    tile_size_uniform: D::Uniform,
+    stencil_texture_uniform: D::Uniform,
+    stencil_texture_size_uniform: D::Uniform,
+}
+
+impl<D> ClipTileProgram<D> where D: Device {
+    fn new(device: &D, resources: &dyn ResourceLoader) -> ClipTileProgram<D> {
+        let program = device.create_program_from_shader_names(resources,
+                                                              "tile_clip",
+                                                              "tile_alpha",
+                                                              "tile_clip");
+        let transform_uniform = device.get_uniform(&program, "Transform");
+        let tile_size_uniform = device.get_uniform(&program, "TileSize");
+        let stencil_texture_uniform = device.get_uniform(&program, "StencilTexture");
+        let stencil_texture_size_uniform = device.get_uniform(&program, "StencilTextureSize");
+        ClipTileProgram {
+            program,
+            transform_uniform,
+            tile_size_uniform,
+            stencil_texture_uniform,
+            stencil_texture_size_uniform,
         }
     }
 }
@@ -1243,6 +2459,67 @@ where
     }
 }
 
+struct ResolveProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    source_uniform: D::Uniform,
+    encode_srgb_uniform: D::Uniform,
+}
+
+impl<D> ResolveProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> ResolveProgram<D> {
+        let program = device.create_program_from_shader_names(resources,
+                                                              "resolve",
+                                                              "post",
+                                                              "resolve");
+        let source_uniform = device.get_uniform(&program, "Source");
+        let encode_srgb_uniform = device.get_uniform(&program, "EncodeSRGB");
+        ResolveProgram { program, source_uniform, encode_srgb_uniform }
+    }
+}
+
+struct BackdropFilterProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    source_uniform: D::Uniform,
+    source_size_uniform: D::Uniform,
+    direction_uniform: D::Uniform,
+    blur_std_dev_uniform: D::Uniform,
+    saturation_uniform: D::Uniform,
+}
+
+impl<D> BackdropFilterProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> BackdropFilterProgram<D> {
+        let program = device.create_program_from_shader_names(resources,
+                                                              "backdrop_filter",
+                                                              "post",
+                                                              "backdrop_filter");
+        let source_uniform = device.get_uniform(&program, "Source");
+        let source_size_uniform = device.get_uniform(&program, "SourceSize");
+        let direction_uniform = device.get_uniform(&program, "Direction");
+        let blur_std_dev_uniform = device.get_uniform(&program, "BlurStdDev");
+        let saturation_uniform = device.get_uniform(&program, "Saturation");
+        BackdropFilterProgram {
+            program,
+            source_uniform,
+            source_size_uniform,
+            direction_uniform,
+            blur_std_dev_uniform,
+            saturation_uniform,
+        }
+    }
+}
+
 struct PostprocessVertexArray<D>
 where
     D: Device,
@@ -1256,13 +2533,12 @@ where
 {
     fn new(
         device: &D,
-        postprocess_program: &PostprocessProgram<D>,
+        program: &D::Program,
         quad_vertex_positions_buffer: &D::Buffer,
         quad_vertex_indices_buffer: &D::Buffer,
     ) -> PostprocessVertexArray<D> {
         let vertex_array = device.create_vertex_array();
-        let position_attr = device.get_vertex_attr(&postprocess_program.program, "Position")
-                                  .unwrap();
+        let position_attr = device.get_vertex_attr(program, "Position").unwrap();
 
         device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
         device.configure_vertex_attr(&vertex_array, &position_attr, &VertexAttrDescriptor {
@@ -1398,6 +2674,167 @@ where
     }
 }
 
+// The compute programs that tile segments for `BuildOptions::gpu_tiling`.
+struct TilingPrograms<D>
+where
+    D: Device,
+{
+    bin_program: BinProgram<D>,
+    propagate_program: PropagateProgram<D>,
+    patch_fills_program: PatchFillsProgram<D>,
+}
+
+impl<D> TilingPrograms<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> TilingPrograms<D> {
+        TilingPrograms {
+            bin_program: BinProgram::new(device, resources),
+            propagate_program: PropagateProgram::new(device, resources),
+            patch_fills_program: PatchFillsProgram::new(device, resources),
+        }
+    }
+}
+
+struct BinProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    segment_count_uniform: D::Uniform,
+    fill_capacity_uniform: D::Uniform,
+}
+
+impl<D> BinProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> BinProgram<D> {
+        let program = device.create_compute_program(resources, "bin");
+        let segment_count_uniform = device.get_uniform(&program, "SegmentCount");
+        let fill_capacity_uniform = device.get_uniform(&program, "FillCapacity");
+        BinProgram { program, segment_count_uniform, fill_capacity_uniform }
+    }
+}
+
+struct PropagateProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    row_count_uniform: D::Uniform,
+    path_count_uniform: D::Uniform,
+    alpha_tile_capacity_uniform: D::Uniform,
+}
+
+impl<D> PropagateProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> PropagateProgram<D> {
+        let program = device.create_compute_program(resources, "propagate");
+        let row_count_uniform = device.get_uniform(&program, "RowCount");
+        let path_count_uniform = device.get_uniform(&program, "PathCount");
+        let alpha_tile_capacity_uniform = device.get_uniform(&program, "AlphaTileCapacity");
+        PropagateProgram {
+            program,
+            row_count_uniform,
+            path_count_uniform,
+            alpha_tile_capacity_uniform,
+        }
+    }
+}
+
+struct PatchFillsProgram<D>
+where
+    D: Device,
+{
+    program: D::Program,
+    fill_capacity_uniform: D::Uniform,
+}
+
+impl<D> PatchFillsProgram<D>
+where
+    D: Device,
+{
+    fn new(device: &D, resources: &dyn ResourceLoader) -> PatchFillsProgram<D> {
+        let program = device.create_compute_program(resources, "patch_fills");
+        let fill_capacity_uniform = device.get_uniform(&program, "FillCapacity");
+        PatchFillsProgram { program, fill_capacity_uniform }
+    }
+}
+
+// The storage buffers that the tiling compute programs read and write. The fills go to the fill
+// vertex buffer, so that they can be drawn where they are.
+struct SegmentBuffers<D>
+where
+    D: Device,
+{
+    segments: D::Buffer,
+    // The index of the path of each segment, in `paths`.
+    segment_paths: D::Buffer,
+    paths: D::Buffer,
+    tiles: D::Buffer,
+    // The tile of each fill, in `tiles`.
+    fill_tiles: D::Buffer,
+    counters: D::Buffer,
+    alpha_tiles: D::Buffer,
+    // In bytes.
+    size: usize,
+}
+
+impl<D> SegmentBuffers<D>
+where
+    D: Device,
+{
+    fn new(device: &D) -> SegmentBuffers<D> {
+        SegmentBuffers {
+            segments: device.create_buffer(),
+            segment_paths: device.create_buffer(),
+            paths: device.create_buffer(),
+            tiles: device.create_buffer(),
+            fill_tiles: device.create_buffer(),
+            counters: device.create_buffer(),
+            alpha_tiles: device.create_buffer(),
+            size: 0,
+        }
+    }
+}
+
+// A `SegmentPath` as the tiling compute programs read it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+struct SegmentPathPrimitive {
+    tile_rect: [i32; 4],
+    // The first tile and the first row of the path, among those of all paths, followed by the
+    // first alpha tile index and the alpha tile count.
+    tiles: [u32; 4],
+    // The object index and fill rule, then padding.
+    info: [u32; 4],
+    tex_matrix: [f32; 4],
+    tex_vector: [f32; 4],
+}
+
+// Allocates `buffer` for a compute program with `data`, returning its size in bytes.
+fn upload_storage_buffer<D, T>(device: &D, buffer: &D::Buffer, data: BufferData<T>) -> usize
+                               where D: Device {
+    let size = match data {
+        BufferData::Uninitialized(length) => length * mem::size_of::<T>(),
+        BufferData::Memory(values) => mem::size_of_val(values),
+    };
+    device.allocate_buffer(buffer, data, BufferTarget::Vertex, BufferUploadMode::Dynamic);
+    size
+}
+
+// The work groups for `invocation_count` invocations of a tiling compute program. Each invocation
+// loops if there are too many for one dispatch.
+fn tiling_dimensions(invocation_count: u32) -> ComputeDimensions {
+    let work_group_count = invocation_count.div_ceil(TILING_WORK_GROUP_SIZE);
+    let x = work_group_count.clamp(1, MAX_TILING_WORK_GROUPS);
+    ComputeDimensions { x, y: 1, z: 1 }
+}
+
 #[derive(Clone, Copy, Default)]
 pub struct PostprocessOptions {
     pub fg_color: ColorF,
@@ -1406,6 +2843,24 @@ pub struct PostprocessOptions {
     pub gamma_correction: bool,
 }
 
+/// GPU memory in use by a renderer, in bytes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GPUMemoryUsage {
+    /// The paint texture and lookup tables.
+    pub textures: u64,
+    /// The mask framebuffer and any intermediate framebuffers, not including the destination.
+    pub framebuffers: u64,
+    /// Vertex, index, and storage buffers.
+    pub buffers: u64,
+}
+
+impl GPUMemoryUsage {
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.textures + self.framebuffers + self.buffers
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct RenderStats {
     pub path_count: usize,
@@ -1440,12 +2895,13 @@ impl Div<usize> for RenderStats {
 
 struct RenderTimers<D> where D: Device {
     stage_0: Vec<D::TimerQuery>,
-    stage_1: Option<D::TimerQuery>,
+    stage_1: Vec<D::TimerQuery>,
+    stage_1_active: bool,
 }
 
 impl<D> RenderTimers<D> where D: Device {
     fn new() -> RenderTimers<D> {
-        RenderTimers { stage_0: vec![], stage_1: None }
+        RenderTimers { stage_0: vec![], stage_1: vec![], stage_1_active: false }
     }
 }
 
@@ -1479,5 +2935,93 @@ bitflags! {
         const MUST_PRESERVE_MASK_FRAMEBUFFER_CONTENTS = 0x01;
         const MUST_PRESERVE_POSTPROCESS_FRAMEBUFFER_CONTENTS = 0x02;
         const MUST_PRESERVE_DEST_FRAMEBUFFER_CONTENTS = 0x04;
+        const MUST_PRESERVE_LINEAR_FRAMEBUFFER_CONTENTS = 0x08;
+    }
+}
+
+trait BlendModeExt {
+    fn to_blend_state(self) -> BlendState;
+    fn needs_dest_copy(self) -> bool;
+}
+
+impl BlendModeExt for BlendMode {
+    // Alpha tiles output premultiplied color, so these are the factors from the Porter-Duff
+    // equations as given.
+    fn to_blend_state(self) -> BlendState {
+        let (src_factor, dest_factor) = match self {
+            BlendMode::Clear => (BlendFactor::Zero, BlendFactor::Zero),
+            BlendMode::Copy => (BlendFactor::One, BlendFactor::Zero),
+            BlendMode::SrcIn => (BlendFactor::DestAlpha, BlendFactor::Zero),
+            BlendMode::SrcOut => (BlendFactor::OneMinusDestAlpha, BlendFactor::Zero),
+            BlendMode::SrcAtop => (BlendFactor::DestAlpha, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::DestIn => (BlendFactor::Zero, BlendFactor::SrcAlpha),
+            BlendMode::DestOut => (BlendFactor::Zero, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::DestOver => (BlendFactor::OneMinusDestAlpha, BlendFactor::One),
+            BlendMode::DestAtop => (BlendFactor::OneMinusDestAlpha, BlendFactor::SrcAlpha),
+            BlendMode::Xor => (BlendFactor::OneMinusDestAlpha, BlendFactor::OneMinusSrcAlpha),
+            BlendMode::Lighter => (BlendFactor::One, BlendFactor::One),
+            BlendMode::Screen => {
+                return BlendState {
+                    src_rgb_factor: BlendFactor::One,
+                    dest_rgb_factor: BlendFactor::OneMinusSrcColor,
+                    src_alpha_factor: BlendFactor::One,
+                    dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+                    ..BlendState::default()
+                };
+            }
+            // The shader blends these with a copy of the destination, which leaves source-over to
+            // the blender. See `needs_dest_copy()`.
+            BlendMode::SrcOver |
+            BlendMode::Multiply |
+            BlendMode::Overlay |
+            BlendMode::Darken |
+            BlendMode::Lighten |
+            BlendMode::ColorDodge |
+            BlendMode::ColorBurn |
+            BlendMode::HardLight |
+            BlendMode::SoftLight |
+            BlendMode::Difference |
+            BlendMode::Exclusion |
+            BlendMode::Hue |
+            BlendMode::Saturation |
+            BlendMode::Color |
+            BlendMode::Luminosity => (BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+        };
+
+        BlendState {
+            src_rgb_factor: src_factor,
+            dest_rgb_factor: dest_factor,
+            src_alpha_factor: src_factor,
+            dest_alpha_factor: dest_factor,
+            ..BlendState::default()
+        }
     }
+
+    // Whether tiles with this blend mode must be drawn with `AlphaTileBlendProgram`, because the
+    // blend function can't be expressed with blend factors.
+    fn needs_dest_copy(self) -> bool {
+        blend_mode_uniform_data(self).is_some()
+    }
+}
+
+// The values of `uBlendMode` in the blend tile shader, for the blend modes that it handles.
+fn blend_mode_uniform_data(blend_mode: BlendMode) -> Option<UniformData> {
+    let value = match blend_mode {
+        BlendMode::Multiply => 0,
+        BlendMode::Overlay => 1,
+        BlendMode::Darken => 2,
+        BlendMode::Lighten => 3,
+        BlendMode::ColorDodge => 4,
+        BlendMode::ColorBurn => 5,
+        BlendMode::HardLight => 6,
+        BlendMode::SoftLight => 7,
+        BlendMode::Difference => 8,
+        BlendMode::Exclusion => 9,
+        BlendMode::Hue => 10,
+        BlendMode::Saturation => 11,
+        BlendMode::Color => 12,
+        BlendMode::Luminosity => 13,
+        _ => return None,
+    };
+    Some(UniformData::Int(value))
 }
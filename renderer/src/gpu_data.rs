@@ -10,12 +10,18 @@
 
 //! Packed data ready to be sent to the GPU.
 
+use crate::coverage::TileCoverage;
 use crate::options::BoundingQuad;
 use crate::tile_map::DenseTileMap;
-use pathfinder_geometry::line_segment::{LineSegmentU4, LineSegmentU8};
-use pathfinder_geometry::rect::RectF;
+use pathfinder_content::effects::{BackdropFilter, BlendMode};
+use pathfinder_content::fill::FillRule;
+use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2I;
 use std::fmt::{Debug, Formatter, Result as DebugResult};
+use std::ops::Range;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug)]
@@ -23,6 +29,8 @@ pub(crate) struct BuiltObject {
     pub bounds: RectF,
     pub fills: Vec<FillBatchPrimitive>,
     pub alpha_tiles: Vec<AlphaTileBatchPrimitive>,
+    /// The number of alpha tile indices this object has allocated, counting from zero.
+    pub alpha_tile_count: u32,
     pub tiles: DenseTileMap<TileObjectPrimitive>,
 }
 
@@ -31,17 +39,94 @@ pub enum RenderCommand {
     AddPaintData(PaintData),
     AddFills(Vec<FillBatchPrimitive>),
     FlushFills,
-    AlphaTile(Vec<AlphaTileBatchPrimitive>),
-    SolidTile(Vec<SolidTileBatchPrimitive>),
+    /// Starts a new page of alpha tiles, whose indices reuse the mask framebuffer from the start.
+    /// The tiles of the previous page have all been drawn, so the framebuffer is cleared before
+    /// the next fills are.
+    ClearMaskFramebuffer,
+    /// Adds paths for the renderer to bin into tiles, for `BuildOptions::gpu_tiling`. Their fills
+    /// are rasterized along with the others at the next `FlushFills`.
+    AddSegments(SegmentBatch),
+    /// Draws the alpha tiles of the paths in this range of those added with `AddSegments` since
+    /// the last `FlushFills`. Their paints are all on one page of the paint texture.
+    SegmentAlphaTile(Range<u32>, BlendMode, FillRule, PaintPageId),
+    /// Replaces the clip path in the stencil buffer with the pixels that these tiles cover more
+    /// than halfway, for `BuildOptions::stencil_clip_paths`. Their fills have been flushed.
+    ClipStencil(Vec<AlphaTileBatchPrimitive>),
+    /// Replaces what's been drawn inside the tiles of one path with a filtered copy of it. The
+    /// path's own tiles follow.
+    BackdropFilter(Vec<AlphaTileBatchPrimitive>, BackdropFilter, FillRule),
+    /// Like `BackdropFilter`, but only inside the clip path last drawn with `ClipStencil`.
+    ClippedBackdropFilter(Vec<AlphaTileBatchPrimitive>, BackdropFilter, FillRule),
+    /// Draws alpha tiles whose paints are all on one page of the paint texture.
+    AlphaTile(Vec<AlphaTileBatchPrimitive>, BlendMode, FillRule, PaintPageId),
+    /// Like `AlphaTile`, but only inside the clip path last drawn with `ClipStencil`.
+    ClippedAlphaTile(Vec<AlphaTileBatchPrimitive>, BlendMode, FillRule, PaintPageId),
+    /// Draws solid tiles whose paints are all on one page of the paint texture.
+    SolidTile(Vec<SolidTileBatchPrimitive>, PaintPageId),
+    /// The tiles that each path touched, for `BuildOptions::record_tile_coverage`. Draws nothing.
+    TileCoverage(Arc<TileCoverage>),
     Finish { build_time: Duration },
 }
 
+/// Paths whose segments the renderer bins into tiles itself.
+#[derive(Clone, Debug, Default)]
+pub struct SegmentBatch {
+    pub paths: Vec<SegmentPath>,
+    /// The line segments of all the paths, in device pixels. Each contour is closed.
+    pub segments: Vec<LineSegment2F>,
+}
+
+/// One path of a `SegmentBatch`.
+#[derive(Clone, Debug)]
+pub struct SegmentPath {
+    /// The path's segments in `SegmentBatch::segments`.
+    pub segments: Range<u32>,
+    /// The tiles that the path may draw to. Fills outside them are dropped.
+    pub tile_rect: RectI,
+    /// The path's alpha tiles get places in the mask framebuffer from this one on, up to
+    /// `alpha_tile_count` of them. Tiles past those keep only their backdrops.
+    pub first_alpha_tile_index: u16,
+    pub alpha_tile_count: u16,
+    /// The most fills that the segments can make, which bounds the alpha tiles too.
+    pub max_fill_count: u32,
+    pub object_index: u16,
+    pub fill_rule: FillRule,
+    /// The transform from device pixels to the paint texture.
+    pub tex_transform: Transform2F,
+}
+
 #[derive(Clone, Debug)]
 pub struct PaintData {
+    /// The pages of the paint texture, indexed by `PaintPageId`.
+    pub pages: Vec<PaintPageData>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PaintPageData {
+    pub format: PaintPageFormat,
     pub size: Vector2I,
+    /// Four bytes per texel for `RGBA8` pages, and one for the others.
     pub texels: Vec<u8>,
 }
 
+/// The index of a page of the paint texture.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PaintPageId(pub u32);
+
+/// How the texels of a page of the paint texture are stored.
+///
+/// Paints that need fewer than four channels go on narrower pages, which take a quarter of the
+/// memory and bandwidth.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PaintPageFormat {
+    /// Straight-alpha RGBA colors. Any paint can go on these pages.
+    RGBA8,
+    /// The gray levels of opaque grayscale paints.
+    Gray8,
+    /// The opacity of black paints, like shadows and masks.
+    Alpha8,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct FillObjectPrimitive {
     pub px: LineSegmentU4,
@@ -72,12 +157,12 @@ pub struct FillBatchPrimitive {
 pub struct SolidTileBatchPrimitive {
     pub tile_x: i16,
     pub tile_y: i16,
-    pub texture_m00: u16,
-    pub texture_m10: u16,
-    pub texture_m01: u16,
-    pub texture_m11: u16,
-    pub texture_m02: u16,
-    pub texture_m12: u16,
+    pub texture_m00: f32,
+    pub texture_m10: f32,
+    pub texture_m01: f32,
+    pub texture_m11: f32,
+    pub texture_m02: f32,
+    pub texture_m12: f32,
     pub object_index: u16,
     pub pad: u16,
 }
@@ -91,12 +176,12 @@ pub struct AlphaTileBatchPrimitive {
     pub backdrop: i8,
     pub object_index: u16,
     pub tile_index: u16,
-    pub texture_m00: u16,
-    pub texture_m10: u16,
-    pub texture_m01: u16,
-    pub texture_m11: u16,
-    pub texture_m02: u16,
-    pub texture_m12: u16,
+    pub texture_m00: f32,
+    pub texture_m10: f32,
+    pub texture_m01: f32,
+    pub texture_m11: f32,
+    pub texture_m02: f32,
+    pub texture_m12: f32,
 }
 
 impl Debug for RenderCommand {
@@ -104,15 +189,66 @@ impl Debug for RenderCommand {
         match *self {
             RenderCommand::Start { .. } => write!(formatter, "Start"),
             RenderCommand::AddPaintData(ref paint_data) => {
-                write!(formatter, "AddPaintData({}x{})", paint_data.size.x(), paint_data.size.y())
+                write!(formatter, "AddPaintData(")?;
+                for (page_index, page) in paint_data.pages.iter().enumerate() {
+                    if page_index > 0 {
+                        write!(formatter, ", ")?;
+                    }
+                    write!(formatter, "{}x{} {:?}", page.size.x(), page.size.y(), page.format)?;
+                }
+                write!(formatter, ")")
             }
             RenderCommand::AddFills(ref fills) => write!(formatter, "AddFills(x{})", fills.len()),
             RenderCommand::FlushFills => write!(formatter, "FlushFills"),
-            RenderCommand::AlphaTile(ref tiles) => {
-                write!(formatter, "AlphaTile(x{})", tiles.len())
+            RenderCommand::ClearMaskFramebuffer => write!(formatter, "ClearMaskFramebuffer"),
+            RenderCommand::AddSegments(ref batch) => {
+                write!(formatter,
+                       "AddSegments(x{}, x{})",
+                       batch.paths.len(),
+                       batch.segments.len())
+            }
+            RenderCommand::SegmentAlphaTile(ref paths, blend_mode, fill_rule, page) => {
+                write!(formatter,
+                       "SegmentAlphaTile({:?}, {:?}, {:?}, page {})",
+                       paths,
+                       blend_mode,
+                       fill_rule,
+                       page.0)
+            }
+            RenderCommand::ClipStencil(ref tiles) => {
+                write!(formatter, "ClipStencil(x{})", tiles.len())
+            }
+            RenderCommand::BackdropFilter(ref tiles, ref filter, fill_rule) => {
+                write!(formatter, "BackdropFilter(x{}, {:?}, {:?})", tiles.len(), filter, fill_rule)
+            }
+            RenderCommand::ClippedBackdropFilter(ref tiles, ref filter, fill_rule) => {
+                write!(formatter,
+                       "ClippedBackdropFilter(x{}, {:?}, {:?})",
+                       tiles.len(),
+                       filter,
+                       fill_rule)
+            }
+            RenderCommand::AlphaTile(ref tiles, blend_mode, fill_rule, page) => {
+                write!(formatter,
+                       "AlphaTile(x{}, {:?}, {:?}, page {})",
+                       tiles.len(),
+                       blend_mode,
+                       fill_rule,
+                       page.0)
+            }
+            RenderCommand::ClippedAlphaTile(ref tiles, blend_mode, fill_rule, page) => {
+                write!(formatter,
+                       "ClippedAlphaTile(x{}, {:?}, {:?}, page {})",
+                       tiles.len(),
+                       blend_mode,
+                       fill_rule,
+                       page.0)
+            }
+            RenderCommand::SolidTile(ref tiles, page) => {
+                write!(formatter, "SolidTile(x{}, page {})", tiles.len(), page.0)
             }
-            RenderCommand::SolidTile(ref tiles) => {
-                write!(formatter, "SolidTile(x{})", tiles.len())
+            RenderCommand::TileCoverage(ref coverage) => {
+                write!(formatter, "TileCoverage(x{})", coverage.path_count())
             }
             RenderCommand::Finish { .. } => write!(formatter, "Finish"),
         }
@@ -16,15 +16,19 @@ extern crate bitflags;
 extern crate log;
 
 pub mod concurrent;
+pub mod coverage;
 pub mod gpu;
 pub mod gpu_data;
 pub mod options;
 pub mod paint;
+pub mod picture;
 pub mod post;
 pub mod scene;
 
 mod allocator;
+mod binning;
 mod builder;
+mod clip;
 mod tile_map;
 mod tiles;
 mod z_buffer;
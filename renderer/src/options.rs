@@ -11,9 +11,10 @@
 //! Options that control how rendering is to be performed.
 
 use crate::gpu_data::RenderCommand;
+use pathfinder_color::ColorSpace;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::transform3d::Perspective;
+use pathfinder_geometry::transform3d::{Perspective, Transform4F};
 use pathfinder_geometry::vector::{Vector2F, Vector4F};
 use pathfinder_content::clip::PolygonClipper3D;
 
@@ -44,6 +45,45 @@ pub struct BuildOptions {
     pub transform: RenderTransform,
     pub dilation: Vector2F,
     pub subpixel_aa_enabled: bool,
+    /// The color space of the destination. Paints in other color spaces are converted to it.
+    pub color_space: ColorSpace,
+    /// Treats the colors of patterns and gradients as premultiplied by alpha, as they are in
+    /// images decoded for premultiplied pipelines. Solid colors are always unpremultiplied.
+    pub premultiplied_paints: bool,
+    /// If present, paths are tiled this many at a time, and the tiles for each chunk are sent to
+    /// the listener as soon as they're ready instead of once the whole scene has been tiled.
+    ///
+    /// This bounds the memory used to build huge scenes. Paths can't occlude paths in earlier
+    /// chunks, so more tiles may be drawn than otherwise.
+    pub streaming_chunk_size: Option<usize>,
+    /// Skips paths that would be less than this many pixels across, for level of detail.
+    ///
+    /// The default of zero draws every path. Like `PathObject::set_zoom_range()`, this only
+    /// applies to paths drawn with 2D transforms.
+    pub lod_tolerance: f32,
+    /// Clips paths that cross the edges of their clip paths with the stencil buffer of the
+    /// destination, instead of intersecting their outlines with the clip paths.
+    ///
+    /// Each clip path is tiled once and drawn into the stencil buffer before the tiles it clips,
+    /// which is much faster for paths clipped by large, complex clip paths, such as map data
+    /// clipped to a viewport. The stencil buffer holds one bit per pixel, so the edges of clip
+    /// paths aren't antialiased. The renderer only has a stencil buffer when drawing straight
+    /// into the default framebuffer, without postprocessing or linear compositing. Paths drawn
+    /// with 3D transforms are still clipped on the CPU.
+    pub stencil_clip_paths: bool,
+    /// Sends a `RenderCommand::TileCoverage` with the tiles that each path touched before the
+    /// scene is finished. The renderer keeps the last one it saw, for
+    /// `Renderer::tile_coverage()`.
+    pub record_tile_coverage: bool,
+    /// Experimental: leaves binning the segments of most paths into tiles and generating their
+    /// fills to the renderer, which does it with compute shaders on devices that support them and
+    /// on the CPU otherwise.
+    ///
+    /// This frees up the thread that builds the scene, which helps animated scenes whose paths
+    /// change every frame. Paths that are clipped, that have backdrop filters, or that use blend
+    /// modes that affect the whole destination are still tiled while building. Paths tiled by the
+    /// renderer can't occlude anything, and this has no effect when `record_tile_coverage` is set.
+    pub gpu_tiling: bool,
 }
 
 impl BuildOptions {
@@ -52,6 +92,13 @@ impl BuildOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            color_space: self.color_space,
+            premultiplied_paints: self.premultiplied_paints,
+            streaming_chunk_size: self.streaming_chunk_size,
+            lod_tolerance: self.lod_tolerance,
+            stencil_clip_paths: self.stencil_clip_paths,
+            record_tile_coverage: self.record_tile_coverage,
+            gpu_tiling: self.gpu_tiling,
         }
     }
 }
@@ -71,16 +118,123 @@ impl Default for RenderTransform {
 
 impl RenderTransform {
     fn prepare(&self, bounds: RectF) -> PreparedRenderTransform {
-        let perspective = match self {
+        match self {
             RenderTransform::Transform2D(ref transform) => {
                 if transform.is_identity() {
                     return PreparedRenderTransform::None;
                 }
-                return PreparedRenderTransform::Transform2D(*transform);
+                PreparedRenderTransform::Transform2D(*transform)
+            }
+            RenderTransform::Perspective(ref perspective) => {
+                PreparedRenderTransform::from_perspective(*perspective, bounds)
+            }
+        }
+    }
+}
+
+pub(crate) struct PreparedBuildOptions {
+    pub(crate) transform: PreparedRenderTransform,
+    pub(crate) dilation: Vector2F,
+    pub(crate) subpixel_aa_enabled: bool,
+    pub(crate) color_space: ColorSpace,
+    pub(crate) premultiplied_paints: bool,
+    pub(crate) streaming_chunk_size: Option<usize>,
+    pub(crate) lod_tolerance: f32,
+    pub(crate) stencil_clip_paths: bool,
+    pub(crate) record_tile_coverage: bool,
+    pub(crate) gpu_tiling: bool,
+}
+
+impl PreparedBuildOptions {
+    #[inline]
+    pub(crate) fn bounding_quad(&self) -> BoundingQuad {
+        match self.transform {
+            PreparedRenderTransform::Perspective { quad, .. } => quad,
+            _ => [Vector4F::default(); 4],
+        }
+    }
+
+    /// The affine transform from scene coordinates to device pixels, including the horizontal
+    /// scale for subpixel antialiasing.
+    ///
+    /// FIXME: This is the identity for perspective transforms, so paints that depend on
+    /// position are drawn incorrectly in 3D.
+    pub(crate) fn transform_2d(&self) -> Transform2F {
+        let mut transform = match self.transform {
+            PreparedRenderTransform::Transform2D(transform) => transform,
+            PreparedRenderTransform::None | PreparedRenderTransform::Perspective { .. } => {
+                Transform2F::default()
             }
-            RenderTransform::Perspective(ref perspective) => *perspective,
         };
+        if self.subpixel_aa_enabled {
+            transform *= Transform2F::from_scale(Vector2F::new(3.0, 1.0))
+        }
+        transform
+    }
+
+    /// The factor by which the 2D transform scales areas, square rooted, or `None` for
+    /// perspective transforms, which scale different parts of the scene differently.
+    pub(crate) fn zoom(&self) -> Option<f32> {
+        match self.transform {
+            PreparedRenderTransform::None => Some(1.0),
+            PreparedRenderTransform::Transform2D(transform) => {
+                Some(f32::sqrt(f32::abs(transform.matrix.det())))
+            }
+            PreparedRenderTransform::Perspective { .. } => None,
+        }
+    }
+
+    /// The perspective transform for a path that `path_transform` places in the scene.
+    ///
+    /// In 3D scenes, the path transform maps to the space that the scene's perspective transform
+    /// applies to. In 2D scenes, it maps to scene coordinates, and the result is projected onto
+    /// `view_box` after the 2D transform (which should be the effective view box).
+    pub(crate) fn path_perspective(&self, path_transform: &Transform4F, view_box: RectF)
+                                   -> Perspective {
+        if let PreparedRenderTransform::Perspective { ref perspective, .. } = self.transform {
+            return *perspective * *path_transform;
+        }
 
+        let window_size = view_box.lower_right().ceil().to_i32();
+        let (width, height) = (window_size.x() as f32, window_size.y() as f32);
+        let to_ndc = Transform4F::row_major(2.0 / width, 0.0,           0.0, -1.0,
+                                            0.0,         -2.0 / height, 0.0, 1.0,
+                                            0.0,         0.0,           1.0, 0.0,
+                                            0.0,         0.0,           0.0, 1.0);
+
+        let transform_2d = self.transform_2d();
+        let (m11, m12) = (transform_2d.m11(), transform_2d.m12());
+        let (m21, m22) = (transform_2d.m21(), transform_2d.m22());
+        let (m14, m24) = (transform_2d.vector.x(), transform_2d.vector.y());
+        let transform = Transform4F::row_major(m11, m12, 0.0,              m14,
+                                               m21, m22, 0.0,              m24,
+                                               0.0, 0.0, PATH_DEPTH_SCALE, 0.0,
+                                               0.0, 0.0, 0.0,              1.0);
+        Perspective::new(&(to_ndc * transform * *path_transform), window_size)
+    }
+}
+
+// Paths with their own 3D transforms in 2D scenes have their depth scaled down by this much, so
+// that only the parts behind the eye are clipped, and not those in front of the near plane or
+// beyond the far one.
+const PATH_DEPTH_SCALE: f32 = 1.0 / 65536.0;
+
+pub(crate) type BoundingQuad = [Vector4F; 4];
+
+pub(crate) enum PreparedRenderTransform {
+    None,
+    Transform2D(Transform2F),
+    Perspective {
+        perspective: Perspective,
+        clip_polygon: Vec<Vector2F>,
+        quad: [Vector4F; 4],
+    },
+}
+
+impl PreparedRenderTransform {
+    /// Clips `bounds`, in scene coordinates, to the view frustum of `perspective`.
+    pub(crate) fn from_perspective(perspective: Perspective, bounds: RectF)
+                                   -> PreparedRenderTransform {
         let mut points = vec![
             bounds.origin().to_4d(),
             bounds.upper_right().to_4d(),
@@ -113,43 +267,9 @@ impl RenderTransform {
         let clip_polygon = points.into_iter()
                                  .map(|point| (inverse_transform * point).to_2d())
                                  .collect();
-        return PreparedRenderTransform::Perspective {
-            perspective,
-            clip_polygon,
-            quad,
-        };
+        PreparedRenderTransform::Perspective { perspective, clip_polygon, quad }
     }
-}
 
-pub(crate) struct PreparedBuildOptions {
-    pub(crate) transform: PreparedRenderTransform,
-    pub(crate) dilation: Vector2F,
-    pub(crate) subpixel_aa_enabled: bool,
-}
-
-impl PreparedBuildOptions {
-    #[inline]
-    pub(crate) fn bounding_quad(&self) -> BoundingQuad {
-        match self.transform {
-            PreparedRenderTransform::Perspective { quad, .. } => quad,
-            _ => [Vector4F::default(); 4],
-        }
-    }
-}
-
-pub(crate) type BoundingQuad = [Vector4F; 4];
-
-pub(crate) enum PreparedRenderTransform {
-    None,
-    Transform2D(Transform2F),
-    Perspective {
-        perspective: Perspective,
-        clip_polygon: Vec<Vector2F>,
-        quad: [Vector4F; 4],
-    },
-}
-
-impl PreparedRenderTransform {
     #[inline]
     pub(crate) fn is_2d(&self) -> bool {
         match *self {
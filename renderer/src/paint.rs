@@ -9,32 +9,51 @@
 // except according to those terms.
 
 use crate::allocator::{TextureAllocator, TextureLocation};
-use crate::gpu_data::PaintData;
+use crate::gpu_data::{PaintData, PaintPageData, PaintPageFormat, PaintPageId};
+use crate::scene::SceneBuildError;
 use hashbrown::HashMap;
-use pathfinder_color::ColorU;
-use pathfinder_content::gradient::Gradient;
-use pathfinder_geometry::rect::RectI;
-use pathfinder_geometry::transform2d::{Matrix2x2I, Transform2I};
-use pathfinder_geometry::vector::Vector2I;
-use pathfinder_simd::default::I32x4;
+use pathfinder_color::{ColorF, ColorSpace, ColorU};
+use pathfinder_content::gradient::{Gradient, GradientGeometry, GradientSampler, GradientWrap};
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_simd::default::F32x4;
 use std::fmt::{self, Debug, Formatter};
 
+#[cfg(feature = "pf-serde")]
+use serde::{Deserialize, Serialize};
+
 const PAINT_TEXTURE_LENGTH: u32 = 1024;
-const PAINT_TEXTURE_SCALE: u32 = 65536 / PAINT_TEXTURE_LENGTH;
 
 const SOLID_COLOR_TILE_LENGTH: u32 = 16;
 const MAX_SOLID_COLORS_PER_TILE: u32 = SOLID_COLOR_TILE_LENGTH * SOLID_COLOR_TILE_LENGTH;
 
+// Linear gradients are baked into rows of at least this many texels, packed into square tiles.
+const MIN_GRADIENT_ROW_LENGTH: u32 = 256;
+// Gradients with so many visible stops that these rows would be crowded get rows of up to this
+// many texels. A tile of these takes up a quarter of the paint texture.
+const MAX_GRADIENT_ROW_LENGTH: u32 = 512;
+// Each texel of a row averages up to this many samples of the gradient, so that stops closer
+// together than a texel are blended rather than skipped.
+const MAX_GRADIENT_SAMPLES_PER_TEXEL: u32 = 16;
+// Radial gradients are rasterized at no more than this many texels on a side.
+const MAX_GRADIENT_RASTER_LENGTH: u32 = 256;
+
 #[derive(Clone)]
 pub struct Palette {
     pub(crate) paints: Vec<Paint>,
-    cache: HashMap<Paint, PaintId>,
+    /// The color space that each paint's colors are given in.
+    pub(crate) color_spaces: Vec<ColorSpace>,
+    cache: HashMap<(Paint, ColorSpace), PaintId>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "pf-serde", derive(Serialize, Deserialize))]
 pub enum Paint {
     Color(ColorU),
     Gradient(Gradient),
+    Pattern(Pattern),
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -51,6 +70,12 @@ impl Debug for Paint {
                 // TODO(pcwalton)
                 write!(formatter, "(gradient)")
             }
+            Paint::Pattern(ref pattern) => {
+                write!(formatter,
+                       "(pattern {}x{})",
+                       pattern.image.size().x(),
+                       pattern.image.size().y())
+            }
         }
     }
 }
@@ -58,7 +83,7 @@ impl Debug for Paint {
 impl Palette {
     #[inline]
     pub fn new() -> Palette {
-        Palette { paints: vec![], cache: HashMap::new() }
+        Palette { paints: vec![], color_spaces: vec![], cache: HashMap::new() }
     }
 }
 
@@ -77,17 +102,43 @@ impl Paint {
         match *self {
             Paint::Color(color) => color.is_opaque(),
             Paint::Gradient(ref gradient) => {
-                gradient.stops().iter().all(|stop| stop.color.is_opaque())
+                !gradient.stops().is_empty() && gradient.covers_plane() &&
+                    gradient.stops().iter().all(|stop| stop.color.is_opaque())
             }
+            Paint::Pattern(ref pattern) => pattern.image.is_opaque(),
         }
     }
 
     pub fn is_fully_transparent(&self) -> bool {
         match *self {
-            Paint::Color(color) => color.is_opaque(),
+            Paint::Color(color) => color.is_fully_transparent(),
             Paint::Gradient(ref gradient) => {
                 gradient.stops().iter().all(|stop| stop.color.is_fully_transparent())
             }
+            Paint::Pattern(ref pattern) => pattern.image.is_fully_transparent(),
+        }
+    }
+
+    /// Returns the format of the narrowest page of the paint texture that this paint fits on
+    /// without losing information.
+    ///
+    /// Solid colors always go on `RGBA8` pages, where they share tiles with each other.
+    pub fn narrowest_page_format(&self) -> PaintPageFormat {
+        match *self {
+            Paint::Color(_) => PaintPageFormat::RGBA8,
+            Paint::Gradient(ref gradient) => {
+                let colors = gradient.stops().iter().map(|stop| stop.color);
+                narrowest_page_format_for_colors(colors, self.is_opaque())
+            }
+            Paint::Pattern(ref pattern) => {
+                // Images are packed into power-of-two squares, and the transparent texels around
+                // an image that doesn't fill its square would turn black on a grayscale page.
+                let image = &pattern.image;
+                let fills_square = image.size().x() == image.size().y() &&
+                    (image.size().x() as u32).is_power_of_two();
+                let colors = image.pixels().iter().cloned();
+                narrowest_page_format_for_colors(colors, image.is_opaque() && fills_square)
+            }
         }
     }
 
@@ -99,6 +150,17 @@ impl Paint {
         match *self {
             Paint::Color(ref mut color) => color.a = (color.a as f32 * alpha).round() as u8,
             Paint::Gradient(ref mut gradient) => gradient.set_opacity(alpha),
+            Paint::Pattern(ref mut pattern) => pattern.set_opacity(alpha),
+        }
+    }
+
+    /// Transforms the coordinate space of this paint, for use with an outline transformed the
+    /// same way.
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        match *self {
+            Paint::Color(_) => {}
+            Paint::Gradient(ref mut gradient) => gradient.apply_transform(transform),
+            Paint::Pattern(ref mut pattern) => pattern.apply_transform(transform),
         }
     }
 }
@@ -114,52 +176,260 @@ pub struct PaintInfo {
 
 #[derive(Debug)]
 pub struct PaintMetadata {
-    /// The transform to apply to the texture coordinates, in 0.16 fixed point.
-    pub tex_transform: Transform2I,
-    /// True if this paint is fully opaque.
+    /// Maps device pixels to normalized coordinates in the paint texture.
+    pub tex_transform: Transform2F,
+    /// The page of the paint texture that this paint is on.
+    pub page: PaintPageId,
+    /// True if this paint is fully opaque everywhere it's drawn, whether it's a color, a
+    /// gradient, or a pattern. Tiles that an opaque paint covers completely go into the Z-buffer
+    /// and occlude everything beneath them.
     pub is_opaque: bool,
 }
 
 impl Palette {
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub fn push_paint(&mut self, paint: &Paint) -> PaintId {
-        if let Some(paint_id) = self.cache.get(paint) {
+        self.push_paint_in_color_space(paint, ColorSpace::Srgb)
+    }
+
+    pub fn push_paint_in_color_space(&mut self, paint: &Paint, color_space: ColorSpace)
+                                     -> PaintId {
+        let key = ((*paint).clone(), color_space);
+        if let Some(paint_id) = self.cache.get(&key) {
             return *paint_id;
         }
 
         let paint_id = PaintId(self.paints.len() as u16);
-        self.cache.insert((*paint).clone(), paint_id);
+        self.cache.insert(key, paint_id);
         self.paints.push((*paint).clone());
+        self.color_spaces.push(color_space);
         paint_id
     }
 
-    pub fn build_paint_info(&self) -> PaintInfo {
-        let mut allocator = TextureAllocator::new(PAINT_TEXTURE_LENGTH);
-        let area = PAINT_TEXTURE_LENGTH as usize * PAINT_TEXTURE_LENGTH as usize;
-        let (mut texels, mut metadata) = (vec![0; area * 4], vec![]);
+    /// Packs the paints into a texture. `render_transform` maps scene coordinates to device
+    /// pixels, and `view_box` is the area of device space that can be drawn to. Gradients are
+    /// only baked as they appear within the view box. Paints are converted to `color_space` as
+    /// they are packed. If `premultiplied_paints` is true, the colors of patterns and gradients
+    /// are premultiplied, and they're unpremultiplied as they are packed.
+    ///
+    /// Grayscale and alpha-only gradients and patterns go on pages of their own, one byte per
+    /// texel. Everything else, including all solid colors, goes on the first page.
+    ///
+    /// Gradients and patterns that don't fit are drawn transparent, but solid colors have nowhere
+    /// else to go, so running out of room for them is an error.
+    pub fn build_paint_info(&self,
+                            render_transform: &Transform2F,
+                            view_box: RectF,
+                            color_space: ColorSpace,
+                            premultiplied_paints: bool)
+                            -> Result<PaintInfo, SceneBuildError> {
+        let mut pages = vec![PaintPageBuilder::new(PaintPageFormat::RGBA8)];
+        let mut metadata = vec![];
         let mut solid_color_tile_builder = SolidColorTileBuilder::new();
+        let mut image_locations: HashMap<(&Image, ColorSpace), (PaintPageId, TextureLocation)> =
+            HashMap::new();
 
-        for paint in &self.paints {
+        for (paint, &paint_color_space) in self.paints.iter().zip(self.color_spaces.iter()) {
             let tex_transform;
+            // Cleared if the paint has to be drawn transparent for lack of room.
+            let mut is_opaque = paint.is_opaque();
+            let mut page_id = PaintPageId(0);
             match paint {
                 Paint::Color(color) => {
-                    // TODO(pcwalton): Handle other paint types.
-                    let texture_location = solid_color_tile_builder.allocate(&mut allocator);
-                    put_pixel(&mut texels, texture_location.rect.origin(), *color);
-                    tex_transform = Transform2I {
-                        matrix: Matrix2x2I(I32x4::default()),
-                        vector: texture_location.rect.origin().scale(PAINT_TEXTURE_SCALE as i32) +
-                            Vector2I::splat(PAINT_TEXTURE_SCALE as i32 / 2),
+                    let page = &mut pages[0];
+                    let texture_location =
+                        solid_color_tile_builder.allocate(&mut page.allocator)?;
+                    put_pixel(&mut page.texels,
+                              texture_location.rect.origin(),
+                              color.convert_color_space(paint_color_space, color_space));
+                    tex_transform = solid_color_tex_transform(texture_location);
+                }
+                Paint::Gradient(ref gradient) => {
+                    let mut gradient = gradient.clone();
+                    gradient.apply_transform(render_transform);
+                    page_id = page_for_format(&mut pages, paint.narrowest_page_format());
+                    let page = &mut pages[page_id.0 as usize];
+                    let texture_location = match *gradient.geometry() {
+                        GradientGeometry::Linear(_) => {
+                            // Fall back to a narrower row, supersampled all the same, if there's
+                            // no room for a wide one.
+                            let row_length = linear_gradient_row_length(&gradient, view_box);
+                            page.allocate_gradient_row(row_length).or_else(|| {
+                                if row_length > MIN_GRADIENT_ROW_LENGTH {
+                                    page.allocate_gradient_row(MIN_GRADIENT_ROW_LENGTH)
+                                } else {
+                                    None
+                                }
+                            })
+                        }
+                        GradientGeometry::Radial { .. } => {
+                            page.allocator.allocate(gradient_raster_size(view_box))
+                        }
+                    };
+                    let gradient_tex_transform = match texture_location {
+                        None => {
+                            // TODO: Support more than one paint texture.
+                            warn!("No room for a gradient in the paint texture!");
+                            None
+                        }
+                        Some(texture_location) => {
+                            let tex_transform = match *gradient.geometry() {
+                                GradientGeometry::Linear(_) => {
+                                    put_linear_gradient(&mut page.texels,
+                                                        texture_location,
+                                                        &gradient,
+                                                        view_box)
+                                }
+                                GradientGeometry::Radial { .. } => {
+                                    Some(put_gradient_raster(&mut page.texels,
+                                                             texture_location,
+                                                             &gradient,
+                                                             view_box))
+                                }
+                            };
+                            convert_texels(&mut page.texels,
+                                           texture_location.rect,
+                                           premultiplied_paints,
+                                           paint_color_space,
+                                           color_space);
+                            tex_transform
+                        }
+                    };
+                    tex_transform = match gradient_tex_transform {
+                        Some(tex_transform) => tex_transform,
+                        None => {
+                            // Degenerate gradients are transparent.
+                            is_opaque = false;
+                            page_id = PaintPageId(0);
+                            let page = &mut pages[0];
+                            let texture_location =
+                                solid_color_tile_builder.allocate(&mut page.allocator)?;
+                            put_pixel(&mut page.texels,
+                                      texture_location.rect.origin(),
+                                      ColorU::transparent_black());
+                            solid_color_tex_transform(texture_location)
+                        }
                     };
                 }
-                Paint::Gradient(_) => unimplemented!(),
+                Paint::Pattern(ref pattern) => {
+                    // Patterns that share an image, like the copies of a repeating canvas
+                    // pattern, share its texels too.
+                    let image = &pattern.image;
+                    let texture_location = match image_locations.get(&(image, paint_color_space)) {
+                        Some(&(page_id, texture_location)) => Some((page_id, texture_location)),
+                        None => {
+                            let length = image.size().x().max(image.size().y()).max(1) as u32;
+                            let size = Vector2I::splat(length.next_power_of_two() as i32);
+                            let page_id = page_for_format(&mut pages,
+                                                          paint.narrowest_page_format());
+                            let page = &mut pages[page_id.0 as usize];
+                            let texture_location = page.allocator.allocate(size);
+                            if let Some(texture_location) = texture_location {
+                                let origin = texture_location.rect.origin();
+                                put_image(&mut page.texels, origin, image);
+                                convert_texels(&mut page.texels,
+                                               RectI::new(origin, image.size()),
+                                               premultiplied_paints,
+                                               paint_color_space,
+                                               color_space);
+                                image_locations.insert((image, paint_color_space),
+                                                       (page_id, texture_location));
+                            }
+                            texture_location.map(|texture_location| (page_id, texture_location))
+                        }
+                    };
+                    match texture_location {
+                        None => {
+                            // TODO: Support more than one paint texture, or downsample.
+                            warn!("No room for a {}x{} pattern in the paint texture!",
+                                  image.size().x(),
+                                  image.size().y());
+                            is_opaque = false;
+                            let page = &mut pages[0];
+                            let texture_location =
+                                solid_color_tile_builder.allocate(&mut page.allocator)?;
+                            put_pixel(&mut page.texels,
+                                      texture_location.rect.origin(),
+                                      ColorU::transparent_black());
+                            tex_transform = solid_color_tex_transform(texture_location);
+                        }
+                        Some((pattern_page_id, texture_location)) => {
+                            // Device pixels -> scene -> image pixels -> paint texture.
+                            page_id = pattern_page_id;
+                            let texture_origin = texture_location.rect.origin().to_f32();
+                            let image_to_texture =
+                                Transform2F::from_uniform_scale(1.0 / PAINT_TEXTURE_LENGTH as f32) *
+                                Transform2F::from_translation(texture_origin);
+                            tex_transform = image_to_texture * pattern.transform.inverse() *
+                                render_transform.inverse();
+                        }
+                    }
+                }
             }
 
-            metadata.push(PaintMetadata { tex_transform, is_opaque: paint.is_opaque() });
+            metadata.push(PaintMetadata { tex_transform, page: page_id, is_opaque });
         }
 
-        let size = Vector2I::splat(PAINT_TEXTURE_LENGTH as i32);
-        return PaintInfo { data: PaintData { size, texels }, metadata };
+        let pages = pages.into_iter().map(PaintPageBuilder::finish).collect();
+        return Ok(PaintInfo { data: PaintData { pages }, metadata });
+
+        // Returns the page that paints of `format` go on, adding it if there isn't one yet.
+        fn page_for_format(pages: &mut Vec<PaintPageBuilder>, format: PaintPageFormat)
+                           -> PaintPageId {
+            let page_index = match pages.iter().position(|page| page.format == format) {
+                Some(page_index) => page_index,
+                None => {
+                    pages.push(PaintPageBuilder::new(format));
+                    pages.len() - 1
+                }
+            };
+            PaintPageId(page_index as u32)
+        }
+
+        // Every pixel samples the center of the texel.
+        fn solid_color_tex_transform(texture_location: TextureLocation) -> Transform2F {
+            let texel_center = texture_location.rect.origin().to_f32() + Vector2F::splat(0.5);
+            Transform2F {
+                matrix: Matrix2x2F::from_scale(Vector2F::default()),
+                vector: texel_center.scale(1.0 / PAINT_TEXTURE_LENGTH as f32),
+            }
+        }
+
+        // Unpremultiplies the texels in `rect` if `premultiplied` is true, and converts them from
+        // the color space `from` to `to`.
+        fn convert_texels(texels: &mut [u8],
+                          rect: RectI,
+                          premultiplied: bool,
+                          from: ColorSpace,
+                          to: ColorSpace) {
+            if !premultiplied && from == to {
+                return;
+            }
+            for y in rect.min_y()..rect.max_y() {
+                for x in rect.min_x()..rect.max_x() {
+                    let index = (y as usize * PAINT_TEXTURE_LENGTH as usize + x as usize) * 4;
+                    let mut color = ColorU {
+                        r: texels[index + 0],
+                        g: texels[index + 1],
+                        b: texels[index + 2],
+                        a: texels[index + 3],
+                    };
+                    if premultiplied {
+                        color = unpremultiply(color);
+                    }
+                    put_pixel(texels, Vector2I::new(x, y), color.convert_color_space(from, to));
+                }
+            }
+        }
+
+        fn unpremultiply(color: ColorU) -> ColorU {
+            if color.a == 0 {
+                return ColorU::transparent_black();
+            }
+            let alpha = color.a as u32;
+            let channel = |value: u8| ((value as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            ColorU { r: channel(color.r), g: channel(color.g), b: channel(color.b), a: color.a }
+        }
 
         fn put_pixel(texels: &mut [u8], position: Vector2I, color: ColorU) {
             let index = (position.y() as usize * PAINT_TEXTURE_LENGTH as usize +
@@ -169,6 +439,270 @@ impl Palette {
             texels[index + 2] = color.b;
             texels[index + 3] = color.a;
         }
+
+        // Bakes a row of the offsets that `gradient`, in device space, takes on within
+        // `view_box`, and returns the transform from device pixels to that row.
+        fn put_linear_gradient(texels: &mut [u8],
+                               texture_location: TextureLocation,
+                               gradient: &Gradient,
+                               view_box: RectF)
+                               -> Option<Transform2F> {
+            let span = LinearGradientSpan::new(gradient, view_box)?;
+
+            // The first and last texel centers take on the minimum and maximum offsets, so that
+            // filtering never reaches past the ends of the row.
+            let row_origin = texture_location.rect.origin();
+            let last_texel_index = texture_location.rect.width() - 1;
+            let texel_offset_range = span.offset_range / last_texel_index as f32;
+            let sample_count = gradient_samples_per_texel(span.visible_stop_count(gradient),
+                                                          texture_location.rect.width() as u32);
+
+            // Each texel averages `sample_count` samples spread evenly over the offsets between
+            // it and its neighbors, so the samples of the whole row are evenly spaced, and can be
+            // taken four at a time.
+            let sampler = GradientSampler::new(gradient);
+            let total_sample_count = (last_texel_index as u32 + 1) * sample_count;
+            let sample_spacing = texel_offset_range / sample_count as f32;
+            let first_sample_offset = span.min_offset +
+                (0.5 / sample_count as f32 - 0.5) * texel_offset_range;
+            let mut sum = F32x4::default();
+            for first_sample_index in (0..total_sample_count).step_by(4) {
+                let sample_indices = F32x4::splat(first_sample_index as f32) +
+                    F32x4::new(0.0, 1.0, 2.0, 3.0);
+                let offsets = F32x4::splat(first_sample_offset) +
+                    sample_indices * F32x4::splat(sample_spacing);
+                let colors = sampler.sample_x4(offsets);
+                let sample_end = total_sample_count.min(first_sample_index + 4);
+                for (sample_index, color) in (first_sample_index..sample_end).zip(colors.iter()) {
+                    let texel_index = (sample_index / sample_count) as i32;
+                    let texel = row_origin + Vector2I::new(texel_index, 0);
+                    if sample_count == 1 {
+                        put_pixel(texels, texel, color.to_u8());
+                        continue;
+                    }
+                    sum = sum + color.premultiply().0;
+                    if sample_index % sample_count == sample_count - 1 {
+                        put_pixel(texels, texel, unpremultiply_average(sum, sample_count));
+                        sum = F32x4::default();
+                    }
+                }
+            }
+
+            // Device pixels -> offset -> position along the row -> paint texture.
+            let origin = span.origin;
+            let scale = last_texel_index as f32 /
+                (span.offset_range * PAINT_TEXTURE_LENGTH as f32);
+            let u_gradient = span.offset_gradient.scale(scale);
+            let u_at_origin = (span.offset_at_origin - span.min_offset) * scale +
+                (row_origin.x() as f32 + 0.5) / PAINT_TEXTURE_LENGTH as f32;
+            let v = (row_origin.y() as f32 + 0.5) / PAINT_TEXTURE_LENGTH as f32;
+            Some(Transform2F::row_major(u_gradient.x(),
+                                        0.0,
+                                        u_gradient.y(),
+                                        0.0,
+                                        u_at_origin - origin.dot(u_gradient),
+                                        v))
+        }
+
+        // Averages `sample_count` colors whose premultiplied sum is `sum`. Colors are averaged
+        // premultiplied, so that transparent stops don't darken their neighbors.
+        fn unpremultiply_average(sum: F32x4, sample_count: u32) -> ColorU {
+            let alpha = sum[3] / sample_count as f32;
+            if alpha == 0.0 {
+                return ColorU::transparent_black();
+            }
+            let reciprocal = 1.0 / sum[3];
+            ColorF(sum * F32x4::new(reciprocal, reciprocal, reciprocal, 0.0) +
+                   F32x4::new(0.0, 0.0, 0.0, alpha)).to_u8()
+        }
+
+        // Rasterizes `gradient`, in device space, over `view_box`, and returns the transform from
+        // device pixels to the raster.
+        //
+        // TODO: Rasterize only the parts of the view box that the paint is used in.
+        fn put_gradient_raster(texels: &mut [u8],
+                               texture_location: TextureLocation,
+                               gradient: &Gradient,
+                               view_box: RectF)
+                               -> Transform2F {
+            let raster_size = texture_location.rect.size();
+            let texel_size = view_box.size() / raster_size.to_f32();
+            for y in 0..raster_size.y() {
+                for x in 0..raster_size.x() {
+                    let texel = Vector2I::new(x, y);
+                    let point = view_box.origin() +
+                        (texel.to_f32() + Vector2F::splat(0.5)) * texel_size;
+                    put_pixel(texels,
+                              texture_location.rect.origin() + texel,
+                              gradient.sample_at(point));
+                }
+            }
+
+            // Device pixels -> raster texels -> paint texture.
+            let texture_origin = texture_location.rect.origin().to_f32();
+            Transform2F::from_uniform_scale(1.0 / PAINT_TEXTURE_LENGTH as f32) *
+                Transform2F::from_translation(texture_origin) *
+                Transform2F::from_scale(Vector2F::splat(1.0) / texel_size) *
+                Transform2F::from_translation(-view_box.origin())
+        }
+
+        fn put_image(texels: &mut [u8], origin: Vector2I, image: &Image) {
+            let width = image.size().x();
+            for (pixel_index, &pixel) in image.pixels().iter().enumerate() {
+                let position = origin + Vector2I::new(pixel_index as i32 % width,
+                                                      pixel_index as i32 / width);
+                put_pixel(texels, position, pixel);
+            }
+        }
+    }
+}
+
+// The size of the raster that a radial gradient is baked into: the size of the view box, rounded
+// up to a power of two, and limited to `MAX_GRADIENT_RASTER_LENGTH`.
+fn gradient_raster_size(view_box: RectF) -> Vector2I {
+    let length = view_box.width().max(view_box.height()).ceil().max(1.0) as u32;
+    Vector2I::splat(length.next_power_of_two().min(MAX_GRADIENT_RASTER_LENGTH) as i32)
+}
+
+// The offsets that a linear gradient, in device space, takes on within a view box. The offset is
+// an affine function of the position.
+struct LinearGradientSpan {
+    origin: Vector2F,
+    offset_at_origin: f32,
+    offset_gradient: Vector2F,
+    min_offset: f32,
+    offset_range: f32,
+}
+
+impl LinearGradientSpan {
+    fn new(gradient: &Gradient, view_box: RectF) -> Option<LinearGradientSpan> {
+        let origin = view_box.origin();
+        let offset_at_origin = gradient.offset_at(origin)?;
+        let offset_gradient =
+            Vector2F::new(gradient.offset_at(origin + Vector2F::new(1.0, 0.0))?,
+                          gradient.offset_at(origin + Vector2F::new(0.0, 1.0))?) -
+            Vector2F::splat(offset_at_origin);
+        if !offset_at_origin.is_finite() || !offset_gradient.x().is_finite() ||
+                !offset_gradient.y().is_finite() {
+            return None;
+        }
+
+        let (mut min_offset, mut max_offset) = (offset_at_origin, offset_at_origin);
+        let corners = [view_box.upper_right(), view_box.lower_left(), view_box.lower_right()];
+        for &corner in &corners {
+            let offset = offset_at_origin + (corner - origin).dot(offset_gradient);
+            min_offset = min_offset.min(offset);
+            max_offset = max_offset.max(offset);
+        }
+        let offset_range = (max_offset - min_offset).max(EPSILON);
+
+        Some(LinearGradientSpan {
+            origin,
+            offset_at_origin,
+            offset_gradient,
+            min_offset,
+            offset_range,
+        })
+    }
+
+    // Roughly how many stops of `gradient` fall within this span, counting each repetition of a
+    // repeating or reflecting gradient.
+    fn visible_stop_count(&self, gradient: &Gradient) -> f32 {
+        let stops = gradient.stops();
+        match gradient.wrap() {
+            GradientWrap::Clamp => {
+                let max_offset = self.min_offset + self.offset_range;
+                stops.iter()
+                     .filter(|stop| stop.offset >= self.min_offset && stop.offset <= max_offset)
+                     .count() as f32
+            }
+            GradientWrap::Repeat | GradientWrap::Reflect => {
+                stops.len() as f32 * self.offset_range.ceil()
+            }
+        }
+    }
+}
+
+// The length of the row that a linear gradient is baked into within `view_box`: enough for two
+// texels per visible stop, rounded up to a power of two, within the range of row lengths.
+fn linear_gradient_row_length(gradient: &Gradient, view_box: RectF) -> u32 {
+    let stop_count = match LinearGradientSpan::new(gradient, view_box) {
+        None => return MIN_GRADIENT_ROW_LENGTH,
+        Some(span) => span.visible_stop_count(gradient),
+    };
+    let length = (stop_count * 2.0).ceil().min(MAX_GRADIENT_ROW_LENGTH as f32) as u32;
+    length.next_power_of_two().max(MIN_GRADIENT_ROW_LENGTH).min(MAX_GRADIENT_ROW_LENGTH)
+}
+
+// The number of samples to average per texel so that each visible stop is sampled a few times
+// across a row of `row_length` texels.
+fn gradient_samples_per_texel(stop_count: f32, row_length: u32) -> u32 {
+    let samples = (stop_count * 4.0 / row_length as f32).ceil();
+    (samples as u32).max(1).min(MAX_GRADIENT_SAMPLES_PER_TEXEL)
+}
+
+// Grays stay gray, and black stays black, through interpolation, unpremultiplication, and color
+// space conversion, so a paint whose colors are all of one kind has texels of the same kind.
+// Only opaque grays are packed, since the texels of a paint that isn't opaque everywhere, like a
+// radial gradient that doesn't cover the plane, may be transparent black.
+fn narrowest_page_format_for_colors<I>(colors: I, is_opaque: bool) -> PaintPageFormat
+                                       where I: Iterator<Item = ColorU> {
+    let (mut is_gray, mut is_black) = (is_opaque, true);
+    for color in colors {
+        is_gray = is_gray && color.r == color.g && color.g == color.b;
+        is_black = is_black && color.r == 0 && color.g == 0 && color.b == 0;
+        if !is_gray && !is_black {
+            return PaintPageFormat::RGBA8;
+        }
+    }
+    if is_gray {
+        PaintPageFormat::Gray8
+    } else {
+        PaintPageFormat::Alpha8
+    }
+}
+
+const EPSILON: f32 = 1.0e-6;
+
+// A page of the paint texture as it's being packed. Texels are written as RGBA and reduced to the
+// format of the page once it's finished, so that paints are rasterized the same way on every
+// page.
+struct PaintPageBuilder {
+    format: PaintPageFormat,
+    allocator: TextureAllocator,
+    texels: Vec<u8>,
+    // Tiles of linear gradient rows, by row length.
+    gradient_tile_builders: HashMap<u32, GradientTileBuilder>,
+}
+
+impl PaintPageBuilder {
+    fn new(format: PaintPageFormat) -> PaintPageBuilder {
+        let area = PAINT_TEXTURE_LENGTH as usize * PAINT_TEXTURE_LENGTH as usize;
+        PaintPageBuilder {
+            format,
+            allocator: TextureAllocator::new(PAINT_TEXTURE_LENGTH),
+            texels: vec![0; area * 4],
+            gradient_tile_builders: HashMap::new(),
+        }
+    }
+
+    fn allocate_gradient_row(&mut self, row_length: u32) -> Option<TextureLocation> {
+        let allocator = &mut self.allocator;
+        self.gradient_tile_builders
+            .entry(row_length)
+            .or_insert_with(|| GradientTileBuilder::new(row_length))
+            .allocate(allocator)
+    }
+
+    fn finish(self) -> PaintPageData {
+        let size = Vector2I::splat(PAINT_TEXTURE_LENGTH as i32);
+        let texels = match self.format {
+            PaintPageFormat::RGBA8 => self.texels,
+            // Color space conversion may round the channels of a gray differently.
+            PaintPageFormat::Gray8 => self.texels.chunks(4).map(|texel| texel[1]).collect(),
+            PaintPageFormat::Alpha8 => self.texels.chunks(4).map(|texel| texel[3]).collect(),
+        };
+        PaintPageData { format: self.format, size, texels }
     }
 }
 
@@ -184,12 +718,13 @@ impl SolidColorTileBuilder {
         SolidColorTileBuilder(None)
     }
 
-    fn allocate(&mut self, allocator: &mut TextureAllocator) -> TextureLocation {
+    fn allocate(&mut self, allocator: &mut TextureAllocator)
+                -> Result<TextureLocation, SceneBuildError> {
         if self.0.is_none() {
-            // TODO(pcwalton): Handle allocation failure gracefully!
+            let tile_size = Vector2I::splat(SOLID_COLOR_TILE_LENGTH as i32);
             self.0 = Some(SolidColorTileBuilderData {
-                tile_location: allocator.allocate(Vector2I::splat(SOLID_COLOR_TILE_LENGTH as i32))
-                                        .expect("Failed to allocate a solid color tile!"),
+                tile_location: allocator.allocate(tile_size)
+                                        .ok_or(SceneBuildError::PaintTextureFull)?,
                 next_index: 0,
             });
         }
@@ -211,6 +746,52 @@ impl SolidColorTileBuilder {
             self.0 = None;
         }
 
-        location
+        Ok(location)
+    }
+}
+
+// Packs one-texel-high rows for linear gradients into square tiles, as many rows as a row is
+// long.
+struct GradientTileBuilder {
+    row_length: u32,
+    data: Option<GradientTileBuilderData>,
+}
+
+struct GradientTileBuilderData {
+    tile_location: TextureLocation,
+    next_row: u32,
+}
+
+impl GradientTileBuilder {
+    fn new(row_length: u32) -> GradientTileBuilder {
+        GradientTileBuilder { row_length, data: None }
+    }
+
+    fn allocate(&mut self, allocator: &mut TextureAllocator) -> Option<TextureLocation> {
+        if self.data.is_none() {
+            let tile_size = Vector2I::splat(self.row_length as i32);
+            self.data = Some(GradientTileBuilderData {
+                tile_location: allocator.allocate(tile_size)?,
+                next_row: 0,
+            });
+        }
+
+        let (location, tile_full);
+        {
+            let data = self.data.as_mut().unwrap();
+            location = TextureLocation {
+                rect: RectI::new(data.tile_location.rect.origin() +
+                                 Vector2I::new(0, data.next_row as i32),
+                                 Vector2I::new(self.row_length as i32, 1)),
+            };
+            data.next_row += 1;
+            tile_full = data.next_row == self.row_length;
+        }
+
+        if tile_full {
+            self.data = None;
+        }
+
+        Some(location)
     }
 }
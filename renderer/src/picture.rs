@@ -0,0 +1,551 @@
+// pathfinder/renderer/src/picture.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recorded drawing commands that can be replayed into scenes.
+
+use crate::paint::Paint;
+use crate::scene::{ClipPath, ClipPathId, PathObject, Scene};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_color::{ColorSpace, ColorU};
+use pathfinder_content::boolean::BooleanOp;
+use pathfinder_content::effects::{BackdropFilter, BlendMode};
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientGeometry, GradientWrap};
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::sync::Arc;
+
+const MAGIC: &[u8; 4] = b"PFPC";
+// Version 2 added the color space of each paint, version 3 the fill rule of each path, and
+// version 4 the backdrop filter of each path.
+const VERSION: u32 = 4;
+
+const NO_CLIP_PATH: u32 = !0;
+
+// Must match the order of `BlendMode`.
+static BLEND_MODES: [BlendMode; 27] = [
+    BlendMode::Clear,
+    BlendMode::Copy,
+    BlendMode::SrcIn,
+    BlendMode::SrcOut,
+    BlendMode::SrcOver,
+    BlendMode::SrcAtop,
+    BlendMode::DestIn,
+    BlendMode::DestOut,
+    BlendMode::DestOver,
+    BlendMode::DestAtop,
+    BlendMode::Xor,
+    BlendMode::Lighter,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::Overlay,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::HardLight,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+    BlendMode::Hue,
+    BlendMode::Saturation,
+    BlendMode::Color,
+    BlendMode::Luminosity,
+];
+
+/// A recorded list of paths, with the paints and clip paths that they use, that can be replayed
+/// into any scene under a transform, like a Skia `SkPicture`.
+///
+/// Pictures are recorded by drawing into a scene, for instance with a canvas, and converting it
+/// with `from_scene()`. They can be recorded on several threads at once and replayed into one
+/// scene, and they can be cached to avoid rebuilding expensive drawings. `write_to()` and
+/// `read_from()` save and load them in a compact binary format.
+#[derive(Clone, Debug)]
+pub struct Picture {
+    paints: Vec<Paint>,
+    paint_color_spaces: Vec<ColorSpace>,
+    clip_paths: Vec<ClipPath>,
+    paths: Vec<PathObject>,
+    bounds: RectF,
+}
+
+impl Picture {
+    #[inline]
+    pub fn new() -> Picture {
+        Picture {
+            paints: vec![],
+            paint_color_spaces: vec![],
+            clip_paths: vec![],
+            paths: vec![],
+            bounds: RectF::default(),
+        }
+    }
+
+    /// Records the paths of `scene`. Its view box is ignored.
+    pub fn from_scene(scene: Scene) -> Picture {
+        Picture {
+            paints: scene.palette.paints,
+            paint_color_spaces: scene.palette.color_spaces,
+            clip_paths: scene.clip_paths,
+            paths: scene.paths,
+            bounds: scene.bounds,
+        }
+    }
+
+    #[inline]
+    pub fn path_count(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// The bounding box of the paths, before replaying applies any transform.
+    #[inline]
+    pub fn bounds(&self) -> RectF {
+        self.bounds
+    }
+
+    /// Multiplies the alpha of every paint by `alpha`.
+    pub fn set_opacity(&mut self, alpha: f32) {
+        for paint in &mut self.paints {
+            paint.set_opacity(alpha);
+        }
+    }
+
+    /// Draws the paths into `scene` under `transform`, on top of the paths already there.
+    ///
+    /// If `clip_path`, a clip path of `scene`, is given, every path is clipped to it as well as
+    /// to its own clip path.
+    pub fn replay(&self,
+                  scene: &mut Scene,
+                  transform: &Transform2F,
+                  clip_path: Option<ClipPathId>) {
+        let paints = self.paints.iter().zip(self.paint_color_spaces.iter());
+        let paint_ids: Vec<_> = paints.map(|(paint, &color_space)| {
+            let mut paint = paint.clone();
+            paint.apply_transform(transform);
+            scene.push_paint_in_color_space(&paint, color_space)
+        }).collect();
+
+        // Push the clip paths that paths refer to, each once.
+        let mut clip_path_ids = vec![None; self.clip_paths.len()];
+        for path in &self.paths {
+            let mut outline = path.transformed_outline().into_owned();
+            outline.transform(transform);
+
+            let path_clip_path = match path.clip_path() {
+                None => clip_path,
+                Some(picture_clip_path_id) => {
+                    let index = picture_clip_path_id.0 as usize;
+                    if clip_path_ids[index].is_none() {
+                        let picture_clip_path = &self.clip_paths[index];
+                        let mut clip_outline = picture_clip_path.outline().clone();
+                        clip_outline.transform(transform);
+                        let new_clip_path = match clip_path {
+                            None => ClipPath::new(clip_outline, picture_clip_path.fill_rule()),
+                            Some(clip_path) => {
+                                let clip_path = scene.clip_path(clip_path);
+                                let clip_outline = clip_path.outline().boolean_op_with_fill_rules(
+                                    clip_path.fill_rule(),
+                                    &clip_outline,
+                                    picture_clip_path.fill_rule(),
+                                    BooleanOp::Intersection);
+                                ClipPath::new(clip_outline, FillRule::Winding)
+                            }
+                        };
+                        clip_path_ids[index] = Some(scene.push_clip_path(new_clip_path));
+                    }
+                    clip_path_ids[index]
+                }
+            };
+
+            let paint_id = paint_ids[path.paint().0 as usize];
+            let mut path_object = PathObject::new(outline, paint_id, path.name().to_owned());
+            path_object.set_blend_mode(path.blend_mode());
+            path_object.set_fill_rule(path.fill_rule());
+            path_object.set_backdrop_filter(path.backdrop_filter());
+            path_object.set_clip_path(path_clip_path);
+            scene.push_path(path_object);
+        }
+    }
+
+    /// Writes the picture in Pathfinder's binary picture format.
+    pub fn write_to<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        writer.write_all(MAGIC)?;
+        writer.write_u32::<LittleEndian>(VERSION)?;
+
+        write_length(writer, self.paints.len())?;
+        for (paint, &color_space) in self.paints.iter().zip(self.paint_color_spaces.iter()) {
+            write_paint(writer, paint)?;
+            write_color_space(writer, color_space)?;
+        }
+
+        write_length(writer, self.clip_paths.len())?;
+        for clip_path in &self.clip_paths {
+            write_fill_rule(writer, clip_path.fill_rule())?;
+            write_outline(writer, clip_path.outline())?;
+        }
+
+        write_length(writer, self.paths.len())?;
+        for path in &self.paths {
+            write_outline(writer, &path.transformed_outline())?;
+            writer.write_u32::<LittleEndian>(path.paint().0 as u32)?;
+            let blend_mode = BLEND_MODES.iter().position(|&mode| mode == path.blend_mode());
+            writer.write_u8(blend_mode.unwrap_or(0) as u8)?;
+            write_fill_rule(writer, path.fill_rule())?;
+            write_backdrop_filter(writer, path.backdrop_filter())?;
+            let clip_path = path.clip_path().map_or(NO_CLIP_PATH, |clip_path| clip_path.0);
+            writer.write_u32::<LittleEndian>(clip_path)?;
+            write_length(writer, path.name().len())?;
+            writer.write_all(path.name().as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a picture written by `write_to()`.
+    pub fn read_from<R>(reader: &mut R) -> io::Result<Picture> where R: Read {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a Pathfinder picture"));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version == 0 || version > VERSION {
+            return Err(invalid_data("unsupported picture version"));
+        }
+
+        let mut picture = Picture::new();
+        for _ in 0..reader.read_u32::<LittleEndian>()? {
+            picture.paints.push(read_paint(reader)?);
+            let color_space = if version >= 2 {
+                read_color_space(reader)?
+            } else {
+                ColorSpace::Srgb
+            };
+            picture.paint_color_spaces.push(color_space);
+        }
+        for _ in 0..reader.read_u32::<LittleEndian>()? {
+            let fill_rule = read_fill_rule(reader)?;
+            picture.clip_paths.push(ClipPath::new(read_outline(reader)?, fill_rule));
+        }
+
+        for _ in 0..reader.read_u32::<LittleEndian>()? {
+            let outline = read_outline(reader)?;
+            let paint = reader.read_u32::<LittleEndian>()?;
+            if paint as usize >= picture.paints.len() {
+                return Err(invalid_data("paint out of range"));
+            }
+            let blend_mode = BLEND_MODES.get(reader.read_u8()? as usize)
+                                        .ok_or_else(|| invalid_data("unknown blend mode"))?;
+            let fill_rule = if version >= 3 { read_fill_rule(reader)? } else { FillRule::Winding };
+            let backdrop_filter = if version >= 4 { read_backdrop_filter(reader)? } else { None };
+            let clip_path = match reader.read_u32::<LittleEndian>()? {
+                NO_CLIP_PATH => None,
+                clip_path if (clip_path as usize) < picture.clip_paths.len() => {
+                    Some(ClipPathId(clip_path))
+                }
+                _ => return Err(invalid_data("clip path out of range")),
+            };
+            let mut name = vec![0; reader.read_u32::<LittleEndian>()? as usize];
+            reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name).map_err(|_| invalid_data("invalid path name"))?;
+
+            picture.bounds = picture.bounds.union_rect(outline.bounds());
+            let mut path = PathObject::new(outline, crate::paint::PaintId(paint as u16), name);
+            path.set_blend_mode(*blend_mode);
+            path.set_fill_rule(fill_rule);
+            path.set_backdrop_filter(backdrop_filter);
+            path.set_clip_path(clip_path);
+            picture.paths.push(path);
+        }
+
+        Ok(picture)
+    }
+}
+
+impl Default for Picture {
+    #[inline]
+    fn default() -> Picture {
+        Picture::new()
+    }
+}
+
+fn write_length<W>(writer: &mut W, length: usize) -> io::Result<()> where W: Write {
+    writer.write_u32::<LittleEndian>(length as u32)
+}
+
+fn write_vector<W>(writer: &mut W, vector: Vector2F) -> io::Result<()> where W: Write {
+    writer.write_f32::<LittleEndian>(vector.x())?;
+    writer.write_f32::<LittleEndian>(vector.y())
+}
+
+fn write_color<W>(writer: &mut W, color: ColorU) -> io::Result<()> where W: Write {
+    writer.write_all(&[color.r, color.g, color.b, color.a])
+}
+
+fn write_transform<W>(writer: &mut W, transform: &Transform2F) -> io::Result<()> where W: Write {
+    for &value in &[
+        transform.m11(),
+        transform.m12(),
+        transform.m21(),
+        transform.m22(),
+        transform.vector.x(),
+        transform.vector.y(),
+    ] {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+    Ok(())
+}
+
+fn write_fill_rule<W>(writer: &mut W, fill_rule: FillRule) -> io::Result<()> where W: Write {
+    writer.write_u8(match fill_rule {
+        FillRule::Winding => 0,
+        FillRule::EvenOdd => 1,
+    })
+}
+
+fn write_backdrop_filter<W>(writer: &mut W, backdrop_filter: Option<BackdropFilter>)
+                           -> io::Result<()>
+                           where W: Write {
+    match backdrop_filter {
+        None => writer.write_u8(0),
+        Some(backdrop_filter) => {
+            writer.write_u8(1)?;
+            writer.write_f32::<LittleEndian>(backdrop_filter.blur_std_dev)?;
+            writer.write_f32::<LittleEndian>(backdrop_filter.saturation)
+        }
+    }
+}
+
+fn write_color_space<W>(writer: &mut W, color_space: ColorSpace) -> io::Result<()>
+                        where W: Write {
+    writer.write_u8(match color_space {
+        ColorSpace::Srgb => 0,
+        ColorSpace::DisplayP3 => 1,
+    })
+}
+
+fn write_paint<W>(writer: &mut W, paint: &Paint) -> io::Result<()> where W: Write {
+    match *paint {
+        Paint::Color(color) => {
+            writer.write_u8(0)?;
+            write_color(writer, color)
+        }
+        Paint::Gradient(ref gradient) => {
+            writer.write_u8(1)?;
+            match *gradient.geometry() {
+                GradientGeometry::Linear(line) => {
+                    writer.write_u8(0)?;
+                    write_vector(writer, line.from())?;
+                    write_vector(writer, line.to())?;
+                }
+                GradientGeometry::Radial { line, start_radius, end_radius } => {
+                    writer.write_u8(1)?;
+                    write_vector(writer, line.from())?;
+                    write_vector(writer, line.to())?;
+                    writer.write_f32::<LittleEndian>(start_radius)?;
+                    writer.write_f32::<LittleEndian>(end_radius)?;
+                }
+            }
+            writer.write_u8(match gradient.wrap() {
+                GradientWrap::Clamp => 0,
+                GradientWrap::Repeat => 1,
+                GradientWrap::Reflect => 2,
+            })?;
+            write_transform(writer, &gradient.transform())?;
+            write_length(writer, gradient.stops().len())?;
+            for stop in gradient.stops() {
+                write_color(writer, stop.color)?;
+                writer.write_f32::<LittleEndian>(stop.offset)?;
+            }
+            Ok(())
+        }
+        Paint::Pattern(ref pattern) => {
+            writer.write_u8(2)?;
+            let size = pattern.image.size();
+            writer.write_i32::<LittleEndian>(size.x())?;
+            writer.write_i32::<LittleEndian>(size.y())?;
+            for &pixel in pattern.image.pixels().iter() {
+                write_color(writer, pixel)?;
+            }
+            write_transform(writer, &pattern.transform)
+        }
+    }
+}
+
+// Contours are written as their points, each flagged as an endpoint or a control point.
+fn write_outline<W>(writer: &mut W, outline: &Outline) -> io::Result<()> where W: Write {
+    write_length(writer, outline.contours().len())?;
+    for contour in outline.contours() {
+        writer.write_u8(contour.is_closed() as u8)?;
+        writer.write_u32::<LittleEndian>(contour.len())?;
+        for point_index in 0..contour.len() {
+            writer.write_u8(!contour.point_is_endpoint(point_index) as u8)?;
+            write_vector(writer, contour.position_of(point_index))?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn read_vector<R>(reader: &mut R) -> io::Result<Vector2F> where R: Read {
+    let x = reader.read_f32::<LittleEndian>()?;
+    let y = reader.read_f32::<LittleEndian>()?;
+    if x.is_nan() || y.is_nan() {
+        return Err(invalid_data("NaN coordinate"));
+    }
+    Ok(Vector2F::new(x, y))
+}
+
+fn read_color<R>(reader: &mut R) -> io::Result<ColorU> where R: Read {
+    let mut color = [0; 4];
+    reader.read_exact(&mut color)?;
+    Ok(ColorU { r: color[0], g: color[1], b: color[2], a: color[3] })
+}
+
+fn read_transform<R>(reader: &mut R) -> io::Result<Transform2F> where R: Read {
+    let mut values = [0.0; 6];
+    for value in &mut values {
+        *value = reader.read_f32::<LittleEndian>()?;
+    }
+    Ok(Transform2F::row_major(values[0], values[1], values[2], values[3], values[4], values[5]))
+}
+
+fn read_fill_rule<R>(reader: &mut R) -> io::Result<FillRule> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(FillRule::Winding),
+        1 => Ok(FillRule::EvenOdd),
+        _ => Err(invalid_data("unknown fill rule")),
+    }
+}
+
+fn read_backdrop_filter<R>(reader: &mut R) -> io::Result<Option<BackdropFilter>>
+                          where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(None),
+        1 => {
+            let blur_std_dev = reader.read_f32::<LittleEndian>()?;
+            let saturation = reader.read_f32::<LittleEndian>()?;
+            if !(blur_std_dev >= 0.0 && blur_std_dev.is_finite() && saturation.is_finite()) {
+                return Err(invalid_data("invalid backdrop filter"));
+            }
+            Ok(Some(BackdropFilter { blur_std_dev, saturation }))
+        }
+        _ => Err(invalid_data("unknown backdrop filter")),
+    }
+}
+
+fn read_color_space<R>(reader: &mut R) -> io::Result<ColorSpace> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(ColorSpace::Srgb),
+        1 => Ok(ColorSpace::DisplayP3),
+        _ => Err(invalid_data("unknown color space")),
+    }
+}
+
+fn read_paint<R>(reader: &mut R) -> io::Result<Paint> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(Paint::Color(read_color(reader)?)),
+        1 => {
+            let geometry = match reader.read_u8()? {
+                0 => {
+                    let (from, to) = (read_vector(reader)?, read_vector(reader)?);
+                    GradientGeometry::Linear(LineSegment2F::new(from, to))
+                }
+                1 => {
+                    let (from, to) = (read_vector(reader)?, read_vector(reader)?);
+                    GradientGeometry::Radial {
+                        line: LineSegment2F::new(from, to),
+                        start_radius: reader.read_f32::<LittleEndian>()?,
+                        end_radius: reader.read_f32::<LittleEndian>()?,
+                    }
+                }
+                _ => return Err(invalid_data("unknown gradient geometry")),
+            };
+            let mut gradient = Gradient::from_geometry(geometry);
+            gradient.set_wrap(match reader.read_u8()? {
+                0 => GradientWrap::Clamp,
+                1 => GradientWrap::Repeat,
+                2 => GradientWrap::Reflect,
+                _ => return Err(invalid_data("unknown gradient wrap mode")),
+            });
+            gradient.apply_transform(&read_transform(reader)?);
+            for _ in 0..reader.read_u32::<LittleEndian>()? {
+                let color = read_color(reader)?;
+                let offset = reader.read_f32::<LittleEndian>()?;
+                gradient.add_color_stop(ColorStop { color, offset });
+            }
+            Ok(Paint::Gradient(gradient))
+        }
+        2 => {
+            let width = reader.read_i32::<LittleEndian>()?;
+            let height = reader.read_i32::<LittleEndian>()?;
+            if width < 0 || height < 0 {
+                return Err(invalid_data("negative image size"));
+            }
+            let mut pixels = Vec::with_capacity(width as usize * height as usize);
+            for _ in 0..(width as usize * height as usize) {
+                pixels.push(read_color(reader)?);
+            }
+            let image = Image::new(Vector2I::new(width, height), Arc::new(pixels));
+            Ok(Paint::Pattern(Pattern::new(image, read_transform(reader)?)))
+        }
+        _ => Err(invalid_data("unknown paint type")),
+    }
+}
+
+fn read_outline<R>(reader: &mut R) -> io::Result<Outline> where R: Read {
+    let mut outline = Outline::new();
+    for _ in 0..reader.read_u32::<LittleEndian>()? {
+        let closed = reader.read_u8()? != 0;
+        let mut contour = Contour::new();
+        let mut ctrls = vec![];
+        for _ in 0..reader.read_u32::<LittleEndian>()? {
+            let is_control_point = reader.read_u8()? != 0;
+            let point = read_vector(reader)?;
+            if is_control_point {
+                if ctrls.len() == 2 || contour.is_empty() {
+                    return Err(invalid_data("misplaced control point"));
+                }
+                ctrls.push(point);
+                continue;
+            }
+            push_segment(&mut contour, &ctrls, point);
+            ctrls.clear();
+        }
+
+        // A closing curve ends at the first point.
+        if !ctrls.is_empty() {
+            let first_point = contour.position_of(0);
+            push_segment(&mut contour, &ctrls, first_point);
+        }
+        if closed {
+            contour.close();
+        }
+        outline.push_contour(contour);
+    }
+    Ok(outline)
+}
+
+fn push_segment(contour: &mut Contour, ctrls: &[Vector2F], point: Vector2F) {
+    match *ctrls {
+        [] => contour.push_endpoint(point),
+        [ctrl] => contour.push_quadratic(ctrl, point),
+        [ctrl0, ctrl1] => contour.push_cubic(ctrl0, ctrl1, point),
+        _ => unreachable!(),
+    }
+}
@@ -15,18 +15,33 @@ use crate::concurrent::executor::Executor;
 use crate::options::{BuildOptions, PreparedBuildOptions};
 use crate::options::{PreparedRenderTransform, RenderCommandListener};
 use crate::paint::{Paint, PaintId, PaintInfo, Palette};
-use pathfinder_color::ColorU;
+use pathfinder_color::{ColorSpace, ColorU};
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_geometry::rect::RectF;
-use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::transform2d::{Transform2F, Transform2F64};
+use pathfinder_geometry::transform3d::Transform4F;
+use pathfinder_content::boolean::BooleanOp;
+use pathfinder_content::effects::{BackdropFilter, BlendMode};
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
+use pathfinder_content::rounded_rect::RoundedRect;
+use std::borrow::Cow;
+use std::f32::consts::PI;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::sync::Arc;
+
+// Paths are numbered with 16-bit object indices, and the Z-buffer stores one more than each.
+pub(crate) const MAX_PATH_COUNT: usize = 65535;
 
 #[derive(Clone)]
 pub struct Scene {
     pub(crate) paths: Vec<PathObject>,
-    palette: Palette,
-    bounds: RectF,
+    pub(crate) clip_paths: Vec<ClipPath>,
+    pub(crate) palette: Palette,
+    pub(crate) bounds: RectF,
     view_box: RectF,
+    view_transform: Transform2F64,
 }
 
 impl Scene {
@@ -34,20 +49,108 @@ impl Scene {
     pub fn new() -> Scene {
         Scene {
             paths: vec![],
+            clip_paths: vec![],
             palette: Palette::new(),
             bounds: RectF::default(),
             view_box: RectF::default(),
+            view_transform: Transform2F64::default(),
         }
     }
 
     pub fn push_path(&mut self, path: PathObject) {
-        self.bounds = self.bounds.union_rect(path.outline.bounds());
+        self.bounds = self.bounds.union_rect(path.bounds());
         self.paths.push(path);
     }
 
+    /// Pushes a path whose outline is placed in the world by `transform`.
+    ///
+    /// The transform is composed with the view transform in double precision and only then
+    /// applied to the outline, so outlines can be far from the origin of the world without losing
+    /// precision, as long as they are small in their own space. The paint is not transformed.
+    pub fn push_path_with_transform(&mut self, mut path: PathObject, transform: &Transform2F64) {
+        let transform = self.view_transform * *transform;
+        if path.is_instance() {
+            // Leave the shared outline alone.
+            let outline_transform = path.outline_transform.to_f64();
+            path.outline_transform = (transform * outline_transform).to_f32();
+        } else {
+            path.transform_outline(&transform.to_f32());
+        }
+        self.push_path(path);
+    }
+
+    pub fn push_clip_path(&mut self, clip_path: ClipPath) -> ClipPathId {
+        let clip_path_id = ClipPathId(self.clip_paths.len() as u32);
+        self.clip_paths.push(clip_path);
+        clip_path_id
+    }
+
+    /// Pushes a clip path whose outline is placed in the world by `transform`, as
+    /// `push_path_with_transform()` does for paths.
+    pub fn push_clip_path_with_transform(&mut self,
+                                         mut clip_path: ClipPath,
+                                         transform: &Transform2F64)
+                                         -> ClipPathId {
+        clip_path.outline.transform(&(self.view_transform * *transform).to_f32());
+        self.push_clip_path(clip_path)
+    }
+
+    /// Adds the paths and clip paths of `other` on top of this scene, placing them with
+    /// `transform`.
+    ///
+    /// Paints are copied into this scene's palette, so paint and clip path IDs in `other` need not
+    /// agree with the ones here. Gradients and patterns are transformed along with the paths. The
+    /// view transform is not applied, and 3D path transforms are copied unchanged.
+    pub fn append_scene(&mut self, other: &Scene, transform: &Transform2F) {
+        let mut paint_ids = Vec::with_capacity(other.palette.paints.len());
+        for (paint, &color_space) in other.palette.paints.iter().zip(&other.palette.color_spaces) {
+            let mut paint = (*paint).clone();
+            paint.apply_transform(transform);
+            paint_ids.push(self.push_paint_in_color_space(&paint, color_space));
+        }
+
+        let clip_path_id_offset = self.clip_paths.len() as u32;
+        for clip_path in &other.clip_paths {
+            let mut clip_path = (*clip_path).clone();
+            clip_path.outline.transform(transform);
+            self.push_clip_path(clip_path);
+        }
+
+        for path in &other.paths {
+            let mut path = (*path).clone();
+            if path.is_instance() {
+                path.outline_transform = *transform * path.outline_transform;
+            } else {
+                path.transform_outline(transform);
+            }
+            path.paint = paint_ids[path.paint.0 as usize];
+            path.clip_path = path.clip_path.map(|clip_path_id| {
+                ClipPathId(clip_path_id.0 + clip_path_id_offset)
+            });
+            self.push_path(path);
+        }
+    }
+
     #[inline]
-    pub fn build_paint_info(&self) -> PaintInfo {
-        self.palette.build_paint_info()
+    pub fn clip_path(&self, clip_path_id: ClipPathId) -> &ClipPath {
+        &self.clip_paths[clip_path_id.0 as usize]
+    }
+
+    /// Packs the paints into a texture. `render_transform` maps scene coordinates to device
+    /// pixels, `view_box` is the area of device space that can be drawn to, and `color_space` is
+    /// the color space of the destination. If `premultiplied_paints` is true, the colors of
+    /// patterns and gradients are taken to be premultiplied.
+    #[inline]
+    pub fn build_paint_info(&self,
+                            render_transform: &Transform2F,
+                            view_box: RectF,
+                            color_space: ColorSpace,
+                            premultiplied_paints: bool)
+                            -> Result<PaintInfo, SceneBuildError> {
+        self.palette.build_paint_info(render_transform,
+                                      view_box,
+                                      color_space,
+                                      premultiplied_paints)
     }
 
     #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -55,11 +158,23 @@ impl Scene {
         self.palette.push_paint(paint)
     }
 
+    /// Adds a paint whose colors are given in `color_space` rather than in sRGB.
+    pub fn push_paint_in_color_space(&mut self, paint: &Paint, color_space: ColorSpace)
+                                     -> PaintId {
+        self.palette.push_paint_in_color_space(paint, color_space)
+    }
+
     #[inline]
     pub fn path_count(&self) -> usize {
         self.paths.len()
     }
 
+    /// Returns the path at `index`, in drawing order.
+    #[inline]
+    pub fn path(&self, index: usize) -> &PathObject {
+        &self.paths[index]
+    }
+
     #[inline]
     pub fn bounds(&self) -> RectF {
         self.bounds
@@ -80,15 +195,45 @@ impl Scene {
         self.view_box = new_view_box;
     }
 
+    /// Returns the transform from world space to scene space that `push_path_with_transform()`
+    /// and `push_clip_path_with_transform()` apply.
+    #[inline]
+    pub fn view_transform(&self) -> Transform2F64 {
+        self.view_transform
+    }
+
+    /// Sets the transform from world space to scene space. For scenes with large coordinates, put
+    /// the pan and zoom here rather than in the build options, which are only single precision.
+    /// Paths that have already been pushed are not affected.
+    #[inline]
+    pub fn set_view_transform(&mut self, new_view_transform: Transform2F64) {
+        self.view_transform = new_view_transform;
+    }
+
+    /// Transforms and clips an outline for tiling. `path_transform` is the 3D transform of the
+    /// path that the outline belongs to, if it has one.
     pub(crate) fn apply_render_options(
         &self,
         original_outline: &Outline,
+        path_transform: Option<&Transform4F>,
         options: &PreparedBuildOptions,
     ) -> Outline {
         let effective_view_box = self.effective_view_box(options);
 
+        let path_render_transform;
+        let render_transform = match path_transform {
+            None => &options.transform,
+            Some(path_transform) => {
+                let perspective = options.path_perspective(path_transform, effective_view_box);
+                path_render_transform =
+                    PreparedRenderTransform::from_perspective(perspective,
+                                                              original_outline.bounds());
+                &path_render_transform
+            }
+        };
+
         let mut outline;
-        match options.transform {
+        match *render_transform {
             PreparedRenderTransform::Perspective {
                 ref perspective,
                 ref clip_polygon,
@@ -101,6 +246,10 @@ impl Scene {
                     outline.clip_against_polygon(clip_polygon);
                     outline.apply_perspective(perspective);
 
+                    // The frustum covers the window of the perspective transform, which need not
+                    // be the view box.
+                    outline.clip_against_rect(effective_view_box);
+
                     // TODO(pcwalton): Support subpixel AA in 3D.
                 }
             }
@@ -108,15 +257,7 @@ impl Scene {
                 // TODO(pcwalton): Short circuit.
                 outline = (*original_outline).clone();
                 if options.transform.is_2d() || options.subpixel_aa_enabled {
-                    let mut transform = match options.transform {
-                        PreparedRenderTransform::Transform2D(transform) => transform,
-                        PreparedRenderTransform::None => Transform2F::default(),
-                        PreparedRenderTransform::Perspective { .. } => unreachable!(),
-                    };
-                    if options.subpixel_aa_enabled {
-                        transform *= Transform2F::from_scale(Vector2F::new(3.0, 1.0))
-                    }
-                    outline.transform(&transform);
+                    outline.transform(&options.transform_2d());
                 }
                 outline.clip_against_rect(effective_view_box);
             }
@@ -148,7 +289,7 @@ impl Scene {
 
         match self.palette.paints[first_paint_id.0 as usize] {
             Paint::Color(color) => Some(color),
-            Paint::Gradient(_) => None,
+            Paint::Gradient(_) | Paint::Pattern(_) => None,
         }
     }
 
@@ -161,11 +302,19 @@ impl Scene {
         }
     }
 
+    /// Builds the scene into render commands and sends them to `listener`.
+    ///
+    /// The commands are the same for the same scene and options, whichever executor builds them
+    /// and however its threads are scheduled, so renders can be compared pixel for pixel. Only
+    /// the build time in `RenderCommand::Finish` varies.
+    ///
+    /// If the scene can't be built, no commands are sent at all.
     #[inline]
     pub fn build<E, L: RenderCommandListener>(&self,
                     options: BuildOptions,
                     listener: L,
                     executor: &E)
+                    -> Result<(), SceneBuildError>
                     where E: Executor {
         let prepared_options = options.prepare(self.bounds);
         SceneBuilder::new(self, &prepared_options, listener).build(executor)
@@ -179,19 +328,47 @@ impl Scene {
     }
 }
 
+/// Why a scene couldn't be built.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SceneBuildError {
+    /// The paint texture has no room left for solid colors.
+    PaintTextureFull,
+    /// The scene has more paths than object indices can number.
+    TooManyPaths { path_count: usize },
+}
+
+impl Display for SceneBuildError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            SceneBuildError::PaintTextureFull => {
+                write!(formatter, "no room for solid colors in the paint texture")
+            }
+            SceneBuildError::TooManyPaths { path_count } => {
+                write!(formatter,
+                       "{} paths is more than the maximum of {}",
+                       path_count,
+                       MAX_PATH_COUNT)
+            }
+        }
+    }
+}
+
+impl Error for SceneBuildError {}
+
 pub struct PathIter<'a> {
     scene: &'a Scene,
     pos: usize
 }
 
 impl<'a> Iterator for PathIter<'a> {
-    type Item = (&'a Paint, &'a Outline, &'a str);
+    type Item = (&'a Paint, Cow<'a, Outline>, &'a str, FillRule);
     fn next(&mut self) -> Option<Self::Item> {
         let item = self.scene.paths.get(self.pos).map(|path_object| {
             (
                 self.scene.palette.paints.get(path_object.paint.0 as usize).unwrap(),
-                &path_object.outline,
-                &*path_object.name
+                path_object.transformed_outline(),
+                &*path_object.name,
+                path_object.fill_rule
             )
         });
         self.pos += 1;
@@ -201,24 +378,364 @@ impl<'a> Iterator for PathIter<'a> {
 
 #[derive(Clone, Debug)]
 pub struct PathObject {
-    outline: Outline,
+    outline: Arc<Outline>,
+    outline_transform: Transform2F,
     paint: PaintId,
+    blend_mode: BlendMode,
+    fill_rule: FillRule,
+    backdrop_filter: Option<BackdropFilter>,
+    clip_path: Option<ClipPathId>,
+    transform_3d: Option<Transform4F>,
+    min_zoom: f32,
+    max_zoom: f32,
+    z_index: i32,
+    // The shape of the outline, if it's known to be a rounded rectangle, in the same space.
+    rounded_rect: Option<RoundedRect>,
     name: String,
 }
 
 impl PathObject {
     #[inline]
     pub fn new(outline: Outline, paint: PaintId, name: String) -> PathObject {
-        PathObject { outline, paint, name }
+        PathObject::new_instance(Arc::new(outline), Transform2F::default(), paint, name)
+    }
+
+    /// Creates a path that draws `outline` under `transform`, without copying it.
+    ///
+    /// This is for drawing one outline many times, such as markers or the points of a scatter
+    /// plot. When instances are drawn with similarity transforms at the same scale, their curves
+    /// are flattened once for all of them.
+    #[inline]
+    pub fn new_instance(outline: Arc<Outline>,
+                        transform: Transform2F,
+                        paint: PaintId,
+                        name: String)
+                        -> PathObject {
+        PathObject {
+            outline,
+            outline_transform: transform,
+            paint,
+            blend_mode: BlendMode::default(),
+            fill_rule: FillRule::Winding,
+            backdrop_filter: None,
+            clip_path: None,
+            transform_3d: None,
+            min_zoom: 0.0,
+            max_zoom: f32::INFINITY,
+            z_index: 0,
+            rounded_rect: None,
+            name,
+        }
     }
 
+    /// Creates a path for a rectangle with rounded corners.
+    ///
+    /// When the path is drawn with a transform that only scales and translates, and without
+    /// clipping, 3D transforms, dilation, or subpixel AA, the scene builder flattens its corners
+    /// directly instead of going through the general curve machinery.
+    #[inline]
+    pub fn from_rounded_rect(rounded_rect: RoundedRect, paint: PaintId, name: String)
+                             -> PathObject {
+        let mut path_object = PathObject::new(rounded_rect.to_outline(), paint, name);
+        path_object.rounded_rect = Some(rounded_rect);
+        path_object
+    }
+
+    /// The outline of this path, before `outline_transform()` is applied.
     #[inline]
     pub fn outline(&self) -> &Outline {
         &self.outline
     }
 
+    /// The transform that places the outline in the scene. This is the identity except for
+    /// paths created with `PathObject::new_instance()`.
+    #[inline]
+    pub fn outline_transform(&self) -> Transform2F {
+        self.outline_transform
+    }
+
+    /// The outline of this path as it's placed in the scene.
+    pub fn transformed_outline(&self) -> Cow<Outline> {
+        if self.outline_transform.is_identity() {
+            return Cow::Borrowed(&self.outline);
+        }
+        let mut outline = (*self.outline).clone();
+        outline.transform(&self.outline_transform);
+        Cow::Owned(outline)
+    }
+
+    /// The bounds of the outline as it's placed in the scene.
+    pub fn bounds(&self) -> RectF {
+        let (transform, bounds) = (self.outline_transform, self.outline.bounds());
+        if transform.is_identity() {
+            return bounds;
+        }
+        RectF::from_points(transform * bounds.origin(), transform * bounds.origin())
+            .union_point(transform * bounds.upper_right())
+            .union_point(transform * bounds.lower_right())
+            .union_point(transform * bounds.lower_left())
+    }
+
+    /// The shape of this path, before `outline_transform()` is applied, if it was created with
+    /// `PathObject::from_rounded_rect()`.
+    #[inline]
+    pub fn rounded_rect(&self) -> Option<&RoundedRect> {
+        self.rounded_rect.as_ref()
+    }
+
+    // Transforms the outline in place, copying it first if it's shared.
+    fn transform_outline(&mut self, transform: &Transform2F) {
+        Arc::make_mut(&mut self.outline).transform(transform);
+        self.rounded_rect = self.rounded_rect.and_then(|rounded_rect| {
+            rounded_rect.transform(transform)
+        });
+    }
+
+    #[inline]
+    pub(crate) fn shared_outline(&self) -> &Arc<Outline> {
+        &self.outline
+    }
+
+    #[inline]
+    pub(crate) fn is_instance(&self) -> bool {
+        !self.outline_transform.is_identity() || Arc::strong_count(&self.outline) > 1
+    }
+
     #[inline]
     pub(crate) fn paint(&self) -> PaintId {
         self.paint
     }
+
+    #[inline]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    #[inline]
+    pub fn set_blend_mode(&mut self, new_blend_mode: BlendMode) {
+        self.blend_mode = new_blend_mode;
+    }
+
+    #[inline]
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    /// Sets the rule that decides which points are inside the outline where its contours overlap.
+    /// The default is `FillRule::Winding`.
+    #[inline]
+    pub fn set_fill_rule(&mut self, new_fill_rule: FillRule) {
+        self.fill_rule = new_fill_rule;
+    }
+
+    #[inline]
+    pub fn backdrop_filter(&self) -> Option<BackdropFilter> {
+        self.backdrop_filter
+    }
+
+    /// Sets the filter applied to what's beneath the path, inside the path and its clip path,
+    /// before the path is drawn.
+    ///
+    /// Renderers with postprocessing don't apply backdrop filters.
+    #[inline]
+    pub fn set_backdrop_filter(&mut self, new_backdrop_filter: Option<BackdropFilter>) {
+        self.backdrop_filter = new_backdrop_filter;
+    }
+
+    #[inline]
+    pub fn clip_path(&self) -> Option<ClipPathId> {
+        self.clip_path
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the clip path of the scene that this path is drawn within, if any.
+    #[inline]
+    pub fn set_clip_path(&mut self, new_clip_path: Option<ClipPathId>) {
+        self.clip_path = new_clip_path;
+    }
+
+    #[inline]
+    pub fn transform_3d(&self) -> Option<&Transform4F> {
+        self.transform_3d.as_ref()
+    }
+
+    /// Places this path on a plane in 3D, for example a tilted panel in a 3D UI.
+    ///
+    /// In scenes built with a perspective transform, this maps the path into the space that the
+    /// perspective transform applies to. Otherwise it maps the path into scene coordinates with a
+    /// perspective division, and ordinary 2D transforms apply afterward. The clip path, if any,
+    /// applies before this transform, in the coordinates of the path.
+    ///
+    /// FIXME: Paints that depend on position are drawn incorrectly, as they are for
+    /// scenes with perspective transforms.
+    #[inline]
+    pub fn set_transform_3d(&mut self, new_transform_3d: Option<Transform4F>) {
+        self.transform_3d = new_transform_3d;
+    }
+
+    /// The range of zoom levels, `min..max`, at which this path is drawn.
+    #[inline]
+    pub fn zoom_range(&self) -> (f32, f32) {
+        (self.min_zoom, self.max_zoom)
+    }
+
+    /// Draws this path only when the scene is scaled by at least `min_zoom` and less than
+    /// `max_zoom`, for level of detail in zoomable content such as maps.
+    ///
+    /// The zoom is the square root of the factor by which the build transform scales areas. Zoom
+    /// ranges only apply to paths drawn with 2D transforms and no 3D transform of their own.
+    #[inline]
+    pub fn set_zoom_range(&mut self, min_zoom: f32, max_zoom: f32) {
+        self.min_zoom = min_zoom;
+        self.max_zoom = max_zoom;
+    }
+
+    #[inline]
+    pub fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    /// Draws this path above the paths with lower z-indices and below those with higher ones,
+    /// regardless of the order they were pushed in.
+    ///
+    /// Paths with the same z-index, including the default of 0, are drawn in the order they were
+    /// pushed, so scenes that don't set z-indices draw as they always have.
+    #[inline]
+    pub fn set_z_index(&mut self, new_z_index: i32) {
+        self.z_index = new_z_index;
+    }
+
+    // Whether level of detail hides this path entirely, so that it needn't be tiled.
+    pub(crate) fn is_culled_by_lod(&self, options: &PreparedBuildOptions) -> bool {
+        let zoom = match options.zoom() {
+            Some(zoom) if self.transform_3d.is_none() => zoom,
+            _ => return false,
+        };
+        if zoom < self.min_zoom || zoom >= self.max_zoom {
+            return true;
+        }
+        let size = self.bounds().size().scale(zoom);
+        f32::max(size.x(), size.y()) < options.lod_tolerance
+    }
+}
+
+/// An outline that limits the area that the paths using it are drawn in.
+#[derive(Clone, Debug)]
+pub struct ClipPath {
+    outline: Outline,
+    fill_rule: FillRule,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ClipPathId(pub u32);
+
+impl ClipPath {
+    #[inline]
+    pub fn new(outline: Outline, fill_rule: FillRule) -> ClipPath {
+        ClipPath { outline, fill_rule }
+    }
+
+    #[inline]
+    pub fn outline(&self) -> &Outline {
+        &self.outline
+    }
+
+    #[inline]
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    /// Returns the part of `outline`, which is filled with the nonzero winding rule, that lies
+    /// within this clip path.
+    #[inline]
+    pub fn clip(&self, outline: &Outline) -> Outline {
+        self.clip_with_fill_rule(outline, FillRule::Winding)
+    }
+
+    /// Like `clip()`, but for an outline filled with `fill_rule`. The result is filled with the
+    /// nonzero winding rule.
+    pub fn clip_with_fill_rule(&self, outline: &Outline, fill_rule: FillRule) -> Outline {
+        outline.boolean_op_with_fill_rules(fill_rule,
+                                           &self.outline,
+                                           self.fill_rule,
+                                           BooleanOp::Intersection)
+    }
+
+    // Clips `outline`, filled with `fill_rule`, for tiling, and returns the fill rule that the
+    // result is to be filled with.
+    //
+    // Clipping to a single convex polygon keeps the winding number of every point inside it, so
+    // that case is handled by the much cheaper polygon clipper, which also keeps the fill rule.
+    // Anything else takes a full boolean intersection.
+    pub(crate) fn clip_for_tiling(&self, outline: &Outline, fill_rule: FillRule)
+                                  -> (Outline, FillRule) {
+        match convex_polygon(&self.outline) {
+            Some(clip_polygon) => {
+                let mut outline = outline.clone();
+                outline.clip_against_polygon(&clip_polygon);
+                (outline, fill_rule)
+            }
+            None => (self.clip_with_fill_rule(outline, fill_rule), FillRule::Winding),
+        }
+    }
+}
+
+// Returns the vertices of `outline`, oriented as `Outline::clip_against_polygon()` expects, if it
+// consists of a single convex polygon.
+fn convex_polygon(outline: &Outline) -> Option<Vec<Vector2F>> {
+    let contour = match outline.contours() {
+        [contour] => contour,
+        _ => return None,
+    };
+
+    let mut points: Vec<Vector2F> = Vec::with_capacity(contour.len() as usize);
+    for point_index in 0..contour.len() {
+        if !contour.point_is_endpoint(point_index) {
+            return None;
+        }
+        let point = contour.position_of(point_index);
+        if points.last() != Some(&point) {
+            points.push(point);
+        }
+    }
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 3 {
+        return None;
+    }
+
+    // Every turn must go the same way, and the turns must add up to a single revolution, which
+    // rules out self-intersecting polygons such as pentagrams.
+    let (mut turn_sign, mut total_angle) = (0.0, 0.0);
+    for point_index in 0..points.len() {
+        let prev = points[(point_index + points.len() - 1) % points.len()];
+        let (point, next) = (points[point_index], points[(point_index + 1) % points.len()]);
+        let (vector_in, vector_out) = (point - prev, next - point);
+        let (turn, dot) = (vector_in.det(vector_out), vector_in.dot(vector_out));
+        if turn == 0.0 {
+            if dot < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        if turn_sign == 0.0 {
+            turn_sign = turn.signum();
+        } else if turn.signum() != turn_sign {
+            return None;
+        }
+        total_angle += f32::atan2(turn, dot).abs();
+    }
+    if turn_sign == 0.0 || (total_angle - 2.0 * PI).abs() > 0.01 {
+        return None;
+    }
+
+    if turn_sign < 0.0 {
+        points.reverse();
+    }
+    Some(points)
 }
@@ -10,6 +10,7 @@
 
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_geometry::rect::RectI;
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub struct DenseTileMap<T> {
@@ -63,3 +64,28 @@ impl<T> DenseTileMap<T> {
         self.rect.origin() + Vector2I::new(index % width, index / width)
     }
 }
+
+/// A tile map that only stores the tiles that have been set, for tile rects too large to allocate
+/// storage for every tile.
+#[derive(Debug)]
+pub struct SparseTileMap<T> {
+    pub data: HashMap<(i32, i32), T>,
+}
+
+impl<T> SparseTileMap<T> {
+    #[inline]
+    pub fn new() -> SparseTileMap<T> {
+        SparseTileMap { data: HashMap::new() }
+    }
+
+    #[inline]
+    pub fn get(&self, coords: Vector2I) -> Option<&T> {
+        self.data.get(&(coords.x(), coords.y()))
+    }
+
+    /// Returns the value of the tile at `coords`, inserting `T::default()` if it isn't set.
+    #[inline]
+    pub fn get_or_insert_default(&mut self, coords: Vector2I) -> &mut T where T: Default {
+        self.data.entry((coords.x(), coords.y())).or_default()
+    }
+}
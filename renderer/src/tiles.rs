@@ -20,18 +20,23 @@ use pathfinder_geometry::rect::{RectF, RectI};
 use crate::options::RenderCommandListener;
 use crate::paint::PaintMetadata;
 
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::{Contour, Outline, PointIndex};
 use pathfinder_content::segment::Segment;
 use pathfinder_content::sorted_vector::SortedVector;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
-use pathfinder_geometry::transform2d::Transform2I;
+use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use std::cmp::Ordering;
 use std::mem;
 
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use pathfinder_simd::x86::avx::{self, F32x8};
+
 // TODO(pcwalton): Make this configurable.
-const FLATTENING_TOLERANCE: f32 = 0.1;
+pub(crate) const FLATTENING_TOLERANCE: f32 = 0.1;
 
 pub const TILE_WIDTH: u32 = 16;
 pub const TILE_HEIGHT: u32 = 16;
@@ -41,7 +46,12 @@ pub(crate) struct Tiler<'a, L: RenderCommandListener> {
     outline: &'a Outline,
     pub built_object: BuiltObject,
     paint_metadata: &'a PaintMetadata,
+    blend_mode: BlendMode,
+    fill_rule: FillRule,
     object_index: u16,
+    /// Whether the tiles this path covers completely go into the Z-buffer. This is false for paths
+    /// that are clipped after tiling, which may not cover them after all.
+    pub(crate) can_occlude: bool,
 
     point_queue: SortedVector<QueuedEndpoint>,
     active_edges: SortedVector<ActiveEdge>,
@@ -56,11 +66,15 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
         view_box: RectF,
         object_index: u16,
         paint_metadata: &'a PaintMetadata,
+        blend_mode: BlendMode,
+        fill_rule: FillRule,
     ) -> Tiler<'a, L> {
-        let bounds = outline
-            .bounds()
-            .intersection(view_box)
-            .unwrap_or(RectF::default());
+        // Destructive blend modes affect the whole view box, not just the path.
+        let bounds = if blend_mode.is_destructive() {
+            view_box
+        } else {
+            outline.bounds().intersection(view_box).unwrap_or(RectF::default())
+        };
         let built_object = BuiltObject::new(bounds);
 
         Tiler {
@@ -69,6 +83,9 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
             built_object,
             object_index,
             paint_metadata,
+            blend_mode,
+            fill_rule,
+            can_occlude: paint_metadata.is_opaque && blend_mode.occludes_backdrop(),
 
             point_queue: SortedVector::new(),
             active_edges: SortedVector::new(),
@@ -123,13 +140,13 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
                 .local_tile_index_to_coords(tile_index as u32);
 
             if tile.is_solid() {
-                // Blank tiles are always skipped.
-                if tile.backdrop == 0 {
-                    continue;
-                }
-
-                // If this is a solid tile, poke it into the Z-buffer and stop here.
-                if self.paint_metadata.is_opaque {
+                // Blank tiles are skipped, unless the blend mode clears what's outside the path.
+                if !self.backdrop_is_inside(tile.backdrop) {
+                    if !self.blend_mode.is_destructive() {
+                        continue;
+                    }
+                } else if self.can_occlude {
+                    // If this is a solid tile, poke it into the Z-buffer and stop here.
                     self.builder.z_buffer.update(tile_coords, self.object_index);
                     continue;
                 }
@@ -140,13 +157,34 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
                 tile.backdrop,
                 self.object_index,
                 tile.alpha_tile_index as u16,
-                self.paint_metadata.tex_transform,
+                &self.paint_metadata.tex_transform,
             );
 
             self.built_object.alpha_tiles.push(alpha_tile);
         }
     }
 
+    // The tiles that this path draws to, whether or not they went into the Z-buffer.
+    pub(crate) fn touched_tiles(&self) -> Vec<Vector2I> {
+        let mut tiles = vec![];
+        for (tile_index, tile) in self.built_object.tiles.data.iter().enumerate() {
+            if tile.is_solid() && !self.backdrop_is_inside(tile.backdrop) &&
+                    !self.blend_mode.is_destructive() {
+                continue;
+            }
+            tiles.push(self.built_object.local_tile_index_to_coords(tile_index as u32));
+        }
+        tiles
+    }
+
+    // Whether a tile that no edge crosses, with this winding number, is inside the path.
+    fn backdrop_is_inside(&self, backdrop: i8) -> bool {
+        match self.fill_rule {
+            FillRule::Winding => backdrop != 0,
+            FillRule::EvenOdd => backdrop % 2 != 0,
+        }
+    }
+
     fn process_old_active_edges(&mut self, tile_y: i32) {
         let mut current_tile_x = self.built_object.tile_rect().min_x();
         let mut current_subtile_x = 0.0;
@@ -201,7 +239,6 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
                 let tile_right_x = ((i32::from(current_tile_x) + 1) * TILE_WIDTH as i32) as f32;
                 let current_tile_coords = Vector2I::new(current_tile_x, tile_y);
                 self.built_object.add_active_fill(
-                    self.builder,
                     current_x,
                     tile_right_x,
                     current_winding,
@@ -240,7 +277,6 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
                     (i32::from(current_tile_x) * TILE_WIDTH as i32) as f32 + current_subtile_x;
                 let current_tile_coords = Vector2I::new(current_tile_x, tile_y);
                 self.built_object.add_active_fill(
-                    self.builder,
                     current_x,
                     segment_x,
                     current_winding,
@@ -255,7 +291,7 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
             // Process the edge.
             debug!("about to process existing active edge {:#?}", active_edge);
             debug_assert!(f32::abs(active_edge.crossing.y() - tile_top) < 0.1);
-            active_edge.process(self.builder, &mut self.built_object, tile_y);
+            active_edge.process(&mut self.built_object, tile_y);
             if !active_edge.segment.is_none() {
                 self.active_edges.push(active_edge);
             }
@@ -291,7 +327,6 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
                 contour,
                 prev_endpoint_index,
                 &mut self.active_edges,
-                self.builder,
                 &mut self.built_object,
                 tile_y,
             );
@@ -315,7 +350,6 @@ impl<'a, L: RenderCommandListener> Tiler<'a, L> {
                 contour,
                 point_index.point(),
                 &mut self.active_edges,
-                self.builder,
                 &mut self.built_object,
                 tile_y,
             );
@@ -368,17 +402,16 @@ pub fn round_rect_out_to_tile_bounds(rect: RectF) -> RectI {
     .to_i32()
 }
 
-fn process_active_segment<L: RenderCommandListener>(
+fn process_active_segment(
     contour: &Contour,
     from_endpoint_index: u32,
     active_edges: &mut SortedVector<ActiveEdge>,
-    builder: &SceneBuilder<L>,
     built_object: &mut BuiltObject,
     tile_y: i32,
 ) {
     let mut active_edge = ActiveEdge::from_segment(&contour.segment_after(from_endpoint_index));
     debug!("... process_active_segment({:#?})", active_edge);
-    active_edge.process(builder, built_object, tile_y);
+    active_edge.process(built_object, tile_y);
     if !active_edge.segment.is_none() {
         debug!("... ... pushing resulting active edge: {:#?}", active_edge);
         active_edges.push(active_edge);
@@ -425,7 +458,7 @@ impl ActiveEdge {
         ActiveEdge { segment: *segment, crossing }
     }
 
-    fn process<L: RenderCommandListener>(&mut self, builder: &SceneBuilder<L>, built_object: &mut BuiltObject, tile_y: i32) {
+    fn process(&mut self, built_object: &mut BuiltObject, tile_y: i32) {
         let tile_bottom = ((i32::from(tile_y) + 1) * TILE_HEIGHT as i32) as f32;
         debug!(
             "process_active_edge({:#?}, tile_y={}({}))",
@@ -438,7 +471,7 @@ impl ActiveEdge {
         if segment.is_line() {
             let line_segment = segment.as_line_segment();
             self.segment =
-                match self.process_line_segment(line_segment, builder, built_object, tile_y) {
+                match self.process_line_segment(line_segment, built_object, tile_y) {
                     Some(lower_part) => Segment::line(lower_part),
                     None => Segment::none(),
                 };
@@ -455,7 +488,7 @@ impl ActiveEdge {
             let first_line_segment =
                 LineSegment2F::new(self.crossing, segment.baseline.upper_point()).orient(winding);
             if self
-                .process_line_segment(first_line_segment, builder, built_object, tile_y)
+                .process_line_segment(first_line_segment, built_object, tile_y)
                 .is_some()
             {
                 return;
@@ -464,20 +497,13 @@ impl ActiveEdge {
 
         let mut oriented_segment = segment.orient(winding);
         loop {
-            let mut split_t = 1.0;
-            let mut before_segment = oriented_segment;
-            let mut after_segment = None;
-
-            while !before_segment
-                .as_cubic_segment()
-                .is_flat(FLATTENING_TOLERANCE)
-            {
-                let next_t = 0.5 * split_t;
-                let (before, after) = oriented_segment.as_cubic_segment().split(next_t);
-                before_segment = before;
-                after_segment = Some(after);
-                split_t = next_t;
-            }
+            let split_t = flat_split_t(&oriented_segment);
+            let (before_segment, after_segment) = if split_t == 1.0 {
+                (oriented_segment, None)
+            } else {
+                let (before, after) = oriented_segment.as_cubic_segment().split(split_t);
+                (before, Some(after))
+            };
 
             debug!(
                 "... tile_y={} winding={} segment={:?} t={} before_segment={:?}
@@ -486,7 +512,7 @@ impl ActiveEdge {
             );
 
             let line = before_segment.baseline.orient(winding);
-            match self.process_line_segment(line, builder, built_object, tile_y) {
+            match self.process_line_segment(line, built_object, tile_y) {
                 Some(lower_part) if split_t == 1.0 => {
                     self.segment = Segment::line(lower_part);
                     return;
@@ -504,10 +530,9 @@ impl ActiveEdge {
         }
     }
 
-    fn process_line_segment<L: RenderCommandListener>(
+    fn process_line_segment(
         &mut self,
         line_segment: LineSegment2F,
-        builder: &SceneBuilder<L>,
         built_object: &mut BuiltObject,
         tile_y: i32,
     ) -> Option<LineSegment2F> {
@@ -518,12 +543,12 @@ impl ActiveEdge {
         );
 
         if line_segment.max_y() <= tile_bottom {
-            built_object.generate_fill_primitives_for_line(builder, line_segment, tile_y);
+            built_object.generate_fill_primitives_for_line(line_segment, tile_y);
             return None;
         }
 
         let (upper_part, lower_part) = line_segment.split_at_y(tile_bottom);
-        built_object.generate_fill_primitives_for_line(builder, upper_part, tile_y);
+        built_object.generate_fill_primitives_for_line(upper_part, tile_y);
         self.crossing = lower_part.upper_point();
         Some(lower_part)
     }
@@ -535,14 +560,84 @@ impl PartialOrd<ActiveEdge> for ActiveEdge {
     }
 }
 
+// Returns the largest of 1, 1/2, 1/4, and so on for which the part of the cubic `segment` before
+// that parameter is flat.
+fn flat_split_t(segment: &Segment) -> f32 {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if avx::is_supported() {
+            return unsafe { flat_split_t_avx2(segment) };
+        }
+    }
+
+    let mut split_t = 1.0;
+    let mut before_segment = *segment;
+    while !before_segment.as_cubic_segment().is_flat(FLATTENING_TOLERANCE) {
+        split_t *= 0.5;
+        before_segment = segment.as_cubic_segment().split(split_t).0;
+    }
+    split_t
+}
+
+// Like `flat_split_t()`, but tries two parameters at a time, with one in each half of the vectors.
+// This is the loop of `CubicSegment::split()` followed by `CubicSegment::is_flat()`.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn flat_split_t_avx2(segment: &Segment) -> f32 {
+    let (p0, p3) = (segment.baseline.from(), segment.baseline.to());
+    let (p1, p2) = (segment.ctrl.from(), segment.ctrl.to());
+    let p0p3 = F32x8::new(p0.x(), p0.y(), p3.x(), p3.y(), p0.x(), p0.y(), p3.x(), p3.y());
+    let p1p2 = F32x8::new(p1.x(), p1.y(), p2.x(), p2.y(), p1.x(), p1.y(), p2.x(), p2.y());
+    let p0p1 = p0p3.concat_xy_xy(p1p2);
+    let tolerance = 16.0 * FLATTENING_TOLERANCE * FLATTENING_TOLERANCE;
+
+    let mut split_t = 1.0;
+    loop {
+        let half_t = 0.5 * split_t;
+        let tttt = F32x8::new(split_t, split_t, split_t, split_t, half_t, half_t, half_t, half_t);
+
+        let p01p12 = p0p1 + tttt * (p1p2 - p0p1);
+        let pxxp23 = p1p2 + tttt * (p0p3 - p1p2);
+        let p12p23 = p01p12.concat_zw_zw(pxxp23);
+        let p012p123 = p01p12 + tttt * (p12p23 - p01p12);
+        let p123 = p012p123.zwzw();
+        let p0123 = p012p123 + tttt * (p123 - p012p123);
+
+        let baseline = p0p3.concat_xy_xy(p0123);
+        let ctrl = p01p12.concat_xy_xy(p012p123);
+        let mut uv = F32x8::splat(3.0) * ctrl - baseline - baseline - baseline.zwxy();
+        uv = uv * uv;
+        uv = uv.max(uv.zwxy());
+
+        // Splitting at 1 reproduces the whole segment, up to rounding.
+        if uv[0] + uv[1] <= tolerance {
+            return split_t;
+        }
+        if uv[4] + uv[5] <= tolerance {
+            return half_t;
+        }
+        split_t *= 0.25;
+    }
+}
+
+/// Moves the origin of a paint's texture transform to the upper left corner of a tile, since the
+/// shaders apply it to positions within the tile.
+pub(crate) fn tile_tex_transform(tex_transform: &Transform2F, tile_coords: Vector2I)
+                                 -> Transform2F {
+    let tile_origin = Vector2F::new((tile_coords.x() * TILE_WIDTH as i32) as f32,
+                                    (tile_coords.y() * TILE_HEIGHT as i32) as f32);
+    Transform2F { matrix: tex_transform.matrix, vector: *tex_transform * tile_origin }
+}
+
 impl AlphaTileBatchPrimitive {
     #[inline]
-    fn new(tile_coords: Vector2I,
+    pub(crate) fn new(tile_coords: Vector2I,
            backdrop: i8,
            object_index: u16,
            tile_index: u16,
-           tex_transform: Transform2I)
+           tex_transform: &Transform2F)
            -> AlphaTileBatchPrimitive {
+        let tex_transform = tile_tex_transform(tex_transform, tile_coords);
         AlphaTileBatchPrimitive {
             tile_x_lo: (tile_coords.x() & 0xff) as u8,
             tile_y_lo: (tile_coords.y() & 0xff) as u8,
@@ -550,12 +645,12 @@ impl AlphaTileBatchPrimitive {
             backdrop,
             object_index,
             tile_index,
-            texture_m00: tex_transform.matrix.m11() as u16,
-            texture_m10: tex_transform.matrix.m21() as u16,
-            texture_m01: tex_transform.matrix.m12() as u16,
-            texture_m11: tex_transform.matrix.m22() as u16,
-            texture_m02: tex_transform.vector.x() as u16,
-            texture_m12: tex_transform.vector.y() as u16,
+            texture_m00: tex_transform.matrix.m11(),
+            texture_m10: tex_transform.matrix.m21(),
+            texture_m01: tex_transform.matrix.m12(),
+            texture_m11: tex_transform.matrix.m22(),
+            texture_m02: tex_transform.vector.x(),
+            texture_m12: tex_transform.vector.y(),
         }
     }
 
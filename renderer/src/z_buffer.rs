@@ -13,38 +13,92 @@
 use crate::gpu_data::SolidTileBatchPrimitive;
 use crate::paint::PaintMetadata;
 use crate::scene::PathObject;
-use crate::tile_map::DenseTileMap;
+use crate::tile_map::{DenseTileMap, SparseTileMap};
 use crate::tiles;
 use pathfinder_geometry::rect::RectF;
-use pathfinder_geometry::transform2d::Transform2I;
+use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2I;
 use std::ops::Range;
+use std::sync::Mutex;
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
+// View boxes with more tiles than this get a sparse Z-buffer, since a dense one would take 8 bytes
+// per tile whether or not anything covers it.
+const MAX_DENSE_TILE_COUNT: usize = 1 << 20;
+
+// A sparse Z-buffer has a lock for each tile row, so that threads tiling different rows don't wait
+// for each other. Past this many rows, rows share locks.
+const MAX_SPARSE_ROW_LOCK_COUNT: usize = 4096;
+
 pub struct ZBuffer {
-    buffer: DenseTileMap<AtomicUsize>,
+    buffer: ZBufferTiles,
+}
+
+// The depth of each tile is one more than the index of the frontmost object that covers it
+// opaquely, or zero if none does.
+enum ZBufferTiles {
+    Dense(DenseTileMap<AtomicUsize>),
+    Sparse(SparseZBufferRows),
+}
+
+struct SparseZBufferRows {
+    rows: Vec<Mutex<SparseTileMap<usize>>>,
+    min_y: i32,
+}
+
+impl SparseZBufferRows {
+    #[inline]
+    fn row(&self, coords: Vector2I) -> &Mutex<SparseTileMap<usize>> {
+        let row_index = (coords.y() - self.min_y) as usize % self.rows.len();
+        &self.rows[row_index]
+    }
 }
 
 impl ZBuffer {
     pub fn new(view_box: RectF) -> ZBuffer {
         let tile_rect = tiles::round_rect_out_to_tile_bounds(view_box);
-        ZBuffer {
-            buffer: DenseTileMap::from_builder(|_| AtomicUsize::new(0), tile_rect),
-        }
+        let tile_count = tile_rect.size().x() as usize * tile_rect.size().y() as usize;
+        let buffer = if tile_count > MAX_DENSE_TILE_COUNT {
+            let row_count = usize::min(tile_rect.size().y() as usize, MAX_SPARSE_ROW_LOCK_COUNT);
+            ZBufferTiles::Sparse(SparseZBufferRows {
+                rows: (0..row_count).map(|_| Mutex::new(SparseTileMap::new())).collect(),
+                min_y: tile_rect.min_y(),
+            })
+        } else {
+            ZBufferTiles::Dense(DenseTileMap::from_builder(|_| AtomicUsize::new(0), tile_rect))
+        };
+        ZBuffer { buffer }
     }
 
     pub fn test(&self, coords: Vector2I, object_index: u32) -> bool {
-        let tile_index = self.buffer.coords_to_index_unchecked(coords);
-        let existing_depth = self.buffer.data[tile_index as usize].load(AtomicOrdering::SeqCst);
+        let existing_depth = match self.buffer {
+            ZBufferTiles::Dense(ref buffer) => {
+                let tile_index = buffer.coords_to_index_unchecked(coords);
+                buffer.data[tile_index as usize].load(AtomicOrdering::SeqCst)
+            }
+            ZBufferTiles::Sparse(ref buffer) => {
+                buffer.row(coords).lock().unwrap().get(coords).cloned().unwrap_or(0)
+            }
+        };
         existing_depth < object_index as usize + 1
     }
 
     pub fn update(&self, coords: Vector2I, object_index: u16) {
-        let tile_index = self.buffer.coords_to_index_unchecked(coords);
-        let mut old_depth = self.buffer.data[tile_index].load(AtomicOrdering::SeqCst);
         let new_depth = (object_index + 1) as usize;
+        let buffer = match self.buffer {
+            ZBufferTiles::Dense(ref buffer) => buffer,
+            ZBufferTiles::Sparse(ref buffer) => {
+                let mut row = buffer.row(coords).lock().unwrap();
+                let depth = row.get_or_insert_default(coords);
+                *depth = usize::max(*depth, new_depth);
+                return;
+            }
+        };
+
+        let tile_index = buffer.coords_to_index_unchecked(coords);
+        let mut old_depth = buffer.data[tile_index].load(AtomicOrdering::SeqCst);
         while old_depth < new_depth {
-            let prev_depth = self.buffer.data[tile_index].compare_and_swap(
+            let prev_depth = buffer.data[tile_index].compare_and_swap(
                 old_depth,
                 new_depth,
                 AtomicOrdering::SeqCst,
@@ -58,29 +112,47 @@ impl ZBuffer {
     }
 
     pub fn build_solid_tiles(&self,
-                             paths: &[PathObject],
+                             paths: &[&PathObject],
                              paint_metadata: &[PaintMetadata],
                              object_range: Range<u32>)
                              -> Vec<SolidTileBatchPrimitive> {
         let mut solid_tiles = vec![];
-        for tile_index in 0..self.buffer.data.len() {
-            let depth = self.buffer.data[tile_index].load(AtomicOrdering::Relaxed);
+        let mut push_solid_tile = |tile_coords: Vector2I, depth: usize| {
             if depth == 0 {
-                continue;
+                return;
             }
-
-            let tile_coords = self.buffer.index_to_coords(tile_index);
             let object_index = (depth - 1) as u32;
             if object_index < object_range.start || object_index >= object_range.end {
-                continue;
+                return;
             }
 
             let paint_id = paths[object_index as usize].paint();
-            let tex_transform = paint_metadata[paint_id.0 as usize].tex_transform;
-
-            solid_tiles.push(SolidTileBatchPrimitive::new(tile_coords + self.buffer.rect.origin(),
+            let tex_transform = &paint_metadata[paint_id.0 as usize].tex_transform;
+            solid_tiles.push(SolidTileBatchPrimitive::new(tile_coords,
                                                           object_index as u16,
                                                           tex_transform));
+        };
+
+        match self.buffer {
+            ZBufferTiles::Dense(ref buffer) => {
+                for (tile_index, depth) in buffer.data.iter().enumerate() {
+                    push_solid_tile(buffer.index_to_coords(tile_index),
+                                    depth.load(AtomicOrdering::Relaxed));
+                }
+            }
+            ZBufferTiles::Sparse(ref buffer) => {
+                // Emit the tiles in row-major order, as a dense Z-buffer does.
+                let mut tiles = vec![];
+                for row in &buffer.rows {
+                    tiles.extend(row.lock().unwrap().data.iter().map(|(&(x, y), &depth)| {
+                        (Vector2I::new(x, y), depth)
+                    }));
+                }
+                tiles.sort_by_key(|&(tile_coords, _)| (tile_coords.y(), tile_coords.x()));
+                for (tile_coords, depth) in tiles {
+                    push_solid_tile(tile_coords, depth);
+                }
+            }
         }
 
         solid_tiles
@@ -88,19 +160,63 @@ impl ZBuffer {
 }
 
 impl SolidTileBatchPrimitive {
-    fn new(tile_coords: Vector2I, object_index: u16, tex_transform: Transform2I)
+    fn new(tile_coords: Vector2I, object_index: u16, tex_transform: &Transform2F)
            -> SolidTileBatchPrimitive {
+        let tex_transform = tiles::tile_tex_transform(tex_transform, tile_coords);
         SolidTileBatchPrimitive {
             tile_x: tile_coords.x() as i16,
             tile_y: tile_coords.y() as i16,
             object_index: object_index,
-            texture_m00: tex_transform.matrix.m11() as u16,
-            texture_m10: tex_transform.matrix.m21() as u16,
-            texture_m01: tex_transform.matrix.m12() as u16,
-            texture_m11: tex_transform.matrix.m22() as u16,
-            texture_m02: tex_transform.vector.x() as u16,
-            texture_m12: tex_transform.vector.y() as u16,
+            texture_m00: tex_transform.matrix.m11(),
+            texture_m10: tex_transform.matrix.m21(),
+            texture_m01: tex_transform.matrix.m12(),
+            texture_m11: tex_transform.matrix.m22(),
+            texture_m02: tex_transform.vector.x(),
+            texture_m12: tex_transform.vector.y(),
             pad: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ZBuffer;
+    use crate::gpu_data::PaintPageId;
+    use crate::paint::{PaintId, PaintMetadata};
+    use crate::scene::PathObject;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I};
+
+    // Solid tiles come back at the coordinates they were stored at, whether or not the view box
+    // is at the origin, and whichever kind of Z-buffer it gets.
+    #[test]
+    fn test_solid_tile_coords() {
+        let paths = vec![PathObject::new(Outline::new(), PaintId(0), String::new()); 2];
+        let paths: Vec<_> = paths.iter().collect();
+        let paint_metadata = vec![PaintMetadata {
+            tex_transform: Transform2F::default(),
+            page: PaintPageId(0),
+            is_opaque: true,
+        }];
+
+        for &view_box_size in &[Vector2F::splat(256.0), Vector2F::splat(32768.0)] {
+            let view_box = RectF::new(Vector2F::new(64.0, 32.0), view_box_size);
+            let z_buffer = ZBuffer::new(view_box);
+            let tile_coords = [Vector2I::new(5, 2), Vector2I::new(4, 3), Vector2I::new(6, 3)];
+            for &coords in &tile_coords {
+                z_buffer.update(coords, 0);
+            }
+            z_buffer.update(tile_coords[1], 1);
+            assert!(!z_buffer.test(tile_coords[1], 0));
+            assert!(z_buffer.test(Vector2I::new(7, 3), 0));
+
+            let solid_tiles = z_buffer.build_solid_tiles(&paths, &paint_metadata, 0..2);
+            let solid_tiles: Vec<_> = solid_tiles.iter().map(|tile| {
+                (tile.tile_x, tile.tile_y, tile.object_index)
+            }).collect();
+            assert_eq!(solid_tiles, vec![(5, 2, 0), (4, 3, 1), (6, 3, 0)]);
+        }
+    }
+}
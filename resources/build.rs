@@ -1,9 +1,14 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::env;
+use std::io::{self, Write};
 use std::path::{PathBuf, Path};
 use phf_codegen::Map;
 
-fn add_dir(map: &mut Map<String>, root: &Path, dir: &Path) {
+// Resources are deflated with the best compression miniz offers, since this only runs once per
+// build.
+const COMPRESSION_LEVEL: u8 = 10;
+
+fn add_dir(map: &mut Map<String>, count: &mut usize, root: &Path, out_dir: &Path, dir: &Path) {
     println!("{:?}", dir);
     let abs_dir = root.join(dir);
     for entry in abs_dir.read_dir().expect("not a directory") {
@@ -12,47 +17,83 @@ fn add_dir(map: &mut Map<String>, root: &Path, dir: &Path) {
         let path = dir.join(entry.file_name());
         if typ.is_file() {
             let file_path = root.join(&path);
-            map.entry(path.to_str().expect("non-utf8 filename").into(), &format!("include_bytes!({:?})", file_path));
+            map.entry(path.to_str().expect("non-utf8 filename").into(),
+                      &embedded_resource(&file_path, &out_dir.join(&path), *count));
+            *count += 1;
         } else if typ.is_dir() {
-            add_dir(map, root, &path)
+            add_dir(map, count, root, out_dir, &path)
         }
     }
 }
 
+// Returns the expression for the resource in `file_path`, writing its compressed bytes to
+// `compressed_path`. Files that don't get smaller, like PNGs, are embedded as they are.
+fn embedded_resource(file_path: &Path, compressed_path: &Path, index: usize) -> String {
+    let data = fs::read(file_path).unwrap();
+    let compressed_data = miniz_oxide::deflate::compress_to_vec(&data, COMPRESSION_LEVEL);
+    if compressed_data.len() >= data.len() {
+        return format!("EmbeddedResource::uncompressed(include_bytes!({:?}), {})",
+                       file_path,
+                       index);
+    }
+
+    fs::create_dir_all(compressed_path.parent().unwrap()).unwrap();
+    fs::write(compressed_path, &compressed_data).unwrap();
+    format!("EmbeddedResource::compressed(include_bytes!({:?}), {}, {})",
+            compressed_path,
+            data.len(),
+            index)
+}
+
+// Writes the cache of inflated resources, with one empty slot per resource.
+fn write_inflated_cache(file: &mut File, count: usize) -> io::Result<()> {
+    writeln!(file, "static INFLATED: [AtomicPtr<Vec<u8>>; {}] = [", count)?;
+    for _ in 0..count {
+        writeln!(file, "    AtomicPtr::new(ptr::null_mut()),")?;
+    }
+    writeln!(file, "];")
+}
+
 fn main() {
     let resources = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap())
         .parent().unwrap()
         .join("resources");
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed={}", resources.to_str().expect("no-utf8 path"));
-    let file_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("resources_data.rs");
-    
-    let mut file = File::create(file_path).unwrap();
-    let mut map = Map::new();
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let file_path = out_dir.join("resources_data.rs");
+    let compressed_dir = out_dir.join("compressed");
 
-    #[cfg(feature="gl3_shaders")]
-    add_dir(&mut map, &resources, Path::new("shaders/gl3"));
+    let dirs: &[&str] = &[
+        #[cfg(feature="gl3_shaders")]
+        "shaders/gl3",
+        #[cfg(feature="gl4_shaders")]
+        "shaders/gl4",
+        #[cfg(feature="metal_shaders")]
+        "shaders/metal",
+        #[cfg(feature="fonts")]
+        "fonts",
+        #[cfg(feature="debug-fonts")]
+        "debug-fonts",
+        #[cfg(feature="svg")]
+        "svg",
+        #[cfg(feature="textures_lut")]
+        "textures/lut",
+        #[cfg(feature="textures_demo")]
+        "textures/demo",
+        #[cfg(feature="textures_debug")]
+        "textures/debug",
+    ];
 
-    #[cfg(feature="metal_shaders")]
-    add_dir(&mut map, &resources, Path::new("shaders/metal"));
-
-    #[cfg(feature="fonts")]
-    add_dir(&mut map, &resources, Path::new("fonts"));
-
-    #[cfg(feature="debug-fonts")]
-    add_dir(&mut map, &resources, Path::new("debug-fonts"));
-
-    #[cfg(feature="svg")]
-    add_dir(&mut map, &resources, Path::new("svg"));
-
-    #[cfg(feature="textures_lut")]
-    add_dir(&mut map, &resources, Path::new("textures/lut"));
-
-    #[cfg(feature="textures_demo")]
-    add_dir(&mut map, &resources, Path::new("textures/demo"));
-
-    #[cfg(feature="textures_debug")]
-    add_dir(&mut map, &resources, Path::new("textures/debug"));
+    let mut file = File::create(file_path).unwrap();
+    let mut map = Map::new();
+    let mut count = 0;
+    for dir in dirs {
+        add_dir(&mut map, &mut count, &resources, &compressed_dir, Path::new(dir));
+    }
 
     map.build(&mut file).unwrap();
+
+    let mut inflated_file = File::create(out_dir.join("resources_inflated.rs")).unwrap();
+    write_inflated_cache(&mut inflated_file, count).unwrap();
 }
@@ -0,0 +1,53 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform vec2 uSourceSize;
+
+uniform vec2 uDirection;
+uniform float uBlurStdDev;
+uniform float uSaturation;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+void main(){
+    vec4 color = texture(uSource, vTexCoord);
+    if(uBlurStdDev > 0.0){
+
+        int radius = int(ceil(uBlurStdDev * 3.0));
+        float totalWeight = 1.0;
+        for(int offset = 1; offset <= radius; offset ++){
+            float weight = exp(- float(offset * offset) / (2.0 * uBlurStdDev * uBlurStdDev));
+            vec2 delta = uDirection * float(offset) / uSourceSize;
+            color += weight * (texture(uSource, vTexCoord - delta) +
+                               texture(uSource, vTexCoord + delta));
+            totalWeight += 2.0 * weight;
+        }
+        color /= totalWeight;
+    }
+
+
+
+    float luminance = dot(color . rgb, vec3(0.213, 0.715, 0.072));
+    color . rgb = clamp(mix(vec3(luminance), color . rgb, uSaturation), 0.0, color . a);
+    oFragColor = color;
+}
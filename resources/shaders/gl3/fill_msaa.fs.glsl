@@ -0,0 +1,25 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+in float vWinding;
+
+out vec4 oFragColor;
+
+void main(){
+
+    oFragColor = vec4(vWinding);
+}
+
@@ -0,0 +1,58 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform vec2 uFramebufferSize;
+uniform vec2 uTileSize;
+
+in uvec2 aTessCoord;
+in uint aFromPx;
+in uint aToPx;
+in vec2 aFromSubpx;
+in vec2 aToSubpx;
+in uint aTileIndex;
+
+out float vWinding;
+
+vec2 computeTileOffset(uint tileIndex, float stencilTextureWidth){
+    uint tilesPerRow = uint(stencilTextureWidth / uTileSize . x);
+    uvec2 tileOffset = uvec2(tileIndex % tilesPerRow, tileIndex / tilesPerRow);
+    return vec2(tileOffset)* uTileSize;
+}
+
+void main(){
+    vec2 tileOrigin = computeTileOffset(aTileIndex, uFramebufferSize . x);
+
+    vec2 from = vec2(aFromPx & 15u, aFromPx >> 4u)+ aFromSubpx;
+    vec2 to = vec2(aToPx & 15u, aToPx >> 4u)+ aToSubpx;
+
+
+    vec2 position = aTessCoord . x == 0u ? from : to;
+    if(aTessCoord . y != 0u)
+        position . y = uTileSize . y;
+
+
+    vWinding = from . x < to . x ? - 1.0 : 1.0;
+
+    vec2 globalPosition =(tileOrigin + position)/ uFramebufferSize * 2.0 - 1.0;
+
+
+
+    gl_Position = vec4(globalPosition, 0.0, 1.0);
+}
+
@@ -0,0 +1,43 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform int uEncodeSRGB;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+vec3 linearToSRGB(vec3 linear){
+    vec3 low = linear * 12.92;
+    vec3 high = 1.055 * pow(linear, vec3(1.0 / 2.4))- 0.055;
+    return mix(low, high, step(vec3(0.0031308), linear));
+}
+
+void main(){
+    vec4 color = texture(uSource, vTexCoord);
+    if(uEncodeSRGB == 0){
+        oFragColor = color;
+        return;
+    }
+
+    vec3 linear = color . a > 0.0 ? clamp(color . rgb / color . a, 0.0, 1.0): vec3(0.0);
+    oFragColor = vec4(linearToSRGB(linear)* color . a, color . a);
+}
+
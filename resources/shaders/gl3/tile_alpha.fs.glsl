@@ -12,11 +12,11 @@
 
 
 
+#extension GL_GOOGLE_include_directive : enable
+
 precision highp float;
 
 uniform sampler2D uStencilTexture;
-uniform sampler2D uPaintTexture;
-uniform vec2 uPaintTextureSize;
 
 in vec2 vMaskTexCoord;
 in vec2 vColorTexCoord;
@@ -25,9 +25,67 @@ in vec4 vColor;
 
 out vec4 oFragColor;
 
-void main(){
-    float coverage = abs(texture(uStencilTexture, vMaskTexCoord). r + vBackdrop);
-    vec4 color = texture(uPaintTexture, vColorTexCoord);
-    oFragColor = vec4(color . rgb, color . a * coverage);
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform int uFillRule;
+
+
+
+float computeCoverage(float winding){
+    if(uFillRule == 1)
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    return abs(winding);
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform sampler2D uPaintTexture;
+uniform vec2 uPaintTextureSize;
+uniform int uPaintFormat;
+
+
+
+vec4 samplePaint(vec2 texCoord){
+    vec4 texel = texture(uPaintTexture, texCoord);
+    if(uPaintFormat == 1)
+        return vec4(texel . rrr, 1.0);
+    if(uPaintFormat == 2)
+        return vec4(vec3(0.0), texel . r);
+    return texel;
 }
 
+
+void main(){
+    float coverage = computeCoverage(texture(uStencilTexture, vMaskTexCoord). r + vBackdrop);
+    vec4 color = samplePaint(vColorTexCoord);
+    color . a *= coverage;
+    color . rgb *= color . a;
+    oFragColor = color;
+}
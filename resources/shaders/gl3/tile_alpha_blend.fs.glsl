@@ -0,0 +1,213 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform sampler2D uStencilTexture;
+
+
+uniform sampler2D uDestTexture;
+uniform vec4 uDestRect;
+uniform int uBlendMode;
+
+in vec2 vMaskTexCoord;
+in vec2 vColorTexCoord;
+in float vBackdrop;
+
+out vec4 oFragColor;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform int uFillRule;
+
+
+
+float computeCoverage(float winding){
+    if(uFillRule == 1)
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    return abs(winding);
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform sampler2D uPaintTexture;
+uniform vec2 uPaintTextureSize;
+uniform int uPaintFormat;
+
+
+
+vec4 samplePaint(vec2 texCoord){
+    vec4 texel = texture(uPaintTexture, texCoord);
+    if(uPaintFormat == 1)
+        return vec4(texel . rrr, 1.0);
+    if(uPaintFormat == 2)
+        return vec4(vec3(0.0), texel . r);
+    return texel;
+}
+
+
+vec3 screen(vec3 dest, vec3 src){
+    return dest + src - dest * src;
+}
+
+vec3 hardLight(vec3 dest, vec3 src){
+    return mix(dest * 2.0 * src,
+               screen(dest, 2.0 * src - 1.0),
+               greaterThan(src, vec3(0.5)));
+}
+
+vec3 colorDodge(vec3 dest, vec3 src){
+    vec3 dodge = mix(min(vec3(1.0), dest / (1.0 - src)),
+                     vec3(1.0),
+                     greaterThanEqual(src, vec3(1.0)));
+    return mix(dodge, vec3(0.0), lessThanEqual(dest, vec3(0.0)));
+}
+
+vec3 colorBurn(vec3 dest, vec3 src){
+    vec3 burn = mix(1.0 - min(vec3(1.0), (1.0 - dest) / src),
+                    vec3(0.0),
+                    lessThanEqual(src, vec3(0.0)));
+    return mix(burn, vec3(1.0), greaterThanEqual(dest, vec3(1.0)));
+}
+
+vec3 softLight(vec3 dest, vec3 src){
+    vec3 darkened = ( (16.0 * dest - 12.0) * dest + 4.0) * dest;
+    vec3 d = mix(sqrt(dest), darkened, lessThanEqual(dest, vec3(0.25)));
+    return mix(dest - (1.0 - 2.0 * src) * dest * (1.0 - dest),
+               dest + (2.0 * src - 1.0) * (d - dest),
+               greaterThan(src, vec3(0.5)));
+}
+
+float lum(vec3 color){
+    return dot(color, vec3(0.3, 0.59, 0.11));
+}
+
+float sat(vec3 color){
+    return max(color . r, max(color . g, color . b)) - min(color . r, min(color . g, color . b));
+}
+
+vec3 clipColor(vec3 color){
+    float l = lum(color);
+    float colorMin = min(color . r, min(color . g, color . b));
+    float colorMax = max(color . r, max(color . g, color . b));
+    if(colorMin < 0.0)
+        color = l + (color - l) * l / (l - colorMin);
+    if(colorMax > 1.0)
+        color = l + (color - l) * (1.0 - l) / (colorMax - l);
+    return color;
+}
+
+vec3 setLum(vec3 color, float l){
+    return clipColor(color + (l - lum(color)));
+}
+
+vec3 setSat(vec3 color, float s){
+    float colorMin = min(color . r, min(color . g, color . b));
+    float colorMax = max(color . r, max(color . g, color . b));
+    if(colorMax <= colorMin)
+        return vec3(0.0);
+    return(color - colorMin) * s / (colorMax - colorMin);
+}
+
+vec3 blend(vec3 dest, vec3 src){
+    if(uBlendMode == 0)
+        return dest * src;
+    if(uBlendMode == 1)
+        return hardLight(src, dest);
+    if(uBlendMode == 2)
+        return min(dest, src);
+    if(uBlendMode == 3)
+        return max(dest, src);
+    if(uBlendMode == 4)
+        return colorDodge(dest, src);
+    if(uBlendMode == 5)
+        return colorBurn(dest, src);
+    if(uBlendMode == 6)
+        return hardLight(dest, src);
+    if(uBlendMode == 7)
+        return softLight(dest, src);
+    if(uBlendMode == 8)
+        return abs(dest - src);
+    if(uBlendMode == 9)
+        return dest + src - 2.0 * dest * src;
+    if(uBlendMode == 10)
+        return setLum(setSat(src, sat(dest)), lum(dest));
+    if(uBlendMode == 11)
+        return setLum(setSat(dest, sat(src)), lum(dest));
+    if(uBlendMode == 12)
+        return setLum(src, lum(dest));
+    return setLum(dest, lum(src));
+}
+
+void main(){
+    float coverage = computeCoverage(texture(uStencilTexture, vMaskTexCoord). r + vBackdrop);
+    vec4 src = samplePaint(vColorTexCoord);
+
+    vec4 dest = texture(uDestTexture, (gl_FragCoord . xy - uDestRect . xy) / uDestRect . zw);
+    vec3 destColor = dest . a > 0.0 ? dest . rgb / dest . a : vec3(0.0);
+
+
+    vec3 color = mix(src . rgb, clamp(blend(destColor, src . rgb), 0.0, 1.0), dest . a);
+    float alpha = src . a * coverage;
+    oFragColor = vec4(color * alpha, alpha);
+}
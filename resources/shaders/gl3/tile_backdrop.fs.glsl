@@ -0,0 +1,67 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+uniform sampler2D uStencilTexture;
+
+
+uniform sampler2D uDestTexture;
+uniform sampler2D uFilteredDestTexture;
+uniform vec4 uDestRect;
+
+in vec2 vMaskTexCoord;
+in vec2 vColorTexCoord;
+in float vBackdrop;
+
+out vec4 oFragColor;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform int uFillRule;
+
+
+
+float computeCoverage(float winding){
+    if(uFillRule == 1)
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    return abs(winding);
+}
+
+
+void main(){
+    float coverage = computeCoverage(texture(uStencilTexture, vMaskTexCoord). r + vBackdrop);
+    vec2 destTexCoord = (gl_FragCoord . xy - uDestRect . xy) / uDestRect . zw;
+    oFragColor = mix(texture(uDestTexture, destTexCoord),
+                     texture(uFilteredDestTexture, destTexCoord),
+                     min(coverage, 1.0));
+}
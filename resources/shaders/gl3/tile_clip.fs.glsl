@@ -0,0 +1,35 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+uniform sampler2D uStencilTexture;
+
+in vec2 vMaskTexCoord;
+in vec2 vColorTexCoord;
+in float vBackdrop;
+
+out vec4 oFragColor;
+
+void main(){
+
+    float coverage = abs(texture(uStencilTexture, vMaskTexCoord). r + vBackdrop);
+    if(coverage < 0.5)
+        discard;
+    oFragColor = vec4(1.0);
+}
+
@@ -12,16 +12,47 @@
 
 
 
+#extension GL_GOOGLE_include_directive : enable
+
 precision highp float;
 
+in vec2 vColorTexCoord;
+
+out vec4 oFragColor;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
 uniform sampler2D uPaintTexture;
 uniform vec2 uPaintTextureSize;
+uniform int uPaintFormat;
 
-in vec2 vColorTexCoord;
 
-out vec4 oFragColor;
 
-void main(){
-    oFragColor = texture(uPaintTexture, vColorTexCoord);
+vec4 samplePaint(vec2 texCoord){
+    vec4 texel = texture(uPaintTexture, texCoord);
+    if(uPaintFormat == 1)
+        return vec4(texel . rrr, 1.0);
+    if(uPaintFormat == 2)
+        return vec4(vec3(0.0), texel . r);
+    return texel;
 }
 
+
+
+void main(){
+    oFragColor = samplePaint(vColorTexCoord);
+}
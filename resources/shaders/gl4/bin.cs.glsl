@@ -0,0 +1,187 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+
+
+layout(local_size_x = 64) in;
+
+uniform int uSegmentCount;
+uniform int uFillCapacity;
+
+struct Path {
+
+    ivec4 tileRect;
+
+
+    uvec4 tiles;
+
+    uvec4 info;
+    vec4 texMatrix;
+    vec4 texVector;
+};
+
+layout(std430, binding = 0) restrict readonly buffer bSegments {
+    vec4 iSegments[];
+};
+
+layout(std430, binding = 1) restrict readonly buffer bSegmentPaths {
+    uint iSegmentPaths[];
+};
+
+layout(std430, binding = 2) restrict readonly buffer bPaths {
+    Path iPaths[];
+};
+
+
+layout(std430, binding = 3) restrict buffer bTiles {
+    int ioTiles[];
+};
+
+layout(std430, binding = 4) restrict writeonly buffer bFills {
+    uvec2 oFills[];
+};
+
+
+layout(std430, binding = 5) restrict writeonly buffer bFillTiles {
+    uint oFillTiles[];
+};
+
+
+layout(std430, binding = 6) restrict buffer bCounters {
+    uint ioCounters[];
+};
+
+int tileIndex(Path path, ivec2 tileCoords){
+    int width = path . tileRect . z - path . tileRect . x;
+    ivec2 localCoords = tileCoords - path . tileRect . xy;
+    return int(path . tiles . x) + localCoords . y * width + localCoords . x;
+}
+
+bool tileIsInPath(Path path, ivec2 tileCoords){
+    return all(greaterThanEqual(tileCoords, path . tileRect . xy)) &&
+        all(lessThan(tileCoords, path . tileRect . zw));
+}
+
+
+
+ivec2 tileSpan(float minValue, float maxValue){
+    int first = int(floor(minValue / 16.0));
+    int last = int(floor(maxValue / 16.0));
+    if(maxValue > minValue && float(last) * 16.0 == maxValue)
+        last--;
+    return ivec2(first, last);
+}
+
+float solveXForY(vec4 segment, float y){
+    float t = (y - segment . y) / (segment . w - segment . y);
+    return segment . x + t * (segment . z - segment . x);
+}
+
+float solveYForX(vec4 segment, float x){
+    float t = (x - segment . x) / (segment . z - segment . x);
+    return segment . y + t * (segment . w - segment . y);
+}
+
+
+
+void addFill(Path path, vec4 segment, ivec2 tileCoords){
+    if(!tileIsInPath(path, tileCoords))
+        return;
+
+    vec4 tileOrigin = vec4(tileCoords, tileCoords) * 16.0;
+    vec4 fixedSegment = floor((segment - tileOrigin) * 256.0 + 0.5);
+    uvec4 fixedPoints = uvec4(clamp(fixedSegment, 0.0, 16.0 * 256.0 - 1.0));
+    if(fixedPoints . x == fixedPoints . z)
+        return;
+
+    uint fillIndex = atomicAdd(ioCounters[0], 1u);
+    if(fillIndex >= uint(uFillCapacity))
+        return;
+
+    uvec4 px = fixedPoints >> 8u, subpx = fixedPoints & 255u;
+    uint fromPx = px . x | (px . y << 4u), toPx = px . z | (px . w << 4u);
+    oFills[fillIndex] = uvec2(fromPx | (toPx << 8u) | (subpx . x << 16u) | (subpx . y << 24u),
+                              subpx . z | (subpx . w << 8u));
+
+    int fillTile = tileIndex(path, tileCoords);
+    oFillTiles[fillIndex] = uint(fillTile);
+    atomicOr(ioTiles[fillTile * 2 + 1], 1);
+}
+
+void binSegment(Path path, vec4 segment){
+    ivec2 rows = tileSpan(min(segment . y, segment . w), max(segment . y, segment . w));
+    for(int tileY = rows . x; tileY <= rows . y; tileY++) {
+
+        vec4 rowSegment = segment;
+        if(segment . y != segment . w) {
+            float top = float(tileY) * 16.0, bottom = top + 16.0;
+            vec2 y = clamp(segment . yw, top, bottom);
+            rowSegment = vec4(solveXForY(segment, y . x), y . x, solveXForY(segment, y . y), y . y);
+        }
+        if(rowSegment . x == rowSegment . z)
+            continue;
+
+        ivec2 columns = tileSpan(min(rowSegment . x, rowSegment . z), max(rowSegment . x, rowSegment . z));
+        for(int tileX = columns . x; tileX <= columns . y; tileX++) {
+            float left = float(tileX) * 16.0, right = left + 16.0;
+            vec2 x = clamp(rowSegment . xz, left, right);
+            vec4 fillSegment = vec4(x . x, solveYForX(rowSegment, x . x),
+                                    x . y, solveYForX(rowSegment, x . y));
+            addFill(path, fillSegment, ivec2(tileX, tileY));
+        }
+    }
+
+
+
+    if(segment . y == segment . w)
+        return;
+    int winding = segment . y < segment . w ? 1 : -1;
+    float maxY = max(segment . y, segment . w);
+    for(int tileY = int(floor(min(segment . y, segment . w) / 16.0)) + 1;
+         float(tileY) * 16.0 <= maxY;
+         tileY++) {
+        if(tileY < path . tileRect . y || tileY >= path . tileRect . w)
+            continue;
+
+        float y = float(tileY) * 16.0;
+        float x = solveXForY(segment, y);
+        int tileX = clamp(int(floor(x / 16.0)), path . tileRect . x, path . tileRect . z - 1);
+        float right = float(tileX + 1) * 16.0;
+        vec4 fillSegment = winding > 0 ? vec4(right, y, x, y) : vec4(x, y, right, y);
+        addFill(path, fillSegment, ivec2(tileX, tileY));
+
+        ivec2 nextTileCoords = ivec2(tileX + 1, tileY);
+        if(tileIsInPath(path, nextTileCoords))
+            atomicAdd(ioTiles[tileIndex(path, nextTileCoords) * 2], winding);
+    }
+}
+
+void main(){
+    uint stride = gl_NumWorkGroups . x * gl_WorkGroupSize . x;
+    for(uint segmentIndex = gl_GlobalInvocationID . x;
+         segmentIndex < uint(uSegmentCount);
+         segmentIndex += stride) {
+        binSegment(iPaths[iSegmentPaths[segmentIndex]], iSegments[segmentIndex]);
+    }
+}
+
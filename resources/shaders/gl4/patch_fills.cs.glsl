@@ -0,0 +1,53 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+
+
+layout(local_size_x = 64) in;
+
+uniform int uFillCapacity;
+
+layout(std430, binding = 0) restrict readonly buffer bTiles {
+    int iTiles[];
+};
+
+layout(std430, binding = 1) restrict buffer bFills {
+    uvec2 ioFills[];
+};
+
+layout(std430, binding = 2) restrict readonly buffer bFillTiles {
+    uint iFillTiles[];
+};
+
+layout(std430, binding = 3) restrict readonly buffer bCounters {
+    uint iCounters[];
+};
+
+void main(){
+    uint fillCount = min(iCounters[0], uint(uFillCapacity));
+    uint stride = gl_NumWorkGroups . x * gl_WorkGroupSize . x;
+    for(uint fillIndex = gl_GlobalInvocationID . x; fillIndex < fillCount; fillIndex += stride) {
+        uint alphaTileIndex = uint(iTiles[iFillTiles[fillIndex] * 2u + 1u]);
+        if(alphaTileIndex == 0xffffu)
+            ioFills[fillIndex] = uvec2(0u);
+        else
+            ioFills[fillIndex] . y = (ioFills[fillIndex] . y & 0xffffu) | (alphaTileIndex << 16u);
+    }
+}
+
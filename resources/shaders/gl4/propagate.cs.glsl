@@ -0,0 +1,123 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+
+
+
+layout(local_size_x = 64) in;
+
+uniform int uRowCount;
+uniform int uPathCount;
+uniform int uAlphaTileCapacity;
+
+struct Path {
+    ivec4 tileRect;
+    uvec4 tiles;
+    uvec4 info;
+    vec4 texMatrix;
+    vec4 texVector;
+};
+
+layout(std430, binding = 0) restrict readonly buffer bPaths {
+    Path iPaths[];
+};
+
+
+layout(std430, binding = 1) restrict buffer bTiles {
+    int ioTiles[];
+};
+
+
+
+layout(std430, binding = 2) restrict buffer bCounters {
+    uint ioCounters[];
+};
+
+layout(std430, binding = 3) restrict writeonly buffer bAlphaTiles {
+    uint oAlphaTiles[];
+};
+
+
+uint findPath(uint row){
+    uint low = 0u, high = uint(uPathCount);
+    while(high - low > 1u) {
+        uint middle = (low + high) / 2u;
+        if(iPaths[middle] . tiles . y <= row)
+            low = middle;
+        else
+            high = middle;
+    }
+    return low;
+}
+
+void addAlphaTile(Path path, ivec2 tileCoords, int backdrop, uint alphaTileIndex){
+    uint outputIndex = atomicAdd(ioCounters[1], 1u);
+    if(outputIndex >= uint(uAlphaTileCapacity))
+        return;
+
+    uvec2 coords = uvec2(tileCoords);
+    uint tileHi = ((coords . x >> 8u) & 0x0fu) | ((coords . y >> 4u) & 0xf0u);
+    vec2 texVector = path . texVector . xy + mat2(path . texMatrix) * (vec2(tileCoords) * 16.0);
+
+    uint base = outputIndex * 8u;
+    oAlphaTiles[base + 0u] = (coords . x & 0xffu) | ((coords . y & 0xffu) << 8u) | (tileHi << 16u) |
+        ((uint(backdrop) & 0xffu) << 24u);
+    oAlphaTiles[base + 1u] = path . info . x | (alphaTileIndex << 16u);
+    oAlphaTiles[base + 2u] = floatBitsToUint(path . texMatrix . x);
+    oAlphaTiles[base + 3u] = floatBitsToUint(path . texMatrix . y);
+    oAlphaTiles[base + 4u] = floatBitsToUint(path . texMatrix . z);
+    oAlphaTiles[base + 5u] = floatBitsToUint(path . texMatrix . w);
+    oAlphaTiles[base + 6u] = floatBitsToUint(texVector . x);
+    oAlphaTiles[base + 7u] = floatBitsToUint(texVector . y);
+}
+
+void propagateRow(uint row){
+    uint pathIndex = findPath(row);
+    Path path = iPaths[pathIndex];
+
+    int width = path . tileRect . z - path . tileRect . x;
+    int tileY = path . tileRect . y + int(row - path . tiles . y);
+    int firstTileIndex = int(path . tiles . x) + int(row - path . tiles . y) * width;
+
+    int backdrop = 0;
+    for(int localX = 0; localX < width; localX++) {
+        int tileIndex = firstTileIndex + localX;
+        backdrop += ioTiles[tileIndex * 2];
+
+        uint alphaTileIndex = 0xffffu;
+        if(ioTiles[tileIndex * 2 + 1] != 0) {
+            uint alphaTileCount = atomicAdd(ioCounters[2u + pathIndex], 1u);
+            if(alphaTileCount < path . tiles . w)
+                alphaTileIndex = path . tiles . z + alphaTileCount;
+        }
+        ioTiles[tileIndex * 2] = backdrop;
+        ioTiles[tileIndex * 2 + 1] = int(alphaTileIndex);
+
+        bool inside = path . info . y == 0u ? backdrop != 0 : backdrop % 2 != 0;
+        if(alphaTileIndex != 0xffffu || inside)
+            addAlphaTile(path, ivec2(path . tileRect . x + localX, tileY), backdrop, alphaTileIndex);
+    }
+}
+
+void main(){
+    uint stride = gl_NumWorkGroups . x * gl_WorkGroupSize . x;
+    for(uint row = gl_GlobalInvocationID . x; row < uint(uRowCount); row += stride)
+        propagateRow(row);
+}
+
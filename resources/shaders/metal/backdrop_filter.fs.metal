@@ -0,0 +1,54 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    texture2d<float> uSource [[id(0)]];
+    sampler uSourceSmplr [[id(1)]];
+    constant float* uBlurStdDev [[id(2)]];
+    constant float2* uDirection [[id(3)]];
+    constant float2* uSourceSize [[id(4)]];
+    constant float* uSaturation [[id(5)]];
+};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vTexCoord [[user(locn0)]];
+};
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
+{
+    main0_out out = {};
+    float4 color = spvDescriptorSet0.uSource.sample(spvDescriptorSet0.uSourceSmplr, in.vTexCoord);
+    if ((*spvDescriptorSet0.uBlurStdDev) > 0.0)
+    {
+        int radius = int(ceil((*spvDescriptorSet0.uBlurStdDev) * 3.0));
+        float totalWeight = 1.0;
+        for (int offset = 1; offset <= radius; offset++)
+        {
+            float weight = exp((-float(offset * offset)) / ((2.0 * (*spvDescriptorSet0.uBlurStdDev)) * (*spvDescriptorSet0.uBlurStdDev)));
+            float2 delta = ((*spvDescriptorSet0.uDirection) * float(offset)) / (*spvDescriptorSet0.uSourceSize);
+            color += ((spvDescriptorSet0.uSource.sample(spvDescriptorSet0.uSourceSmplr, (in.vTexCoord - delta)) + spvDescriptorSet0.uSource.sample(spvDescriptorSet0.uSourceSmplr, (in.vTexCoord + delta))) * weight);
+            totalWeight += (2.0 * weight);
+        }
+        color /= float4(totalWeight);
+    }
+    float luminance = dot(color.xyz, float3(0.212999999523162841796875, 0.714999973773956298828125, 0.0719999969005584716796875));
+    float4 _109 = color;
+    float _118 = color.w;
+    float3 _121 = fast::clamp(mix(float3(luminance), _109.xyz, float3((*spvDescriptorSet0.uSaturation))), float3(0.0), float3(_118));
+    color.x = _121.x;
+    color.y = _121.y;
+    color.z = _121.z;
+    out.oFragColor = color;
+    return out;
+}
+
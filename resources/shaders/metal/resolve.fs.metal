@@ -0,0 +1,54 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    texture2d<float> uSource [[id(0)]];
+    sampler uSourceSmplr [[id(1)]];
+    constant int* uEncodeSRGB [[id(2)]];
+};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vTexCoord [[user(locn0)]];
+};
+
+float3 linearToSRGB(thread const float3& linear)
+{
+    float3 low = linear * 12.9200000762939453125;
+    float3 high = (pow(linear, float3(0.4166666567325592041015625)) * 1.05499994754791259765625) - float3(0.054999999701976776123046875);
+    return mix(low, high, step(float3(0.003130800090730190277099609375), linear));
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
+{
+    main0_out out = {};
+    float4 color = spvDescriptorSet0.uSource.sample(spvDescriptorSet0.uSourceSmplr, in.vTexCoord);
+    if ((*spvDescriptorSet0.uEncodeSRGB) == 0)
+    {
+        out.oFragColor = color;
+        return out;
+    }
+    float3 _55;
+    if (color.w > 0.0)
+    {
+        _55 = fast::clamp(color.xyz / float3(color.w), float3(0.0), float3(1.0));
+    }
+    else
+    {
+        _55 = float3(0.0);
+    }
+    float3 linear = _55;
+    float3 param = linear;
+    out.oFragColor = float4(linearToSRGB(param) * color.w, color.w);
+    return out;
+}
+
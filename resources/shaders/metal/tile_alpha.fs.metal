@@ -1,4 +1,6 @@
 // Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
 #include <metal_stdlib>
 #include <simd/simd.h>
 
@@ -6,10 +8,12 @@ using namespace metal;
 
 struct spvDescriptorSetBuffer0
 {
-    texture2d<float> uStencilTexture [[id(0)]];
-    sampler uStencilTextureSmplr [[id(1)]];
-    texture2d<float> uPaintTexture [[id(2)]];
-    sampler uPaintTextureSmplr [[id(3)]];
+    constant int* uFillRule [[id(0)]];
+    texture2d<float> uPaintTexture [[id(1)]];
+    sampler uPaintTextureSmplr [[id(2)]];
+    constant int* uPaintFormat [[id(3)]];
+    texture2d<float> uStencilTexture [[id(4)]];
+    sampler uStencilTextureSmplr [[id(5)]];
 };
 
 struct main0_out
@@ -24,12 +28,53 @@ struct main0_in
     float vBackdrop [[user(locn2)]];
 };
 
+// Implementation of the GLSL mod() function, which is slightly different than Metal fmod()
+template<typename Tx, typename Ty>
+inline Tx mod(Tx x, Ty y)
+{
+    return x - y * floor(x / y);
+}
+
+static inline __attribute__((always_inline))
+float computeCoverage(thread const float& winding, thread int uFillRule)
+{
+    if (uFillRule == 1)
+    {
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    }
+    return abs(winding);
+}
+
+static inline __attribute__((always_inline))
+float4 samplePaint(thread const float2& texCoord, thread texture2d<float> uPaintTexture, thread const sampler uPaintTextureSmplr, thread int uPaintFormat)
+{
+    float4 texel = uPaintTexture.sample(uPaintTextureSmplr, texCoord);
+    if (uPaintFormat == 1)
+    {
+        return float4(texel.xxx, 1.0);
+    }
+    if (uPaintFormat == 2)
+    {
+        return float4(0.0, 0.0, 0.0, texel.x);
+    }
+    return texel;
+}
+
 fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
 {
     main0_out out = {};
-    float coverage = abs(spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vMaskTexCoord).x + in.vBackdrop);
-    float4 color = spvDescriptorSet0.uPaintTexture.sample(spvDescriptorSet0.uPaintTextureSmplr, in.vColorTexCoord);
-    out.oFragColor = float4(color.xyz, color.w * coverage);
+    float param = spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vMaskTexCoord).x + in.vBackdrop;
+    float coverage = computeCoverage(param, (*spvDescriptorSet0.uFillRule));
+    float2 param_1 = in.vColorTexCoord;
+    float4 color = samplePaint(param_1, spvDescriptorSet0.uPaintTexture, spvDescriptorSet0.uPaintTextureSmplr, (*spvDescriptorSet0.uPaintFormat));
+    color.w *= coverage;
+    float _108 = color.w;
+    float4 _109 = color;
+    float3 _111 = _109.xyz * _108;
+    color.x = _111.x;
+    color.y = _111.y;
+    color.z = _111.z;
+    out.oFragColor = color;
     return out;
 }
 
@@ -0,0 +1,265 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    constant int* uFillRule [[id(0)]];
+    texture2d<float> uPaintTexture [[id(1)]];
+    sampler uPaintTextureSmplr [[id(2)]];
+    constant int* uPaintFormat [[id(3)]];
+    constant int* uBlendMode [[id(4)]];
+    texture2d<float> uStencilTexture [[id(5)]];
+    sampler uStencilTextureSmplr [[id(6)]];
+    texture2d<float> uDestTexture [[id(7)]];
+    sampler uDestTextureSmplr [[id(8)]];
+    constant float4* uDestRect [[id(9)]];
+};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vMaskTexCoord [[user(locn0)]];
+    float2 vColorTexCoord [[user(locn1)]];
+    float vBackdrop [[user(locn2)]];
+};
+
+// Implementation of the GLSL mod() function, which is slightly different than Metal fmod()
+template<typename Tx, typename Ty>
+inline Tx mod(Tx x, Ty y)
+{
+    return x - y * floor(x / y);
+}
+
+static inline __attribute__((always_inline))
+float computeCoverage(thread const float& winding, thread int uFillRule)
+{
+    if (uFillRule == 1)
+    {
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    }
+    return abs(winding);
+}
+
+static inline __attribute__((always_inline))
+float4 samplePaint(thread const float2& texCoord, thread texture2d<float> uPaintTexture, thread const sampler uPaintTextureSmplr, thread int uPaintFormat)
+{
+    float4 texel = uPaintTexture.sample(uPaintTextureSmplr, texCoord);
+    if (uPaintFormat == 1)
+    {
+        return float4(texel.xxx, 1.0);
+    }
+    if (uPaintFormat == 2)
+    {
+        return float4(0.0, 0.0, 0.0, texel.x);
+    }
+    return texel;
+}
+
+static inline __attribute__((always_inline))
+float3 screen(thread const float3& dest, thread const float3& src)
+{
+    return (dest + src) - (dest * src);
+}
+
+static inline __attribute__((always_inline))
+float3 hardLight(thread const float3& dest, thread const float3& src)
+{
+    float3 param = dest;
+    float3 param_1 = (src * 2.0) - float3(1.0);
+    return select((dest * 2.0) * src, screen(param, param_1), src > float3(0.5));
+}
+
+static inline __attribute__((always_inline))
+float3 colorDodge(thread const float3& dest, thread const float3& src)
+{
+    float3 dodge = select(fast::min(float3(1.0), dest / (float3(1.0) - src)), float3(1.0), src >= float3(1.0));
+    return select(dodge, float3(0.0), dest <= float3(0.0));
+}
+
+static inline __attribute__((always_inline))
+float3 colorBurn(thread const float3& dest, thread const float3& src)
+{
+    float3 burn = select(float3(1.0) - fast::min(float3(1.0), (float3(1.0) - dest) / src), float3(0.0), src <= float3(0.0));
+    return select(burn, float3(1.0), dest >= float3(1.0));
+}
+
+static inline __attribute__((always_inline))
+float3 softLight(thread const float3& dest, thread const float3& src)
+{
+    float3 darkened = ((((dest * 16.0) - float3(12.0)) * dest) + float3(4.0)) * dest;
+    float3 d = select(sqrt(dest), darkened, dest <= float3(0.25));
+    return select(dest - (((float3(1.0) - (src * 2.0)) * dest) * (float3(1.0) - dest)), dest + (((src * 2.0) - float3(1.0)) * (d - dest)), src > float3(0.5));
+}
+
+static inline __attribute__((always_inline))
+float sat(thread const float3& color)
+{
+    return fast::max(color.x, fast::max(color.y, color.z)) - fast::min(color.x, fast::min(color.y, color.z));
+}
+
+static inline __attribute__((always_inline))
+float3 setSat(thread const float3& color, thread const float& s)
+{
+    float colorMin = fast::min(color.x, fast::min(color.y, color.z));
+    float colorMax = fast::max(color.x, fast::max(color.y, color.z));
+    if (colorMax <= colorMin)
+    {
+        return float3(0.0);
+    }
+    return ((color - float3(colorMin)) * s) / float3(colorMax - colorMin);
+}
+
+static inline __attribute__((always_inline))
+float lum(thread const float3& color)
+{
+    return dot(color, float3(0.300000011920928955078125, 0.589999973773956298828125, 0.10999999940395355224609375));
+}
+
+static inline __attribute__((always_inline))
+float3 clipColor(thread float3& color)
+{
+    float3 param = color;
+    float l = lum(param);
+    float colorMin = fast::min(color.x, fast::min(color.y, color.z));
+    float colorMax = fast::max(color.x, fast::max(color.y, color.z));
+    if (colorMin < 0.0)
+    {
+        color = float3(l) + (((color - float3(l)) * l) / float3(l - colorMin));
+    }
+    if (colorMax > 1.0)
+    {
+        color = float3(l) + (((color - float3(l)) * (1.0 - l)) / float3(colorMax - l));
+    }
+    return color;
+}
+
+static inline __attribute__((always_inline))
+float3 setLum(thread const float3& color, thread const float& l)
+{
+    float3 param = color;
+    float3 param_1 = color + float3(l - lum(param));
+    float3 _342 = clipColor(param_1);
+    return _342;
+}
+
+static inline __attribute__((always_inline))
+float3 blend(thread const float3& dest, thread const float3& src, thread int uBlendMode)
+{
+    if (uBlendMode == 0)
+    {
+        return dest * src;
+    }
+    if (uBlendMode == 1)
+    {
+        float3 param = src;
+        float3 param_1 = dest;
+        return hardLight(param, param_1);
+    }
+    if (uBlendMode == 2)
+    {
+        return fast::min(dest, src);
+    }
+    if (uBlendMode == 3)
+    {
+        return fast::max(dest, src);
+    }
+    if (uBlendMode == 4)
+    {
+        float3 param_2 = dest;
+        float3 param_3 = src;
+        return colorDodge(param_2, param_3);
+    }
+    if (uBlendMode == 5)
+    {
+        float3 param_4 = dest;
+        float3 param_5 = src;
+        return colorBurn(param_4, param_5);
+    }
+    if (uBlendMode == 6)
+    {
+        float3 param_6 = dest;
+        float3 param_7 = src;
+        return hardLight(param_6, param_7);
+    }
+    if (uBlendMode == 7)
+    {
+        float3 param_8 = dest;
+        float3 param_9 = src;
+        return softLight(param_8, param_9);
+    }
+    if (uBlendMode == 8)
+    {
+        return abs(dest - src);
+    }
+    if (uBlendMode == 9)
+    {
+        return (dest + src) - ((dest * 2.0) * src);
+    }
+    if (uBlendMode == 10)
+    {
+        float3 param_10 = dest;
+        float3 param_11 = src;
+        float param_12 = sat(param_10);
+        float3 param_13 = dest;
+        float3 param_14 = setSat(param_11, param_12);
+        float param_15 = lum(param_13);
+        return setLum(param_14, param_15);
+    }
+    if (uBlendMode == 11)
+    {
+        float3 param_16 = src;
+        float3 param_17 = dest;
+        float param_18 = sat(param_16);
+        float3 param_19 = dest;
+        float3 param_20 = setSat(param_17, param_18);
+        float param_21 = lum(param_19);
+        return setLum(param_20, param_21);
+    }
+    if (uBlendMode == 12)
+    {
+        float3 param_22 = dest;
+        float3 param_23 = src;
+        float param_24 = lum(param_22);
+        return setLum(param_23, param_24);
+    }
+    float3 param_25 = src;
+    float3 param_26 = dest;
+    float param_27 = lum(param_25);
+    return setLum(param_26, param_27);
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]], float4 gl_FragCoord [[position]])
+{
+    main0_out out = {};
+    float param = spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vMaskTexCoord).x + in.vBackdrop;
+    float coverage = computeCoverage(param, (*spvDescriptorSet0.uFillRule));
+    float2 param_1 = in.vColorTexCoord;
+    float4 src = samplePaint(param_1, spvDescriptorSet0.uPaintTexture, spvDescriptorSet0.uPaintTextureSmplr, (*spvDescriptorSet0.uPaintFormat));
+    float4 dest = spvDescriptorSet0.uDestTexture.sample(spvDescriptorSet0.uDestTextureSmplr, ((gl_FragCoord.xy - (*spvDescriptorSet0.uDestRect).xy) / (*spvDescriptorSet0.uDestRect).zw));
+    float3 _587;
+    if (dest.w > 0.0)
+    {
+        _587 = dest.xyz / float3(dest.w);
+    }
+    else
+    {
+        _587 = float3(0.0);
+    }
+    float3 destColor = _587;
+    float3 param_2 = destColor;
+    float3 param_3 = src.xyz;
+    float3 color = mix(src.xyz, fast::clamp(blend(param_2, param_3, (*spvDescriptorSet0.uBlendMode)), float3(0.0), float3(1.0)), float3(dest.w));
+    float alpha = src.w * coverage;
+    out.oFragColor = float4(color * alpha, alpha);
+    return out;
+}
+
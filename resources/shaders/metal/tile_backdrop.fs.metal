@@ -0,0 +1,58 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    constant int* uFillRule [[id(0)]];
+    texture2d<float> uStencilTexture [[id(1)]];
+    sampler uStencilTextureSmplr [[id(2)]];
+    constant float4* uDestRect [[id(3)]];
+    texture2d<float> uDestTexture [[id(4)]];
+    sampler uDestTextureSmplr [[id(5)]];
+    texture2d<float> uFilteredDestTexture [[id(6)]];
+    sampler uFilteredDestTextureSmplr [[id(7)]];
+};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vMaskTexCoord [[user(locn0)]];
+    float vBackdrop [[user(locn2)]];
+};
+
+// Implementation of the GLSL mod() function, which is slightly different than Metal fmod()
+template<typename Tx, typename Ty>
+inline Tx mod(Tx x, Ty y)
+{
+    return x - y * floor(x / y);
+}
+
+static inline __attribute__((always_inline))
+float computeCoverage(thread const float& winding, thread int uFillRule)
+{
+    if (uFillRule == 1)
+    {
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    }
+    return abs(winding);
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]], float4 gl_FragCoord [[position]])
+{
+    main0_out out = {};
+    float param = spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vMaskTexCoord).x + in.vBackdrop;
+    float coverage = computeCoverage(param, (*spvDescriptorSet0.uFillRule));
+    float2 destTexCoord = (gl_FragCoord.xy - (*spvDescriptorSet0.uDestRect).xy) / (*spvDescriptorSet0.uDestRect).zw;
+    out.oFragColor = mix(spvDescriptorSet0.uDestTexture.sample(spvDescriptorSet0.uDestTextureSmplr, destTexCoord), spvDescriptorSet0.uFilteredDestTexture.sample(spvDescriptorSet0.uFilteredDestTextureSmplr, destTexCoord), float4(fast::min(coverage, 1.0)));
+    return out;
+}
+
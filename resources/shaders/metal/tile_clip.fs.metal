@@ -0,0 +1,35 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct spvDescriptorSetBuffer0
+{
+    texture2d<float> uStencilTexture [[id(0)]];
+    sampler uStencilTextureSmplr [[id(1)]];
+};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vMaskTexCoord [[user(locn0)]];
+    float vBackdrop [[user(locn2)]];
+};
+
+fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
+{
+    main0_out out = {};
+    float coverage = abs(spvDescriptorSet0.uStencilTexture.sample(spvDescriptorSet0.uStencilTextureSmplr, in.vMaskTexCoord).x + in.vBackdrop);
+    if (coverage < 0.5)
+    {
+        discard_fragment();
+    }
+    out.oFragColor = float4(1.0);
+    return out;
+}
+
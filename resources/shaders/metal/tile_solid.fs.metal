@@ -1,4 +1,6 @@
 // Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
 #include <metal_stdlib>
 #include <simd/simd.h>
 
@@ -8,6 +10,7 @@ struct spvDescriptorSetBuffer0
 {
     texture2d<float> uPaintTexture [[id(0)]];
     sampler uPaintTextureSmplr [[id(1)]];
+    constant int* uPaintFormat [[id(2)]];
 };
 
 struct main0_out
@@ -20,10 +23,26 @@ struct main0_in
     float2 vColorTexCoord [[user(locn0)]];
 };
 
+static inline __attribute__((always_inline))
+float4 samplePaint(thread const float2& texCoord, thread texture2d<float> uPaintTexture, thread const sampler uPaintTextureSmplr, constant int& uPaintFormat)
+{
+    float4 texel = uPaintTexture.sample(uPaintTextureSmplr, texCoord);
+    if (uPaintFormat == 1)
+    {
+        return float4(texel.xxx, 1.0);
+    }
+    if (uPaintFormat == 2)
+    {
+        return float4(float3(0.0), texel.x);
+    }
+    return texel;
+}
+
 fragment main0_out main0(main0_in in [[stage_in]], constant spvDescriptorSetBuffer0& spvDescriptorSet0 [[buffer(0)]])
 {
     main0_out out = {};
-    out.oFragColor = spvDescriptorSet0.uPaintTexture.sample(spvDescriptorSet0.uPaintTextureSmplr, in.vColorTexCoord);
+    float2 param = in.vColorTexCoord;
+    out.oFragColor = samplePaint(param, spvDescriptorSet0.uPaintTexture, spvDescriptorSet0.uPaintTextureSmplr, (*spvDescriptorSet0.uPaintFormat));
     return out;
 }
 
@@ -1,19 +1,96 @@
 use std::borrow::Cow;
 use std::io::{Error as IOError, ErrorKind};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
 use pathfinder_gpu::resources::ResourceLoader;
 use phf::Map;
 
 pub struct EmbeddedResourceLoader;
-static RESOURCES: Map<&'static str, &'static [u8]> = include!(concat!(env!("OUT_DIR"), "/", "resources_data.rs"));
+static RESOURCES: Map<&'static str, EmbeddedResource> = include!(concat!(env!("OUT_DIR"), "/", "resources_data.rs"));
+
+// The inflated data of each compressed resource, indexed by `EmbeddedResource::index` and leaked
+// on first access so that every later one can borrow it. This lives outside `RESOURCES` because a
+// `static` can't borrow interior mutable data.
+include!(concat!(env!("OUT_DIR"), "/", "resources_inflated.rs"));
 
 impl ResourceLoader for EmbeddedResourceLoader {
     fn slurp(&self, virtual_path: &str) -> Result<Cow<'static, [u8]>, IOError> {
         match RESOURCES.get(virtual_path) {
-            Some(&data) => Ok(data.into()),
+            Some(resource) => Ok(resource.data()?.into()),
             None => {
                 let msg = format!("{} is not embedded. check your feature flags.", virtual_path);
                 Err(IOError::new(ErrorKind::NotFound, msg))
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// A resource embedded by the build script, deflated unless that didn't make it smaller.
+struct EmbeddedResource {
+    data: &'static [u8],
+    // The length of the data once inflated, or `None` if it's stored uncompressed.
+    inflated_len: Option<usize>,
+    // The index of this resource in `INFLATED`.
+    index: usize,
+}
+
+// Which constructors the build script uses depends on the resources that the features embed.
+#[allow(dead_code)]
+impl EmbeddedResource {
+    const fn uncompressed(data: &'static [u8], index: usize) -> EmbeddedResource {
+        EmbeddedResource { data, inflated_len: None, index }
+    }
+
+    const fn compressed(data: &'static [u8], inflated_len: usize, index: usize)
+                        -> EmbeddedResource {
+        EmbeddedResource { data, inflated_len: Some(inflated_len), index }
+    }
+
+    fn data(&'static self) -> Result<&'static [u8], IOError> {
+        let inflated_len = match self.inflated_len {
+            None => return Ok(self.data),
+            Some(inflated_len) => inflated_len,
+        };
+
+        let cache = &INFLATED[self.index];
+        let inflated = cache.load(Ordering::Acquire);
+        if !inflated.is_null() {
+            return Ok(unsafe { (*inflated).as_slice() });
+        }
+
+        let data = miniz_oxide::inflate::decompress_to_vec(self.data).map_err(|status| {
+            let msg = format!("embedded resource is corrupt: {:?}", status);
+            IOError::new(ErrorKind::InvalidData, msg)
+        })?;
+        debug_assert_eq!(data.len(), inflated_len);
+
+        // If another thread inflated the resource first, use its copy and free this one.
+        let new_inflated = Box::into_raw(Box::new(data));
+        match cache.compare_exchange(ptr::null_mut(),
+                                     new_inflated,
+                                     Ordering::AcqRel,
+                                     Ordering::Acquire) {
+            Ok(_) => Ok(unsafe { (*new_inflated).as_slice() }),
+            Err(inflated) => {
+                drop(unsafe { Box::from_raw(new_inflated) });
+                Ok(unsafe { (*inflated).as_slice() })
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "gl3_shaders"))]
+mod test {
+    use super::EmbeddedResourceLoader;
+    use pathfinder_gpu::resources::ResourceLoader;
+
+    #[test]
+    fn test_inflate_shader() {
+        let loader = EmbeddedResourceLoader;
+        let path = "shaders/gl3/tile_alpha.fs.glsl";
+        let first = loader.slurp(path).unwrap();
+        assert!(first.starts_with(b"#version"));
+        // The second access borrows the copy inflated by the first.
+        assert_eq!(loader.slurp(path).unwrap().as_ptr(), first.as_ptr());
+    }
+}
@@ -0,0 +1,51 @@
+#version 330
+
+// pathfinder/shaders/backdrop_filter.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// One pass of a backdrop filter: a one-dimensional Gaussian blur of the premultiplied source along
+// `uDirection`, followed by a saturation change. The renderer blurs horizontally, then
+// vertically, and saturates in the second pass.
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform vec2 uSourceSize;
+// A unit vector along a row or a column.
+uniform vec2 uDirection;
+uniform float uBlurStdDev;
+uniform float uSaturation;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+void main() {
+    vec4 color = texture(uSource, vTexCoord);
+    if (uBlurStdDev > 0.0) {
+        // Three standard deviations cover all but a fraction of a percent of the kernel.
+        int radius = int(ceil(uBlurStdDev * 3.0));
+        float totalWeight = 1.0;
+        for (int offset = 1; offset <= radius; offset++) {
+            float weight = exp(-float(offset * offset) / (2.0 * uBlurStdDev * uBlurStdDev));
+            vec2 delta = uDirection * float(offset) / uSourceSize;
+            color += weight * (texture(uSource, vTexCoord - delta) +
+                               texture(uSource, vTexCoord + delta));
+            totalWeight += 2.0 * weight;
+        }
+        color /= totalWeight;
+    }
+
+    // The saturation matrix of the "Filter Effects" specification. It's linear, so it applies to
+    // premultiplied colors unchanged.
+    float luminance = dot(color.rgb, vec3(0.213, 0.715, 0.072));
+    color.rgb = clamp(mix(vec3(luminance), color.rgb, uSaturation), 0.0, color.a);
+    oFragColor = color;
+}
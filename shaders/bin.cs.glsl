@@ -0,0 +1,184 @@
+#version 430
+
+// pathfinder/shaders/bin.cs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Bins line segments into tiles, for `BuildOptions::gpu_tiling`. This is the first pass of three,
+// followed by `propagate` and `patch_fills`, and does what `binning.rs` does on the CPU.
+//
+// Each segment makes a fill in every tile it crosses, and where it crosses the top edge of a tile,
+// a fill along that edge from the crossing to the right side of the tile. It adds its winding to
+// the backdrop of the tile to the right of that one.
+
+precision highp float;
+
+#define TILE_SIZE 16.0
+
+layout(local_size_x = 64) in;
+
+uniform int uSegmentCount;
+uniform int uFillCapacity;
+
+struct Path {
+    // The tiles the path may draw to: min x, min y, max x, max y.
+    ivec4 tileRect;
+    // The first tile of the path in `ioTiles` and the first row of the path, followed by its first
+    // alpha tile index and its alpha tile count.
+    uvec4 tiles;
+    // The object index and fill rule.
+    uvec4 info;
+    vec4 texMatrix;
+    vec4 texVector;
+};
+
+layout(std430, binding = 0) restrict readonly buffer bSegments {
+    vec4 iSegments[];
+};
+
+layout(std430, binding = 1) restrict readonly buffer bSegmentPaths {
+    uint iSegmentPaths[];
+};
+
+layout(std430, binding = 2) restrict readonly buffer bPaths {
+    Path iPaths[];
+};
+
+// Two values per tile: the change in backdrop, and whether the tile has any fills.
+layout(std430, binding = 3) restrict buffer bTiles {
+    int ioTiles[];
+};
+
+layout(std430, binding = 4) restrict writeonly buffer bFills {
+    uvec2 oFills[];
+};
+
+// The tile of each fill, in `ioTiles`.
+layout(std430, binding = 5) restrict writeonly buffer bFillTiles {
+    uint oFillTiles[];
+};
+
+// The fill count, followed by counters that the next passes use.
+layout(std430, binding = 6) restrict buffer bCounters {
+    uint ioCounters[];
+};
+
+int tileIndex(Path path, ivec2 tileCoords) {
+    int width = path.tileRect.z - path.tileRect.x;
+    ivec2 localCoords = tileCoords - path.tileRect.xy;
+    return int(path.tiles.x) + localCoords.y * width + localCoords.x;
+}
+
+bool tileIsInPath(Path path, ivec2 tileCoords) {
+    return all(greaterThanEqual(tileCoords, path.tileRect.xy)) &&
+        all(lessThan(tileCoords, path.tileRect.zw));
+}
+
+// The tiles from the one containing `minValue` to the one containing `maxValue`. A `maxValue` on a
+// tile edge belongs to the tile before it, unless the span is empty.
+ivec2 tileSpan(float minValue, float maxValue) {
+    int first = int(floor(minValue / TILE_SIZE));
+    int last = int(floor(maxValue / TILE_SIZE));
+    if (maxValue > minValue && float(last) * TILE_SIZE == maxValue)
+        last--;
+    return ivec2(first, last);
+}
+
+float solveXForY(vec4 segment, float y) {
+    float t = (y - segment.y) / (segment.w - segment.y);
+    return segment.x + t * (segment.z - segment.x);
+}
+
+float solveYForX(vec4 segment, float x) {
+    float t = (x - segment.x) / (segment.z - segment.x);
+    return segment.y + t * (segment.w - segment.y);
+}
+
+// Converts a fill to 4.8 fixed point relative to its tile and packs it as a
+// `FillBatchPrimitive`. Its alpha tile index is filled in by `patch_fills`.
+void addFill(Path path, vec4 segment, ivec2 tileCoords) {
+    if (!tileIsInPath(path, tileCoords))
+        return;
+
+    vec4 tileOrigin = vec4(tileCoords, tileCoords) * TILE_SIZE;
+    vec4 fixedSegment = floor((segment - tileOrigin) * 256.0 + 0.5);
+    uvec4 fixedPoints = uvec4(clamp(fixedSegment, 0.0, TILE_SIZE * 256.0 - 1.0));
+    if (fixedPoints.x == fixedPoints.z)
+        return;
+
+    uint fillIndex = atomicAdd(ioCounters[0], 1u);
+    if (fillIndex >= uint(uFillCapacity))
+        return;
+
+    uvec4 px = fixedPoints >> 8u, subpx = fixedPoints & 255u;
+    uint fromPx = px.x | (px.y << 4u), toPx = px.z | (px.w << 4u);
+    oFills[fillIndex] = uvec2(fromPx | (toPx << 8u) | (subpx.x << 16u) | (subpx.y << 24u),
+                              subpx.z | (subpx.w << 8u));
+
+    int fillTile = tileIndex(path, tileCoords);
+    oFillTiles[fillIndex] = uint(fillTile);
+    atomicOr(ioTiles[fillTile * 2 + 1], 1);
+}
+
+void binSegment(Path path, vec4 segment) {
+    ivec2 rows = tileSpan(min(segment.y, segment.w), max(segment.y, segment.w));
+    for (int tileY = rows.x; tileY <= rows.y; tileY++) {
+        // Clip the segment to the row, keeping its direction.
+        vec4 rowSegment = segment;
+        if (segment.y != segment.w) {
+            float top = float(tileY) * TILE_SIZE, bottom = top + TILE_SIZE;
+            vec2 y = clamp(segment.yw, top, bottom);
+            rowSegment = vec4(solveXForY(segment, y.x), y.x, solveXForY(segment, y.y), y.y);
+        }
+        if (rowSegment.x == rowSegment.z)
+            continue;
+
+        ivec2 columns = tileSpan(min(rowSegment.x, rowSegment.z), max(rowSegment.x, rowSegment.z));
+        for (int tileX = columns.x; tileX <= columns.y; tileX++) {
+            float left = float(tileX) * TILE_SIZE, right = left + TILE_SIZE;
+            vec2 x = clamp(rowSegment.xz, left, right);
+            vec4 fillSegment = vec4(x.x, solveYForX(rowSegment, x.x),
+                                    x.y, solveYForX(rowSegment, x.y));
+            addFill(path, fillSegment, ivec2(tileX, tileY));
+        }
+    }
+
+    // A segment crosses the top edges at `y` with `minY < y <= maxY`, so that a contour passing
+    // through a corner of a tile crosses it once.
+    if (segment.y == segment.w)
+        return;
+    int winding = segment.y < segment.w ? 1 : -1;
+    float maxY = max(segment.y, segment.w);
+    for (int tileY = int(floor(min(segment.y, segment.w) / TILE_SIZE)) + 1;
+         float(tileY) * TILE_SIZE <= maxY;
+         tileY++) {
+        if (tileY < path.tileRect.y || tileY >= path.tileRect.w)
+            continue;
+
+        float y = float(tileY) * TILE_SIZE;
+        float x = solveXForY(segment, y);
+        int tileX = clamp(int(floor(x / TILE_SIZE)), path.tileRect.x, path.tileRect.z - 1);
+        float right = float(tileX + 1) * TILE_SIZE;
+        vec4 fillSegment = winding > 0 ? vec4(right, y, x, y) : vec4(x, y, right, y);
+        addFill(path, fillSegment, ivec2(tileX, tileY));
+
+        ivec2 nextTileCoords = ivec2(tileX + 1, tileY);
+        if (tileIsInPath(path, nextTileCoords))
+            atomicAdd(ioTiles[tileIndex(path, nextTileCoords) * 2], winding);
+    }
+}
+
+void main() {
+    uint stride = gl_NumWorkGroups.x * gl_WorkGroupSize.x;
+    for (uint segmentIndex = gl_GlobalInvocationID.x;
+         segmentIndex < uint(uSegmentCount);
+         segmentIndex += stride) {
+        binSegment(iPaths[iSegmentPaths[segmentIndex]], iSegments[segmentIndex]);
+    }
+}
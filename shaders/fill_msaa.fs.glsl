@@ -0,0 +1,22 @@
+#version 330
+
+// pathfinder/shaders/fill_msaa.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+precision highp float;
+
+in float vWinding;
+
+out vec4 oFragColor;
+
+void main() {
+    // Each covered sample gets the full winding. Resolving averages the samples into coverage.
+    oFragColor = vec4(vWinding);
+}
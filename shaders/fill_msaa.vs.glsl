@@ -0,0 +1,55 @@
+#version 330
+
+// pathfinder/shaders/fill_msaa.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Draws the region between a line and the bottom of its tile, for rasterization with hardware
+// multisampling instead of the area lookup table.
+
+precision highp float;
+
+uniform vec2 uFramebufferSize;
+uniform vec2 uTileSize;
+
+in uvec2 aTessCoord;
+in uint aFromPx;
+in uint aToPx;
+in vec2 aFromSubpx;
+in vec2 aToSubpx;
+in uint aTileIndex;
+
+out float vWinding;
+
+vec2 computeTileOffset(uint tileIndex, float stencilTextureWidth) {
+    uint tilesPerRow = uint(stencilTextureWidth / uTileSize.x);
+    uvec2 tileOffset = uvec2(tileIndex % tilesPerRow, tileIndex / tilesPerRow);
+    return vec2(tileOffset) * uTileSize;
+}
+
+void main() {
+    vec2 tileOrigin = computeTileOffset(aTileIndex, uFramebufferSize.x);
+
+    vec2 from = vec2(aFromPx & 15u, aFromPx >> 4u) + aFromSubpx;
+    vec2 to = vec2(aToPx & 15u, aToPx >> 4u) + aToSubpx;
+
+    // The corners are the endpoints of the line and their projections onto the bottom edge.
+    vec2 position = aTessCoord.x == 0u ? from : to;
+    if (aTessCoord.y != 0u)
+        position.y = uTileSize.y;
+
+    // Match the sign of the coverage that the area lookup table produces.
+    vWinding = from.x < to.x ? -1.0 : 1.0;
+
+    vec2 globalPosition = (tileOrigin + position) / uFramebufferSize * 2.0 - 1.0;
+#ifdef PF_ORIGIN_UPPER_LEFT
+    globalPosition.y = -globalPosition.y;
+#endif
+    gl_Position = vec4(globalPosition, 0.0, 1.0);
+}
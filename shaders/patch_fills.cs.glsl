@@ -0,0 +1,50 @@
+#version 430
+
+// pathfinder/shaders/patch_fills.cs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Gives the fills that `bin` made the alpha tile indices that `propagate` chose. Fills in tiles
+// that didn't get places in the mask framebuffer are emptied, so that they cover nothing.
+
+precision highp float;
+
+#define NO_ALPHA_TILE 0xffffu
+
+layout(local_size_x = 64) in;
+
+uniform int uFillCapacity;
+
+layout(std430, binding = 0) restrict readonly buffer bTiles {
+    int iTiles[];
+};
+
+layout(std430, binding = 1) restrict buffer bFills {
+    uvec2 ioFills[];
+};
+
+layout(std430, binding = 2) restrict readonly buffer bFillTiles {
+    uint iFillTiles[];
+};
+
+layout(std430, binding = 3) restrict readonly buffer bCounters {
+    uint iCounters[];
+};
+
+void main() {
+    uint fillCount = min(iCounters[0], uint(uFillCapacity));
+    uint stride = gl_NumWorkGroups.x * gl_WorkGroupSize.x;
+    for (uint fillIndex = gl_GlobalInvocationID.x; fillIndex < fillCount; fillIndex += stride) {
+        uint alphaTileIndex = uint(iTiles[iFillTiles[fillIndex] * 2u + 1u]);
+        if (alphaTileIndex == NO_ALPHA_TILE)
+            ioFills[fillIndex] = uvec2(0u);
+        else
+            ioFills[fillIndex].y = (ioFills[fillIndex].y & 0xffffu) | (alphaTileIndex << 16u);
+    }
+}
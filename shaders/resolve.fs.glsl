@@ -0,0 +1,40 @@
+#version 330
+
+// pathfinder/shaders/resolve.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Converts the premultiplied linear-light color buffer to the encoding of the destination:
+// premultiplied sRGB, or, for extended-range destinations, premultiplied linear light unchanged.
+
+precision highp float;
+
+uniform sampler2D uSource;
+uniform int uEncodeSRGB;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+vec3 linearToSRGB(vec3 linear) {
+    vec3 low = linear * 12.92;
+    vec3 high = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return mix(low, high, step(vec3(0.0031308), linear));
+}
+
+void main() {
+    vec4 color = texture(uSource, vTexCoord);
+    if (uEncodeSRGB == 0) {
+        oFragColor = color;
+        return;
+    }
+
+    vec3 linear = color.a > 0.0 ? clamp(color.rgb / color.a, 0.0, 1.0) : vec3(0.0);
+    oFragColor = vec4(linearToSRGB(linear) * color.a, color.a);
+}
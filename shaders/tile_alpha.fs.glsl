@@ -10,11 +10,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#extension GL_GOOGLE_include_directive : enable
+
 precision highp float;
 
 uniform sampler2D uStencilTexture;
-uniform sampler2D uPaintTexture;
-uniform vec2 uPaintTextureSize;
 
 in vec2 vMaskTexCoord;
 in vec2 vColorTexCoord;
@@ -23,8 +23,13 @@ in vec4 vColor;
 
 out vec4 oFragColor;
 
+#include "tile_coverage.inc.glsl"
+#include "tile_paint.inc.glsl"
+
 void main() {
-    float coverage = abs(texture(uStencilTexture, vMaskTexCoord).r + vBackdrop);
-    vec4 color = texture(uPaintTexture, vColorTexCoord);
-    oFragColor = vec4(color.rgb, color.a * coverage);
+    float coverage = computeCoverage(texture(uStencilTexture, vMaskTexCoord).r + vBackdrop);
+    vec4 color = samplePaint(vColorTexCoord);
+    color.a *= coverage;
+    color.rgb *= color.a;
+    oFragColor = color;
 }
@@ -0,0 +1,157 @@
+#version 330
+
+// pathfinder/shaders/tile_alpha_blend.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Alpha tiles with the blend modes that can't be expressed as blend factors. These read a copy of
+// the destination and compute `B(Cb, Cs)` from the W3C "Compositing and Blending" specification.
+// The result is composited with source-over by the fixed-function blender.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+// The values of `uBlendMode`. See `blend_mode_uniform_data()`.
+#define BLEND_MODE_MULTIPLY     0
+#define BLEND_MODE_OVERLAY      1
+#define BLEND_MODE_DARKEN       2
+#define BLEND_MODE_LIGHTEN      3
+#define BLEND_MODE_COLOR_DODGE  4
+#define BLEND_MODE_COLOR_BURN   5
+#define BLEND_MODE_HARD_LIGHT   6
+#define BLEND_MODE_SOFT_LIGHT   7
+#define BLEND_MODE_DIFFERENCE   8
+#define BLEND_MODE_EXCLUSION    9
+#define BLEND_MODE_HUE          10
+#define BLEND_MODE_SATURATION   11
+#define BLEND_MODE_COLOR        12
+#define BLEND_MODE_LUMINOSITY   13
+
+uniform sampler2D uStencilTexture;
+// A premultiplied copy of the destination, covering `uDestRect` (origin, then size) in window
+// coordinates.
+uniform sampler2D uDestTexture;
+uniform vec4 uDestRect;
+uniform int uBlendMode;
+
+in vec2 vMaskTexCoord;
+in vec2 vColorTexCoord;
+in float vBackdrop;
+
+out vec4 oFragColor;
+
+#include "tile_coverage.inc.glsl"
+#include "tile_paint.inc.glsl"
+
+vec3 screen(vec3 dest, vec3 src) {
+    return dest + src - dest * src;
+}
+
+vec3 hardLight(vec3 dest, vec3 src) {
+    return mix(dest * 2.0 * src,
+               screen(dest, 2.0 * src - 1.0),
+               greaterThan(src, vec3(0.5)));
+}
+
+vec3 colorDodge(vec3 dest, vec3 src) {
+    vec3 dodge = mix(min(vec3(1.0), dest / (1.0 - src)),
+                     vec3(1.0),
+                     greaterThanEqual(src, vec3(1.0)));
+    return mix(dodge, vec3(0.0), lessThanEqual(dest, vec3(0.0)));
+}
+
+vec3 colorBurn(vec3 dest, vec3 src) {
+    vec3 burn = mix(1.0 - min(vec3(1.0), (1.0 - dest) / src),
+                    vec3(0.0),
+                    lessThanEqual(src, vec3(0.0)));
+    return mix(burn, vec3(1.0), greaterThanEqual(dest, vec3(1.0)));
+}
+
+vec3 softLight(vec3 dest, vec3 src) {
+    vec3 darkened = ((16.0 * dest - 12.0) * dest + 4.0) * dest;
+    vec3 d = mix(sqrt(dest), darkened, lessThanEqual(dest, vec3(0.25)));
+    return mix(dest - (1.0 - 2.0 * src) * dest * (1.0 - dest),
+               dest + (2.0 * src - 1.0) * (d - dest),
+               greaterThan(src, vec3(0.5)));
+}
+
+float lum(vec3 color) {
+    return dot(color, vec3(0.3, 0.59, 0.11));
+}
+
+float sat(vec3 color) {
+    return max(color.r, max(color.g, color.b)) - min(color.r, min(color.g, color.b));
+}
+
+vec3 clipColor(vec3 color) {
+    float l = lum(color);
+    float colorMin = min(color.r, min(color.g, color.b));
+    float colorMax = max(color.r, max(color.g, color.b));
+    if (colorMin < 0.0)
+        color = l + (color - l) * l / (l - colorMin);
+    if (colorMax > 1.0)
+        color = l + (color - l) * (1.0 - l) / (colorMax - l);
+    return color;
+}
+
+vec3 setLum(vec3 color, float l) {
+    return clipColor(color + (l - lum(color)));
+}
+
+vec3 setSat(vec3 color, float s) {
+    float colorMin = min(color.r, min(color.g, color.b));
+    float colorMax = max(color.r, max(color.g, color.b));
+    if (colorMax <= colorMin)
+        return vec3(0.0);
+    return (color - colorMin) * s / (colorMax - colorMin);
+}
+
+vec3 blend(vec3 dest, vec3 src) {
+    if (uBlendMode == BLEND_MODE_MULTIPLY)
+        return dest * src;
+    if (uBlendMode == BLEND_MODE_OVERLAY)
+        return hardLight(src, dest);
+    if (uBlendMode == BLEND_MODE_DARKEN)
+        return min(dest, src);
+    if (uBlendMode == BLEND_MODE_LIGHTEN)
+        return max(dest, src);
+    if (uBlendMode == BLEND_MODE_COLOR_DODGE)
+        return colorDodge(dest, src);
+    if (uBlendMode == BLEND_MODE_COLOR_BURN)
+        return colorBurn(dest, src);
+    if (uBlendMode == BLEND_MODE_HARD_LIGHT)
+        return hardLight(dest, src);
+    if (uBlendMode == BLEND_MODE_SOFT_LIGHT)
+        return softLight(dest, src);
+    if (uBlendMode == BLEND_MODE_DIFFERENCE)
+        return abs(dest - src);
+    if (uBlendMode == BLEND_MODE_EXCLUSION)
+        return dest + src - 2.0 * dest * src;
+    if (uBlendMode == BLEND_MODE_HUE)
+        return setLum(setSat(src, sat(dest)), lum(dest));
+    if (uBlendMode == BLEND_MODE_SATURATION)
+        return setLum(setSat(dest, sat(src)), lum(dest));
+    if (uBlendMode == BLEND_MODE_COLOR)
+        return setLum(src, lum(dest));
+    return setLum(dest, lum(src));
+}
+
+void main() {
+    float coverage = computeCoverage(texture(uStencilTexture, vMaskTexCoord).r + vBackdrop);
+    vec4 src = samplePaint(vColorTexCoord);
+
+    vec4 dest = texture(uDestTexture, (gl_FragCoord.xy - uDestRect.xy) / uDestRect.zw);
+    vec3 destColor = dest.a > 0.0 ? dest.rgb / dest.a : vec3(0.0);
+
+    // Where the destination is transparent, the source shows through unblended.
+    vec3 color = mix(src.rgb, clamp(blend(destColor, src.rgb), 0.0, 1.0), dest.a);
+    float alpha = src.a * coverage;
+    oFragColor = vec4(color * alpha, alpha);
+}
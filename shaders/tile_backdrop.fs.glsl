@@ -0,0 +1,41 @@
+#version 330
+
+// pathfinder/shaders/tile_backdrop.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Replaces the destination inside a path with a filtered copy of it, drawn with the alpha tile
+// vertex shader and no blending.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+uniform sampler2D uStencilTexture;
+// Copies of the destination before and after filtering, covering `uDestRect` (origin, then size)
+// in window coordinates.
+uniform sampler2D uDestTexture;
+uniform sampler2D uFilteredDestTexture;
+uniform vec4 uDestRect;
+
+in vec2 vMaskTexCoord;
+in vec2 vColorTexCoord;
+in float vBackdrop;
+
+out vec4 oFragColor;
+
+#include "tile_coverage.inc.glsl"
+
+void main() {
+    float coverage = computeCoverage(texture(uStencilTexture, vMaskTexCoord).r + vBackdrop);
+    vec2 destTexCoord = (gl_FragCoord.xy - uDestRect.xy) / uDestRect.zw;
+    oFragColor = mix(texture(uDestTexture, destTexCoord),
+                     texture(uFilteredDestTexture, destTexCoord),
+                     min(coverage, 1.0));
+}
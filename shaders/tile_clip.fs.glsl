@@ -0,0 +1,32 @@
+#version 330
+
+// pathfinder/shaders/tile_clip.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Marks the pixels inside a clip path, drawn with the alpha tile vertex shader into the stencil
+// buffer.
+
+precision highp float;
+
+uniform sampler2D uStencilTexture;
+
+in vec2 vMaskTexCoord;
+in vec2 vColorTexCoord;
+in float vBackdrop;
+
+out vec4 oFragColor;
+
+void main() {
+    // The stencil buffer holds one bit per pixel, so pixels more than half covered are inside.
+    float coverage = abs(texture(uStencilTexture, vMaskTexCoord).r + vBackdrop);
+    if (coverage < 0.5)
+        discard;
+    oFragColor = vec4(1.0);
+}
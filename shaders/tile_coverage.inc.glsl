@@ -0,0 +1,23 @@
+// pathfinder/shaders/tile_coverage.inc.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The fill rules of alpha tiles. See `FillRule`.
+#define FILL_RULE_WINDING   0
+#define FILL_RULE_EVEN_ODD  1
+
+uniform int uFillRule;
+
+// Returns how much of the pixel is inside the path, given the winding number accumulated in the
+// mask, which is fractional where edges cross the pixel.
+float computeCoverage(float winding) {
+    if (uFillRule == FILL_RULE_EVEN_ODD)
+        return 1.0 - abs(1.0 - mod(abs(winding), 2.0));
+    return abs(winding);
+}
@@ -0,0 +1,29 @@
+// pathfinder/shaders/tile_paint.inc.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The formats of the pages of the paint texture. See `PaintPageFormat`.
+#define PAINT_FORMAT_RGBA8  0
+#define PAINT_FORMAT_GRAY8  1
+#define PAINT_FORMAT_ALPHA8 2
+
+uniform sampler2D uPaintTexture;
+uniform vec2 uPaintTextureSize;
+uniform int uPaintFormat;
+
+// Returns the straight-alpha color of the paint at `texCoord`. One-channel pages hold either the
+// gray levels of opaque paints or the opacity of black ones.
+vec4 samplePaint(vec2 texCoord) {
+    vec4 texel = texture(uPaintTexture, texCoord);
+    if (uPaintFormat == PAINT_FORMAT_GRAY8)
+        return vec4(texel.rrr, 1.0);
+    if (uPaintFormat == PAINT_FORMAT_ALPHA8)
+        return vec4(vec3(0.0), texel.r);
+    return texel;
+}
@@ -10,15 +10,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-precision highp float;
+#extension GL_GOOGLE_include_directive : enable
 
-uniform sampler2D uPaintTexture;
-uniform vec2 uPaintTextureSize;
+precision highp float;
 
 in vec2 vColorTexCoord;
 
 out vec4 oFragColor;
 
+#include "tile_paint.inc.glsl"
+
 void main() {
-    oFragColor = texture(uPaintTexture, vColorTexCoord);
+    oFragColor = samplePaint(vColorTexCoord);
 }
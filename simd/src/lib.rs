@@ -8,20 +8,17 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![cfg_attr(pf_rustc_nightly, feature(link_llvm_intrinsics, platform_intrinsics))]
-#![cfg_attr(pf_rustc_nightly, feature(simd_ffi, stdsimd))]
-
 //! A minimal SIMD abstraction, usable outside of Pathfinder.
+//!
+//! Without the default `std` feature, this crate only needs `core`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[cfg(all(not(feature = "pf-no-simd"), pf_rustc_nightly, target_arch = "aarch64"))]
+#[cfg(all(not(feature = "pf-no-simd"), target_arch = "aarch64"))]
 pub use crate::arm as default;
 #[cfg(any(
     feature = "pf-no-simd",
-    not(any(
-        target_arch = "x86",
-        target_arch = "x86_64",
-        all(pf_rustc_nightly, target_arch = "aarch64")
-    ))
+    not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))
 ))]
 pub use crate::scalar as default;
 #[cfg(all(
@@ -30,9 +27,11 @@ pub use crate::scalar as default;
 ))]
 pub use crate::x86 as default;
 
-#[cfg(all(pf_rustc_nightly, target_arch = "aarch64"))]
+#[cfg(target_arch = "aarch64")]
 pub mod arm;
 mod extras;
+#[cfg(not(feature = "std"))]
+pub mod math;
 pub mod scalar;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub mod x86;
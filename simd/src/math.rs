@@ -0,0 +1,137 @@
+// pathfinder/simd/src/math.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Floating point functions that `core` lacks, for builds without the standard library.
+//!
+//! Importing `FloatExt` makes these available with the same names as the methods that `std`
+//! provides on `f32` and `f64`, so code can be written once for both kinds of build:
+//!
+//! ```ignore
+//! #[cfg(not(feature = "std"))]
+//! use pathfinder_simd::math::FloatExt;
+//! ```
+
+/// The methods of `f32` and `f64` that need the standard library, implemented with `libm`.
+pub trait FloatExt: Sized {
+    fn sqrt(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn trunc(self) -> Self;
+    fn fract(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn powi(self, exponent: i32) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn log2(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+}
+
+macro_rules! impl_float_ext {
+    ($float:ty, $sqrt:ident, $floor:ident, $ceil:ident, $round:ident, $trunc:ident,
+     $sin:ident, $cos:ident, $tan:ident, $asin:ident, $acos:ident, $atan:ident, $atan2:ident,
+     $pow:ident, $exp:ident, $log:ident, $log2:ident, $hypot:ident) => {
+        impl FloatExt for $float {
+            #[inline]
+            fn sqrt(self) -> $float {
+                libm::$sqrt(self)
+            }
+            #[inline]
+            fn floor(self) -> $float {
+                libm::$floor(self)
+            }
+            #[inline]
+            fn ceil(self) -> $float {
+                libm::$ceil(self)
+            }
+            #[inline]
+            fn round(self) -> $float {
+                libm::$round(self)
+            }
+            #[inline]
+            fn trunc(self) -> $float {
+                libm::$trunc(self)
+            }
+            #[inline]
+            fn fract(self) -> $float {
+                self - libm::$trunc(self)
+            }
+            #[inline]
+            fn sin(self) -> $float {
+                libm::$sin(self)
+            }
+            #[inline]
+            fn cos(self) -> $float {
+                libm::$cos(self)
+            }
+            #[inline]
+            fn sin_cos(self) -> ($float, $float) {
+                (libm::$sin(self), libm::$cos(self))
+            }
+            #[inline]
+            fn tan(self) -> $float {
+                libm::$tan(self)
+            }
+            #[inline]
+            fn asin(self) -> $float {
+                libm::$asin(self)
+            }
+            #[inline]
+            fn acos(self) -> $float {
+                libm::$acos(self)
+            }
+            #[inline]
+            fn atan(self) -> $float {
+                libm::$atan(self)
+            }
+            #[inline]
+            fn atan2(self, other: $float) -> $float {
+                libm::$atan2(self, other)
+            }
+            #[inline]
+            fn powf(self, exponent: $float) -> $float {
+                libm::$pow(self, exponent)
+            }
+            #[inline]
+            fn powi(self, exponent: i32) -> $float {
+                libm::$pow(self, exponent as $float)
+            }
+            #[inline]
+            fn exp(self) -> $float {
+                libm::$exp(self)
+            }
+            #[inline]
+            fn ln(self) -> $float {
+                libm::$log(self)
+            }
+            #[inline]
+            fn log2(self) -> $float {
+                libm::$log2(self)
+            }
+            #[inline]
+            fn hypot(self, other: $float) -> $float {
+                libm::$hypot(self, other)
+            }
+        }
+    }
+}
+
+impl_float_ext!(f32, sqrtf, floorf, ceilf, roundf, truncf, sinf, cosf, tanf, asinf, acosf, atanf,
+                atan2f, powf, expf, logf, log2f, hypotf);
+impl_float_ext!(f64, sqrt, floor, ceil, round, trunc, sin, cos, tan, asin, acos, atan, atan2, pow,
+                exp, log, log2, hypot);
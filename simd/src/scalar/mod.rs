@@ -8,9 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::f32;
-use std::fmt::{self, Debug, Formatter};
-use std::ops::{Add, BitAnd, BitOr, Div, Index, IndexMut, Mul, Shr, Sub};
+use core::f32;
+use core::fmt::{self, Debug, Formatter};
+use core::ops::{Add, BitAnd, BitOr, Div, Index, IndexMut, Mul, Shr, Sub};
+
+#[cfg(not(feature = "std"))]
+use crate::math::FloatExt;
 
 mod swizzle_f32x4;
 mod swizzle_i32x4;
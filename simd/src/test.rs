@@ -37,7 +37,11 @@ fn test_f32x4_accessors_and_mutators() {
 fn test_f32x4_basic_ops() {
     let a = F32x4::new(1.0, 3.0, 5.0, 7.0);
     let b = F32x4::new(2.0, 2.0, 6.0, 6.0);
+    // The precision of reciprocal estimates depends on the instruction set.
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), not(feature = "pf-no-simd")))]
     assert_eq!(a.approx_recip(), F32x4::new(0.99975586, 0.33325195, 0.19995117, 0.14282227));
+    #[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), not(feature = "pf-no-simd"))))]
+    assert!(a.approx_recip().approx_eq(F32x4::new(1.0, 1.0 / 3.0, 0.2, 1.0 / 7.0), 0.002));
     assert_eq!(a.min(b), F32x4::new(1.0, 2.0, 5.0, 6.0));
     assert_eq!(a.max(b), F32x4::new(2.0, 3.0, 6.0, 7.0));
     let c = F32x4::new(-1.0, 1.3, -20.0, 3.6);
@@ -689,3 +693,34 @@ fn test_f32x4s_basic_ops() {
     assert_eq!(c.ceil(), F32x4S::new(-1.0, 2.0, -20.0, 4.0));
     assert_eq!(c.to_i32x4().to_f32x4(), F32x4S::new(-1.0, 1.0, -20.0, 4.0));
 }
+
+// F32x8 and I32x8
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[test]
+fn test_f32x8_avx() {
+    use crate::x86::avx::{self, F32x8, I32x8};
+    use crate::x86::F32x4 as F32x4X;
+    if !avx::is_supported() {
+        return;
+    }
+    unsafe {
+        let a = F32x8::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0);
+        let b = F32x8::from_halves(F32x4X::new(1.0, 2.0, 3.0, 4.0),
+                                   F32x4X::new(5.0, 6.0, 7.0, 8.0));
+        assert_eq!(a, b);
+        assert_eq!(a.low(), F32x4X::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(a.high(), F32x4X::new(5.0, 6.0, 7.0, 8.0));
+        assert_eq!(a.zwxy(), F32x8::new(3.0, 4.0, 1.0, 2.0, 7.0, 8.0, 5.0, 6.0));
+        assert_eq!(a.concat_xy_zw(a.zwxy()),
+                   F32x8::new(1.0, 2.0, 1.0, 2.0, 5.0, 6.0, 5.0, 6.0));
+        assert_eq!(a * F32x8::splat(2.0) - a + a, a * F32x8::splat(2.0));
+        assert_eq!(a.clamp(F32x8::splat(2.0), F32x8::splat(7.0)),
+                   F32x8::new(2.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 7.0));
+
+        let c = F32x8::new(0.4, 1.6, 2.5, 3.5, 256.0, 512.0, 3840.0, 4095.0).to_i32x8();
+        assert_eq!(c, I32x8::new(0, 2, 2, 4, 256, 512, 3840, 4095));
+        let d = c & I32x8::splat(0xf00);
+        assert_eq!((d >> 8) | (d >> 4).yxwz(), I32x8::new(0, 0, 0, 0, 0x21, 0x12, 0xff, 0xff));
+    }
+}
@@ -0,0 +1,305 @@
+// pathfinder/simd/src/x86/avx.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Eight-lane types implemented with AVX2, for hot loops that are dispatched at runtime.
+//!
+//! The constructors are `unsafe`, because the CPU must support AVX2; check with `is_supported()`
+//! first. Once a value exists, the operations on it are safe. Callers should put their loops in a
+//! function marked `#[target_feature(enable = "avx2")]` so that these get inlined.
+//!
+//! As with the AVX instructions themselves, swizzles and concatenations act on each four-lane
+//! half separately.
+
+use crate::x86::{F32x4, I32x4};
+use core::fmt::{self, Debug, Formatter};
+use core::mem;
+use core::ops::{Add, BitAnd, BitOr, Index, Mul, Shr, Sub};
+
+#[cfg(target_pointer_width = "32")]
+use core::arch::x86::{__m256, __m256i};
+#[cfg(target_pointer_width = "32")]
+use core::arch::x86;
+#[cfg(target_pointer_width = "64")]
+use core::arch::x86_64::{__m256, __m256i};
+#[cfg(target_pointer_width = "64")]
+use core::arch::x86_64 as x86;
+
+/// Returns true if the CPU supports the instructions these types use.
+///
+/// This is always false if SIMD has been disabled with the `pf-no-simd` feature. Without the
+/// `std` feature, the CPU can't be queried, so this is true only if AVX2 was enabled at compile
+/// time.
+#[cfg(feature = "std")]
+#[inline]
+pub fn is_supported() -> bool {
+    !cfg!(feature = "pf-no-simd") && is_x86_feature_detected!("avx2")
+}
+
+#[cfg(not(feature = "std"))]
+#[inline]
+pub fn is_supported() -> bool {
+    !cfg!(feature = "pf-no-simd") && cfg!(target_feature = "avx2")
+}
+
+// Eight 32-bit floats
+
+#[derive(Clone, Copy)]
+pub struct F32x8(pub __m256);
+
+impl F32x8 {
+    // Constructors
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32, g: f32, h: f32) -> F32x8 {
+        let vector = [a, b, c, d, e, f, g, h];
+        F32x8(x86::_mm256_loadu_ps(vector.as_ptr()))
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[inline]
+    pub unsafe fn splat(x: f32) -> F32x8 {
+        F32x8(x86::_mm256_set1_ps(x))
+    }
+
+    /// Places `low` in lanes 0-3 and `high` in lanes 4-7.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[inline]
+    pub unsafe fn from_halves(low: F32x4, high: F32x4) -> F32x8 {
+        F32x8(x86::_mm256_set_m128(high.0, low.0))
+    }
+
+    // Basic operations
+
+    #[inline]
+    pub fn min(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_min_ps(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn max(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_max_ps(self.0, other.0)) }
+    }
+
+    #[inline]
+    pub fn clamp(self, min: F32x8, max: F32x8) -> F32x8 {
+        self.max(min).min(max)
+    }
+
+    #[inline]
+    pub fn floor(self) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_floor_ps(self.0)) }
+    }
+
+    // Halves
+
+    #[inline]
+    pub fn low(self) -> F32x4 {
+        unsafe { F32x4(x86::_mm256_castps256_ps128(self.0)) }
+    }
+
+    #[inline]
+    pub fn high(self) -> F32x4 {
+        unsafe { F32x4(x86::_mm256_extractf128_ps(self.0, 1)) }
+    }
+
+    // Swizzles and concatenations, within each half
+
+    #[inline]
+    pub fn zwxy(self) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_shuffle_ps(self.0, self.0, 0b0100_1110)) }
+    }
+
+    #[inline]
+    pub fn zwzw(self) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_shuffle_ps(self.0, self.0, 0b1110_1110)) }
+    }
+
+    #[inline]
+    pub fn concat_xy_xy(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_shuffle_ps(self.0, other.0, 0b0100_0100)) }
+    }
+
+    #[inline]
+    pub fn concat_xy_zw(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_shuffle_ps(self.0, other.0, 0b1110_0100)) }
+    }
+
+    #[inline]
+    pub fn concat_zw_zw(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_shuffle_ps(self.0, other.0, 0b1110_1110)) }
+    }
+
+    // Conversions
+
+    /// Converts these packed floats to integers via rounding.
+    #[inline]
+    pub fn to_i32x8(self) -> I32x8 {
+        unsafe { I32x8(x86::_mm256_cvtps_epi32(self.0)) }
+    }
+}
+
+impl Debug for F32x8 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
+    }
+}
+
+impl Index<usize> for F32x8 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, index: usize) -> &f32 {
+        unsafe { &mem::transmute::<&__m256, &[f32; 8]>(&self.0)[index] }
+    }
+}
+
+impl PartialEq for F32x8 {
+    #[inline]
+    fn eq(&self, other: &F32x8) -> bool {
+        unsafe {
+            let (this, other) = (mem::transmute::<__m256, [f32; 8]>(self.0),
+                                 mem::transmute::<__m256, [f32; 8]>(other.0));
+            this == other
+        }
+    }
+}
+
+impl Add<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn add(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_add_ps(self.0, other.0)) }
+    }
+}
+
+impl Mul<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn mul(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_mul_ps(self.0, other.0)) }
+    }
+}
+
+impl Sub<F32x8> for F32x8 {
+    type Output = F32x8;
+    #[inline]
+    fn sub(self, other: F32x8) -> F32x8 {
+        unsafe { F32x8(x86::_mm256_sub_ps(self.0, other.0)) }
+    }
+}
+
+// Eight 32-bit signed integers
+
+#[derive(Clone, Copy)]
+pub struct I32x8(pub __m256i);
+
+impl I32x8 {
+    // Constructors
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn new(a: i32, b: i32, c: i32, d: i32, e: i32, f: i32, g: i32, h: i32) -> I32x8 {
+        let vector = [a, b, c, d, e, f, g, h];
+        I32x8(x86::_mm256_loadu_si256(vector.as_ptr() as *const __m256i))
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[inline]
+    pub unsafe fn splat(x: i32) -> I32x8 {
+        I32x8(x86::_mm256_set1_epi32(x))
+    }
+
+    // Halves
+
+    #[inline]
+    pub fn low(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm256_castsi256_si128(self.0)) }
+    }
+
+    #[inline]
+    pub fn high(self) -> I32x4 {
+        unsafe { I32x4(x86::_mm256_extracti128_si256(self.0, 1)) }
+    }
+
+    // Swizzles, within each half
+
+    #[inline]
+    pub fn yxwz(self) -> I32x8 {
+        unsafe { I32x8(x86::_mm256_shuffle_epi32(self.0, 0b1011_0001)) }
+    }
+}
+
+impl Debug for I32x8 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "<{}, {}, {}, {}, {}, {}, {}, {}>",
+               self[0], self[1], self[2], self[3], self[4], self[5], self[6], self[7])
+    }
+}
+
+impl Index<usize> for I32x8 {
+    type Output = i32;
+    #[inline]
+    fn index(&self, index: usize) -> &i32 {
+        unsafe { &mem::transmute::<&__m256i, &[i32; 8]>(&self.0)[index] }
+    }
+}
+
+impl PartialEq for I32x8 {
+    #[inline]
+    fn eq(&self, other: &I32x8) -> bool {
+        unsafe {
+            let (this, other) = (mem::transmute::<__m256i, [i32; 8]>(self.0),
+                                 mem::transmute::<__m256i, [i32; 8]>(other.0));
+            this == other
+        }
+    }
+}
+
+impl BitAnd<I32x8> for I32x8 {
+    type Output = I32x8;
+    #[inline]
+    fn bitand(self, other: I32x8) -> I32x8 {
+        unsafe { I32x8(x86::_mm256_and_si256(self.0, other.0)) }
+    }
+}
+
+impl BitOr<I32x8> for I32x8 {
+    type Output = I32x8;
+    #[inline]
+    fn bitor(self, other: I32x8) -> I32x8 {
+        unsafe { I32x8(x86::_mm256_or_si256(self.0, other.0)) }
+    }
+}
+
+/// Logical shift right, as for `U32x4`. This shifts in zeroes, so there is no need for an
+/// unsigned eight-lane type.
+impl Shr<u32> for I32x8 {
+    type Output = I32x8;
+    #[inline]
+    fn shr(self, amount: u32) -> I32x8 {
+        unsafe { I32x8(x86::_mm256_srl_epi32(self.0, x86::_mm_cvtsi32_si128(amount as i32))) }
+    }
+}
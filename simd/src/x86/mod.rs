@@ -8,19 +8,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::cmp::PartialEq;
-use std::fmt::{self, Debug, Formatter};
-use std::mem;
-use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shr, Sub};
+use core::cmp::PartialEq;
+use core::fmt::{self, Debug, Formatter};
+use core::mem;
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Index, IndexMut, Mul, Not, Shr, Sub};
 
 #[cfg(target_pointer_width = "32")]
-use std::arch::x86::{__m128, __m128i};
+use core::arch::x86::{__m128, __m128i};
 #[cfg(target_pointer_width = "32")]
-use std::arch::x86;
+use core::arch::x86;
 #[cfg(target_pointer_width = "64")]
-use std::arch::x86_64::{__m128, __m128i};
+use core::arch::x86_64::{__m128, __m128i};
 #[cfg(target_pointer_width = "64")]
-use std::arch::x86_64 as x86;
+use core::arch::x86_64 as x86;
+
+pub mod avx;
 
 mod swizzle_f32x4;
 mod swizzle_i32x4;
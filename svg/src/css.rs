@@ -0,0 +1,240 @@
+// pathfinder/svg/src/css.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Applies `<style>` stylesheets, for SVGs that keep their fills and strokes in CSS classes.
+//!
+//! Before usvg parses a document, the declarations of the stylesheet rules that select each
+//! element are written into its `style` attribute, and the `<style>` elements are removed. usvg
+//! then sees only inline styles, which it resolves along with the presentation attributes as
+//! usual. Matching is done by `simplecss`, which supports type, class, ID, attribute, and
+//! universal selectors, descendant and child combinators, and `:first-child`.
+
+use roxmltree::{Document, Node};
+use simplecss::{AttributeOperator, Declaration, DeclarationTokenizer, Element, PseudoClass};
+use simplecss::{Rule, StyleSheet};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Returns `text` with the rules of its stylesheets moved into the `style` attributes of the
+/// elements they select.
+///
+/// Returns `text` unchanged if it has no stylesheets or can't be parsed, leaving usvg to report
+/// any errors.
+pub fn inline_stylesheets(text: &str) -> Cow<str> {
+    let document = match Document::parse(text) {
+        Ok(document) => document,
+        Err(_) => return Cow::Borrowed(text),
+    };
+
+    let mut style_sheet = StyleSheet::new();
+    let mut edits: Vec<(Range<usize>, String)> = vec![];
+    for node in document.descendants().filter(is_style_sheet) {
+        for kid in node.children() {
+            if let Some(kid_text) = kid.text() {
+                style_sheet.parse_more(kid_text);
+            }
+        }
+        edits.push((node.range(), String::new()));
+    }
+    if edits.is_empty() {
+        return Cow::Borrowed(text);
+    }
+
+    // Later rules win over earlier ones of the same specificity, so the sort must be stable.
+    style_sheet.rules.sort_by_key(|rule| rule.selector.specificity());
+
+    for node in document.descendants() {
+        if !node.is_element() || is_style_sheet(&node) {
+            continue;
+        }
+        if let Some(edit) = style_attribute_edit(text, node, &style_sheet.rules) {
+            edits.push(edit);
+        }
+    }
+
+    edits.sort_by_key(|&(ref range, _)| range.start);
+    let mut new_text = String::with_capacity(text.len());
+    let mut position = 0;
+    for (range, replacement) in edits {
+        new_text.push_str(&text[position..range.start]);
+        new_text.push_str(&replacement);
+        position = range.end;
+    }
+    new_text.push_str(&text[position..]);
+    Cow::Owned(new_text)
+}
+
+fn is_style_sheet(node: &Node) -> bool {
+    node.is_element() && node.tag_name().name() == "style" &&
+        node.attribute("type").map_or(true, |content_type| content_type == "text/css")
+}
+
+// Returns the edit that gives `node` the declarations of the rules in `rules` that select it,
+// or `None` if none do.
+fn style_attribute_edit(text: &str, node: Node, rules: &[Rule])
+                        -> Option<(Range<usize>, String)> {
+    let element = XmlElement(node);
+    let mut sheet_declarations: Vec<&Declaration> = vec![];
+    for rule in rules {
+        if rule.selector.matches(&element) {
+            sheet_declarations.extend(rule.declarations.iter());
+        }
+    }
+    if sheet_declarations.is_empty() {
+        return None;
+    }
+
+    let inline_style = node.attributes().iter().find(|attribute| attribute.name() == "style");
+    let inline_declarations: Vec<Declaration> = match inline_style {
+        None => vec![],
+        Some(attribute) => DeclarationTokenizer::from(attribute.value()).collect(),
+    };
+
+    // Inline declarations win over stylesheet ones, unless only the stylesheet ones are
+    // important. The last declaration of a property wins.
+    let mut style = String::new();
+    for &important in &[false, true] {
+        let sheet = sheet_declarations.iter().cloned();
+        for declaration in sheet.chain(inline_declarations.iter()) {
+            if declaration.important == important {
+                push_declaration(&mut style, declaration);
+            }
+        }
+    }
+
+    match inline_style {
+        Some(attribute) => Some((attribute.value_range(), style)),
+        None => {
+            // Add the attribute right after the tag name.
+            let name_start = node.range().start + 1;
+            let name_length = text[name_start..].find(|character: char| {
+                character.is_whitespace() || character == '/' || character == '>'
+            })?;
+            let name_end = name_start + name_length;
+            Some((name_end..name_end, format!(" style=\"{}\"", style)))
+        }
+    }
+}
+
+// Appends `declaration` to the value of a `style` attribute.
+fn push_declaration(style: &mut String, declaration: &Declaration) {
+    if !style.is_empty() {
+        style.push(';');
+    }
+    push_escaped(style, declaration.name);
+    style.push(':');
+    push_escaped(style, declaration.value);
+}
+
+fn push_escaped(string: &mut String, text: &str) {
+    for character in text.chars() {
+        match character {
+            '&' => string.push_str("&amp;"),
+            '"' => string.push_str("&quot;"),
+            '<' => string.push_str("&lt;"),
+            _ => string.push(character),
+        }
+    }
+}
+
+struct XmlElement<'a, 'input>(Node<'a, 'input>);
+
+impl<'a, 'input> Element for XmlElement<'a, 'input> {
+    #[inline]
+    fn parent_element(&self) -> Option<Self> {
+        self.0.parent_element().map(XmlElement)
+    }
+
+    #[inline]
+    fn prev_sibling_element(&self) -> Option<Self> {
+        self.0.prev_sibling_element().map(XmlElement)
+    }
+
+    #[inline]
+    fn has_local_name(&self, local_name: &str) -> bool {
+        self.0.tag_name().name() == local_name
+    }
+
+    fn attribute_matches(&self, local_name: &str, operator: AttributeOperator) -> bool {
+        match self.0.attribute(local_name) {
+            None => false,
+            Some(value) => operator.matches(value),
+        }
+    }
+
+    fn pseudo_class_matches(&self, class: PseudoClass) -> bool {
+        match class {
+            PseudoClass::FirstChild => self.prev_sibling_element().is_none(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::css::inline_stylesheets;
+    use std::borrow::Cow;
+
+    // Each case is a document and what it should look like with its stylesheets inlined.
+    const CASES: &[(&str, &str)] = &[
+        // Colors, by class.
+        (concat!(r#"<svg><style>.a { fill: #ff0000; stroke: rgb(0, 0, 255) }</style>"#,
+                 r#"<rect class="a"/></svg>"#),
+         r#"<svg><rect style="fill:#ff0000;stroke:rgb(0, 0, 255)" class="a"/></svg>"#),
+        // Fonts, by type, with quotes and ampersands escaped.
+        (concat!(r#"<svg><style>text { font: bold 12px serif; font-family: "A&amp;B" }</style>"#,
+                 r#"<text>x</text></svg>"#),
+         concat!(r#"<svg><text style="font:bold 12px serif;font-family:&quot;A&amp;B&quot;">"#,
+                 r#"x</text></svg>"#)),
+        // Inline declarations win over the stylesheet...
+        (r#"<svg><style>.a { fill: red }</style><rect class="a" style="fill: blue"/></svg>"#,
+         r#"<svg><rect class="a" style="fill:red;fill:blue"/></svg>"#),
+        // ...unless the stylesheet's are important.
+        (concat!(r#"<svg><style>.a { fill: red !important }</style>"#,
+                 r#"<rect class="a" style="fill: blue"/></svg>"#),
+         r#"<svg><rect class="a" style="fill:blue;fill:red"/></svg>"#),
+        // More specific rules win, wherever they are.
+        (r#"<svg><style>#b { fill: green } rect { fill: red }</style><rect id="b"/></svg>"#,
+         r#"<svg><rect style="fill:red;fill:green" id="b"/></svg>"#),
+        // Later rules win over earlier ones of the same specificity.
+        (r#"<svg><style>.a { fill: red } .b { fill: green }</style><rect class="b a"/></svg>"#,
+         r#"<svg><rect style="fill:red;fill:green" class="b a"/></svg>"#),
+        // Descendant and child combinators, and `:first-child`. The stylesheet comes last, since
+        // it would be the first child otherwise.
+        (concat!(r#"<svg><rect/><g><rect/></g><rect/>"#,
+                 r#"<style>g rect { fill: red } svg > rect:first-child { fill: blue }</style>"#,
+                 r#"</svg>"#),
+         r#"<svg><rect style="fill:blue"/><g><rect style="fill:red"/></g><rect/></svg>"#),
+        // Several stylesheets, in order.
+        (concat!(r#"<svg><style>rect { fill: red }</style><rect/>"#,
+                 r#"<style>rect { stroke: blue }</style></svg>"#),
+         r#"<svg><rect style="fill:red;stroke:blue"/></svg>"#),
+        // Stylesheets in other languages are left alone.
+        (r#"<svg><style type="text/less">rect { fill: red }</style><rect/></svg>"#,
+         r#"<svg><style type="text/less">rect { fill: red }</style><rect/></svg>"#),
+    ];
+
+    #[test]
+    fn test_inline_stylesheets() {
+        for &(text, expected) in CASES {
+            assert_eq!(inline_stylesheets(text), expected, "inlining {}", text);
+        }
+    }
+
+    #[test]
+    fn test_documents_without_stylesheets_are_borrowed() {
+        for text in &[r#"<svg><rect style="fill: red"/></svg>"#, "<svg><rect", "not xml"] {
+            match inline_stylesheets(text) {
+                Cow::Borrowed(borrowed) => assert_eq!(borrowed, *text),
+                Cow::Owned(owned) => panic!("{} became {}", text, owned),
+            }
+        }
+    }
+}
@@ -0,0 +1,539 @@
+// pathfinder/svg/src/filter.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! SVG filter effects.
+//!
+//! The filtered content is rasterized and filtered on the CPU, then drawn as an image. Only the
+//! primitives that drop shadows, blurs, and color adjustments are built from are supported:
+//! `<feGaussianBlur>`, `<feOffset>`, `<feFlood>`, `<feComposite>`, `<feMerge>`, and
+//! `<feColorMatrix>`. Other primitives pass their input through unchanged, with a warning.
+//!
+//! The `BackgroundImage` and `BackgroundAlpha` inputs are what's already drawn behind the
+//! filtered group, so that backdrop filters like the frosted glass of translucent panels can be
+//! built by blurring the background, compositing it `in` the `SourceAlpha`, and merging the
+//! `SourceGraphic` over it. The whole background counts, as with CSS `backdrop-filter`, whether or
+//! not an ancestor sets `enable-background`.
+//!
+//! TODO: Filter on the GPU instead.
+
+use crate::usvg_rect_to_euclid_rect;
+use pathfinder_color::ColorU;
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_content::raster;
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::Scene;
+use std::collections::HashMap;
+use std::sync::Arc;
+use usvg::{FeColorMatrixKind, FeCompositeOperator, Filter, FilterInput, FilterKind, Units};
+
+// Filter regions are rasterized at one pixel per scene unit, but no more than this many pixels
+// on a side.
+//
+// TODO: Rasterize at the resolution that the scene is rendered at.
+const MAX_FILTER_RASTER_LENGTH: f32 = 2048.0;
+
+/// The result of applying a filter to some content.
+pub(crate) struct FilterOutput {
+    /// The outline of the filter region, in scene coordinates.
+    pub(crate) outline: Outline,
+    /// The filtered image, drawn into the outline.
+    pub(crate) paint: Paint,
+    /// True if the content should be drawn as vectors on top of the image, because the filter
+    /// ends by merging the unfiltered content over the rest of its result.
+    pub(crate) draws_source_on_top: bool,
+}
+
+// A premultiplied RGBA image covering the filter region.
+type Raster = Vec<[f32; 4]>;
+
+/// Applies `filter` to the contents of `source`, drawn over `backdrop`.
+///
+/// `bounds` is the bounding box of the content in user space, `transform` maps user space to the
+/// scene, and `view_box` is the visible part of the scene. Primitives that can't be applied are
+/// reported in `warnings`.
+pub(crate) fn apply_filter(filter: &Filter,
+                           source: &Scene,
+                           backdrop: &Scene,
+                           bounds: RectF,
+                           transform: &Transform2F,
+                           view_box: RectF,
+                           warnings: &mut Vec<String>)
+                           -> Option<FilterOutput> {
+    // Find the visible part of the filter region, in the scene.
+    let region = usvg_rect_to_euclid_rect(&filter.rect);
+    let region = match filter.units {
+        Units::UserSpaceOnUse => region,
+        Units::ObjectBoundingBox => {
+            if bounds.width() == 0.0 || bounds.height() == 0.0 {
+                return None;
+            }
+            RectF::new(bounds.origin() + region.origin() * bounds.size(),
+                       region.size() * bounds.size())
+        }
+    };
+    let mut scene_region = RectF::from_points(*transform * region.origin(),
+                                              *transform * region.origin());
+    for &corner in &[region.upper_right(), region.lower_right(), region.lower_left()] {
+        scene_region = scene_region.union_point(*transform * corner);
+    }
+    let scene_region = scene_region.intersection(view_box)?.round_out();
+    if scene_region.width() <= 0.0 || scene_region.height() <= 0.0 {
+        return None;
+    }
+
+    let max_length = f32::max(scene_region.width(), scene_region.height());
+    let scale = f32::min(1.0, MAX_FILTER_RASTER_LENGTH / max_length);
+    let size = scene_region.size().scale(scale).ceil().to_i32();
+    let to_raster = Transform2F::from_uniform_scale(scale) *
+        Transform2F::from_translation(-scene_region.origin());
+
+    // Converts primitive lengths, like blur radii and offsets, to raster pixels.
+    let primitive_scale = match filter.primitive_units {
+        Units::UserSpaceOnUse => Vector2F::splat(1.0),
+        Units::ObjectBoundingBox => bounds.size(),
+    };
+    let to_raster_vector = |vector: Vector2F| {
+        let vector = vector * primitive_scale;
+        (*transform * vector - *transform * Vector2F::default()).scale(scale)
+    };
+    let to_raster_lengths = |lengths: Vector2F| {
+        Vector2F::new(to_raster_vector(Vector2F::new(lengths.x(), 0.0)).length(),
+                      to_raster_vector(Vector2F::new(0.0, lengths.y())).length())
+    };
+
+    let source_graphic = rasterize_scene(source, &to_raster, size);
+    // The backdrop is only rasterized if the filter uses it.
+    let mut background_image: Option<Raster> = None;
+    let mut results: HashMap<&str, Raster> = HashMap::new();
+    let mut last_result: Option<Raster> = None;
+    let mut draws_source_on_top = false;
+
+    for (primitive_index, primitive) in filter.children.iter().enumerate() {
+        // TODO: Support primitive subregions.
+        // TODO: Filter in linearRGB when `color-interpolation-filters` asks for it.
+        let mut input = |input: &FilterInput| -> Raster {
+            match *input {
+                FilterInput::SourceGraphic => source_graphic.clone(),
+                FilterInput::SourceAlpha => {
+                    source_graphic.iter().map(|pixel| [0.0, 0.0, 0.0, pixel[3]]).collect()
+                }
+                FilterInput::BackgroundImage => {
+                    background_image.get_or_insert_with(|| {
+                        rasterize_scene(backdrop, &to_raster, size)
+                    }).clone()
+                }
+                FilterInput::BackgroundAlpha => {
+                    let background_image = background_image.get_or_insert_with(|| {
+                        rasterize_scene(backdrop, &to_raster, size)
+                    });
+                    background_image.iter().map(|pixel| [0.0, 0.0, 0.0, pixel[3]]).collect()
+                }
+                FilterInput::Reference(ref name) => {
+                    match results.get(&**name).or(last_result.as_ref()) {
+                        Some(result) => result.clone(),
+                        None => source_graphic.clone(),
+                    }
+                }
+                _ => {
+                    warnings.push(format!("unsupported filter input {:?} in filter `{}`",
+                                          input,
+                                          filter.id));
+                    transparent_raster(size)
+                }
+            }
+        };
+
+        let result = match primitive.kind {
+            FilterKind::FeGaussianBlur(ref blur) => {
+                let std_dev = Vector2F::new(blur.std_dev_x.value() as f32,
+                                            blur.std_dev_y.value() as f32);
+                let mut result = input(&blur.input);
+                gaussian_blur(&mut result, size, to_raster_lengths(std_dev));
+                result
+            }
+            FilterKind::FeOffset(ref offset) => {
+                let offset_vector = Vector2F::new(offset.dx as f32, offset.dy as f32);
+                let offset_vector = to_raster_vector(offset_vector).round().to_i32();
+                translate(&input(&offset.input), size, offset_vector)
+            }
+            FilterKind::FeFlood(ref flood) => {
+                let (color, alpha) = (flood.color, flood.opacity.value() as f32);
+                let pixel = premultiply(color.red, color.green, color.blue, alpha);
+                vec![pixel; size.x() as usize * size.y() as usize]
+            }
+            FilterKind::FeComposite(ref composite) => {
+                let (source, destination) = (input(&composite.input1), input(&composite.input2));
+                self::composite(&source, &destination, &composite.operator)
+            }
+            FilterKind::FeColorMatrix(ref color_matrix) => {
+                let matrix = color_matrix_for_kind(&color_matrix.kind);
+                transform_colors(&input(&color_matrix.input), &matrix)
+            }
+            FilterKind::FeMerge(ref merge) => {
+                // If the content is merged over everything else at the end, draw it as vectors
+                // instead of rasterizing it.
+                let mut inputs = &merge.inputs[..];
+                if primitive_index + 1 == filter.children.len() {
+                    if let Some(FilterInput::SourceGraphic) = inputs.last() {
+                        inputs = &inputs[..(inputs.len() - 1)];
+                        draws_source_on_top = true;
+                    }
+                }
+                let mut result = transparent_raster(size);
+                for merge_input in inputs {
+                    result = composite(&input(merge_input), &result, &FeCompositeOperator::Over);
+                }
+                result
+            }
+            ref kind => {
+                let kind = format!("{:?}", kind);
+                warnings.push(format!("unsupported filter primitive {} in filter `{}`",
+                                      kind.split('(').next().unwrap_or(""),
+                                      filter.id));
+                match last_result {
+                    Some(ref last_result) => last_result.clone(),
+                    None => source_graphic.clone(),
+                }
+            }
+        };
+
+        results.insert(&primitive.result, result.clone());
+        last_result = Some(result);
+    }
+
+    let result = last_result.unwrap_or(source_graphic);
+    let pixels: Vec<ColorU> = result.iter().map(|&pixel| unpremultiply(pixel)).collect();
+    let image = Image::new(size, Arc::new(pixels));
+    if image.is_fully_transparent() && !draws_source_on_top {
+        return None;
+    }
+
+    let mut contour = Contour::new();
+    contour.push_endpoint(scene_region.origin());
+    contour.push_endpoint(scene_region.upper_right());
+    contour.push_endpoint(scene_region.lower_right());
+    contour.push_endpoint(scene_region.lower_left());
+    contour.close();
+    let mut outline = Outline::new();
+    outline.push_contour(contour);
+
+    let paint = Paint::Pattern(Pattern::new(image, to_raster.inverse()));
+    Some(FilterOutput { outline, paint, draws_source_on_top })
+}
+
+// Draws the paths of `scene` with source-over compositing.
+//
+// TODO: Apply the clip paths and blend modes of the paths.
+fn rasterize_scene(scene: &Scene, to_raster: &Transform2F, size: Vector2I) -> Raster {
+    let mut raster = transparent_raster(size);
+    let from_raster = to_raster.inverse();
+    let raster_rect = RectI::new(Vector2I::default(), size);
+    for (paint, outline, _, fill_rule) in scene.paths() {
+        let mut outline = outline.into_owned();
+        outline.transform(to_raster);
+        let rect = match outline.bounds().round_out().to_i32().intersection(raster_rect) {
+            None => continue,
+            Some(rect) => rect,
+        };
+
+        let coverage = raster::rasterize_coverage(&outline, rect, fill_rule);
+        for (index, &coverage) in coverage.iter().enumerate() {
+            if coverage <= 0.0 {
+                continue;
+            }
+            let position = rect.origin() + Vector2I::new(index as i32 % rect.width(),
+                                                         index as i32 / rect.width());
+            let point = from_raster * (position.to_f32() + Vector2F::splat(0.5));
+            let color = sample_paint(paint, point);
+            let source = premultiply(color.r, color.g, color.b, color.a as f32 / 255.0 * coverage);
+
+            let destination = &mut raster[(position.y() * size.x() + position.x()) as usize];
+            let source_alpha = source[3];
+            for (destination, source) in destination.iter_mut().zip(source.iter()) {
+                *destination = source + *destination * (1.0 - source_alpha);
+            }
+        }
+    }
+    raster
+}
+
+// Returns the color of `paint` at `point`, in scene coordinates.
+fn sample_paint(paint: &Paint, point: Vector2F) -> ColorU {
+    match *paint {
+        Paint::Color(color) => color,
+        Paint::Gradient(ref gradient) => gradient.sample_at(point),
+        Paint::Pattern(ref pattern) => {
+            let image = &pattern.image;
+            let position = (pattern.transform.inverse() * point).floor().to_i32();
+            if position.x() < 0 || position.y() < 0 || position.x() >= image.size().x() ||
+                    position.y() >= image.size().y() {
+                return ColorU::transparent_black();
+            }
+            image.pixels()[(position.y() * image.size().x() + position.x()) as usize]
+        }
+    }
+}
+
+fn gaussian_blur(raster: &mut Raster, size: Vector2I, std_dev: Vector2F) {
+    let mut channel_values = vec![0.0; raster.len()];
+    for channel in 0..4 {
+        for (value, pixel) in channel_values.iter_mut().zip(raster.iter()) {
+            *value = pixel[channel];
+        }
+        raster::gaussian_blur(&mut channel_values, size, std_dev);
+        for (value, pixel) in channel_values.iter().zip(raster.iter_mut()) {
+            pixel[channel] = *value;
+        }
+    }
+}
+
+// Moves the pixels of `raster` by `offset`, leaving transparent black behind.
+fn translate(raster: &Raster, size: Vector2I, offset: Vector2I) -> Raster {
+    let mut result = transparent_raster(size);
+    for y in 0..size.y() {
+        for x in 0..size.x() {
+            let (source_x, source_y) = (x - offset.x(), y - offset.y());
+            if source_x < 0 || source_y < 0 || source_x >= size.x() || source_y >= size.y() {
+                continue;
+            }
+            result[(y * size.x() + x) as usize] =
+                raster[(source_y * size.x() + source_x) as usize];
+        }
+    }
+    result
+}
+
+// Composites `source` with `destination` as `<feComposite>` does.
+fn composite(source: &Raster, destination: &Raster, operator: &FeCompositeOperator) -> Raster {
+    source.iter().zip(destination.iter()).map(|(source, destination)| {
+        let (source_alpha, destination_alpha) = (source[3], destination[3]);
+        let mut result = [0.0; 4];
+        for (result, (&s, &d)) in result.iter_mut().zip(source.iter().zip(destination.iter())) {
+            *result = match *operator {
+                FeCompositeOperator::Over => s + d * (1.0 - source_alpha),
+                FeCompositeOperator::In => s * destination_alpha,
+                FeCompositeOperator::Out => s * (1.0 - destination_alpha),
+                FeCompositeOperator::Atop => {
+                    s * destination_alpha + d * (1.0 - source_alpha)
+                }
+                FeCompositeOperator::Xor => {
+                    s * (1.0 - destination_alpha) + d * (1.0 - source_alpha)
+                }
+                FeCompositeOperator::Arithmetic { k1, k2, k3, k4 } => {
+                    let value = k1 as f32 * s * d + k2 as f32 * s + k3 as f32 * d + k4 as f32;
+                    f32::min(f32::max(value, 0.0), 1.0)
+                }
+            };
+        }
+
+        // Arithmetic compositing can yield colors brighter than their alpha allows.
+        let alpha = result[3];
+        for value in &mut result[..3] {
+            *value = f32::min(*value, alpha);
+        }
+        result
+    }).collect()
+}
+
+// The 5×4 matrix, in row-major order, that `<feColorMatrix>` multiplies unpremultiplied RGBA
+// colors by, with an extra column of offsets. The coefficients are the ones the SVG specification
+// gives.
+fn color_matrix_for_kind(kind: &FeColorMatrixKind) -> [f32; 20] {
+    match *kind {
+        FeColorMatrixKind::Matrix(ref values) if values.len() == 20 => {
+            let mut matrix = [0.0; 20];
+            for (element, &value) in matrix.iter_mut().zip(values.iter()) {
+                *element = value as f32;
+            }
+            matrix
+        }
+        FeColorMatrixKind::Matrix(_) => [
+            1.0, 0.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ],
+        FeColorMatrixKind::Saturate(ref saturation) => {
+            let s = saturation.value() as f32;
+            [
+                0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.0, 0.0,
+                0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.0, 0.0,
+                0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.0, 0.0,
+                0.0,               0.0,               0.0,               1.0, 0.0,
+            ]
+        }
+        FeColorMatrixKind::HueRotate(degrees) => {
+            let (sin, cos) = (degrees as f32).to_radians().sin_cos();
+            [
+                0.213 + 0.787 * cos - 0.213 * sin, 0.715 - 0.715 * cos - 0.715 * sin,
+                    0.072 - 0.072 * cos + 0.928 * sin, 0.0, 0.0,
+                0.213 - 0.213 * cos + 0.143 * sin, 0.715 + 0.285 * cos + 0.140 * sin,
+                    0.072 - 0.072 * cos - 0.283 * sin, 0.0, 0.0,
+                0.213 - 0.213 * cos - 0.787 * sin, 0.715 - 0.715 * cos + 0.715 * sin,
+                    0.072 + 0.928 * cos + 0.072 * sin, 0.0, 0.0,
+                0.0, 0.0, 0.0, 1.0, 0.0,
+            ]
+        }
+        FeColorMatrixKind::LuminanceToAlpha => [
+            0.0,    0.0,    0.0,    0.0, 0.0,
+            0.0,    0.0,    0.0,    0.0, 0.0,
+            0.0,    0.0,    0.0,    0.0, 0.0,
+            0.2125, 0.7154, 0.0721, 0.0, 0.0,
+        ],
+    }
+}
+
+// Multiplies the unpremultiplied colors of `raster` by `matrix`, as `<feColorMatrix>` does.
+fn transform_colors(raster: &Raster, matrix: &[f32; 20]) -> Raster {
+    raster.iter().map(|pixel| {
+        let alpha = pixel[3];
+        let color = if alpha > 0.0 {
+            [pixel[0] / alpha, pixel[1] / alpha, pixel[2] / alpha, alpha]
+        } else {
+            [0.0; 4]
+        };
+
+        let mut result = [0.0; 4];
+        for (channel, result) in result.iter_mut().enumerate() {
+            let row = &matrix[(channel * 5)..(channel * 5 + 5)];
+            let value = row[0] * color[0] + row[1] * color[1] + row[2] * color[2] +
+                row[3] * color[3] + row[4];
+            *result = f32::min(f32::max(value, 0.0), 1.0);
+        }
+
+        let alpha = result[3];
+        [result[0] * alpha, result[1] * alpha, result[2] * alpha, alpha]
+    }).collect()
+}
+
+#[inline]
+fn transparent_raster(size: Vector2I) -> Raster {
+    vec![[0.0; 4]; size.x() as usize * size.y() as usize]
+}
+
+#[inline]
+fn premultiply(r: u8, g: u8, b: u8, alpha: f32) -> [f32; 4] {
+    [r as f32 / 255.0 * alpha, g as f32 / 255.0 * alpha, b as f32 / 255.0 * alpha, alpha]
+}
+
+fn unpremultiply(pixel: [f32; 4]) -> ColorU {
+    let alpha = f32::min(pixel[3], 1.0);
+    if alpha <= 0.0 {
+        return ColorU::transparent_black();
+    }
+    let to_u8 = |value: f32| (f32::min(value / alpha, 1.0) * 255.0).round() as u8;
+    ColorU {
+        r: to_u8(pixel[0]),
+        g: to_u8(pixel[1]),
+        b: to_u8(pixel[2]),
+        a: (alpha * 255.0).round() as u8,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::BuiltSVG;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::pattern::Pattern;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::Vector2F;
+    use pathfinder_renderer::paint::Paint;
+    use usvg::Options;
+
+    // Builds a 100×100 document with a red square from (30, 30) to (70, 70), filtered by the
+    // primitives `primitives`.
+    fn filtered_square(primitives: &str) -> BuiltSVG {
+        let svg = format!(concat!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" "#,
+                                  r#"height="100"><filter id="f" x="-50%" y="-50%" width="200%" "#,
+                                  r#"height="200%">{}</filter><rect x="30" y="30" width="40" "#,
+                                  r#"height="40" fill="red" filter="url(#f)"/></svg>"#),
+                          primitives);
+        BuiltSVG::from_data(svg.as_bytes(), &Options::default()).unwrap()
+    }
+
+    // The names and bounds of the paths of the scene, and the pattern of the first.
+    fn paths(built_svg: &BuiltSVG) -> (Vec<(String, RectF)>, Pattern) {
+        let paths: Vec<_> = built_svg.scene.paths().map(|(paint, outline, name, _)| {
+            (paint.clone(), name.to_owned(), outline.bounds())
+        }).collect();
+        let pattern = match paths[0].0 {
+            Paint::Pattern(ref pattern) => pattern.clone(),
+            _ => panic!("expected the filter to draw an image"),
+        };
+        (paths.into_iter().map(|(_, name, bounds)| (name, bounds)).collect(), pattern)
+    }
+
+    // The color of the pixel of `pattern` at `point`, in scene coordinates.
+    fn pixel_at(pattern: &Pattern, point: Vector2F) -> ColorU {
+        let position = (pattern.transform.inverse() * point).floor().to_i32();
+        let size = pattern.image.size();
+        pattern.image.pixels()[(position.y() * size.x() + position.x()) as usize]
+    }
+
+    #[test]
+    fn test_gaussian_blur() {
+        let built_svg = filtered_square(r#"<feGaussianBlur stdDeviation="4"/>"#);
+        assert!(built_svg.warnings.is_empty(), "{:?}", built_svg.warnings);
+
+        // The filter region replaces the square with one image.
+        let (paths, pattern) = paths(&built_svg);
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].0.starts_with("Filter("));
+        assert_eq!(paths[0].1, RectF::new(Vector2F::splat(10.0), Vector2F::splat(80.0)));
+
+        // The inside stays red, the edges are half covered, and the blur fades out beyond them.
+        let center = pixel_at(&pattern, Vector2F::splat(50.0));
+        assert_eq!(center, ColorU { r: 255, g: 0, b: 0, a: 255 });
+        let edge = pixel_at(&pattern, Vector2F::new(30.0, 50.0));
+        assert_eq!(edge.r, 255);
+        assert!(edge.a > 96 && edge.a < 160, "{:?}", edge);
+        assert!(pixel_at(&pattern, Vector2F::new(24.0, 50.0)).a > 0);
+        assert_eq!(pixel_at(&pattern, Vector2F::new(11.0, 50.0)).a, 0);
+    }
+
+    #[test]
+    fn test_drop_shadow() {
+        let built_svg = filtered_square(concat!(
+            r#"<feOffset in="SourceAlpha" dx="10" dy="0"/><feGaussianBlur stdDeviation="1"/>"#,
+            r#"<feMerge><feMergeNode/><feMergeNode in="SourceGraphic"/></feMerge>"#));
+        assert!(built_svg.warnings.is_empty(), "{:?}", built_svg.warnings);
+
+        // The shadow is drawn as an image, with the square on top of it as a vector path.
+        let (paths, pattern) = paths(&built_svg);
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].0.starts_with("Filter("));
+        assert_eq!(paths[1].1, RectF::new(Vector2F::splat(30.0), Vector2F::splat(40.0)));
+
+        assert_eq!(pixel_at(&pattern, Vector2F::new(75.0, 50.0)),
+                   ColorU { r: 0, g: 0, b: 0, a: 255 });
+        assert_eq!(pixel_at(&pattern, Vector2F::new(35.0, 50.0)).a, 0);
+        assert_eq!(pixel_at(&pattern, Vector2F::new(85.0, 50.0)).a, 0);
+    }
+
+    #[test]
+    fn test_unsupported_primitives_are_skipped() {
+        let built_svg = filtered_square(concat!(r#"<feMorphology radius="2"/>"#,
+                                                r#"<feGaussianBlur stdDeviation="4"/>"#));
+        assert_eq!(built_svg.warnings.len(), 1);
+        assert!(built_svg.warnings[0].contains("FeMorphology"), "{:?}", built_svg.warnings);
+
+        // The rest of the filter still applies to the square.
+        let (paths, pattern) = paths(&built_svg);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(pixel_at(&pattern, Vector2F::splat(50.0)),
+                   ColorU { r: 255, g: 0, b: 0, a: 255 });
+        let edge = pixel_at(&pattern, Vector2F::new(30.0, 50.0));
+        assert!(edge.a > 96 && edge.a < 160, "{:?}", edge);
+    }
+}
@@ -9,12 +9,36 @@
 // except according to those terms.
 
 //! Converts a subset of SVG to a Pathfinder scene.
+//!
+//! With the `pf-text` feature, which is on by default, usvg lays out `<text>` and `<tspan>`
+//! elements, including their positioning, anchoring, fills, and strokes, and converts them to
+//! paths with the fonts that its options find. Without it, text isn't drawn.
+//!
+//! With the `pf-image` feature, PNG and JPEG images in `<image>` elements, whether embedded as
+//! data URLs or referred to by path, are decoded and drawn. SVG images aren't drawn.
+//!
+//! Stylesheets in `<style>` elements are applied to the elements that their rules select when
+//! parsing with `BuiltSVG::from_data()`.
+//!
+//! usvg also resolves `<use>` references to `<defs>` and `<symbol>` content, producing groups
+//! with the referencing element's transform and styles and clipped to the referenced viewport.
 
 #[macro_use]
 extern crate bitflags;
 
+pub mod css;
+mod filter;
+
+// Trees passed to `BuiltSVG` must come from this version of usvg.
+pub use usvg;
+
 use pathfinder_color::ColorU;
-use pathfinder_content::outline::Outline;
+use pathfinder_content::boolean::BooleanOp;
+use pathfinder_content::dash::OutlineDash;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientWrap};
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern};
 use pathfinder_content::segment::{Segment, SegmentFlags};
 use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
 use pathfinder_content::transform::Transform2FPathIter;
@@ -23,18 +47,43 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_renderer::paint::Paint;
-use pathfinder_renderer::scene::{PathObject, Scene};
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, PathObject, Scene};
 use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::mem;
-use usvg::{Color as SvgColor, LineCap as UsvgLineCap, LineJoin as UsvgLineJoin, Node, NodeExt};
-use usvg::{NodeKind, Opacity, Paint as UsvgPaint, PathSegment as UsvgPathSegment};
-use usvg::{Rect as UsvgRect, Transform as UsvgTransform, Tree, Visibility};
+use std::str;
+use usvg::{Align, AspectRatio, BaseGradient, Color as SvgColor, FillRule as UsvgFillRule};
+use usvg::{Filter as UsvgFilter, LineCap as UsvgLineCap, LineJoin as UsvgLineJoin, Node, NodeExt};
+use usvg::{Error as UsvgError, Image as UsvgImage, NodeKind, Opacity, Options as UsvgOptions};
+use usvg::Paint as UsvgPaint;
+use usvg::{PathSegment as UsvgPathSegment, Pattern as UsvgPattern, Rect as UsvgRect};
+use usvg::{SpreadMethod, Transform as UsvgTransform, Tree, Units, ViewBox, Visibility};
 
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 
+// Patterns that would take more copies of their tile than this to fill a path aren't drawn.
+const MAX_PATTERN_TILE_COUNT: i64 = 4096;
+
 pub struct BuiltSVG {
     pub scene: Scene,
     pub result_flags: BuildResultFlags,
+    /// Descriptions of the parts of the SVG that were drawn only approximately, like unsupported
+    /// filter primitives.
+    pub warnings: Vec<String>,
+    /// Maps the user space of the root `<svg>` element to scene coordinates. Its inverse maps
+    /// points in the scene, like the positions of input events, back to SVG coordinates.
+    pub transform: Transform2F,
+    // The copies of `<pattern>` tiles whose content is being drawn, innermost last.
+    pattern_tiles: Vec<PatternTile>,
+    // The intersection of the clip paths of the groups being drawn and the paths that the
+    // patterns being drawn fill.
+    clip_path: Option<ClipPathId>,
+}
+
+// One copy of the tile of a pattern, drawn into the path that the pattern fills.
+struct PatternTile {
+    // The tile, in scene coordinates. Its content is clipped to it.
+    polygon: [Vector2F; 4],
+    opacity: f32,
 }
 
 bitflags! {
@@ -60,21 +109,48 @@ bitflags! {
 }
 
 impl BuiltSVG {
-    pub fn from_tree(tree: Tree) -> BuiltSVG {
+    /// Parses SVG data with the given usvg options and converts it to a scene.
+    ///
+    /// The rules of `<style>` elements are applied first, as `css::inline_stylesheets()`
+    /// describes. Compressed data is passed to usvg as it is.
+    pub fn from_data(data: &[u8], options: &UsvgOptions) -> Result<BuiltSVG, UsvgError> {
+        let tree = match str::from_utf8(data) {
+            Ok(text) => Tree::from_data(css::inline_stylesheets(text).as_bytes(), options)?,
+            Err(_) => Tree::from_data(data, options)?,
+        };
+        Ok(BuiltSVG::from_tree(&tree))
+    }
+
+    /// Converts a tree that usvg has already parsed, or that the application has built or
+    /// modified, to a scene. The tree isn't consumed, so it can be converted again after further
+    /// changes.
+    pub fn from_tree(tree: &Tree) -> BuiltSVG {
         Self::from_tree_with_transformation(tree, Transform2F::default())
     }
-    pub fn from_tree_with_transformation(tree: Tree, global_transform: Transform2F) -> BuiltSVG {
+
+    /// Like `from_tree()`, but with `global_transform` applied to the whole SVG.
+    pub fn from_tree_with_transformation(tree: &Tree, global_transform: Transform2F) -> BuiltSVG {
         let mut built_svg = BuiltSVG {
             scene: Scene::new(),
             result_flags: BuildResultFlags::empty(),
+            warnings: vec![],
+            transform: global_transform,
+            pattern_tiles: vec![],
+            clip_path: None,
         };
 
         let root = &tree.root();
         match *root.borrow() {
             NodeKind::Svg(ref svg) => {
-                built_svg.scene.set_view_box(usvg_rect_to_euclid_rect(&svg.view_box.rect));
+                // The `viewBox` is fit to the viewport that `width` and `height` specify, as
+                // `preserveAspectRatio` specifies.
+                let size = Vector2F::new(svg.size.width() as f32, svg.size.height() as f32);
+                built_svg.scene.set_view_box(RectF::new(Vector2F::default(), size));
+                built_svg.transform = global_transform * view_box_transform(&svg.view_box, size);
+
+                let transform = built_svg.transform;
                 for kid in root.children() {
-                    built_svg.process_node(&kid, &global_transform);
+                    built_svg.process_node(tree, &kid, &transform);
                 }
             }
             _ => unreachable!(),
@@ -83,52 +159,81 @@ impl BuiltSVG {
         built_svg
     }
 
-    fn process_node(&mut self, node: &Node, transform: &Transform2F) {
+    fn process_node(&mut self, tree: &Tree, node: &Node, transform: &Transform2F) {
         let node_transform = usvg_transform_to_transform_2d(&node.transform());
-        let transform = node_transform * *transform;
+        let transform = *transform * node_transform;
 
         match *node.borrow() {
             NodeKind::Group(ref group) => {
-                if group.clip_path.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_CLIP_PATH_ATTR);
-                }
-                if group.filter.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
-                }
                 if group.mask.is_some() {
                     self.result_flags
                         .insert(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
                 }
 
-                for kid in node.children() {
-                    self.process_node(&kid, &transform)
+                // usvg expands `<use>` elements into groups, and the viewports of the `<symbol>`
+                // and `<svg>` elements that they refer to into clip paths.
+                let outer_clip_path = self.clip_path;
+                if let Some(ref clip_path_id) = group.clip_path {
+                    match clip_outline(tree, node, clip_path_id, &transform) {
+                        None => {
+                            self.result_flags
+                                .insert(BuildResultFlags::UNSUPPORTED_CLIP_PATH_ATTR);
+                        }
+                        Some(outline) => {
+                            let outline = match outer_clip_path {
+                                None => outline,
+                                Some(clip_path) => self.scene.clip_path(clip_path).clip(&outline),
+                            };
+                            if outline.contours().is_empty() {
+                                return;
+                            }
+                            let clip_path = ClipPath::new(outline, FillRule::Winding);
+                            self.clip_path = Some(self.scene.push_clip_path(clip_path));
+                        }
+                    }
+                }
+
+                let filter_node = group.filter.as_ref().and_then(|id| tree.defs_by_id(id));
+                let filter_kind = filter_node.as_ref().map(|filter_node| filter_node.borrow());
+                match filter_kind.as_ref().map(|kind| &**kind) {
+                    Some(NodeKind::Filter(filter)) => {
+                        self.push_filtered_group(tree, node, filter, &transform);
+                    }
+                    _ => {
+                        if group.filter.is_some() {
+                            self.result_flags
+                                .insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
+                        }
+                        for kid in node.children() {
+                            self.process_node(tree, &kid, &transform)
+                        }
+                    }
                 }
+
+                self.clip_path = outer_clip_path;
             }
             NodeKind::Path(ref path) if path.visibility == Visibility::Visible => {
-                if let Some(ref fill) = path.fill {
-                    let style = self.scene.push_paint(&Paint::from_svg_paint(
-                        &fill.paint,
-                        fill.opacity,
-                        &mut self.result_flags,
-                    ));
+                // Object bounding box units for paint servers are relative to the bounds of the
+                // fill, even for strokes.
+                let segments = UsvgPathToSegments::new(path.data.iter().cloned());
+                let bounds = Outline::from_segments(segments).bounds();
 
+                if let Some(ref fill) = path.fill {
                     let path = UsvgPathToSegments::new(path.data.iter().cloned());
                     let path = Transform2FPathIter::new(path, &transform);
                     let outline = Outline::from_segments(path);
 
                     let name = format!("Fill({})", node.id());
-                    self.scene.push_path(PathObject::new(outline, style, name));
+                    self.push_painted_path(tree,
+                                           outline,
+                                           &fill.paint,
+                                           fill.opacity,
+                                           bounds,
+                                           &transform,
+                                           name);
                 }
 
                 if let Some(ref stroke) = path.stroke {
-                    let style = self.scene.push_paint(&Paint::from_svg_paint(
-                        &stroke.paint,
-                        stroke.opacity,
-                        &mut self.result_flags,
-                    ));
-
                     let stroke_style = StrokeStyle {
                         line_width: f32::max(stroke.width.value() as f32, HAIRLINE_STROKE_WIDTH),
                         line_cap: LineCap::from_usvg_line_cap(stroke.linecap),
@@ -137,7 +242,20 @@ impl BuiltSVG {
                     };
 
                     let path = UsvgPathToSegments::new(path.data.iter().cloned());
-                    let outline = Outline::from_segments(path);
+                    let mut outline = Outline::from_segments(path);
+
+                    if let Some(ref dash_array) = stroke.dasharray {
+                        let mut dashes: Vec<f32> = dash_array.iter().map(|&dash| dash as f32)
+                                                                    .collect();
+                        // Per SVG, an odd number of dashes is repeated to yield an even number.
+                        if dashes.len() % 2 == 1 {
+                            dashes.extend_from_slice(&dashes.clone());
+                        }
+                        let mut dash = OutlineDash::new(&outline, &dashes, stroke.dashoffset);
+                        dash.set_restart_per_contour(true);
+                        dash.dash();
+                        outline = dash.into_outline();
+                    }
 
                     let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
                     stroke_to_fill.offset();
@@ -145,50 +263,259 @@ impl BuiltSVG {
                     outline.transform(&transform);
 
                     let name = format!("Stroke({})", node.id());
-                    self.scene.push_path(PathObject::new(outline, style, name));
+                    self.push_painted_path(tree,
+                                           outline,
+                                           &stroke.paint,
+                                           stroke.opacity,
+                                           bounds,
+                                           &transform,
+                                           name);
                 }
             }
             NodeKind::Path(..) => {}
-            NodeKind::ClipPath(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_CLIP_PATH_NODE);
-            }
             NodeKind::Defs { .. } => {
-                if node.has_children() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_DEFS_NODE);
+                // Definitions are only drawn where they're referenced, but report the ones we
+                // can't draw.
+                for kid in node.children() {
+                    self.process_node(tree, &kid, &transform)
                 }
             }
-            NodeKind::Filter(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_FILTER_NODE);
-            }
-            NodeKind::Image(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
+            NodeKind::Image(ref image) if image.visibility == Visibility::Visible => {
+                self.push_image(image, &transform);
             }
-            NodeKind::LinearGradient(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_LINEAR_GRADIENT_NODE);
+            NodeKind::Image(..) => {}
+            NodeKind::ClipPath(..) | NodeKind::Filter(..) | NodeKind::LinearGradient(..) |
+            NodeKind::Pattern(..) | NodeKind::RadialGradient(..) => {
+                // Drawn where other nodes refer to them.
             }
             NodeKind::Mask(..) => {
                 self.result_flags
                     .insert(BuildResultFlags::UNSUPPORTED_MASK_NODE);
             }
-            NodeKind::Pattern(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
-            }
-            NodeKind::RadialGradient(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_RADIAL_GRADIENT_NODE);
-            }
             NodeKind::Svg(..) => {
                 self.result_flags
                     .insert(BuildResultFlags::UNSUPPORTED_NESTED_SVG_NODE);
             }
         }
     }
+
+    // `bounds` is the bounding box of the filled path, in user space, and `transform` maps user
+    // space to the scene.
+    fn push_painted_path(&mut self,
+                         tree: &Tree,
+                         outline: Outline,
+                         svg_paint: &UsvgPaint,
+                         opacity: Opacity,
+                         bounds: RectF,
+                         transform: &Transform2F,
+                         name: String) {
+        if let UsvgPaint::Link(ref id) = *svg_paint {
+            if let Some(node) = tree.defs_by_id(id) {
+                if let NodeKind::Pattern(ref pattern) = *node.borrow() {
+                    self.push_pattern_path(tree,
+                                           outline,
+                                           &node,
+                                           pattern,
+                                           opacity,
+                                           bounds,
+                                           transform);
+                    return;
+                }
+            }
+        }
+
+        let paint = Paint::from_svg_paint(tree,
+                                          svg_paint,
+                                          opacity,
+                                          bounds,
+                                          transform,
+                                          &mut self.result_flags);
+        self.push_path(outline, paint, name);
+    }
+
+    // Draws the content of `pattern` once for each copy of its tile that `outline` overlaps,
+    // clipped to the tile and to the outline.
+    //
+    // TODO: Render the tile once and draw it as an image instead when the content is
+    // complex.
+    fn push_pattern_path(&mut self,
+                         tree: &Tree,
+                         mut outline: Outline,
+                         node: &Node,
+                         pattern: &UsvgPattern,
+                         opacity: Opacity,
+                         bounds: RectF,
+                         transform: &Transform2F) {
+        let tile_rect = usvg_rect_to_euclid_rect(&pattern.rect);
+        let tile_rect = match pattern.units {
+            Units::UserSpaceOnUse => tile_rect,
+            Units::ObjectBoundingBox => {
+                RectF::new(bounds.origin() + tile_rect.origin() * bounds.size(),
+                           tile_rect.size() * bounds.size())
+            }
+        };
+        let region = match outline.bounds().intersection(self.scene.view_box()) {
+            Some(region) if tile_rect.width() > 0.0 && tile_rect.height() > 0.0 => region,
+            _ => return,
+        };
+
+        // Maps the content to the tile, and the tile to the scene.
+        let content_transform = match pattern.view_box {
+            Some(ref view_box) => view_box_transform(view_box, tile_rect.size()),
+            None => {
+                match pattern.content_units {
+                    Units::UserSpaceOnUse => Transform2F::default(),
+                    Units::ObjectBoundingBox => Transform2F::from_scale(bounds.size()),
+                }
+            }
+        };
+        let content_transform = Transform2F::from_translation(tile_rect.origin()) *
+            content_transform;
+        let pattern_transform = *transform * usvg_transform_to_transform_2d(&pattern.transform);
+
+        // Find the copies of the tile that overlap the visible part of the outline.
+        let inverse_transform = pattern_transform.inverse();
+        let mut pattern_region = RectF::from_points(inverse_transform * region.origin(),
+                                                    inverse_transform * region.origin());
+        for &corner in &[region.upper_right(), region.lower_right(), region.lower_left()] {
+            pattern_region = pattern_region.union_point(inverse_transform * corner);
+        }
+        let (tile_origin, tile_size) = (tile_rect.origin(), tile_rect.size());
+        let first_copy = ((pattern_region.origin() - tile_origin) / tile_size).floor().to_i32();
+        let last_copy = ((pattern_region.lower_right() - tile_origin) / tile_size).ceil().to_i32();
+        let copy_count = (last_copy.x() - first_copy.x()) as i64 *
+            (last_copy.y() - first_copy.y()) as i64;
+        if copy_count > MAX_PATTERN_TILE_COUNT {
+            self.result_flags.insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
+            return;
+        }
+
+        // Clip the content to the outline, as it's clipped itself.
+        for tile in &self.pattern_tiles {
+            outline.clip_against_polygon(&tile.polygon);
+        }
+        if let Some(clip_path) = self.clip_path {
+            outline = self.scene.clip_path(clip_path).clip(&outline);
+        }
+        if outline.contours().is_empty() {
+            return;
+        }
+        let outer_clip_path = self.clip_path;
+        let clip_path = ClipPath::new(outline, FillRule::Winding);
+        self.clip_path = Some(self.scene.push_clip_path(clip_path));
+
+        for y in first_copy.y()..last_copy.y() {
+            for x in first_copy.x()..last_copy.x() {
+                let copy_offset = Vector2F::new(x as f32, y as f32) * tile_size;
+                let copy_transform = pattern_transform *
+                    Transform2F::from_translation(copy_offset);
+                self.pattern_tiles.push(PatternTile {
+                    polygon: transformed_rect_polygon(&copy_transform, tile_rect),
+                    opacity: opacity.value() as f32,
+                });
+                for kid in node.children() {
+                    self.process_node(tree, &kid, &(copy_transform * content_transform));
+                }
+                self.pattern_tiles.pop();
+            }
+        }
+
+        self.clip_path = outer_clip_path;
+    }
+
+    // Draws the content of the group `node` into a scene of its own, then draws it filtered by
+    // `filter`.
+    fn push_filtered_group(&mut self,
+                           tree: &Tree,
+                           node: &Node,
+                           filter: &UsvgFilter,
+                           transform: &Transform2F) {
+        let view_box = self.scene.view_box();
+        let mut source = Scene::new();
+        source.set_view_box(view_box);
+        let pattern_tiles = mem::replace(&mut self.pattern_tiles, vec![]);
+        let clip_path = self.clip_path.take();
+        let scene = mem::replace(&mut self.scene, source);
+        for kid in node.children() {
+            self.process_node(tree, &kid, transform);
+        }
+        let source = mem::replace(&mut self.scene, scene);
+        self.pattern_tiles = pattern_tiles;
+        self.clip_path = clip_path;
+
+        let bounds = children_bounds(node, &Transform2F::default()).unwrap_or_default();
+
+        let output = match filter::apply_filter(filter,
+                                                &source,
+                                                &self.scene,
+                                                bounds,
+                                                transform,
+                                                view_box,
+                                                &mut self.warnings) {
+            None => return,
+            Some(output) => output,
+        };
+        self.push_path(output.outline, output.paint, format!("Filter({})", node.id()));
+
+        if output.draws_source_on_top {
+            for kid in node.children() {
+                self.process_node(tree, &kid, transform);
+            }
+        }
+    }
+
+    // Draws `image` into its viewport, as its `preserveAspectRatio` specifies. `transform` maps
+    // user space to the scene.
+    fn push_image(&mut self, image: &UsvgImage, transform: &Transform2F) {
+        let decoded = match decode_image(image) {
+            Ok(decoded) => decoded,
+            Err(message) => {
+                self.result_flags.insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
+                self.warnings.push(format!("image \"{}\" wasn't drawn: {}", image.id, message));
+                return;
+            }
+        };
+
+        let viewport = usvg_rect_to_euclid_rect(&image.view_box.rect);
+        let image_rect = RectF::new(Vector2F::default(), decoded.size().to_f32());
+        let image_transform = Transform2F::from_translation(viewport.origin()) *
+            fit_rect_transform(image_rect, image.view_box.aspect, viewport.size());
+
+        // With `slice`, the image overflows its viewport; otherwise, it may not fill it.
+        let image_bounds = RectF::from_points(image_transform * image_rect.origin(),
+                                              image_transform * image_rect.lower_right());
+        let rect = match image_bounds.intersection(viewport) {
+            Some(rect) if rect.width() > 0.0 && rect.height() > 0.0 => rect,
+            _ => return,
+        };
+
+        let mut contour = Contour::new();
+        for &point in &transformed_rect_polygon(transform, rect) {
+            contour.push_endpoint(point);
+        }
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let paint = Paint::Pattern(Pattern::new(decoded, *transform * image_transform));
+        self.push_path(outline, paint, format!("Image({})", image.id));
+    }
+
+    fn push_path(&mut self, mut outline: Outline, mut paint: Paint, name: String) {
+        // Pattern content is clipped to its tile and to the path that the pattern fills.
+        for tile in &self.pattern_tiles {
+            outline.clip_against_polygon(&tile.polygon);
+            paint.set_opacity(tile.opacity);
+        }
+        if outline.contours().is_empty() {
+            return;
+        }
+
+        let paint_id = self.scene.push_paint(&paint);
+        let mut path = PathObject::new(outline, paint_id, name);
+        path.set_clip_path(self.clip_path);
+        self.scene.push_path(path);
+    }
 }
 
 impl Display for BuildResultFlags {
@@ -234,23 +561,94 @@ impl Display for BuildResultFlags {
 }
 
 trait PaintExt {
-    fn from_svg_paint(svg_paint: &UsvgPaint, opacity: Opacity, result_flags: &mut BuildResultFlags)
+    fn from_svg_paint(tree: &Tree,
+                      svg_paint: &UsvgPaint,
+                      opacity: Opacity,
+                      bounds: RectF,
+                      transform: &Transform2F,
+                      result_flags: &mut BuildResultFlags)
                       -> Self;
 }
 
 impl PaintExt for Paint {
-    #[inline]
-    fn from_svg_paint(svg_paint: &UsvgPaint, opacity: Opacity, result_flags: &mut BuildResultFlags)
+    /// `bounds` is the bounding box of the path being painted, in user space, and `transform`
+    /// maps user space to the scene.
+    fn from_svg_paint(tree: &Tree,
+                      svg_paint: &UsvgPaint,
+                      opacity: Opacity,
+                      bounds: RectF,
+                      transform: &Transform2F,
+                      result_flags: &mut BuildResultFlags)
                       -> Paint {
-        // TODO(pcwalton): Support gradients.
-        Paint::Color(match *svg_paint {
-            UsvgPaint::Color(color) => ColorU::from_svg_color(color, opacity),
-            UsvgPaint::Link(_) => {
-                // TODO(pcwalton)
+        let id = match *svg_paint {
+            UsvgPaint::Color(color) => return Paint::Color(ColorU::from_svg_color(color, opacity)),
+            UsvgPaint::Link(ref id) => id,
+        };
+
+        let node = match tree.defs_by_id(id) {
+            Some(node) => node,
+            None => {
                 result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
-                ColorU::black()
+                return Paint::black();
             }
-        })
+        };
+        let kind = node.borrow();
+        let (mut gradient, base) = match *kind {
+            NodeKind::LinearGradient(ref linear) => {
+                let line = LineSegment2F::new(Vector2F::new(linear.x1 as f32, linear.y1 as f32),
+                                              Vector2F::new(linear.x2 as f32, linear.y2 as f32));
+                (Gradient::new(line), &linear.base)
+            }
+            NodeKind::RadialGradient(ref radial) => {
+                // SVG radial gradients start from a point at the focus and end at the circle.
+                let line = LineSegment2F::new(Vector2F::new(radial.fx as f32, radial.fy as f32),
+                                              Vector2F::new(radial.cx as f32, radial.cy as f32));
+                (Gradient::radial(line, 0.0, radial.r.value() as f32), &radial.base)
+            }
+            _ => {
+                result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
+                return Paint::black();
+            }
+        };
+        gradient.add_usvg_stops(base);
+
+        let units_transform = match base.units {
+            Units::UserSpaceOnUse => Transform2F::default(),
+            Units::ObjectBoundingBox => {
+                // Per SVG, bounding box units are meaningless for flat shapes, and the paint
+                // isn't drawn.
+                if bounds.width() == 0.0 || bounds.height() == 0.0 {
+                    return Paint::transparent_black();
+                }
+                Transform2F::from_translation(bounds.origin()) *
+                    Transform2F::from_scale(bounds.size())
+            }
+        };
+        let gradient_transform = usvg_transform_to_transform_2d(&base.transform);
+        gradient.apply_transform(&(*transform * units_transform * gradient_transform));
+        gradient.set_opacity(opacity.value() as f32);
+        Paint::Gradient(gradient)
+    }
+}
+
+trait GradientExt {
+    fn add_usvg_stops(&mut self, base: &BaseGradient);
+}
+
+impl GradientExt for Gradient {
+    /// Adds the stops and spread method of an SVG gradient.
+    fn add_usvg_stops(&mut self, base: &BaseGradient) {
+        for stop in &base.stops {
+            self.add_color_stop(ColorStop {
+                color: ColorU::from_svg_color(stop.color, stop.opacity),
+                offset: stop.offset.value() as f32,
+            });
+        }
+        self.set_wrap(match base.spread_method {
+            SpreadMethod::Pad => GradientWrap::Clamp,
+            SpreadMethod::Reflect => GradientWrap::Reflect,
+            SpreadMethod::Repeat => GradientWrap::Repeat,
+        });
     }
 }
 
@@ -272,6 +670,172 @@ fn usvg_transform_to_transform_2d(transform: &UsvgTransform) -> Transform2F {
     )
 }
 
+// Returns the outline of the `<clipPath>` named `id`, in scene coordinates, as it applies to
+// the group `node`. `transform` maps the user space of the group to the scene.
+fn clip_outline(tree: &Tree, node: &Node, id: &str, transform: &Transform2F) -> Option<Outline> {
+    let clip_node = tree.defs_by_id(id)?;
+    let clip_node_kind = clip_node.borrow();
+    let clip_path = match *clip_node_kind {
+        NodeKind::ClipPath(ref clip_path) => clip_path,
+        _ => return None,
+    };
+
+    let units_transform = match clip_path.units {
+        Units::UserSpaceOnUse => Transform2F::default(),
+        Units::ObjectBoundingBox => {
+            let bounds = children_bounds(node, &Transform2F::default()).unwrap_or_default();
+            Transform2F::from_translation(bounds.origin()) *
+                Transform2F::from_scale(bounds.size())
+        }
+    };
+    let clip_transform = *transform * units_transform *
+        usvg_transform_to_transform_2d(&clip_path.transform);
+
+    let mut outline = Outline::new();
+    add_to_clip_outline(&mut outline, &clip_node, &clip_transform);
+
+    // Clip paths can be clipped themselves.
+    if let Some(ref clip_path_id) = clip_path.clip_path {
+        let outer_outline = clip_outline(tree, node, clip_path_id, transform)?;
+        outline = outline.intersection(&outer_outline);
+    }
+    Some(outline)
+}
+
+// Adds the union of the paths among the descendants of `node` to `outline`. `transform` maps the
+// user space of `node` to the scene.
+fn add_to_clip_outline(outline: &mut Outline, node: &Node, transform: &Transform2F) {
+    for kid in node.children() {
+        let transform = *transform * usvg_transform_to_transform_2d(&kid.transform());
+        match *kid.borrow() {
+            NodeKind::Path(ref path) if path.visibility == Visibility::Visible => {
+                let fill_rule = match path.fill.as_ref().map(|fill| fill.rule) {
+                    Some(UsvgFillRule::EvenOdd) => FillRule::EvenOdd,
+                    _ => FillRule::Winding,
+                };
+                let segments = UsvgPathToSegments::new(path.data.iter().cloned());
+                let path_outline =
+                    Outline::from_segments(Transform2FPathIter::new(segments, &transform));
+                *outline = path_outline.boolean_op_with_fill_rules(fill_rule,
+                                                                   outline,
+                                                                   FillRule::Winding,
+                                                                   BooleanOp::Union);
+            }
+            NodeKind::Group(_) => add_to_clip_outline(outline, &kid, &transform),
+            _ => {}
+        }
+    }
+}
+
+// Returns the bounding box of the paths among the descendants of `node`, or `None` if it has
+// none. `transform` maps the user space of `node` to the space of the result.
+fn children_bounds(node: &Node, transform: &Transform2F) -> Option<RectF> {
+    let mut bounds: Option<RectF> = None;
+    for kid in node.children() {
+        let transform = *transform * usvg_transform_to_transform_2d(&kid.transform());
+        let kid_bounds = match *kid.borrow() {
+            NodeKind::Path(ref path) => {
+                let segments = UsvgPathToSegments::new(path.data.iter().cloned());
+                let outline = Outline::from_segments(Transform2FPathIter::new(segments,
+                                                                              &transform));
+                Some(outline.bounds())
+            }
+            NodeKind::Group(_) => children_bounds(&kid, &transform),
+            _ => None,
+        };
+        if let Some(kid_bounds) = kid_bounds {
+            bounds = Some(match bounds {
+                None => kid_bounds,
+                Some(bounds) => bounds.union_rect(kid_bounds),
+            });
+        }
+    }
+    bounds
+}
+
+/// Maps `view_box` to a viewport of `size` at the origin, as its `preserveAspectRatio` specifies.
+///
+/// Without alignment, the view box is stretched to fill the viewport. Otherwise, it's scaled
+/// uniformly to fit inside the viewport (`meet`) or to cover it (`slice`), and aligned within it.
+pub fn view_box_transform(view_box: &ViewBox, size: Vector2F) -> Transform2F {
+    fit_rect_transform(usvg_rect_to_euclid_rect(&view_box.rect), view_box.aspect, size)
+}
+
+// Maps `rect` to a viewport of `size` at the origin, as the `preserveAspectRatio` value `aspect`
+// specifies.
+fn fit_rect_transform(rect: RectF, aspect: AspectRatio, size: Vector2F) -> Transform2F {
+    if rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return Transform2F::from_scale(Vector2F::default());
+    }
+
+    let align = match aspect.align {
+        Align::None => None,
+        Align::XMinYMin => Some(Vector2F::new(0.0, 0.0)),
+        Align::XMidYMin => Some(Vector2F::new(0.5, 0.0)),
+        Align::XMaxYMin => Some(Vector2F::new(1.0, 0.0)),
+        Align::XMinYMid => Some(Vector2F::new(0.0, 0.5)),
+        Align::XMidYMid => Some(Vector2F::new(0.5, 0.5)),
+        Align::XMaxYMid => Some(Vector2F::new(1.0, 0.5)),
+        Align::XMinYMax => Some(Vector2F::new(0.0, 1.0)),
+        Align::XMidYMax => Some(Vector2F::new(0.5, 1.0)),
+        Align::XMaxYMax => Some(Vector2F::new(1.0, 1.0)),
+    };
+
+    let scale = size / rect.size();
+    let (scale, offset) = match align {
+        None => (scale, Vector2F::default()),
+        Some(align) => {
+            let scale = Vector2F::splat(if aspect.slice {
+                f32::max(scale.x(), scale.y())
+            } else {
+                f32::min(scale.x(), scale.y())
+            });
+            (scale, (size - rect.size() * scale) * align)
+        }
+    };
+    Transform2F::from_translation(offset) * Transform2F::from_scale(scale) *
+        Transform2F::from_translation(-rect.origin())
+}
+
+// Returns the corners of `rect` under `transform`, wound as `Outline::clip_against_polygon()`
+// expects.
+fn transformed_rect_polygon(transform: &Transform2F, rect: RectF) -> [Vector2F; 4] {
+    let mut polygon = [
+        *transform * rect.origin(),
+        *transform * rect.upper_right(),
+        *transform * rect.lower_right(),
+        *transform * rect.lower_left(),
+    ];
+    if transform.matrix.det() < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+#[cfg(feature = "pf-image")]
+fn decode_image(image: &UsvgImage) -> Result<Image, String> {
+    use usvg::{ImageData, ImageFormat};
+
+    let format = match image.format {
+        ImageFormat::PNG => image::ImageFormat::PNG,
+        ImageFormat::JPEG => image::ImageFormat::JPEG,
+        ImageFormat::SVG => return Err("SVG images are unsupported".to_owned()),
+    };
+    let data = match image.data {
+        ImageData::Raw(ref data) => Image::decode_with_format(data, format),
+        ImageData::Path(ref path) => {
+            let data = std::fs::read(path).map_err(|error| error.to_string())?;
+            Image::decode_with_format(&data, format)
+        }
+    };
+    data.map_err(|error| error.to_string())
+}
+
+#[cfg(not(feature = "pf-image"))]
+fn decode_image(_: &UsvgImage) -> Result<Image, String> {
+    Err("decoding images requires the `pf-image` feature".to_owned())
+}
+
 struct UsvgPathToSegments<I>
 where
     I: Iterator<Item = UsvgPathSegment>,
@@ -0,0 +1,91 @@
+// pathfinder/text/src/baseline.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Baselines other than the alphabetic one, as described by the OpenType `BASE` table.
+
+use crate::color::{read_u16, read_u32};
+use font_kit::loader::Loader;
+
+const BASE_TAG: u32 = 0x42415345;
+
+const HANG_TAG: u32 = 0x68616e67;
+const IDEO_TAG: u32 = 0x6964656f;
+const ROMN_TAG: u32 = 0x726f6d6e;
+const LATN_TAG: u32 = 0x6c61746e;
+
+const BASE_SCRIPT_RECORD_SIZE: usize = 6;
+
+/// The heights of the baselines of a font above its alphabetic baseline, in font units with Y up.
+///
+/// Baselines are `None` if the font doesn't record them.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct FontBaselines {
+    /// The baseline that Devanagari and Tibetan characters hang from.
+    pub hanging: Option<f32>,
+    /// The bottom of the ideographic em box.
+    pub ideographic: Option<f32>,
+}
+
+impl FontBaselines {
+    /// Reads the horizontal baselines of `font`, for Latin text if the font distinguishes
+    /// scripts.
+    pub fn from_font<F>(font: &F) -> FontBaselines where F: Loader {
+        match font.load_font_table(BASE_TAG) {
+            None => FontBaselines::default(),
+            Some(base) => read_baselines(&base).unwrap_or_default(),
+        }
+    }
+}
+
+fn read_baselines(base: &[u8]) -> Option<FontBaselines> {
+    let axis_offset = read_u16(base, 4)? as usize;
+    if axis_offset == 0 {
+        return None;
+    }
+    let tag_list_offset = axis_offset + read_u16(base, axis_offset)? as usize;
+    let script_list_offset = axis_offset + read_u16(base, axis_offset + 2)? as usize;
+
+    // Use the Latin script if there is one, and otherwise the first script with baselines.
+    let script_count = read_u16(base, script_list_offset)? as usize;
+    let mut values_offset = None;
+    for script_index in 0..script_count {
+        let record_offset = script_list_offset + 2 + script_index * BASE_SCRIPT_RECORD_SIZE;
+        let script_offset = script_list_offset + read_u16(base, record_offset + 4)? as usize;
+        let script_values_offset = read_u16(base, script_offset)? as usize;
+        if script_values_offset == 0 {
+            continue;
+        }
+        if values_offset.is_none() || read_u32(base, record_offset)? == LATN_TAG {
+            values_offset = Some(script_offset + script_values_offset);
+        }
+    }
+    let values_offset = values_offset?;
+
+    // Coordinates are listed in the same order as the tags.
+    let tag_count = read_u16(base, tag_list_offset)? as usize;
+    let coord_count = read_u16(base, values_offset + 2)? as usize;
+    let (mut alphabetic, mut baselines) = (0.0, FontBaselines::default());
+    for tag_index in 0..tag_count.min(coord_count) {
+        let tag = read_u32(base, tag_list_offset + 2 + tag_index * 4)?;
+        let coord_offset_offset = values_offset + 4 + tag_index * 2;
+        let coord_offset = values_offset + read_u16(base, coord_offset_offset)? as usize;
+        let coordinate = read_u16(base, coord_offset + 2)? as i16 as f32;
+        match tag {
+            ROMN_TAG => alphabetic = coordinate,
+            HANG_TAG => baselines.hanging = Some(coordinate),
+            IDEO_TAG => baselines.ideographic = Some(coordinate),
+            _ => {}
+        }
+    }
+
+    baselines.hanging = baselines.hanging.map(|hanging| hanging - alphabetic);
+    baselines.ideographic = baselines.ideographic.map(|ideographic| ideographic - alphabetic);
+    Some(baselines)
+}
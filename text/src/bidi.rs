@@ -0,0 +1,268 @@
+// pathfinder/text/src/bidi.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Bidirectional text, as the Unicode Bidirectional Algorithm orders it.
+//!
+//! Text is split into runs of a single direction, which are laid out separately and placed side
+//! by side in visual order. Shapers lay each run out from left to right in logical order, so
+//! right-to-left runs are mirrored afterward.
+
+use crate::TextShaper;
+use crate::fallback::layout_advance;
+use skribo::{FontCollection, Layout, TextStyle};
+use std::borrow::Cow;
+use std::ops::Range;
+use unicode_bidi::{BidiInfo, Level};
+
+/// The direction of a paragraph or run of text.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+/// A run of text in a single direction.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BidiRun {
+    /// The byte range of the run within the text.
+    pub range: Range<usize>,
+    pub direction: TextDirection,
+}
+
+/// Splits `text` into runs of a single direction, in visual order from left to right.
+///
+/// Each paragraph has the direction `base_direction`, or, if that's `None`, the direction of its
+/// first strongly directional character. Paragraphs follow each other on the same line.
+pub fn visual_runs(text: &str, base_direction: Option<TextDirection>) -> Vec<BidiRun> {
+    let base_level = base_direction.map(|base_direction| {
+        match base_direction {
+            TextDirection::LeftToRight => Level::ltr(),
+            TextDirection::RightToLeft => Level::rtl(),
+        }
+    });
+    let bidi_info = BidiInfo::new(text, base_level);
+
+    let mut runs = vec![];
+    for paragraph in &bidi_info.paragraphs {
+        let (levels, level_runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+        for range in level_runs {
+            let direction = if levels[range.start].is_rtl() {
+                TextDirection::RightToLeft
+            } else {
+                TextDirection::LeftToRight
+            };
+            runs.push(BidiRun { range, direction });
+        }
+    }
+    runs
+}
+
+/// Lays text out with another shaper, one directional run at a time, and places the runs in
+/// visual order.
+///
+/// Brackets in right-to-left runs are replaced with their mirror images before shaping.
+#[derive(Clone, Debug, Default)]
+pub struct BidiShaper<S> {
+    shaper: S,
+    base_direction: Option<TextDirection>,
+}
+
+impl<S> BidiShaper<S> where S: TextShaper {
+    /// Creates a shaper that takes the direction of each paragraph from its text.
+    #[inline]
+    pub fn new(shaper: S) -> BidiShaper<S> {
+        BidiShaper { shaper, base_direction: None }
+    }
+
+    /// Creates a shaper that lays every paragraph out in the given direction.
+    #[inline]
+    pub fn with_base_direction(shaper: S, base_direction: TextDirection) -> BidiShaper<S> {
+        BidiShaper { shaper, base_direction: Some(base_direction) }
+    }
+}
+
+impl<S> TextShaper for BidiShaper<S> where S: TextShaper {
+    fn shape(&self, text: &str, style: &TextStyle, collection: &FontCollection) -> Layout {
+        let mut layout: Option<Layout> = None;
+        let mut pen_x = 0.0;
+        for run in visual_runs(text, self.base_direction) {
+            let run_text = &text[run.range];
+            let mut run_layout = match run.direction {
+                TextDirection::LeftToRight => self.shaper.shape(run_text, style, collection),
+                TextDirection::RightToLeft => {
+                    let run_text = mirror_brackets(run_text);
+                    let mut run_layout = self.shaper.shape(&run_text, style, collection);
+                    reverse_layout(&mut run_layout);
+                    run_layout
+                }
+            };
+
+            let run_advance = layout_advance(&run_layout);
+            for glyph in &mut run_layout.glyphs {
+                glyph.offset.x += pen_x;
+            }
+            pen_x += run_advance;
+
+            match layout {
+                None => layout = Some(run_layout),
+                Some(ref mut layout) => layout.glyphs.extend(run_layout.glyphs.into_iter()),
+            }
+        }
+
+        match layout {
+            Some(layout) => layout,
+            None => self.shaper.shape("", style, collection),
+        }
+    }
+}
+
+// Mirrors the glyphs of a layout within its advance, so that the logically first glyph ends up on
+// the right, and puts them in left-to-right order.
+fn reverse_layout(layout: &mut Layout) {
+    let advance = layout_advance(layout);
+    let size = layout.size;
+    for glyph in &mut layout.glyphs {
+        let font = &glyph.font.font;
+        let scale = size / font.metrics().units_per_em as f32;
+        let glyph_advance = font.advance(glyph.glyph_id).map(|advance| advance.x).unwrap_or(0.0);
+        glyph.offset.x = advance - glyph.offset.x - glyph_advance * scale;
+    }
+    layout.glyphs.reverse();
+}
+
+fn mirror_brackets(text: &str) -> Cow<str> {
+    if !text.chars().any(|character| mirrored_char(character).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.chars().map(|character| {
+        mirrored_char(character).unwrap_or(character)
+    }).collect())
+}
+
+// The common pairs of `BidiMirroring.txt`.
+fn mirrored_char(character: char) -> Option<char> {
+    let mirrored_char = match character {
+        '(' => ')',
+        ')' => '(',
+        '<' => '>',
+        '>' => '<',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        '⁅' => '⁆',
+        '⁆' => '⁅',
+        '≤' => '≥',
+        '≥' => '≤',
+        '〈' => '〉',
+        '〉' => '〈',
+        '《' => '》',
+        '》' => '《',
+        '「' => '」',
+        '」' => '「',
+        '『' => '』',
+        '』' => '『',
+        '【' => '】',
+        '】' => '【',
+        _ => return None,
+    };
+    Some(mirrored_char)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bidi::{BidiRun, BidiShaper, TextDirection, mirror_brackets, visual_runs};
+    use crate::fallback::FontFallbackChain;
+    use crate::TextShaper;
+    use font_kit::loaders::default::Font;
+    use skribo::{FontCollection, TextStyle};
+    use std::borrow::Cow;
+    use std::sync::Arc;
+
+    const LTR: TextDirection = TextDirection::LeftToRight;
+    const RTL: TextDirection = TextDirection::RightToLeft;
+
+    // Start and end byte offsets, and directions.
+    type Runs = [(usize, usize, TextDirection)];
+
+    fn overpass() -> Font {
+        let data = include_bytes!("../../resources/fonts/overpass-regular.otf");
+        Font::from_bytes(Arc::new(data.to_vec()), 0).unwrap()
+    }
+
+    fn runs(runs: &Runs) -> Vec<BidiRun> {
+        runs.iter().map(|&(start, end, direction)| BidiRun { range: start..end, direction })
+            .collect()
+    }
+
+    #[test]
+    fn test_visual_runs() {
+        // Hebrew letters take two bytes each.
+        let cases: &[(&str, Option<TextDirection>, &Runs)] = &[
+            ("abc", None, &[(0, 3, LTR)]),
+            ("אבג", None, &[(0, 6, RTL)]),
+            ("abc אבג def", None, &[(0, 4, LTR), (4, 10, RTL), (10, 14, LTR)]),
+            // The paragraph takes the direction of its first letter, so the Latin comes first.
+            ("אבג abc", None, &[(7, 10, LTR), (0, 7, RTL)]),
+            ("אבג abc", Some(LTR), &[(0, 6, RTL), (6, 10, LTR)]),
+            // Spaces between runs take the direction of the paragraph.
+            ("abc אבג", Some(RTL), &[(3, 10, RTL), (0, 3, LTR)]),
+            ("", None, &[]),
+        ];
+        for &(text, base_direction, expected) in cases {
+            assert_eq!(visual_runs(text, base_direction), runs(expected), "runs of {:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_mirror_brackets() {
+        assert_eq!(mirror_brackets("(a) [b] «c» ≤"), ")a( ]b[ »c« ≥");
+        match mirror_brackets("abc") {
+            Cow::Borrowed(text) => assert_eq!(text, "abc"),
+            Cow::Owned(text) => panic!("nothing to mirror, but got {:?}", text),
+        }
+    }
+
+    #[test]
+    fn test_runs_are_laid_out_in_visual_order() {
+        let mut chain = FontFallbackChain::new();
+        chain.push(overpass());
+        let style = TextStyle { size: 16.0 };
+        let collection = FontCollection::new();
+
+        // The font has no Hebrew, so the letters of the right-to-left run are `.notdef` glyphs,
+        // but the bracket between them shows whether the run was mirrored and reversed.
+        let font = overpass();
+        let glyph = |character| font.glyph_for_char(character).unwrap();
+        let layout = BidiShaper::new(&chain).shape("ab א(ב", &style, &collection);
+        let glyph_ids: Vec<u32> = layout.glyphs.iter().map(|glyph| glyph.glyph_id).collect();
+        assert_eq!(glyph_ids, [glyph('a'), glyph('b'), glyph(' '), 0, glyph(')'), 0]);
+        for pair in layout.glyphs.windows(2) {
+            assert!(pair[0].offset.x < pair[1].offset.x);
+        }
+
+        // Left-to-right text comes out as the shaper lays it out, whatever the base direction.
+        let plain = chain.shape("abc", &style, &collection);
+        for shaper in &[BidiShaper::new(&chain), BidiShaper::with_base_direction(&chain, RTL)] {
+            let layout = shaper.shape("abc", &style, &collection);
+            assert_eq!(layout.glyphs.len(), plain.glyphs.len());
+            for (glyph, plain_glyph) in layout.glyphs.iter().zip(plain.glyphs.iter()) {
+                assert_eq!(glyph.glyph_id, plain_glyph.glyph_id);
+                assert_eq!(glyph.offset.x, plain_glyph.offset.x);
+            }
+        }
+
+        assert!(BidiShaper::new(&chain).shape("", &style, &collection).glyphs.is_empty());
+    }
+}
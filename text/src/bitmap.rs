@@ -0,0 +1,206 @@
+// pathfinder/text/src/bitmap.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Embedded bitmap glyphs, as described by the OpenType `sbix` table and the `CBLC` and `CBDT`
+//! tables.
+//!
+//! Only PNG bitmaps are supported, which is what color emoji fonts use. Each font may have
+//! several strikes, one per pixel size.
+
+use crate::color::{read_u16, read_u32};
+use font_kit::loader::Loader;
+use pathfinder_geometry::vector::Vector2F;
+use std::cmp::Ordering;
+
+const SBIX_TAG: u32 = 0x73626978;
+const CBLC_TAG: u32 = 0x43424c43;
+const CBDT_TAG: u32 = 0x43424454;
+const MAXP_TAG: u32 = 0x6d617870;
+
+const PNG_GRAPHIC_TYPE: u32 = 0x706e6720;
+
+const BITMAP_SIZE_RECORD_SIZE: usize = 48;
+const INDEX_SUBTABLE_RECORD_SIZE: usize = 8;
+
+/// A PNG image of a glyph at one pixel size.
+#[derive(Clone, Debug)]
+pub struct BitmapGlyph {
+    /// The encoded PNG image.
+    pub png_data: Vec<u8>,
+    /// The number of pixels per em of the strike the image comes from.
+    pub pixels_per_em: f32,
+    /// The position of the lower left corner of the image relative to the glyph origin, in
+    /// pixels of the strike, with Y up.
+    pub lower_left: Vector2F,
+}
+
+/// The bitmap tables of a font.
+#[derive(Clone, Debug)]
+pub struct BitmapGlyphTables {
+    sbix: Option<Box<[u8]>>,
+    cblc_and_cbdt: Option<(Box<[u8]>, Box<[u8]>)>,
+    glyph_count: u32,
+}
+
+impl BitmapGlyphTables {
+    /// Loads the bitmap tables of `font`. Returns `None` if the font has no bitmap glyphs.
+    pub fn from_font<F>(font: &F) -> Option<BitmapGlyphTables> where F: Loader {
+        let sbix = font.load_font_table(SBIX_TAG);
+        let cblc_and_cbdt = match (font.load_font_table(CBLC_TAG), font.load_font_table(CBDT_TAG)) {
+            (Some(cblc), Some(cbdt)) => Some((cblc, cbdt)),
+            _ => None,
+        };
+        if sbix.is_none() && cblc_and_cbdt.is_none() {
+            return None;
+        }
+        let glyph_count = read_u16(&font.load_font_table(MAXP_TAG)?, 4)? as u32;
+        Some(BitmapGlyphTables { sbix, cblc_and_cbdt, glyph_count })
+    }
+
+    /// Returns the bitmap of the given glyph from the strike that best suits drawing it at
+    /// `pixels_per_em`: the smallest one that's at least that large, or the largest one if
+    /// none are. Returns `None` if the glyph has no PNG bitmap.
+    pub fn glyph(&self, glyph_id: u32, pixels_per_em: f32) -> Option<BitmapGlyph> {
+        if glyph_id >= self.glyph_count {
+            return None;
+        }
+        if let Some(ref sbix) = self.sbix {
+            if let Some(glyph) = sbix_glyph(sbix, glyph_id, pixels_per_em) {
+                return Some(glyph);
+            }
+        }
+        match self.cblc_and_cbdt {
+            Some((ref cblc, ref cbdt)) => cbdt_glyph(cblc, cbdt, glyph_id, pixels_per_em),
+            None => None,
+        }
+    }
+}
+
+fn sbix_glyph(sbix: &[u8], glyph_id: u32, pixels_per_em: f32) -> Option<BitmapGlyph> {
+    let strike_count = read_u32(sbix, 4)? as usize;
+    let mut strikes = Vec::with_capacity(strike_count);
+    for strike_index in 0..strike_count {
+        let strike_offset = read_u32(sbix, 8 + strike_index * 4)? as usize;
+        strikes.push((read_u16(sbix, strike_offset)? as f32, strike_offset));
+    }
+
+    // Glyphs may be missing from the best strike, so try the others in order of preference.
+    sort_strikes(&mut strikes, pixels_per_em);
+    for &(strike_pixels_per_em, strike_offset) in &strikes {
+        let data_offsets_offset = strike_offset + 4 + glyph_id as usize * 4;
+        let data_start = strike_offset + read_u32(sbix, data_offsets_offset)? as usize;
+        let data_end = strike_offset + read_u32(sbix, data_offsets_offset + 4)? as usize;
+        if data_end < data_start + 8 || read_u32(sbix, data_start + 4)? != PNG_GRAPHIC_TYPE {
+            continue;
+        }
+        let origin_x = read_u16(sbix, data_start)? as i16;
+        let origin_y = read_u16(sbix, data_start + 2)? as i16;
+        return Some(BitmapGlyph {
+            png_data: sbix.get((data_start + 8)..data_end)?.to_vec(),
+            pixels_per_em: strike_pixels_per_em,
+            lower_left: Vector2F::new(origin_x as f32, origin_y as f32),
+        });
+    }
+    None
+}
+
+fn cbdt_glyph(cblc: &[u8], cbdt: &[u8], glyph_id: u32, pixels_per_em: f32)
+              -> Option<BitmapGlyph> {
+    let size_count = read_u32(cblc, 4)? as usize;
+    let mut strikes = Vec::with_capacity(size_count);
+    for size_index in 0..size_count {
+        let size_offset = 8 + size_index * BITMAP_SIZE_RECORD_SIZE;
+        let start_glyph_id = read_u16(cblc, size_offset + 40)? as u32;
+        let end_glyph_id = read_u16(cblc, size_offset + 42)? as u32;
+        if glyph_id >= start_glyph_id && glyph_id <= end_glyph_id {
+            strikes.push((*cblc.get(size_offset + 45)? as f32, size_offset));
+        }
+    }
+
+    sort_strikes(&mut strikes, pixels_per_em);
+    for &(strike_pixels_per_em, size_offset) in &strikes {
+        let (image_format, image_offset) = match cblc_image_location(cblc, size_offset, glyph_id) {
+            None => continue,
+            Some(location) => location,
+        };
+
+        // Both small and big glyph metrics start with the height, width, and horizontal bearings.
+        let metrics = cbdt.get(image_offset..(image_offset + 4))?;
+        let data_offset = match image_format {
+            17 => image_offset + 5,
+            18 => image_offset + 8,
+            _ => continue,
+        };
+        let (height, bearing_x, bearing_y) =
+            (metrics[0] as f32, metrics[2] as i8 as f32, metrics[3] as i8 as f32);
+        let data_length = read_u32(cbdt, data_offset)? as usize;
+        let data_start = data_offset + 4;
+        return Some(BitmapGlyph {
+            png_data: cbdt.get(data_start..(data_start + data_length))?.to_vec(),
+            pixels_per_em: strike_pixels_per_em,
+            lower_left: Vector2F::new(bearing_x, bearing_y - height),
+        });
+    }
+    None
+}
+
+// Returns the image format and offset in `CBDT` of the given glyph in the strike whose bitmap size
+// record is at `size_offset`.
+fn cblc_image_location(cblc: &[u8], size_offset: usize, glyph_id: u32) -> Option<(u16, usize)> {
+    let subtable_array_offset = read_u32(cblc, size_offset)? as usize;
+    let subtable_count = read_u32(cblc, size_offset + 8)? as usize;
+    for subtable_index in 0..subtable_count {
+        let record_offset = subtable_array_offset + subtable_index * INDEX_SUBTABLE_RECORD_SIZE;
+        let first_glyph_id = read_u16(cblc, record_offset)? as u32;
+        let last_glyph_id = read_u16(cblc, record_offset + 2)? as u32;
+        if glyph_id < first_glyph_id || glyph_id > last_glyph_id {
+            continue;
+        }
+
+        let subtable_offset = subtable_array_offset + read_u32(cblc, record_offset + 4)? as usize;
+        let index_format = read_u16(cblc, subtable_offset)?;
+        let image_format = read_u16(cblc, subtable_offset + 2)?;
+        let image_data_offset = read_u32(cblc, subtable_offset + 4)? as usize;
+        let glyph_index = (glyph_id - first_glyph_id) as usize;
+        let (start, end) = match index_format {
+            // 32-bit offsets.
+            1 => {
+                let offset = subtable_offset + 8 + glyph_index * 4;
+                (read_u32(cblc, offset)? as usize, read_u32(cblc, offset + 4)? as usize)
+            }
+            // 16-bit offsets.
+            3 => {
+                let offset = subtable_offset + 8 + glyph_index * 2;
+                (read_u16(cblc, offset)? as usize, read_u16(cblc, offset + 2)? as usize)
+            }
+            // TODO: Support the other index formats.
+            _ => return None,
+        };
+        if start == end {
+            return None;
+        }
+        return Some((image_format, image_data_offset + start));
+    }
+    None
+}
+
+// Sorts strikes, given as their pixels per em and some other value, so that the smallest strike at
+// least `pixels_per_em` large comes first, then the rest of the larger strikes, then the smaller
+// strikes from largest to smallest.
+fn sort_strikes<T>(strikes: &mut [(f32, T)], pixels_per_em: f32) {
+    strikes.sort_by(|&(a, _), &(b, _)| {
+        match (a >= pixels_per_em, b >= pixels_per_em) {
+            (true, true) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            (false, false) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        }
+    });
+}
@@ -0,0 +1,322 @@
+// pathfinder/text/src/cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reuses glyph outlines across frames.
+//!
+//! Outlines are cached in device space, already scaled to a size bucket and shifted by a
+//! quantized subpixel offset, so a cache hit only needs an integer translation. Glyphs drawn with
+//! rotated or skewed transforms bypass the cache.
+
+use crate::{OutlinePathBuilder, TextHinting, TextRenderMode, TextRenderOptions};
+use crate::{push_glyph_outline, uniform_scale};
+use font_kit::error::GlyphLoadingError;
+use font_kit::hinting::HintingOptions;
+use font_kit::loader::Loader;
+use font_kit::loaders::default::Font;
+use lyon_path::builder::Build;
+use pathfinder_content::outline::Outline;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_renderer::paint::PaintId;
+use pathfinder_renderer::scene::Scene;
+use skribo::{Layout, TextStyle};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Font sizes are rounded to this fraction of a pixel.
+const SIZE_BUCKETS_PER_PIXEL: f32 = 4.0;
+// Unhinted glyph origins are rounded to this fraction of a pixel vertically, and origins are
+// rounded to it horizontally if the render options don't round them.
+const SUBPIXEL_OFFSETS_PER_PIXEL: u8 = 4;
+
+/// A cache of glyph outlines, keyed by font, glyph ID, size bucket, subpixel offset, and hinting.
+///
+/// Keep one of these alive across frames and call `evict_unused()` once per frame to drop the
+/// glyphs that weren't drawn.
+#[derive(Clone, Default)]
+pub struct GlyphCache {
+    entries: HashMap<GlyphCacheKey, GlyphCacheEntry>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct GlyphCacheKey {
+    // The address of the font, which the entry keeps alive.
+    font: usize,
+    glyph_id: u32,
+    size_bucket: u32,
+    horizontal_subpixel_positions: u8,
+    subpixel_offset: (u8, u8),
+    hinting: TextHinting,
+}
+
+#[derive(Clone)]
+struct GlyphCacheEntry {
+    #[allow(dead_code)]
+    font: Arc<Font>,
+    outline: Outline,
+    used: bool,
+}
+
+impl GlyphCache {
+    #[inline]
+    pub fn new() -> GlyphCache {
+        GlyphCache::default()
+    }
+
+    /// The number of cached outlines.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Drops every outline that hasn't been used since the last call to this method.
+    pub fn evict_unused(&mut self) {
+        self.entries.retain(|_, entry| entry.used);
+        self.entries.values_mut().for_each(|entry| entry.used = false);
+    }
+
+    /// Like `SceneExt::push_layout`, but takes glyph outlines from the cache when possible and
+    /// adds newly-loaded ones to it.
+    ///
+    /// Glyph origins are rounded as `options` describes, except that a horizontal subpixel
+    /// position count of 0 is treated as 4, since outlines at arbitrary offsets can't be reused.
+    /// Sizes are also rounded to a quarter of a pixel.
+    ///
+    /// TODO: Cache the layers of color glyphs too. For now they're drawn in the text
+    /// color.
+    pub fn push_layout(&mut self,
+                       scene: &mut Scene,
+                       layout: &Layout,
+                       style: &TextStyle,
+                       transform: &Transform2F,
+                       render_mode: TextRenderMode,
+                       options: &TextRenderOptions,
+                       paint_id: PaintId)
+                       -> Result<(), GlyphLoadingError> {
+        for glyph in &layout.glyphs {
+            let origin = *transform * Vector2F::new(glyph.offset.x, glyph.offset.y);
+            let outline = self.get_or_load(&glyph.font.font,
+                                           glyph.glyph_id,
+                                           style.size,
+                                           transform,
+                                           origin,
+                                           options)?;
+            push_glyph_outline(scene, outline, render_mode, paint_id);
+        }
+        Ok(())
+    }
+
+    fn get_or_load(&mut self,
+                   font: &Arc<Font>,
+                   glyph_id: u32,
+                   font_size: f32,
+                   transform: &Transform2F,
+                   origin: Vector2F,
+                   options: &TextRenderOptions)
+                   -> Result<Outline, GlyphLoadingError> {
+        let units_per_em = font.metrics().units_per_em as f32;
+
+        // Only axis-aligned, uniform scales can be cached.
+        let scale = match uniform_scale(transform) {
+            Some(scale) => scale,
+            None => {
+                let glyph_scale = font_size / units_per_em;
+                let glyph_transform = Transform2F { matrix: transform.matrix, vector: origin } *
+                    Transform2F::from_scale(Vector2F::new(glyph_scale, -glyph_scale));
+                return load_outline(&**font, glyph_id, &glyph_transform, HintingOptions::None);
+            }
+        };
+
+        let horizontal_subpixel_positions = match options.horizontal_subpixel_positions {
+            0 => SUBPIXEL_OFFSETS_PER_PIXEL,
+            positions => positions,
+        };
+        let vertical_subpixel_positions = match options.hinting {
+            TextHinting::None => SUBPIXEL_OFFSETS_PER_PIXEL,
+            TextHinting::Slight | TextHinting::Full => 1,
+        };
+        let positions = Vector2F::new(horizontal_subpixel_positions as f32,
+                                      vertical_subpixel_positions as f32);
+        let position_size = Vector2F::splat(1.0) / positions;
+
+        let size_bucket = (font_size * scale * SIZE_BUCKETS_PER_PIXEL).round() as u32;
+        let quantized_origin = (origin * positions).round();
+        let integer_origin = (quantized_origin * position_size).floor();
+        let fraction = quantized_origin - integer_origin * positions;
+
+        let key = GlyphCacheKey {
+            font: &**font as *const Font as usize,
+            glyph_id,
+            size_bucket,
+            horizontal_subpixel_positions,
+            subpixel_offset: (fraction.x() as u8, fraction.y() as u8),
+            hinting: options.hinting,
+        };
+
+        if !self.entries.contains_key(&key) {
+            let pixel_size = size_bucket as f32 / SIZE_BUCKETS_PER_PIXEL;
+            let glyph_scale = pixel_size / units_per_em;
+            let subpixel_offset = fraction * position_size;
+            let glyph_transform = Transform2F::from_translation(subpixel_offset) *
+                Transform2F::from_scale(Vector2F::new(glyph_scale, -glyph_scale));
+            let hinting_options = options.hinting.to_hinting_options(pixel_size);
+            let outline = load_outline(&**font, glyph_id, &glyph_transform, hinting_options)?;
+            let entry = GlyphCacheEntry { font: (*font).clone(), outline, used: false };
+            self.entries.insert(key, entry);
+        }
+
+        let entry = self.entries.get_mut(&key).unwrap();
+        entry.used = true;
+        let mut outline = entry.outline.clone();
+        outline.transform(&Transform2F::from_translation(integer_origin));
+        Ok(outline)
+    }
+}
+
+fn load_outline<F>(font: &F,
+                   glyph_id: u32,
+                   transform: &Transform2F,
+                   hinting_options: HintingOptions)
+                   -> Result<Outline, GlyphLoadingError>
+                   where F: Loader {
+    let mut outline_builder = OutlinePathBuilder::new(transform);
+    font.outline(glyph_id, hinting_options, &mut outline_builder)?;
+    Ok(outline_builder.build())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::GlyphCache;
+    use crate::fallback::FontFallbackChain;
+    use crate::{SceneExt, TextHinting, TextRenderMode, TextRenderOptions};
+    use font_kit::loaders::default::Font;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+    use pathfinder_renderer::paint::Paint;
+    use pathfinder_renderer::scene::Scene;
+    use skribo::{Layout, TextStyle};
+    use std::sync::Arc;
+
+    const STYLE: TextStyle = TextStyle { size: 16.0 };
+
+    fn layout(text: &str) -> Layout {
+        let data = include_bytes!("../../resources/fonts/overpass-regular.otf");
+        let mut chain = FontFallbackChain::new();
+        chain.push(Font::from_bytes(Arc::new(data.to_vec()), 0).unwrap());
+        chain.layout(text, &STYLE)
+    }
+
+    fn push(cache: &mut GlyphCache,
+            scene: &mut Scene,
+            layout: &Layout,
+            transform: &Transform2F,
+            options: &TextRenderOptions) {
+        let paint_id = scene.push_paint(&Paint::black());
+        cache.push_layout(scene, layout, &STYLE, transform, TextRenderMode::Fill, options, paint_id)
+             .unwrap();
+    }
+
+    #[test]
+    fn test_outlines_match_uncached_ones() {
+        let layout = layout("abc ab");
+        let transform = Transform2F::from_translation(Vector2F::new(10.25, 40.0));
+        let options = TextRenderOptions::default();
+
+        let mut cache = GlyphCache::new();
+        let mut scene = Scene::new();
+        push(&mut cache, &mut scene, &layout, &transform, &options);
+
+        let mut uncached_scene = Scene::new();
+        let paint_id = uncached_scene.push_paint(&Paint::black());
+        uncached_scene.push_layout_with_options(&layout,
+                                                &STYLE,
+                                                &transform,
+                                                TextRenderMode::Fill,
+                                                &options,
+                                                paint_id)
+                      .unwrap();
+
+        assert_eq!(scene.path_count(), uncached_scene.path_count());
+        for ((_, outline, _, _), (_, uncached_outline, _, _)) in
+                scene.paths().zip(uncached_scene.paths()) {
+            let (bounds, uncached_bounds) = (outline.bounds(), uncached_outline.bounds());
+            assert!((bounds.origin() - uncached_bounds.origin()).length() < 0.01);
+            assert!((bounds.lower_right() - uncached_bounds.lower_right()).length() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_repeated_glyphs_are_cached_once() {
+        let layout = layout("abab");
+        let options = TextRenderOptions { horizontal_subpixel_positions: 1, ..Default::default() };
+        let mut cache = GlyphCache::new();
+        let mut scene = Scene::new();
+        push(&mut cache, &mut scene, &layout, &Transform2F::default(), &options);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(scene.path_count(), 4);
+
+        // Whole-pixel moves reuse the outlines, and subpixel ones need new ones.
+        let transform = Transform2F::from_translation(Vector2F::new(3.0, 7.0));
+        push(&mut cache, &mut scene, &layout, &transform, &options);
+        assert_eq!(cache.len(), 2);
+        let options = TextRenderOptions::default();
+        let transform = Transform2F::from_translation(Vector2F::new(0.25, 0.5));
+        push(&mut cache, &mut scene, &layout, &transform, &options);
+        assert!(cache.len() > 2);
+
+        // As do other sizes and hinting.
+        let length = cache.len();
+        let transform = Transform2F::from_scale(Vector2F::splat(2.0));
+        push(&mut cache, &mut scene, &layout, &transform, &options);
+        assert!(cache.len() > length);
+        let length = cache.len();
+        let options = TextRenderOptions { hinting: TextHinting::Full, ..options };
+        push(&mut cache, &mut scene, &layout, &transform, &options);
+        assert!(cache.len() > length);
+
+        // Rotated glyphs bypass the cache.
+        let length = cache.len();
+        push(&mut cache, &mut scene, &layout, &Transform2F::from_rotation(0.5), &options);
+        assert_eq!(cache.len(), length);
+        assert_eq!(scene.path_count(), 24);
+    }
+
+    #[test]
+    fn test_evict_unused() {
+        let options = TextRenderOptions::default();
+        let mut cache = GlyphCache::new();
+        let mut scene = Scene::new();
+        push(&mut cache, &mut scene, &layout("ab"), &Transform2F::default(), &options);
+        assert_eq!(cache.len(), 2);
+
+        // Glyphs drawn since the last eviction survive it.
+        cache.evict_unused();
+        assert_eq!(cache.len(), 2);
+        push(&mut cache, &mut scene, &layout("a"), &Transform2F::default(), &options);
+        cache.evict_unused();
+        assert_eq!(cache.len(), 1);
+        cache.evict_unused();
+        assert!(cache.is_empty());
+
+        push(&mut cache, &mut scene, &layout("ab"), &Transform2F::default(), &options);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
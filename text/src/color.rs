@@ -0,0 +1,138 @@
+// pathfinder/text/src/color.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Layered color glyphs, as described by the OpenType `COLR` and `CPAL` tables.
+//!
+//! Only version 0 of `COLR` is supported: each color glyph is a stack of ordinary glyphs, each
+//! filled with a single palette entry.
+
+use font_kit::loader::Loader;
+use pathfinder_color::ColorU;
+
+const COLR_TAG: u32 = 0x434f4c52;
+const CPAL_TAG: u32 = 0x4350414c;
+
+const BASE_GLYPH_RECORD_SIZE: usize = 6;
+const LAYER_RECORD_SIZE: usize = 4;
+const COLOR_RECORD_SIZE: usize = 4;
+
+/// The palette index that means "use the text color".
+pub const FOREGROUND_PALETTE_INDEX: u16 = 0xffff;
+
+/// One layer of a color glyph.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorGlyphLayer {
+    pub glyph_id: u32,
+    /// The color of this layer, or `None` if it should be drawn with the text color.
+    pub color: Option<ColorU>,
+}
+
+/// The color tables of a font.
+#[derive(Clone, Debug)]
+pub struct ColorGlyphTables {
+    colr: Box<[u8]>,
+    cpal: Box<[u8]>,
+}
+
+impl ColorGlyphTables {
+    /// Loads the color tables of `font`. Returns `None` if the font has no color glyphs that we
+    /// can draw.
+    pub fn from_font<F>(font: &F) -> Option<ColorGlyphTables> where F: Loader {
+        let colr = font.load_font_table(COLR_TAG)?;
+        let cpal = font.load_font_table(CPAL_TAG)?;
+        if read_u16(&colr, 0)? != 0 {
+            return None;
+        }
+        Some(ColorGlyphTables { colr, cpal })
+    }
+
+    /// Returns the layers of the given glyph, bottommost first, using palette `palette_index`.
+    /// Returns `None` if the glyph isn't a color glyph.
+    pub fn layers(&self, glyph_id: u32, palette_index: u16) -> Option<Vec<ColorGlyphLayer>> {
+        let colr = &*self.colr;
+        let base_glyph_count = read_u16(colr, 2)? as usize;
+        let base_glyphs_offset = read_u32(colr, 4)? as usize;
+        let layers_offset = read_u32(colr, 8)? as usize;
+        let layer_count = read_u16(colr, 12)? as usize;
+
+        // Base glyph records are sorted by glyph ID.
+        let (mut low, mut high) = (0, base_glyph_count);
+        let record_offset = loop {
+            if low >= high {
+                return None;
+            }
+            let mid = (low + high) / 2;
+            let record_offset = base_glyphs_offset + mid * BASE_GLYPH_RECORD_SIZE;
+            let record_glyph_id = read_u16(colr, record_offset)? as u32;
+            if record_glyph_id == glyph_id {
+                break record_offset;
+            } else if record_glyph_id < glyph_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        };
+
+        let first_layer_index = read_u16(colr, record_offset + 2)? as usize;
+        let layer_count_in_glyph = read_u16(colr, record_offset + 4)? as usize;
+        if first_layer_index + layer_count_in_glyph > layer_count {
+            return None;
+        }
+
+        let mut layers = Vec::with_capacity(layer_count_in_glyph);
+        for layer_index in first_layer_index..(first_layer_index + layer_count_in_glyph) {
+            let layer_offset = layers_offset + layer_index * LAYER_RECORD_SIZE;
+            let layer_glyph_id = read_u16(colr, layer_offset)? as u32;
+            let layer_palette_entry = read_u16(colr, layer_offset + 2)?;
+            let color = if layer_palette_entry == FOREGROUND_PALETTE_INDEX {
+                None
+            } else {
+                Some(self.palette_color(palette_index, layer_palette_entry)?)
+            };
+            layers.push(ColorGlyphLayer { glyph_id: layer_glyph_id, color });
+        }
+        Some(layers)
+    }
+
+    fn palette_color(&self, palette_index: u16, entry: u16) -> Option<ColorU> {
+        let cpal = &*self.cpal;
+        let entry_count = read_u16(cpal, 2)?;
+        let palette_count = read_u16(cpal, 4)?;
+        let color_record_count = read_u16(cpal, 6)? as usize;
+        let color_records_offset = read_u32(cpal, 8)? as usize;
+        if entry >= entry_count {
+            return None;
+        }
+
+        // Fall back to the default palette if the requested one doesn't exist.
+        let palette_index = if palette_index < palette_count { palette_index } else { 0 };
+        let first_color_index = read_u16(cpal, 12 + palette_index as usize * 2)? as usize;
+        let color_index = first_color_index + entry as usize;
+        if color_index >= color_record_count {
+            return None;
+        }
+
+        // Color records are stored in BGRA order.
+        let record_offset = color_records_offset + color_index * COLOR_RECORD_SIZE;
+        let record = cpal.get(record_offset..(record_offset + COLOR_RECORD_SIZE))?;
+        Some(ColorU { r: record[2], g: record[1], b: record[0], a: record[3] })
+    }
+}
+
+#[inline]
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..(offset + 2))?;
+    Some(((bytes[0] as u16) << 8) | bytes[1] as u16)
+}
+
+#[inline]
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(((read_u16(data, offset)? as u32) << 16) | read_u16(data, offset + 2)? as u32)
+}
@@ -0,0 +1,284 @@
+// pathfinder/text/src/fallback.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Font fallback chains, for text that mixes scripts.
+
+use crate::TextShaper;
+use font_kit::loaders::default::Font;
+use skribo::{FontCollection, FontFamily, Layout, TextStyle};
+use std::ops::Range;
+
+/// An ordered list of fonts. Each cluster of text is drawn with the first font that contains all
+/// of its characters.
+pub struct FontFallbackChain {
+    fonts: Vec<Font>,
+    // A collection containing only the corresponding font, for shaping runs.
+    collections: Vec<FontCollection>,
+}
+
+/// A run of text drawn with a single font of a fallback chain.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FallbackRun {
+    /// The byte range of the run within the text.
+    pub range: Range<usize>,
+    /// The index of the font within the chain, or `None` if no font contains the text.
+    pub font_index: Option<usize>,
+}
+
+impl FontFallbackChain {
+    #[inline]
+    pub fn new() -> FontFallbackChain {
+        FontFallbackChain { fonts: vec![], collections: vec![] }
+    }
+
+    /// Adds a font to the end of the chain, to be used when no earlier font has a character.
+    pub fn push(&mut self, font: Font) {
+        let mut collection = FontCollection::new();
+        collection.add_family(FontFamily::new_from_font(font.clone()));
+        self.fonts.push(font);
+        self.collections.push(collection);
+    }
+
+    #[inline]
+    pub fn fonts(&self) -> &[Font] {
+        &self.fonts
+    }
+
+    /// Returns the index of the first font in the chain that contains `character`.
+    pub fn font_index_for_char(&self, character: char) -> Option<usize> {
+        self.fonts.iter().position(|font| font_has_char(font, character))
+    }
+
+    /// Splits `text` into runs that each use a single font.
+    ///
+    /// Combining marks, variation selectors, emoji modifiers, and characters joined with a
+    /// zero-width joiner stay with the font of the character before them if that font has them,
+    /// so that clusters aren't split across fonts.
+    pub fn itemize(&self, text: &str) -> Vec<FallbackRun> {
+        let mut runs: Vec<FallbackRun> = vec![];
+        let mut previous_char = None;
+        for (offset, character) in text.char_indices() {
+            let next_offset = offset + character.len_utf8();
+            let continues_cluster = is_cluster_continuation(character) ||
+                previous_char == Some(ZERO_WIDTH_JOINER);
+            previous_char = Some(character);
+
+            if let Some(last_run) = runs.last_mut() {
+                let last_font_has_char = match last_run.font_index {
+                    None => true,
+                    Some(font_index) => font_has_char(&self.fonts[font_index], character),
+                };
+                if continues_cluster && last_font_has_char {
+                    last_run.range.end = next_offset;
+                    continue;
+                }
+
+                let font_index = self.font_index_for_char(character);
+                if font_index == last_run.font_index {
+                    last_run.range.end = next_offset;
+                    continue;
+                }
+                runs.push(FallbackRun { range: offset..next_offset, font_index });
+                continue;
+            }
+
+            let font_index = self.font_index_for_char(character);
+            runs.push(FallbackRun { range: offset..next_offset, font_index });
+        }
+        runs
+    }
+
+    /// Lays out `text`, shaping each run with its own font and placing the runs one after another.
+    /// Text that no font contains is laid out with the first font, which usually draws it as
+    /// `.notdef` boxes.
+    pub fn layout(&self, text: &str, style: &TextStyle) -> Layout {
+        let mut layout: Option<Layout> = None;
+        let mut pen_x = 0.0;
+        for run in self.itemize(text) {
+            let collection = match self.collections.get(run.font_index.unwrap_or(0)) {
+                None => continue,
+                Some(collection) => collection,
+            };
+
+            let mut run_layout = skribo::layout(style, collection, &text[run.range.clone()]);
+            let run_advance = layout_advance(&run_layout);
+            for glyph in &mut run_layout.glyphs {
+                glyph.offset.x += pen_x;
+            }
+            pen_x += run_advance;
+
+            match layout {
+                None => layout = Some(run_layout),
+                Some(ref mut layout) => layout.glyphs.extend(run_layout.glyphs.into_iter()),
+            }
+        }
+
+        match layout {
+            Some(layout) => layout,
+            None => skribo::layout(style, &FontCollection::new(), ""),
+        }
+    }
+}
+
+impl Default for FontFallbackChain {
+    #[inline]
+    fn default() -> FontFallbackChain {
+        FontFallbackChain::new()
+    }
+}
+
+/// A fallback chain ignores the font collection it's given.
+impl TextShaper for FontFallbackChain {
+    #[inline]
+    fn shape(&self, text: &str, style: &TextStyle, _: &FontCollection) -> Layout {
+        self.layout(text, style)
+    }
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+#[inline]
+fn font_has_char(font: &Font, character: char) -> bool {
+    match font.glyph_for_char(character) {
+        None | Some(0) => false,
+        Some(_) => true,
+    }
+}
+
+fn is_cluster_continuation(character: char) -> bool {
+    match character as u32 {
+        // Combining diacritical marks.
+        0x0300..=0x036f | 0x1ab0..=0x1aff | 0x1dc0..=0x1dff | 0x20d0..=0x20ff | 0xfe20..=0xfe2f => {
+            true
+        }
+        // Zero-width joiner, variation selectors, emoji modifiers, and tags.
+        0x200d | 0xfe00..=0xfe0f | 0x1f3fb..=0x1f3ff | 0xe0020..=0xe007f | 0xe0100..=0xe01ef => {
+            true
+        }
+        _ => false,
+    }
+}
+
+// The distance from the origin of the layout to the pen position after its last glyph.
+pub(crate) fn layout_advance(layout: &Layout) -> f32 {
+    let last_glyph = match layout.glyphs.last() {
+        None => return 0.0,
+        Some(last_glyph) => last_glyph,
+    };
+
+    let font = &last_glyph.font.font;
+    let scale = layout.size / font.metrics().units_per_em as f32;
+    let advance = font.advance(last_glyph.glyph_id).map(|advance| advance.x).unwrap_or(0.0);
+    last_glyph.offset.x + advance * scale
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fallback::{FallbackRun, FontFallbackChain};
+    use crate::TextShaper;
+    use font_kit::family_name::FamilyName;
+    use font_kit::loaders::default::Font;
+    use font_kit::properties::Properties;
+    use font_kit::source::SystemSource;
+    use skribo::{FontCollection, TextStyle};
+    use std::sync::Arc;
+
+    // Outside of every font.
+    const PRIVATE_USE: char = '\u{10fffd}';
+
+    // Start and end byte offsets, and font indices.
+    type Runs = [(usize, usize, Option<usize>)];
+
+    // Overpass covers Latin, but not Cyrillic, Greek, or combining marks.
+    fn overpass() -> Font {
+        let data = include_bytes!("../../resources/fonts/overpass-regular.otf");
+        Font::from_bytes(Arc::new(data.to_vec()), 0).unwrap()
+    }
+
+    fn system_font() -> Font {
+        let font = SystemSource::new().select_best_match(&[FamilyName::SansSerif],
+                                                         &Properties::new())
+                                      .unwrap()
+                                      .load()
+                                      .unwrap();
+        for &character in &['Ж', 'α', '\u{301}'] {
+            assert!(font.glyph_for_char(character).is_some(),
+                    "{} has no {:?}",
+                    font.full_name(),
+                    character);
+        }
+        font
+    }
+
+    fn chain() -> FontFallbackChain {
+        let mut chain = FontFallbackChain::new();
+        chain.push(overpass());
+        chain.push(system_font());
+        chain
+    }
+
+    fn runs(runs: &Runs) -> Vec<FallbackRun> {
+        runs.iter().map(|&(start, end, font_index)| {
+            FallbackRun { range: start..end, font_index }
+        }).collect()
+    }
+
+    #[test]
+    fn test_font_index_for_char() {
+        let chain = chain();
+        assert_eq!(chain.fonts().len(), 2);
+        assert_eq!(chain.font_index_for_char('a'), Some(0));
+        assert_eq!(chain.font_index_for_char('Ж'), Some(1));
+        assert_eq!(chain.font_index_for_char(PRIVATE_USE), None);
+        assert_eq!(FontFallbackChain::new().font_index_for_char('a'), None);
+    }
+
+    #[test]
+    fn test_itemize() {
+        let chain = chain();
+        // Cyrillic and Greek letters take two bytes each, and the private use character four.
+        let cases: &[(&str, &Runs)] = &[
+            ("ab Жα cd", &[(0, 3, Some(0)), (3, 7, Some(1)), (7, 10, Some(0))]),
+            // Combining marks stay with their letter if its font has them...
+            ("Ж\u{301}a", &[(0, 4, Some(1)), (4, 5, Some(0))]),
+            // ...and go to the font that has them otherwise.
+            ("e\u{301}", &[(0, 1, Some(0)), (1, 3, Some(1))]),
+            // Text that no font has makes its own runs, which keep their marks.
+            ("a\u{10fffd}\u{301}b", &[(0, 1, Some(0)), (1, 7, None), (7, 8, Some(0))]),
+            ("", &[]),
+        ];
+        for &(text, expected) in cases {
+            assert_eq!(chain.itemize(text), runs(expected), "runs of {:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_layout_uses_each_run_font() {
+        let chain = chain();
+        let style = TextStyle { size: 16.0 };
+        let layout = chain.shape("aЖb", &style, &FontCollection::new());
+
+        let names: Vec<String> =
+            layout.glyphs.iter().map(|glyph| glyph.font.font.full_name()).collect();
+        let (overpass_name, system_name) = (overpass().full_name(), system_font().full_name());
+        assert_eq!(names, [overpass_name.clone(), system_name, overpass_name]);
+        assert!(layout.glyphs.iter().all(|glyph| glyph.glyph_id != 0));
+        for pair in layout.glyphs.windows(2) {
+            assert!(pair[0].offset.x < pair[1].offset.x);
+        }
+
+        // Without any font for it, text is drawn with the first font.
+        let layout = chain.layout(&PRIVATE_USE.to_string(), &style);
+        assert_eq!(layout.glyphs.len(), 1);
+        assert_eq!(layout.glyphs[0].font.font.full_name(), overpass().full_name());
+
+        assert!(FontFallbackChain::new().layout("abc", &style).glyphs.is_empty());
+    }
+}
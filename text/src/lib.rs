@@ -8,20 +8,36 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::bitmap::BitmapGlyphTables;
+use crate::color::ColorGlyphTables;
 use euclid::Angle;
 use euclid::default::{Point2D, Vector2D};
 use font_kit::error::GlyphLoadingError;
 use font_kit::hinting::HintingOptions;
 use font_kit::loader::Loader;
+use font_kit::loaders::default::Font;
 use lyon_path::builder::{FlatPathBuilder, PathBuilder, Build};
 use pathfinder_content::outline::{Contour, Outline};
 use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
-use pathfinder_renderer::paint::PaintId;
+use pathfinder_renderer::paint::{Paint, PaintId};
 use pathfinder_renderer::scene::{PathObject, Scene};
+#[cfg(feature = "pf-image")]
+use pathfinder_content::pattern::{Image, Pattern};
+#[cfg(feature = "pf-image")]
+use pathfinder_geometry::rect::RectF;
 use skribo::{FontCollection, Layout, TextStyle};
 use std::mem;
+use ttf_parser::{Font as TtfFont, GlyphId, OutlineBuilder, Tag};
+
+pub mod baseline;
+pub mod bidi;
+pub mod bitmap;
+pub mod cache;
+pub mod color;
+pub mod fallback;
+pub mod vertical;
 
 pub trait SceneExt {
     // TODO(pcwalton): Support stroked glyphs.
@@ -35,6 +51,36 @@ pub trait SceneExt {
                      -> Result<(), GlyphLoadingError>
                      where F: Loader;
 
+    /// Like `push_glyph`, but loads the outline at the given position in the design space of a
+    /// variable font. Axes that the font doesn't have are ignored. Variable glyphs are not
+    /// hinted.
+    ///
+    /// This copies and parses the font data on every call, so prefer
+    /// `push_layout_with_variations` for runs of glyphs, which does so once per font.
+    fn push_glyph_with_variations<F>(&mut self,
+                                     font: &F,
+                                     glyph_id: u32,
+                                     variations: &[FontVariation],
+                                     transform: &Transform2F,
+                                     render_mode: TextRenderMode,
+                                     paint_id: PaintId)
+                                     -> Result<(), GlyphLoadingError>
+                                     where F: Loader;
+
+    /// Pushes the glyph as one path per color layer if the font has a color version of it, and
+    /// as a single path with `paint_id` otherwise. Layers that use the text color are filled with
+    /// `paint_id`.
+    fn push_color_glyph<F>(&mut self,
+                           font: &F,
+                           glyph_id: u32,
+                           transform: &Transform2F,
+                           hinting_options: HintingOptions,
+                           paint_id: PaintId)
+                           -> Result<(), GlyphLoadingError>
+                           where F: Loader;
+
+    /// Color glyphs in the layout are drawn in color when filling. With the `pf-image` feature,
+    /// so are glyphs that have an embedded PNG bitmap but no outline, such as color emoji.
     fn push_layout(&mut self,
                    layout: &Layout,
                    style: &TextStyle,
@@ -44,6 +90,29 @@ pub trait SceneExt {
                    paint_id: PaintId)
                    -> Result<(), GlyphLoadingError>;
 
+    /// Like `push_layout`, but fits glyphs to the device pixel grid as `options` describes.
+    ///
+    /// Glyphs are only repositioned and hinted when `transform` is an axis-aligned, uniform
+    /// scale, since the pixel grid has no meaning for rotated or skewed text.
+    fn push_layout_with_options(&mut self,
+                                layout: &Layout,
+                                style: &TextStyle,
+                                transform: &Transform2F,
+                                render_mode: TextRenderMode,
+                                options: &TextRenderOptions,
+                                paint_id: PaintId)
+                                -> Result<(), GlyphLoadingError>;
+
+    /// Like `push_layout`, but loads every glyph with the given font variations.
+    fn push_layout_with_variations(&mut self,
+                                   layout: &Layout,
+                                   style: &TextStyle,
+                                   variations: &[FontVariation],
+                                   transform: &Transform2F,
+                                   render_mode: TextRenderMode,
+                                   paint_id: PaintId)
+                                   -> Result<(), GlyphLoadingError>;
+
     fn push_text(&mut self,
                  text: &str,
                  style: &TextStyle,
@@ -68,16 +137,51 @@ impl SceneExt for Scene {
                      where F: Loader {
         let mut outline_builder = OutlinePathBuilder::new(transform);
         font.outline(glyph_id, hinting_options, &mut outline_builder)?;
-        let mut outline = outline_builder.build();
+        push_glyph_outline(self, outline_builder.build(), render_mode, paint_id);
+        Ok(())
+    }
 
-        if let TextRenderMode::Stroke(stroke_style) = render_mode {
-            let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
-            stroke_to_fill.offset();
-            outline = stroke_to_fill.into_outline();
-        }
+    fn push_glyph_with_variations<F>(&mut self,
+                                     font: &F,
+                                     glyph_id: u32,
+                                     variations: &[FontVariation],
+                                     transform: &Transform2F,
+                                     render_mode: TextRenderMode,
+                                     paint_id: PaintId)
+                                     -> Result<(), GlyphLoadingError>
+                                     where F: Loader {
+        let font_data = font.copy_font_data().ok_or(GlyphLoadingError::NoSuchGlyph)?;
+        let ttf_font = parse_variable_font(&font_data, variations)?;
+        push_variable_glyph(self, &ttf_font, glyph_id, transform, render_mode, paint_id)
+    }
 
-        self.push_path(PathObject::new(outline, paint_id, String::new()));
-        Ok(())
+    fn push_color_glyph<F>(&mut self,
+                           font: &F,
+                           glyph_id: u32,
+                           transform: &Transform2F,
+                           hinting_options: HintingOptions,
+                           paint_id: PaintId)
+                           -> Result<(), GlyphLoadingError>
+                           where F: Loader {
+        match ColorGlyphTables::from_font(font) {
+            Some(color_tables) => {
+                push_color_glyph_with_tables(self,
+                                             font,
+                                             &color_tables,
+                                             glyph_id,
+                                             transform,
+                                             hinting_options,
+                                             paint_id)
+            }
+            None => {
+                self.push_glyph(font,
+                                glyph_id,
+                                transform,
+                                TextRenderMode::Fill,
+                                hinting_options,
+                                paint_id)
+            }
+        }
     }
 
     fn push_layout(&mut self,
@@ -88,19 +192,79 @@ impl SceneExt for Scene {
                    hinting_options: HintingOptions,
                    paint_id: PaintId)
                    -> Result<(), GlyphLoadingError> {
+        push_layout_glyphs(self, layout, render_mode, paint_id, |font, offset| {
+            (layout_glyph_transform(font, offset, style, transform), hinting_options)
+        })
+    }
+
+    fn push_layout_with_options(&mut self,
+                                layout: &Layout,
+                                style: &TextStyle,
+                                transform: &Transform2F,
+                                render_mode: TextRenderMode,
+                                options: &TextRenderOptions,
+                                paint_id: PaintId)
+                                -> Result<(), GlyphLoadingError> {
+        let scale = match uniform_scale(transform) {
+            None => {
+                return self.push_layout(layout,
+                                        style,
+                                        transform,
+                                        render_mode,
+                                        HintingOptions::None,
+                                        paint_id);
+            }
+            Some(scale) => scale,
+        };
+
+        let hinting_options = options.hinting.to_hinting_options(style.size * scale);
+        push_layout_glyphs(self, layout, render_mode, paint_id, |font, offset| {
+            let origin = options.snap_origin(*transform * offset);
+            let glyph_scale = style.size / (font.metrics().units_per_em as f32);
+            let glyph_transform = Transform2F { matrix: transform.matrix, vector: origin } *
+                Transform2F::from_scale(Vector2F::new(glyph_scale, -glyph_scale));
+            (glyph_transform, hinting_options)
+        })
+    }
+
+    fn push_layout_with_variations(&mut self,
+                                   layout: &Layout,
+                                   style: &TextStyle,
+                                   variations: &[FontVariation],
+                                   transform: &Transform2F,
+                                   render_mode: TextRenderMode,
+                                   paint_id: PaintId)
+                                   -> Result<(), GlyphLoadingError> {
+        // Copy the data of each font in the layout once, and then parse each copy once, so
+        // that the parsed fonts can borrow from the copies.
+        let (mut font_data, mut font_indices) = (vec![], Vec::with_capacity(layout.glyphs.len()));
         for glyph in &layout.glyphs {
-            let offset = Vector2F::new(glyph.offset.x, glyph.offset.y);
+            let font_ptr = &*glyph.font.font as *const _ as *const ();
+            let font_index = match font_data.iter().position(|&(other_font_ptr, _)| {
+                other_font_ptr == font_ptr
+            }) {
+                Some(font_index) => font_index,
+                None => {
+                    let data = glyph.font.font
+                                    .copy_font_data()
+                                    .ok_or(GlyphLoadingError::NoSuchGlyph)?;
+                    font_data.push((font_ptr, data));
+                    font_data.len() - 1
+                }
+            };
+            font_indices.push(font_index);
+        }
+        let mut ttf_fonts = Vec::with_capacity(font_data.len());
+        for &(_, ref data) in &font_data {
+            ttf_fonts.push(parse_variable_font(data, variations)?);
+        }
+
+        for (glyph, &font_index) in layout.glyphs.iter().zip(font_indices.iter()) {
             let font = &*glyph.font.font;
-            // FIXME(pcwalton): Cache this!
-            let scale = style.size / (font.metrics().units_per_em as f32);
-            let scale = Vector2F::new(scale, -scale);
-            let transform = *transform * Transform2F::from_scale(scale).translate(offset);
-            self.push_glyph(font,
-                            glyph.glyph_id,
-                            &transform,
-                            render_mode,
-                            hinting_options,
-                            paint_id)?;
+            let ttf_font = &ttf_fonts[font_index];
+            let offset = Vector2F::new(glyph.offset.x, glyph.offset.y);
+            let transform = layout_glyph_transform(font, offset, style, transform);
+            push_variable_glyph(self, ttf_font, glyph.glyph_id, &transform, render_mode, paint_id)?;
         }
         Ok(())
     }
@@ -115,17 +279,362 @@ impl SceneExt for Scene {
                  hinting_options: HintingOptions,
                  paint_id: PaintId)
                  -> Result<(), GlyphLoadingError> {
-        let layout = skribo::layout(style, collection, text);
+        let layout = SkriboShaper.shape(text, style, collection);
         self.push_layout(&layout, style, &transform, render_mode, hinting_options, paint_id)
     }
 }
 
+/// Converts a string into a layout of positioned glyphs.
+///
+/// Implement this to plug in a full shaping engine such as HarfBuzz, which handles ligatures and
+/// complex scripts. Shapers that already produce glyph IDs and positions can instead build a
+/// `Layout` directly and pass it to `push_layout`.
+pub trait TextShaper {
+    fn shape(&self, text: &str, style: &TextStyle, collection: &FontCollection) -> Layout;
+}
+
+impl<'a, S> TextShaper for &'a S where S: TextShaper + ?Sized {
+    #[inline]
+    fn shape(&self, text: &str, style: &TextStyle, collection: &FontCollection) -> Layout {
+        (**self).shape(text, style, collection)
+    }
+}
+
+/// The default shaper, which uses `skribo`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SkriboShaper;
+
+impl TextShaper for SkriboShaper {
+    #[inline]
+    fn shape(&self, text: &str, style: &TextStyle, collection: &FontCollection) -> Layout {
+        skribo::layout(style, collection, text)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TextRenderMode {
     Fill,
     Stroke(StrokeStyle),
 }
 
+/// How glyph outlines are fitted to the pixel grid.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TextHinting {
+    /// Outlines are drawn exactly as designed. Small text may look blurry.
+    None,
+    /// Outlines are fitted to the pixel grid vertically only, keeping the shapes and advances of
+    /// glyphs intact.
+    Slight,
+    /// Outlines are fitted to the pixel grid in both directions, at the cost of distorting the
+    /// shapes of glyphs.
+    Full,
+}
+
+impl TextHinting {
+    /// Returns the `font-kit` hinting options for glyphs `pixel_size` device pixels tall.
+    #[inline]
+    pub fn to_hinting_options(self, pixel_size: f32) -> HintingOptions {
+        match self {
+            TextHinting::None => HintingOptions::None,
+            TextHinting::Slight => HintingOptions::Vertical(pixel_size),
+            TextHinting::Full => HintingOptions::Full(pixel_size),
+        }
+    }
+}
+
+/// Options that control how glyphs are placed on the device pixel grid.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextRenderOptions {
+    pub hinting: TextHinting,
+    /// The number of positions per pixel that glyph origins are rounded to horizontally. 1 snaps
+    /// glyphs to whole pixels, and 0 leaves them where the layout puts them.
+    ///
+    /// Vertically, origins are snapped to whole pixels when hinting is on, so that the baseline
+    /// stays on the grid the hinter fitted the outlines to.
+    pub horizontal_subpixel_positions: u8,
+}
+
+impl Default for TextRenderOptions {
+    #[inline]
+    fn default() -> TextRenderOptions {
+        TextRenderOptions { hinting: TextHinting::None, horizontal_subpixel_positions: 4 }
+    }
+}
+
+impl TextRenderOptions {
+    /// Rounds a glyph origin in device space as these options describe.
+    pub fn snap_origin(&self, origin: Vector2F) -> Vector2F {
+        let x = match self.horizontal_subpixel_positions {
+            0 => origin.x(),
+            positions => {
+                let positions = positions as f32;
+                (origin.x() * positions).round() / positions
+            }
+        };
+        let y = match self.hinting {
+            TextHinting::None => origin.y(),
+            TextHinting::Slight | TextHinting::Full => origin.y().round(),
+        };
+        Vector2F::new(x, y)
+    }
+}
+
+/// A coordinate along one of the variation axes of a variable font.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FontVariation {
+    /// The four-byte OpenType axis tag, for example `*b"wght"`.
+    pub tag: [u8; 4],
+    /// The position along the axis, in the units the font's `fvar` table uses.
+    pub value: f32,
+}
+
+impl FontVariation {
+    #[inline]
+    pub fn new(tag: [u8; 4], value: f32) -> FontVariation {
+        FontVariation { tag, value }
+    }
+}
+
+// Parses font data with ttf-parser and moves it to the given point in its design space.
+// FIXME: Handle fonts that aren't the first in a collection.
+fn parse_variable_font<'a>(font_data: &'a [u8], variations: &[FontVariation])
+                           -> Result<TtfFont<'a>, GlyphLoadingError> {
+    let mut ttf_font = TtfFont::from_data(font_data, 0).ok_or(GlyphLoadingError::NoSuchGlyph)?;
+    for variation in variations {
+        ttf_font.set_variation(Tag::from_bytes(&variation.tag), variation.value);
+    }
+    Ok(ttf_font)
+}
+
+fn push_variable_glyph(scene: &mut Scene,
+                       ttf_font: &TtfFont,
+                       glyph_id: u32,
+                       transform: &Transform2F,
+                       render_mode: TextRenderMode,
+                       paint_id: PaintId)
+                       -> Result<(), GlyphLoadingError> {
+    let mut outline_builder = OutlinePathBuilder::new(transform);
+    if ttf_font.outline_glyph(GlyphId(glyph_id as u16), &mut outline_builder).is_none() {
+        return Err(GlyphLoadingError::NoSuchGlyph);
+    }
+    push_glyph_outline(scene, outline_builder.build(), render_mode, paint_id);
+    Ok(())
+}
+
+fn layout_glyph_transform<F>(font: &F,
+                             offset: Vector2F,
+                             style: &TextStyle,
+                             transform: &Transform2F)
+                             -> Transform2F
+                             where F: Loader {
+    // FIXME(pcwalton): Cache this!
+    let scale = style.size / (font.metrics().units_per_em as f32);
+    let scale = Vector2F::new(scale, -scale);
+    *transform * Transform2F::from_scale(scale).translate(offset)
+}
+
+// Returns the scale of `transform` if it's an axis-aligned, uniform scale without reflections.
+fn uniform_scale(transform: &Transform2F) -> Option<f32> {
+    const EPSILON: f32 = 0.0001;
+    let scale = transform.m11();
+    if scale <= 0.0 || (transform.m22() - scale).abs() > EPSILON ||
+            transform.m12().abs() > EPSILON || transform.m21().abs() > EPSILON {
+        None
+    } else {
+        Some(scale)
+    }
+}
+
+// The tables of a font that describe glyphs in ways other than a single outline.
+struct FontGlyphTables {
+    font: *const (),
+    color: Option<ColorGlyphTables>,
+    #[cfg_attr(not(feature = "pf-image"), allow(dead_code))]
+    bitmap: Option<BitmapGlyphTables>,
+}
+
+impl FontGlyphTables {
+    fn new(font: &Font) -> FontGlyphTables {
+        FontGlyphTables {
+            font: font as *const _ as *const (),
+            color: ColorGlyphTables::from_font(font),
+            #[cfg(feature = "pf-image")]
+            bitmap: BitmapGlyphTables::from_font(font),
+            #[cfg(not(feature = "pf-image"))]
+            bitmap: None,
+        }
+    }
+}
+
+// Pushes each glyph of `layout`, with the transform and hinting options that `place_glyph`
+// returns for its font and offset. Color and bitmap glyphs are drawn in color when filling.
+fn push_layout_glyphs<P>(scene: &mut Scene,
+                         layout: &Layout,
+                         render_mode: TextRenderMode,
+                         paint_id: PaintId,
+                         mut place_glyph: P)
+                         -> Result<(), GlyphLoadingError>
+                         where P: FnMut(&Font, Vector2F) -> (Transform2F, HintingOptions) {
+    // The tables of each font in the layout, looked up the first time it's seen.
+    let mut font_tables: Vec<FontGlyphTables> = vec![];
+
+    for glyph in &layout.glyphs {
+        let font = &*glyph.font.font;
+        let offset = Vector2F::new(glyph.offset.x, glyph.offset.y);
+        let (transform, hinting_options) = place_glyph(font, offset);
+
+        // TODO: Stroke color glyphs layer by layer?
+        if let TextRenderMode::Fill = render_mode {
+            let font_ptr = font as *const _ as *const ();
+            let tables_index = match font_tables.iter().position(|tables| tables.font == font_ptr) {
+                Some(index) => index,
+                None => {
+                    font_tables.push(FontGlyphTables::new(font));
+                    font_tables.len() - 1
+                }
+            };
+            let tables = &font_tables[tables_index];
+            if let Some(ref font_color_tables) = tables.color {
+                push_color_glyph_with_tables(scene,
+                                             font,
+                                             font_color_tables,
+                                             glyph.glyph_id,
+                                             &transform,
+                                             hinting_options,
+                                             paint_id)?;
+                continue;
+            }
+
+            #[cfg(feature = "pf-image")]
+            {
+                if let Some(ref bitmap_tables) = tables.bitmap {
+                    let mut outline_builder = OutlinePathBuilder::new(&transform);
+                    let outline = match font.outline(glyph.glyph_id,
+                                                     hinting_options,
+                                                     &mut outline_builder) {
+                        Ok(()) => outline_builder.build(),
+                        Err(_) => Outline::new(),
+                    };
+                    if !outline.contours().is_empty() {
+                        push_glyph_outline(scene, outline, render_mode, paint_id);
+                        continue;
+                    }
+                    if push_bitmap_glyph(scene, font, bitmap_tables, glyph.glyph_id, &transform) {
+                        continue;
+                    }
+                }
+            }
+        }
+
+        scene.push_glyph(font,
+                         glyph.glyph_id,
+                         &transform,
+                         render_mode,
+                         hinting_options,
+                         paint_id)?;
+    }
+    Ok(())
+}
+
+// Draws the embedded bitmap of the given glyph, if it has one, as a rectangle filled with the
+// image. Returns true if it did.
+#[cfg(feature = "pf-image")]
+fn push_bitmap_glyph<F>(scene: &mut Scene,
+                        font: &F,
+                        bitmap_tables: &BitmapGlyphTables,
+                        glyph_id: u32,
+                        transform: &Transform2F)
+                        -> bool
+                        where F: Loader {
+    let units_per_em = font.metrics().units_per_em as f32;
+    let device_pixels_per_em = units_per_em * transform.matrix.det().abs().sqrt();
+    let bitmap_glyph = match bitmap_tables.glyph(glyph_id, device_pixels_per_em) {
+        None => return false,
+        Some(bitmap_glyph) => bitmap_glyph,
+    };
+
+    // TODO: Cache decoded images across frames.
+    let image = match Image::decode(&bitmap_glyph.png_data) {
+        Err(_) => return false,
+        Ok(image) => image,
+    };
+
+    // Maps image pixels, with Y down, to font units, with Y up.
+    let units_per_pixel = units_per_em / bitmap_glyph.pixels_per_em;
+    let image_size = image.size().to_f32();
+    let upper_left = bitmap_glyph.lower_left + Vector2F::new(0.0, image_size.y());
+    let image_transform = *transform *
+        Transform2F::from_scale(Vector2F::new(units_per_pixel, -units_per_pixel))
+            .translate(upper_left.scale(units_per_pixel));
+
+    let image_rect = RectF::new(Vector2F::default(), image_size);
+    let mut contour = Contour::new();
+    for &corner in &[image_rect.origin(),
+                     image_rect.upper_right(),
+                     image_rect.lower_right(),
+                     image_rect.lower_left()] {
+        contour.push_endpoint(image_transform * corner);
+    }
+    contour.close();
+    let mut outline = Outline::new();
+    outline.push_contour(contour);
+
+    let paint_id = scene.push_paint(&Paint::Pattern(Pattern::new(image, image_transform)));
+    scene.push_path(PathObject::new(outline, paint_id, String::new()));
+    true
+}
+
+fn push_color_glyph_with_tables<F>(scene: &mut Scene,
+                                   font: &F,
+                                   color_tables: &ColorGlyphTables,
+                                   glyph_id: u32,
+                                   transform: &Transform2F,
+                                   hinting_options: HintingOptions,
+                                   paint_id: PaintId)
+                                   -> Result<(), GlyphLoadingError>
+                                   where F: Loader {
+    // TODO: Allow the palette to be chosen.
+    // TODO: Fall back to `sbix` and `CBDT` bitmaps for fonts without `COLR`.
+    let layers = match color_tables.layers(glyph_id, 0) {
+        None => {
+            return scene.push_glyph(font,
+                                    glyph_id,
+                                    transform,
+                                    TextRenderMode::Fill,
+                                    hinting_options,
+                                    paint_id);
+        }
+        Some(layers) => layers,
+    };
+
+    for layer in layers {
+        let layer_paint_id = match layer.color {
+            None => paint_id,
+            Some(color) => scene.push_paint(&Paint::Color(color)),
+        };
+        scene.push_glyph(font,
+                         layer.glyph_id,
+                         transform,
+                         TextRenderMode::Fill,
+                         hinting_options,
+                         layer_paint_id)?;
+    }
+    Ok(())
+}
+
+fn push_glyph_outline(scene: &mut Scene,
+                      mut outline: Outline,
+                      render_mode: TextRenderMode,
+                      paint_id: PaintId) {
+    if let TextRenderMode::Stroke(stroke_style) = render_mode {
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+        stroke_to_fill.offset();
+        outline = stroke_to_fill.into_outline();
+    }
+
+    scene.push_path(PathObject::new(outline, paint_id, String::new()));
+}
+
 struct OutlinePathBuilder {
     outline: Outline,
     current_contour: Contour,
@@ -220,3 +729,25 @@ impl FlatPathBuilder for OutlinePathBuilder {
     }
 
     }
+
+impl OutlineBuilder for OutlinePathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        FlatPathBuilder::move_to(self, Point2D::new(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        FlatPathBuilder::line_to(self, Point2D::new(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.quadratic_bezier_to(Point2D::new(x1, y1), Point2D::new(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.cubic_bezier_to(Point2D::new(x1, y1), Point2D::new(x2, y2), Point2D::new(x, y));
+    }
+
+    fn close(&mut self) {
+        FlatPathBuilder::close(self);
+    }
+}
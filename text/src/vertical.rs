@@ -0,0 +1,351 @@
+// pathfinder/text/src/vertical.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Vertical text layout, for CJK text set in columns from top to bottom.
+//!
+//! Glyphs are set upright and centered on the vertical central baseline. In a vertical layout,
+//! that baseline is the line X = 0, and Y increases downward from the top of the first glyph's em
+//! box. Vertical advances and origins come from the `vmtx` and `VORG` tables when the font has
+//! them. Otherwise each glyph is one ascent plus descent tall, with its origin at the ascent.
+
+use crate::TextShaper;
+use crate::color::read_u16;
+use font_kit::loader::Loader;
+use font_kit::loaders::default::Font;
+use skribo::{FontCollection, Layout, TextStyle};
+
+const VHEA_TAG: u32 = 0x76686561;
+const VMTX_TAG: u32 = 0x766d7478;
+const VORG_TAG: u32 = 0x564f5247;
+
+const LONG_VER_METRIC_SIZE: usize = 4;
+const VERT_ORIGIN_Y_METRICS_SIZE: usize = 4;
+
+/// Replaces glyphs with their vertical alternates, as the OpenType `vert` and `vrt2` features do.
+///
+/// Implement this with a shaping engine that can apply those features. Without it, punctuation
+/// such as `、` and `「` keeps its horizontal form.
+pub trait VerticalSubstitution {
+    /// Returns the vertical alternate of the given glyph, or the glyph itself if it has none.
+    fn substitute(&self, font: &Font, glyph_id: u32) -> u32;
+}
+
+/// Leaves every glyph as it is.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoVerticalSubstitution;
+
+impl VerticalSubstitution for NoVerticalSubstitution {
+    #[inline]
+    fn substitute(&self, _: &Font, glyph_id: u32) -> u32 {
+        glyph_id
+    }
+}
+
+/// The vertical metrics of a font, in font units.
+#[derive(Clone, Debug)]
+pub struct VerticalMetricsTables {
+    // The `vmtx` table and the number of long metrics at its start.
+    vmtx: Option<(Box<[u8]>, usize)>,
+    vorg: Option<Box<[u8]>>,
+    ascent: f32,
+    descent: f32,
+}
+
+impl VerticalMetricsTables {
+    pub fn from_font<F>(font: &F) -> VerticalMetricsTables where F: Loader {
+        let vmtx = match (font.load_font_table(VHEA_TAG), font.load_font_table(VMTX_TAG)) {
+            (Some(vhea), Some(vmtx)) => {
+                match read_u16(&vhea, 34) {
+                    Some(long_metric_count) if long_metric_count > 0 => {
+                        Some((vmtx, long_metric_count as usize))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let vorg = font.load_font_table(VORG_TAG).filter(|vorg| read_u16(vorg, 0) == Some(1));
+        let metrics = font.metrics();
+        VerticalMetricsTables { vmtx, vorg, ascent: metrics.ascent, descent: metrics.descent }
+    }
+
+    /// The distance the pen moves down after the given glyph.
+    pub fn advance(&self, glyph_id: u32) -> f32 {
+        let default_advance = self.ascent - self.descent;
+        let (vmtx, long_metric_count) = match self.vmtx {
+            None => return default_advance,
+            Some((ref vmtx, long_metric_count)) => (vmtx, long_metric_count),
+        };
+        // Glyphs past the long metrics share the advance of the last one.
+        let metric_index = (glyph_id as usize).min(long_metric_count - 1);
+        match read_u16(vmtx, metric_index * LONG_VER_METRIC_SIZE) {
+            None => default_advance,
+            Some(advance) => advance as f32,
+        }
+    }
+
+    /// The height of the vertical origin of the given glyph above its horizontal baseline, with Y
+    /// up. The vertical origin is where the pen sits, on the horizontal center of the glyph.
+    pub fn origin_y<F>(&self, font: &F, glyph_id: u32) -> f32 where F: Loader {
+        if let Some(ref vorg) = self.vorg {
+            if let Some(origin_y) = vorg_origin_y(vorg, glyph_id) {
+                return origin_y;
+            }
+        }
+
+        // Without `VORG`, the origin is the top side bearing above the top of the glyph.
+        if let Some(top_side_bearing) = self.top_side_bearing(glyph_id) {
+            if let Ok(bounds) = font.typographic_bounds(glyph_id) {
+                if bounds.size.height > 0.0 {
+                    return bounds.max_y() + top_side_bearing;
+                }
+            }
+        }
+        self.ascent
+    }
+
+    fn top_side_bearing(&self, glyph_id: u32) -> Option<f32> {
+        let (ref vmtx, long_metric_count) = *self.vmtx.as_ref()?;
+        let glyph_index = glyph_id as usize;
+        let offset = if glyph_index < long_metric_count {
+            glyph_index * LONG_VER_METRIC_SIZE + 2
+        } else {
+            long_metric_count * LONG_VER_METRIC_SIZE + (glyph_index - long_metric_count) * 2
+        };
+        Some(read_u16(vmtx, offset)? as i16 as f32)
+    }
+}
+
+fn vorg_origin_y(vorg: &[u8], glyph_id: u32) -> Option<f32> {
+    let default_origin_y = read_u16(vorg, 4)? as i16 as f32;
+    let metric_count = read_u16(vorg, 6)? as usize;
+
+    // Metrics are sorted by glyph ID.
+    let (mut low, mut high) = (0, metric_count);
+    while low < high {
+        let mid = (low + high) / 2;
+        let record_offset = 8 + mid * VERT_ORIGIN_Y_METRICS_SIZE;
+        let record_glyph_id = read_u16(vorg, record_offset)? as u32;
+        if record_glyph_id == glyph_id {
+            return Some(read_u16(vorg, record_offset + 2)? as i16 as f32);
+        } else if record_glyph_id < glyph_id {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Some(default_origin_y)
+}
+
+/// Sets the glyphs of a horizontal layout in a column instead, in the same order, replacing them
+/// with the vertical alternates that `substitution` returns.
+///
+/// Horizontal positions from shaping are discarded, so kerning and mark positioning don't carry
+/// over.
+pub fn layout_vertical<S>(mut layout: Layout, substitution: &S) -> Layout
+                          where S: VerticalSubstitution + ?Sized {
+    // The tables of each font in the layout, looked up the first time it's seen.
+    let mut font_tables: Vec<(*const (), VerticalMetricsTables)> = vec![];
+
+    let mut pen_y = 0.0;
+    for glyph in &mut layout.glyphs {
+        let font = &*glyph.font.font;
+        let font_ptr = font as *const _ as *const ();
+        let tables_index = match font_tables.iter().position(|&(ptr, _)| ptr == font_ptr) {
+            Some(index) => index,
+            None => {
+                font_tables.push((font_ptr, VerticalMetricsTables::from_font(font)));
+                font_tables.len() - 1
+            }
+        };
+        let tables = &font_tables[tables_index].1;
+
+        glyph.glyph_id = substitution.substitute(font, glyph.glyph_id);
+        let scale = layout.size / font.metrics().units_per_em as f32;
+        let advance_x = font.advance(glyph.glyph_id).map(|advance| advance.x).unwrap_or(0.0);
+
+        // Put the vertical origin of the glyph on the pen. Layout offsets have Y down.
+        glyph.offset.x = -0.5 * advance_x * scale;
+        glyph.offset.y = pen_y + tables.origin_y(font, glyph.glyph_id) * scale;
+        pen_y += tables.advance(glyph.glyph_id) * scale;
+    }
+    layout
+}
+
+/// Lays text out with another shaper, then sets it vertically with `layout_vertical()`.
+#[derive(Clone, Debug, Default)]
+pub struct VerticalShaper<S, V = NoVerticalSubstitution> {
+    shaper: S,
+    substitution: V,
+}
+
+impl<S> VerticalShaper<S> where S: TextShaper {
+    #[inline]
+    pub fn new(shaper: S) -> VerticalShaper<S> {
+        VerticalShaper { shaper, substitution: NoVerticalSubstitution }
+    }
+}
+
+impl<S, V> VerticalShaper<S, V> where S: TextShaper, V: VerticalSubstitution {
+    #[inline]
+    pub fn with_substitution(shaper: S, substitution: V) -> VerticalShaper<S, V> {
+        VerticalShaper { shaper, substitution }
+    }
+}
+
+impl<S, V> TextShaper for VerticalShaper<S, V> where S: TextShaper, V: VerticalSubstitution {
+    fn shape(&self, text: &str, style: &TextStyle, collection: &FontCollection) -> Layout {
+        layout_vertical(self.shaper.shape(text, style, collection), &self.substitution)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TextShaper;
+    use crate::fallback::FontFallbackChain;
+    use crate::vertical::{NoVerticalSubstitution, VerticalMetricsTables, VerticalShaper};
+    use crate::vertical::{VerticalSubstitution, layout_vertical, vorg_origin_y};
+    use font_kit::loaders::default::Font;
+    use skribo::{FontCollection, Layout, TextStyle};
+    use std::sync::Arc;
+
+    const EPSILON: f32 = 0.001;
+
+    // Overpass has no vertical metrics, so its glyphs are one ascent plus descent tall.
+    fn overpass() -> Font {
+        let data = include_bytes!("../../resources/fonts/overpass-regular.otf");
+        Font::from_bytes(Arc::new(data.to_vec()), 0).unwrap()
+    }
+
+    fn horizontal_layout(text: &str) -> Layout {
+        let mut chain = FontFallbackChain::new();
+        chain.push(overpass());
+        chain.layout(text, &TextStyle { size: 10.0 })
+    }
+
+    fn big_endian(values: &[i16]) -> Box<[u8]> {
+        values.iter().flat_map(|value| (*value as u16).to_be_bytes().to_vec()).collect()
+    }
+
+    // Swaps two glyphs, standing in for a `vert` lookup.
+    struct SwapGlyphs(u32, u32);
+
+    impl VerticalSubstitution for SwapGlyphs {
+        fn substitute(&self, _: &Font, glyph_id: u32) -> u32 {
+            match glyph_id {
+                glyph_id if glyph_id == self.0 => self.1,
+                glyph_id if glyph_id == self.1 => self.0,
+                glyph_id => glyph_id,
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_metrics() {
+        let font = overpass();
+        let metrics = font.metrics();
+        let tables = VerticalMetricsTables::from_font(&font);
+        let glyph_id = font.glyph_for_char('a').unwrap();
+        assert_eq!(tables.advance(glyph_id), metrics.ascent - metrics.descent);
+        assert_eq!(tables.origin_y(&font, glyph_id), metrics.ascent);
+    }
+
+    #[test]
+    fn test_vmtx_metrics() {
+        // Two long metrics of advance and top side bearing, then one more top side bearing.
+        let vmtx = big_endian(&[900, 50, 1100, -20, 70]);
+        let tables = VerticalMetricsTables {
+            vmtx: Some((vmtx, 2)),
+            vorg: None,
+            ascent: 800.0,
+            descent: -200.0,
+        };
+        assert_eq!(tables.advance(0), 900.0);
+        assert_eq!(tables.advance(1), 1100.0);
+        // Later glyphs share the advance of the last long metric.
+        assert_eq!(tables.advance(2), 1100.0);
+        assert_eq!(tables.advance(100), 1100.0);
+        assert_eq!(tables.top_side_bearing(0), Some(50.0));
+        assert_eq!(tables.top_side_bearing(1), Some(-20.0));
+        assert_eq!(tables.top_side_bearing(2), Some(70.0));
+        assert_eq!(tables.top_side_bearing(3), None);
+
+        // The origin is the top side bearing above the top of the glyph.
+        let font = overpass();
+        let glyph_id = font.glyph_for_char('a').unwrap();
+        let long_metrics: Vec<i16> = (0..=glyph_id).flat_map(|_| vec![1000, 30]).collect();
+        let tables = VerticalMetricsTables {
+            vmtx: Some((big_endian(&long_metrics), glyph_id as usize + 1)),
+            ..tables
+        };
+        let top = font.typographic_bounds(glyph_id).unwrap().max_y();
+        assert!((tables.origin_y(&font, glyph_id) - (top + 30.0)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_vorg_origins() {
+        // Version 1.0, a default of 880, and records for glyphs 3, 7, and 12.
+        let vorg = big_endian(&[1, 0, 880, 3, 3, 900, 7, -40, 12, 860]);
+        assert_eq!(vorg_origin_y(&vorg, 3), Some(900.0));
+        assert_eq!(vorg_origin_y(&vorg, 7), Some(-40.0));
+        assert_eq!(vorg_origin_y(&vorg, 12), Some(860.0));
+        for &glyph_id in &[0, 5, 8, 13] {
+            assert_eq!(vorg_origin_y(&vorg, glyph_id), Some(880.0));
+        }
+        assert_eq!(vorg_origin_y(&vorg[0..6], 3), None);
+
+        let tables = VerticalMetricsTables {
+            vmtx: None,
+            vorg: Some(vorg),
+            ascent: 800.0,
+            descent: -200.0,
+        };
+        assert_eq!(tables.origin_y(&overpass(), 7), -40.0);
+    }
+
+    #[test]
+    fn test_layout_vertical() {
+        let font = overpass();
+        let metrics = font.metrics();
+        let scale = 10.0 / metrics.units_per_em as f32;
+        let (glyph_a, glyph_b) = (font.glyph_for_char('a').unwrap(),
+                                  font.glyph_for_char('b').unwrap());
+        let advance_x = |glyph_id| font.advance(glyph_id).unwrap().x * scale;
+
+        let layout = layout_vertical(horizontal_layout("ab"), &NoVerticalSubstitution);
+        assert_eq!(layout.glyphs.len(), 2);
+        let height = (metrics.ascent - metrics.descent) * scale;
+        for (index, &glyph_id) in [glyph_a, glyph_b].iter().enumerate() {
+            let glyph = &layout.glyphs[index];
+            assert_eq!(glyph.glyph_id, glyph_id);
+            // Centered on the baseline, and stacked downward one glyph height at a time.
+            assert!((glyph.offset.x + 0.5 * advance_x(glyph_id)).abs() < EPSILON);
+            let expected_y = index as f32 * height + metrics.ascent * scale;
+            assert!((glyph.offset.y - expected_y).abs() < EPSILON);
+        }
+
+        // Substituted glyphs are centered by their own advances.
+        let layout = layout_vertical(horizontal_layout("ab"), &SwapGlyphs(glyph_a, glyph_b));
+        assert_eq!(layout.glyphs[0].glyph_id, glyph_b);
+        assert_eq!(layout.glyphs[1].glyph_id, glyph_a);
+        assert!((layout.glyphs[0].offset.x + 0.5 * advance_x(glyph_b)).abs() < EPSILON);
+
+        // The shaper lays the text out and then sets it vertically.
+        let mut chain = FontFallbackChain::new();
+        chain.push(overpass());
+        let shaper = VerticalShaper::with_substitution(&chain, SwapGlyphs(glyph_a, glyph_b));
+        let shaped = shaper.shape("ab", &TextStyle { size: 10.0 }, &FontCollection::new());
+        assert_eq!(shaped.glyphs.len(), 2);
+        for (shaped_glyph, glyph) in shaped.glyphs.iter().zip(layout.glyphs.iter()) {
+            assert_eq!(shaped_glyph.glyph_id, glyph.glyph_id);
+            assert_eq!(shaped_glyph.offset, glyph.offset);
+        }
+    }
+}
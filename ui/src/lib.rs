@@ -21,7 +21,7 @@ use pathfinder_color::ColorU;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendFunc, BlendState, BufferData, BufferTarget, BufferUploadMode, Device};
+use pathfinder_gpu::{BlendState, BufferData, BufferTarget, BufferUploadMode, Device};
 use pathfinder_gpu::{Primitive, RenderOptions, RenderState, RenderTarget, UniformData};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_simd::default::F32x4;
@@ -187,10 +187,7 @@ impl<D> UIPresenter<D> where D: Device {
             textures: &[],
             viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
             options: RenderOptions {
-                blend: Some(BlendState {
-                    func: BlendFunc::RGBOneAlphaOneMinusSrcAlpha,
-                    ..BlendState::default()
-                }),
+                blend: Some(BlendState::default()),
                 ..RenderOptions::default()
             },
         });
@@ -425,10 +422,7 @@ impl<D> UIPresenter<D> where D: Device {
             ],
             viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
             options: RenderOptions {
-                blend: Some(BlendState {
-                    func: BlendFunc::RGBOneAlphaOneMinusSrcAlpha,
-                    ..BlendState::default()
-                }),
+                blend: Some(BlendState::default()),
                 ..RenderOptions::default()
             },
         });
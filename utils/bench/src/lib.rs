@@ -0,0 +1,320 @@
+// pathfinder/utils/bench/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Measures the performance of Pathfinder on procedurally generated scenes.
+//!
+//! Scenes are generated from a handful of parameters and a seed, so the same parameters always
+//! produce the same scene, and timings taken before and after a change are comparable. Each frame
+//! goes through three stages, which are timed separately: building the scene, tiling it into
+//! render commands, and submitting those commands to the GPU.
+
+use pathfinder_color::ColorU;
+use pathfinder_content::gradient::{ColorStop, Gradient};
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_gpu::Device;
+use pathfinder_renderer::concurrent::executor::Executor;
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::gpu_data::RenderCommand;
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_renderer::paint::{Paint, PaintId};
+use pathfinder_renderer::scene::{PathObject, Scene};
+use std::f32::consts::PI;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long to wait for the GPU to report how long the last frames took to render.
+const GPU_TIMER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Describes a procedurally generated scene.
+#[derive(Clone, Copy, Debug)]
+pub struct SceneParams {
+    pub path_count: u32,
+    /// The number of segments in the contour of each path.
+    pub segments_per_path: u32,
+    /// The fraction of segments that are cubic curves rather than lines, from 0 to 1.
+    pub curve_fraction: f32,
+    /// The number of distinct paints that paths choose from.
+    pub paint_count: u32,
+    /// The fraction of paints that are gradients rather than solid colors, from 0 to 1.
+    pub gradient_fraction: f32,
+    /// The total area of the paths as a multiple of the area of the view box. At 1, each pixel is
+    /// covered by one path on average.
+    pub overlap: f32,
+    pub view_box_size: Vector2I,
+    pub seed: u64,
+}
+
+impl Default for SceneParams {
+    fn default() -> SceneParams {
+        SceneParams {
+            path_count: 1000,
+            segments_per_path: 16,
+            curve_fraction: 0.5,
+            paint_count: 32,
+            gradient_fraction: 0.25,
+            overlap: 4.0,
+            view_box_size: Vector2I::new(1920, 1080),
+            seed: 1,
+        }
+    }
+}
+
+/// Generates the scene that `params` describes.
+///
+/// Each path is a closed, roughly star-shaped contour around a random center, sized so that all
+/// of the paths together cover `overlap` times the area of the view box.
+pub fn generate_scene(params: &SceneParams) -> Scene {
+    let mut random = Random::new(params.seed);
+    let mut scene = Scene::new();
+    let view_box = RectF::new(Vector2F::default(), params.view_box_size.to_f32());
+    scene.set_view_box(view_box);
+
+    let paint_ids: Vec<PaintId> = (0..params.paint_count.max(1)).map(|_| {
+        let paint = generate_paint(&mut random, view_box, params.gradient_fraction);
+        scene.push_paint(&paint)
+    }).collect();
+
+    let view_box_area = view_box.width() * view_box.height();
+    let path_area = view_box_area * params.overlap / params.path_count.max(1) as f32;
+    let radius = f32::sqrt(path_area / PI);
+    for _ in 0..params.path_count {
+        let center = view_box.origin() + view_box.size() * random.next_vector();
+        let outline = generate_outline(&mut random, center, radius, params);
+        let paint_id = paint_ids[random.next_index(paint_ids.len())];
+        scene.push_path(PathObject::new(outline, paint_id, String::new()));
+    }
+    scene
+}
+
+fn generate_outline(random: &mut Random, center: Vector2F, radius: f32, params: &SceneParams)
+                    -> Outline {
+    let segment_count = params.segments_per_path.max(3);
+    let points: Vec<Vector2F> = (0..segment_count).map(|point_index| {
+        let angle = point_index as f32 / segment_count as f32 * PI * 2.0;
+        let distance = radius * (0.5 + 0.5 * random.next_f32());
+        center + Vector2F::new(f32::cos(angle), f32::sin(angle)).scale(distance)
+    }).collect();
+
+    let mut contour = Contour::with_capacity(segment_count as usize * 3);
+    contour.push_endpoint(points[0]);
+    for point_index in 0..points.len() {
+        let (from, to) = (points[point_index], points[(point_index + 1) % points.len()]);
+        if random.next_f32() < params.curve_fraction {
+            // Control points wander off the chord on either side, so that curves are as likely to
+            // bulge outward as to cut inward.
+            let normal = (to - from).yx().scale_xy(Vector2F::new(-1.0, 1.0)).normalize();
+            let ctrl0 = from.lerp(to, 1.0 / 3.0) + normal.scale(radius * random.next_signed_f32());
+            let ctrl1 = from.lerp(to, 2.0 / 3.0) + normal.scale(radius * random.next_signed_f32());
+            contour.push_cubic(ctrl0, ctrl1, to);
+        } else if point_index + 1 < points.len() {
+            contour.push_endpoint(to);
+        }
+    }
+    contour.close();
+
+    let mut outline = Outline::new();
+    outline.push_contour(contour);
+    outline
+}
+
+fn generate_paint(random: &mut Random, view_box: RectF, gradient_fraction: f32) -> Paint {
+    if random.next_f32() >= gradient_fraction {
+        return Paint::Color(random.next_color());
+    }
+
+    let from = view_box.origin() + view_box.size() * random.next_vector();
+    let to = view_box.origin() + view_box.size() * random.next_vector();
+    let line = LineSegment2F::new(from, to);
+    let mut gradient = if random.next_f32() < 0.5 {
+        Gradient::new(line)
+    } else {
+        Gradient::radial(line, 0.0, (to - from).length())
+    };
+    let stop_count = 2 + random.next_index(3);
+    for stop_index in 0..stop_count {
+        let offset = stop_index as f32 / (stop_count - 1) as f32;
+        gradient.add_color_stop(ColorStop { color: random.next_color(), offset });
+    }
+    Paint::Gradient(gradient)
+}
+
+/// Builds `scene` into render commands, returning them along with the time that took.
+///
+/// Panics if the scene can't be built.
+pub fn tile_scene<E>(scene: &Scene, options: BuildOptions, executor: &E)
+                     -> (Vec<RenderCommand>, Duration)
+                     where E: Executor {
+    let commands = Mutex::new(vec![]);
+    let start_time = Instant::now();
+    scene.build(options, |command| commands.lock().unwrap().push(command), executor).unwrap();
+    let elapsed_time = start_time.elapsed();
+    (commands.into_inner().unwrap(), elapsed_time)
+}
+
+/// Sends `commands` to `renderer` as one frame, returning the CPU time that took.
+///
+/// This only covers recording and submitting GPU commands; the time the GPU takes to execute them
+/// is reported later by `Renderer::shift_rendering_time()`.
+pub fn submit_commands<D>(renderer: &mut Renderer<D>, commands: &[RenderCommand]) -> Duration
+                          where D: Device {
+    let start_time = Instant::now();
+    renderer.begin_scene();
+    for command in commands {
+        renderer.render_command(command);
+    }
+    renderer.end_scene();
+    start_time.elapsed()
+}
+
+/// The time one frame spent in each stage.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FrameTimes {
+    pub scene_building: Duration,
+    pub tiling: Duration,
+    /// The CPU time spent submitting commands to the GPU, if there was a renderer.
+    pub gpu_submission: Option<Duration>,
+    /// The time the GPU spent rendering, if there was a renderer that supports timer queries.
+    pub gpu_rendering: Option<Duration>,
+}
+
+/// Generates, tiles, and renders the scene that `params` describes `frame_count` times.
+///
+/// If `renderer` is `None`, only scene building and tiling are timed.
+pub fn run<D, E>(params: &SceneParams,
+                 options: &BuildOptions,
+                 executor: &E,
+                 mut renderer: Option<&mut Renderer<D>>,
+                 frame_count: u32)
+                 -> Vec<FrameTimes>
+                 where D: Device, E: Executor {
+    let mut frames = vec![];
+    let mut gpu_rendering_times = vec![];
+    for _ in 0..frame_count {
+        let start_time = Instant::now();
+        let scene = generate_scene(params);
+        let scene_building = start_time.elapsed();
+
+        let (commands, tiling) = tile_scene(&scene, options.clone(), executor);
+
+        let mut gpu_submission = None;
+        if let Some(ref mut renderer) = renderer {
+            gpu_submission = Some(submit_commands(renderer, &commands));
+            while let Some(rendering_time) = renderer.shift_rendering_time() {
+                gpu_rendering_times.push(rendering_time.stage_0 + rendering_time.stage_1);
+            }
+        }
+
+        frames.push(FrameTimes {
+            scene_building,
+            tiling,
+            gpu_submission,
+            gpu_rendering: None,
+        });
+    }
+
+    // Timer queries finish a few frames behind, so wait for the last ones.
+    if let Some(renderer) = renderer {
+        let start_time = Instant::now();
+        while gpu_rendering_times.len() < frames.len() && start_time.elapsed() < GPU_TIMER_TIMEOUT {
+            match renderer.shift_rendering_time() {
+                Some(rendering_time) => {
+                    gpu_rendering_times.push(rendering_time.stage_0 + rendering_time.stage_1)
+                }
+                None => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+    }
+    for (frame, gpu_rendering_time) in frames.iter_mut().zip(gpu_rendering_times) {
+        frame.gpu_rendering = Some(gpu_rendering_time);
+    }
+    frames
+}
+
+/// Statistics of the times one stage took over several frames.
+#[derive(Clone, Copy, Debug)]
+pub struct Summary {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    /// Summarizes `times`. Returns `None` if there are none.
+    pub fn new<I>(times: I) -> Option<Summary> where I: IntoIterator<Item = Duration> {
+        let mut times: Vec<Duration> = times.into_iter().collect();
+        if times.is_empty() {
+            return None;
+        }
+        times.sort();
+        let total: Duration = times.iter().sum();
+        Some(Summary {
+            min: times[0],
+            median: times[times.len() / 2],
+            mean: total / times.len() as u32,
+            max: times[times.len() - 1],
+        })
+    }
+}
+
+// A xorshift generator, so that scenes don't depend on the algorithm of some other crate's random
+// number generator staying the same.
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        // The state must never be zero.
+        Random { state: (seed ^ 0x2545_f491_4f6c_dd1d) | 1 }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 32) as u32
+    }
+
+    // Returns a number in [0, 1).
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    // Returns a number in [-1, 1).
+    fn next_signed_f32(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+
+    fn next_vector(&mut self) -> Vector2F {
+        let x = self.next_f32();
+        Vector2F::new(x, self.next_f32())
+    }
+
+    fn next_index(&mut self, length: usize) -> usize {
+        (self.next_u32() as usize) % length
+    }
+
+    // Returns a color that's at least half opaque, so that blending is exercised without paths
+    // disappearing.
+    fn next_color(&mut self) -> ColorU {
+        let rgba = self.next_u32();
+        ColorU {
+            r: rgba as u8,
+            g: (rgba >> 8) as u8,
+            b: (rgba >> 16) as u8,
+            a: ((rgba >> 24) as u8) | 0x80,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// pathfinder/utils/bench/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Times each stage of rendering a procedurally generated scene and prints statistics.
+
+use clap::{App, Arg, ArgMatches};
+use glutin::dpi::PhysicalSize;
+use glutin::event_loop::EventLoop;
+use glutin::{ContextBuilder, GlProfile, GlRequest};
+use pathfinder_bench::{FrameTimes, SceneParams, Summary};
+use pathfinder_color::ColorF;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::resources::FilesystemResourceLoader;
+use pathfinder_gpu::{Device, TextureFormat};
+use pathfinder_renderer::concurrent::executor::{Executor, SequentialExecutor};
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::BuildOptions;
+use std::str::FromStr;
+use std::time::Duration;
+
+fn main() {
+    let matches = App::new("Pathfinder Benchmark")
+        .version("0.1")
+        .author("The Pathfinder Project Developers")
+        .about("Times scene building, tiling, and GPU submission of generated scenes")
+        .arg(numeric_arg("paths", "The number of paths"))
+        .arg(numeric_arg("segments", "The number of segments per path"))
+        .arg(numeric_arg("curves", "The fraction of segments that are curves, from 0 to 1"))
+        .arg(numeric_arg("paints", "The number of distinct paints"))
+        .arg(numeric_arg("gradients", "The fraction of paints that are gradients, from 0 to 1"))
+        .arg(numeric_arg("overlap", "The total area of the paths relative to the view box"))
+        .arg(numeric_arg("width", "The width of the view box"))
+        .arg(numeric_arg("height", "The height of the view box"))
+        .arg(numeric_arg("seed", "The seed of the scene generator"))
+        .arg(numeric_arg("frames", "The number of frames to time"))
+        .arg(Arg::with_name("sequential").long("sequential")
+                                         .help("Builds scenes on a single thread"))
+        .arg(Arg::with_name("no-gpu").long("no-gpu")
+                                     .help("Skips GPU submission and rendering"))
+        .get_matches();
+
+    let defaults = SceneParams::default();
+    let params = SceneParams {
+        path_count: value_of(&matches, "paths", defaults.path_count),
+        segments_per_path: value_of(&matches, "segments", defaults.segments_per_path),
+        curve_fraction: value_of(&matches, "curves", defaults.curve_fraction),
+        paint_count: value_of(&matches, "paints", defaults.paint_count),
+        gradient_fraction: value_of(&matches, "gradients", defaults.gradient_fraction),
+        overlap: value_of(&matches, "overlap", defaults.overlap),
+        view_box_size: Vector2I::new(value_of(&matches, "width", defaults.view_box_size.x()),
+                                     value_of(&matches, "height", defaults.view_box_size.y())),
+        seed: value_of(&matches, "seed", defaults.seed),
+    };
+    let frame_count = value_of(&matches, "frames", 50);
+    let use_gpu = !matches.is_present("no-gpu");
+
+    let frames = if matches.is_present("sequential") {
+        run(&params, &SequentialExecutor, use_gpu, frame_count)
+    } else {
+        run(&params, &RayonExecutor, use_gpu, frame_count)
+    };
+
+    println!("{:?}", params);
+    println!("{:<16}{:>10}{:>10}{:>10}{:>10}", "stage (ms)", "min", "median", "mean", "max");
+    print_summary("scene building", frames.iter().map(|frame| Some(frame.scene_building)));
+    print_summary("tiling", frames.iter().map(|frame| Some(frame.tiling)));
+    print_summary("gpu submission", frames.iter().map(|frame| frame.gpu_submission));
+    print_summary("gpu rendering", frames.iter().map(|frame| frame.gpu_rendering));
+}
+
+fn run<E>(params: &SceneParams, executor: &E, use_gpu: bool, frame_count: u32)
+          -> Vec<FrameTimes>
+          where E: Executor {
+    let options = BuildOptions::default();
+    if !use_gpu {
+        return pathfinder_bench::run::<GLDevice, _>(params, &options, executor, None, frame_count);
+    }
+
+    // Render offscreen, so that presenting to a window doesn't affect the timings.
+    let event_loop = EventLoop::new();
+    let size = params.view_box_size;
+    let context = ContextBuilder::new().with_gl(GlRequest::Latest)
+                                       .with_gl_profile(GlProfile::Core)
+                                       .build_headless(&event_loop,
+                                                       PhysicalSize::new(size.x() as u32,
+                                                                         size.y() as u32))
+                                       .unwrap();
+    let context = unsafe { context.make_current().unwrap() };
+    gl::load_with(|name| context.get_proc_address(name) as *const _);
+
+    let device = GLDevice::new(GLVersion::GL3, 0);
+    let texture = device.create_texture(TextureFormat::RGBA8, size);
+    let framebuffer = device.create_framebuffer(texture);
+    let mut renderer = Renderer::new(device,
+                                     &FilesystemResourceLoader::locate(),
+                                     DestFramebuffer::Other(framebuffer),
+                                     RendererOptions {
+                                         background_color: Some(ColorF::white()),
+                                         ..RendererOptions::default()
+                                     });
+    pathfinder_bench::run(params, &options, executor, Some(&mut renderer), frame_count)
+}
+
+fn numeric_arg<'a, 'b>(name: &'a str, help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name(name).long(name).takes_value(true).help(help)
+}
+
+fn value_of<T>(matches: &ArgMatches, name: &str, default: T) -> T where T: FromStr {
+    match matches.value_of(name) {
+        None => default,
+        Some(value) => match value.parse() {
+            Ok(value) => value,
+            Err(_) => panic!("`--{}` must be a number", name),
+        },
+    }
+}
+
+fn print_summary<I>(stage: &str, times: I) where I: Iterator<Item = Option<Duration>> {
+    match Summary::new(times.flatten()) {
+        None => println!("{:<16}{:>10}", stage, "-"),
+        Some(summary) => {
+            println!("{:<16}{:>10.3}{:>10.3}{:>10.3}{:>10.3}",
+                     stage,
+                     to_ms(summary.min),
+                     to_ms(summary.median),
+                     to_ms(summary.mean),
+                     to_ms(summary.max));
+        }
+    }
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1000000.0
+}
@@ -18,7 +18,7 @@ use web_sys::WebGl2RenderingContext as WebGl;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{RenderTarget, BlendFunc, BlendOp, BufferData, BufferTarget, BufferUploadMode};
+use pathfinder_gpu::{RenderTarget, BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode};
 use pathfinder_gpu::{ClearOps, DepthFunc, Device, Primitive, RenderOptions, RenderState};
 use pathfinder_gpu::{ShaderKind, StencilFunc, TextureData, TextureDataRef, TextureFormat, UniformData};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
@@ -182,8 +182,10 @@ impl WebGlDevice {
         let (origin, size) = (render_state.viewport.origin(), render_state.viewport.size());
         self.context.viewport(origin.x(), origin.y(), size.x(), size.y());
 
+        // The scissor must be in place before clearing, since it restricts clears too.
+        self.set_scissor(render_state.options.scissor);
         if render_state.options.clear_ops.has_ops() {
-            self.clear(&render_state.options.clear_ops);
+            self.apply_clear_ops(&render_state.options.clear_ops);
         }
 
         self.context.use_program(Some(&render_state.program.gl_program));
@@ -211,29 +213,12 @@ impl WebGlDevice {
                 }
                 self.ck();
 
-                match blend.func {
-                    BlendFunc::RGBOneAlphaOne => {
-                        self.context.blend_func(WebGl::ONE, WebGl::ONE);
-                    }
-                    BlendFunc::RGBOneAlphaOneMinusSrcAlpha => {
-                        self.context.blend_equation(WebGl::FUNC_ADD);
-                        self.context.blend_func_separate(
-                            WebGl::ONE,
-                            WebGl::ONE_MINUS_SRC_ALPHA,
-                            WebGl::ONE,
-                            WebGl::ONE,
-                        );
-                    }
-                    BlendFunc::RGBSrcAlphaAlphaOneMinusSrcAlpha => {
-                        self.context.blend_equation(WebGl::FUNC_ADD);
-                        self.context.blend_func_separate(
-                            WebGl::SRC_ALPHA,
-                            WebGl::ONE_MINUS_SRC_ALPHA,
-                            WebGl::ONE,
-                            WebGl::ONE,
-                        );
-                    }
-                }
+                self.context.blend_func_separate(
+                    blend.src_rgb_factor.to_gl_blend_factor(),
+                    blend.dest_rgb_factor.to_gl_blend_factor(),
+                    blend.src_alpha_factor.to_gl_blend_factor(),
+                    blend.dest_alpha_factor.to_gl_blend_factor(),
+                );
                 self.context.enable(WebGl::BLEND);
                 self.ck();
             }
@@ -311,13 +296,29 @@ impl WebGlDevice {
             self.context.disable(WebGl::STENCIL_TEST);
         }
 
+        if render_options.scissor.is_some() {
+            self.set_scissor(None);
+        }
+
         self.context.color_mask(true, true, true, true);
         self.ck();
     }
 
+    fn set_scissor(&self, scissor: Option<RectI>) {
+        match scissor {
+            None => self.context.disable(WebGl::SCISSOR_TEST),
+            Some(scissor) => {
+                let (origin, size) = (scissor.origin(), scissor.size());
+                self.context.scissor(origin.x(), origin.y(), size.x(), size.y());
+                self.context.enable(WebGl::SCISSOR_TEST);
+            }
+        }
+        self.ck();
+    }
+
     
     #[inline]
-    fn clear(&self, ops: &ClearOps) {
+    fn apply_clear_ops(&self, ops: &ClearOps) {
         let mut flags = 0;
         if let Some(color) = ops.color {
             self.context.color_mask(true, true, true, true);
@@ -593,10 +594,64 @@ impl Device for WebGlDevice {
         WebGlFramebuffer {
             context: self.context.clone(),
             framebuffer: gl_framebuffer,
+            renderbuffer: None,
             texture
         }
     }
 
+    fn create_multisampled_framebuffer(&self,
+                                       format: TextureFormat,
+                                       size: Vector2I,
+                                       sample_count: u32)
+                                       -> WebGlFramebuffer {
+        let renderbuffer = self.context.create_renderbuffer().unwrap();
+        self.context.bind_renderbuffer(WebGl::RENDERBUFFER, Some(&renderbuffer));
+        self.context.renderbuffer_storage_multisample(
+            WebGl::RENDERBUFFER,
+            sample_count as i32,
+            format.gl_internal_format(),
+            size.x(),
+            size.y(),
+        );
+
+        let gl_framebuffer = self.context.create_framebuffer().unwrap();
+        self.context.bind_framebuffer(WebGl::FRAMEBUFFER, Some(&gl_framebuffer));
+        self.context.framebuffer_renderbuffer(
+            WebGl::FRAMEBUFFER,
+            WebGl::COLOR_ATTACHMENT0,
+            WebGl::RENDERBUFFER,
+            Some(&renderbuffer),
+        );
+        self.ck();
+        assert_eq!(self.context.check_framebuffer_status(WebGl::FRAMEBUFFER),
+                   WebGl::FRAMEBUFFER_COMPLETE);
+
+        // The samples live in the renderbuffer, so the texture only records the size and format.
+        let texture = WebGlTexture {
+            texture: self.context.create_texture().unwrap(),
+            format,
+            size,
+            context: self.context.clone(),
+        };
+        WebGlFramebuffer {
+            context: self.context.clone(),
+            framebuffer: gl_framebuffer,
+            renderbuffer: Some(renderbuffer),
+            texture,
+        }
+    }
+
+    fn resolve_framebuffer(&self, source: &WebGlFramebuffer, dest: &WebGlFramebuffer) {
+        let (width, height) = (source.texture.size.x(), source.texture.size.y());
+        self.context.bind_framebuffer(WebGl::READ_FRAMEBUFFER, Some(&source.framebuffer));
+        self.context.bind_framebuffer(WebGl::DRAW_FRAMEBUFFER, Some(&dest.framebuffer));
+        self.context.blit_framebuffer(0, 0, width, height,
+                                      0, 0, width, height,
+                                      WebGl::COLOR_BUFFER_BIT,
+                                      WebGl::NEAREST);
+        self.ck();
+    }
+
     fn create_buffer(&self) -> WebGlBuffer {
         let buffer = self.context.create_buffer().unwrap();
         WebGlBuffer { buffer, context: self.context.clone() }
@@ -628,6 +683,10 @@ impl Device for WebGlDevice {
     }
 
     #[inline]
+    fn texture_format(&self, texture: &Self::Texture) -> TextureFormat {
+        texture.format
+    }
+
     fn texture_size(&self, texture: &Self::Texture) -> Vector2I {
         texture.size
     }
@@ -673,6 +732,27 @@ impl Device for WebGlDevice {
         panic!("read_pixels is not supported");
     }
 
+    fn copy_render_target(&self, target: &RenderTarget<WebGlDevice>, rect: RectI)
+                          -> WebGlTexture {
+        let format = match *target {
+            RenderTarget::Default => TextureFormat::RGBA8,
+            RenderTarget::Framebuffer(framebuffer) => framebuffer.texture.format,
+        };
+        let texture = self.create_texture(format, rect.size());
+        self.bind_render_target(target);
+        self.bind_texture(&texture, 0);
+        self.context.copy_tex_sub_image_2d(WebGl::TEXTURE_2D,
+                                           0,
+                                           0,
+                                           0,
+                                           rect.origin().x(),
+                                           rect.origin().y(),
+                                           rect.size().x(),
+                                           rect.size().y());
+        self.ck();
+        texture
+    }
+
     fn begin_commands(&self) {
         // TODO(pcwalton): Add some checks in debug mode to make sure render commands are bracketed
         // by these?
@@ -682,6 +762,17 @@ impl Device for WebGlDevice {
         self.context.flush();
     }
 
+    fn clear(&self, target: &RenderTarget<Self>, scissor: Option<RectI>, ops: &ClearOps) {
+        self.bind_render_target(target);
+        if ops.has_ops() {
+            self.set_scissor(scissor);
+            self.apply_clear_ops(ops);
+            if scissor.is_some() {
+                self.set_scissor(None);
+            }
+        }
+    }
+
     fn draw_arrays(&self, index_count: u32, render_state: &RenderState<Self>) {
         self.set_render_state(render_state);
         self.context.draw_arrays(
@@ -792,12 +883,15 @@ pub struct WebGlVertexAttr {
 pub struct WebGlFramebuffer {
     context: web_sys::WebGl2RenderingContext,
     pub framebuffer: web_sys::WebGlFramebuffer,
+    /// The multisampled color buffer, if the framebuffer doesn't draw into `texture`.
+    pub renderbuffer: Option<web_sys::WebGlRenderbuffer>,
     pub texture: WebGlTexture,
 }
 
 impl Drop for WebGlFramebuffer {
     fn drop(&mut self) {
         self.context.delete_framebuffer(Some(&self.framebuffer));
+        self.context.delete_renderbuffer(self.renderbuffer.as_ref());
     }
 }
 
@@ -848,6 +942,25 @@ pub struct WebGlTimerQuery {
 }
 
 
+trait BlendFactorExt {
+    fn to_gl_blend_factor(self) -> u32;
+}
+
+impl BlendFactorExt for BlendFactor {
+    fn to_gl_blend_factor(self) -> u32 {
+        match self {
+            BlendFactor::Zero => WebGl::ZERO,
+            BlendFactor::One => WebGl::ONE,
+            BlendFactor::SrcAlpha => WebGl::SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => WebGl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::DestAlpha => WebGl::DST_ALPHA,
+            BlendFactor::OneMinusDestAlpha => WebGl::ONE_MINUS_DST_ALPHA,
+            BlendFactor::DestColor => WebGl::DST_COLOR,
+            BlendFactor::OneMinusSrcColor => WebGl::ONE_MINUS_SRC_COLOR,
+        }
+    }
+}
+
 trait BufferTargetExt {
     fn to_gl_target(self) -> u32;
 }
@@ -927,13 +1040,17 @@ impl TextureFormatExt for TextureFormat {
             TextureFormat::RGBA8 => WebGl::RGBA,
             TextureFormat::RGBA16F => WebGl::RGBA16F,
             TextureFormat::RGBA32F => WebGl::RGBA32F,
+            TextureFormat::RGB10A2 => WebGl::RGB10_A2,
         }
     }
 
     fn gl_format(self) -> u32 {
         match self {
             TextureFormat::R8 | TextureFormat::R16F => WebGl::RED,
-            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => WebGl::RGBA,
+            TextureFormat::RGBA8 |
+            TextureFormat::RGBA16F |
+            TextureFormat::RGBA32F |
+            TextureFormat::RGB10A2 => WebGl::RGBA,
         }
     }
 
@@ -942,6 +1059,7 @@ impl TextureFormatExt for TextureFormat {
             TextureFormat::R8 | TextureFormat::RGBA8 => WebGl::UNSIGNED_BYTE,
             TextureFormat::R16F | TextureFormat::RGBA16F => WebGl::HALF_FLOAT,
             TextureFormat::RGBA32F => WebGl::FLOAT,
+            TextureFormat::RGB10A2 => WebGl::UNSIGNED_INT_2_10_10_10_REV,
         }
     }
 }